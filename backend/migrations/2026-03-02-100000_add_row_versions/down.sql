@@ -0,0 +1,9 @@
+DROP TRIGGER IF EXISTS bump_sensors_version ON sensors;
+DROP TRIGGER IF EXISTS bump_sequences_version ON sequences;
+DROP TRIGGER IF EXISTS bump_entries_version ON entries;
+
+DROP FUNCTION IF EXISTS bump_row_version();
+
+ALTER TABLE "sensors" DROP COLUMN "version";
+ALTER TABLE "sequences" DROP COLUMN "version";
+ALTER TABLE "entries" DROP COLUMN "version";
@@ -0,0 +1,25 @@
+-- Versionsspalten für optimistische Nebenläufigkeitskontrolle (ETag / If-Match).
+ALTER TABLE "entries" ADD COLUMN "version" BIGINT NOT NULL DEFAULT 1;
+ALTER TABLE "sequences" ADD COLUMN "version" BIGINT NOT NULL DEFAULT 1;
+ALTER TABLE "sensors" ADD COLUMN "version" BIGINT NOT NULL DEFAULT 1;
+
+-- Jede tatsächliche Änderung einer Zeile erhöht die Version, egal von welchem
+-- Schreiber sie kommt (REST-API, Scanner, Plugins).
+CREATE OR REPLACE FUNCTION bump_row_version()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW IS DISTINCT FROM OLD THEN
+        NEW.version = OLD.version + 1;
+    END IF;
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER bump_entries_version BEFORE UPDATE ON entries
+    FOR EACH ROW EXECUTE FUNCTION bump_row_version();
+
+CREATE TRIGGER bump_sequences_version BEFORE UPDATE ON sequences
+    FOR EACH ROW EXECUTE FUNCTION bump_row_version();
+
+CREATE TRIGGER bump_sensors_version BEFORE UPDATE ON sensors
+    FOR EACH ROW EXECUTE FUNCTION bump_row_version();
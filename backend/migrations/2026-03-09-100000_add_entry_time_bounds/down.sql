@@ -0,0 +1,2 @@
+ALTER TABLE "entries" DROP COLUMN "mcap_end_ns";
+ALTER TABLE "entries" DROP COLUMN "mcap_start_ns";
@@ -0,0 +1,4 @@
+-- Zeitspanne der Aufnahme laut MCAP (Log-Zeit in Nanosekunden).
+-- Sequenzen eines Eintrags müssen innerhalb dieser Spanne liegen.
+ALTER TABLE "entries" ADD COLUMN "mcap_start_ns" BIGINT;
+ALTER TABLE "entries" ADD COLUMN "mcap_end_ns" BIGINT;
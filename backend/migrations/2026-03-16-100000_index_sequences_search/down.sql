@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS sequences_entry_id_idx;
+DROP INDEX IF EXISTS sequences_tags_gin;
@@ -0,0 +1,3 @@
+-- Indizes für die katalogweite Sequenzsuche (GET /sequences).
+CREATE INDEX IF NOT EXISTS sequences_tags_gin ON sequences USING GIN (tags);
+CREATE INDEX IF NOT EXISTS sequences_entry_id_idx ON sequences (entry_id);
@@ -0,0 +1,6 @@
+DROP TRIGGER IF EXISTS link_sensors_to_catalog ON sensors;
+DROP FUNCTION IF EXISTS link_sensor_to_catalog();
+DROP FUNCTION IF EXISTS sensor_identity_key(TEXT, TEXT, TEXT, JSONB);
+DROP TABLE IF EXISTS entry_sensors;
+DROP TABLE IF EXISTS sensor_identity_keys;
+DROP TABLE IF EXISTS sensor_catalog;
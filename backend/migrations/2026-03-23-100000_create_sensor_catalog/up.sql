@@ -0,0 +1,99 @@
+-- Normalisierter Sensorkatalog: ein Eintrag pro physischem Sensor (Seriennummer)
+-- bzw. pro Sensormodell, unabhängig davon, in wie vielen Aufnahmen er vorkommt.
+CREATE TABLE sensor_catalog (
+  id BIGINT PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+  sensor_name TEXT NOT NULL,
+  manufacturer TEXT,
+  sensor_type TEXT,
+  model TEXT,
+  serial_number TEXT,
+  calibration JSONB,
+  custom_parameters JSONB,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT now() NOT NULL,
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT now() NOT NULL,
+  version BIGINT NOT NULL DEFAULT 1
+);
+
+CREATE TRIGGER bump_sensor_catalog_version BEFORE UPDATE ON sensor_catalog
+    FOR EACH ROW EXECUTE FUNCTION bump_row_version();
+
+-- Alle Identitätsschlüssel, die auf einen Katalogsensor zeigen. Nach einem Merge
+-- bleiben die Schlüssel der zusammengeführten Sensoren hier erhalten, damit ein
+-- erneuter Scan keine Duplikate anlegt.
+CREATE TABLE sensor_identity_keys (
+  identity_key TEXT PRIMARY KEY,
+  catalog_sensor_id BIGINT NOT NULL REFERENCES sensor_catalog(id) ON DELETE CASCADE
+);
+
+-- Zuordnung Eintrag -> Katalogsensor, je Sensor aus der Metadaten-YAML.
+CREATE TABLE entry_sensors (
+  id BIGINT PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+  entry_id BIGINT NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+  catalog_sensor_id BIGINT NOT NULL REFERENCES sensor_catalog(id) ON DELETE RESTRICT,
+  sensor_id BIGINT NOT NULL UNIQUE REFERENCES sensors(id) ON DELETE CASCADE,
+  ros_topics TEXT[] NOT NULL DEFAULT '{}',
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT now() NOT NULL
+);
+CREATE INDEX entry_sensors_entry_id_idx ON entry_sensors (entry_id);
+CREATE INDEX entry_sensors_catalog_sensor_id_idx ON entry_sensors (catalog_sensor_id);
+
+-- Identität eines Sensors: Hersteller + Seriennummer, sonst Hersteller + Typ +
+-- Modell (ersatzweise der Sensorname). Groß-/Kleinschreibung, Leer- und
+-- Satzzeichen werden ignoriert ("Velodyne Inc." == "velodyne inc").
+CREATE OR REPLACE FUNCTION sensor_identity_key(
+  manufacturer TEXT, sensor_type TEXT, sensor_name TEXT, params JSONB
+) RETURNS TEXT AS $$
+DECLARE
+  serial TEXT := NULLIF(COALESCE(params->>'serial_number', params->>'serial'), '');
+BEGIN
+  IF serial IS NOT NULL THEN
+    RETURN 'serial:' || lower(regexp_replace(COALESCE(manufacturer, ''), '[^[:alnum:]]', '', 'g'))
+      || ':' || lower(regexp_replace(serial, '[^[:alnum:]]', '', 'g'));
+  END IF;
+  RETURN 'model:' || lower(regexp_replace(COALESCE(manufacturer, ''), '[^[:alnum:]]', '', 'g'))
+    || ':' || lower(regexp_replace(COALESCE(sensor_type, ''), '[^[:alnum:]]', '', 'g'))
+    || ':' || lower(regexp_replace(COALESCE(params->>'model', sensor_name, ''), '[^[:alnum:]]', '', 'g'));
+END;
+$$ LANGUAGE plpgsql IMMUTABLE;
+
+-- Hält entry_sensors für jeden Schreiber (Scanner, REST-API) aktuell.
+CREATE OR REPLACE FUNCTION link_sensor_to_catalog()
+RETURNS TRIGGER AS $$
+DECLARE
+  key TEXT := sensor_identity_key(NEW.manufacturer, NEW.sensor_type, NEW.sensor_name, NEW.custom_parameters);
+  cid BIGINT;
+BEGIN
+  SELECT catalog_sensor_id INTO cid FROM sensor_identity_keys WHERE identity_key = key;
+  IF cid IS NULL THEN
+    INSERT INTO sensor_catalog (sensor_name, manufacturer, sensor_type, model, serial_number, calibration, custom_parameters)
+    VALUES (
+      NEW.sensor_name,
+      NEW.manufacturer,
+      NEW.sensor_type,
+      NEW.custom_parameters->>'model',
+      NULLIF(COALESCE(NEW.custom_parameters->>'serial_number', NEW.custom_parameters->>'serial'), ''),
+      NEW.custom_parameters->'calibration',
+      NEW.custom_parameters
+    )
+    RETURNING id INTO cid;
+    INSERT INTO sensor_identity_keys (identity_key, catalog_sensor_id) VALUES (key, cid)
+      ON CONFLICT (identity_key) DO NOTHING;
+    IF NOT FOUND THEN
+      -- parallel angelegt: den anderen Katalogeintrag verwenden
+      DELETE FROM sensor_catalog WHERE id = cid;
+      SELECT catalog_sensor_id INTO cid FROM sensor_identity_keys WHERE identity_key = key;
+    END IF;
+  END IF;
+  INSERT INTO entry_sensors (entry_id, catalog_sensor_id, sensor_id, ros_topics)
+  VALUES (NEW.entry_id, cid, NEW.id, NEW.ros_topics)
+  ON CONFLICT (sensor_id) DO UPDATE
+    SET catalog_sensor_id = EXCLUDED.catalog_sensor_id, ros_topics = EXCLUDED.ros_topics;
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER link_sensors_to_catalog AFTER INSERT OR UPDATE ON sensors
+    FOR EACH ROW EXECUTE FUNCTION link_sensor_to_catalog();
+
+-- Bestehende Sensoren übernehmen (löst den Trigger für jede Zeile aus).
+UPDATE sensors SET ros_topics = ros_topics;
@@ -0,0 +1,9 @@
+-- Ergebnis der letzten Sensor/Topic-Konsistenzprüfung je Eintrag.
+CREATE TABLE entry_consistency (
+  entry_id BIGINT PRIMARY KEY REFERENCES entries(id) ON DELETE CASCADE,
+  checked_at TIMESTAMP WITH TIME ZONE DEFAULT now() NOT NULL,
+  missing_topics INTEGER NOT NULL DEFAULT 0,
+  unattributed_topics INTEGER NOT NULL DEFAULT 0,
+  frequency_mismatches INTEGER NOT NULL DEFAULT 0,
+  issues JSONB NOT NULL DEFAULT '[]'::jsonb
+);
@@ -0,0 +1,13 @@
+-- Metadaten zu Tags. Die Tags selbst bleiben Arrays an entries/sequences; eine
+-- Definition ist optional und existiert nur für kuratierte Tags.
+CREATE TABLE tag_definitions (
+  name TEXT PRIMARY KEY,
+  color TEXT,
+  description TEXT,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT now() NOT NULL,
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT now() NOT NULL,
+  version BIGINT NOT NULL DEFAULT 1
+);
+
+CREATE TRIGGER bump_tag_definitions_version BEFORE UPDATE ON tag_definitions
+    FOR EACH ROW EXECUTE FUNCTION bump_row_version();
@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS collection_snapshot_items;
+DROP TABLE IF EXISTS collection_snapshots;
+DROP TABLE IF EXISTS collection_items;
+DROP TABLE IF EXISTS collections;
@@ -0,0 +1,52 @@
+-- Benannte Sammlungen von Einträgen bzw. Sequenzen (z.B. Trainings-/Evaluationssets).
+CREATE TABLE collections (
+  id BIGINT PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+  name TEXT NOT NULL UNIQUE,
+  description TEXT,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT now() NOT NULL,
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT now() NOT NULL,
+  version BIGINT NOT NULL DEFAULT 1
+);
+
+CREATE TRIGGER bump_collections_version BEFORE UPDATE ON collections
+    FOR EACH ROW EXECUTE FUNCTION bump_row_version();
+
+-- Ohne sequence_id gehört der ganze Eintrag zur Sammlung.
+CREATE TABLE collection_items (
+  id BIGINT PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+  collection_id BIGINT NOT NULL REFERENCES collections(id) ON DELETE CASCADE,
+  entry_id BIGINT NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+  sequence_id BIGINT REFERENCES sequences(id) ON DELETE CASCADE,
+  added_at TIMESTAMP WITH TIME ZONE DEFAULT now() NOT NULL,
+  UNIQUE NULLS NOT DISTINCT (collection_id, entry_id, sequence_id)
+);
+CREATE INDEX collection_items_collection_id_idx ON collection_items (collection_id);
+
+-- Eingefrorene, unveränderliche Versionen einer Sammlung. Solange Snapshots
+-- existieren, kann die Sammlung nicht gelöscht werden.
+CREATE TABLE collection_snapshots (
+  id BIGINT PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+  collection_id BIGINT NOT NULL REFERENCES collections(id) ON DELETE RESTRICT,
+  snapshot_version INTEGER NOT NULL,
+  note TEXT,
+  item_count INTEGER NOT NULL,
+  total_size BIGINT NOT NULL,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT now() NOT NULL,
+  UNIQUE (collection_id, snapshot_version)
+);
+
+-- Stand der Dateien zum Zeitpunkt des Einfrierens. Bewusst ohne Fremdschlüssel
+-- auf entries/sequences: der Snapshot bleibt auch nach dem Löschen erhalten.
+CREATE TABLE collection_snapshot_items (
+  id BIGINT PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+  snapshot_id BIGINT NOT NULL REFERENCES collection_snapshots(id) ON DELETE CASCADE,
+  entry_id BIGINT NOT NULL,
+  entry_name TEXT NOT NULL,
+  path TEXT NOT NULL,
+  size BIGINT NOT NULL,
+  sequence_id BIGINT,
+  sequence_name TEXT,
+  start_timestamp BIGINT,
+  end_timestamp BIGINT
+);
+CREATE INDEX collection_snapshot_items_snapshot_id_idx ON collection_snapshot_items (snapshot_id);
@@ -0,0 +1,26 @@
+-- Lokale Benutzerkonten. Rollen sind gestuft, jede schließt die Rechte der
+-- vorherigen ein: viewer < editor < plugin-operator < admin.
+CREATE TABLE users (
+  id BIGINT PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+  username TEXT NOT NULL UNIQUE,
+  -- Argon2id im PHC-Format
+  password_hash TEXT NOT NULL,
+  role TEXT NOT NULL CHECK (role IN ('viewer', 'editor', 'plugin-operator', 'admin')),
+  disabled BOOLEAN NOT NULL DEFAULT false,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT now() NOT NULL,
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT now() NOT NULL,
+  version BIGINT NOT NULL DEFAULT 1
+);
+
+CREATE TRIGGER bump_users_version BEFORE UPDATE ON users
+    FOR EACH ROW EXECUTE FUNCTION bump_row_version();
+
+-- Anmeldesitzungen. Gespeichert wird nur der SHA-256 des Tokens, ein Datenbank-
+-- Dump enthält also keine gültigen Zugangsdaten.
+CREATE TABLE sessions (
+  token_hash TEXT PRIMARY KEY,
+  user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT now() NOT NULL,
+  expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+CREATE INDEX sessions_user_id_idx ON sessions (user_id);
@@ -0,0 +1,16 @@
+-- API-Schlüssel für Skripte und Automatisierung. Statt einer Rolle trägt ein
+-- Schlüssel eine Liste von Scopes (read:entries, write:metadata, run:plugins).
+-- Wie bei den Sitzungen wird nur der SHA-256 des Schlüssels gespeichert.
+CREATE TABLE api_keys (
+  id BIGINT PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+  name TEXT NOT NULL,
+  key_hash TEXT NOT NULL UNIQUE,
+  -- Anfang des Schlüssels, damit man ihn in Listen wiedererkennt
+  key_prefix TEXT NOT NULL,
+  scopes TEXT[] NOT NULL,
+  created_by BIGINT REFERENCES users(id) ON DELETE SET NULL,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT now() NOT NULL,
+  -- NULL: läuft nicht ab
+  expires_at TIMESTAMP WITH TIME ZONE,
+  last_used_at TIMESTAMP WITH TIME ZONE
+);
@@ -0,0 +1,21 @@
+-- Protokoll aller ändernden API-Aufrufe (POST, PUT, PATCH, DELETE), auch der
+-- abgewiesenen. Einträge werden nie geändert oder gelöscht.
+CREATE TABLE audit_log (
+  id BIGINT PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+  at TIMESTAMP WITH TIME ZONE DEFAULT now() NOT NULL,
+  -- user:<name>, api_key:<id>, plugin:<name>:<instance> oder anonymous
+  actor TEXT NOT NULL,
+  method TEXT NOT NULL,
+  -- Routenmuster, z.B. /entries/<entry_id>/metadata; NULL ohne passende Route
+  route TEXT,
+  path TEXT NOT NULL,
+  -- Pfadparameter der Route, z.B. {"entry_id": "12"}
+  target_ids JSONB NOT NULL,
+  -- Query-Parameter; Request-Bodies werden nicht protokolliert
+  parameters JSONB NOT NULL,
+  status INTEGER NOT NULL,
+  outcome TEXT NOT NULL CHECK (outcome IN ('success', 'denied', 'rejected', 'error'))
+);
+CREATE INDEX audit_log_at_idx ON audit_log (at);
+CREATE INDEX audit_log_actor_idx ON audit_log (actor);
+CREATE INDEX audit_log_path_idx ON audit_log (path text_pattern_ops);
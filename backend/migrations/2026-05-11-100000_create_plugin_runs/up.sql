@@ -0,0 +1,24 @@
+-- Verlauf aller Plugin-Instanzen. Ersetzt die Historie im Speicher des
+-- Plugin-Managers, die bei jedem Neustart und bei PUT /plugins/register verloren ging.
+CREATE TABLE plugin_runs (
+  instance_id BIGINT PRIMARY KEY,
+  plugin_name TEXT NOT NULL,
+  -- PLUGIN_VERSION des Moduls, falls angegeben
+  plugin_version TEXT,
+  -- manual, on_entry_create, on_entry_update, on_entry_delete oder on_schedule
+  trigger TEXT NOT NULL,
+  -- an plugin.run(data) übergebene Daten; kein JSON wird als String abgelegt
+  payload JSONB NOT NULL,
+  entry_path TEXT,
+  started_at TIMESTAMP WITH TIME ZONE DEFAULT now() NOT NULL,
+  -- NULL, solange die Instanz läuft
+  ended_at TIMESTAMP WITH TIME ZONE,
+  -- InstanceState wie in der API (Running, Completed, Failed, ...)
+  state TEXT NOT NULL,
+  -- `result` aus der exited-Nachricht des Runners
+  result JSONB,
+  error TEXT,
+  trace TEXT
+);
+CREATE INDEX plugin_runs_started_at_idx ON plugin_runs (started_at);
+CREATE INDEX plugin_runs_plugin_name_idx ON plugin_runs (plugin_name);
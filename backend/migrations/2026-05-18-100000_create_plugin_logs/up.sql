@@ -0,0 +1,15 @@
+-- Log-Zeilen je Plugin-Instanz: `log`-Nachrichten des Runners und stderr des
+-- Python-Prozesses. Kein Fremdschlüssel auf plugin_runs, weil beide Tabellen
+-- unabhängig voneinander geschrieben werden.
+CREATE TABLE plugin_logs (
+  instance_id BIGINT NOT NULL,
+  -- fortlaufend je Instanz, in der Reihenfolge des Eintreffens
+  seq INTEGER NOT NULL,
+  at TIMESTAMP WITH TIME ZONE NOT NULL,
+  -- Python-Level (DEBUG, INFO, WARNING, ERROR, CRITICAL); stderr als ERROR
+  level TEXT NOT NULL,
+  -- Name des Python-Loggers, `stderr` für die Fehlerausgabe des Prozesses
+  logger TEXT NOT NULL,
+  message TEXT NOT NULL,
+  PRIMARY KEY (instance_id, seq)
+);
@@ -0,0 +1,16 @@
+-- Ergebnisse von Plugin-Läufen, die an einem Eintrag hängen. Tags, Metadaten und
+-- Sequenzen werden zusätzlich direkt am Eintrag übernommen; hier steht, welcher
+-- Lauf sie geliefert hat.
+CREATE TABLE entry_artifacts (
+  id BIGSERIAL PRIMARY KEY,
+  entry_id BIGINT NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+  kind TEXT NOT NULL CHECK (kind IN ('tags', 'metadata', 'sequence', 'file', 'metrics')),
+  -- das Artefakt ohne `type`; Sequenzen mit `sequence_id`, Dateien mit `size_bytes`
+  content JSONB NOT NULL,
+  plugin_name TEXT NOT NULL,
+  plugin_version TEXT,
+  -- Lauf aus plugin_runs; kein Fremdschlüssel, der Lauf wird getrennt geschrieben
+  instance_id BIGINT NOT NULL,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT now() NOT NULL
+);
+CREATE INDEX entry_artifacts_entry_id_idx ON entry_artifacts (entry_id);
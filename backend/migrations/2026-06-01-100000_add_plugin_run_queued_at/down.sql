@@ -0,0 +1 @@
+ALTER TABLE "plugin_runs" DROP COLUMN "queued_at";
@@ -0,0 +1,4 @@
+-- Zeitpunkt, zu dem ein Lauf in die Warteschlange kam. Solange er wartet
+-- (state = 'Queued'), steht derselbe Zeitpunkt in `started_at`; beim Start des
+-- Runners wird `started_at` neu gesetzt.
+ALTER TABLE "plugin_runs" ADD COLUMN "queued_at" TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,3 @@
+DROP INDEX plugin_runs_retry_of_idx;
+ALTER TABLE "plugin_runs" DROP COLUMN "retry_of";
+ALTER TABLE "plugin_runs" DROP COLUMN "attempt";
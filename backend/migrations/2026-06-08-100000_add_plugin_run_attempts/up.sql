@@ -0,0 +1,7 @@
+-- Wiederholungen gescheiterter Läufe. Jeder Versuch ist ein eigener Lauf mit
+-- eigener instance_id; `retry_of` verweist auf den ersten Versuch, `attempt`
+-- zählt ab 1. Ein Versuch, nach dem ein weiterer eingereiht wurde, endet mit
+-- state = 'Retrying'.
+ALTER TABLE "plugin_runs" ADD COLUMN "attempt" INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE "plugin_runs" ADD COLUMN "retry_of" BIGINT;
+CREATE INDEX plugin_runs_retry_of_idx ON plugin_runs (retry_of);
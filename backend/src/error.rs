@@ -2,6 +2,7 @@ use deadpool_diesel::{InteractError, postgres::PoolError};
 use diesel::ConnectionError;
 use rocket::http::Status;
 use rocket::response::{self, Responder};
+use rocket::serde::json::Json;
 use serde::Serialize;
 
 /// Zentrale Fehlerart der Anwendung.
@@ -21,6 +22,28 @@ pub enum Error {
     CustomError(String),
     /// Allgemeiner I/O-Fehler.
     IoError(std::io::Error),
+    /// Fehlende, ungültige oder abgelaufene Anmeldung.
+    Unauthorized(String),
+    /// Angemeldet, aber die Rolle reicht für die Aktion nicht aus.
+    Forbidden(String),
+    /// Ungültige Felder in Eingabedaten; Antwort 400 mit allen Feldfehlern.
+    InvalidFields(Vec<FieldError>),
+}
+
+/// Fehler zu einem Feld der Eingabedaten, z.B. der Startdaten eines Plugins.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        FieldError {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
 }
 
 impl From<StorageError> for Error {
@@ -68,6 +91,8 @@ impl Error {
             Error::StorageError(se) => match se {
                 StorageError::NotFound(msg) => (Status::NotFound, msg.clone()),
                 StorageError::AlreadyExists(msg) => (Status::Conflict, msg.clone()),
+                StorageError::PreconditionFailed(msg) => (Status::PreconditionFailed, msg.clone()),
+                StorageError::ValidationError(msg) => (Status::BadRequest, msg.clone()),
                 StorageError::DecodingError(msg) => (Status::BadRequest, msg.clone()),
 
                 // Verbindungs-/Poolprobleme sind häufig temporär.
@@ -92,6 +117,16 @@ impl Error {
             ),
 
             Error::IoError(_) => (Status::InternalServerError, "I/O error".to_string()),
+
+            Error::Unauthorized(msg) => (Status::Unauthorized, msg.clone()),
+            Error::Forbidden(msg) => (Status::Forbidden, msg.clone()),
+            Error::InvalidFields(errors) => {
+                let fields: Vec<String> = errors
+                    .iter()
+                    .map(|e| format!("{}: {}", e.field, e.message))
+                    .collect();
+                (Status::BadRequest, fields.join("; "))
+            }
         }
     }
 }
@@ -103,6 +138,16 @@ impl Error {
 impl<'r, 'o: 'r> Responder<'r, 'o> for Error {
     fn respond_to(self, req: &'r rocket::Request<'_>) -> response::Result<'o> {
         let (status, message) = match self {
+            // Veraltete Version (If-Match passt nicht) -> 412, damit der Client
+            // neu laden und seine Änderung erneut anwenden kann.
+            Error::StorageError(StorageError::PreconditionFailed(msg)) => (
+                rocket::http::Status::PreconditionFailed,
+                format!("Precondition failed: {}", msg),
+            ),
+            Error::StorageError(StorageError::ValidationError(msg)) => (
+                rocket::http::Status::BadRequest,
+                format!("Validation error: {}", msg),
+            ),
             Error::StorageError(e) => (
                 rocket::http::Status::InternalServerError,
                 format!("Storage error: {:?}", e),
@@ -123,6 +168,25 @@ impl<'r, 'o: 'r> Responder<'r, 'o> for Error {
                 rocket::http::Status::InternalServerError,
                 format!("IO error: {:?}", e),
             ),
+            Error::Unauthorized(msg) => {
+                return response::Response::build_from(
+                    format!("Unauthorized: {}", msg).respond_to(req)?,
+                )
+                .status(rocket::http::Status::Unauthorized)
+                .raw_header("WWW-Authenticate", "Bearer")
+                .ok();
+            }
+            Error::Forbidden(msg) => (
+                rocket::http::Status::Forbidden,
+                format!("Forbidden: {}", msg),
+            ),
+            // als JSON `[{ "field": ..., "message": ... }]`, damit der Client
+            // die Fehler den Feldern zuordnen kann
+            Error::InvalidFields(errors) => {
+                return response::Response::build_from(Json(errors).respond_to(req)?)
+                    .status(rocket::http::Status::BadRequest)
+                    .ok();
+            }
         };
         response::Response::build_from(message.respond_to(req)?)
             .status(status)
@@ -135,6 +199,10 @@ pub enum StorageError {
     IoError(std::io::Error),
     NotFound(String),
     AlreadyExists(String),
+    /// Die erwartete Version (`If-Match`) passt nicht mehr zur gespeicherten Zeile.
+    PreconditionFailed(String),
+    /// Eingabe verletzt eine fachliche Regel (z.B. Sequenz außerhalb der Aufnahme).
+    ValidationError(String),
     DecodingError(String),
     ConnectionError(ConnectionError),
     PoolError(PoolError),
@@ -0,0 +1,164 @@
+//! Ereignisse über Änderungen am Katalog und an Plugin-Instanzen.
+//!
+//! Der [`EventBus`] verteilt Ereignisse an alle Abonnenten (z.B. den
+//! SSE-Endpunkt `GET /events`). Ohne Abonnenten gehen Ereignisse verloren; wer zu
+//! langsam liest, verpasst ältere Ereignisse und muss den Stand neu laden.
+
+use crate::error::StorageError;
+use crate::plugin_manager::manager::InstanceState;
+use crate::plugin_manager::pipeline::PipelineStatus;
+use crate::storage::models::EntryID;
+use rocket::serde::Serialize;
+use std::collections::HashSet;
+use tokio::sync::broadcast;
+
+/// Gepufferte Ereignisse je Abonnent.
+pub const EVENT_BUFFER: usize = 1024;
+
+/// Alle Ereignistypen, wie sie in `type` und im SSE-Feld `event` erscheinen.
+pub const EVENT_TYPES: &[&str] = &[
+    "entry_created",
+    "entry_updated",
+    "entry_deleted",
+    "metadata_updated",
+    "plugin_state",
+    "plugin_progress",
+    "pipeline_state",
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(crate = "rocket::serde", tag = "type", rename_all = "snake_case")]
+pub enum CatalogEvent {
+    /// Der Scanner hat eine neue Aufnahme eingetragen.
+    EntryCreated { entry_id: EntryID, path: String },
+    /// Der Scanner hat eine Aufnahme (oder ihre Metadaten-YAML) neu eingelesen.
+    EntryUpdated { entry_id: EntryID, path: String },
+    /// Die Datei ist verschwunden, der Eintrag wurde gelöscht.
+    EntryDeleted { entry_id: EntryID, path: String },
+    /// Metadaten wurden über die API geändert (einzeln, per Bulk oder Import).
+    MetadataUpdated { entry_id: EntryID },
+    PluginState {
+        instance_id: u64,
+        plugin_name: String,
+        state: InstanceState,
+    },
+    PluginProgress {
+        instance_id: u64,
+        plugin_name: String,
+        progress: f32,
+    },
+    /// Ein Schritt eines Pipeline-Laufs hat begonnen oder geendet.
+    PipelineState {
+        run_id: u64,
+        pipeline: String,
+        status: PipelineStatus,
+        progress: f32,
+    },
+}
+
+impl CatalogEvent {
+    /// Ereignistyp, einer der [`EVENT_TYPES`].
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CatalogEvent::EntryCreated { .. } => "entry_created",
+            CatalogEvent::EntryUpdated { .. } => "entry_updated",
+            CatalogEvent::EntryDeleted { .. } => "entry_deleted",
+            CatalogEvent::MetadataUpdated { .. } => "metadata_updated",
+            CatalogEvent::PluginState { .. } => "plugin_state",
+            CatalogEvent::PluginProgress { .. } => "plugin_progress",
+            CatalogEvent::PipelineState { .. } => "pipeline_state",
+        }
+    }
+
+    /// Betroffener Eintrag; Plugin-Ereignisse gehören zu keinem Eintrag.
+    pub fn entry_id(&self) -> Option<EntryID> {
+        match self {
+            CatalogEvent::EntryCreated { entry_id, .. }
+            | CatalogEvent::EntryUpdated { entry_id, .. }
+            | CatalogEvent::EntryDeleted { entry_id, .. }
+            | CatalogEvent::MetadataUpdated { entry_id } => Some(*entry_id),
+            CatalogEvent::PluginState { .. }
+            | CatalogEvent::PluginProgress { .. }
+            | CatalogEvent::PipelineState { .. } => None,
+        }
+    }
+}
+
+/// Filter eines Abonnenten. Leere Felder lassen alles durch.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventFilter {
+    pub types: Option<HashSet<String>>,
+    pub entry_id: Option<EntryID>,
+}
+
+impl EventFilter {
+    /// Liest `types` als Komma-Liste; unbekannte Typen sind ein Fehler.
+    pub fn parse(types: Option<&str>, entry_id: Option<EntryID>) -> Result<Self, StorageError> {
+        let types = match types {
+            None => None,
+            Some(list) => {
+                let types: HashSet<String> = list
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|t| !t.is_empty())
+                    .map(str::to_string)
+                    .collect();
+                let mut unknown: Vec<&str> = types
+                    .iter()
+                    .map(String::as_str)
+                    .filter(|t| !EVENT_TYPES.contains(t))
+                    .collect();
+                if !unknown.is_empty() {
+                    unknown.sort();
+                    return Err(StorageError::ValidationError(format!(
+                        "unknown event types: {}",
+                        unknown.join(", ")
+                    )));
+                }
+                Some(types)
+            }
+        };
+        Ok(EventFilter { types, entry_id })
+    }
+
+    /// Ist `entry_id` gesetzt, fallen Plugin-Ereignisse heraus.
+    pub fn matches(&self, event: &CatalogEvent) -> bool {
+        if let Some(types) = &self.types
+            && !types.contains(event.kind())
+        {
+            return false;
+        }
+        match self.entry_id {
+            Some(id) => event.entry_id() == Some(id),
+            None => true,
+        }
+    }
+}
+
+/// Verteiler für [`CatalogEvent`]s; Klone teilen sich denselben Kanal.
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<CatalogEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUFFER);
+        EventBus { sender }
+    }
+
+    pub fn publish(&self, event: CatalogEvent) {
+        // Fehler heißt nur: niemand hört zu
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<CatalogEvent> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,4 +1,5 @@
 pub mod error;
+pub mod events;
 pub mod plugin_manager;
 pub mod routes;
 pub mod schema;
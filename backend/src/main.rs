@@ -2,7 +2,14 @@
 use backend::AppState;
 use backend::plugin_manager::manager::PluginManager;
 use backend::plugin_manager::plugin::Trigger;
+use backend::plugin_manager::queue;
+use backend::routes::audit::{AuditLog, get_audit_log};
+use backend::routes::auth::{
+    create_api_key, create_user, delete_api_key, delete_user, forbidden, get_api_keys, get_user,
+    get_users, login, logout, me, unauthorized, update_user,
+};
 use backend::routes::database::*;
+use backend::routes::events::get_events;
 use backend::routes::health_check::health;
 use backend::routes::logs::*;
 use backend::routes::plugins::*;
@@ -81,9 +88,39 @@ async fn main() {
 
     #[allow(unused_mut)]
     let mut storage_manager = StorageManager::new(&db_url).unwrap();
+    // Exportverzeichnis (z.B. für MCAP-Clips) optional überschreiben.
+    if let Ok(export_dir) = env::var("EXPORT_DIR") {
+        storage_manager = storage_manager.with_export_dir(export_dir);
+    }
+
+    // Solange es keine Benutzer gibt, ein Admin-Konto aus der Umgebung anlegen.
+    if let Ok(password) = env::var("ADMIN_PASSWORD") {
+        let username = env::var("ADMIN_USERNAME").unwrap_or("admin".to_string());
+        match storage_manager
+            .ensure_initial_admin(username.clone(), password)
+            .await
+        {
+            Ok(true) => tracing::info!("Created initial admin account '{}'", username),
+            Ok(false) => {}
+            // ohne Konto kommt niemand ins System, also nicht stillschweigend weiterlaufen
+            Err(e) => panic!(
+                "ADMIN_PASSWORD is set but the initial admin account '{}' was rejected: {:?}",
+                username, e
+            ),
+        }
+    }
+
+    // Läufe aus einer früheren Sitzung können nicht mehr enden.
+    match storage_manager.abandon_unfinished_plugin_runs().await {
+        Ok(0) => {}
+        Ok(n) => tracing::warn!("Marked {} unfinished plugin runs as failed", n),
+        Err(e) => tracing::error!("Failed to close unfinished plugin runs: {:?}", e),
+    }
 
     // Plugin-Manager initialisieren und Plugins aus dem Verzeichnis laden.
-    let mut plugin_manager = PluginManager::new();
+    let mut plugin_manager = PluginManager::new()
+        .with_events(storage_manager.events().clone())
+        .with_storage(storage_manager.clone());
     plugin_manager
         .register_plugins(PathBuf::from("/plugins"))
         .unwrap();
@@ -94,6 +131,10 @@ async fn main() {
     // Gemeinsamer, asynchroner Zugriff auf den Plugin-Manager.
     let plugin_manager_arc = Arc::new(tokio::sync::Mutex::new(plugin_manager));
 
+    // Dispatcher starten: startet eingereihte Plugin-Instanzen,
+    // sobald Worker und das Limit des Plugins es zulassen.
+    tokio::spawn(queue::run_dispatcher(plugin_manager_arc.clone()));
+
     // File-Watcher starten, damit Dateisystem-Events in Backend-Events
     // bzw. Storage-Aktionen übersetzt werden können.
     file_watcher::start_scanning(
@@ -104,6 +145,30 @@ async fn main() {
     .await
     .unwrap();
 
+    // Hintergrundtask:
+    // löscht Exporte (Clips, Plugin-Artefakte), die älter als
+    // `EXPORT_RETENTION_HOURS` (Standard 24) sind.
+    {
+        let retention_hours = env::var("EXPORT_RETENTION_HOURS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(24);
+        let sm_clone = storage_manager.clone();
+        tokio::spawn(async move {
+            loop {
+                match sm_clone
+                    .cleanup_exports(Duration::from_secs(retention_hours * 3600))
+                    .await
+                {
+                    Ok(0) => {}
+                    Ok(n) => debug!("Removed {n} expired exports"),
+                    Err(e) => tracing::warn!("Export cleanup failed: {e:?}"),
+                }
+                tokio::time::sleep(Duration::from_secs(3600)).await;
+            }
+        });
+    }
+
     // Hintergrundtask:
     // räumt abgeschlossene oder unresponsive Plugin-Instanzen auf.
     {
@@ -258,23 +323,73 @@ async fn main() {
             "/",
             routes![
                 health,
+                login,
+                logout,
+                me,
+                get_users,
+                create_user,
+                get_user,
+                update_user,
+                delete_user,
+                get_api_keys,
+                create_api_key,
+                delete_api_key,
+                get_audit_log,
                 get_entries,
+                bulk_entries,
                 get_entry_by_path,
                 get_entry,
                 get_sensors,
+                get_sensor,
                 get_all_sensors,
                 add_sensor,
                 update_sensor,
                 remove_sensor,
+                get_sensor_catalog,
+                get_catalog_sensor_entries,
+                get_entry_sensor_assignments,
+                update_catalog_sensor,
+                merge_catalog_sensors,
                 get_sequences,
+                get_sequence,
+                search_sequences,
                 get_topics,
                 get_metadata,
                 update_metadata,
                 add_sequence,
                 remove_sequence,
                 update_sequence,
+                split_sequence,
+                merge_sequences,
+                get_overlapping_sequences,
+                get_timeline,
+                get_entry_consistency,
+                get_entry_artifacts,
+                check_entry_consistency,
+                get_sequence_clip,
                 add_tag,
                 remove_tag,
+                get_tags,
+                update_tag_definition,
+                delete_tag,
+                rename_tag,
+                merge_tags,
+                bulk_tag_entries,
+                get_collections,
+                create_collection,
+                get_collection,
+                update_collection,
+                delete_collection,
+                add_collection_items,
+                remove_collection_item,
+                freeze_collection,
+                get_collection_snapshots,
+                get_collection_snapshot,
+                diff_collection,
+                export_collection_snapshot,
+                export_manifest,
+                import_metadata,
+                get_events,
                 get_logs,
                 start_transaction,
                 commit_transaction,
@@ -285,11 +400,22 @@ async fn main() {
                 pause_plugin_instance,
                 resume_plugin_instance,
                 get_plugin_instances,
+                get_plugin_queue,
+                cancel_queued_plugin_instance,
+                get_pipelines,
+                start_pipeline,
+                get_pipeline_runs,
+                get_pipeline_run,
+                get_plugin_instance_attempts,
+                get_plugin_instance_logs,
+                stream_plugin_instance_logs,
                 get_registered_plugins,
                 enable_plugin,
                 disable_plugin,
             ],
         )
+        .register("/", catchers![unauthorized, forbidden])
+        .attach(AuditLog)
         .manage(AppState {
             storage_manager,
             plugin_manager: plugin_manager_arc,
@@ -0,0 +1,144 @@
+use crate::storage::models::PluginLogLine;
+use crate::storage::storage_manager::StorageManager;
+use chrono::Utc;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+/// Zeilen je Seite, wenn `GET /plugin/instances/<id>/logs` kein `page_size` bekommt.
+pub const DEFAULT_PAGE_SIZE: u32 = 500;
+
+/// Letzte Zeilen, die eine laufende Instanz für neue Live-Abonnenten vorhält.
+pub const LOG_BACKLOG: usize = 1000;
+
+/// Gepufferte Zeilen je Live-Abonnent, bevor ein langsamer Leser Zeilen verpasst.
+/// Die Persistenz hängt nicht daran, sie bekommt jede Zeile.
+pub const LOG_CHANNEL_CAPACITY: usize = 1024;
+
+/// Höchstens so viele Zeilen schreibt ein `INSERT`.
+const LOG_BATCH: usize = 200;
+
+/// Logger-Name für Zeilen aus stderr des Python-Prozesses.
+pub const STDERR_LOGGER: &str = "stderr";
+
+/// Level für Zeilen aus stderr; wie bisher im Backend-Log als Fehler geführt.
+pub const STDERR_LEVEL: &str = "ERROR";
+
+#[derive(Debug)]
+struct LogState {
+    next_seq: i32,
+    backlog: VecDeque<PluginLogLine>,
+    /// `None`, sobald die Instanz beendet ist; Abonnenten sehen dann `Closed`.
+    tx: Option<broadcast::Sender<PluginLogLine>>,
+    /// Verlustfreier Kanal zum Schreiber in `plugin_logs`, siehe
+    /// [`InstanceLogs::persist`].
+    persist_tx: Option<mpsc::UnboundedSender<PluginLogLine>>,
+}
+
+/// Log-Zeilen einer laufenden Instanz. Der Actor schreibt, Persistenz und
+/// Live-Tails lesen; Klone teilen sich denselben Zustand.
+///
+/// Live-Tails hängen am verlustbehafteten Broadcast, die Persistenz an einem
+/// eigenen, unbeschränkten Kanal, damit in der Datenbank keine Zeile fehlt.
+#[derive(Debug, Clone)]
+pub struct InstanceLogs {
+    instance_id: i64,
+    state: Arc<Mutex<LogState>>,
+}
+
+/// Stand beim Abonnieren: vorgehaltene Zeilen und alle folgenden.
+#[derive(Debug)]
+pub struct LogSubscription {
+    pub backlog: Vec<PluginLogLine>,
+    pub rx: broadcast::Receiver<PluginLogLine>,
+}
+
+impl InstanceLogs {
+    pub fn new(instance_id: u64) -> Self {
+        let (tx, _) = broadcast::channel(LOG_CHANNEL_CAPACITY);
+        InstanceLogs {
+            instance_id: instance_id as i64,
+            state: Arc::new(Mutex::new(LogState {
+                next_seq: 0,
+                backlog: VecDeque::new(),
+                tx: Some(tx),
+                persist_tx: None,
+            })),
+        }
+    }
+
+    /// Hängt eine Zeile an; nach [`InstanceLogs::finish`] wird sie verworfen.
+    pub fn push(&self, level: &str, logger: &str, message: &str) {
+        let mut state = self.state.lock().expect("instance logs lock");
+        let Some(tx) = state.tx.clone() else {
+            return;
+        };
+        let line = PluginLogLine {
+            instance_id: self.instance_id,
+            seq: state.next_seq,
+            at: Utc::now(),
+            level: level.to_string(),
+            logger: logger.to_string(),
+            message: message.to_string(),
+        };
+        state.next_seq += 1;
+        if state.backlog.len() == LOG_BACKLOG {
+            state.backlog.pop_front();
+        }
+        state.backlog.push_back(line.clone());
+        if let Some(persist_tx) = &state.persist_tx {
+            let _ = persist_tx.send(line.clone());
+        }
+        // Fehler heißt nur: niemand hört zu
+        let _ = tx.send(line);
+    }
+
+    /// Beendet den Log der Instanz; Abonnenten lesen noch den Rest und enden dann.
+    pub fn finish(&self) {
+        let mut state = self.state.lock().expect("instance logs lock");
+        state.tx = None;
+        state.persist_tx = None;
+    }
+
+    /// Empfänger für die Persistenz: erst die vorgehaltenen Zeilen, dann jede
+    /// weitere bis zum Ende der Instanz. Ersetzt einen früheren Empfänger;
+    /// `None`, wenn die Instanz schon beendet ist.
+    pub fn persist(&self) -> Option<mpsc::UnboundedReceiver<PluginLogLine>> {
+        let mut state = self.state.lock().expect("instance logs lock");
+        state.tx.as_ref()?;
+        let (persist_tx, rx) = mpsc::unbounded_channel();
+        for line in state.backlog.iter() {
+            let _ = persist_tx.send(line.clone());
+        }
+        state.persist_tx = Some(persist_tx);
+        Some(rx)
+    }
+
+    /// Vorgehaltene Zeilen und ein Empfänger für alle späteren, ohne Lücke
+    /// dazwischen. `None`, wenn die Instanz schon beendet ist.
+    pub fn subscribe(&self) -> Option<LogSubscription> {
+        let state = self.state.lock().expect("instance logs lock");
+        let rx = state.tx.as_ref()?.subscribe();
+        Some(LogSubscription {
+            backlog: state.backlog.iter().cloned().collect(),
+            rx,
+        })
+    }
+}
+
+/// Schreibt die Zeilen einer Instanz in `plugin_logs`, bis ihr Log endet.
+pub async fn persist_instance_logs(
+    storage: StorageManager,
+    mut rx: mpsc::UnboundedReceiver<PluginLogLine>,
+) {
+    let mut batch = Vec::with_capacity(LOG_BATCH);
+    while rx.recv_many(&mut batch, LOG_BATCH).await > 0 {
+        let lines = std::mem::take(&mut batch);
+        let count = lines.len();
+        if let Err(e) = storage.insert_plugin_logs(lines).await {
+            warn!("failed to store {count} plugin log lines: {e:?}");
+        }
+    }
+}
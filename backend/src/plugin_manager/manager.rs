@@ -1,9 +1,29 @@
+use crate::events::{CatalogEvent, EventBus};
+use crate::plugin_manager::logs::{self, InstanceLogs};
+use crate::plugin_manager::parameters::ParameterSchema;
+use crate::plugin_manager::pipeline::{
+    PipelineConfig, PipelineRun, PipelineRunView, PipelineTrigger, Pipelines, ReadyStep, StepRef,
+};
 use crate::plugin_manager::plugin::{BackendEvent, Trigger, TriggerKind};
 use crate::plugin_manager::python_bridge;
-use crate::{error::Error, plugin_manager::plugin::Plugin};
+use crate::plugin_manager::queue::{self, JobPriority, JobQueue, QueuedJob};
+use crate::plugin_manager::retry::RetryPolicy;
+use crate::plugin_manager::runs::{self, RunOutcome};
+use crate::plugin_manager::sandbox::{
+    DATA_DIR, PreparedSandbox, SandboxLimits, SandboxPolicy, SandboxSettings,
+};
+use crate::plugin_manager::trigger_filter::{TriggerFilter, TriggerSubject};
+use crate::plugin_manager::watchdog::{RunLimits, TimeoutKind, Watchdog};
+use crate::storage::auth;
+use crate::storage::models::NewPluginRun;
+use crate::storage::storage_manager::StorageManager;
+use crate::{
+    error::{Error, StorageError},
+    plugin_manager::plugin::Plugin,
+};
 use cron::Schedule;
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
 use std::process::Stdio;
@@ -25,6 +45,8 @@ const PYTHON_UNBUFFERED_FLAG: &str = "-u";
 const RUNNER_PATH: &str = "src/plugin_manager/plugins/plugin_runner.py";
 const ARG_PLUGIN_PATH: &str = "--plugin-path";
 const ARG_INSTANCE_ID: &str = "--instance-id";
+/// Umgebungsvariable mit dem Runner-Token für Rückrufe an die Backend-API.
+const ENV_API_TOKEN: &str = "CATALOG_API_TOKEN";
 const FALLBACK_PLUGIN_NAME: &str = "unknown";
 
 const TRIGGER_ON_SCHEDULE_PREFIX: &str = "on_schedule:";
@@ -47,6 +69,15 @@ const ERR_PY_STDOUT_CLOSED: &str = "Python runner stdout closed";
 const ERR_UNKNOWN_ERROR: &str = "unknown_error";
 const ERR_FAILED_SEND_CMD_PREFIX: &str = "Failed to send cmd to python runner: ";
 const ERR_FAILED_FLUSH_CMD_PREFIX: &str = "Failed to flush cmd to python runner: ";
+const ERR_INVALID_PARAMETERS_PREFIX: &str = "Plugin '";
+const ERR_INVALID_PARAMETERS_MID: &str = "': invalid PLUGIN_PARAMETERS: ";
+const ERR_INVALID_RETRY_PREFIX: &str = "Plugin '";
+const ERR_INVALID_RETRY_MID: &str = "': invalid PLUGIN_RETRY: ";
+const ERR_INVALID_TIMEOUT_PREFIX: &str = "Plugin '";
+const ERR_INVALID_TIMEOUT_MID: &str = "': invalid timeout: ";
+const ERR_INVALID_TRIGGER_FILTER_PREFIX: &str = "Plugin '";
+const ERR_INVALID_TRIGGER_FILTER_MID: &str = "': invalid PLUGIN_TRIGGER_FILTER: ";
+const ERR_FAILED_PREPARE_SANDBOX_PREFIX: &str = "Failed to prepare plugin sandbox: ";
 
 const CMD_START: &str = "start";
 const CMD_STOP: &str = "stop";
@@ -61,6 +92,9 @@ const TIMEOUT_START_ACK: Duration = Duration::from_secs(5);
 const TIMEOUT_SOFT_STOP_ACK: Duration = Duration::from_secs(2);
 const TIMEOUT_PAUSE_ACK: Duration = Duration::from_secs(2);
 const TIMEOUT_RESUME_ACK: Duration = Duration::from_secs(2);
+/// Frist nach `stop` und nach SIGTERM, bevor eine Instanz, die ihr Zeitlimit
+/// überschritten hat, härter beendet wird.
+const TIMEOUT_KILL_GRACE: Duration = Duration::from_secs(5);
 
 type InstanceID = u64;
 
@@ -112,8 +146,29 @@ pub fn parse_trigger_public(py_trigger: Option<&str>) -> Result<Trigger, Error>
     parse_trigger(py_trigger)
 }
 
+/// Ob ein Event dieser Art das Plugin auslöst: aktiviert, gültig, passender
+/// Trigger und erfüllte Trigger-Bedingungen.
+fn plugin_fires(plugin: &Plugin, kind: TriggerKind, subject: &TriggerSubject) -> bool {
+    if !(plugin.enabled() && plugin.valid()) {
+        return false;
+    }
+    let kind_matches = matches!(
+        (kind, plugin.trigger()),
+        (TriggerKind::OnEntryCreate, Trigger::OnEntryCreate)
+            | (TriggerKind::OnEntryUpdate, Trigger::OnEntryUpdate)
+            | (TriggerKind::OnEntryDelete, Trigger::OnEntryDelete)
+            | (TriggerKind::OnSchedule, Trigger::OnSchedule(_))
+    );
+    kind_matches
+        && plugin
+            .trigger_filter()
+            .is_none_or(|filter| filter.matches(subject))
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
 pub enum InstanceState {
+    /// Instanz wartet in der Warteschlange auf einen freien Worker.
+    Queued,
     /// Instanz läuft aktiv.
     Running,
     /// Instanz wurde pausiert.
@@ -124,6 +179,10 @@ pub enum InstanceState {
     Completed,
     /// Instanz ist mit Fehler beendet worden.
     Failed,
+    /// Instanz ist gescheitert; ein weiterer Versuch ist eingereiht.
+    Retrying,
+    /// Instanz hat ein Zeitlimit überschritten und wurde beendet.
+    TimedOut,
     /// Instanz reagierte nicht mehr auf Status-/Steueranfragen.
     Unresponsive,
 }
@@ -147,6 +206,9 @@ struct RunnerMsg {
     /// Optionaler Stacktrace.
     #[serde(default)]
     trace: Option<String>,
+    /// Klasse der Exception und ihre Basisklassen, nur bei `exited`.
+    #[serde(default)]
+    error_types: Option<Vec<String>>,
     /// Optionaler Event-Name wie `log`, `progress`, `exited`.
     #[serde(default)]
     event: Option<String>,
@@ -157,11 +219,27 @@ pub struct PluginConfig {
     pub name: String,
     #[serde(default)]
     pub enabled: bool,
+    /// Höchstens so viele Instanzen dieses Plugins laufen gleichzeitig.
+    #[serde(default)]
+    pub max_concurrency: Option<usize>,
+    /// Ressourcengrenzen; fehlende Angaben aus `sandbox.defaults`.
+    #[serde(default)]
+    pub sandbox: SandboxLimits,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct PluginsConfig {
+    /// Gleichzeitig laufende Instanzen aller Plugins; ohne Angabe
+    /// [`queue::DEFAULT_MAX_WORKERS`].
+    #[serde(default)]
+    pub max_workers: Option<usize>,
+    /// Arbeitsverzeichnisse, cgroups und Standardgrenzen der Runner.
+    #[serde(default)]
+    pub sandbox: SandboxSettings,
     pub plugins: Vec<PluginConfig>,
+    /// Verkettete Plugins, siehe [`crate::plugin_manager::pipeline`].
+    #[serde(default)]
+    pub pipelines: Vec<PipelineConfig>,
 }
 
 #[derive(Debug)]
@@ -186,6 +264,15 @@ pub struct PluginHandle {
     pub status_rx: watch::Receiver<InstanceState>,
     /// Watch-Kanal für Fortschrittswerte von 0.0 bis 1.0.
     pub progress_rx: watch::Receiver<f32>,
+    /// Daten, mit denen `plugin.run(data)` gestartet wurde.
+    pub data: String,
+    /// Ergebnis der Instanz, sobald sie einen Endzustand erreicht.
+    pub outcome_rx: watch::Receiver<Option<RunOutcome>>,
+    /// Log-Zeilen der Instanz aus dem Runner und aus stderr.
+    pub logs: InstanceLogs,
+    /// Solange ein Sender lebt, bleibt das Arbeitsverzeichnis nach dem Ende
+    /// des Runners stehen, damit Datei-Artefakte übernommen werden können.
+    pub workdir_hold: Option<mpsc::Sender<()>>,
 }
 
 #[derive(Debug)]
@@ -197,6 +284,16 @@ pub struct PluginManager {
     /// Historie beendeter Instanzen:
     /// instance_id -> (plugin_index, letzter Zustand)
     pub history: HashMap<InstanceID, (usize, InstanceState)>,
+    /// Ziel für Zustands- und Fortschrittsereignisse der Instanzen.
+    events: EventBus,
+    /// Persistiert den Verlauf der Instanzen in `plugin_runs`, falls gesetzt.
+    storage: Option<StorageManager>,
+    /// Wartende Starts; abgearbeitet von [`queue::run_dispatcher`].
+    queue: JobQueue,
+    /// Abschnitt `sandbox` aus `plugins.yaml`.
+    sandbox: SandboxSettings,
+    /// Pipelines aus `plugins.yaml` und ihre Läufe.
+    pipelines: Pipelines,
 }
 
 impl PluginManager {
@@ -206,6 +303,374 @@ impl PluginManager {
             registered: Vec::new(),
             running: HashMap::new(),
             history: HashMap::new(),
+            events: EventBus::new(),
+            storage: None,
+            queue: JobQueue::default(),
+            sandbox: SandboxSettings::default(),
+            pipelines: Pipelines::default(),
+        }
+    }
+
+    /// Veröffentlicht Instanz-Ereignisse auf `events` (üblicherweise dem Bus des
+    /// Storage-Managers) statt auf einem eigenen, unbeobachteten Bus.
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Schreibt Start und Ende jeder Instanz in `plugin_runs`.
+    pub fn with_storage(mut self, storage: StorageManager) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Warteschlange der Starts.
+    pub fn queue(&self) -> &JobQueue {
+        &self.queue
+    }
+
+    /// Reiht den Start einer Instanz ein; gestartet wird sie vom Dispatcher,
+    /// sobald Worker und `max_concurrency` des Plugins es zulassen.
+    pub fn enqueue(
+        &self,
+        plugin_index: usize,
+        instance_id: InstanceID,
+        data: String,
+        priority: JobPriority,
+    ) -> Result<(), Error> {
+        if self.running.contains_key(&instance_id) || self.queue.contains(instance_id) {
+            return Err(Error::CustomError(format!(
+                "{ERR_INSTANCE_ALREADY_RUNNING_PREFIX}{} is already running",
+                instance_id
+            )));
+        }
+        self.push_job(self.new_job(plugin_index, instance_id, data, priority));
+        Ok(())
+    }
+
+    /// Job mit den aktuellen Regeln des Plugins.
+    fn new_job(
+        &self,
+        plugin_index: usize,
+        instance_id: InstanceID,
+        data: String,
+        priority: JobPriority,
+    ) -> QueuedJob {
+        let plugin = &self.registered[plugin_index];
+        QueuedJob::new(
+            instance_id,
+            plugin_index,
+            plugin.name().clone(),
+            plugin.path().clone(),
+            data,
+            priority,
+        )
+        .with_retry(plugin.retry_policy().cloned())
+        .with_limits(plugin.run_limits())
+        .with_sandbox(self.sandbox_policy(plugin_index))
+    }
+
+    /// Instanz-ID, die weder läuft noch wartet.
+    fn next_instance_id(&self) -> InstanceID {
+        let mut instance_id = chrono::Utc::now().timestamp_micros().max(0) as u64;
+        while self.running.contains_key(&instance_id) || self.queue.contains(instance_id) {
+            instance_id += 1;
+        }
+        instance_id
+    }
+
+    /// Reiht den nächsten Versuch eines gescheiterten Jobs ein; er startet
+    /// frühestens nach `delay`.
+    pub fn enqueue_retry(&mut self, failed: &QueuedJob, delay: Duration) {
+        let instance_id = (chrono::Utc::now().timestamp_micros().max(0) as u64)
+            .max(failed.instance_id.saturating_add(1));
+        let job = failed.next_attempt(instance_id, delay);
+        info!(
+            "Retrying instance {} of plugin '{}' as {} (attempt {}) in {:?}",
+            failed.instance_id, job.plugin_name, instance_id, job.attempt, delay
+        );
+        if let Some(step) = &job.pipeline
+            && let Some(run) = self.pipelines.run_mut(step.run_id)
+        {
+            run.assign(&step.step, instance_id);
+        }
+        self.push_job(job);
+    }
+
+    /// Schreibt einen Job als `Queued` in `plugin_runs`, meldet ihn und legt
+    /// ihn in die Warteschlange.
+    fn push_job(&self, job: QueuedJob) {
+        let instance_id = job.instance_id;
+        if let Some(storage) = &self.storage {
+            let run = self.new_plugin_run(&job, InstanceState::Queued);
+            let storage = storage.clone();
+            tokio::spawn(async move {
+                if let Err(e) = storage.insert_plugin_run(run).await {
+                    warn!("failed to record queued plugin instance {instance_id}: {e:?}");
+                }
+            });
+        }
+        self.events.publish(CatalogEvent::PluginState {
+            instance_id,
+            plugin_name: job.plugin_name.clone(),
+            state: InstanceState::Queued,
+        });
+        debug!(
+            "Queued instance {} of plugin '{}' with priority {:?}",
+            instance_id, job.plugin_name, job.priority
+        );
+        self.queue.push(job);
+    }
+
+    /// Ob das Plugin eines wartenden Jobs noch registriert und startbar ist.
+    pub fn job_plugin_available(&self, job: &QueuedJob) -> bool {
+        self.registered.get(job.plugin_index).is_some_and(|p| {
+            p.name() == &job.plugin_name && p.path() == &job.plugin_path && p.enabled() && p.valid()
+        })
+    }
+
+    /// Nimmt eine wartende Instanz aus der Warteschlange; sie endet als `Stopped`.
+    pub fn cancel_queued_instance(&mut self, instance_id: InstanceID) -> Result<(), Error> {
+        let job = self.queue.cancel(instance_id).ok_or_else(|| {
+            StorageError::NotFound(format!("instance {instance_id} is not queued"))
+        })?;
+        self.finish_queued_instance(
+            job,
+            RunOutcome::failed(InstanceState::Stopped, "cancelled before start".to_string()),
+        );
+        Ok(())
+    }
+
+    /// Hält das Ende einer Instanz fest, die nie gestartet wurde
+    /// (abgebrochen oder Start gescheitert).
+    pub fn finish_queued_instance(&mut self, job: QueuedJob, outcome: RunOutcome) {
+        let instance_id = job.instance_id;
+        let state = outcome.state;
+        // Wiederholt wird der Schritt mit dem nächsten Versuch.
+        let step_outcome =
+            (job.pipeline.is_some() && state != InstanceState::Retrying).then(|| outcome.clone());
+        self.record_history(instance_id, job.plugin_index, state);
+        if let Some(storage) = &self.storage {
+            // Zeile erst anlegen, falls das Einreihen noch nicht geschrieben wurde.
+            let run = self.new_plugin_run(&job, InstanceState::Queued);
+            let storage = storage.clone();
+            tokio::spawn(async move {
+                let finished = async {
+                    storage.insert_plugin_run(run).await?;
+                    storage.finish_plugin_run(instance_id as i64, outcome).await
+                };
+                if let Err(e) = finished.await {
+                    warn!("failed to record end of queued plugin instance {instance_id}: {e:?}");
+                }
+            });
+        }
+        self.events.publish(CatalogEvent::PluginState {
+            instance_id,
+            plugin_name: job.plugin_name.clone(),
+            state,
+        });
+        if let Some(outcome) = step_outcome {
+            self.pipeline_step_finished(&job, &outcome);
+        }
+    }
+
+    /// Pipelines aus `plugins.yaml` und ihre Läufe.
+    pub fn pipelines(&self) -> &Pipelines {
+        &self.pipelines
+    }
+
+    /// Lauf mit dem Live-Fortschritt seiner laufenden Schritte.
+    pub fn pipeline_run_view(&self, run_id: u64) -> Option<PipelineRunView> {
+        let run = self.pipelines.run(run_id)?;
+        Some(self.view_run(run))
+    }
+
+    /// Alle Läufe, neueste zuerst.
+    pub fn pipeline_run_views(&self) -> Vec<PipelineRunView> {
+        self.pipelines
+            .runs()
+            .map(|run| self.view_run(run))
+            .collect()
+    }
+
+    fn view_run(&self, run: &PipelineRun) -> PipelineRunView {
+        run.view(|instance_id| {
+            self.running
+                .get(&instance_id)
+                .map(|h| (*h.status_rx.borrow(), *h.progress_rx.borrow()))
+        })
+    }
+
+    /// Startet einen Lauf der Pipeline `name`; Schritte ohne `needs` bekommen
+    /// `data`.
+    pub fn start_pipeline(
+        &mut self,
+        name: &str,
+        data: String,
+        priority: JobPriority,
+    ) -> Result<u64, Error> {
+        let config = self
+            .pipelines
+            .get(name)
+            .ok_or_else(|| StorageError::NotFound(format!("pipeline '{name}' not found")))?;
+        if !config.enabled {
+            return Err(Error::CustomError(format!("Pipeline '{name}' is disabled")));
+        }
+        let run = PipelineRun::new(self.pipelines.next_run_id(), config, data);
+        let run_id = run.id;
+        info!("Starting run {run_id} of pipeline '{name}'");
+        self.pipelines.insert(run);
+        self.advance_pipeline(run_id, priority);
+        Ok(run_id)
+    }
+
+    /// Startet alle Pipelines, deren Trigger und Filter zum Event passen.
+    pub fn start_pipelines_for(
+        &mut self,
+        event: &BackendEvent,
+        subject: &TriggerSubject,
+        data: String,
+    ) -> Vec<u64> {
+        let Some(kind) = event.trigger_kind() else {
+            return Vec::new();
+        };
+        let names: Vec<String> = self
+            .pipelines
+            .configs()
+            .iter()
+            .filter(|p| p.fires(kind, subject))
+            .map(|p| p.name.clone())
+            .collect();
+        names
+            .iter()
+            .filter_map(|name| {
+                match self.start_pipeline(name, data.clone(), JobPriority::Triggered) {
+                    Ok(run_id) => Some(run_id),
+                    Err(e) => {
+                        warn!("failed to start pipeline '{name}': {e:?}");
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Bricht alle laufenden Pipeline-Läufe ab. Eingereihte Schritte bleiben in
+    /// der Warteschlange und werden vom Aufrufer verworfen.
+    pub fn cancel_pipeline_runs(&mut self, reason: &str) {
+        let mut cancelled = Vec::new();
+        for run in self.pipelines.runs_mut().filter(|r| !r.is_finished()) {
+            run.cancel(reason);
+            cancelled.push(run.id);
+        }
+        for run_id in cancelled {
+            self.publish_pipeline_state(run_id);
+        }
+    }
+
+    /// Reiht alle startbaren Schritte eines Laufs ein. Schritte, deren Plugin
+    /// fehlt oder deaktiviert ist, scheitern sofort.
+    fn advance_pipeline(&mut self, run_id: u64, priority: JobPriority) {
+        loop {
+            let Some(run) = self.pipelines.run_mut(run_id) else {
+                return;
+            };
+            let ready = run.take_ready();
+            if ready.is_empty() {
+                break;
+            }
+            for step in ready {
+                match self.enqueue_step(run_id, &step, priority) {
+                    Ok(instance_id) => {
+                        if let Some(run) = self.pipelines.run_mut(run_id) {
+                            run.assign(&step.step, instance_id);
+                        }
+                    }
+                    Err(e) => {
+                        warn!("pipeline run {run_id}: step '{}' failed: {e}", step.step);
+                        self.record_step_end(
+                            run_id,
+                            &step.step,
+                            &RunOutcome::failed(InstanceState::Failed, e),
+                        );
+                    }
+                }
+            }
+        }
+        self.publish_pipeline_state(run_id);
+    }
+
+    fn enqueue_step(
+        &self,
+        run_id: u64,
+        step: &ReadyStep,
+        priority: JobPriority,
+    ) -> Result<InstanceID, String> {
+        let plugin_index = self
+            .registered
+            .iter()
+            .position(|p| p.name() == &step.plugin && p.enabled() && p.valid())
+            .ok_or_else(|| format!("plugin '{}' is not registered or disabled", step.plugin))?;
+        let instance_id = self.next_instance_id();
+        let job = self
+            .new_job(plugin_index, instance_id, step.data.clone(), priority)
+            .with_pipeline(Some(StepRef {
+                run_id,
+                step: step.step.clone(),
+            }));
+        self.push_job(job);
+        Ok(instance_id)
+    }
+
+    /// Hält das Ende des Pipeline-Schritts eines Jobs fest und reiht die
+    /// nächsten Schritte ein.
+    pub fn pipeline_step_finished(&mut self, job: &QueuedJob, outcome: &RunOutcome) {
+        let Some(step) = &job.pipeline else {
+            return;
+        };
+        self.record_step_end(step.run_id, &step.step, outcome);
+        self.advance_pipeline(step.run_id, job.priority);
+    }
+
+    /// Beendet einen Schritt; bei `on_failure: stop` werden die übrigen
+    /// wartenden Schritte aus der Warteschlange genommen.
+    fn record_step_end(&mut self, run_id: u64, step: &str, outcome: &RunOutcome) {
+        let Some(run) = self.pipelines.run_mut(run_id) else {
+            return;
+        };
+        for instance_id in run.finish(step, outcome) {
+            // Bereits gestartete Schritte laufen regulär zu Ende.
+            let _ = self.cancel_queued_instance(instance_id);
+        }
+    }
+
+    fn publish_pipeline_state(&self, run_id: u64) {
+        if let Some(view) = self.pipeline_run_view(run_id) {
+            self.events.publish(CatalogEvent::PipelineState {
+                run_id,
+                pipeline: view.pipeline,
+                status: view.status,
+                progress: view.progress,
+            });
+        }
+    }
+
+    /// Zeile für `plugin_runs` zu einem Job.
+    fn new_plugin_run(&self, job: &QueuedJob, state: InstanceState) -> NewPluginRun {
+        let plugin = self.registered.get(job.plugin_index);
+        let payload = runs::parse_payload(&job.data);
+        let plugin_trigger = plugin.map_or(TRIGGER_MANUAL, |p| trigger_name(p.trigger()));
+        NewPluginRun {
+            instance_id: job.instance_id as i64,
+            plugin_name: job.plugin_name.clone(),
+            plugin_version: plugin.and_then(|p| p.version().cloned()),
+            trigger: runs::run_trigger(&payload, plugin_trigger),
+            entry_path: runs::entry_path(&payload),
+            payload,
+            state: runs::state_name(state),
+            queued_at: Some(job.queued_at),
+            attempt: job.attempt as i32,
+            retry_of: job.retry_of.map(|id| id as i64),
         }
     }
 
@@ -234,16 +699,34 @@ impl PluginManager {
             plugin_path,
             instance_id,
             data,
+            self.registered[plugin_index].run_limits(),
+            self.sandbox_policy(plugin_index),
         )
         .await
     }
 
     /// Phase 1 (kurz, unter Lock): finde alle Plugins, die zu diesem Event passen
     /// und gib die Start-Pläne zurück (plugin_index, plugin_path, instance_id).
+    ///
+    /// Bekannt ist hier nur der Pfad; Plugins, deren `PLUGIN_TRIGGER_FILTER`
+    /// Topics, Metadaten oder eine Größe verlangt, werden übersprungen. Mit
+    /// dem Eintrag dazu [`Self::prepare_fire_event_for`] verwenden.
     #[instrument]
     pub fn prepare_fire_event(
         &self,
         event: &BackendEvent,
+    ) -> Result<Vec<(usize, PathBuf, InstanceID)>, Error> {
+        let subject = TriggerSubject::from_path(event.entry_path().unwrap_or_default());
+        self.prepare_fire_event_for(event, &subject)
+    }
+
+    /// Wie [`Self::prepare_fire_event`], prüft die Trigger-Bedingungen aber
+    /// gegen den Eintrag samt Topics.
+    #[instrument(skip(self))]
+    pub fn prepare_fire_event_for(
+        &self,
+        event: &BackendEvent,
+        subject: &TriggerSubject,
     ) -> Result<Vec<(usize, PathBuf, InstanceID)>, Error> {
         let Some(kind) = event.trigger_kind() else {
             return Ok(Vec::new());
@@ -253,18 +736,7 @@ impl PluginManager {
             .registered
             .iter()
             .enumerate()
-            .filter(|(_i, p)| {
-                if !(p.enabled() && p.valid()) {
-                    return false;
-                }
-                match (&kind, p.trigger()) {
-                    (TriggerKind::OnEntryCreate, Trigger::OnEntryCreate) => true,
-                    (TriggerKind::OnEntryUpdate, Trigger::OnEntryUpdate) => true,
-                    (TriggerKind::OnEntryDelete, Trigger::OnEntryDelete) => true,
-                    (TriggerKind::OnSchedule, Trigger::OnSchedule(_)) => true,
-                    _ => false,
-                }
-            })
+            .filter(|(_i, p)| plugin_fires(p, kind, subject))
             .map(|(i, _)| i)
             .collect();
 
@@ -297,6 +769,60 @@ impl PluginManager {
         let config: PluginsConfig = serde_yaml::from_str(&content)
             .map_err(|e| Error::CustomError(format!("{ERR_FAILED_PARSE_CONFIG_PREFIX}{e}")))?;
 
+        let max_workers = config.max_workers.unwrap_or(queue::DEFAULT_MAX_WORKERS);
+        if max_workers == 0 {
+            return Err(Error::CustomError(format!(
+                "{ERR_FAILED_PARSE_CONFIG_PREFIX}max_workers must be at least 1"
+            )));
+        }
+        config.sandbox.defaults.validate().map_err(|e| {
+            Error::CustomError(format!(
+                "{ERR_FAILED_PARSE_CONFIG_PREFIX}sandbox.defaults: {e}"
+            ))
+        })?;
+        let mut pipeline_names = HashSet::new();
+        for pipeline in &config.pipelines {
+            let unique = pipeline_names.insert(pipeline.name.as_str());
+            pipeline
+                .validate()
+                .and_then(|()| {
+                    unique
+                        .then_some(())
+                        .ok_or_else(|| "duplicate pipeline name".to_string())
+                })
+                .map_err(|e| {
+                    Error::CustomError(format!(
+                        "{ERR_FAILED_PARSE_CONFIG_PREFIX}pipeline '{}': {e}",
+                        pipeline.name
+                    ))
+                })?;
+        }
+        let mut limits = HashMap::new();
+        for plugin_cfg in &config.plugins {
+            plugin_cfg.sandbox.validate().map_err(|e| {
+                Error::CustomError(format!(
+                    "{ERR_FAILED_PARSE_CONFIG_PREFIX}sandbox of plugin '{}': {e}",
+                    plugin_cfg.name
+                ))
+            })?;
+            match plugin_cfg.max_concurrency {
+                Some(0) => {
+                    return Err(Error::CustomError(format!(
+                        "{ERR_FAILED_PARSE_CONFIG_PREFIX}max_concurrency of plugin '{}' must be at least 1",
+                        plugin_cfg.name
+                    )));
+                }
+                Some(limit) => {
+                    limits.insert(plugin_cfg.name.clone(), limit);
+                }
+                None => {}
+            }
+        }
+        self.queue.configure(max_workers, limits);
+        for plugin in &mut self.registered {
+            plugin.set_sandbox_limits(config.sandbox.defaults.clone());
+        }
+
         // CHANGED: apply enabled flag only if plugin exists; otherwise warn and continue
         for plugin_cfg in config.plugins {
             match self
@@ -306,6 +832,9 @@ impl PluginManager {
             {
                 Some(plugin) => {
                     plugin.set_enabled(plugin_cfg.enabled);
+                    plugin.set_sandbox_limits(
+                        plugin_cfg.sandbox.with_defaults(&config.sandbox.defaults),
+                    );
                 }
                 None => {
                     warn!(
@@ -316,9 +845,55 @@ impl PluginManager {
             }
         }
 
+        let pipelines = config
+            .pipelines
+            .into_iter()
+            .filter(|pipeline| self.pipeline_plugins_registered(pipeline))
+            .collect();
+        self.pipelines.configure(pipelines);
+
+        self.sandbox = config.sandbox;
+
         Ok(())
     }
 
+    /// Ob alle Plugins einer Pipeline registriert sind. Warnt, wenn ein Schritt
+    /// über seinen eigenen Trigger zusätzlich auf dasselbe Event startet.
+    fn pipeline_plugins_registered(&self, pipeline: &PipelineConfig) -> bool {
+        for step in &pipeline.steps {
+            let Some(plugin) = self.registered.iter().find(|p| p.name() == &step.plugin) else {
+                warn!(
+                    "Pipeline '{}' references plugin '{}' but it is not registered; skipping",
+                    pipeline.name, step.plugin
+                );
+                return false;
+            };
+            let own_trigger = matches!(
+                (pipeline.trigger, plugin.trigger()),
+                (PipelineTrigger::OnEntryCreate, Trigger::OnEntryCreate)
+                    | (PipelineTrigger::OnEntryUpdate, Trigger::OnEntryUpdate)
+                    | (PipelineTrigger::OnEntryDelete, Trigger::OnEntryDelete)
+            );
+            if pipeline.enabled && plugin.enabled() && own_trigger {
+                warn!(
+                    "Plugin '{}' of pipeline '{}' also runs on its own trigger",
+                    step.plugin, pipeline.name
+                );
+            }
+        }
+        true
+    }
+
+    /// Abschottung, mit der eine Instanz des Plugins startet.
+    fn sandbox_policy(&self, plugin_index: usize) -> SandboxPolicy {
+        let plugin = &self.registered[plugin_index];
+        SandboxPolicy::new(
+            &self.sandbox,
+            plugin.sandbox_limits().clone(),
+            plugin.write_access(),
+        )
+    }
+
     /// Liefert einen Handle auf eine laufende Instanz.
 
     #[instrument]
@@ -404,6 +979,25 @@ impl PluginManager {
         &mut self,
         instance_id: InstanceID,
         handle: PluginHandle,
+    ) -> Result<(), Error> {
+        self.commit_instance(instance_id, handle, None)
+    }
+
+    /// Wie [`PluginManager::commit_started_instance`] für einen Job aus der
+    /// Warteschlange; der Lauf übernimmt Versuch und Wiederholungsregeln.
+    pub fn commit_queued_job(
+        &mut self,
+        job: &QueuedJob,
+        handle: PluginHandle,
+    ) -> Result<(), Error> {
+        self.commit_instance(job.instance_id, handle, Some(job))
+    }
+
+    fn commit_instance(
+        &mut self,
+        instance_id: InstanceID,
+        mut handle: PluginHandle,
+        job: Option<&QueuedJob>,
     ) -> Result<(), Error> {
         if self.running.contains_key(&instance_id) {
             return Err(Error::CustomError(format!(
@@ -411,6 +1005,57 @@ impl PluginManager {
                 instance_id
             )));
         }
+        let plugin = self.registered.get(handle.plugin_index);
+        let plugin_name = plugin
+            .map(|p| p.name().clone())
+            .unwrap_or_else(|| "unknown".to_string());
+        if let Some(storage) = &self.storage {
+            let payload = runs::parse_payload(&handle.data);
+            let plugin_trigger = plugin.map_or(TRIGGER_MANUAL, |p| trigger_name(p.trigger()));
+            let run = NewPluginRun {
+                instance_id: instance_id as i64,
+                plugin_name: plugin_name.clone(),
+                plugin_version: plugin.and_then(|p| p.version().cloned()),
+                trigger: runs::run_trigger(&payload, plugin_trigger),
+                entry_path: runs::entry_path(&payload),
+                payload,
+                state: runs::state_name(InstanceState::Running),
+                queued_at: job.map(|j| j.queued_at),
+                attempt: job.map_or(1, |j| j.attempt as i32),
+                retry_of: job.and_then(|j| j.retry_of).map(|id| id as i64),
+            };
+            // Dateien als Artefakt nur aus dem Arbeitsverzeichnis, /exports und /data
+            let workdir = plugin
+                .map(|_| self.sandbox_policy(handle.plugin_index).workdir(instance_id));
+            let files = runs::ArtifactFiles {
+                roots: workdir
+                    .iter()
+                    .cloned()
+                    .chain([storage.export_dir().clone(), PathBuf::from(DATA_DIR)])
+                    .collect(),
+                workdir,
+                workdir_hold: handle.workdir_hold.take(),
+            };
+            tokio::spawn(runs::record_instance_run(
+                storage.clone(),
+                run,
+                handle.status_rx.clone(),
+                handle.outcome_rx.clone(),
+                job.and_then(|j| j.retry.clone()),
+                files,
+            ));
+            if let Some(rx) = handle.logs.persist() {
+                tokio::spawn(logs::persist_instance_logs(storage.clone(), rx));
+            }
+        }
+        tokio::spawn(forward_instance_events(
+            self.events.clone(),
+            instance_id,
+            plugin_name,
+            handle.status_rx.clone(),
+            handle.progress_rx.clone(),
+        ));
+        handle.workdir_hold = None;
         self.running.insert(instance_id, handle);
         debug!("Committed started instance {}", instance_id);
         Ok(())
@@ -439,7 +1084,16 @@ impl PluginManager {
         instance_id: InstanceID,
     ) -> Result<PluginHandle, Error> {
         let plugin_name = self.registered[plugin_index].name().clone();
-        build_started_instance_core(plugin_index, plugin_name, plugin_path, instance_id).await
+        let limits = self.registered[plugin_index].run_limits();
+        build_started_instance_core(
+            plugin_index,
+            plugin_name,
+            plugin_path,
+            instance_id,
+            limits,
+            self.sandbox_policy(plugin_index),
+        )
+        .await
     }
 
     #[instrument]
@@ -473,7 +1127,8 @@ impl PluginManager {
                 Err(Error::CustomError(ref s)) if s.contains("already registered") => {
                     debug!("Plugin {:?} already registered, skipping", path);
                 }
-                Err(e) => return Err(e),
+                // ein kaputtes Plugin darf die übrigen nicht verhindern
+                Err(e) => warn!("Skipping plugin {:?}: {:?}", path, e),
             }
         }
         Ok(())
@@ -510,23 +1165,94 @@ impl PluginManager {
 
         let fallback_description = format!("Plugin loaded from {:?}", canonical_path);
 
-        // Auslesen aus Python-Modul
-        let (py_name, py_description, py_trigger) =
-            python_bridge::read_module_constants(canonical_path.as_path())
-                .unwrap_or((None, None, None));
-
-        let name = py_name.unwrap_or(fallback_name);
-        let description = py_description.unwrap_or(fallback_description);
+        // Fehlerhafte Konstanten verhindern nur den Start dieses Plugins: es wird
+        // als ungültig registriert, die Gründe stehen bei den Warnungen.
+        let mut problems = Vec::new();
 
-        let trigger = parse_trigger(py_trigger.as_deref())?;
+        // Auslesen aus Python-Modul
+        let constants = python_bridge::read_module_constants(canonical_path.as_path())
+            .unwrap_or_else(|e| {
+                problems.push(match e {
+                    Error::CustomError(msg) => msg,
+                    other => format!("{other:?}"),
+                });
+                python_bridge::ModuleConstants::default()
+            });
+
+        let name = constants.name.unwrap_or(fallback_name);
+        let description = constants.description.unwrap_or(fallback_description);
+
+        let trigger = parse_trigger(constants.trigger.as_deref())?;
+
+        let parameters = constants
+            .parameters
+            .map(ParameterSchema::from_declaration)
+            .transpose()
+            .unwrap_or_else(|e| {
+                problems.push(format!(
+                    "{ERR_INVALID_PARAMETERS_PREFIX}{name}{ERR_INVALID_PARAMETERS_MID}{e}"
+                ));
+                None
+            });
+
+        let retry = constants
+            .retry
+            .map(RetryPolicy::from_declaration)
+            .transpose()
+            .unwrap_or_else(|e| {
+                problems.push(format!(
+                    "{ERR_INVALID_RETRY_PREFIX}{name}{ERR_INVALID_RETRY_MID}{e}"
+                ));
+                None
+            });
+
+        let run_limits = RunLimits::from_seconds(constants.timeout, constants.stall_timeout)
+            .unwrap_or_else(|e| {
+                problems.push(format!(
+                    "{ERR_INVALID_TIMEOUT_PREFIX}{name}{ERR_INVALID_TIMEOUT_MID}{e}"
+                ));
+                RunLimits::default()
+            });
+
+        let trigger_filter = constants
+            .trigger_filter
+            .map(|raw| {
+                if !matches!(
+                    trigger,
+                    Trigger::OnEntryCreate | Trigger::OnEntryUpdate | Trigger::OnEntryDelete
+                ) {
+                    return Err(format!(
+                        "only on_entry_* triggers can be filtered, not {}",
+                        trigger.to_string()
+                    ));
+                }
+                TriggerFilter::from_declaration(raw)
+            })
+            .transpose()
+            .unwrap_or_else(|e| {
+                problems.push(format!(
+                    "{ERR_INVALID_TRIGGER_FILTER_PREFIX}{name}{ERR_INVALID_TRIGGER_FILTER_MID}{e}"
+                ));
+                None
+            });
 
         let mut plugin = Plugin::new(name, description, trigger, canonical_path);
+        for problem in &problems {
+            warn!("{problem}; plugin is registered as invalid");
+        }
         debug!(
-            "Plugin '{}' validated and prepared for registration",
-            plugin.name()
+            "Plugin '{}' validated and prepared for registration (valid={})",
+            plugin.name(),
+            problems.is_empty()
         );
-        plugin.set_valid(true);
-        plugin.set_validation_warnings(warnings);
+        plugin.set_valid(problems.is_empty());
+        plugin.set_validation_warnings(warnings.into_iter().chain(problems).collect());
+        plugin.set_version(constants.version);
+        plugin.set_parameters(parameters);
+        plugin.set_retry_policy(retry);
+        plugin.set_trigger_filter(trigger_filter);
+        plugin.set_run_limits(run_limits);
+        plugin.set_write_access(constants.write_access.unwrap_or(false));
 
         self.registered.push(plugin);
         Ok(())
@@ -550,13 +1276,15 @@ impl PluginManager {
             )));
         }
 
-        let (plugin_index, path) = self.prepare_start(plugin_name)?;
-        let handle = self
-            .build_started_instance(plugin_index, &path, instance_id)
-            .await?;
-        self.running.insert(instance_id, handle);
+        let (plugin_index, _path) = self.prepare_start(plugin_name)?;
+        self.enqueue(
+            plugin_index,
+            instance_id,
+            String::new(),
+            JobPriority::Manual,
+        )?;
         debug!(
-            "Started instance {} for plugin '{}'",
+            "Queued instance {} for plugin '{}'",
             instance_id, plugin_name
         );
         Ok(())
@@ -702,7 +1430,10 @@ impl PluginManager {
             // If the actor already reports a final state, move to history.
             if matches!(
                 state,
-                InstanceState::Completed | InstanceState::Failed | InstanceState::Stopped
+                InstanceState::Completed
+                    | InstanceState::Failed
+                    | InstanceState::TimedOut
+                    | InstanceState::Stopped
             ) {
                 if let Ok(h) = self.take_instance_handle(instance_id) {
                     self.record_history(instance_id, h.plugin_index, state);
@@ -733,6 +1464,31 @@ impl PluginManager {
                 // Ensure the instance is removed and recorded as Unresponsive
                 if let Ok(h) = self.take_instance_handle(instance_id) {
                     self.record_history(instance_id, h.plugin_index, InstanceState::Unresponsive);
+                    if let Some(storage) = self.storage.clone() {
+                        tokio::spawn(async move {
+                            let outcome = RunOutcome::failed(
+                                InstanceState::Unresponsive,
+                                "instance did not answer liveness check".to_string(),
+                            );
+                            if let Err(e) =
+                                storage.finish_plugin_run(instance_id as i64, outcome).await
+                            {
+                                warn!(
+                                    "failed to record unresponsive instance {instance_id}: {e:?}"
+                                );
+                            }
+                        });
+                    }
+                    // läuft nicht über den Watch-Kanal der Instanz
+                    self.events.publish(CatalogEvent::PluginState {
+                        instance_id,
+                        plugin_name: self
+                            .registered
+                            .get(h.plugin_index)
+                            .map(|p| p.name().clone())
+                            .unwrap_or_else(|| "unknown".to_string()),
+                        state: InstanceState::Unresponsive,
+                    });
                     info!(
                         "Marked instance {} as Unresponsive and recorded history",
                         instance_id
@@ -782,81 +1538,37 @@ impl PluginManager {
         plugin_manager: Arc<tokio::sync::Mutex<PluginManager>>,
         event: BackendEvent,
     ) -> Result<Vec<u64>, Error> {
-        // Phase 1:
-        // Unter Lock nur schnell vorbereiten:
-        // - passende Plugins bestimmen
-        // - Instanz-IDs planen
-        // - Payload für jedes Plugin erzeugen
-        let plans: Vec<(usize, String, PathBuf, u64, String)> = {
-            let pm = plugin_manager.lock().await;
-
-            let raw_plans = pm.prepare_fire_event(&event)?;
-
-            let event_name = match &event {
-                BackendEvent::EntryCreated { .. } => "created",
-                BackendEvent::EntryUpdated { .. } => "updated",
-                BackendEvent::EntryDeleted { .. } => "deleted",
-                BackendEvent::OnSchedule { .. } => "schedule",
-                BackendEvent::Manual { .. } => "manual",
-            }
-            .to_string();
+        // Passende Plugins bestimmen und je Plugin einen Job mit der Payload
+        // für das Python-Plugin einreihen. Gestartet wird im Dispatcher.
+        let pm = plugin_manager.lock().await;
 
-            let event_path = match &event {
-                BackendEvent::EntryCreated { path }
-                | BackendEvent::EntryUpdated { path }
-                | BackendEvent::EntryDeleted { path } => path.clone(),
-                BackendEvent::OnSchedule { path, .. } => path.clone(),
-                BackendEvent::Manual { plugin_name } => plugin_name.clone(),
-            };
+        let raw_plans = pm.prepare_fire_event(&event)?;
 
-            raw_plans
-                .into_iter()
-                .map(|(plugin_index, plugin_path, instance_id)| {
-                    let plugin_name = pm
-                        .registered
-                        .get(plugin_index)
-                        .map(|p| p.name().clone())
-                        .unwrap_or_else(|| "unknown".to_string());
-
-                    // Payload, die an das Python-Plugin weitergereicht wird.
-                    let data = serde_json::json!({
-                        "event": event_name,
-                        "path": event_path,
-                        "plugin_path": plugin_path.to_string_lossy(),
-                    })
-                    .to_string();
-
-                    (plugin_index, plugin_name, plugin_path, instance_id, data)
-                })
-                .collect()
+        let event_name = event.event_name();
+
+        let event_path = match &event {
+            BackendEvent::EntryCreated { path }
+            | BackendEvent::EntryUpdated { path }
+            | BackendEvent::EntryDeleted { path } => path.clone(),
+            BackendEvent::OnSchedule { path, .. } => path.clone(),
+            BackendEvent::Manual { plugin_name } => plugin_name.clone(),
         };
 
-        // Phase 2:
-        // Instanzen bauen, OHNE den globalen PluginManager-Lock zu halten.
-        // Das ist wichtig, weil Prozessstarts und IPC-Aufbau länger dauern können.
-        let mut built: Vec<(u64, PluginHandle)> = Vec::new();
-        for (plugin_index, plugin_name, plugin_path, instance_id, data) in plans {
-            let handle = build_started_instance_core_with_data(
-                plugin_index,
-                plugin_name,
-                &plugin_path,
-                instance_id,
-                data,
-            )
-            .await?;
-            built.push((instance_id, handle));
-        }
+        let mut queued = Vec::new();
+        for (plugin_index, plugin_path, instance_id) in raw_plans {
+            // Payload, die an das Python-Plugin weitergereicht wird.
+            let data = serde_json::json!({
+                "event": event_name,
+                "path": event_path,
+                "plugin_path": plugin_path.to_string_lossy(),
+            })
+            .to_string();
 
-        // Phase 3:
-        // Fertige Handles wieder unter Lock in `running` eintragen.
-        let mut started = Vec::new();
-        let mut pm = plugin_manager.lock().await;
-        for (instance_id, handle) in built {
-            pm.commit_started_instance(instance_id, handle)?;
-            started.push(instance_id);
+            pm.enqueue(plugin_index, instance_id, data, JobPriority::Triggered)?;
+            queued.push(instance_id);
         }
 
-        Ok(started)
+        Ok(queued)
     }
 
     pub async fn fire_event(&mut self, event: BackendEvent) -> Result<Vec<u64>, Error> {
@@ -865,18 +1577,12 @@ impl PluginManager {
         };
 
         // passende Plugins sammeln (Indices), damit wir nicht gleichzeitig mut/immut borrow-chaos bekommen
+        let subject = TriggerSubject::from_path(event.entry_path().unwrap_or_default());
         let plugin_indices: Vec<usize> = self
             .registered
             .iter()
             .enumerate()
-            .filter(|(_i, p)| p.enabled() && p.valid())
-            .filter(|(_i, p)| match (kind, p.trigger()) {
-                (TriggerKind::OnEntryCreate, Trigger::OnEntryCreate) => true,
-                (TriggerKind::OnEntryUpdate, Trigger::OnEntryUpdate) => true,
-                (TriggerKind::OnEntryDelete, Trigger::OnEntryDelete) => true,
-                (TriggerKind::OnSchedule, Trigger::OnSchedule(_)) => true,
-                _ => false,
-            })
+            .filter(|(_i, p)| plugin_fires(p, kind, &subject))
             .map(|(i, _)| i)
             .collect();
 
@@ -901,15 +1607,7 @@ impl PluginManager {
                 String::new()
             };
 
-            let handle = if kind == TriggerKind::OnSchedule {
-                self.build_started_instance_with_data(plugin_index, &plugin_path, instance_id, data)
-                    .await?
-            } else {
-                self.build_started_instance(plugin_index, &plugin_path, instance_id)
-                    .await?
-            };
-
-            self.commit_started_instance(instance_id, handle)?;
+            self.enqueue(plugin_index, instance_id, data, JobPriority::Triggered)?;
             started.push(instance_id);
         }
 
@@ -956,7 +1654,17 @@ async fn send_runner_cmd(
 
     Ok(())
 }
-#[instrument(skip(child, child_stdin, stdout_rx, command_rx, status_tx, progress_tx))]
+#[allow(clippy::too_many_arguments)]
+#[instrument(skip(
+    child,
+    child_stdin,
+    stdout_rx,
+    command_rx,
+    status_tx,
+    progress_tx,
+    outcome_tx,
+    logs
+))]
 async fn run_instance_actor(
     instance_id: InstanceID,
     plugin_name: String,
@@ -966,6 +1674,9 @@ async fn run_instance_actor(
     mut command_rx: mpsc::Receiver<PluginCommand>,
     status_tx: watch::Sender<InstanceState>,
     progress_tx: watch::Sender<f32>,
+    outcome_tx: watch::Sender<Option<RunOutcome>>,
+    logs: InstanceLogs,
+    limits: RunLimits,
 ) {
     // PendingReply speichert offene Requests, auf deren ACK wir noch warten.
     // Dadurch kann eine spätere Antwort aus Python korrekt dem ursprünglichen
@@ -978,7 +1689,13 @@ async fn run_instance_actor(
     let mut pending_acks: HashMap<String, PendingReply> = HashMap::new();
     let mut next_request_seq = 1u64;
 
+    // Der Runner führt eine eigene Prozessgruppe an (siehe `spawn_runner_core_with_data`).
+    let process_group = child.id();
+    let mut watchdog = Watchdog::new(limits, tokio::time::Instant::now());
+    let mut timed_out: Option<TimeoutKind> = None;
+
     loop {
+        let deadline = watchdog.deadline();
         tokio::select! {
             // Zweig 1:
             // Befehle aus Rust entgegennehmen und an den Python-Runner weiterleiten.
@@ -1040,6 +1757,7 @@ async fn run_instance_actor(
                         if let Some(ev) = &msg.event {
                             // Fortschritts-Events aktualisieren den watch-Kanal.
                             if ev == "progress" {
+                                watchdog.progress(tokio::time::Instant::now());
                                 if let Some(val) = &msg.result {
                                     if let Some(p) = val.get("progress").and_then(|v| v.as_f64()) {
                                         let clamped = p.clamp(0.0, 1.0) as f32;
@@ -1052,18 +1770,7 @@ async fn run_instance_actor(
                             // Log-Events aus Python in Rust-Logs übersetzen.
                             if ev == "log" {
                                 if let Some(val) = &msg.result {
-                                    if let Some(level) = val.get("level").and_then(|v| v.as_str()) {
-                                        let message = val.get("msg").and_then(|v| v.as_str()).unwrap_or_default();
-                                        let logger_name = format!("plugin.{}.{}", instance_id, level.to_lowercase());
-
-                                        match level {
-                                            "DEBUG" => debug!("{} {}", logger_name, message),
-                                            "INFO" => info!("{} {}", logger_name, message),
-                                            "WARN" | "WARNING" => warn!("{} {}", logger_name, message),
-                                            "ERROR" | "CRITICAL" => error!("{} {}", logger_name, message),
-                                            _ => debug!("{} {}", logger_name, message),
-                                        }
-                                    }
+                                    forward_log_event(instance_id, val, &logs);
                                 }
                                 continue;
                             }
@@ -1081,6 +1788,7 @@ async fn run_instance_actor(
 
                                 status_tx.send(final_state).ok();
                                 let _ = progress_tx.send(1.0);
+                                runs::record_outcome(&outcome_tx, exited_outcome(final_state, &msg));
 
                                 if final_state == InstanceState::Failed {
                                     if let Some(err) = &msg.error {
@@ -1103,9 +1811,18 @@ async fn run_instance_actor(
                                         if msg.ok.unwrap_or(false) {
                                             // Erfolgreiche Steuerkommandos aktualisieren den Status lokal.
                                             match cmd.as_str() {
-                                                CMD_PAUSE => { status_tx.send(InstanceState::Paused).ok(); }
-                                                CMD_RESUME => { status_tx.send(InstanceState::Running).ok(); }
-                                                CMD_STOP => { status_tx.send(InstanceState::Stopped).ok(); }
+                                                CMD_PAUSE => {
+                                                    watchdog.pause(tokio::time::Instant::now());
+                                                    status_tx.send(InstanceState::Paused).ok();
+                                                }
+                                                CMD_RESUME => {
+                                                    watchdog.resume(tokio::time::Instant::now());
+                                                    status_tx.send(InstanceState::Running).ok();
+                                                }
+                                                CMD_STOP => {
+                                                    status_tx.send(InstanceState::Stopped).ok();
+                                                    runs::record_outcome(&outcome_tx, RunOutcome::new(InstanceState::Stopped));
+                                                }
                                                 _ => {}
                                             }
                                             let _ = reply.send(Ok(()));
@@ -1138,7 +1855,7 @@ async fn run_instance_actor(
             // Zweig 3:
             // Child-Prozess selbst ist beendet.
             exit_status = child.wait() => {
-                let s = match exit_status {
+                let s = match &exit_status {
                     Ok(s) if s.success() => InstanceState::Completed,
                     _ => InstanceState::Failed,
                 };
@@ -1172,24 +1889,20 @@ async fn run_instance_actor(
 
                                 if ev == "log" {
                                     if let Some(val) = &msg.result {
-                                        if let Some(level) = val.get("level").and_then(|v| v.as_str()) {
-                                            let message = val.get("msg").and_then(|v| v.as_str()).unwrap_or_default();
-                                            let logger_name = format!("plugin.{}.{}", instance_id, level.to_lowercase());
-
-                                            match level {
-                                                "DEBUG" => debug!("{} {}", logger_name, message),
-                                                "INFO" => info!("{} {}", logger_name, message),
-                                                "WARN" | "WARNING" => warn!("{} {}", logger_name, message),
-                                                "ERROR" | "CRITICAL" => error!("{} {}", logger_name, message),
-                                                _ => debug!("{} {}", logger_name, message),
-                                            }
-                                        }
+                                        forward_log_event(instance_id, val, &logs);
                                     }
                                     continue;
                                 }
 
                                 if ev == "exited" {
-                                    // Bereits über den Prozess-Exit behandelt.
+                                    // Zustand bereits über den Prozess-Exit gesetzt;
+                                    // Ergebnis und Fehler stehen nur in der Nachricht.
+                                    let state = if msg.ok.unwrap_or(false) {
+                                        InstanceState::Completed
+                                    } else {
+                                        InstanceState::Failed
+                                    };
+                                    runs::record_outcome(&outcome_tx, exited_outcome(state, &msg));
                                     continue;
                                 }
                             }
@@ -1202,7 +1915,10 @@ async fn run_instance_actor(
                                                 match cmd.as_str() {
                                                     CMD_PAUSE => { status_tx.send(InstanceState::Paused).ok(); }
                                                     CMD_RESUME => { status_tx.send(InstanceState::Running).ok(); }
-                                                    CMD_STOP => { status_tx.send(InstanceState::Stopped).ok(); }
+                                                    CMD_STOP => {
+                                                        status_tx.send(InstanceState::Stopped).ok();
+                                                        runs::record_outcome(&outcome_tx, RunOutcome::new(InstanceState::Stopped));
+                                                    }
                                                     _ => {}
                                                 }
                                                 let _ = reply.send(Ok(()));
@@ -1232,14 +1948,143 @@ async fn run_instance_actor(
                     }
                 }
 
+                // Ohne `exited`-Nachricht bleibt nur der Exit-Status des Prozesses.
+                let fallback = match exit_status {
+                    Ok(status) if status.success() => RunOutcome::new(s),
+                    Ok(status) => RunOutcome::failed(s, format!("python runner exited with {status}")),
+                    Err(e) => RunOutcome::failed(s, format!("failed to wait for python runner: {e}")),
+                };
+                runs::record_outcome(&outcome_tx, fallback);
+
+                break;
+            }
+
+            // Zweig 4:
+            // Zeitlimit aus `PLUGIN_TIMEOUT` oder `PLUGIN_STALL_TIMEOUT` abgelaufen.
+            kind = wait_for_deadline(deadline) => {
+                timed_out = Some(kind);
                 break;
             }
         }
     }
 
+    if let Some(kind) = timed_out {
+        let message = kind.message(watchdog.limits());
+        warn!(
+            "plugin instance {} ('{}') timed out: {}",
+            instance_id, plugin_name, message
+        );
+        // Endzustand vor dem Beenden festhalten, damit `stop` und der Exit ihn
+        // nicht überschreiben.
+        status_tx.send(InstanceState::TimedOut).ok();
+        runs::record_outcome(
+            &outcome_tx,
+            RunOutcome::failed(InstanceState::TimedOut, message),
+        );
+        terminate_runaway(instance_id, &mut child, child_stdin, process_group).await;
+    }
+
     // Sicherheitsnetz:
     // Wenn noch ein Child-Prozess existiert, wird er am Ende beendet.
     let _ = child.kill().await;
+    // Rückrufe mit dem Token der Instanz sind ab jetzt nicht mehr erlaubt.
+    auth::revoke_runner_token(instance_id);
+    logs.finish();
+}
+
+/// Wartet bis zum nächsten Zeitlimit; ohne Limit nie.
+async fn wait_for_deadline(deadline: Option<(tokio::time::Instant, TimeoutKind)>) -> TimeoutKind {
+    match deadline {
+        Some((at, kind)) => {
+            tokio::time::sleep_until(at).await;
+            kind
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Beendet eine Instanz nach überschrittenem Zeitlimit: erst weich über `stop`
+/// und geschlossenes stdin, dann die ganze Prozessgruppe mit SIGTERM und
+/// zuletzt mit SIGKILL. So enden auch Kommandos, die `run()` gestartet hat.
+async fn terminate_runaway(
+    instance_id: InstanceID,
+    child: &mut Child,
+    mut child_stdin: ChildStdin,
+    process_group: Option<u32>,
+) {
+    let request_id = format!("{instance_id}-timeout");
+    let _ = send_runner_cmd(instance_id, &mut child_stdin, CMD_STOP, &request_id).await;
+    drop(child_stdin);
+
+    let mut exited = timeout(TIMEOUT_KILL_GRACE, child.wait()).await.is_ok();
+    if !exited {
+        warn!("instance {instance_id} ignored stop after timeout, sending SIGTERM");
+        signal_process_group(process_group, libc::SIGTERM);
+        exited = timeout(TIMEOUT_KILL_GRACE, child.wait()).await.is_ok();
+    }
+    if !exited {
+        warn!("instance {instance_id} survived SIGTERM, sending SIGKILL");
+    }
+    // Auch nach einem Exit des Runners können Kindprozesse übrig sein.
+    signal_process_group(process_group, libc::SIGKILL);
+    let _ = child.wait().await;
+}
+
+/// Schickt `signal` an die Prozessgruppe des Runners.
+fn signal_process_group(process_group: Option<u32>, signal: libc::c_int) {
+    let Some(pgid) = process_group.and_then(|id| libc::pid_t::try_from(id).ok()) else {
+        return;
+    };
+    // SAFETY: killpg hat keine Speicher-Vorbedingungen; eine bereits leere
+    // Gruppe (ESRCH) ist hier kein Fehler.
+    unsafe {
+        libc::killpg(pgid, signal);
+    }
+}
+
+/// Übernimmt ein `log`-Event in die Logs der Instanz und ins Backend-Log.
+/// stderr-Zeilen stehen dort schon über den stderr-Reader.
+fn forward_log_event(instance_id: InstanceID, val: &serde_json::Value, logs: &InstanceLogs) {
+    let Some(level) = val.get("level").and_then(|v| v.as_str()) else {
+        return;
+    };
+    let message = val.get("msg").and_then(|v| v.as_str()).unwrap_or_default();
+    let logger = val.get("logger").and_then(|v| v.as_str()).unwrap_or("root");
+    logs.push(level, logger, message);
+    if logger == logs::STDERR_LOGGER {
+        return;
+    }
+
+    let logger_name = format!("plugin.{}.{}", instance_id, level.to_lowercase());
+    match level {
+        "DEBUG" => debug!("{} {}", logger_name, message),
+        "INFO" => info!("{} {}", logger_name, message),
+        "WARN" | "WARNING" => warn!("{} {}", logger_name, message),
+        "ERROR" | "CRITICAL" => error!("{} {}", logger_name, message),
+        _ => debug!("{} {}", logger_name, message),
+    }
+}
+
+/// Ergebnis aus der `exited`-Nachricht des Runners.
+fn exited_outcome(state: InstanceState, msg: &RunnerMsg) -> RunOutcome {
+    RunOutcome {
+        state,
+        result: msg.result.clone(),
+        error: msg.error.clone(),
+        trace: msg.trace.clone(),
+        error_classes: msg.error_types.clone().unwrap_or_default(),
+    }
+}
+
+/// Name eines Triggers, wie er in `PLUGIN_TRIGGER` steht (ohne Cron-Ausdruck).
+fn trigger_name(trigger: &Trigger) -> &'static str {
+    match trigger {
+        Trigger::OnEntryCreate => TRIGGER_ON_ENTRY_CREATE,
+        Trigger::OnEntryUpdate => TRIGGER_ON_ENTRY_UPDATE,
+        Trigger::OnEntryDelete => TRIGGER_ON_ENTRY_DELETE,
+        Trigger::OnSchedule(_) => "on_schedule",
+        Trigger::Manual => TRIGGER_MANUAL,
+    }
 }
 
 // TODO clean up
@@ -1247,10 +2092,17 @@ async fn run_instance_actor(
 #[instrument]
 async fn spawn_runner_core_with_data(
     plugin_path: &PathBuf,
+    plugin_name: &str,
     instance_id: InstanceID,
     data: &str,
+    sandbox: &PreparedSandbox,
 ) -> Result<(Child, ChildStdin, mpsc::Receiver<RunnerMsg>), Error> {
-    let runner_path = PathBuf::from(RUNNER_PATH);
+    // Der Runner läuft im Arbeitsverzeichnis der Instanz, relative Pfade
+    // gelten dort nicht mehr.
+    let runner_path = std::path::absolute(RUNNER_PATH)
+        .map_err(|e| Error::CustomError(format!("{ERR_FAILED_SPAWN_PY_PREFIX}{e}")))?;
+    let plugin_path = std::path::absolute(plugin_path)
+        .map_err(|e| Error::CustomError(format!("{ERR_FAILED_SPAWN_PY_PREFIX}{e}")))?;
 
     debug!(
         "Spawning python runner: exe='{}' runner='{:?}' plugin_path='{:?}' instance_id={} data_bytes={}",
@@ -1266,7 +2118,10 @@ async fn spawn_runner_core_with_data(
     // - stdin  -> Kommandos an Python
     // - stdout -> JSON-Nachrichten zurück zu Rust
     // - stderr -> reine Fehler-/Debugausgabe
-    let mut child = Command::new(PYTHON_EXECUTABLE)
+    // Das Runner-Token macht Rückrufe des Plugins zuordenbar und beschränkt sie
+    // auf `auth::RUNNER_SCOPES`.
+    let mut command = Command::new(PYTHON_EXECUTABLE);
+    command
         .arg(PYTHON_UNBUFFERED_FLAG)
         .arg(runner_path)
         .arg(ARG_PLUGIN_PATH)
@@ -1275,11 +2130,21 @@ async fn spawn_runner_core_with_data(
         .arg(instance_id.to_string())
         .arg("--data")
         .arg(data)
+        .env(
+            ENV_API_TOKEN,
+            auth::issue_runner_token(instance_id, plugin_name, chrono::Utc::now()),
+        )
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
-        .spawn()
-        .map_err(|e| Error::CustomError(format!("{ERR_FAILED_SPAWN_PY_PREFIX}{e}")))?;
+        // Eigene Prozessgruppe, damit ein Timeout auch Kindprozesse beendet.
+        .process_group(0);
+    // Arbeitsverzeichnis, rlimits und cgroup der Instanz.
+    sandbox.apply(&mut command);
+    let mut child = command.spawn().map_err(|e| {
+        auth::revoke_runner_token(instance_id);
+        Error::CustomError(format!("{ERR_FAILED_SPAWN_PY_PREFIX}{e}"))
+    })?;
 
     let child_stdin = child
         .stdin
@@ -1291,20 +2156,39 @@ async fn spawn_runner_core_with_data(
         .take()
         .ok_or_else(|| Error::CustomError(ERR_FAILED_OPEN_STDOUT.to_string()))?;
 
-    // stderr des Python-Prozesses separat in die Rust-Logs spiegeln.
+    // stdout-Reader und stderr-Reader schicken ihre Nachrichten in einen
+    // Tokio-Kanal, aus dem später der Actor liest.
+    let (tx, rx) = mpsc::channel::<RunnerMsg>(128);
+
+    // stderr des Python-Prozesses in die Rust-Logs spiegeln und als `log`-Event
+    // an den Actor geben, damit die Zeilen in den Logs der Instanz landen.
     if let Some(stderr) = child.stderr.take() {
+        let tx = tx.clone();
         tokio::spawn(async move {
             let mut lines = BufReader::new(stderr).lines();
             while let Ok(Some(line)) = lines.next_line().await {
                 error!(LOG_PY_STDERR_PREFIX, line);
+                let msg = RunnerMsg {
+                    instance_id,
+                    request_id: None,
+                    ok: None,
+                    result: Some(serde_json::json!({
+                        "level": logs::STDERR_LEVEL,
+                        "msg": line,
+                        "logger": logs::STDERR_LOGGER,
+                    })),
+                    error: None,
+                    trace: None,
+                    error_types: None,
+                    event: Some("log".to_string()),
+                };
+                if tx.send(msg).await.is_err() {
+                    break;
+                }
             }
         });
     }
 
-    // stdout-Reader:
-    // Liest jede JSON-Zeile und schickt sie in einen Tokio-Kanal,
-    // aus dem später der Actor liest.
-    let (tx, rx) = mpsc::channel::<RunnerMsg>(128);
     tokio::spawn(async move {
         let mut lines = BufReader::new(stdout).lines();
         while let Ok(Some(line)) = lines.next_line().await {
@@ -1332,9 +2216,66 @@ async fn spawn_runner_core_with_data(
 #[instrument]
 async fn spawn_runner_core(
     plugin_path: &PathBuf,
+    plugin_name: &str,
     instance_id: InstanceID,
+    sandbox: &PreparedSandbox,
 ) -> Result<(Child, ChildStdin, mpsc::Receiver<RunnerMsg>), Error> {
-    spawn_runner_core_with_data(plugin_path, instance_id, "").await
+    spawn_runner_core_with_data(plugin_path, plugin_name, instance_id, "", sandbox).await
+}
+
+/// Überträgt Zustands- und Fortschrittswechsel einer Instanz auf den Event-Bus,
+/// bis der Actor seine Watch-Sender verwirft.
+async fn forward_instance_events(
+    events: EventBus,
+    instance_id: InstanceID,
+    plugin_name: String,
+    mut status_rx: watch::Receiver<InstanceState>,
+    mut progress_rx: watch::Receiver<f32>,
+) {
+    let state = *status_rx.borrow_and_update();
+    events.publish(CatalogEvent::PluginState {
+        instance_id,
+        plugin_name: plugin_name.clone(),
+        state,
+    });
+    let (mut status_open, mut progress_open) = (true, true);
+    loop {
+        tokio::select! {
+            changed = status_rx.changed(), if status_open => match changed {
+                Ok(()) => {
+                    let state = *status_rx.borrow_and_update();
+                    events.publish(CatalogEvent::PluginState {
+                        instance_id,
+                        plugin_name: plugin_name.clone(),
+                        state,
+                    });
+                }
+                Err(_) => status_open = false,
+            },
+            changed = progress_rx.changed(), if progress_open => match changed {
+                Ok(()) => {
+                    let progress = *progress_rx.borrow_and_update();
+                    events.publish(CatalogEvent::PluginProgress {
+                        instance_id,
+                        plugin_name: plugin_name.clone(),
+                        progress,
+                    });
+                }
+                Err(_) => progress_open = false,
+            },
+            else => break,
+        }
+    }
+}
+
+/// Legt Arbeitsverzeichnis und cgroup einer Instanz an.
+fn prepare_sandbox(
+    sandbox: &SandboxPolicy,
+    instance_id: InstanceID,
+) -> Result<PreparedSandbox, Error> {
+    sandbox
+        .prepare(instance_id)
+        .map_err(|e| Error::CustomError(format!("{ERR_FAILED_PREPARE_SANDBOX_PREFIX}{e}")))
 }
 
 #[instrument]
@@ -1343,17 +2284,32 @@ pub async fn build_started_instance_core(
     plugin_name: String,
     plugin_path: &PathBuf,
     instance_id: InstanceID,
+    limits: RunLimits,
+    sandbox: SandboxPolicy,
 ) -> Result<PluginHandle, Error> {
-    let (child, mut child_stdin, stdout_rx) = spawn_runner_core(plugin_path, instance_id).await?;
+    let sandbox = prepare_sandbox(&sandbox, instance_id)?;
+    let (child, mut child_stdin, stdout_rx) =
+        match spawn_runner_core(plugin_path, &plugin_name, instance_id, &sandbox).await {
+            Ok(spawned) => spawned,
+            Err(e) => {
+                sandbox.cleanup();
+                return Err(e);
+            }
+        };
     let (command_tx, command_rx) = mpsc::channel(32);
     let (status_tx, status_rx) = watch::channel(InstanceState::Running);
 
     // NEW
     let (progress_tx, progress_rx) = watch::channel(0.0_f32);
+    let (outcome_tx, outcome_rx) = watch::channel(None);
+    let logs = InstanceLogs::new(instance_id);
 
     // Perform initial start handshake before spawning the actor
     let request_id = format!("{}-0", instance_id);
-    send_runner_cmd(instance_id, &mut child_stdin, CMD_START, &request_id).await?;
+    if let Err(e) = send_runner_cmd(instance_id, &mut child_stdin, CMD_START, &request_id).await {
+        sandbox.cleanup();
+        return Err(e);
+    }
 
     // // Wait for CMD_START ACK (or init_error)
     // timeout(TIMEOUT_START_ACK, async {
@@ -1389,22 +2345,38 @@ pub async fn build_started_instance_core(
     //         TIMEOUT_START_ACK
     //     ))
     // })??;
-    tokio::spawn(run_instance_actor(
-        instance_id,
-        plugin_name.clone(),
-        child,
-        child_stdin,
-        stdout_rx,
-        command_rx,
-        status_tx,
-        progress_tx,
-    ));
+    let logs_for_actor = logs.clone();
+    let (workdir_hold, mut workdir_released) = mpsc::channel::<()>(1);
+    tokio::spawn(async move {
+        run_instance_actor(
+            instance_id,
+            plugin_name,
+            child,
+            child_stdin,
+            stdout_rx,
+            command_rx,
+            status_tx,
+            progress_tx,
+            outcome_tx,
+            logs_for_actor,
+            limits,
+        )
+        .await;
+        // Arbeitsverzeichnis und cgroup erst nach dem Ende des Runners entfernen
+        // und nachdem die Artefakte des Laufs übernommen sind.
+        while workdir_released.recv().await.is_some() {}
+        let _ = tokio::task::spawn_blocking(move || sandbox.cleanup()).await;
+    });
 
     Ok(PluginHandle {
         plugin_index,
         command_tx,
         status_rx,
         progress_rx,
+        data: String::new(),
+        outcome_rx,
+        logs,
+        workdir_hold: Some(workdir_hold),
     })
 }
 
@@ -1415,36 +2387,63 @@ pub async fn build_started_instance_core_with_data(
     plugin_path: &PathBuf,
     instance_id: InstanceID,
     data: String,
+    limits: RunLimits,
+    sandbox: SandboxPolicy,
 ) -> Result<PluginHandle, Error> {
-    // 1. Python-Runner-Prozess starten
-    let (child, mut child_stdin, stdout_rx) =
-        spawn_runner_core_with_data(plugin_path, instance_id, &data).await?;
+    // 1. Arbeitsverzeichnis vorbereiten und Python-Runner-Prozess starten
+    let sandbox = prepare_sandbox(&sandbox, instance_id)?;
+    let spawned =
+        spawn_runner_core_with_data(plugin_path, &plugin_name, instance_id, &data, &sandbox).await;
+    let (child, mut child_stdin, stdout_rx) = match spawned {
+        Ok(spawned) => spawned,
+        Err(e) => {
+            sandbox.cleanup();
+            return Err(e);
+        }
+    };
 
     // 2. Interne Kommunikationskanäle für den Actor aufbauen
     let (command_tx, command_rx) = mpsc::channel(32);
     let (status_tx, status_rx) = watch::channel(InstanceState::Running);
     let (progress_tx, progress_rx) = watch::channel(0.0_f32);
+    let (outcome_tx, outcome_rx) = watch::channel(None);
+    let logs = InstanceLogs::new(instance_id);
 
     // 3. Initiales Start-Kommando an den Runner senden.
     // Erst dadurch startet der eigentliche Worker-Thread in Python.
     let request_id = format!("{}-0", instance_id);
-    send_runner_cmd(instance_id, &mut child_stdin, CMD_START, &request_id).await?;
+    if let Err(e) = send_runner_cmd(instance_id, &mut child_stdin, CMD_START, &request_id).await {
+        sandbox.cleanup();
+        return Err(e);
+    }
 
     // 4. Den Instanz-Actor im Hintergrund starten.
     // Er verwaltet ab jetzt:
     // - eingehende Steuerkommandos
     // - ausgehende Runner-Nachrichten
     // - Status- und Fortschrittsupdates
-    tokio::spawn(run_instance_actor(
-        instance_id,
-        plugin_name.clone(),
-        child,
-        child_stdin,
-        stdout_rx,
-        command_rx,
-        status_tx,
-        progress_tx,
-    ));
+    let logs_for_actor = logs.clone();
+    let (workdir_hold, mut workdir_released) = mpsc::channel::<()>(1);
+    tokio::spawn(async move {
+        run_instance_actor(
+            instance_id,
+            plugin_name,
+            child,
+            child_stdin,
+            stdout_rx,
+            command_rx,
+            status_tx,
+            progress_tx,
+            outcome_tx,
+            logs_for_actor,
+            limits,
+        )
+        .await;
+        // Arbeitsverzeichnis und cgroup erst nach dem Ende des Runners entfernen
+        // und nachdem die Artefakte des Laufs übernommen sind.
+        while workdir_released.recv().await.is_some() {}
+        let _ = tokio::task::spawn_blocking(move || sandbox.cleanup()).await;
+    });
 
     // 5. Handle zurückgeben, mit dem der Rest des Systems die Instanz steuern kann.
     Ok(PluginHandle {
@@ -1452,5 +2451,9 @@ pub async fn build_started_instance_core_with_data(
         command_tx,
         status_rx,
         progress_rx,
+        data,
+        outcome_rx,
+        logs,
+        workdir_hold: Some(workdir_hold),
     })
 }
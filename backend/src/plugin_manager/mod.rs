@@ -6,6 +6,33 @@ pub mod manager;
 /// `Trigger`, `TriggerKind` und `BackendEvent`.
 pub mod plugin;
 
+/// Log-Zeilen je Instanz: Vorhalt für Live-Tails und Ablage in `plugin_logs`.
+pub mod logs;
+
 /// Brücke zwischen Rust und Python:
 /// Import, Validierung und Auslesen von Plugin-Metadaten.
 pub mod python_bridge;
+
+/// Pipelines aus `plugins.yaml`: Schritte, Abhängigkeiten und Fehlerverhalten.
+pub mod pipeline;
+
+/// Parameter-Schemas aus `PLUGIN_PARAMETERS` und Prüfung der Startdaten.
+pub mod parameters;
+
+/// Warteschlange der Starts mit globalem und plugin-eigenem Limit.
+pub mod queue;
+
+/// Wiederholungsregeln aus `PLUGIN_RETRY` mit Backoff und Fehlerklassen.
+pub mod retry;
+
+/// Ressourcengrenzen, Arbeitsverzeichnis und Schreibschutz der Runner-Prozesse.
+pub mod sandbox;
+
+/// Pfad-, Topic- und Metadatenbedingungen aus `PLUGIN_TRIGGER_FILTER`.
+pub mod trigger_filter;
+
+/// Zeitlimits aus `PLUGIN_TIMEOUT` und `PLUGIN_STALL_TIMEOUT` mit Watchdog je Instanz.
+pub mod watchdog;
+
+/// Verlauf der Instanzen in `plugin_runs`: Start, Endzustand und Ergebnis.
+pub mod runs;
@@ -0,0 +1,247 @@
+//! Parameter-Schemas von Plugins aus `PLUGIN_PARAMETERS`.
+//!
+//! Ein Plugin beschreibt darin die Daten, die es beim manuellen Start erwartet:
+//!
+//! ```python
+//! PLUGIN_PARAMETERS = {
+//!     "entry_path": {"type": "entry", "required": True, "description": "Aufnahme"},
+//!     "format": {"type": "string", "enum": ["yaml", "json"], "default": "yaml"},
+//!     "limit": {"type": "integer", "minimum": 1, "default": 10},
+//! }
+//! ```
+//!
+//! `POST /plugins/<name>/start` prüft die Daten dagegen und ergänzt Defaults;
+//! ohne Schema wird jedes JSON-Objekt unverändert durchgereicht. Ein
+//! Sammelstart über `POST /entries/bulk` prüft einmal und setzt den ersten
+//! `entry`-Parameter je Eintrag.
+
+use crate::error::Error;
+pub use crate::error::FieldError;
+use rocket::serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Zulässige Parametertypen. `entry` ist der Pfad einer Aufnahme, z.B. für
+/// eine Eintragsauswahl im Frontend.
+pub const PARAMETER_TYPES: &[&str] = &["string", "integer", "number", "boolean", "entry"];
+
+/// Beschreibung eines einzelnen Parameters.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", deny_unknown_fields)]
+pub struct ParameterSpec {
+    /// Schlüssel in den Startdaten; kommt aus dem Schlüssel in `PLUGIN_PARAMETERS`.
+    #[serde(default)]
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+    #[serde(default, rename = "enum", skip_serializing_if = "Option::is_none")]
+    pub choices: Option<Vec<Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<f64>,
+}
+
+impl ParameterSpec {
+    /// Prüft einen Wert gegen Typ, Auswahl und Grenzen.
+    fn check(&self, value: &Value) -> Result<(), String> {
+        let type_ok = match self.kind.as_str() {
+            "string" | "entry" => value.is_string(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "number" => value.is_number(),
+            "boolean" => value.is_boolean(),
+            _ => false,
+        };
+        if !type_ok {
+            return Err(format!("expected {}, got {value}", self.kind));
+        }
+        if self.kind == "entry" && value.as_str().is_some_and(|p| p.trim().is_empty()) {
+            return Err("entry path must not be empty".to_string());
+        }
+        if let Some(choices) = &self.choices
+            && !choices.contains(value)
+        {
+            let allowed: Vec<String> = choices.iter().map(Value::to_string).collect();
+            return Err(format!("{value} is not one of {}", allowed.join(", ")));
+        }
+        if let Some(number) = value.as_f64() {
+            if let Some(minimum) = self.minimum
+                && number < minimum
+            {
+                return Err(format!("{value} is less than minimum {minimum}"));
+            }
+            if let Some(maximum) = self.maximum
+                && number > maximum
+            {
+                return Err(format!("{value} is greater than maximum {maximum}"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Meldet Feldfehler der Startdaten als 400 mit `[{ "field": ..., "message": ... }]`.
+pub fn invalid_parameters(errors: Vec<FieldError>) -> Error {
+    Error::InvalidFields(errors)
+}
+
+/// Schema eines Plugins, Parameter in der Reihenfolge der Deklaration.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(crate = "rocket::serde", transparent)]
+pub struct ParameterSchema {
+    parameters: Vec<ParameterSpec>,
+}
+
+impl ParameterSchema {
+    /// Baut das Schema aus den Einträgen von `PLUGIN_PARAMETERS` und prüft
+    /// es, inklusive der Defaults.
+    pub fn from_declaration(declaration: Vec<(String, Value)>) -> Result<Self, String> {
+        let mut parameters: Vec<ParameterSpec> = Vec::with_capacity(declaration.len());
+        for (name, raw) in declaration {
+            if name.trim().is_empty() {
+                return Err("parameter names must not be empty".to_string());
+            }
+            let mut spec: ParameterSpec =
+                serde_json::from_value(raw).map_err(|e| format!("parameter '{name}': {e}"))?;
+            spec.name = name;
+            let name = &spec.name;
+            if !PARAMETER_TYPES.contains(&spec.kind.as_str()) {
+                return Err(format!(
+                    "parameter '{name}': unknown type '{}', expected one of {}",
+                    spec.kind,
+                    PARAMETER_TYPES.join(", ")
+                ));
+            }
+            if spec.required && spec.default.is_some() {
+                return Err(format!(
+                    "parameter '{name}': a required parameter cannot have a default"
+                ));
+            }
+            if let Some(choices) = &spec.choices {
+                if choices.is_empty() {
+                    return Err(format!("parameter '{name}': enum must not be empty"));
+                }
+                let unrestricted = ParameterSpec {
+                    choices: None,
+                    minimum: None,
+                    maximum: None,
+                    ..spec.clone()
+                };
+                for choice in choices {
+                    unrestricted
+                        .check(choice)
+                        .map_err(|e| format!("parameter '{name}': enum value {e}"))?;
+                }
+            }
+            if (spec.minimum.is_some() || spec.maximum.is_some())
+                && !matches!(spec.kind.as_str(), "integer" | "number")
+            {
+                return Err(format!(
+                    "parameter '{name}': minimum/maximum only apply to integer and number"
+                ));
+            }
+            if let Some(default) = &spec.default {
+                spec.check(default)
+                    .map_err(|e| format!("parameter '{name}': default {e}"))?;
+            }
+            parameters.push(spec);
+        }
+        Ok(ParameterSchema { parameters })
+    }
+
+    pub fn parameters(&self) -> &[ParameterSpec] {
+        &self.parameters
+    }
+
+    /// Prüft Startdaten gegen das Schema und ergänzt Defaults. `null` gilt
+    /// wie ein fehlender Wert; unbekannte Felder werden abgelehnt.
+    pub fn validate(
+        &self,
+        payload: Map<String, Value>,
+    ) -> Result<Map<String, Value>, Vec<FieldError>> {
+        self.validate_except(payload, None)
+    }
+
+    /// Erster `entry`-Parameter; ein Sammelstart füllt ihn je Eintrag.
+    pub fn entry_parameter(&self) -> Option<&str> {
+        self.parameters
+            .iter()
+            .find(|p| p.kind == "entry")
+            .map(|p| p.name.as_str())
+    }
+
+    /// Wie [`ParameterSchema::validate`] für einen Start je Eintrag: der
+    /// [`ParameterSchema::entry_parameter`] fehlt in den Daten noch und darf
+    /// auch nicht mitgegeben werden, der Aufrufer setzt ihn je Eintrag.
+    pub fn validate_per_entry(
+        &self,
+        payload: Map<String, Value>,
+    ) -> Result<Map<String, Value>, Vec<FieldError>> {
+        self.validate_except(payload, self.entry_parameter())
+    }
+
+    fn validate_except(
+        &self,
+        payload: Map<String, Value>,
+        per_entry: Option<&str>,
+    ) -> Result<Map<String, Value>, Vec<FieldError>> {
+        let mut errors = Vec::new();
+        for field in payload.keys() {
+            if !self.parameters.iter().any(|p| &p.name == field) {
+                errors.push(FieldError::new(field, "unknown parameter"));
+            }
+        }
+        let mut validated = Map::new();
+        for spec in &self.parameters {
+            if per_entry == Some(spec.name.as_str()) {
+                if payload.get(&spec.name).is_some_and(|v| !v.is_null()) {
+                    errors.push(FieldError::new(
+                        &spec.name,
+                        "set from the selected entries, must not be given",
+                    ));
+                }
+                continue;
+            }
+            match payload.get(&spec.name).filter(|v| !v.is_null()) {
+                Some(value) => match spec.check(value) {
+                    Ok(()) => {
+                        validated.insert(spec.name.clone(), value.clone());
+                    }
+                    Err(message) => errors.push(FieldError::new(&spec.name, message)),
+                },
+                None if spec.required => {
+                    errors.push(FieldError::new(&spec.name, "required parameter is missing"))
+                }
+                None => {
+                    if let Some(default) = &spec.default {
+                        validated.insert(spec.name.clone(), default.clone());
+                    }
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(validated)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Werte der `entry`-Parameter in geprüften Startdaten als (Name, Pfad).
+    pub fn entry_paths<'a>(&'a self, payload: &'a Map<String, Value>) -> Vec<(&'a str, &'a str)> {
+        self.parameters
+            .iter()
+            .filter(|p| p.kind == "entry")
+            .filter_map(|p| {
+                payload
+                    .get(&p.name)
+                    .and_then(Value::as_str)
+                    .map(|path| (p.name.as_str(), path))
+            })
+            .collect()
+    }
+}
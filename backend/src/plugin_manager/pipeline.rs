@@ -0,0 +1,603 @@
+//! Pipelines aus `plugins.yaml`: Plugins als Schritte eines Abhängigkeitsgraphen.
+//!
+//! ```yaml
+//! pipelines:
+//!   - name: ingest
+//!     enabled: true
+//!     trigger: on_entry_create
+//!     filter:
+//!       paths: ["*.mcap"]
+//!     on_failure: skip_dependents
+//!     steps:
+//!       - name: validate
+//!         plugin: validate_mcap
+//!       - name: gps
+//!         plugin: extract_gps
+//!         needs: [validate]
+//!       - name: preview
+//!         plugin: generate_preview
+//!         needs: [validate]
+//!         on_failure: continue
+//!       - name: export
+//!         plugin: metadata_yaml_export
+//!         needs: [gps, preview]
+//! ```
+//!
+//! Ein Schritt wird eingereiht, sobald alle Schritte aus `needs` beendet sind.
+//! Ohne `needs` bekommt er die Daten des Auslösers, mit einem Vorgänger dessen
+//! `result` (Text unverändert, sonst als JSON), mit mehreren ein Objekt
+//! Schrittname → `result`. `on_failure` der Pipeline, je Schritt
+//! überschreibbar, legt fest, was nach einem gescheiterten Schritt passiert:
+//! - `stop`: wartende und eingereihte Schritte entfallen, laufende enden regulär
+//! - `skip_dependents`: nur die davon abhängigen Schritte entfallen
+//! - `continue`: abhängige Schritte laufen trotzdem, mit `null` als Ergebnis
+//!
+//! Ein gestoppter Schritt gilt als abgebrochen; seine abhängigen Schritte
+//! entfallen. Läufe werden nur im Speicher gehalten, die einzelnen Schritte
+//! stehen wie jede Instanz in `plugin_runs`.
+
+use crate::plugin_manager::manager::InstanceState;
+use crate::plugin_manager::plugin::TriggerKind;
+use crate::plugin_manager::runs::RunOutcome;
+use crate::plugin_manager::trigger_filter::{TriggerFilter, TriggerSubject};
+use chrono::{DateTime, Utc};
+use rocket::serde::{Deserialize, Serialize};
+use serde::de::{Deserializer, Error as _};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashSet};
+
+/// So viele beendete Läufe bleiben für `GET /plugin/pipelines/runs` erhalten.
+pub const MAX_FINISHED_RUNS: usize = 100;
+
+/// Verhalten nach einem gescheiterten Schritt.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", rename_all = "snake_case")]
+pub enum FailurePolicy {
+    Stop,
+    #[default]
+    SkipDependents,
+    Continue,
+}
+
+/// Auslöser einer Pipeline; manuell startbar ist jede.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", rename_all = "snake_case")]
+pub enum PipelineTrigger {
+    #[default]
+    Manual,
+    OnEntryCreate,
+    OnEntryUpdate,
+    OnEntryDelete,
+}
+
+impl PipelineTrigger {
+    pub fn kind(&self) -> Option<TriggerKind> {
+        match self {
+            PipelineTrigger::Manual => None,
+            PipelineTrigger::OnEntryCreate => Some(TriggerKind::OnEntryCreate),
+            PipelineTrigger::OnEntryUpdate => Some(TriggerKind::OnEntryUpdate),
+            PipelineTrigger::OnEntryDelete => Some(TriggerKind::OnEntryDelete),
+        }
+    }
+}
+
+/// Ein Schritt einer Pipeline.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", deny_unknown_fields)]
+pub struct StepConfig {
+    pub name: String,
+    /// Name des registrierten Plugins.
+    pub plugin: String,
+    /// Schritte, die vorher beendet sein müssen.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub needs: Vec<String>,
+    /// Überschreibt `on_failure` der Pipeline für diesen Schritt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_failure: Option<FailurePolicy>,
+}
+
+fn deserialize_filter<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<TriggerFilter>, D::Error> {
+    Option::<Value>::deserialize(deserializer)?
+        .map(TriggerFilter::from_declaration)
+        .transpose()
+        .map_err(|e| D::Error::custom(format!("invalid filter: {e}")))
+}
+
+/// Eintrag unter `pipelines` in `plugins.yaml`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", deny_unknown_fields)]
+pub struct PipelineConfig {
+    pub name: String,
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub trigger: PipelineTrigger,
+    /// Bedingungen wie in `PLUGIN_TRIGGER_FILTER`, nur für Entry-Trigger.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_filter",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub filter: Option<TriggerFilter>,
+    #[serde(default)]
+    pub on_failure: FailurePolicy,
+    pub steps: Vec<StepConfig>,
+}
+
+impl PipelineConfig {
+    /// Prüft Schrittnamen, `needs`, Zyklen und den Filter.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("pipeline name must not be empty".to_string());
+        }
+        if self.steps.is_empty() {
+            return Err("at least one step is required".to_string());
+        }
+        if self.filter.is_some() && self.trigger.kind().is_none() {
+            return Err("filter requires an on_entry_* trigger".to_string());
+        }
+        let mut names = HashSet::new();
+        for step in &self.steps {
+            if step.name.trim().is_empty() || step.plugin.trim().is_empty() {
+                return Err("steps need a name and a plugin".to_string());
+            }
+            if !names.insert(step.name.as_str()) {
+                return Err(format!("duplicate step '{}'", step.name));
+            }
+        }
+        for step in &self.steps {
+            if let Some(missing) = step.needs.iter().find(|n| !names.contains(n.as_str())) {
+                return Err(format!(
+                    "step '{}' needs unknown step '{missing}'",
+                    step.name
+                ));
+            }
+        }
+        // Kahn: was nach dem Abbauen übrig bleibt, liegt auf einem Zyklus.
+        let mut done: HashSet<&str> = HashSet::new();
+        while done.len() < self.steps.len() {
+            let next: Vec<&str> = self
+                .steps
+                .iter()
+                .filter(|s| !done.contains(s.name.as_str()))
+                .filter(|s| s.needs.iter().all(|n| done.contains(n.as_str())))
+                .map(|s| s.name.as_str())
+                .collect();
+            if next.is_empty() {
+                let mut cycle: Vec<&str> = self
+                    .steps
+                    .iter()
+                    .map(|s| s.name.as_str())
+                    .filter(|n| !done.contains(n))
+                    .collect();
+                cycle.sort();
+                return Err(format!("steps form a cycle: {}", cycle.join(", ")));
+            }
+            done.extend(next);
+        }
+        Ok(())
+    }
+
+    /// Ob ein Event dieser Art mit diesem Eintrag die Pipeline auslöst.
+    pub fn fires(&self, kind: TriggerKind, subject: &TriggerSubject) -> bool {
+        self.enabled
+            && self.trigger.kind() == Some(kind)
+            && self.filter.as_ref().is_none_or(|f| f.matches(subject))
+    }
+}
+
+/// Zustand eines Schritts in einem Lauf.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum StepState {
+    /// Wartet auf Schritte aus `needs`.
+    Waiting,
+    /// Instanz ist eingereiht.
+    Queued,
+    Running,
+    Paused,
+    Completed,
+    Failed,
+    /// Entfällt, weil ein benötigter Schritt gescheitert ist oder entfiel.
+    Skipped,
+    /// Gestoppt oder durch `on_failure: stop` abgebrochen.
+    Cancelled,
+}
+
+impl StepState {
+    pub fn is_done(&self) -> bool {
+        matches!(
+            self,
+            StepState::Completed | StepState::Failed | StepState::Skipped | StepState::Cancelled
+        )
+    }
+}
+
+/// Gesamtzustand eines Laufs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum PipelineStatus {
+    Running,
+    /// Alle Schritte abgeschlossen.
+    Completed,
+    /// Alle Schritte beendet, mindestens einer nicht erfolgreich.
+    Failed,
+}
+
+/// Verweis eines Jobs auf seinen Pipeline-Schritt.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StepRef {
+    pub run_id: u64,
+    pub step: String,
+}
+
+/// Schritt, der eingereiht werden kann.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadyStep {
+    pub step: String,
+    pub plugin: String,
+    /// Daten für `plugin.run(data)`.
+    pub data: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct StepRun {
+    config: StepConfig,
+    policy: FailurePolicy,
+    state: StepState,
+    instance_id: Option<u64>,
+    result: Option<Value>,
+    error: Option<String>,
+}
+
+impl StepRun {
+    /// Ob abhängige Schritte nach diesem Schritt starten dürfen.
+    fn releases_dependents(&self) -> bool {
+        match self.state {
+            StepState::Completed => true,
+            StepState::Failed => self.policy == FailurePolicy::Continue,
+            _ => false,
+        }
+    }
+}
+
+/// Ein Lauf einer Pipeline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PipelineRun {
+    pub id: u64,
+    pub pipeline: String,
+    /// Daten des Auslösers für Schritte ohne `needs`.
+    pub input: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    steps: Vec<StepRun>,
+}
+
+impl PipelineRun {
+    pub fn new(id: u64, config: &PipelineConfig, input: String) -> Self {
+        PipelineRun {
+            id,
+            pipeline: config.name.clone(),
+            input,
+            started_at: Utc::now(),
+            finished_at: None,
+            steps: config
+                .steps
+                .iter()
+                .map(|step| StepRun {
+                    config: step.clone(),
+                    policy: step.on_failure.unwrap_or(config.on_failure),
+                    state: StepState::Waiting,
+                    instance_id: None,
+                    result: None,
+                    error: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn step(&self, name: &str) -> Option<&StepRun> {
+        self.steps.iter().find(|s| s.config.name == name)
+    }
+
+    fn step_mut(&mut self, name: &str) -> Option<&mut StepRun> {
+        self.steps.iter_mut().find(|s| s.config.name == name)
+    }
+
+    /// Markiert Schritte, deren Vorgänger entfallen oder gescheitert sind, als
+    /// `Skipped` und gibt alle startbaren Schritte als `Queued` zurück.
+    pub fn take_ready(&mut self) -> Vec<ReadyStep> {
+        loop {
+            let blocked: Vec<(usize, String)> = self
+                .steps
+                .iter()
+                .enumerate()
+                .filter(|(_, s)| s.state == StepState::Waiting)
+                .filter_map(|(i, s)| {
+                    s.config
+                        .needs
+                        .iter()
+                        .filter_map(|n| self.step(n))
+                        .find(|dep| dep.state.is_done() && !dep.releases_dependents())
+                        .map(|dep| (i, dep.config.name.clone()))
+                })
+                .collect();
+            if blocked.is_empty() {
+                break;
+            }
+            for (i, dep) in blocked {
+                self.steps[i].state = StepState::Skipped;
+                self.steps[i].error = Some(format!("skipped because step '{dep}' did not succeed"));
+            }
+        }
+
+        let ready: Vec<usize> = (0..self.steps.len())
+            .filter(|&i| self.steps[i].state == StepState::Waiting)
+            .filter(|&i| {
+                self.steps[i]
+                    .config
+                    .needs
+                    .iter()
+                    .all(|n| self.step(n).is_some_and(StepRun::releases_dependents))
+            })
+            .collect();
+        let ready = ready
+            .into_iter()
+            .map(|i| {
+                let data = self.step_data(&self.steps[i].config.needs);
+                let step = &mut self.steps[i];
+                step.state = StepState::Queued;
+                ReadyStep {
+                    step: step.config.name.clone(),
+                    plugin: step.config.plugin.clone(),
+                    data,
+                }
+            })
+            .collect();
+        self.update_finished();
+        ready
+    }
+
+    /// Daten eines Schritts aus den Ergebnissen seiner Vorgänger.
+    fn step_data(&self, needs: &[String]) -> String {
+        let result = |name: &String| {
+            self.step(name)
+                .and_then(|s| s.result.clone())
+                .unwrap_or(Value::Null)
+        };
+        match needs {
+            [] => self.input.clone(),
+            [single] => match result(single) {
+                Value::String(text) => text,
+                other => other.to_string(),
+            },
+            many => {
+                let results: Map<String, Value> =
+                    many.iter().map(|n| (n.clone(), result(n))).collect();
+                Value::Object(results).to_string()
+            }
+        }
+    }
+
+    /// Ordnet einem eingereihten Schritt seine (neue) Instanz zu, z.B. bei
+    /// einer Wiederholung.
+    pub fn assign(&mut self, step: &str, instance_id: u64) {
+        if let Some(step) = self.step_mut(step)
+            && step.state == StepState::Queued
+        {
+            step.instance_id = Some(instance_id);
+        }
+    }
+
+    /// Hält das Ende eines Schritts fest. Liefert bei `on_failure: stop` die
+    /// Instanzen der übrigen eingereihten Schritte, die aus der Warteschlange
+    /// genommen werden sollen. Schritte, die nicht mehr eingereiht sind (z.B.
+    /// abgebrochen), ändern sich nicht.
+    pub fn finish(&mut self, step: &str, outcome: &RunOutcome) -> Vec<u64> {
+        let Some(step) = self.step_mut(step) else {
+            return Vec::new();
+        };
+        if step.state != StepState::Queued {
+            return Vec::new();
+        }
+        step.state = match outcome.state {
+            InstanceState::Completed => StepState::Completed,
+            InstanceState::Stopped => StepState::Cancelled,
+            _ => StepState::Failed,
+        };
+        step.result = outcome.result.clone();
+        step.error = match step.state {
+            StepState::Completed => None,
+            _ => Some(
+                outcome
+                    .error
+                    .clone()
+                    .unwrap_or_else(|| format!("step ended as {:?}", outcome.state)),
+            ),
+        };
+        let stop = step.state == StepState::Failed && step.policy == FailurePolicy::Stop;
+        let failed = step.config.name.clone();
+
+        // Eingereihte Schritte enden, sobald der Aufrufer ihre Instanzen
+        // abbricht; gestartete laufen regulär zu Ende.
+        let mut queued = Vec::new();
+        if stop {
+            for other in self.steps.iter_mut() {
+                match other.state {
+                    StepState::Waiting => {
+                        other.state = StepState::Cancelled;
+                        other.error =
+                            Some(format!("pipeline stopped after step '{failed}' failed"));
+                    }
+                    StepState::Queued => queued.extend(other.instance_id),
+                    _ => {}
+                }
+            }
+        }
+        self.update_finished();
+        queued
+    }
+
+    /// Bricht alle offenen Schritte ab, z.B. vor einem Rescan der Plugins.
+    pub fn cancel(&mut self, reason: &str) {
+        for step in self.steps.iter_mut().filter(|s| !s.state.is_done()) {
+            step.state = StepState::Cancelled;
+            step.error = Some(reason.to_string());
+        }
+        self.update_finished();
+    }
+
+    fn update_finished(&mut self) {
+        if self.finished_at.is_none() && self.steps.iter().all(|s| s.state.is_done()) {
+            self.finished_at = Some(Utc::now());
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished_at.is_some()
+    }
+
+    pub fn status(&self) -> PipelineStatus {
+        if !self.is_finished() {
+            PipelineStatus::Running
+        } else if self.steps.iter().all(|s| s.state == StepState::Completed) {
+            PipelineStatus::Completed
+        } else {
+            PipelineStatus::Failed
+        }
+    }
+
+    /// Lauf mit Zustand und Fortschritt der Schritte. `live` liefert Zustand
+    /// und Fortschritt einer laufenden Instanz; beendete Schritte zählen voll,
+    /// wartende und eingereihte nicht.
+    pub fn view(&self, live: impl Fn(u64) -> Option<(InstanceState, f32)>) -> PipelineRunView {
+        let steps: Vec<StepView> = self
+            .steps
+            .iter()
+            .map(|step| {
+                let live = step
+                    .instance_id
+                    .filter(|_| step.state == StepState::Queued)
+                    .and_then(&live);
+                let (state, progress) = match live {
+                    Some((InstanceState::Paused, progress)) => (StepState::Paused, progress),
+                    Some((InstanceState::Running, progress)) => (StepState::Running, progress),
+                    Some((_, progress)) => (step.state, progress),
+                    None if step.state.is_done() => (step.state, 1.0),
+                    None => (step.state, 0.0),
+                };
+                StepView {
+                    name: step.config.name.clone(),
+                    plugin: step.config.plugin.clone(),
+                    needs: step.config.needs.clone(),
+                    state,
+                    instance_id: step.instance_id,
+                    progress: progress.clamp(0.0, 1.0),
+                    result: step.result.clone(),
+                    error: step.error.clone(),
+                }
+            })
+            .collect();
+        let progress = steps.iter().map(|s| s.progress).sum::<f32>() / steps.len().max(1) as f32;
+        PipelineRunView {
+            id: self.id,
+            pipeline: self.pipeline.clone(),
+            status: self.status(),
+            progress,
+            started_at: self.started_at,
+            finished_at: self.finished_at,
+            steps,
+        }
+    }
+}
+
+/// API-Darstellung eines Schritts.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StepView {
+    pub name: String,
+    pub plugin: String,
+    pub needs: Vec<String>,
+    pub state: StepState,
+    pub instance_id: Option<u64>,
+    pub progress: f32,
+    pub result: Option<Value>,
+    pub error: Option<String>,
+}
+
+/// API-Darstellung eines Laufs; `progress` ist der Mittelwert der Schritte.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PipelineRunView {
+    pub id: u64,
+    pub pipeline: String,
+    pub status: PipelineStatus,
+    pub progress: f32,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub steps: Vec<StepView>,
+}
+
+/// Pipelines aus `plugins.yaml` und ihre Läufe.
+#[derive(Debug, Clone, Default)]
+pub struct Pipelines {
+    configs: Vec<PipelineConfig>,
+    runs: BTreeMap<u64, PipelineRun>,
+}
+
+impl Pipelines {
+    /// Ersetzt die Pipelines; bestehende Läufe bleiben.
+    pub fn configure(&mut self, configs: Vec<PipelineConfig>) {
+        self.configs = configs;
+    }
+
+    pub fn configs(&self) -> &[PipelineConfig] {
+        &self.configs
+    }
+
+    pub fn get(&self, name: &str) -> Option<&PipelineConfig> {
+        self.configs.iter().find(|p| p.name == name)
+    }
+
+    /// Neue, eindeutige Lauf-ID.
+    pub fn next_run_id(&self) -> u64 {
+        let now = Utc::now().timestamp_micros().max(0) as u64;
+        self.runs
+            .keys()
+            .next_back()
+            .map_or(now, |&last| now.max(last + 1))
+    }
+
+    /// Legt einen Lauf an und verwirft die ältesten beendeten Läufe über
+    /// [`MAX_FINISHED_RUNS`].
+    pub fn insert(&mut self, run: PipelineRun) {
+        self.runs.insert(run.id, run);
+        let finished: Vec<u64> = self
+            .runs
+            .values()
+            .filter(|r| r.is_finished())
+            .map(|r| r.id)
+            .collect();
+        for id in finished
+            .iter()
+            .take(finished.len().saturating_sub(MAX_FINISHED_RUNS))
+        {
+            self.runs.remove(id);
+        }
+    }
+
+    pub fn run(&self, run_id: u64) -> Option<&PipelineRun> {
+        self.runs.get(&run_id)
+    }
+
+    pub fn run_mut(&mut self, run_id: u64) -> Option<&mut PipelineRun> {
+        self.runs.get_mut(&run_id)
+    }
+
+    /// Läufe, neueste zuerst.
+    pub fn runs(&self) -> impl Iterator<Item = &PipelineRun> {
+        self.runs.values().rev()
+    }
+
+    pub fn runs_mut(&mut self) -> impl Iterator<Item = &mut PipelineRun> {
+        self.runs.values_mut()
+    }
+}
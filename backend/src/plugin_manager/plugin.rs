@@ -1,3 +1,8 @@
+use crate::plugin_manager::parameters::ParameterSchema;
+use crate::plugin_manager::retry::RetryPolicy;
+use crate::plugin_manager::sandbox::SandboxLimits;
+use crate::plugin_manager::trigger_filter::TriggerFilter;
+use crate::plugin_manager::watchdog::RunLimits;
 use cron::Schedule;
 use tracing::debug;
 
@@ -19,6 +24,8 @@ pub struct Plugin {
     description: String,
     /// Legt fest, wann das Plugin automatisch ausgelöst werden soll.
     trigger: Trigger,
+    /// Zusätzliche Bedingungen aus `PLUGIN_TRIGGER_FILTER`.
+    trigger_filter: Option<TriggerFilter>,
     /// Dateipfad zur Python-Datei des Plugins.
     path: std::path::PathBuf,
 
@@ -28,6 +35,18 @@ pub struct Plugin {
     valid: bool,
     /// Nicht-kritische Probleme aus der Validierung.
     validation_warnings: Vec<String>,
+    /// Optionale Version aus `PLUGIN_VERSION`, wird mit jedem Lauf gespeichert.
+    version: Option<String>,
+    /// Schema der Startdaten aus `PLUGIN_PARAMETERS`, falls deklariert.
+    parameters: Option<ParameterSchema>,
+    /// Wiederholungsregeln aus `PLUGIN_RETRY`; ohne sie wird nie wiederholt.
+    retry_policy: Option<RetryPolicy>,
+    /// Zeitlimits aus `PLUGIN_TIMEOUT` und `PLUGIN_STALL_TIMEOUT`.
+    run_limits: RunLimits,
+    /// Schreibrecht auf `/data` aus `PLUGIN_WRITE_ACCESS`.
+    write_access: bool,
+    /// Ressourcengrenzen aus `plugins.yaml`.
+    sandbox_limits: SandboxLimits,
 }
 
 impl Plugin {
@@ -46,11 +65,18 @@ impl Plugin {
             name,
             description,
             trigger,
+            trigger_filter: None,
             path,
 
             enabled: false,
             valid: true,
             validation_warnings: Vec::new(),
+            version: None,
+            parameters: None,
+            retry_policy: None,
+            run_limits: RunLimits::default(),
+            write_access: false,
+            sandbox_limits: SandboxLimits::default(),
         }
     }
 
@@ -69,6 +95,25 @@ impl Plugin {
         &self.trigger
     }
 
+    /// Liefert die Trigger-Bedingungen, falls das Plugin welche deklariert.
+    pub fn trigger_filter(&self) -> Option<&TriggerFilter> {
+        self.trigger_filter.as_ref()
+    }
+
+    /// Setzt die Trigger-Bedingungen des Plugins.
+    pub fn set_trigger_filter(&mut self, trigger_filter: Option<TriggerFilter>) {
+        self.trigger_filter = trigger_filter;
+    }
+
+    /// Trigger samt Bedingungen, z. B.
+    /// `OnEntryCreate if has message type sensor_msgs/msg/PointCloud2`.
+    pub fn trigger_description(&self) -> String {
+        match &self.trigger_filter {
+            Some(filter) => format!("{} if {}", self.trigger.to_string(), filter.describe()),
+            None => self.trigger.to_string(),
+        }
+    }
+
     /// Liefert den Dateipfad der Plugin-Datei.
     pub fn path(&self) -> &std::path::PathBuf {
         &self.path
@@ -110,6 +155,65 @@ impl Plugin {
         );
         self.validation_warnings = warnings;
     }
+
+    /// Liefert die Version aus `PLUGIN_VERSION`, falls das Modul eine angibt.
+    pub fn version(&self) -> Option<&String> {
+        self.version.as_ref()
+    }
+
+    /// Setzt die Version des Plugins.
+    pub fn set_version(&mut self, version: Option<String>) {
+        self.version = version;
+    }
+
+    /// Liefert das Parameter-Schema, falls das Plugin eines deklariert.
+    pub fn parameters(&self) -> Option<&ParameterSchema> {
+        self.parameters.as_ref()
+    }
+
+    /// Setzt das Parameter-Schema des Plugins.
+    pub fn set_parameters(&mut self, parameters: Option<ParameterSchema>) {
+        self.parameters = parameters;
+    }
+
+    /// Liefert die Wiederholungsregeln, falls das Plugin welche deklariert.
+    pub fn retry_policy(&self) -> Option<&RetryPolicy> {
+        self.retry_policy.as_ref()
+    }
+
+    /// Setzt die Wiederholungsregeln des Plugins.
+    pub fn set_retry_policy(&mut self, retry_policy: Option<RetryPolicy>) {
+        self.retry_policy = retry_policy;
+    }
+
+    /// Liefert die Zeitlimits des Plugins.
+    pub fn run_limits(&self) -> RunLimits {
+        self.run_limits
+    }
+
+    /// Setzt die Zeitlimits des Plugins.
+    pub fn set_run_limits(&mut self, run_limits: RunLimits) {
+        self.run_limits = run_limits;
+    }
+
+    /// Ob das Plugin unter `/data` schreiben darf.
+    pub fn write_access(&self) -> bool {
+        self.write_access
+    }
+
+    pub fn set_write_access(&mut self, write_access: bool) {
+        self.write_access = write_access;
+    }
+
+    /// Liefert die Ressourcengrenzen des Plugins.
+    pub fn sandbox_limits(&self) -> &SandboxLimits {
+        &self.sandbox_limits
+    }
+
+    /// Setzt die Ressourcengrenzen des Plugins.
+    pub fn set_sandbox_limits(&mut self, sandbox_limits: SandboxLimits) {
+        self.sandbox_limits = sandbox_limits;
+    }
 }
 
 /// Vereinfachte Trigger-Art ohne zusätzliche Daten.
@@ -156,6 +260,27 @@ impl BackendEvent {
             BackendEvent::Manual { .. } => None,
         }
     }
+
+    /// Name des Events in den Daten ausgelöster Instanzen (`"event"`).
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            BackendEvent::EntryCreated { .. } => "created",
+            BackendEvent::EntryUpdated { .. } => "updated",
+            BackendEvent::EntryDeleted { .. } => "deleted",
+            BackendEvent::OnSchedule { .. } => "schedule",
+            BackendEvent::Manual { .. } => "manual",
+        }
+    }
+
+    /// Pfad des betroffenen Eintrags bei Entry-Events.
+    pub fn entry_path(&self) -> Option<&str> {
+        match self {
+            BackendEvent::EntryCreated { path }
+            | BackendEvent::EntryUpdated { path }
+            | BackendEvent::EntryDeleted { path } => Some(path),
+            BackendEvent::OnSchedule { .. } | BackendEvent::Manual { .. } => None,
+        }
+    }
 }
 
 /// Konkreter Trigger eines Plugins.
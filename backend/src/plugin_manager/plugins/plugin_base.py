@@ -2,6 +2,7 @@
 from __future__ import annotations
 
 from pathlib import Path
+import os
 import threading
 
 # -------------------- constants --------------------
@@ -17,11 +18,52 @@ RESULT_PAUSED = "paused"
 RESULT_RESUMED = "resumed"
 RESULT_STOPPING = "stopping"
 
+# Umgebungsvariable, in der der Plugin-Manager jeder Instanz ein kurzlebiges
+# Token für Rückrufe an die Backend-API mitgibt.
+API_TOKEN_ENV = "CATALOG_API_TOKEN"
+
 # Hinweise zur Ablage:
 # - plugins_dir/config enthält Konfiguration
 # - Plugins liegen unter plugins_dir
 # - YAML aus src/plugin_manager/plugins/config muss nach plugins_dir/config kopiert werden
 
+def api_headers(extra: dict | None = None) -> dict:
+    """
+    HTTP-Header für Anfragen an die Backend-API, inklusive Runner-Token.
+
+    Ohne Token (z. B. beim direkten Aufruf außerhalb des Plugin-Managers)
+    bleibt nur `extra` übrig.
+    """
+    headers = dict(extra or {})
+    token = os.environ.get(API_TOKEN_ENV)
+    if token:
+        headers["Authorization"] = f"Bearer {token}"
+    return headers
+
+
+def artifacts_result(artifacts: list[dict], entry_id: int | None = None) -> dict:
+    """
+    Rückgabewert für run(data), mit dem ein Plugin Ergebnisse an einen Eintrag hängt.
+
+    Jedes Artefakt ist ein dict mit `type`:
+    - {"type": "tags", "tags": [...]}
+    - {"type": "metadata", "patch": {...}}
+    - {"type": "sequence", "name": ..., "start_timestamp": ..., "end_timestamp": ...}
+    - {"type": "file", "path": "/abs/pfad", "role": "thumbnail" | "report" | "converted" | "other"}
+    - {"type": "metrics", "values": {"name": 1.0}}
+
+    Dateien dürfen im Arbeitsverzeichnis (cwd), unter /exports oder unter /data
+    liegen; aus dem Arbeitsverzeichnis kopiert der Manager sie vor dessen
+    Löschen nach /exports/artifacts/<instance_id>/.
+    Ohne `entry_id` gilt der Eintrag, mit dem das Plugin gestartet wurde.
+    Der Manager prüft alle Artefakte; ist eines ungültig, wird keines übernommen.
+    """
+    result: dict = {"artifacts": list(artifacts)}
+    if entry_id is not None:
+        result["entry_id"] = entry_id
+    return result
+
+
 class BasePlugin:
     """
     Gemeinsame Basisklasse für alle Python-Plugins.
@@ -2,11 +2,12 @@
 PLUGIN_NAME = "append_42"
 PLUGIN_DESCRIPTION = "Append 42 to description of all entries"
 PLUGIN_TRIGGER = "manual"
+PLUGIN_VERSION = "1.0.0"
 
 # Rückgabewert bei sauberem Ende.
 STOPPED = "stopped"
 
-from plugin_base import BasePlugin, TICK_SECONDS
+from plugin_base import BasePlugin, TICK_SECONDS, api_headers
 import logging
 import urllib.request
 import json
@@ -34,7 +35,7 @@ class PluginImpl(BasePlugin):
             und liefert die Antwort als Python-Objekt zurück.
             """
             url = base + path
-            req = urllib.request.Request(url, headers={"Accept": "application/json"})
+            req = urllib.request.Request(url, headers=api_headers({"Accept": "application/json"}))
             with urllib.request.urlopen(req, timeout=10) as resp:
                 return json.loads(resp.read().decode())
 
@@ -45,7 +46,7 @@ class PluginImpl(BasePlugin):
             url = base + path
             body = json.dumps(obj, default=str).encode("utf-8")
             req = urllib.request.Request(
-                url, data=body, headers={"Content-Type": "application/json"}
+                url, data=body, headers=api_headers({"Content-Type": "application/json"})
             )
             req.get_method = lambda: "PUT"
             with urllib.request.urlopen(req, timeout=10) as resp:
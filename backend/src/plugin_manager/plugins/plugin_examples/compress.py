@@ -2,6 +2,23 @@
 PLUGIN_NAME = "compress"
 PLUGIN_DESCRIPTION = "Compress selected entry. Only run on a single plugin"
 PLUGIN_TRIGGER = "manual"
+# Startdaten, geprüft von `POST /plugins/compress/start`.
+PLUGIN_PARAMETERS = {
+    "entry_path": {
+        "type": "entry",
+        "required": True,
+        "description": "Aufnahme, die komprimiert wird",
+    },
+}
+# Bis zu drei Versuche, wenn die Netzfreigabe kurz nicht erreichbar ist.
+PLUGIN_RETRY = {
+    "max_attempts": 3,
+    "backoff_seconds": 30,
+    "retry_on": ["crash", "OSError"],
+}
+# Große Aufnahmen brauchen lange; ein hängendes Kompressionskommando wird
+# trotzdem nach vier Stunden beendet.
+PLUGIN_TIMEOUT = 4 * 60 * 60
 
 # Standard-Rückgabewert bei regulärem Ende.
 STOPPED = "stopped"
@@ -9,6 +26,7 @@ STOPPED = "stopped"
 from plugin_base import BasePlugin
 import json
 import logging
+import os
 import subprocess
 
 
@@ -56,8 +74,13 @@ class PluginImpl(BasePlugin):
             return STOPPED
 
         # Zielname erzeugen:
-        # aus foo.mcap wird foo.compressed.mcap
-        output_path = str(parsed["entry_path"]).replace(".mcap", ".compressed.mcap")
+        # aus /data/a/foo.mcap wird /exports/foo.compressed.mcap
+        # (/data ist für Plugins ohne PLUGIN_WRITE_ACCESS nur lesbar).
+        export_dir = os.environ.get("EXPORT_DIR", "/exports")
+        file_name = os.path.basename(str(parsed["entry_path"]))
+        output_path = os.path.join(
+            export_dir, file_name.replace(".mcap", ".compressed.mcap")
+        )
 
         # Shell-Kommando zusammensetzen.
         cmd = f"mcap compress {parsed['entry_path']} -o {output_path}"
@@ -0,0 +1,71 @@
+# Metadaten für den Plugin-Manager.
+PLUGIN_NAME = "lidar_pointcloud_stats"
+PLUGIN_DESCRIPTION = "Log point cloud topics and message rates of new lidar recordings."
+PLUGIN_TRIGGER = "on_entry_create"
+# Nur Aufnahmen mit Punktwolken; reine Radar-Aufnahmen lösen nichts aus.
+PLUGIN_TRIGGER_FILTER = {
+    "paths": ["*.mcap"],
+    "message_types": ["sensor_msgs/msg/PointCloud2"],
+}
+
+# Rückgabewert bei regulärem Ende.
+STOPPED = "stopped"
+
+from plugin_base import BasePlugin, api_headers
+import json
+import logging
+import urllib.request
+
+logger = logging.getLogger(__name__)
+
+POINT_CLOUD_TYPE = "sensor_msgs/msg/PointCloud2"
+
+
+class PluginImpl(BasePlugin):
+    def run(self, data: str):
+        """
+        Liest die Topics des neuen Eintrags und protokolliert alle
+        Punktwolken-Topics mit Nachrichtenzahl und Frequenz.
+
+        Das Ergebnis `{"entry_path": ..., "point_cloud_topics": [...]}` kann
+        in einer Pipeline direkt an `metadata_yaml_export` gehen.
+
+        Erwartet die Payload von `on_entry_create`, z. B.:
+        {
+          "entry_id": 7,
+          "mcap_path": "/data/drive_01/lidar.mcap",
+          "metadata": {...}
+        }
+        Als erster Schritt einer Pipeline reicht `{"entry_id": 7}`.
+        """
+        base = "http://127.0.0.1:8080"
+        payload = json.loads(data or "{}")
+        entry_id = payload.get("entry_id")
+        if entry_id is None:
+            logger.warning("lidar_pointcloud_stats: no entry_id in %s", data)
+            return STOPPED
+
+        url = f"{base}/entries/{entry_id}/topics/tx/0"
+        req = urllib.request.Request(url, headers=api_headers({"Accept": "application/json"}))
+        with urllib.request.urlopen(req, timeout=10) as resp:
+            # Antwort ist ein Objekt Topic-ID -> Topic.
+            topics = json.loads(resp.read().decode())
+
+        mcap_path = payload.get("mcap_path") or payload.get("path")
+        clouds = [t for t in topics.values() if t.get("topic_type") == POINT_CLOUD_TYPE]
+        for i, topic in enumerate(clouds, start=1):
+            if self.should_stop():
+                break
+            logger.info(
+                "%s: %s with %d messages at %s Hz",
+                mcap_path,
+                topic.get("topic_name"),
+                topic.get("message_count", 0),
+                topic.get("frequency"),
+            )
+            logger.info(f"PROGRESS:{i / len(clouds):.2f}")
+
+        return {
+            "entry_path": mcap_path,
+            "point_cloud_topics": [t.get("topic_name") for t in clouds],
+        }
@@ -2,11 +2,20 @@
 PLUGIN_NAME = "metadata_yaml_export"
 PLUGIN_DESCRIPTION = "Export entry metadata to YAML file."
 PLUGIN_TRIGGER = "manual"
+# Startdaten, geprüft von `POST /plugins/metadata_yaml_export/start`.
+PLUGIN_PARAMETERS = {
+    "entry_path": {
+        "type": "entry",
+        "description": "Nur diese Aufnahme exportieren; ohne Angabe alle",
+    },
+}
+# Legt die YAML-Datei neben der Aufnahme unter /data ab.
+PLUGIN_WRITE_ACCESS = True
 
 # Rückgabewert bei regulärem Ende.
 STOPPED = "stopped"
 
-from plugin_base import BasePlugin, TICK_SECONDS
+from plugin_base import BasePlugin, TICK_SECONDS, api_headers
 import logging
 import urllib.request
 import json
@@ -35,7 +44,7 @@ class PluginImpl(BasePlugin):
             und liefert JSON als Python-Objekt zurück.
             """
             url = base + path
-            req = urllib.request.Request(url, headers={"Accept": "application/json"})
+            req = urllib.request.Request(url, headers=api_headers({"Accept": "application/json"}))
             with urllib.request.urlopen(req, timeout=10) as resp:
                 return json.loads(resp.read().decode())
 
@@ -14,6 +14,7 @@ PLUGIN_DESCRIPTION = (
     "(which is mounted to the project ./test_data)."
 )
 PLUGIN_TRIGGER = "manual"  # alternativ z. B. "on_schedule: */5 * * * *"
+PLUGIN_WRITE_ACCESS = True
 
 log = logging.getLogger(__name__)
 
@@ -24,6 +24,7 @@ PATH: Final[Literal["--plugin-path"]] = "--plugin-path"
 
 TRACE: Final[Literal["trace"]] = "trace"
 ERROR: Final[Literal["error"]] = "error"
+ERROR_TYPES: Final[Literal["error_types"]] = "error_types"
 RESULT: Final[Literal["result"]] = "result"
 OK: Final[Literal["ok"]] = "ok"
 
@@ -83,10 +84,21 @@ def emit_exited(instance_id: int, run_result: "RunResult") -> None:
             RESULT: run_result.get(RESULT),
             ERROR: run_result.get(ERROR),
             TRACE: run_result.get(TRACE),
+            ERROR_TYPES: run_result.get(ERROR_TYPES),
         }
     )
 
 
+def error_types(exception: BaseException) -> list[str]:
+    """
+    Klasse der Exception und ihre Basisklassen, speziellste zuerst.
+
+    Der Rust-Manager vergleicht sie mit `retry_on` aus `PLUGIN_RETRY`,
+    damit dort auch Basisklassen wie `OSError` angegeben werden können.
+    """
+    return [cls.__name__ for cls in type(exception).__mro__ if cls is not object]
+
+
 def load_plugin(plugin_path: str):
     """
     Lädt das konkrete Plugin-Modul direkt aus einer Datei.
@@ -166,6 +178,7 @@ class RunResult(TypedDict):
     result: NotRequired[Any]
     error: NotRequired[str]
     trace: NotRequired[str]
+    error_types: NotRequired[list[str]]
     event: NotRequired[str]
 
 
@@ -309,6 +322,7 @@ def main() -> int:
             run_result[OK] = False
             run_result[ERROR] = str(exception)
             run_result[TRACE] = traceback.format_exc()
+            run_result[ERROR_TYPES] = error_types(exception)
         finally:
             # Sorgt dafür, dass der Prozess mindestens kurz genug lebt,
             # damit Logs und Abschlussnachrichten noch sauber beim Manager ankommen.
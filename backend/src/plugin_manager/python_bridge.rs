@@ -1,4 +1,5 @@
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
 use std::path::Path;
 use tracing::{debug, warn};
 
@@ -14,16 +15,26 @@ const ERR_INVALID_PLUGIN_FILENAME: &str = "Invalid plugin filename";
 const PY_MOD_SYS: &str = "sys";
 const PY_SYS_PATH_ATTR: &str = "path";
 const PY_SYS_PATH_INSERT: &str = "insert";
+const PY_MOD_JSON: &str = "json";
+const PY_JSON_DUMPS: &str = "dumps";
 
 const PY_IMPORT_SYS_FAILED_PREFIX: &str = "Python import sys failed: ";
 const PY_SYS_PATH_ACCESS_FAILED_PREFIX: &str = "Python sys.path access failed: ";
 const PY_SYS_PATH_INSERT_FAILED_PREFIX: &str = "Python sys.path insert failed: ";
+const PY_IMPORT_JSON_FAILED_PREFIX: &str = "Python import json failed: ";
 const PY_IMPORT_MODULE_FAILED_PREFIX: &str = "Python import '";
 const PY_IMPORT_MODULE_FAILED_SUFFIX: &str = "' failed: ";
 
 const PY_ATTR_PLUGIN_NAME: &str = "PLUGIN_NAME";
 const PY_ATTR_PLUGIN_DESCRIPTION: &str = "PLUGIN_DESCRIPTION";
 const PY_ATTR_PLUGIN_TRIGGER: &str = "PLUGIN_TRIGGER";
+const PY_ATTR_PLUGIN_VERSION: &str = "PLUGIN_VERSION";
+const PY_ATTR_PLUGIN_PARAMETERS: &str = "PLUGIN_PARAMETERS";
+const PY_ATTR_PLUGIN_RETRY: &str = "PLUGIN_RETRY";
+const PY_ATTR_PLUGIN_TIMEOUT: &str = "PLUGIN_TIMEOUT";
+const PY_ATTR_PLUGIN_STALL_TIMEOUT: &str = "PLUGIN_STALL_TIMEOUT";
+const PY_ATTR_PLUGIN_WRITE_ACCESS: &str = "PLUGIN_WRITE_ACCESS";
+const PY_ATTR_PLUGIN_TRIGGER_FILTER: &str = "PLUGIN_TRIGGER_FILTER";
 
 const PY_ATTR_PLUGIN_IMPL: &str = "PluginImpl";
 const PY_ATTR_RUN: &str = "run";
@@ -40,6 +51,21 @@ const ERR_PLUGIN_IMPL_HAS_NO_RUN_MID: &str = "': PluginImpl has no run() method:
 const ERR_PLUGIN_RUN_NOT_CALLABLE_PREFIX: &str = "Plugin '";
 const ERR_PLUGIN_RUN_NOT_CALLABLE_SUFFIX: &str = "': PluginImpl.run exists but is not callable";
 
+const ERR_INVALID_PLUGIN_PARAMETERS_PREFIX: &str = "Plugin '";
+const ERR_INVALID_PLUGIN_PARAMETERS_MID: &str = "': invalid PLUGIN_PARAMETERS: ";
+
+const ERR_INVALID_PLUGIN_RETRY_PREFIX: &str = "Plugin '";
+const ERR_INVALID_PLUGIN_RETRY_MID: &str = "': invalid PLUGIN_RETRY: ";
+
+const ERR_INVALID_PLUGIN_TIMEOUT_PREFIX: &str = "Plugin '";
+const ERR_INVALID_PLUGIN_TIMEOUT_MID: &str = "': invalid ";
+
+const ERR_INVALID_PLUGIN_TRIGGER_FILTER_PREFIX: &str = "Plugin '";
+const ERR_INVALID_PLUGIN_TRIGGER_FILTER_MID: &str = "': invalid PLUGIN_TRIGGER_FILTER: ";
+
+const ERR_INVALID_PLUGIN_WRITE_ACCESS_PREFIX: &str = "Plugin '";
+const ERR_INVALID_PLUGIN_WRITE_ACCESS_MID: &str = "': PLUGIN_WRITE_ACCESS must be True or False: ";
+
 const WARN_MISSING_PLUGIN_NAME_PREFIX: &str = "Plugin '";
 const WARN_MISSING_PLUGIN_NAME_SUFFIX: &str =
     "': missing PLUGIN_NAME constant (will use filename fallback)";
@@ -125,14 +151,113 @@ fn prepare_module_import<'py>(
     Ok((module, module_name))
 }
 
+/// Optionale Konstanten eines Plugin-Moduls.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModuleConstants {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub trigger: Option<String>,
+    pub version: Option<String>,
+    /// Einträge aus `PLUGIN_PARAMETERS` als (Name, Beschreibung als JSON) in
+    /// der Reihenfolge der Deklaration.
+    pub parameters: Option<Vec<(String, serde_json::Value)>>,
+    /// `PLUGIN_RETRY` als JSON.
+    pub retry: Option<serde_json::Value>,
+    /// `PLUGIN_TIMEOUT` in Sekunden.
+    pub timeout: Option<f64>,
+    /// `PLUGIN_STALL_TIMEOUT` in Sekunden.
+    pub stall_timeout: Option<f64>,
+    /// `PLUGIN_WRITE_ACCESS`: Schreibrecht auf `/data`.
+    pub write_access: Option<bool>,
+    /// `PLUGIN_TRIGGER_FILTER` als JSON.
+    pub trigger_filter: Option<serde_json::Value>,
+}
+
+/// Liest `PLUGIN_PARAMETERS` als Liste von (Name, JSON-Wert) aus.
+///
+/// Die Werte laufen durch Pythons `json`-Modul; ein Dictionary mit Werten,
+/// die sich nicht als JSON darstellen lassen, ist ein Fehler.
+fn read_parameters(
+    py: Python<'_>,
+    module: &Bound<'_, PyModule>,
+    module_name: &str,
+) -> Result<Option<Vec<(String, serde_json::Value)>>, Error> {
+    let Ok(raw) = module.getattr(PY_ATTR_PLUGIN_PARAMETERS) else {
+        return Ok(None);
+    };
+    let invalid = |reason: String| {
+        Error::CustomError(format!(
+            "{ERR_INVALID_PLUGIN_PARAMETERS_PREFIX}{module_name}{ERR_INVALID_PLUGIN_PARAMETERS_MID}{reason}"
+        ))
+    };
+    let dict = raw
+        .cast::<PyDict>()
+        .map_err(|_| invalid("expected a dict of parameter name to description".to_string()))?;
+    let json = py
+        .import(PY_MOD_JSON)
+        .map_err(|e| invalid(format!("{PY_IMPORT_JSON_FAILED_PREFIX}{e}")))?;
+
+    let mut parameters = Vec::with_capacity(dict.len());
+    for (key, value) in dict.iter() {
+        let name: String = key
+            .extract()
+            .map_err(|_| invalid(format!("parameter name {key} is not a string")))?;
+        let text: String = json
+            .call_method1(PY_JSON_DUMPS, (value,))
+            .and_then(|v| v.extract())
+            .map_err(|e| invalid(format!("parameter '{name}' is not JSON serializable: {e}")))?;
+        let value =
+            serde_json::from_str(&text).map_err(|e| invalid(format!("parameter '{name}': {e}")))?;
+        parameters.push((name, value));
+    }
+    Ok(Some(parameters))
+}
+
+/// Liest eine Konstante über Pythons `json`-Modul als JSON-Wert aus.
+fn read_json(
+    py: Python<'_>,
+    module: &Bound<'_, PyModule>,
+    attr: &str,
+    invalid: impl Fn(String) -> Error,
+) -> Result<Option<serde_json::Value>, Error> {
+    let Ok(raw) = module.getattr(attr) else {
+        return Ok(None);
+    };
+    let text: String = py
+        .import(PY_MOD_JSON)
+        .map_err(|e| invalid(format!("{PY_IMPORT_JSON_FAILED_PREFIX}{e}")))?
+        .call_method1(PY_JSON_DUMPS, (raw,))
+        .and_then(|v| v.extract())
+        .map_err(|e| invalid(format!("not JSON serializable: {e}")))?;
+    serde_json::from_str(&text)
+        .map(Some)
+        .map_err(|e| invalid(e.to_string()))
+}
+
+/// Liest eine Zeitangabe in Sekunden (`int` oder `float`) aus.
+fn read_seconds(
+    module: &Bound<'_, PyModule>,
+    module_name: &str,
+    attr: &str,
+) -> Result<Option<f64>, Error> {
+    let Ok(raw) = module.getattr(attr) else {
+        return Ok(None);
+    };
+    raw.extract::<f64>().map(Some).map_err(|e| {
+        Error::CustomError(format!(
+            "{ERR_INVALID_PLUGIN_TIMEOUT_PREFIX}{module_name}{ERR_INVALID_PLUGIN_TIMEOUT_MID}{attr}: expected seconds: {e}"
+        ))
+    })
+}
+
 /// Liest optionale Konstanten aus dem Python-Modul aus.
 ///
 /// Diese Funktion ist bewusst tolerant:
 /// Fehlt eine Konstante, wird `None` zurückgegeben statt eines Fehlers.
-/// So können Fallback-Werte verwendet werden.
-pub fn read_module_constants(
-    plugin_file: &Path,
-) -> Result<(Option<String>, Option<String>, Option<String>), Error> {
+/// So können Fallback-Werte verwendet werden. Nur ein vorhandenes, aber
+/// unlesbares `PLUGIN_PARAMETERS`, `PLUGIN_RETRY`, `PLUGIN_TRIGGER_FILTER`,
+/// Zeitlimit oder `PLUGIN_WRITE_ACCESS` ist ein Fehler.
+pub fn read_module_constants(plugin_file: &Path) -> Result<ModuleConstants, Error> {
     // im Wesentlichen Aktion in Python (Closure)
     Python::attach(|py| {
         // Vorbereitung Plugin-Import in Rust
@@ -154,12 +279,62 @@ pub fn read_module_constants(
             .ok()
             .and_then(|v| v.extract::<String>().ok());
 
+        let version = module
+            .getattr(PY_ATTR_PLUGIN_VERSION)
+            .ok()
+            .and_then(|v| v.extract::<String>().ok());
+
+        let parameters = read_parameters(py, &module, &module_name)?;
+        let retry = read_json(py, &module, PY_ATTR_PLUGIN_RETRY, |reason| {
+            Error::CustomError(format!(
+                "{ERR_INVALID_PLUGIN_RETRY_PREFIX}{module_name}{ERR_INVALID_PLUGIN_RETRY_MID}{reason}"
+            ))
+        })?;
+        let trigger_filter = read_json(py, &module, PY_ATTR_PLUGIN_TRIGGER_FILTER, |reason| {
+            Error::CustomError(format!(
+                "{ERR_INVALID_PLUGIN_TRIGGER_FILTER_PREFIX}{module_name}{ERR_INVALID_PLUGIN_TRIGGER_FILTER_MID}{reason}"
+            ))
+        })?;
+        let timeout = read_seconds(&module, &module_name, PY_ATTR_PLUGIN_TIMEOUT)?;
+        let stall_timeout = read_seconds(&module, &module_name, PY_ATTR_PLUGIN_STALL_TIMEOUT)?;
+        let write_access = module
+            .getattr(PY_ATTR_PLUGIN_WRITE_ACCESS)
+            .ok()
+            .map(|v| v.extract::<bool>())
+            .transpose()
+            .map_err(|e| {
+                Error::CustomError(format!(
+                    "{ERR_INVALID_PLUGIN_WRITE_ACCESS_PREFIX}{module_name}{ERR_INVALID_PLUGIN_WRITE_ACCESS_MID}{e}"
+                ))
+            })?;
+
         debug!(
-            "read_module_constants {}: name={:?} description={:?} trigger={:?}",
-            module_name, name, description, trigger
+            "read_module_constants {}: name={:?} description={:?} trigger={:?} version={:?} parameters={:?} retry={:?} trigger_filter={:?} timeout={:?} stall_timeout={:?} write_access={:?}",
+            module_name,
+            name,
+            description,
+            trigger,
+            version,
+            parameters,
+            retry,
+            trigger_filter,
+            timeout,
+            stall_timeout,
+            write_access
         );
 
-        Ok((name, description, trigger))
+        Ok(ModuleConstants {
+            name,
+            description,
+            trigger,
+            version,
+            parameters,
+            retry,
+            timeout,
+            stall_timeout,
+            write_access,
+            trigger_filter,
+        })
     })
 }
 
@@ -0,0 +1,424 @@
+//! Warteschlange zwischen Auslösern und Python-Prozessen.
+//!
+//! Jeder Start (API, Bulk, Dateiereignis, Metadaten-Trigger, Schedule) legt
+//! einen Job an; der Dispatcher startet Jobs erst, wenn ein Worker frei ist und
+//! das Plugin sein `max_concurrency` nicht ausschöpft. Manuelle Starts kommen
+//! vor ausgelösten, innerhalb einer Priorität gilt die Reihenfolge des Eintreffens.
+//! Wiederholungen gescheiterter Läufe warten außerdem bis `not_before`.
+
+use crate::plugin_manager::manager::{
+    InstanceState, PluginManager, build_started_instance_core_with_data,
+};
+use crate::plugin_manager::pipeline::StepRef;
+use crate::plugin_manager::retry::RetryPolicy;
+use crate::plugin_manager::runs::{self, RunOutcome};
+use crate::plugin_manager::sandbox::SandboxPolicy;
+use crate::plugin_manager::watchdog::RunLimits;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tracing::{debug, warn};
+
+/// Gleichzeitig laufende Instanzen, wenn `plugins.yaml` kein `max_workers` setzt.
+pub const DEFAULT_MAX_WORKERS: usize = 4;
+
+/// Priorität eines Jobs; höhere Werte werden zuerst gestartet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobPriority {
+    /// Ausgelöst durch Ereignisse oder Schedules.
+    Triggered,
+    /// Über die API gestartet.
+    Manual,
+}
+
+/// Ein wartender Start.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueuedJob {
+    pub instance_id: u64,
+    pub plugin_name: String,
+    pub priority: JobPriority,
+    pub queued_at: DateTime<Utc>,
+    pub entry_path: Option<String>,
+    /// Nummer des Versuchs, ab 1.
+    pub attempt: u32,
+    /// Erster Versuch, falls dieser Job eine Wiederholung ist.
+    pub retry_of: Option<u64>,
+    /// Frühester Start einer Wiederholung.
+    pub not_before: Option<DateTime<Utc>>,
+    /// Pipeline-Schritt, den dieser Job ausführt.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pipeline: Option<StepRef>,
+    /// Regeln aus `PLUGIN_RETRY` zum Zeitpunkt des ersten Versuchs.
+    #[serde(skip)]
+    pub retry: Option<RetryPolicy>,
+    /// Zeitlimits des Plugins zum Zeitpunkt des ersten Versuchs.
+    #[serde(skip)]
+    pub limits: RunLimits,
+    /// Abschottung des Runners zum Zeitpunkt des ersten Versuchs.
+    #[serde(skip)]
+    pub sandbox: SandboxPolicy,
+    #[serde(skip)]
+    pub plugin_index: usize,
+    #[serde(skip)]
+    pub plugin_path: PathBuf,
+    /// Daten für `plugin.run(data)`.
+    #[serde(skip)]
+    pub data: String,
+}
+
+impl QueuedJob {
+    pub fn new(
+        instance_id: u64,
+        plugin_index: usize,
+        plugin_name: String,
+        plugin_path: PathBuf,
+        data: String,
+        priority: JobPriority,
+    ) -> Self {
+        QueuedJob {
+            instance_id,
+            plugin_name,
+            priority,
+            queued_at: Utc::now(),
+            entry_path: runs::entry_path(&runs::parse_payload(&data)),
+            attempt: 1,
+            retry_of: None,
+            not_before: None,
+            pipeline: None,
+            retry: None,
+            limits: RunLimits::default(),
+            sandbox: SandboxPolicy::default(),
+            plugin_index,
+            plugin_path,
+            data,
+        }
+    }
+
+    pub fn with_retry(mut self, retry: Option<RetryPolicy>) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn with_limits(mut self, limits: RunLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn with_sandbox(mut self, sandbox: SandboxPolicy) -> Self {
+        self.sandbox = sandbox;
+        self
+    }
+
+    pub fn with_pipeline(mut self, pipeline: Option<StepRef>) -> Self {
+        self.pipeline = pipeline;
+        self
+    }
+
+    /// Nächster Versuch mit denselben Daten, frühestens nach `delay`.
+    pub fn next_attempt(&self, instance_id: u64, delay: Duration) -> QueuedJob {
+        let queued_at = Utc::now();
+        QueuedJob {
+            instance_id,
+            queued_at,
+            attempt: self.attempt + 1,
+            retry_of: self.retry_of.or(Some(self.instance_id)),
+            not_before: Some(
+                queued_at + chrono::Duration::from_std(delay).unwrap_or(chrono::Duration::MAX),
+            ),
+            ..self.clone()
+        }
+    }
+
+    fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.not_before.is_none_or(|at| at <= now)
+    }
+}
+
+/// Zustand der Warteschlange für `GET /plugin/queue`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueueSnapshot {
+    pub max_workers: usize,
+    /// Laufende Instanzen insgesamt.
+    pub active: usize,
+    /// Laufende Instanzen je Plugin.
+    pub running: BTreeMap<String, usize>,
+    /// `max_concurrency` je Plugin, soweit gesetzt.
+    pub limits: BTreeMap<String, usize>,
+    /// Wartende Jobs in der Reihenfolge, in der sie gestartet würden.
+    pub jobs: Vec<QueuedJob>,
+}
+
+#[derive(Debug)]
+struct QueueState {
+    max_workers: usize,
+    limits: HashMap<String, usize>,
+    /// Wartende Jobs, nach Priorität und Eingang sortiert.
+    jobs: Vec<QueuedJob>,
+    running: HashMap<String, usize>,
+    active: usize,
+}
+
+impl QueueState {
+    fn has_capacity(&self, plugin_name: &str) -> bool {
+        let running = self.running.get(plugin_name).copied().unwrap_or(0);
+        self.limits
+            .get(plugin_name)
+            .is_none_or(|&limit| running < limit)
+    }
+}
+
+/// Gemeinsam genutzte Warteschlange; Klone teilen sich denselben Zustand.
+#[derive(Debug, Clone)]
+pub struct JobQueue {
+    state: Arc<Mutex<QueueState>>,
+    notify: Arc<Notify>,
+}
+
+impl Default for JobQueue {
+    fn default() -> Self {
+        JobQueue::new(DEFAULT_MAX_WORKERS)
+    }
+}
+
+impl JobQueue {
+    pub fn new(max_workers: usize) -> Self {
+        JobQueue {
+            state: Arc::new(Mutex::new(QueueState {
+                max_workers,
+                limits: HashMap::new(),
+                jobs: Vec::new(),
+                running: HashMap::new(),
+                active: 0,
+            })),
+            notify: Arc::new(Notify::new()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, QueueState> {
+        self.state.lock().expect("job queue lock")
+    }
+
+    /// Setzt die Grenzen aus `plugins.yaml`; laufende Instanzen bleiben.
+    pub fn configure(&self, max_workers: usize, limits: HashMap<String, usize>) {
+        {
+            let mut state = self.lock();
+            state.max_workers = max_workers;
+            state.limits = limits;
+        }
+        self.notify.notify_one();
+    }
+
+    /// Reiht einen Job hinter allen Jobs gleicher oder höherer Priorität ein.
+    pub fn push(&self, job: QueuedJob) {
+        {
+            let mut state = self.lock();
+            let position = state
+                .jobs
+                .iter()
+                .position(|queued| queued.priority < job.priority)
+                .unwrap_or(state.jobs.len());
+            state.jobs.insert(position, job);
+        }
+        self.notify.notify_one();
+    }
+
+    pub fn contains(&self, instance_id: u64) -> bool {
+        self.lock()
+            .jobs
+            .iter()
+            .any(|job| job.instance_id == instance_id)
+    }
+
+    /// Nimmt einen wartenden Job heraus.
+    pub fn cancel(&self, instance_id: u64) -> Option<QueuedJob> {
+        let mut state = self.lock();
+        let position = state
+            .jobs
+            .iter()
+            .position(|job| job.instance_id == instance_id)?;
+        Some(state.jobs.remove(position))
+    }
+
+    /// Entfernt alle wartenden Jobs, z.B. vor einem Rescan der Plugins.
+    pub fn drain(&self) -> Vec<QueuedJob> {
+        std::mem::take(&mut self.lock().jobs)
+    }
+
+    /// Nächster startbarer Job. Er belegt sofort einen Worker und einen Platz
+    /// seines Plugins, bis [`JobQueue::release`] ihn freigibt. Jobs eines
+    /// ausgelasteten Plugins und noch nicht fällige Wiederholungen halten
+    /// nachfolgende Jobs nicht auf.
+    pub fn next_ready(&self) -> Option<QueuedJob> {
+        let now = Utc::now();
+        let mut state = self.lock();
+        if state.active >= state.max_workers {
+            return None;
+        }
+        let position = state
+            .jobs
+            .iter()
+            .position(|job| job.is_due(now) && state.has_capacity(&job.plugin_name))?;
+        let job = state.jobs.remove(position);
+        state.active += 1;
+        *state.running.entry(job.plugin_name.clone()).or_insert(0) += 1;
+        Some(job)
+    }
+
+    /// Gibt den Platz eines beendeten oder gescheiterten Jobs frei.
+    pub fn release(&self, plugin_name: &str) {
+        {
+            let mut state = self.lock();
+            state.active = state.active.saturating_sub(1);
+            if let Some(running) = state.running.get_mut(plugin_name) {
+                *running = running.saturating_sub(1);
+                if *running == 0 {
+                    state.running.remove(plugin_name);
+                }
+            }
+        }
+        self.notify.notify_one();
+    }
+
+    pub fn snapshot(&self) -> QueueSnapshot {
+        let state = self.lock();
+        QueueSnapshot {
+            max_workers: state.max_workers,
+            active: state.active,
+            running: state.running.iter().map(|(k, v)| (k.clone(), *v)).collect(),
+            limits: state.limits.iter().map(|(k, v)| (k.clone(), *v)).collect(),
+            jobs: state.jobs.clone(),
+        }
+    }
+
+    /// Frühester Zeitpunkt, zu dem eine wartende Wiederholung fällig wird.
+    pub fn next_due(&self) -> Option<DateTime<Utc>> {
+        let now = Utc::now();
+        self.lock()
+            .jobs
+            .iter()
+            .filter_map(|job| job.not_before)
+            .filter(|&at| at > now)
+            .min()
+    }
+
+    /// Wartet, bis sich an Jobs, Grenzen oder belegten Plätzen etwas ändert.
+    pub async fn changed(&self) {
+        self.notify.notified().await;
+    }
+}
+
+/// Startet wartende Jobs, sobald Plätze frei werden. Läuft für die ganze
+/// Lebensdauer des Backends.
+pub async fn run_dispatcher(plugin_manager: Arc<tokio::sync::Mutex<PluginManager>>) {
+    let queue = plugin_manager.lock().await.queue().clone();
+    loop {
+        while let Some(job) = queue.next_ready() {
+            tokio::spawn(run_job(plugin_manager.clone(), queue.clone(), job));
+        }
+        match queue.next_due() {
+            Some(at) => {
+                let wait = (at - Utc::now()).to_std().unwrap_or_default();
+                let _ = tokio::time::timeout(wait, queue.changed()).await;
+            }
+            None => queue.changed().await,
+        }
+    }
+}
+
+/// Startet den Runner eines Jobs und hält dessen Platz, bis der Actor endet.
+/// Scheitert der Lauf und erlaubt `PLUGIN_RETRY` es, wird danach der nächste
+/// Versuch eingereiht, sonst geht eine Pipeline mit dem nächsten Schritt weiter.
+async fn run_job(
+    plugin_manager: Arc<tokio::sync::Mutex<PluginManager>>,
+    queue: JobQueue,
+    job: QueuedJob,
+) {
+    let instance_id = job.instance_id;
+    debug!(
+        "dispatching queued instance {} (attempt {}) of plugin '{}'",
+        instance_id, job.attempt, job.plugin_name
+    );
+    let retry_delay = |outcome: &RunOutcome| {
+        job.retry
+            .as_ref()
+            .and_then(|policy| policy.retry_delay(job.attempt, outcome))
+    };
+    // Bis zum Start kann das Plugin deaktiviert oder neu eingelesen worden sein.
+    if !plugin_manager.lock().await.job_plugin_available(&job) {
+        plugin_manager.lock().await.finish_queued_instance(
+            job.clone(),
+            RunOutcome::failed(
+                InstanceState::Failed,
+                format!("plugin '{}' is no longer available", job.plugin_name),
+            ),
+        );
+        queue.release(&job.plugin_name);
+        return;
+    }
+    let built = build_started_instance_core_with_data(
+        job.plugin_index,
+        job.plugin_name.clone(),
+        &job.plugin_path,
+        instance_id,
+        job.data.clone(),
+        job.limits,
+        job.sandbox.clone(),
+    )
+    .await;
+    let delay = match built {
+        Ok(handle) => {
+            let mut status_rx = handle.status_rx.clone();
+            let mut outcome_rx = handle.outcome_rx.clone();
+            let committed = plugin_manager.lock().await.commit_queued_job(&job, handle);
+            match committed {
+                Ok(()) => {
+                    let outcome = runs::wait_for_outcome(&status_rx, &mut outcome_rx).await;
+                    // Der Actor hält den Sender, bis der Prozess beendet ist.
+                    while status_rx.changed().await.is_ok() {}
+                    let delay = retry_delay(&outcome);
+                    if delay.is_none() {
+                        plugin_manager
+                            .lock()
+                            .await
+                            .pipeline_step_finished(&job, &outcome);
+                    }
+                    delay
+                }
+                Err(e) => {
+                    warn!("failed to commit queued instance {instance_id}: {e:?}");
+                    plugin_manager.lock().await.pipeline_step_finished(
+                        &job,
+                        &RunOutcome::failed(
+                            InstanceState::Failed,
+                            format!("failed to start: {e:?}"),
+                        ),
+                    );
+                    None
+                }
+            }
+        }
+        Err(e) => {
+            warn!("failed to start queued instance {instance_id}: {e:?}");
+            let mut outcome =
+                RunOutcome::failed(InstanceState::Failed, format!("failed to start: {e:?}"));
+            let delay = retry_delay(&outcome);
+            if delay.is_some() {
+                outcome.state = InstanceState::Retrying;
+            }
+            plugin_manager
+                .lock()
+                .await
+                .finish_queued_instance(job.clone(), outcome);
+            delay
+        }
+    };
+    queue.release(&job.plugin_name);
+    if let Some(delay) = delay {
+        plugin_manager.lock().await.enqueue_retry(&job, delay);
+    }
+}
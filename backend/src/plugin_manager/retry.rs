@@ -0,0 +1,148 @@
+//! Wiederholungen gescheiterter Läufe aus `PLUGIN_RETRY`.
+//!
+//! ```python
+//! PLUGIN_RETRY = {
+//!     "max_attempts": 3,
+//!     "backoff_seconds": 10,
+//!     "backoff_factor": 2,
+//!     "max_backoff_seconds": 300,
+//!     "retry_on": ["crash", "OSError"],
+//! }
+//! ```
+//!
+//! `retry_on` nennt Fehlerklassen: Namen von Python-Exceptions (Unterklassen
+//! zählen mit, `OSError` umfasst also `ConnectionResetError`) oder `crash` für
+//! einen Runner, der ohne Endzustand beendet wurde. Ohne `retry_on` wird jeder
+//! Fehlschlag wiederholt. Überschrittene Zeitlimits (`TimedOut`) werden nur
+//! wiederholt, wenn `retry_on` ausdrücklich `timeout` nennt. Gestoppte Läufe
+//! und abgelehnte Artefakte werden nie wiederholt.
+
+use crate::plugin_manager::manager::InstanceState;
+use crate::plugin_manager::runs::RunOutcome;
+use rocket::serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+/// Fehlerklasse eines Runners, der ohne `exited`-Nachricht endete.
+pub const CRASH_CLASS: &str = "crash";
+
+/// Fehlerklasse eines Laufs, der ein Zeitlimit überschritten hat.
+pub const TIMEOUT_CLASS: &str = "timeout";
+
+/// Obergrenze für `max_attempts`.
+pub const MAX_ATTEMPTS_LIMIT: u32 = 10;
+
+fn default_backoff_seconds() -> f64 {
+    10.0
+}
+
+fn default_backoff_factor() -> f64 {
+    2.0
+}
+
+fn default_max_backoff_seconds() -> f64 {
+    3600.0
+}
+
+/// Wiederholungsregeln eines Plugins.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", deny_unknown_fields)]
+pub struct RetryPolicy {
+    /// Versuche insgesamt, einschließlich des ersten.
+    pub max_attempts: u32,
+    /// Wartezeit vor dem zweiten Versuch.
+    #[serde(default = "default_backoff_seconds")]
+    pub backoff_seconds: f64,
+    /// Faktor, um den jede weitere Wartezeit wächst.
+    #[serde(default = "default_backoff_factor")]
+    pub backoff_factor: f64,
+    #[serde(default = "default_max_backoff_seconds")]
+    pub max_backoff_seconds: f64,
+    /// Wiederholbare Fehlerklassen; `None` heißt alle.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_on: Option<Vec<String>>,
+}
+
+impl RetryPolicy {
+    /// Liest und prüft den Wert von `PLUGIN_RETRY`.
+    pub fn from_declaration(raw: Value) -> Result<Self, String> {
+        if !raw.is_object() {
+            return Err(format!("expected a dict, got {raw}"));
+        }
+        let policy: RetryPolicy = serde_json::from_value(raw).map_err(|e| e.to_string())?;
+        if !(1..=MAX_ATTEMPTS_LIMIT).contains(&policy.max_attempts) {
+            return Err(format!(
+                "max_attempts must be between 1 and {MAX_ATTEMPTS_LIMIT}, got {}",
+                policy.max_attempts
+            ));
+        }
+        if !policy.backoff_seconds.is_finite() || policy.backoff_seconds < 0.0 {
+            return Err(format!(
+                "backoff_seconds must not be negative, got {}",
+                policy.backoff_seconds
+            ));
+        }
+        if !policy.backoff_factor.is_finite() || policy.backoff_factor < 1.0 {
+            return Err(format!(
+                "backoff_factor must be at least 1, got {}",
+                policy.backoff_factor
+            ));
+        }
+        if !policy.max_backoff_seconds.is_finite()
+            || policy.max_backoff_seconds < policy.backoff_seconds
+        {
+            return Err(format!(
+                "max_backoff_seconds must be at least backoff_seconds, got {}",
+                policy.max_backoff_seconds
+            ));
+        }
+        if let Some(classes) = &policy.retry_on
+            && classes.iter().any(|c| c.trim().is_empty())
+        {
+            return Err("retry_on must not contain empty class names".to_string());
+        }
+        Ok(policy)
+    }
+
+    /// Wartezeit nach dem gescheiterten Versuch `attempt` (ab 1).
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let seconds = self.backoff_seconds * self.backoff_factor.powi(exponent);
+        Duration::from_secs_f64(seconds.min(self.max_backoff_seconds))
+    }
+
+    /// Ob ein Fehler dieser Klassen wiederholt wird.
+    pub fn retries_class(&self, classes: &[String]) -> bool {
+        match &self.retry_on {
+            None => true,
+            Some(retry_on) => classes.iter().any(|c| retry_on.contains(c)),
+        }
+    }
+
+    /// Wartezeit bis zum nächsten Versuch, falls der Versuch `attempt` mit
+    /// `outcome` wiederholt wird.
+    pub fn retry_delay(&self, attempt: u32, outcome: &RunOutcome) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+        let retried = match outcome.state {
+            InstanceState::Failed => self.retries_class(&failure_classes(outcome)),
+            InstanceState::TimedOut => self
+                .retry_on
+                .as_ref()
+                .is_some_and(|retry_on| retry_on.iter().any(|c| c == TIMEOUT_CLASS)),
+            _ => false,
+        };
+        retried.then(|| self.delay(attempt))
+    }
+}
+
+/// Fehlerklassen eines gescheiterten Laufs: die Exception mit ihren
+/// Basisklassen, sonst [`CRASH_CLASS`].
+pub fn failure_classes(outcome: &RunOutcome) -> Vec<String> {
+    if outcome.error_classes.is_empty() {
+        vec![CRASH_CLASS.to_string()]
+    } else {
+        outcome.error_classes.clone()
+    }
+}
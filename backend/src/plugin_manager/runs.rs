@@ -0,0 +1,239 @@
+use crate::error::StorageError;
+use crate::plugin_manager::manager::InstanceState;
+use crate::plugin_manager::retry::RetryPolicy;
+use crate::storage::artifacts::{self, ArtifactProvenance};
+use crate::storage::models::NewPluginRun;
+use crate::storage::storage_manager::StorageManager;
+use serde_json::Value;
+use std::path::PathBuf;
+use tokio::sync::{mpsc, watch};
+use tracing::warn;
+
+/// Läufe je Seite, wenn `GET /plugin/instances` kein `page_size` bekommt.
+pub const DEFAULT_PAGE_SIZE: u32 = 100;
+
+/// Ergebnis einer Instanz, festgehalten beim ersten Endzustand.
+///
+/// Spätere Zustandswechsel (z.B. `Completed` nach einem bestätigten Stop, wenn der
+/// Runner danach regulär endet) ändern es nicht mehr.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunOutcome {
+    pub state: InstanceState,
+    /// `result` aus der `exited`-Nachricht des Runners.
+    pub result: Option<Value>,
+    pub error: Option<String>,
+    pub trace: Option<String>,
+    /// Exception aus `plugin.run` mit ihren Basisklassen, z.B.
+    /// `["ConnectionResetError", "ConnectionError", "OSError", "Exception"]`.
+    pub error_classes: Vec<String>,
+}
+
+impl RunOutcome {
+    pub fn new(state: InstanceState) -> Self {
+        RunOutcome {
+            state,
+            result: None,
+            error: None,
+            trace: None,
+            error_classes: Vec::new(),
+        }
+    }
+
+    pub fn failed(state: InstanceState, error: String) -> Self {
+        RunOutcome {
+            error: Some(error),
+            ..RunOutcome::new(state)
+        }
+    }
+}
+
+/// Setzt das Ergebnis, falls noch keins vorliegt.
+pub fn record_outcome(outcome_tx: &watch::Sender<Option<RunOutcome>>, outcome: RunOutcome) {
+    outcome_tx.send_if_modified(|current| {
+        if current.is_some() {
+            return false;
+        }
+        *current = Some(outcome);
+        true
+    });
+}
+
+/// Name eines Zustands, wie er in der API und in `plugin_runs.state` steht.
+pub fn state_name(state: InstanceState) -> String {
+    format!("{state:?}")
+}
+
+/// Daten für `plugin.run(data)` als JSON; anderer Text bleibt ein String.
+pub fn parse_payload(data: &str) -> Value {
+    if data.is_empty() {
+        return Value::Null;
+    }
+    serde_json::from_str(data).unwrap_or_else(|_| Value::String(data.to_string()))
+}
+
+fn string_field(payload: &Value, key: &str) -> Option<String> {
+    payload.get(key).and_then(Value::as_str).map(str::to_string)
+}
+
+/// Aufnahme, auf die sich ein Lauf bezieht.
+///
+/// Manuelle Starts geben `entry_path` an, Metadaten-Trigger `mcap_path`;
+/// Dateiereignisse und Bulk-Starts (`manual` mit `entry_id`) tragen sie in `path`.
+pub fn entry_path(payload: &Value) -> Option<String> {
+    if let Some(path) = string_field(payload, "entry_path").or(string_field(payload, "mcap_path")) {
+        return Some(path);
+    }
+    match payload.get("event").and_then(Value::as_str) {
+        Some("created" | "updated" | "deleted") => string_field(payload, "path"),
+        Some("manual") if payload.get("entry_id").is_some() => string_field(payload, "path"),
+        _ => None,
+    }
+}
+
+/// Auslöser eines Laufs: das Ereignis aus den Daten, sonst der Trigger des
+/// Plugins (z.B. `on_entry_update`) für Metadaten-Trigger mit `mcap_path` und
+/// `manual` für API-Starts.
+pub fn run_trigger(payload: &Value, plugin_trigger: &str) -> String {
+    let trigger = match payload.get("event").and_then(Value::as_str) {
+        Some("created") => "on_entry_create",
+        Some("updated") => "on_entry_update",
+        Some("deleted") => "on_entry_delete",
+        Some("schedule") => "on_schedule",
+        Some(_) => "manual",
+        None if payload.get("mcap_path").is_some() => plugin_trigger,
+        None => "manual",
+    };
+    trigger.to_string()
+}
+
+/// Wartet auf das Ergebnis einer Instanz. Endet der Actor ohne eines, gilt der
+/// Lauf als gescheitert.
+pub async fn wait_for_outcome(
+    status_rx: &watch::Receiver<InstanceState>,
+    outcome_rx: &mut watch::Receiver<Option<RunOutcome>>,
+) -> RunOutcome {
+    loop {
+        if let Some(outcome) = outcome_rx.borrow_and_update().clone() {
+            return outcome;
+        }
+        if outcome_rx.changed().await.is_err() {
+            let state = match *status_rx.borrow() {
+                InstanceState::Running | InstanceState::Paused => InstanceState::Failed,
+                state => state,
+            };
+            return RunOutcome::failed(state, "runner ended without final state".to_string());
+        }
+    }
+}
+
+/// Schreibt den Lauf beim Start in `plugin_runs` und trägt das Ergebnis nach,
+/// sobald der Actor eines festhält oder endet. Artefakte eines erfolgreichen
+/// Laufs werden vorher übernommen, Dateien nur unterhalb von `artifact_roots`;
+/// ein Fehlschlag, den `retry` wiederholt, endet als `Retrying`.
+pub async fn record_instance_run(
+    storage: StorageManager,
+    run: NewPluginRun,
+    status_rx: watch::Receiver<InstanceState>,
+    mut outcome_rx: watch::Receiver<Option<RunOutcome>>,
+    retry: Option<RetryPolicy>,
+    files: ArtifactFiles,
+) {
+    let instance_id = run.instance_id;
+    let provenance = ArtifactProvenance {
+        plugin_name: run.plugin_name.clone(),
+        plugin_version: run.plugin_version.clone(),
+        instance_id,
+    };
+    let run_entry_path = run.entry_path.clone();
+    let attempt = run.attempt.max(1) as u32;
+    if let Err(e) = storage.insert_plugin_run(run).await {
+        warn!("failed to record start of plugin instance {instance_id}: {e:?}");
+    }
+    let mut outcome = wait_for_outcome(&status_rx, &mut outcome_rx).await;
+    // Dieselbe Entscheidung trifft der Dispatcher, der den nächsten Versuch einreiht.
+    if retry.is_some_and(|policy| policy.retry_delay(attempt, &outcome).is_some()) {
+        outcome.state = InstanceState::Retrying;
+    }
+    let outcome = apply_artifacts(&storage, provenance, run_entry_path, &files, outcome).await;
+    // erst jetzt darf das Arbeitsverzeichnis weg
+    drop(files);
+    if let Err(e) = storage.finish_plugin_run(instance_id, outcome).await {
+        warn!("failed to record end of plugin instance {instance_id}: {e:?}");
+    }
+}
+
+/// Wo Datei-Artefakte eines Laufs liegen dürfen.
+#[derive(Debug, Default)]
+pub struct ArtifactFiles {
+    /// Erlaubte Wurzeln, siehe [`artifacts::resolve_files`].
+    pub roots: Vec<PathBuf>,
+    /// Arbeitsverzeichnis der Instanz; Dateien von dort werden vor dessen
+    /// Entfernen nach [`StorageManager::artifact_dir`] kopiert.
+    pub workdir: Option<PathBuf>,
+    /// Hält das Arbeitsverzeichnis, bis die Artefakte übernommen sind.
+    pub workdir_hold: Option<mpsc::Sender<()>>,
+}
+
+/// Übernimmt die Artefakte aus dem Ergebnis eines abgeschlossenen Laufs.
+/// Ungültige Artefakte werden verworfen und lassen den Lauf scheitern.
+async fn apply_artifacts(
+    storage: &StorageManager,
+    provenance: ArtifactProvenance,
+    entry_path: Option<String>,
+    files: &ArtifactFiles,
+    outcome: RunOutcome,
+) -> RunOutcome {
+    if outcome.state != InstanceState::Completed {
+        return outcome;
+    }
+    let Some(result) = outcome.result.as_ref() else {
+        return outcome;
+    };
+    let instance_id = provenance.instance_id;
+    let kept_dir = storage.artifact_dir(instance_id);
+    let applied = async {
+        let Some(mut plugin_result) = artifacts::parse_result(result)? else {
+            return Ok(());
+        };
+        artifacts::resolve_files(&mut plugin_result.artifacts, &files.roots).await?;
+        if let Some(workdir) = &files.workdir {
+            artifacts::keep_workdir_files(&mut plugin_result.artifacts, workdir, &kept_dir).await?;
+        }
+        let entry_id = match (plugin_result.entry_id, entry_path) {
+            (Some(entry_id), _) => entry_id,
+            (None, Some(path)) => {
+                storage
+                    .get_entry_by_path(path.clone(), 0)
+                    .await?
+                    .ok_or_else(|| StorageError::NotFound(format!("no entry for path '{path}'")))?
+                    .id
+            }
+            (None, None) => {
+                return Err(StorageError::ValidationError(
+                    "entry_id is required for runs without an entry".to_string(),
+                ));
+            }
+        };
+        storage
+            .apply_plugin_artifacts(entry_id, provenance, plugin_result.artifacts)
+            .await
+            .map(|_| ())
+    }
+    .await;
+    match applied {
+        Ok(()) => outcome,
+        Err(e) => {
+            let reason = match e {
+                StorageError::ValidationError(msg) | StorageError::NotFound(msg) => msg,
+                other => format!("{other:?}"),
+            };
+            warn!("rejected artifacts of plugin instance {instance_id}: {reason}");
+            let _ = tokio::fs::remove_dir_all(&kept_dir).await;
+            RunOutcome {
+                state: InstanceState::Failed,
+                error: Some(format!("plugin artifacts rejected: {reason}")),
+                ..outcome
+            }
+        }
+    }
+}
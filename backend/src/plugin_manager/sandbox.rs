@@ -0,0 +1,493 @@
+//! Ressourcengrenzen und Abschottung der Runner-Prozesse.
+//!
+//! ```yaml
+//! sandbox:
+//!   scratch_dir: /tmp/catalog-plugins      # Arbeitsverzeichnisse der Instanzen
+//!   cgroup_root: /sys/fs/cgroup/plugins    # optional, delegierter cgroup-v2-Baum
+//!   defaults:
+//!     memory_mb: 2048
+//!     max_open_files: 256
+//! plugins:
+//!   - name: compress
+//!     sandbox:
+//!       max_file_mb: 20480
+//!       network: deny
+//! ```
+//!
+//! Ist `cgroup_root` beschreibbar, bekommt jede Instanz dort eine eigene
+//! cgroup für Speicher (`memory_mb`) und CPU-Anteil (`cpus`); sonst begrenzt
+//! `RLIMIT_AS` den Speicher und `cpus` entfällt. CPU-Zeit, offene Dateien und
+//! Dateigröße sind immer rlimits und gelten damit auch für Kommandos, die ein
+//! Plugin startet.
+//!
+//! Netzwerksperre und schreibgeschütztes `/data` setzt der Kernel durch: der
+//! Runner bekommt vor `exec` einen eigenen Netzwerk-Namespace ohne
+//! Schnittstellen bzw. einen eigenen Mount-Namespace, in dem `/data` nur lesbar
+//! eingebunden ist (siehe [`Isolation`]). Das gilt auch für Kommandos, die ein
+//! Plugin startet. Der Backend-Prozess braucht dafür `CAP_SYS_ADMIN`; fehlt es,
+//! schlägt der Start solcher Instanzen fehl. Schreibrechte auf `/data` bekommt
+//! ein Plugin mit `PLUGIN_WRITE_ACCESS = True`. Jede Instanz läuft in einem
+//! eigenen Verzeichnis unter `scratch_dir`, das nach ihrem Ende gelöscht wird;
+//! Datei-Artefakte von dort werden vorher in das Export-Verzeichnis kopiert.
+
+use rocket::serde::{Deserialize, Serialize};
+use std::ffi::{CStr, CString};
+use std::fs;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::ptr;
+use tokio::process::Command;
+use tracing::warn;
+
+/// Verzeichnis der Aufnahmen; ohne Schreibrecht nur lesbar.
+pub const DATA_DIR: &str = "/data";
+
+/// Verzeichnis unter dem temporären Verzeichnis, falls `scratch_dir` fehlt.
+const DEFAULT_SCRATCH_DIR_NAME: &str = "catalog-plugins";
+
+/// So viele Sekunden nach SIGXCPU folgt SIGKILL.
+const CPU_KILL_GRACE_SECONDS: u64 = 5;
+
+/// Periode für `cpu.max` in Mikrosekunden.
+const CPU_PERIOD_US: u64 = 100_000;
+
+/// Python selbst braucht einige Dateideskriptoren.
+const MIN_OPEN_FILES: u64 = 16;
+
+const MIB: u64 = 1024 * 1024;
+
+/// Netzwerkzugriff eines Plugins.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase")]
+pub enum NetworkMode {
+    #[default]
+    Allow,
+    /// Kein Netzwerk, auch nicht zur Backend-API auf localhost; der Runner
+    /// spricht weiter über stdin/stdout mit dem Manager.
+    Deny,
+}
+
+/// Grenzen eines Plugins; `None` heißt unbegrenzt.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", default, deny_unknown_fields)]
+pub struct SandboxLimits {
+    /// Arbeitsspeicher in MiB.
+    pub memory_mb: Option<u64>,
+    /// CPU-Zeit in Sekunden; danach wird der Prozess beendet.
+    pub cpu_seconds: Option<u64>,
+    /// CPU-Anteil in Kernen, nur mit cgroup.
+    pub cpus: Option<f64>,
+    pub max_open_files: Option<u64>,
+    /// Größte Datei, die geschrieben werden kann, in MiB.
+    pub max_file_mb: Option<u64>,
+    pub network: Option<NetworkMode>,
+}
+
+impl SandboxLimits {
+    /// Eigene Angaben, fehlende aus `defaults`.
+    pub fn with_defaults(&self, defaults: &SandboxLimits) -> SandboxLimits {
+        SandboxLimits {
+            memory_mb: self.memory_mb.or(defaults.memory_mb),
+            cpu_seconds: self.cpu_seconds.or(defaults.cpu_seconds),
+            cpus: self.cpus.or(defaults.cpus),
+            max_open_files: self.max_open_files.or(defaults.max_open_files),
+            max_file_mb: self.max_file_mb.or(defaults.max_file_mb),
+            network: self.network.or(defaults.network),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value) in [
+            ("memory_mb", self.memory_mb),
+            ("cpu_seconds", self.cpu_seconds),
+            ("max_file_mb", self.max_file_mb),
+        ] {
+            if value == Some(0) {
+                return Err(format!("{name} must be at least 1"));
+            }
+        }
+        if let Some(files) = self.max_open_files
+            && files < MIN_OPEN_FILES
+        {
+            return Err(format!(
+                "max_open_files must be at least {MIN_OPEN_FILES}, got {files}"
+            ));
+        }
+        if let Some(cpus) = self.cpus
+            && !(cpus.is_finite() && cpus > 0.0)
+        {
+            return Err(format!("cpus must be a positive number, got {cpus}"));
+        }
+        Ok(())
+    }
+
+    pub fn network(&self) -> NetworkMode {
+        self.network.unwrap_or_default()
+    }
+}
+
+/// Abschnitt `sandbox` in `plugins.yaml`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(crate = "rocket::serde", default, deny_unknown_fields)]
+pub struct SandboxSettings {
+    /// Wurzel der Arbeitsverzeichnisse; ohne Angabe unter dem temporären
+    /// Verzeichnis des Systems.
+    pub scratch_dir: Option<PathBuf>,
+    /// Delegierter cgroup-v2-Baum, unter dem Instanzen eigene cgroups bekommen.
+    pub cgroup_root: Option<PathBuf>,
+    /// Grenzen für Plugins ohne eigene Angabe.
+    pub defaults: SandboxLimits,
+}
+
+impl SandboxSettings {
+    pub fn scratch_dir(&self) -> PathBuf {
+        self.scratch_dir
+            .clone()
+            .unwrap_or_else(|| std::env::temp_dir().join(DEFAULT_SCRATCH_DIR_NAME))
+    }
+}
+
+/// Ressource eines rlimits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resource {
+    AddressSpace,
+    CpuTime,
+    OpenFiles,
+    FileSize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rlimit {
+    pub resource: Resource,
+    pub soft: u64,
+    pub hard: u64,
+}
+
+impl Rlimit {
+    fn fixed(resource: Resource, value: u64) -> Self {
+        Rlimit {
+            resource,
+            soft: value,
+            hard: value,
+        }
+    }
+
+    /// Setzt das Limit für den aufrufenden Prozess; ruft nur `setrlimit` auf
+    /// und darf deshalb zwischen `fork` und `exec` laufen.
+    fn apply(&self) -> io::Result<()> {
+        let resource = match self.resource {
+            Resource::AddressSpace => libc::RLIMIT_AS,
+            Resource::CpuTime => libc::RLIMIT_CPU,
+            Resource::OpenFiles => libc::RLIMIT_NOFILE,
+            Resource::FileSize => libc::RLIMIT_FSIZE,
+        };
+        let limit = libc::rlimit {
+            rlim_cur: self.soft as libc::rlim_t,
+            rlim_max: self.hard as libc::rlim_t,
+        };
+        // SAFETY: `limit` ist ein gültiger Zeiger für die Dauer des Aufrufs.
+        if unsafe { libc::setrlimit(resource, &limit) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+/// Eigene Namespaces des Runners.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Isolation {
+    /// Eigener Netzwerk-Namespace, bei `network: deny`.
+    pub private_network: bool,
+    /// Eigener Mount-Namespace mit nur lesbarem `/data`, ohne `PLUGIN_WRITE_ACCESS`.
+    pub read_only_data: bool,
+}
+
+/// Abschottung einer Instanz, festgelegt beim Einreihen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SandboxPolicy {
+    pub limits: SandboxLimits,
+    /// `PLUGIN_WRITE_ACCESS` des Plugins.
+    pub write_access: bool,
+    pub scratch_dir: PathBuf,
+    pub cgroup_root: Option<PathBuf>,
+}
+
+impl Default for SandboxPolicy {
+    fn default() -> Self {
+        SandboxPolicy::new(&SandboxSettings::default(), SandboxLimits::default(), false)
+    }
+}
+
+impl SandboxPolicy {
+    pub fn new(settings: &SandboxSettings, limits: SandboxLimits, write_access: bool) -> Self {
+        SandboxPolicy {
+            limits,
+            write_access,
+            scratch_dir: settings.scratch_dir(),
+            cgroup_root: settings.cgroup_root.clone(),
+        }
+    }
+
+    /// rlimits des Runners; den Speicher begrenzt `RLIMIT_AS` nur ohne cgroup.
+    pub fn rlimits(&self, memory_in_cgroup: bool) -> Vec<Rlimit> {
+        let limits = &self.limits;
+        let mut rlimits = Vec::new();
+        if let Some(memory_mb) = limits.memory_mb
+            && !memory_in_cgroup
+        {
+            rlimits.push(Rlimit::fixed(
+                Resource::AddressSpace,
+                memory_mb.saturating_mul(MIB),
+            ));
+        }
+        if let Some(seconds) = limits.cpu_seconds {
+            rlimits.push(Rlimit {
+                resource: Resource::CpuTime,
+                soft: seconds,
+                hard: seconds.saturating_add(CPU_KILL_GRACE_SECONDS),
+            });
+        }
+        if let Some(files) = limits.max_open_files {
+            rlimits.push(Rlimit::fixed(Resource::OpenFiles, files));
+        }
+        if let Some(file_mb) = limits.max_file_mb {
+            rlimits.push(Rlimit::fixed(
+                Resource::FileSize,
+                file_mb.saturating_mul(MIB),
+            ));
+        }
+        rlimits
+    }
+
+    /// Dateien und Werte der cgroup einer Instanz.
+    pub fn cgroup_files(&self) -> Vec<(&'static str, String)> {
+        let mut files = Vec::new();
+        if let Some(memory_mb) = self.limits.memory_mb {
+            files.push(("memory.max", memory_mb.saturating_mul(MIB).to_string()));
+        }
+        if let Some(cpus) = self.limits.cpus {
+            let quota = ((cpus * CPU_PERIOD_US as f64).round() as u64).max(1000);
+            files.push(("cpu.max", format!("{quota} {CPU_PERIOD_US}")));
+        }
+        files
+    }
+
+    /// Namespaces, die der Runner vor `exec` bekommt.
+    pub fn isolation(&self) -> Isolation {
+        Isolation {
+            private_network: self.limits.network() == NetworkMode::Deny,
+            read_only_data: !self.write_access,
+        }
+    }
+
+    /// Arbeitsverzeichnis einer Instanz unter `scratch_dir`.
+    pub fn workdir(&self, instance_id: u64) -> PathBuf {
+        self.scratch_dir.join(format!("instance-{instance_id}"))
+    }
+
+    /// Legt Arbeitsverzeichnis und, falls möglich, die cgroup der Instanz an.
+    pub fn prepare(&self, instance_id: u64) -> io::Result<PreparedSandbox> {
+        let workdir = self.workdir(instance_id);
+        if workdir.exists() {
+            fs::remove_dir_all(&workdir)?;
+        }
+        fs::create_dir_all(&workdir)?;
+
+        let files = self.cgroup_files();
+        let cgroup = match &self.cgroup_root {
+            Some(root) if !files.is_empty() => {
+                let cgroup = root.join(format!("instance-{instance_id}"));
+                match create_cgroup(&cgroup, &files) {
+                    Ok(()) => Some(cgroup),
+                    Err(e) => {
+                        warn!(
+                            "cgroup {:?} unavailable, falling back to rlimits: {}",
+                            cgroup, e
+                        );
+                        let _ = fs::remove_dir(&cgroup);
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+        if cgroup.is_none() && self.limits.cpus.is_some() {
+            warn!("instance {instance_id}: cpus requires a cgroup and is ignored");
+        }
+        let cgroup_procs = cgroup
+            .as_ref()
+            .map(|dir| CString::new(dir.join("cgroup.procs").as_os_str().as_bytes()))
+            .transpose()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+        let isolation = self.isolation();
+        // ohne /data gibt es nichts zu schützen
+        let read_only_data = (isolation.read_only_data && Path::new(DATA_DIR).is_dir())
+            .then(|| CString::new(DATA_DIR))
+            .transpose()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+        Ok(PreparedSandbox {
+            rlimits: self.rlimits(cgroup.is_some() && self.limits.memory_mb.is_some()),
+            private_network: isolation.private_network,
+            read_only_data,
+            workdir,
+            cgroup,
+            cgroup_procs,
+        })
+    }
+}
+
+fn create_cgroup(dir: &Path, files: &[(&'static str, String)]) -> io::Result<()> {
+    fs::create_dir(dir)?;
+    for (name, value) in files {
+        fs::write(dir.join(name), value)?;
+    }
+    Ok(())
+}
+
+/// Arbeitsverzeichnis, cgroup, Namespaces und rlimits einer gestarteten Instanz.
+#[derive(Debug)]
+pub struct PreparedSandbox {
+    workdir: PathBuf,
+    cgroup: Option<PathBuf>,
+    cgroup_procs: Option<CString>,
+    rlimits: Vec<Rlimit>,
+    private_network: bool,
+    /// Verzeichnis, das im Mount-Namespace des Runners nur lesbar ist.
+    read_only_data: Option<CString>,
+}
+
+impl PreparedSandbox {
+    pub fn workdir(&self) -> &Path {
+        &self.workdir
+    }
+
+    /// Ob der Runner einen eigenen Netzwerk-Namespace bekommt.
+    pub fn private_network(&self) -> bool {
+        self.private_network
+    }
+
+    /// Verzeichnis, das der Runner nur lesen kann.
+    pub fn read_only_data(&self) -> Option<&CStr> {
+        self.read_only_data.as_deref()
+    }
+
+    /// Richtet den Runner-Prozess ein: Arbeitsverzeichnis, Umgebung, und noch
+    /// vor `exec` cgroup, Namespaces und rlimits.
+    pub fn apply(&self, command: &mut Command) {
+        command
+            .current_dir(&self.workdir)
+            .env("HOME", &self.workdir)
+            .env("TMPDIR", &self.workdir);
+
+        let rlimits = self.rlimits.clone();
+        let cgroup_procs = self.cgroup_procs.clone();
+        let private_network = self.private_network;
+        let read_only_data = self.read_only_data.clone();
+        // SAFETY: Der Hook läuft zwischen `fork` und `exec` und nutzt nur
+        // `setrlimit`, `open`, `write`, `close`, `unshare` und `mount` ohne
+        // Allokation.
+        unsafe {
+            command.pre_exec(move || {
+                if let Some(procs) = &cgroup_procs {
+                    enter_cgroup(procs)?;
+                }
+                enter_namespaces(private_network, read_only_data.as_deref())?;
+                for rlimit in &rlimits {
+                    rlimit.apply()?;
+                }
+                Ok(())
+            });
+        }
+    }
+
+    /// Entfernt Arbeitsverzeichnis und cgroup; übrig gebliebene Prozesse der
+    /// cgroup werden vorher beendet.
+    pub fn cleanup(&self) {
+        if let Some(cgroup) = &self.cgroup {
+            let _ = fs::write(cgroup.join("cgroup.kill"), "1");
+            if let Err(e) = fs::remove_dir(cgroup) {
+                warn!("failed to remove cgroup {:?}: {}", cgroup, e);
+            }
+        }
+        if let Err(e) = fs::remove_dir_all(&self.workdir)
+            && e.kind() != io::ErrorKind::NotFound
+        {
+            warn!("failed to remove scratch dir {:?}: {}", self.workdir, e);
+        }
+    }
+}
+
+/// Löst den aufrufenden Prozess in eigene Namespaces: ohne Netzwerk und/oder
+/// mit `read_only` als nur lesbarem Bind-Mount. Mounts bleiben privat und
+/// erreichen den Namespace des Backends nicht.
+fn enter_namespaces(private_network: bool, read_only: Option<&CStr>) -> io::Result<()> {
+    let mut flags = 0;
+    if private_network {
+        flags |= libc::CLONE_NEWNET;
+    }
+    if read_only.is_some() {
+        flags |= libc::CLONE_NEWNS;
+    }
+    if flags == 0 {
+        return Ok(());
+    }
+    let check = |result: libc::c_int| {
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    };
+    // SAFETY: Alle Pfade sind gültige, nullterminierte Strings; Nullzeiger
+    // sind für Quelle, Dateisystemtyp und Daten bei Bind- und Propagation-Mounts
+    // zulässig.
+    unsafe {
+        check(libc::unshare(flags))?;
+        if let Some(dir) = read_only {
+            check(libc::mount(
+                ptr::null(),
+                c"/".as_ptr(),
+                ptr::null(),
+                libc::MS_REC | libc::MS_PRIVATE,
+                ptr::null(),
+            ))?;
+            check(libc::mount(
+                dir.as_ptr(),
+                dir.as_ptr(),
+                ptr::null(),
+                libc::MS_BIND | libc::MS_REC,
+                ptr::null(),
+            ))?;
+            check(libc::mount(
+                ptr::null(),
+                dir.as_ptr(),
+                ptr::null(),
+                libc::MS_BIND | libc::MS_REMOUNT | libc::MS_RDONLY,
+                ptr::null(),
+            ))?;
+        }
+    }
+    Ok(())
+}
+
+/// Verschiebt den aufrufenden Prozess in die cgroup (`0` steht für ihn selbst).
+fn enter_cgroup(procs: &CString) -> io::Result<()> {
+    // SAFETY: `procs` ist ein gültiger, nullterminierter Pfad; der Puffer für
+    // `write` ist statisch.
+    unsafe {
+        let fd = libc::open(procs.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let written = libc::write(fd, b"0".as_ptr().cast(), 1);
+        let error = io::Error::last_os_error();
+        libc::close(fd);
+        if written != 1 {
+            return Err(error);
+        }
+    }
+    Ok(())
+}
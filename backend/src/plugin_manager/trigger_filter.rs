@@ -0,0 +1,406 @@
+//! Bedingungen für automatische Trigger aus `PLUGIN_TRIGGER_FILTER`.
+//!
+//! ```python
+//! PLUGIN_TRIGGER = "on_entry_create"
+//! PLUGIN_TRIGGER_FILTER = {
+//!     "paths": ["/data/**/*.mcap"],
+//!     "topics": ["/lidar/*"],
+//!     "message_types": ["sensor_msgs/msg/PointCloud2"],
+//!     "metadata": {"platform_name": "car2", "sequence_duration": {">=": 30}},
+//!     "min_size_mb": 100,
+//! }
+//! ```
+//!
+//! Alle angegebenen Bedingungen müssen zutreffen. Von `paths` genügt ein
+//! Muster; Muster ohne `/` gelten nur für den Dateinamen. Jedes Muster in
+//! `topics` und jeder Typ in `message_types` muss in der Aufnahme vorkommen.
+//! `*` und `?` passen nicht über `/` hinweg, `**` schon. `metadata` vergleicht
+//! Felder des Eintrags, entweder auf Gleichheit oder mit genau einem Operator
+//! aus [`Comparison`]. Ein fehlendes oder leeres Feld erfüllt keine Bedingung.
+//!
+//! Geprüft wird vor dem Einreihen gegen ein [`TriggerSubject`]; fehlen diesem
+//! Topics oder Metadaten, passt ein Filter, der sie verlangt, nicht.
+
+use crate::storage::models::Entry;
+use rocket::serde::{Deserialize, Serialize};
+use serde::Serializer;
+use serde::ser::SerializeMap;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Felder eines Eintrags, die in `metadata` verglichen werden können.
+pub const METADATA_FIELDS: &[&str] = &[
+    "name",
+    "path",
+    "size",
+    "status",
+    "tags",
+    "mcap_start_ns",
+    "mcap_end_ns",
+    "time_machine",
+    "platform_name",
+    "platform_image_link",
+    "scenario_name",
+    "scenario_creation_time",
+    "scenario_description",
+    "sequence_duration",
+    "sequence_distance",
+    "sequence_lat_starting_point_deg",
+    "sequence_lon_starting_point_deg",
+    "weather_cloudiness",
+    "weather_precipitation",
+    "weather_precipitation_deposits",
+    "weather_wind_intensity",
+    "weather_road_humidity",
+    "weather_fog",
+    "weather_snow",
+];
+
+const BYTES_PER_MB: f64 = 1024.0 * 1024.0;
+
+/// Vergleichsoperator einer Metadaten-Bedingung.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// Feldwert ist einer der Werte einer Liste.
+    In,
+    /// Liste enthält den Wert bzw. Text enthält den Teiltext, z. B. bei `tags`.
+    Contains,
+}
+
+impl Comparison {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Comparison::Eq => "==",
+            Comparison::Ne => "!=",
+            Comparison::Lt => "<",
+            Comparison::Le => "<=",
+            Comparison::Gt => ">",
+            Comparison::Ge => ">=",
+            Comparison::In => "in",
+            Comparison::Contains => "contains",
+        }
+    }
+
+    fn from_symbol(symbol: &str) -> Option<Self> {
+        [
+            Comparison::Eq,
+            Comparison::Ne,
+            Comparison::Lt,
+            Comparison::Le,
+            Comparison::Gt,
+            Comparison::Ge,
+            Comparison::In,
+            Comparison::Contains,
+        ]
+        .into_iter()
+        .find(|c| c.symbol() == symbol)
+    }
+}
+
+/// Bedingung an ein einzelnes Metadatenfeld.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Predicate {
+    pub op: Comparison,
+    pub value: Value,
+}
+
+impl Predicate {
+    /// Liest `"car2"` als Gleichheit oder `{">=": 30}` als Vergleich.
+    fn from_declaration(field: &str, raw: Value) -> Result<Self, String> {
+        let (op, value) = match raw {
+            Value::Object(map) => {
+                let mut entries = map.into_iter();
+                let (Some((symbol, value)), None) = (entries.next(), entries.next()) else {
+                    return Err(format!("metadata '{field}' must use exactly one operator"));
+                };
+                let op = Comparison::from_symbol(&symbol)
+                    .ok_or_else(|| format!("metadata '{field}': unknown operator '{symbol}'"))?;
+                (op, value)
+            }
+            value => (Comparison::Eq, value),
+        };
+        let valid = match op {
+            Comparison::Eq | Comparison::Ne | Comparison::Contains => !value.is_null(),
+            Comparison::Lt | Comparison::Le | Comparison::Gt | Comparison::Ge => {
+                value.is_number() || value.is_string()
+            }
+            Comparison::In => value.as_array().is_some_and(|v| !v.is_empty()),
+        };
+        if !valid {
+            return Err(format!(
+                "metadata '{field}': invalid value {value} for '{}'",
+                op.symbol()
+            ));
+        }
+        Ok(Predicate { op, value })
+    }
+
+    /// Ob der Feldwert die Bedingung erfüllt.
+    pub fn holds(&self, actual: &Value) -> bool {
+        if actual.is_null() {
+            return false;
+        }
+        match self.op {
+            Comparison::Eq => same(actual, &self.value),
+            Comparison::Ne => !same(actual, &self.value),
+            Comparison::Lt => order(actual, &self.value).is_some_and(|o| o.is_lt()),
+            Comparison::Le => order(actual, &self.value).is_some_and(|o| o.is_le()),
+            Comparison::Gt => order(actual, &self.value).is_some_and(|o| o.is_gt()),
+            Comparison::Ge => order(actual, &self.value).is_some_and(|o| o.is_ge()),
+            Comparison::In => self
+                .value
+                .as_array()
+                .is_some_and(|values| values.iter().any(|v| same(actual, v))),
+            Comparison::Contains => match (actual, &self.value) {
+                (Value::Array(items), expected) => items.iter().any(|v| same(v, expected)),
+                (Value::String(text), Value::String(part)) => text.contains(part.as_str()),
+                _ => false,
+            },
+        }
+    }
+}
+
+/// Gleichheit, bei der `2` und `2.0` übereinstimmen.
+fn same(a: &Value, b: &Value) -> bool {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(x), Some(y)) => x == y,
+        _ => a == b,
+    }
+}
+
+/// Zahlen numerisch, Texte lexikographisch (passt für RFC-3339-Zeitpunkte).
+fn order(a: &Value, b: &Value) -> Option<std::cmp::Ordering> {
+    match (a, b) {
+        (Value::String(x), Value::String(y)) => Some(x.cmp(y)),
+        _ => a.as_f64()?.partial_cmp(&b.as_f64()?),
+    }
+}
+
+impl Serialize for Predicate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(self.op.symbol(), &self.value)?;
+        map.end()
+    }
+}
+
+impl fmt::Display for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.op.symbol(), self.value)
+    }
+}
+
+/// Rohform von `PLUGIN_TRIGGER_FILTER` vor der Prüfung.
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde", deny_unknown_fields)]
+struct Declaration {
+    #[serde(default)]
+    paths: Vec<String>,
+    #[serde(default)]
+    topics: Vec<String>,
+    #[serde(default)]
+    message_types: Vec<String>,
+    #[serde(default)]
+    metadata: BTreeMap<String, Value>,
+    min_size_mb: Option<f64>,
+}
+
+/// Bedingungen, unter denen ein Trigger ein Plugin startet.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct TriggerFilter {
+    /// Glob-Muster für den Pfad; eines muss passen.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+    /// Glob-Muster für Topic-Namen; jedes muss vorkommen.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub topics: Vec<String>,
+    /// Nachrichtentypen; jeder muss vorkommen.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub message_types: Vec<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, Predicate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_size_mb: Option<f64>,
+}
+
+impl TriggerFilter {
+    /// Liest und prüft den Wert von `PLUGIN_TRIGGER_FILTER`.
+    pub fn from_declaration(raw: Value) -> Result<Self, String> {
+        if !raw.is_object() {
+            return Err(format!("expected a dict, got {raw}"));
+        }
+        let declaration: Declaration = serde_json::from_value(raw).map_err(|e| e.to_string())?;
+
+        for (key, patterns) in [
+            ("paths", &declaration.paths),
+            ("topics", &declaration.topics),
+            ("message_types", &declaration.message_types),
+        ] {
+            if patterns.iter().any(|p| p.trim().is_empty()) {
+                return Err(format!("{key} must not contain empty entries"));
+            }
+        }
+        if let Some(mb) = declaration.min_size_mb
+            && !(mb.is_finite() && mb >= 0.0)
+        {
+            return Err(format!("min_size_mb must not be negative, got {mb}"));
+        }
+
+        let mut metadata = BTreeMap::new();
+        for (field, raw) in declaration.metadata {
+            if !METADATA_FIELDS.contains(&field.as_str()) {
+                return Err(format!("metadata: unknown entry field '{field}'"));
+            }
+            let predicate = Predicate::from_declaration(&field, raw)?;
+            metadata.insert(field, predicate);
+        }
+
+        let filter = TriggerFilter {
+            paths: declaration.paths,
+            topics: declaration.topics,
+            message_types: declaration.message_types,
+            metadata,
+            min_size_mb: declaration.min_size_mb,
+        };
+        if filter == TriggerFilter::default() {
+            return Err("declares no conditions".to_string());
+        }
+        Ok(filter)
+    }
+
+    /// Ob der Trigger für diesen Eintrag auslösen soll.
+    pub fn matches(&self, subject: &TriggerSubject) -> bool {
+        if !self.paths.is_empty() && !self.paths.iter().any(|p| path_matches(p, &subject.path)) {
+            return false;
+        }
+        if let Some(mb) = self.min_size_mb {
+            match subject.size_bytes {
+                Some(bytes) if bytes as f64 >= mb * BYTES_PER_MB => {}
+                _ => return false,
+            }
+        }
+        if !(self.topics.is_empty() && self.message_types.is_empty()) {
+            let Some(topics) = &subject.topics else {
+                return false;
+            };
+            let has_topic =
+                |pattern: &String| topics.iter().any(|(name, _)| glob_match(pattern, name));
+            let has_type = |wanted: &String| {
+                topics
+                    .iter()
+                    .any(|(_, ty)| ty.as_deref().is_some_and(|ty| glob_match(wanted, ty)))
+            };
+            if !(self.topics.iter().all(has_topic) && self.message_types.iter().all(has_type)) {
+                return false;
+            }
+        }
+        self.metadata.iter().all(|(field, predicate)| {
+            subject
+                .metadata
+                .get(field)
+                .is_some_and(|actual| predicate.holds(actual))
+        })
+    }
+
+    /// Lesbare Form für die `trigger`-Beschreibung der API.
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.paths.is_empty() {
+            parts.push(format!("path matches {}", self.paths.join(" | ")));
+        }
+        for topic in &self.topics {
+            parts.push(format!("has topic {topic}"));
+        }
+        for message_type in &self.message_types {
+            parts.push(format!("has message type {message_type}"));
+        }
+        for (field, predicate) in &self.metadata {
+            parts.push(format!("{field} {predicate}"));
+        }
+        if let Some(mb) = self.min_size_mb {
+            parts.push(format!("size >= {mb} MB"));
+        }
+        parts.join(" and ")
+    }
+}
+
+/// Was über einen Eintrag beim Auslösen bekannt ist.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TriggerSubject {
+    pub path: String,
+    pub size_bytes: Option<u64>,
+    /// Felder des Eintrags als JSON, siehe [`METADATA_FIELDS`].
+    pub metadata: Map<String, Value>,
+    /// Topics als (Name, Nachrichtentyp); `None`, wenn sie nicht bekannt sind.
+    pub topics: Option<Vec<(String, Option<String>)>>,
+}
+
+impl TriggerSubject {
+    /// Nur der Pfad ist bekannt, z. B. bei Events ohne Datenbankeintrag.
+    pub fn from_path(path: impl Into<String>) -> Self {
+        TriggerSubject {
+            path: path.into(),
+            ..TriggerSubject::default()
+        }
+    }
+
+    pub fn from_entry(entry: &Entry, topics: Vec<(String, Option<String>)>) -> Self {
+        let metadata = match serde_json::to_value(entry) {
+            Ok(Value::Object(map)) => map,
+            _ => Map::new(),
+        };
+        TriggerSubject {
+            path: entry.path.clone(),
+            size_bytes: u64::try_from(entry.size).ok(),
+            metadata,
+            topics: Some(topics),
+        }
+    }
+}
+
+/// Muster mit `/` gelten für den ganzen Pfad, sonst für den Dateinamen.
+fn path_matches(pattern: &str, path: &str) -> bool {
+    if pattern.contains('/') {
+        glob_match(pattern, path)
+    } else {
+        glob_match(pattern, path.rsplit('/').next().unwrap_or(path))
+    }
+}
+
+/// Glob-Vergleich mit `*`, `?` und `**`; `**/` darf auch null Verzeichnisse
+/// überspringen.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    fn go(p: &[u8], t: &[u8]) -> bool {
+        match p {
+            [] => t.is_empty(),
+            [b'*', b'*', rest @ ..] => {
+                if let [b'/', after @ ..] = rest
+                    && go(after, t)
+                {
+                    return true;
+                }
+                (0..=t.len()).any(|i| go(rest, &t[i..]))
+            }
+            [b'*', rest @ ..] => (0..=t.len())
+                .take_while(|&i| i == 0 || t[i - 1] != b'/')
+                .any(|i| go(rest, &t[i..])),
+            [b'?', rest @ ..] => match t {
+                [c, tail @ ..] if *c != b'/' => go(rest, tail),
+                _ => false,
+            },
+            [c, rest @ ..] => match t {
+                [d, tail @ ..] if c == d => go(rest, tail),
+                _ => false,
+            },
+        }
+    }
+    go(pattern.as_bytes(), text.as_bytes())
+}
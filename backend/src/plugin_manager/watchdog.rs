@@ -0,0 +1,151 @@
+//! Zeitlimits einer Instanz aus `PLUGIN_TIMEOUT` und `PLUGIN_STALL_TIMEOUT`.
+//!
+//! ```python
+//! PLUGIN_TIMEOUT = 3600        # Laufzeit von run() in Sekunden
+//! PLUGIN_STALL_TIMEOUT = 300   # höchstens so lange ohne report_progress()
+//! ```
+//!
+//! Die Liveness-Prüfung in `reap_dead_and_unresponsive` erkennt nur einen
+//! hängenden Runner, nicht ein `run()`, das nicht vorankommt. Der Actor der
+//! Instanz fragt deshalb den [`Watchdog`]; ist ein Limit überschritten, wird die
+//! Instanz weich gestoppt, danach ihre Prozessgruppe mit SIGTERM und zuletzt mit
+//! SIGKILL beendet. Pausen zählen bei keinem der beiden Limits mit.
+
+use rocket::serde::Serialize;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Zeitlimits eines Plugins in Sekunden; `None` heißt unbegrenzt.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RunLimits {
+    pub timeout_seconds: Option<f64>,
+    pub stall_timeout_seconds: Option<f64>,
+}
+
+impl RunLimits {
+    /// Prüft die Werte von `PLUGIN_TIMEOUT` und `PLUGIN_STALL_TIMEOUT`.
+    pub fn from_seconds(
+        timeout_seconds: Option<f64>,
+        stall_timeout_seconds: Option<f64>,
+    ) -> Result<Self, String> {
+        for (name, value) in [
+            ("PLUGIN_TIMEOUT", timeout_seconds),
+            ("PLUGIN_STALL_TIMEOUT", stall_timeout_seconds),
+        ] {
+            if let Some(seconds) = value
+                && !(seconds.is_finite() && seconds > 0.0)
+            {
+                return Err(format!(
+                    "{name} must be a positive number of seconds, got {seconds}"
+                ));
+            }
+        }
+        Ok(RunLimits {
+            timeout_seconds,
+            stall_timeout_seconds,
+        })
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout_seconds.map(Duration::from_secs_f64)
+    }
+
+    pub fn stall_timeout(&self) -> Option<Duration> {
+        self.stall_timeout_seconds.map(Duration::from_secs_f64)
+    }
+}
+
+/// Welches Limit überschritten wurde.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeoutKind {
+    /// Gesamtlaufzeit (`PLUGIN_TIMEOUT`).
+    Run,
+    /// Zeit seit dem letzten Fortschritt (`PLUGIN_STALL_TIMEOUT`).
+    Stall,
+}
+
+impl TimeoutKind {
+    /// Fehlertext für `plugin_runs.error`.
+    pub fn message(&self, limits: &RunLimits) -> String {
+        match self {
+            TimeoutKind::Run => format!(
+                "run exceeded PLUGIN_TIMEOUT of {}s",
+                limits.timeout_seconds.unwrap_or_default()
+            ),
+            TimeoutKind::Stall => format!(
+                "no progress within PLUGIN_STALL_TIMEOUT of {}s",
+                limits.stall_timeout_seconds.unwrap_or_default()
+            ),
+        }
+    }
+}
+
+/// Überwacht Laufzeit und Fortschritt einer Instanz.
+#[derive(Debug, Clone)]
+pub struct Watchdog {
+    limits: RunLimits,
+    started: Instant,
+    last_progress: Instant,
+    paused_since: Option<Instant>,
+    paused_total: Duration,
+}
+
+impl Watchdog {
+    pub fn new(limits: RunLimits, now: Instant) -> Self {
+        Watchdog {
+            limits,
+            started: now,
+            last_progress: now,
+            paused_since: None,
+            paused_total: Duration::ZERO,
+        }
+    }
+
+    pub fn limits(&self) -> &RunLimits {
+        &self.limits
+    }
+
+    /// Ein `progress`-Event ist eingetroffen.
+    pub fn progress(&mut self, now: Instant) {
+        self.last_progress = now;
+    }
+
+    pub fn pause(&mut self, now: Instant) {
+        self.paused_since.get_or_insert(now);
+    }
+
+    /// Nach einer Pause beginnt die Frist für Fortschritt neu.
+    pub fn resume(&mut self, now: Instant) {
+        if let Some(since) = self.paused_since.take() {
+            self.paused_total += now.saturating_duration_since(since);
+            self.last_progress = now;
+        }
+    }
+
+    /// Nächstes Limit, das abläuft; `None` ohne Limits oder während einer Pause.
+    pub fn deadline(&self) -> Option<(Instant, TimeoutKind)> {
+        if self.paused_since.is_some() {
+            return None;
+        }
+        let run = self
+            .limits
+            .timeout()
+            .map(|t| (self.started + self.paused_total + t, TimeoutKind::Run));
+        let stall = self
+            .limits
+            .stall_timeout()
+            .map(|t| (self.last_progress + t, TimeoutKind::Stall));
+        match (run, stall) {
+            (Some(run), Some(stall)) => Some(if stall.0 < run.0 { stall } else { run }),
+            (run, stall) => run.or(stall),
+        }
+    }
+
+    /// Das zu `now` überschrittene Limit, falls eines.
+    pub fn expired(&self, now: Instant) -> Option<TimeoutKind> {
+        self.deadline()
+            .filter(|(at, _)| *at <= now)
+            .map(|(_, kind)| kind)
+    }
+}
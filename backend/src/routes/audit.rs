@@ -0,0 +1,151 @@
+use crate::AppState;
+use crate::error::Error;
+use crate::routes::auth::{RequireAdmin, authenticated_caller};
+use crate::storage::audit::{self, AuditFilter};
+use crate::storage::models::{AuditRecord, NewAuditRecord};
+use crate::storage::storage_manager::TxID;
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::request::{self, FromRequest};
+use rocket::serde::Serialize;
+use rocket::serde::json::Json;
+use rocket::{Request, Response, State, get};
+use serde_json::{Map, Value};
+use std::convert::Infallible;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Protokolliert jeden ändernden Aufruf (POST, PUT, PATCH, DELETE) nach der
+/// Antwort: Akteur, Route, Ziel-IDs aus dem Pfad, Query-Parameter, die vom
+/// Handler über [`AuditDetail`] festgehaltenen Parameter und Ergebnis.
+///
+/// Der Akteur stammt aus den Auth-Guards der Route; abgewiesene oder nicht
+/// angemeldete Aufrufe werden als `anonymous` bzw. mit `denied` erfasst.
+pub struct AuditLog;
+
+/// Anfrage-lokaler Zwischenspeicher für [`AuditDetail`].
+#[derive(Default)]
+struct AuditParameters {
+    /// Route hat den Guard angefordert und wird auch bei GET protokolliert.
+    requested: AtomicBool,
+    fields: Mutex<Map<String, Value>>,
+}
+
+/// Guard, über den ein Handler seine Eingaben ins Audit-Log schreibt.
+///
+/// Der Body einer Anfrage steht dem Fairing nicht mehr zur Verfügung, daher
+/// hält der Handler ihn (oder eine Zusammenfassung ohne Geheimnisse) selbst
+/// fest. Routen mit diesem Guard werden unabhängig von der Methode
+/// protokolliert, etwa das ändernde `GET /transaction/<txid>/commit`.
+pub struct AuditDetail<'r>(&'r AuditParameters);
+
+impl AuditDetail<'_> {
+    /// Hält `value` unter `name` in den Parametern des Audit-Eintrags fest.
+    pub fn record(&self, name: &str, value: impl Serialize) {
+        match serde_json::to_value(value) {
+            Ok(value) => {
+                self.0
+                    .fields
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert(name.to_string(), value);
+            }
+            Err(e) => tracing::warn!("failed to record audit parameter '{name}': {e}"),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AuditDetail<'r> {
+    type Error = Infallible;
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let parameters = req.local_cache(AuditParameters::default);
+        parameters.requested.store(true, Ordering::Relaxed);
+        request::Outcome::Success(AuditDetail(parameters))
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for AuditLog {
+    fn info(&self) -> Info {
+        Info {
+            name: "Audit log",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let method = req.method().as_str();
+        let detail = req.local_cache(AuditParameters::default);
+        if !audit::is_audited_method(method) && !detail.requested.load(Ordering::Relaxed) {
+            return;
+        }
+        let Some(state) = req.rocket().state::<AppState>() else {
+            return;
+        };
+        let path = req.uri().path().to_string();
+        let route = req.route().map(|r| r.uri.origin.path().to_string());
+        let target_ids = route
+            .as_deref()
+            .map(|template| audit::path_params(template, &path))
+            .unwrap_or_default();
+        let mut parameters: Map<String, Value> = req
+            .query_fields()
+            .map(|field| {
+                (
+                    field.name.to_string(),
+                    Value::String(field.value.to_string()),
+                )
+            })
+            .collect();
+        parameters.extend(
+            detail
+                .fields
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .clone(),
+        );
+        let status = res.status().code;
+        let record = NewAuditRecord {
+            actor: authenticated_caller(req)
+                .map(|caller| caller.actor())
+                .unwrap_or_else(|| audit::ANONYMOUS.to_string()),
+            method: method.to_string(),
+            route,
+            path,
+            target_ids: Value::Object(target_ids),
+            parameters: Value::Object(parameters),
+            status: i32::from(status),
+            outcome: audit::outcome(status).to_string(),
+        };
+        if let Err(e) = state.storage_manager.record_audit(record).await {
+            tracing::error!(
+                "failed to write audit record for {method} {}: {e:?}",
+                req.uri()
+            );
+        }
+    }
+}
+
+/// Audit-Einträge, neueste zuerst. `since`/`until` im RFC-3339-Format;
+/// ohne `page_size` kommen 100 Einträge je Seite.
+#[get("/audit?<actor>&<target>&<since>&<until>&<page>&<page_size>&<txid>")]
+#[allow(clippy::too_many_arguments)]
+pub async fn get_audit_log(
+    state: &State<AppState>,
+    _auth: RequireAdmin,
+    actor: Option<String>,
+    target: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+    page: Option<u32>,
+    page_size: Option<u32>,
+    txid: Option<TxID>,
+) -> Result<Json<(Vec<AuditRecord>, u32)>, Error> {
+    let filter = AuditFilter::parse(actor, target, since.as_deref(), until.as_deref())?;
+    let (records, num_pages) = state
+        .storage_manager
+        .get_audit_log(filter, page, page_size, txid.unwrap_or(0))
+        .await?;
+    Ok(Json((records, num_pages)))
+}
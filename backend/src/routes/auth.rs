@@ -0,0 +1,490 @@
+use crate::AppState;
+use crate::error::Error;
+use crate::routes::audit::AuditDetail;
+use crate::routes::database::{IfMatch, Versioned};
+use crate::storage::auth::{self, Role, RunnerClaims, Scope};
+use crate::storage::models::{ApiKey, ApiKeyID, NewApiKey, Session, User, UserID};
+use crate::storage::storage_manager::TxID;
+use chrono::{DateTime, Utc};
+use rocket::http::{Cookie, CookieJar, SameSite, Status};
+use rocket::outcome::Outcome;
+use rocket::request::{self, FromRequest, Request};
+use rocket::serde::json::Json;
+use rocket::serde::{Deserialize, Serialize};
+use rocket::{State, catch, delete, get, post, put, response::status};
+
+/// Angemeldeter Benutzer mit gültiger Sitzung.
+///
+/// Das Token kommt aus `Authorization: Bearer <token>` oder, für den Browser,
+/// aus dem `session`-Cookie (HttpOnly, SameSite=Strict).
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub user: User,
+    pub role: Role,
+    token: String,
+}
+
+/// Aufrufer einer Route: Benutzersitzung, API-Schlüssel oder Plugin-Instanz.
+///
+/// Schlüssel (`ck_…`) und Runner-Tokens (`rt.…`) werden nur im
+/// `Authorization`-Header angenommen und dürfen, was ihre Scopes abdecken.
+#[derive(Debug, Clone)]
+pub enum Caller {
+    User(AuthUser),
+    ApiKey(ApiKey),
+    Runner(RunnerClaims),
+}
+
+impl Caller {
+    /// Für Routen, deren nötige Rolle erst vom Inhalt abhängt.
+    pub fn require(&self, role: Role) -> Result<(), Error> {
+        let scopes: Vec<&str> = match self {
+            Caller::User(user) if user.role.allows(role) => return Ok(()),
+            Caller::User(user) => {
+                return Err(Error::Forbidden(format!(
+                    "role {} required, user '{}' has role {}",
+                    role, user.user.username, user.role
+                )));
+            }
+            Caller::ApiKey(key) => key.scopes.iter().map(String::as_str).collect(),
+            Caller::Runner(_) => auth::RUNNER_SCOPES.iter().map(Scope::as_str).collect(),
+        };
+        match Scope::for_role(role) {
+            Some(scope) if scopes.contains(&scope.as_str()) => Ok(()),
+            Some(scope) => Err(Error::Forbidden(format!(
+                "scope {} required, {} has scopes {}",
+                scope,
+                self,
+                scopes.join(", ")
+            ))),
+            None => Err(Error::Forbidden(format!(
+                "role {role} required, which needs a user session; got {self}"
+            ))),
+        }
+    }
+
+    /// Kennung für das Audit-Log: `user:<name>`, `api_key:<id>` oder
+    /// `plugin:<name>:<instance>`.
+    pub fn actor(&self) -> String {
+        match self {
+            Caller::User(user) => format!("user:{}", user.user.username),
+            Caller::ApiKey(key) => format!("api_key:{}", key.id),
+            Caller::Runner(runner) => {
+                format!("plugin:{}:{}", runner.plugin_name, runner.instance_id)
+            }
+        }
+    }
+
+    /// Benutzer hinter dem Aufruf, falls es eine Sitzung ist.
+    pub fn user(&self) -> Option<&User> {
+        match self {
+            Caller::User(user) => Some(&user.user),
+            Caller::ApiKey(_) | Caller::Runner(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Caller {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Caller::User(user) => {
+                write!(f, "user '{}' with role {}", user.user.username, user.role)
+            }
+            Caller::ApiKey(key) => write!(f, "api key {} '{}'", key.id, key.name),
+            Caller::Runner(runner) => write!(
+                f,
+                "plugin '{}' (instance {})",
+                runner.plugin_name, runner.instance_id
+            ),
+        }
+    }
+}
+
+/// Grund einer abgelehnten Anmeldung für die 401/403-Catcher.
+struct AuthFailure(String);
+
+fn fail<T>(req: &Request<'_>, status: Status, error: Error) -> request::Outcome<T, Error> {
+    let message = match &error {
+        Error::Unauthorized(m) | Error::Forbidden(m) => m.clone(),
+        other => format!("{other:?}"),
+    };
+    req.local_cache(|| AuthFailure(message));
+    Outcome::Error((status, error))
+}
+
+fn request_token(req: &Request<'_>) -> Option<String> {
+    if let Some(header) = req.headers().get_one("Authorization") {
+        return auth::bearer_token(header).map(str::to_string);
+    }
+    req.cookies()
+        .get(auth::SESSION_COOKIE)
+        .map(|c| c.value().to_string())
+}
+
+/// Aufrufer, den ein Guard dieser Anfrage bereits erkannt hat.
+pub fn authenticated_caller(req: &Request<'_>) -> Option<Caller> {
+    req.local_cache(|| Err::<Caller, Error>(Error::Unauthorized(String::new())))
+        .as_ref()
+        .ok()
+        .cloned()
+}
+
+async fn resolve_caller(req: &Request<'_>) -> Result<Caller, Error> {
+    let token = request_token(req)
+        .ok_or_else(|| Error::Unauthorized("authentication required".to_string()))?;
+    if token.starts_with(auth::RUNNER_TOKEN_PREFIX) {
+        let claims = auth::verify_runner_token(&token, Utc::now())
+            .ok_or_else(|| Error::Unauthorized("invalid or expired runner token".to_string()))?;
+        return Ok(Caller::Runner(claims));
+    }
+    let state = req
+        .rocket()
+        .state::<AppState>()
+        .ok_or_else(|| Error::CustomError("application state missing".to_string()))?;
+    let sm = &state.storage_manager;
+    if token.starts_with(auth::API_KEY_PREFIX) {
+        let key = sm
+            .authenticate_api_key(token)
+            .await?
+            .ok_or_else(|| Error::Unauthorized("invalid or expired api key".to_string()))?;
+        return Ok(Caller::ApiKey(key));
+    }
+    let user = sm
+        .authenticate(token.clone())
+        .await?
+        .ok_or_else(|| Error::Unauthorized("invalid or expired session".to_string()))?;
+    let role = Role::parse(&user.role)?;
+    Ok(Caller::User(AuthUser { user, role, token }))
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Caller {
+    type Error = Error;
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        // mehrere Guards einer Route fragen die Datenbank nur einmal
+        let cached = req.local_cache_async(resolve_caller(req)).await;
+        match cached {
+            Ok(caller) => Outcome::Success(caller.clone()),
+            Err(Error::Unauthorized(m)) => {
+                fail(req, Status::Unauthorized, Error::Unauthorized(m.clone()))
+            }
+            Err(e) => fail(
+                req,
+                Status::ServiceUnavailable,
+                Error::CustomError(format!("authentication failed: {e:?}")),
+            ),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AuthUser {
+    type Error = Error;
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        match Caller::from_request(req).await {
+            Outcome::Success(Caller::User(user)) => Outcome::Success(user),
+            Outcome::Success(caller) => fail(
+                req,
+                Status::Forbidden,
+                Error::Forbidden(format!("user session required, got {caller}")),
+            ),
+            Outcome::Error(e) => Outcome::Error(e),
+            Outcome::Forward(s) => Outcome::Forward(s),
+        }
+    }
+}
+
+async fn authorize(req: &Request<'_>, role: Role) -> request::Outcome<Caller, Error> {
+    match Caller::from_request(req).await {
+        Outcome::Success(caller) => match caller.require(role) {
+            Ok(()) => Outcome::Success(caller),
+            Err(e) => fail(req, Status::Forbidden, e),
+        },
+        Outcome::Error(e) => Outcome::Error(e),
+        Outcome::Forward(s) => Outcome::Forward(s),
+    }
+}
+
+macro_rules! role_guard {
+    ($(#[$doc:meta])* $name:ident, $role:expr) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone)]
+        pub struct $name(pub Caller);
+
+        #[rocket::async_trait]
+        impl<'r> FromRequest<'r> for $name {
+            type Error = Error;
+
+            async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+                authorize(req, $role).await.map($name)
+            }
+        }
+    };
+}
+
+role_guard!(
+    /// Lesender Zugriff (Scope `read:entries`).
+    RequireViewer,
+    Role::Viewer
+);
+role_guard!(
+    /// Änderungen an Katalog, Tags, Sequenzen, Sensoren und Sammlungen
+    /// (Scope `write:metadata`).
+    RequireEditor,
+    Role::Editor
+);
+role_guard!(
+    /// Plugin-Instanzen starten und steuern (Scope `run:plugins`).
+    RequirePluginOperator,
+    Role::PluginOperator
+);
+role_guard!(
+    /// Plugin-Verwaltung, Logs, Benutzer und API-Schlüssel; nur mit Sitzung.
+    RequireAdmin,
+    Role::Admin
+);
+
+fn failure_message(req: &Request<'_>, default: &str) -> String {
+    let message = &req.local_cache(|| AuthFailure(String::new())).0;
+    if message.is_empty() {
+        default.to_string()
+    } else {
+        message.clone()
+    }
+}
+
+#[catch(401)]
+pub fn unauthorized(req: &Request<'_>) -> Error {
+    Error::Unauthorized(failure_message(req, "authentication required"))
+}
+
+#[catch(403)]
+pub fn forbidden(req: &Request<'_>) -> Error {
+    Error::Forbidden(failure_message(req, "insufficient role"))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct LoginWeb {
+    pub username: String,
+    pub password: String,
+}
+
+/// Meldet an, setzt das Sitzungs-Cookie und liefert das Token für
+/// `Authorization: Bearer`.
+#[post("/auth/login", format = "json", data = "<login>")]
+pub async fn login(
+    state: &State<AppState>,
+    login: Json<LoginWeb>,
+    cookies: &CookieJar<'_>,
+    audit: AuditDetail<'_>,
+) -> Result<Json<Session>, Error> {
+    let sm = &state.storage_manager;
+    let l = login.into_inner();
+    audit.record("username", &l.username);
+    let Some(session) = sm.login(l.username, l.password).await? else {
+        return Err(Error::Unauthorized(
+            "invalid username or password".to_string(),
+        ));
+    };
+    let expires =
+        rocket::time::OffsetDateTime::from_unix_timestamp(session.expires_at.timestamp()).ok();
+    let mut cookie = Cookie::build((auth::SESSION_COOKIE, session.token.clone()))
+        .http_only(true)
+        .same_site(SameSite::Strict)
+        .path("/");
+    if let Some(expires) = expires {
+        cookie = cookie.expires(expires);
+    }
+    cookies.add(cookie);
+    Ok(Json(session))
+}
+
+#[post("/auth/logout")]
+pub async fn logout(
+    state: &State<AppState>,
+    user: AuthUser,
+    cookies: &CookieJar<'_>,
+) -> Result<status::NoContent, Error> {
+    state.storage_manager.logout(user.token).await?;
+    cookies.remove(Cookie::from(auth::SESSION_COOKIE));
+    Ok(status::NoContent)
+}
+
+#[get("/auth/me")]
+pub fn me(user: AuthUser) -> Json<User> {
+    Json(user.user)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct NewUserWeb {
+    pub username: String,
+    pub password: String,
+    pub role: Role,
+}
+
+/// Nicht gesetzte Felder bleiben unverändert.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", deny_unknown_fields)]
+pub struct UserUpdateWeb {
+    pub role: Option<Role>,
+    pub password: Option<String>,
+    pub disabled: Option<bool>,
+}
+
+#[get("/users?<txid>")]
+pub async fn get_users(
+    state: &State<AppState>,
+    _auth: RequireAdmin,
+    txid: Option<TxID>,
+) -> Result<Json<Vec<User>>, Error> {
+    let users = state.storage_manager.get_users(txid.unwrap_or(0)).await?;
+    Ok(Json(users))
+}
+
+#[post("/users?<txid>", format = "json", data = "<user>")]
+pub async fn create_user(
+    state: &State<AppState>,
+    _auth: RequireAdmin,
+    user: Json<NewUserWeb>,
+    txid: Option<TxID>,
+    audit: AuditDetail<'_>,
+) -> Result<status::Created<Json<User>>, Error> {
+    let u = user.into_inner();
+    // ohne Passwort
+    audit.record("username", &u.username);
+    audit.record("role", u.role);
+    let created = state
+        .storage_manager
+        .create_user(u.username, u.password, u.role, txid.unwrap_or(0))
+        .await?;
+    Ok(status::Created::new(format!("/users/{}", created.id)).body(Json(created)))
+}
+
+#[get("/users/<user_id>?<txid>")]
+pub async fn get_user(
+    state: &State<AppState>,
+    _auth: RequireAdmin,
+    user_id: UserID,
+    txid: Option<TxID>,
+) -> Result<Versioned<User>, Error> {
+    let user = state
+        .storage_manager
+        .get_user(user_id, txid.unwrap_or(0))
+        .await?;
+    Ok(Versioned {
+        version: user.version,
+        body: user,
+    })
+}
+
+/// Neues Passwort oder Sperre beenden alle Sitzungen des Kontos.
+#[put("/users/<user_id>?<txid>", format = "json", data = "<update>")]
+pub async fn update_user(
+    state: &State<AppState>,
+    _auth: RequireAdmin,
+    user_id: UserID,
+    update: Json<UserUpdateWeb>,
+    txid: Option<TxID>,
+    if_match: IfMatch,
+    audit: AuditDetail<'_>,
+) -> Result<Versioned<User>, Error> {
+    let u = update.into_inner();
+    // ohne Passwort, nur ob es geändert wird
+    audit.record("role", u.role);
+    audit.record("disabled", u.disabled);
+    audit.record("password_changed", u.password.is_some());
+    let user = state
+        .storage_manager
+        .update_user(
+            user_id,
+            u.role,
+            u.password,
+            u.disabled,
+            if_match.0,
+            txid.unwrap_or(0),
+        )
+        .await?;
+    Ok(Versioned {
+        version: user.version,
+        body: user,
+    })
+}
+
+#[delete("/users/<user_id>?<txid>")]
+pub async fn delete_user(
+    state: &State<AppState>,
+    _auth: RequireAdmin,
+    user_id: UserID,
+    txid: Option<TxID>,
+) -> Result<status::NoContent, Error> {
+    state
+        .storage_manager
+        .delete_user(user_id, txid.unwrap_or(0))
+        .await?;
+    Ok(status::NoContent)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct NewApiKeyWeb {
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    /// Ohne Angabe läuft der Schlüssel nicht ab.
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[get("/api-keys?<txid>")]
+pub async fn get_api_keys(
+    state: &State<AppState>,
+    _auth: RequireAdmin,
+    txid: Option<TxID>,
+) -> Result<Json<Vec<ApiKey>>, Error> {
+    let keys = state
+        .storage_manager
+        .get_api_keys(txid.unwrap_or(0))
+        .await?;
+    Ok(Json(keys))
+}
+
+/// Legt einen Schlüssel an; `key` steht nur in dieser Antwort im Klartext.
+#[post("/api-keys?<txid>", format = "json", data = "<key>")]
+pub async fn create_api_key(
+    state: &State<AppState>,
+    auth: RequireAdmin,
+    key: Json<NewApiKeyWeb>,
+    txid: Option<TxID>,
+    audit: AuditDetail<'_>,
+) -> Result<status::Created<Json<NewApiKey>>, Error> {
+    let k = key.into_inner();
+    audit.record("body", &k);
+    let created = state
+        .storage_manager
+        .create_api_key(
+            k.name,
+            k.scopes,
+            k.expires_at,
+            auth.0.user().map(|u| u.id),
+            txid.unwrap_or(0),
+        )
+        .await?;
+    Ok(status::Created::new(format!("/api-keys/{}", created.api_key.id)).body(Json(created)))
+}
+
+#[delete("/api-keys/<key_id>?<txid>")]
+pub async fn delete_api_key(
+    state: &State<AppState>,
+    _auth: RequireAdmin,
+    key_id: ApiKeyID,
+    txid: Option<TxID>,
+) -> Result<status::NoContent, Error> {
+    state
+        .storage_manager
+        .delete_api_key(key_id, txid.unwrap_or(0))
+        .await?;
+    Ok(status::NoContent)
+}
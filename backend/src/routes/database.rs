@@ -1,8 +1,19 @@
 use crate::AppState;
 use crate::error::{Error, StorageError};
 use crate::plugin_manager::plugin::BackendEvent;
+use crate::plugin_manager::queue::JobPriority;
+use crate::plugin_manager::trigger_filter::TriggerSubject;
+use crate::routes::audit::AuditDetail;
+use crate::routes::auth::{RequireEditor, RequireViewer};
+use crate::routes::plugins;
+use crate::storage::auth::Role;
 use crate::storage::models::{
-    Entry, EntryID, Sensor, SensorID, Sequence, SequenceID, Topic, TopicID,
+    BulkEntryResult, BulkEntryStatus, CatalogSensor, CatalogSensorID, CatalogSensorUsage,
+    CatalogSensorWithUsage, Collection, CollectionDetails, CollectionID, CollectionItem,
+    CollectionSnapshot, CollectionSnapshotDetails, CollectionSummary, Entry, EntryArtifact,
+    EntryConsistency, EntryID, EntryMetadataPatch, EntrySensorAssignment, Sensor, SensorID,
+    Sequence, SequenceID, SequenceSearchHit, TagChangeCount, TagDefinition, TagInfo, Topic,
+    TopicID,
 };
 use chrono::{DateTime, Utc};
 use rocket::serde::{Deserialize, Serialize};
@@ -10,7 +21,6 @@ use serde_json::Value as JsonValue;
 use tokio::time::{Duration, timeout};
 
 const PM_LOCK_TIMEOUT: Duration = Duration::from_secs(1);
-const ROUTE_OP_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(crate = "rocket::serde")]
@@ -45,6 +55,116 @@ pub struct SensorWeb {
     pub custom_parameters: Option<JsonValue>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct CatalogSensorWeb {
+    pub sensor_name: String,
+    pub manufacturer: Option<String>,
+    pub sensor_type: Option<String>,
+    pub model: Option<String>,
+    pub serial_number: Option<String>,
+    pub calibration: Option<JsonValue>,
+    pub custom_parameters: Option<JsonValue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct MergeCatalogSensorsWeb {
+    /// Katalogsensor, der erhalten bleibt.
+    pub target_id: CatalogSensorID,
+    /// Duplikate, die in `target_id` aufgehen und danach gelöscht werden.
+    pub source_ids: Vec<CatalogSensorID>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct TagDefinitionWeb {
+    /// Farbe im Format `#rrggbb`.
+    pub color: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct RenameTagWeb {
+    pub new_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct MergeTagsWeb {
+    pub sources: Vec<String>,
+    pub target: String,
+}
+
+/// Tags an allen Einträgen setzen/entfernen, die Suche und Filter treffen
+/// (wie bei `GET /entries`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct BulkTagWeb {
+    pub search_string: Option<String>,
+    pub consistency: Option<String>,
+    #[serde(default)]
+    pub add: Vec<String>,
+    #[serde(default)]
+    pub remove: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde", tag = "kind", rename_all = "snake_case")]
+pub enum BulkOperationWeb {
+    AddTags {
+        tags: Vec<String>,
+    },
+    RemoveTags {
+        tags: Vec<String>,
+    },
+    /// Nur die angegebenen Felder werden überschrieben.
+    PatchMetadata {
+        fields: Box<EntryMetadataPatch>,
+    },
+    /// Startet je Eintrag eine Instanz. Mit `PLUGIN_PARAMETERS` wird `parameters`
+    /// einmal wie bei `POST /plugins/<name>/start` geprüft und jeder Eintrag
+    /// bekommt diese Daten mit seinem Pfad im ersten `entry`-Parameter; ohne
+    /// Schema wird `parameters` an die Payload angehängt.
+    StartPlugin {
+        plugin_name: String,
+        parameters: Option<JsonValue>,
+    },
+}
+
+/// Sammeloperation über eine explizite Id-Liste oder eine Suche wie bei
+/// `GET /entries` (genau eins von beiden).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct BulkRequestWeb {
+    pub entry_ids: Option<Vec<EntryID>>,
+    pub search_string: Option<String>,
+    pub consistency: Option<String>,
+    pub operation: BulkOperationWeb,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct CollectionWeb {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+/// Element einer Sammlung: ein ganzer Eintrag oder eine seiner Sequenzen.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct CollectionItemWeb {
+    pub entry_id: EntryID,
+    pub sequence_id: Option<SequenceID>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct FreezeCollectionWeb {
+    pub note: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(crate = "rocket::serde")]
 pub struct SequenceWeb {
@@ -54,7 +174,32 @@ pub struct SequenceWeb {
     pub end_timestamp: i64,
     pub tags: Vec<String>,
 }
-use crate::storage::storage_manager::{Map, TxID};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct SplitSequenceWeb {
+    /// Teilungszeitpunkt in Nanosekunden (MCAP-Log-Zeit).
+    pub at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct MergeSequencesWeb {
+    pub sequence_ids: Vec<SequenceID>,
+    /// Name der zusammengefassten Sequenz; ohne Angabe bleibt der der frühesten.
+    pub name: Option<String>,
+}
+use crate::storage::collections::{self, CollectionDiff};
+use crate::storage::manifest::{ManifestEncoder, ManifestFilter, ManifestFormat, ManifestHeader};
+use crate::storage::metadata_import::{self, MetadataImportReport};
+use crate::storage::storage_manager::{BulkEntryChange, EntryFilter, Map, TxID};
+use crate::storage::timeline::Timeline;
+use rocket::data::{Data, ToByteUnit};
+use rocket::fs::NamedFile;
+use rocket::http::{ContentType, Header, Status};
+use rocket::request::{self, FromRequest, Request};
+use rocket::response::stream::ByteStream;
+use rocket::response::{self, Responder};
 use rocket::serde::json::Json;
 use rocket::{State, delete, get, post, put, response::status};
 
@@ -75,12 +220,81 @@ fn not_found<T>(msg: String) -> Result<T, Error> {
     Err(StorageError::NotFound(msg).into())
 }
 
+/// Erwartete Zeilenversion aus dem `If-Match`-Header.
+///
+/// Akzeptiert `"3"`; fehlt der Header oder ist er `*`, wird ohne
+/// Versionsprüfung geschrieben. `If-Match` verlangt nach RFC 7232 §3.1 den
+/// starken Vergleich, schwache Tags wie `W/"3"` passen daher nie (412).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IfMatch(pub Option<i64>);
+
+impl IfMatch {
+    pub fn parse(value: &str) -> Result<IfMatch, Error> {
+        let value = value.trim();
+        if value == "*" {
+            return Ok(IfMatch(None));
+        }
+        if value.starts_with("W/") {
+            return Err(StorageError::PreconditionFailed(format!(
+                "If-Match requires a strong entity tag, got {value}"
+            ))
+            .into());
+        }
+        let tag = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .unwrap_or(value);
+        tag.parse::<i64>()
+            .map(|v| IfMatch(Some(v)))
+            .map_err(|_| Error::ParsingError(format!("invalid If-Match header: {value}")))
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for IfMatch {
+    type Error = Error;
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        match req.headers().get_one("If-Match") {
+            None => request::Outcome::Success(IfMatch(None)),
+            Some(raw) => match IfMatch::parse(raw) {
+                Ok(m) => request::Outcome::Success(m),
+                Err(e) => {
+                    let status = match e {
+                        Error::StorageError(StorageError::PreconditionFailed(_)) => {
+                            Status::PreconditionFailed
+                        }
+                        _ => Status::BadRequest,
+                    };
+                    request::Outcome::Error((status, e))
+                }
+            },
+        }
+    }
+}
+
+/// JSON-Antwort mit `ETag`-Header, der die Zeilenversion trägt.
+#[derive(Debug)]
+pub struct Versioned<T> {
+    pub body: T,
+    pub version: i64,
+}
+
+impl<'r, T: Serialize> Responder<'r, 'static> for Versioned<T> {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        let mut res = Json(self.body).respond_to(req)?;
+        res.set_header(Header::new("ETag", format!("\"{}\"", self.version)));
+        Ok(res)
+    }
+}
+
 #[get("/entries/<entry_id>/metadata/tx/<txid>")]
 pub async fn get_metadata(
     state: &State<AppState>,
+    _auth: RequireViewer,
     entry_id: EntryID,
     txid: TxID,
-) -> Result<Json<MetadataWeb>, Error> {
+) -> Result<Versioned<MetadataWeb>, Error> {
     let sm = &state.storage_manager;
 
     let entry = sm.get_metadata(entry_id, txid).await?;
@@ -117,13 +331,15 @@ pub async fn get_metadata(
                     }
                 },
             };
-            Ok(Json(md))
+            Ok(Versioned {
+                body: md,
+                version: e.version,
+            })
         }
         None => not_found(format!("metadata for entry {entry_id} not found")),
     }
 }
 
-
 #[put(
     "/entries/<entry_id>/metadata/tx/<txid>",
     format = "json",
@@ -131,71 +347,217 @@ pub async fn get_metadata(
 )]
 pub async fn update_metadata(
     state: &State<AppState>,
+    _auth: RequireEditor,
     entry_id: EntryID,
     metadata: Json<MetadataWeb>,
     txid: TxID,
+    if_match: IfMatch,
+    audit: AuditDetail<'_>,
 ) -> Result<status::NoContent, Error> {
+    audit.record("body", &*metadata);
     let sm = &state.storage_manager;
     let m = metadata.into_inner();
 
-    sm.update_entry(entry_id, m.clone(), txid).await?;
+    sm.update_entry(entry_id, m.clone(), if_match.0, txid)
+        .await?;
 
-    // ---- Trigger: OnEntryUpdate (Plugins starten, ohne globalen Lock über await zu halten) ----
-    // Wir brauchen den Entry-Pfad für das Event. Falls der Entry nicht existiert, skippen wir Trigger.
-    let entry_path = sm.get_entry(entry_id, txid).await?.map(|e| e.path);
+    fire_entry_updated(state, entry_id, |_| m, txid).await?;
 
-    if let Some(path) = entry_path {
-        let event = BackendEvent::EntryUpdated { path: path.clone() };
+    Ok(status::NoContent)
+}
 
-        // Phase 1: prepare (kurz unter Lock)
-        let plans = {
-            let pm = lock_plugin_manager(state).await?;
-            pm.prepare_fire_event(&event)?
+/// Reiht nach einer Metadatenänderung die `OnEntryUpdate`-Plugins und passenden
+/// Pipelines für den Eintrag ein; `metadata` liefert die Nutzlast für die Plugins.
+async fn fire_entry_updated(
+    state: &State<AppState>,
+    entry_id: EntryID,
+    metadata: impl FnOnce(&Entry) -> MetadataWeb,
+    txid: TxID,
+) -> Result<(), Error> {
+    let sm = &state.storage_manager;
+    // ---- Trigger: OnEntryUpdate (Plugins einreihen, gestartet wird im Dispatcher) ----
+    // Trigger-Bedingungen brauchen den geänderten Eintrag samt Topics. Falls der
+    // Entry nicht existiert, skippen wir Trigger.
+    if let Some(entry) = sm.get_entry(entry_id, txid).await? {
+        let topics = sm
+            .get_topics(entry_id, txid)
+            .await?
+            .into_values()
+            .map(|t| (t.topic_name, t.topic_type))
+            .collect();
+        let subject = TriggerSubject::from_entry(&entry, topics);
+        let event = BackendEvent::EntryUpdated {
+            path: entry.path.clone(),
         };
 
         // Build payload for plugins that expect metadata on update
         let plugin_data = serde_json::json!({
-            "metadata": serde_json::to_value(&m).unwrap_or(serde_json::Value::Null),
-            "mcap_path": path,
+            "metadata": serde_json::to_value(metadata(&entry)).unwrap_or(serde_json::Value::Null),
+            "mcap_path": entry.path,
         })
         .to_string();
 
-        // Phase 2: build (langsam, ohne "langen" globalen Lock)
-        let mut built: Vec<(u64, crate::plugin_manager::manager::PluginHandle)> = Vec::new();
-        for (plugin_index, plugin_path, instance_id) in plans {
-            let data = plugin_data.clone();
-            let handle = timeout(ROUTE_OP_TIMEOUT, async {
-                let pm = lock_plugin_manager(state).await?;
-                pm.build_started_instance_with_data(plugin_index, &plugin_path, instance_id, data)
-                    .await
-            })
-            .await
-            .map_err(|_| {
-                Error::CustomError(format!(
-                    "event start timed out after {:?}",
-                    ROUTE_OP_TIMEOUT
-                ))
-            })??;
-
-            built.push((instance_id, handle));
+        let mut pm = lock_plugin_manager(state).await?;
+        for (plugin_index, _plugin_path, instance_id) in
+            pm.prepare_fire_event_for(&event, &subject)?
+        {
+            pm.enqueue(
+                plugin_index,
+                instance_id,
+                plugin_data.clone(),
+                JobPriority::Triggered,
+            )?;
         }
+        pm.start_pipelines_for(&event, &subject, plugin_data);
+    }
+    Ok(())
+}
 
-        // Phase 3: commit (kurz unter Lock)
-        {
-            let mut pm = lock_plugin_manager(state).await?;
-            for (instance_id, handle) in built {
-                pm.commit_started_instance(instance_id, handle)?;
+/// Wendet eine Operation auf viele Einträge an und liefert einen Bericht je Eintrag.
+///
+/// Tag- und Metadatenänderungen laufen in einer Datenbanktransaktion; Plugin-Starts
+/// werden je Eintrag eingereiht, ein Fehlschlag wird nur für den jeweiligen Eintrag
+/// gemeldet, und verlangen die Rolle `plugin-operator`.
+#[post("/entries/bulk?<txid>", format = "json", data = "<bulk>")]
+pub async fn bulk_entries(
+    state: &State<AppState>,
+    auth: RequireEditor,
+    bulk: Json<BulkRequestWeb>,
+    txid: Option<TxID>,
+    audit: AuditDetail<'_>,
+) -> Result<Json<Vec<BulkEntryResult>>, Error> {
+    audit.record("body", &*bulk);
+    let sm = &state.storage_manager;
+    let txid = txid.unwrap_or(0);
+    let b = bulk.into_inner();
+    let filter = EntryFilter {
+        consistency: b.consistency,
+    };
+    let entry_ids = sm
+        .select_entry_ids(b.entry_ids, b.search_string, filter, txid)
+        .await?;
+
+    let change = match b.operation {
+        BulkOperationWeb::AddTags { tags } => BulkEntryChange::AddTags(tags),
+        BulkOperationWeb::RemoveTags { tags } => BulkEntryChange::RemoveTags(tags),
+        BulkOperationWeb::PatchMetadata { fields } => BulkEntryChange::PatchMetadata(fields),
+        BulkOperationWeb::StartPlugin {
+            plugin_name,
+            parameters,
+        } => {
+            auth.0.require(Role::PluginOperator)?;
+            let results =
+                start_plugin_for_entries(state, &plugin_name, parameters, entry_ids, txid).await?;
+            return Ok(Json(results));
+        }
+    };
+    let patch = match &change {
+        BulkEntryChange::PatchMetadata(fields) => Some(fields.clone()),
+        _ => None,
+    };
+    let results = sm.bulk_update_entries(entry_ids, change, txid).await?;
+    // wie bei `update_metadata`: Trigger erst nach dem Commit, je geändertem Eintrag
+    if let Some(patch) = patch {
+        for result in results.iter() {
+            if result.status == BulkEntryStatus::Updated {
+                fire_entry_updated(
+                    state,
+                    result.entry_id,
+                    |entry| metadata_import::metadata_with_patch(entry, &patch),
+                    txid,
+                )
+                .await?;
             }
         }
     }
+    Ok(Json(results))
+}
 
-    Ok(status::NoContent)
+async fn start_plugin_for_entries(
+    state: &State<AppState>,
+    plugin_name: &str,
+    parameters: Option<JsonValue>,
+    entry_ids: Vec<EntryID>,
+    txid: TxID,
+) -> Result<Vec<BulkEntryResult>, Error> {
+    let sm = &state.storage_manager;
+    // Plugin und Parameter einmal vorab prüfen: das betrifft alle Einträge gleich.
+    let (plugin_index, plugin_path, schema) = {
+        let pm = lock_plugin_manager(state).await?;
+        let (plugin_index, plugin_path) = pm.prepare_start(plugin_name)?;
+        let schema = pm.registered[plugin_index].parameters().cloned();
+        (plugin_index, plugin_path, schema)
+    };
+    let validated = match &schema {
+        Some(schema) => {
+            let payload = plugins::start_data_object(parameters.clone())?;
+            let validated = schema
+                .validate_per_entry(payload)
+                .map_err(Error::InvalidFields)?;
+            plugins::check_entry_parameters(state, schema, &validated).await?;
+            Some(validated)
+        }
+        None => None,
+    };
+
+    let mut results = Vec::with_capacity(entry_ids.len());
+    let mut last_instance_id = 0u64;
+    for entry_id in entry_ids {
+        let Some(entry) = sm.get_entry(entry_id, txid).await? else {
+            results.push(BulkEntryResult::new(entry_id, BulkEntryStatus::NotFound));
+            continue;
+        };
+        // Zeitstempel-IDs können in einer schnellen Schleife kollidieren.
+        let instance_id =
+            (chrono::Utc::now().timestamp_micros().max(0) as u64).max(last_instance_id + 1);
+        last_instance_id = instance_id;
+        let data = match (&schema, &validated) {
+            // dieselbe flache Form wie beim Einzelstart
+            (Some(schema), Some(validated)) => {
+                let mut data = validated.clone();
+                if let Some(field) = schema.entry_parameter() {
+                    data.insert(field.to_string(), JsonValue::String(entry.path.clone()));
+                }
+                JsonValue::Object(data)
+            }
+            _ => serde_json::json!({
+                "event": "manual",
+                "path": entry.path,
+                "entry_id": entry.id,
+                "plugin_path": plugin_path.to_string_lossy(),
+                "parameters": parameters,
+            }),
+        }
+        .to_string();
+
+        let queued = async {
+            let pm = lock_plugin_manager(state).await?;
+            pm.enqueue(plugin_index, instance_id, data, JobPriority::Manual)
+        }
+        .await;
+        results.push(match queued {
+            Ok(()) => BulkEntryResult {
+                instance_id: Some(instance_id),
+                ..BulkEntryResult::new(entry_id, BulkEntryStatus::Queued)
+            },
+            Err(e) => BulkEntryResult {
+                message: Some(format!("{e:?}")),
+                ..BulkEntryResult::new(entry_id, BulkEntryStatus::Failed)
+            },
+        });
+    }
+    Ok(results)
 }
 
-#[get("/entries?<search_string>&<sort_by>&<ascending>&<page>&<page_size>&<txid>")]
+/// `consistency` filtert nach dem Ergebnis der Sensor/Topic-Prüfung, z.B.
+/// `missing_topics` für Aufnahmen mit ausgefallenem Sensor.
+#[get("/entries?<search_string>&<consistency>&<sort_by>&<ascending>&<page>&<page_size>&<txid>")]
+#[allow(clippy::too_many_arguments)]
 pub async fn get_entries(
     state: &State<AppState>,
+    _auth: RequireViewer,
     search_string: Option<String>,
+    consistency: Option<String>,
     sort_by: Option<String>,
     ascending: Option<bool>,
     page: Option<u32>,
@@ -204,25 +566,71 @@ pub async fn get_entries(
 ) -> Result<Json<(Vec<Entry>, u32)>, Error> {
     let sm = &state.storage_manager;
     let txid = txid.unwrap_or(0);
+    let filter = EntryFilter { consistency };
 
     let (entries, num_pages) = sm
-        .get_entries(search_string, sort_by, ascending, page, page_size, txid)
+        .get_entries_filtered(
+            search_string,
+            filter,
+            sort_by,
+            ascending,
+            page,
+            page_size,
+            txid,
+        )
         .await?;
 
     Ok(Json((entries, num_pages)))
 }
 
+#[get("/sequences?<search>&<tag>&<min_duration>&<sort_by>&<ascending>&<page>&<page_size>&<txid>")]
+#[allow(clippy::too_many_arguments)]
+pub async fn search_sequences(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+    search: Option<String>,
+    tag: Option<String>,
+    min_duration: Option<f64>,
+    sort_by: Option<String>,
+    ascending: Option<bool>,
+    page: Option<u32>,
+    page_size: Option<u32>,
+    txid: Option<TxID>,
+) -> Result<Json<(Vec<SequenceSearchHit>, u32)>, Error> {
+    let sm = &state.storage_manager;
+    let txid = txid.unwrap_or(0);
+
+    let (hits, num_pages) = sm
+        .search_sequences(
+            search,
+            tag,
+            min_duration,
+            sort_by,
+            ascending,
+            page,
+            page_size,
+            txid,
+        )
+        .await?;
+
+    Ok(Json((hits, num_pages)))
+}
+
 #[get("/entries/<entry_id>/tx/<txid>")]
 pub async fn get_entry(
     state: &State<AppState>,
+    _auth: RequireViewer,
     entry_id: EntryID,
     txid: TxID,
-) -> Result<Json<Entry>, Error> {
+) -> Result<Versioned<Entry>, Error> {
     let sm = &state.storage_manager;
 
     let entry = sm.get_entry(entry_id, txid).await?;
     match entry {
-        Some(e) => Ok(Json(e)),
+        Some(e) => Ok(Versioned {
+            version: e.version,
+            body: e,
+        }),
         None => not_found(format!("entry {entry_id} not found")),
     }
 }
@@ -230,14 +638,18 @@ pub async fn get_entry(
 #[get("/paths/tx/<txid>?<path>")]
 pub async fn get_entry_by_path(
     state: &State<AppState>,
+    _auth: RequireViewer,
     path: String,
     txid: TxID,
-) -> Result<Json<Entry>, Error> {
+) -> Result<Versioned<Entry>, Error> {
     let sm = &state.storage_manager;
 
     let entry = sm.get_entry_by_path(path.clone(), txid).await?;
     match entry {
-        Some(e) => Ok(Json(e)),
+        Some(e) => Ok(Versioned {
+            version: e.version,
+            body: e,
+        }),
         None => not_found(format!("entry with path '{path}' not found")),
     }
 }
@@ -245,6 +657,7 @@ pub async fn get_entry_by_path(
 #[get("/entries/<entry_id>/sequences/tx/<txid>")]
 pub async fn get_sequences(
     state: &State<AppState>,
+    _auth: RequireViewer,
     entry_id: EntryID,
     txid: TxID,
 ) -> Result<Json<Map<SequenceID, Sequence>>, Error> {
@@ -254,9 +667,31 @@ pub async fn get_sequences(
     Ok(Json(sequences))
 }
 
+#[get("/entries/<entry_id>/sequences/<sequence_id>/tx/<txid>")]
+pub async fn get_sequence(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+    entry_id: EntryID,
+    sequence_id: SequenceID,
+    txid: TxID,
+) -> Result<Versioned<Sequence>, Error> {
+    let sm = &state.storage_manager;
+
+    match sm.get_sequence(entry_id, sequence_id, txid).await? {
+        Some(s) => Ok(Versioned {
+            version: s.version,
+            body: s,
+        }),
+        None => not_found(format!(
+            "sequence {sequence_id} of entry {entry_id} not found"
+        )),
+    }
+}
+
 #[get("/entries/<entry_id>/topics/tx/<txid>")]
 pub async fn get_topics(
     state: &State<AppState>,
+    _auth: RequireViewer,
     entry_id: EntryID,
     txid: TxID,
 ) -> Result<Json<Map<TopicID, Topic>>, Error> {
@@ -269,6 +704,7 @@ pub async fn get_topics(
 #[get("/entries/<entry_id>/sensors/tx/<txid>")]
 pub async fn get_sensors(
     state: &State<AppState>,
+    _auth: RequireViewer,
     entry_id: EntryID,
     txid: TxID,
 ) -> Result<Json<Map<SensorID, Sensor>>, Error> {
@@ -278,9 +714,29 @@ pub async fn get_sensors(
     Ok(Json(sensors))
 }
 
+#[get("/entries/<entry_id>/sensors/<sensor_id>/tx/<txid>")]
+pub async fn get_sensor(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+    entry_id: EntryID,
+    sensor_id: SensorID,
+    txid: TxID,
+) -> Result<Versioned<Sensor>, Error> {
+    let sm = &state.storage_manager;
+
+    match sm.get_sensor(entry_id, sensor_id, txid).await? {
+        Some(s) => Ok(Versioned {
+            version: s.version,
+            body: s,
+        }),
+        None => not_found(format!("sensor {sensor_id} of entry {entry_id} not found")),
+    }
+}
+
 #[get("/sensors/tx/<txid>")]
 pub async fn get_all_sensors(
     state: &State<AppState>,
+    _auth: RequireViewer,
     txid: TxID,
 ) -> Result<Json<Map<SensorID, Sensor>>, Error> {
     let sm = &state.storage_manager;
@@ -296,10 +752,13 @@ pub async fn get_all_sensors(
 )]
 pub async fn add_sensor(
     state: &State<AppState>,
+    _auth: RequireEditor,
     entry_id: EntryID,
     sensor: Json<SensorWeb>,
     txid: TxID,
+    audit: AuditDetail<'_>,
 ) -> Result<status::Created<Json<SensorID>>, Error> {
+    audit.record("body", &*sensor);
     let sm = &state.storage_manager;
 
     let s = sensor.into_inner();
@@ -311,6 +770,7 @@ pub async fn add_sensor(
         sensor_type: s.sensor_type,
         ros_topics: s.ros_topics,
         custom_parameters: s.custom_parameters,
+        version: 0,
     };
 
     let new_id = sm.add_sensor(storage_sensor, txid).await?;
@@ -324,11 +784,15 @@ pub async fn add_sensor(
 )]
 pub async fn update_sensor(
     state: &State<AppState>,
+    _auth: RequireEditor,
     entry_id: EntryID,
     sensor_id: SensorID,
     sensor: Json<SensorWeb>,
     txid: TxID,
+    if_match: IfMatch,
+    audit: AuditDetail<'_>,
 ) -> Result<status::NoContent, Error> {
+    audit.record("body", &*sensor);
     let sm = &state.storage_manager;
     let s = sensor.into_inner();
 
@@ -340,23 +804,122 @@ pub async fn update_sensor(
         sensor_type: s.sensor_type,
         ros_topics: s.ros_topics,
         custom_parameters: s.custom_parameters,
+        version: 0,
     };
 
-    sm.update_sensor(storage_sensor, txid).await?;
+    sm.update_sensor(storage_sensor, if_match.0, txid).await?;
     Ok(status::NoContent)
 }
 
 #[delete("/sensors/<sensor_id>/tx/<txid>")]
 pub async fn remove_sensor(
     state: &State<AppState>,
+    _auth: RequireEditor,
     sensor_id: SensorID,
     txid: TxID,
+    if_match: IfMatch,
+) -> Result<status::NoContent, Error> {
+    let sm = &state.storage_manager;
+    sm.remove_sensor(sensor_id, if_match.0, txid).await?;
+    Ok(status::NoContent)
+}
+
+/// Globaler Sensorkatalog; `search` filtert über Name, Hersteller, Typ, Modell und Seriennummer.
+#[get("/sensors/catalog?<search>&<txid>")]
+pub async fn get_sensor_catalog(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+    search: Option<String>,
+    txid: Option<TxID>,
+) -> Result<Json<Vec<CatalogSensorWithUsage>>, Error> {
+    let sm = &state.storage_manager;
+    let sensors = sm.get_sensor_catalog(search, txid.unwrap_or(0)).await?;
+    Ok(Json(sensors))
+}
+
+#[get("/sensors/catalog/<catalog_sensor_id>/entries?<txid>")]
+pub async fn get_catalog_sensor_entries(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+    catalog_sensor_id: CatalogSensorID,
+    txid: Option<TxID>,
+) -> Result<Json<Vec<CatalogSensorUsage>>, Error> {
+    let sm = &state.storage_manager;
+    let usages = sm
+        .get_catalog_sensor_entries(catalog_sensor_id, txid.unwrap_or(0))
+        .await?;
+    Ok(Json(usages))
+}
+
+#[get("/entries/<entry_id>/sensors/catalog?<txid>")]
+pub async fn get_entry_sensor_assignments(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+    entry_id: EntryID,
+    txid: Option<TxID>,
+) -> Result<Json<Vec<EntrySensorAssignment>>, Error> {
+    let sm = &state.storage_manager;
+    let assignments = sm
+        .get_entry_sensor_assignments(entry_id, txid.unwrap_or(0))
+        .await?;
+    Ok(Json(assignments))
+}
+
+#[put(
+    "/sensors/catalog/<catalog_sensor_id>?<txid>",
+    format = "json",
+    data = "<sensor>"
+)]
+pub async fn update_catalog_sensor(
+    state: &State<AppState>,
+    _auth: RequireEditor,
+    catalog_sensor_id: CatalogSensorID,
+    sensor: Json<CatalogSensorWeb>,
+    txid: Option<TxID>,
+    if_match: IfMatch,
+    audit: AuditDetail<'_>,
 ) -> Result<status::NoContent, Error> {
+    audit.record("body", &*sensor);
     let sm = &state.storage_manager;
-    sm.remove_sensor(sensor_id, txid).await?;
+    let s = sensor.into_inner();
+    let now = Utc::now();
+
+    let catalog_sensor = CatalogSensor {
+        id: catalog_sensor_id,
+        sensor_name: s.sensor_name,
+        manufacturer: s.manufacturer,
+        sensor_type: s.sensor_type,
+        model: s.model,
+        serial_number: s.serial_number,
+        calibration: s.calibration,
+        custom_parameters: s.custom_parameters,
+        created_at: now,
+        updated_at: now,
+        version: 0,
+    };
+
+    sm.update_catalog_sensor(catalog_sensor, if_match.0, txid.unwrap_or(0))
+        .await?;
     Ok(status::NoContent)
 }
 
+#[post("/sensors/catalog/merge?<txid>", format = "json", data = "<merge>")]
+pub async fn merge_catalog_sensors(
+    state: &State<AppState>,
+    _auth: RequireEditor,
+    merge: Json<MergeCatalogSensorsWeb>,
+    txid: Option<TxID>,
+    audit: AuditDetail<'_>,
+) -> Result<Json<CatalogSensor>, Error> {
+    audit.record("body", &*merge);
+    let sm = &state.storage_manager;
+    let m = merge.into_inner();
+    let merged = sm
+        .merge_catalog_sensors(m.target_id, m.source_ids, txid.unwrap_or(0))
+        .await?;
+    Ok(Json(merged))
+}
+
 #[post(
     "/entries/<entry_id>/sequences/tx/<txid>",
     format = "json",
@@ -364,10 +927,13 @@ pub async fn remove_sensor(
 )]
 pub async fn add_sequence(
     state: &State<AppState>,
+    _auth: RequireEditor,
     entry_id: EntryID,
     sequence: Json<SequenceWeb>,
     txid: TxID,
+    audit: AuditDetail<'_>,
 ) -> Result<status::Created<Json<SequenceID>>, Error> {
+    audit.record("body", &*sequence);
     let sm = &state.storage_manager;
     let s = sequence.into_inner();
     let storage_sequence = Sequence {
@@ -380,6 +946,7 @@ pub async fn add_sequence(
         created_at: Utc::now(),
         updated_at: Utc::now(),
         tags: s.tags,
+        version: 0,
     };
 
     let new_id = sm.add_sequence(entry_id, storage_sequence, txid).await?;
@@ -393,11 +960,15 @@ pub async fn add_sequence(
 )]
 pub async fn update_sequence(
     state: &State<AppState>,
+    _auth: RequireEditor,
     entry_id: EntryID,
     sequence_id: SequenceID,
     sequence: Json<SequenceWeb>,
     txid: TxID,
+    if_match: IfMatch,
+    audit: AuditDetail<'_>,
 ) -> Result<status::NoContent, Error> {
+    audit.record("body", &*sequence);
     let sm = &state.storage_manager;
     let s = sequence.into_inner();
 
@@ -411,9 +982,10 @@ pub async fn update_sequence(
         created_at: Utc::now(),
         updated_at: Utc::now(),
         tags: s.tags,
+        version: 0,
     };
 
-    sm.update_sequence(entry_id, sequence_id, storage_sequence, txid)
+    sm.update_sequence(entry_id, sequence_id, storage_sequence, if_match.0, txid)
         .await?;
     Ok(status::NoContent)
 }
@@ -421,49 +993,760 @@ pub async fn update_sequence(
 #[delete("/entries/<entry_id>/sequences/<sequence_id>/tx/<txid>")]
 pub async fn remove_sequence(
     state: &State<AppState>,
+    _auth: RequireEditor,
     entry_id: EntryID,
     sequence_id: SequenceID,
     txid: TxID,
+    if_match: IfMatch,
 ) -> Result<status::NoContent, Error> {
     let sm = &state.storage_manager;
-    sm.remove_sequence(entry_id, sequence_id, txid).await?;
+    sm.remove_sequence(entry_id, sequence_id, if_match.0, txid)
+        .await?;
     Ok(status::NoContent)
 }
 
+#[post(
+    "/entries/<entry_id>/sequences/<sequence_id>/split/tx/<txid>",
+    format = "json",
+    data = "<split>"
+)]
+pub async fn split_sequence(
+    state: &State<AppState>,
+    _auth: RequireEditor,
+    entry_id: EntryID,
+    sequence_id: SequenceID,
+    split: Json<SplitSequenceWeb>,
+    txid: TxID,
+    if_match: IfMatch,
+    audit: AuditDetail<'_>,
+) -> Result<Json<(Sequence, Sequence)>, Error> {
+    audit.record("body", &*split);
+    let sm = &state.storage_manager;
+    let halves = sm
+        .split_sequence(entry_id, sequence_id, split.at, if_match.0, txid)
+        .await?;
+    Ok(Json(halves))
+}
+
+#[post(
+    "/entries/<entry_id>/sequences/merge/tx/<txid>",
+    format = "json",
+    data = "<merge>"
+)]
+pub async fn merge_sequences(
+    state: &State<AppState>,
+    _auth: RequireEditor,
+    entry_id: EntryID,
+    merge: Json<MergeSequencesWeb>,
+    txid: TxID,
+    audit: AuditDetail<'_>,
+) -> Result<Json<Sequence>, Error> {
+    audit.record("body", &*merge);
+    let sm = &state.storage_manager;
+    let m = merge.into_inner();
+    let merged = sm
+        .merge_sequences(entry_id, m.sequence_ids, m.name, txid)
+        .await?;
+    Ok(Json(merged))
+}
+
+#[get("/entries/<entry_id>/sequences/overlaps/tx/<txid>?<start>&<end>")]
+pub async fn get_overlapping_sequences(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+    entry_id: EntryID,
+    start: i64,
+    end: i64,
+    txid: TxID,
+) -> Result<Json<Map<SequenceID, Sequence>>, Error> {
+    let sm = &state.storage_manager;
+    let sequences = sm
+        .get_overlapping_sequences(entry_id, start, end, txid)
+        .await?;
+    Ok(Json(sequences))
+}
+
+/// MCAP-Datei als Download (`Content-Disposition: attachment`).
+#[derive(rocket::Responder)]
+#[response(content_type = "binary")]
+pub struct McapDownload {
+    inner: NamedFile,
+    disposition: Header<'static>,
+}
+
+impl McapDownload {
+    pub async fn open(path: &std::path::Path) -> Result<Self, Error> {
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().replace('"', ""))
+            .unwrap_or_else(|| "clip.mcap".to_string());
+        let inner = NamedFile::open(path).await.map_err(StorageError::from)?;
+        Ok(McapDownload {
+            inner,
+            disposition: Header::new(
+                "Content-Disposition",
+                format!("attachment; filename=\"{file_name}\""),
+            ),
+        })
+    }
+}
+
+/// Schneidet eine Sequenz als MCAP-Clip aus; `topics` ist eine kommagetrennte Liste.
+#[get("/entries/<entry_id>/sequences/<sequence_id>/clip?<topics>&<txid>")]
+pub async fn get_sequence_clip(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+    entry_id: EntryID,
+    sequence_id: SequenceID,
+    topics: Option<String>,
+    txid: Option<TxID>,
+) -> Result<McapDownload, Error> {
+    let sm = &state.storage_manager;
+    let txid = txid.unwrap_or(0);
+    let topics = topics.map(|t| {
+        t.split(',')
+            .map(|topic| topic.trim().to_string())
+            .collect::<Vec<String>>()
+    });
+
+    let path = sm
+        .export_sequence_clip(entry_id, sequence_id, topics, txid)
+        .await?;
+    McapDownload::open(&path).await
+}
+
+#[get("/entries/<entry_id>/consistency?<txid>")]
+pub async fn get_entry_consistency(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+    entry_id: EntryID,
+    txid: Option<TxID>,
+) -> Result<Json<EntryConsistency>, Error> {
+    let sm = &state.storage_manager;
+    match sm
+        .get_entry_consistency(entry_id, txid.unwrap_or(0))
+        .await?
+    {
+        Some(report) => Ok(Json(report)),
+        None => not_found(format!("entry {entry_id} has not been checked yet")),
+    }
+}
+
+/// Ergebnisse von Plugin-Läufen an einem Eintrag, optional nur einer Art.
+#[get("/entries/<entry_id>/artifacts?<kind>&<txid>")]
+pub async fn get_entry_artifacts(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+    entry_id: EntryID,
+    kind: Option<String>,
+    txid: Option<TxID>,
+) -> Result<Json<Vec<EntryArtifact>>, Error> {
+    let sm = &state.storage_manager;
+    let artifacts = sm
+        .get_entry_artifacts(entry_id, kind, txid.unwrap_or(0))
+        .await?;
+    Ok(Json(artifacts))
+}
+
+/// Führt die Sensor/Topic-Konsistenzprüfung sofort aus und speichert das Ergebnis.
+#[post("/entries/<entry_id>/consistency?<txid>")]
+pub async fn check_entry_consistency(
+    state: &State<AppState>,
+    _auth: RequireEditor,
+    entry_id: EntryID,
+    txid: Option<TxID>,
+) -> Result<Json<EntryConsistency>, Error> {
+    let sm = &state.storage_manager;
+    let report = sm
+        .check_entry_consistency(entry_id, txid.unwrap_or(0))
+        .await?;
+    Ok(Json(report))
+}
+
+#[get("/entries/<entry_id>/timeline/tx/<txid>")]
+pub async fn get_timeline(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+    entry_id: EntryID,
+    txid: TxID,
+) -> Result<Json<Timeline>, Error> {
+    let sm = &state.storage_manager;
+    let timeline = sm.get_timeline(entry_id, txid).await?;
+    Ok(Json(timeline))
+}
+
 #[put("/entries/<entry_id>/tags/tx/<txid>", data = "<tag>")]
 pub async fn add_tag(
     state: &State<AppState>,
+    _auth: RequireEditor,
     entry_id: EntryID,
     tag: String,
     txid: TxID,
+    if_match: IfMatch,
+    audit: AuditDetail<'_>,
 ) -> Result<status::NoContent, Error> {
+    audit.record("tag", &tag);
     let sm = &state.storage_manager;
-    sm.add_tag(entry_id, tag, txid).await?;
+    sm.add_tag(entry_id, tag, if_match.0, txid).await?;
     Ok(status::NoContent)
 }
 
 #[delete("/entries/<entry_id>/tags/tx/<txid>", data = "<tag>")]
 pub async fn remove_tag(
     state: &State<AppState>,
+    _auth: RequireEditor,
     entry_id: EntryID,
     tag: String,
     txid: TxID,
+    if_match: IfMatch,
+    audit: AuditDetail<'_>,
+) -> Result<status::NoContent, Error> {
+    audit.record("tag", &tag);
+    let sm = &state.storage_manager;
+    sm.remove_tag(entry_id, tag, if_match.0, txid).await?;
+    Ok(status::NoContent)
+}
+
+#[get("/tags?<namespace>&<search>&<txid>")]
+pub async fn get_tags(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+    namespace: Option<String>,
+    search: Option<String>,
+    txid: Option<TxID>,
+) -> Result<Json<Vec<TagInfo>>, Error> {
+    let sm = &state.storage_manager;
+    let tags = sm.get_tags(namespace, search, txid.unwrap_or(0)).await?;
+    Ok(Json(tags))
+}
+
+#[put("/tags/<name>?<txid>", format = "json", data = "<definition>")]
+pub async fn update_tag_definition(
+    state: &State<AppState>,
+    _auth: RequireEditor,
+    name: String,
+    definition: Json<TagDefinitionWeb>,
+    txid: Option<TxID>,
+    if_match: IfMatch,
+    audit: AuditDetail<'_>,
+) -> Result<Versioned<TagDefinition>, Error> {
+    audit.record("body", &*definition);
+    let sm = &state.storage_manager;
+    let d = definition.into_inner();
+    let stored = sm
+        .update_tag_definition(name, d.color, d.description, if_match.0, txid.unwrap_or(0))
+        .await?;
+    Ok(Versioned {
+        version: stored.version,
+        body: stored,
+    })
+}
+
+#[delete("/tags/<name>?<txid>")]
+pub async fn delete_tag(
+    state: &State<AppState>,
+    _auth: RequireEditor,
+    name: String,
+    txid: Option<TxID>,
+) -> Result<Json<TagChangeCount>, Error> {
+    let sm = &state.storage_manager;
+    let changed = sm.delete_tag(name, txid.unwrap_or(0)).await?;
+    Ok(Json(changed))
+}
+
+#[put("/tags/<name>/rename?<txid>", format = "json", data = "<rename>")]
+pub async fn rename_tag(
+    state: &State<AppState>,
+    _auth: RequireEditor,
+    name: String,
+    rename: Json<RenameTagWeb>,
+    txid: Option<TxID>,
+    audit: AuditDetail<'_>,
+) -> Result<Json<TagChangeCount>, Error> {
+    audit.record("body", &*rename);
+    let sm = &state.storage_manager;
+    let changed = sm
+        .rename_tag(name, rename.into_inner().new_name, txid.unwrap_or(0))
+        .await?;
+    Ok(Json(changed))
+}
+
+#[post("/tags/merge?<txid>", format = "json", data = "<merge>")]
+pub async fn merge_tags(
+    state: &State<AppState>,
+    _auth: RequireEditor,
+    merge: Json<MergeTagsWeb>,
+    txid: Option<TxID>,
+    audit: AuditDetail<'_>,
+) -> Result<Json<TagChangeCount>, Error> {
+    audit.record("body", &*merge);
+    let sm = &state.storage_manager;
+    let m = merge.into_inner();
+    let changed = sm
+        .merge_tags(m.sources, m.target, txid.unwrap_or(0))
+        .await?;
+    Ok(Json(changed))
+}
+
+/// Liefert die Zahl der getroffenen Einträge.
+#[post("/tags/bulk?<txid>", format = "json", data = "<bulk>")]
+pub async fn bulk_tag_entries(
+    state: &State<AppState>,
+    _auth: RequireEditor,
+    bulk: Json<BulkTagWeb>,
+    txid: Option<TxID>,
+    audit: AuditDetail<'_>,
+) -> Result<Json<usize>, Error> {
+    audit.record("body", &*bulk);
+    let sm = &state.storage_manager;
+    let b = bulk.into_inner();
+    let filter = EntryFilter {
+        consistency: b.consistency,
+    };
+    let matched = sm
+        .bulk_tag_entries(b.search_string, filter, b.add, b.remove, txid.unwrap_or(0))
+        .await?;
+    Ok(Json(matched))
+}
+
+#[get("/collections?<txid>")]
+pub async fn get_collections(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+    txid: Option<TxID>,
+) -> Result<Json<Vec<CollectionSummary>>, Error> {
+    let sm = &state.storage_manager;
+    let collections = sm.get_collections(txid.unwrap_or(0)).await?;
+    Ok(Json(collections))
+}
+
+#[post("/collections?<txid>", format = "json", data = "<collection>")]
+pub async fn create_collection(
+    state: &State<AppState>,
+    _auth: RequireEditor,
+    collection: Json<CollectionWeb>,
+    txid: Option<TxID>,
+    audit: AuditDetail<'_>,
+) -> Result<status::Created<Json<Collection>>, Error> {
+    audit.record("body", &*collection);
+    let sm = &state.storage_manager;
+    let c = collection.into_inner();
+    let created = sm
+        .create_collection(c.name, c.description, txid.unwrap_or(0))
+        .await?;
+    Ok(status::Created::new(format!("/collections/{}", created.id)).body(Json(created)))
+}
+
+#[get("/collections/<collection_id>?<txid>")]
+pub async fn get_collection(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+    collection_id: CollectionID,
+    txid: Option<TxID>,
+) -> Result<Versioned<CollectionDetails>, Error> {
+    let sm = &state.storage_manager;
+    match sm.get_collection(collection_id, txid.unwrap_or(0)).await? {
+        Some(details) => Ok(Versioned {
+            version: details.collection.version,
+            body: details,
+        }),
+        None => not_found(format!("collection {collection_id} not found")),
+    }
+}
+
+#[put(
+    "/collections/<collection_id>?<txid>",
+    format = "json",
+    data = "<collection>"
+)]
+pub async fn update_collection(
+    state: &State<AppState>,
+    _auth: RequireEditor,
+    collection_id: CollectionID,
+    collection: Json<CollectionWeb>,
+    txid: Option<TxID>,
+    if_match: IfMatch,
+    audit: AuditDetail<'_>,
+) -> Result<status::NoContent, Error> {
+    audit.record("body", &*collection);
+    let sm = &state.storage_manager;
+    let c = collection.into_inner();
+    sm.update_collection(
+        collection_id,
+        c.name,
+        c.description,
+        if_match.0,
+        txid.unwrap_or(0),
+    )
+    .await?;
+    Ok(status::NoContent)
+}
+
+/// Sammlungen mit eingefrorenen Snapshots können nicht gelöscht werden.
+#[delete("/collections/<collection_id>?<txid>")]
+pub async fn delete_collection(
+    state: &State<AppState>,
+    _auth: RequireEditor,
+    collection_id: CollectionID,
+    txid: Option<TxID>,
+) -> Result<status::NoContent, Error> {
+    let sm = &state.storage_manager;
+    sm.delete_collection(collection_id, txid.unwrap_or(0))
+        .await?;
+    Ok(status::NoContent)
+}
+
+/// Fügt Einträge oder einzelne Sequenzen hinzu; liefert nur die neu
+/// hinzugekommenen Elemente.
+#[post(
+    "/collections/<collection_id>/items?<txid>",
+    format = "json",
+    data = "<items>"
+)]
+pub async fn add_collection_items(
+    state: &State<AppState>,
+    _auth: RequireEditor,
+    collection_id: CollectionID,
+    items: Json<Vec<CollectionItemWeb>>,
+    txid: Option<TxID>,
+    audit: AuditDetail<'_>,
+) -> Result<Json<Vec<CollectionItem>>, Error> {
+    audit.record("body", &*items);
+    let sm = &state.storage_manager;
+    let items = items
+        .into_inner()
+        .into_iter()
+        .map(|i| (i.entry_id, i.sequence_id))
+        .collect();
+    let added = sm
+        .add_collection_items(collection_id, items, txid.unwrap_or(0))
+        .await?;
+    Ok(Json(added))
+}
+
+#[delete("/collections/<collection_id>/items/<item_id>?<txid>")]
+pub async fn remove_collection_item(
+    state: &State<AppState>,
+    _auth: RequireEditor,
+    collection_id: CollectionID,
+    item_id: i64,
+    txid: Option<TxID>,
 ) -> Result<status::NoContent, Error> {
     let sm = &state.storage_manager;
-    sm.remove_tag(entry_id, tag, txid).await?;
+    sm.remove_collection_item(collection_id, item_id, txid.unwrap_or(0))
+        .await?;
     Ok(status::NoContent)
 }
 
+/// Friert den aktuellen Stand als neue, unveränderliche Snapshot-Version ein.
+#[post(
+    "/collections/<collection_id>/snapshots?<txid>",
+    format = "json",
+    data = "<snapshot>"
+)]
+pub async fn freeze_collection(
+    state: &State<AppState>,
+    _auth: RequireEditor,
+    collection_id: CollectionID,
+    snapshot: Json<FreezeCollectionWeb>,
+    txid: Option<TxID>,
+    audit: AuditDetail<'_>,
+) -> Result<status::Created<Json<CollectionSnapshotDetails>>, Error> {
+    audit.record("body", &*snapshot);
+    let sm = &state.storage_manager;
+    let details = sm
+        .freeze_collection(collection_id, snapshot.into_inner().note, txid.unwrap_or(0))
+        .await?;
+    let location = format!(
+        "/collections/{collection_id}/snapshots/{}",
+        details.snapshot.snapshot_version
+    );
+    Ok(status::Created::new(location).body(Json(details)))
+}
+
+#[get("/collections/<collection_id>/snapshots?<txid>")]
+pub async fn get_collection_snapshots(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+    collection_id: CollectionID,
+    txid: Option<TxID>,
+) -> Result<Json<Vec<CollectionSnapshot>>, Error> {
+    let sm = &state.storage_manager;
+    let txid = txid.unwrap_or(0);
+    if sm.get_collection(collection_id, txid).await?.is_none() {
+        return not_found(format!("collection {collection_id} not found"));
+    }
+    let snapshots = sm.get_collection_snapshots(collection_id, txid).await?;
+    Ok(Json(snapshots))
+}
+
+#[get("/collections/<collection_id>/snapshots/<version>?<txid>")]
+pub async fn get_collection_snapshot(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+    collection_id: CollectionID,
+    version: i32,
+    txid: Option<TxID>,
+) -> Result<Json<CollectionSnapshotDetails>, Error> {
+    let sm = &state.storage_manager;
+    match sm
+        .get_collection_snapshot(collection_id, version, txid.unwrap_or(0))
+        .await?
+    {
+        Some(details) => Ok(Json(details)),
+        None => not_found(format!(
+            "snapshot {version} of collection {collection_id} not found"
+        )),
+    }
+}
+
+/// Unterschied zwischen zwei Snapshot-Versionen. Ohne `from` wird der neueste
+/// Snapshot verwendet, ohne `to` der aktuelle Stand.
+#[get("/collections/<collection_id>/diff?<from>&<to>&<txid>")]
+pub async fn diff_collection(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+    collection_id: CollectionID,
+    from: Option<i32>,
+    to: Option<i32>,
+    txid: Option<TxID>,
+) -> Result<Json<CollectionDiff>, Error> {
+    let sm = &state.storage_manager;
+    let diff = sm
+        .diff_collection(collection_id, from, to, txid.unwrap_or(0))
+        .await?;
+    Ok(Json(diff))
+}
+
+/// Export eines Snapshots als Manifest für Trainings-Pipelines.
+#[derive(rocket::Responder)]
+pub enum SnapshotExport {
+    Json(Json<CollectionSnapshotDetails>),
+    #[response(content_type = "text/csv")]
+    Csv(String, Header<'static>),
+}
+
+/// Exportiert einen Snapshot als `json` (Standard) oder `csv`.
+#[get("/collections/<collection_id>/snapshots/<version>/export?<format>&<txid>")]
+pub async fn export_collection_snapshot(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+    collection_id: CollectionID,
+    version: i32,
+    format: Option<String>,
+    txid: Option<TxID>,
+) -> Result<SnapshotExport, Error> {
+    let sm = &state.storage_manager;
+    let Some(details) = sm
+        .get_collection_snapshot(collection_id, version, txid.unwrap_or(0))
+        .await?
+    else {
+        return not_found(format!(
+            "snapshot {version} of collection {collection_id} not found"
+        ));
+    };
+    match format.as_deref().unwrap_or("json") {
+        "json" => Ok(SnapshotExport::Json(Json(details))),
+        "csv" => {
+            let file_name = format!(
+                "{}-v{}.csv",
+                details.collection_name.replace(['"', '/', '\\'], "_"),
+                version
+            );
+            Ok(SnapshotExport::Csv(
+                collections::items_to_csv(&details.items),
+                Header::new(
+                    "Content-Disposition",
+                    format!("attachment; filename=\"{file_name}\""),
+                ),
+            ))
+        }
+        other => Err(StorageError::ValidationError(format!(
+            "unsupported export format '{other}', expected json or csv"
+        ))
+        .into()),
+    }
+}
+
+/// Anzahl Einträge, deren Details je Block geladen und ausgegeben werden.
+const EXPORT_CHUNK_SIZE: usize = 200;
+
+/// Beliebige Antwort als Download (`Content-Disposition: attachment`).
+#[derive(rocket::Responder)]
+pub struct Attachment<R> {
+    inner: R,
+    disposition: Header<'static>,
+}
+
+impl<R> Attachment<R> {
+    pub fn new(inner: R, file_name: &str) -> Self {
+        Attachment {
+            inner,
+            disposition: Header::new(
+                "Content-Disposition",
+                format!("attachment; filename=\"{}\"", file_name.replace('"', "")),
+            ),
+        }
+    }
+}
+
+/// Katalog als flache Datei (`json`, `csv` oder `parquet`) mit denselben Filtern
+/// wie `GET /entries`.
+///
+/// Die Einträge werden vorab gefiltert (Fehler dort ergeben 400), Topics,
+/// Sensoren und Sequenzen danach blockweise geladen und sofort gestreamt.
+#[get("/export?<format>&<search_string>&<consistency>&<sort_by>&<ascending>&<txid>")]
+#[allow(clippy::too_many_arguments)]
+pub async fn export_manifest(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+    format: Option<String>,
+    search_string: Option<String>,
+    consistency: Option<String>,
+    sort_by: Option<String>,
+    ascending: Option<bool>,
+    txid: Option<TxID>,
+) -> Result<Attachment<(ContentType, ByteStream![Vec<u8>])>, Error> {
+    let sm = state.storage_manager.clone();
+    let txid = txid.unwrap_or(0);
+    let format = ManifestFormat::parse(format.as_deref().unwrap_or("json"))?;
+    let filter = ManifestFilter {
+        search_string: search_string.clone(),
+        consistency: consistency.clone(),
+        sort_by: sort_by.clone(),
+        ascending,
+    };
+    let (entries, _) = sm
+        .get_entries_filtered(
+            search_string,
+            EntryFilter { consistency },
+            sort_by,
+            ascending,
+            None,
+            None,
+            txid,
+        )
+        .await?;
+
+    let header = ManifestHeader::new(filter, entries.len());
+    let file_name = format!(
+        "manifest-{}.{}",
+        header.generated_at.format("%Y%m%dT%H%M%SZ"),
+        format.extension()
+    );
+    let content_type = match format {
+        ManifestFormat::Json => ContentType::JSON,
+        ManifestFormat::Csv => ContentType::CSV,
+        ManifestFormat::Parquet => ContentType::new("application", "vnd.apache.parquet"),
+    };
+    let mut encoder = ManifestEncoder::new(format, header)?;
+    let begin = encoder.begin()?;
+
+    let stream = ByteStream! {
+        yield begin;
+        for chunk in entries.chunks(EXPORT_CHUNK_SIZE) {
+            let encoded = match sm.get_manifest_rows(chunk.to_vec(), txid).await {
+                Ok(rows) => encoder.encode(&rows),
+                Err(e) => Err(e),
+            };
+            match encoded {
+                Ok(bytes) => yield bytes,
+                Err(e) => {
+                    // Status ist bereits gesendet; der Client erkennt den Abbruch
+                    // am unvollständigen Dokument.
+                    tracing::error!("manifest export aborted: {:?}", e);
+                    return;
+                }
+            }
+        }
+        match encoder.finish() {
+            Ok(bytes) => yield bytes,
+            Err(e) => tracing::error!("manifest export aborted: {:?}", e),
+        }
+    };
+    Ok(Attachment::new((content_type, stream), &file_name))
+}
+
+/// Größte angenommene Import-Datei.
+const IMPORT_LIMIT_MIB: u64 = 16;
+
+/// Importiert Metadaten, Tags und Sequenzen für viele Einträge aus CSV oder JSON.
+///
+/// Zeilen werden über `path` (bevorzugt) oder `name` zugeordnet; eine Manifest-Datei
+/// aus `GET /export` kann direkt zurückgespielt werden. Mit `dry_run` oder bei
+/// mindestens einer ungültigen Zeile wird nichts geschrieben; der Bericht kommt
+/// dann mit 422 zurück, wenn Zeilen ungültig sind.
+#[post("/import/metadata?<format>&<dry_run>&<txid>", data = "<data>")]
+pub async fn import_metadata(
+    state: &State<AppState>,
+    _auth: RequireEditor,
+    content_type: Option<&ContentType>,
+    data: Data<'_>,
+    format: Option<String>,
+    dry_run: Option<bool>,
+    txid: Option<TxID>,
+    audit: AuditDetail<'_>,
+) -> Result<status::Custom<Json<MetadataImportReport>>, Error> {
+    let sm = &state.storage_manager;
+    let txid = txid.unwrap_or(0);
+    let format = match format.as_deref() {
+        Some(f) => f.to_ascii_lowercase(),
+        None if content_type.is_some_and(|ct| ct.is_csv()) => "csv".to_string(),
+        None => "json".to_string(),
+    };
+    let body = data
+        .open(IMPORT_LIMIT_MIB.mebibytes())
+        .into_string()
+        .await
+        .map_err(StorageError::from)?;
+    if !body.is_complete() {
+        return Err(StorageError::ValidationError(format!(
+            "import file larger than {IMPORT_LIMIT_MIB} MiB"
+        ))
+        .into());
+    }
+    let rows = match format.as_str() {
+        "csv" => metadata_import::parse_csv_rows(&body)?,
+        "json" => metadata_import::parse_json_rows(&body)?,
+        other => {
+            return Err(StorageError::ValidationError(format!(
+                "unknown import format '{other}', expected csv or json"
+            ))
+            .into());
+        }
+    };
+    // die Datei selbst ist zu groß für das Audit-Log
+    audit.record("format", &format);
+    audit.record("size_bytes", body.len());
+    audit.record("rows", rows.len());
+    let report = sm
+        .import_metadata(rows, dry_run.unwrap_or(false), txid)
+        .await?;
+    let code = if report.invalid > 0 {
+        Status::UnprocessableEntity
+    } else {
+        Status::Ok
+    };
+    Ok(status::Custom(code, Json(report)))
+}
+
 #[get("/transaction")]
-pub async fn start_transaction(state: &State<AppState>) -> Result<Json<TxID>, Error> {
+pub async fn start_transaction(
+    _auth: RequireEditor,
+    state: &State<AppState>,
+) -> Result<Json<TxID>, Error> {
     let sm = &state.storage_manager;
     let txid = sm.start_transaction();
     Ok(Json(txid))
 }
 
+/// Ändert trotz GET den Datenbestand und wird daher ebenfalls protokolliert.
 #[get("/transaction/<txid>/commit")]
 pub async fn commit_transaction(
+    // vor dem Auth-Guard, damit auch abgewiesene Versuche im Audit-Log landen
+    _audit: AuditDetail<'_>,
     state: &State<AppState>,
+    _auth: RequireEditor,
     txid: TxID,
 ) -> Result<status::NoContent, Error> {
     let sm = &state.storage_manager;
@@ -0,0 +1,46 @@
+use crate::AppState;
+use crate::error::Error;
+use crate::events::EventFilter;
+use crate::routes::auth::RequireViewer;
+use crate::storage::models::EntryID;
+use rocket::response::stream::{Event, EventStream};
+use rocket::tokio::select;
+use rocket::tokio::sync::broadcast::error::RecvError;
+use rocket::{Shutdown, State, get};
+
+/// Server-Sent Events über Änderungen an Einträgen, Metadaten und Plugin-Instanzen.
+///
+/// `types` ist eine Komma-Liste aus [`crate::events::EVENT_TYPES`], `entry_id`
+/// beschränkt auf einen Eintrag (und blendet damit Plugin-Ereignisse aus). Der
+/// SSE-Name jedes Ereignisses ist sein Typ, die Daten sind das JSON-Objekt. Hat
+/// ein Client Ereignisse verpasst, kommt `lagged` mit deren Anzahl; er sollte
+/// dann neu laden.
+#[get("/events?<types>&<entry_id>")]
+pub fn get_events(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+    types: Option<String>,
+    entry_id: Option<EntryID>,
+    mut shutdown: Shutdown,
+) -> Result<EventStream![], Error> {
+    let filter = EventFilter::parse(types.as_deref(), entry_id)?;
+    let mut rx = state.storage_manager.events().subscribe();
+    Ok(EventStream! {
+        loop {
+            let received = select! {
+                received = rx.recv() => received,
+                _ = &mut shutdown => break,
+            };
+            match received {
+                Ok(event) if filter.matches(&event) => {
+                    yield Event::json(&event).event(event.kind());
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(missed)) => {
+                    yield Event::data(missed.to_string()).event("lagged");
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    })
+}
@@ -1,3 +1,4 @@
+use crate::routes::auth::RequireAdmin;
 use rocket::get;
 use rocket::serde::json::Json;
 use serde::Serialize;
@@ -14,7 +15,11 @@ pub struct LogEntry {
 }
 
 #[get("/logs?<level>&<limit>")]
-pub fn get_logs(level: Option<String>, limit: Option<usize>) -> Json<Vec<LogEntry>> {
+pub fn get_logs(
+    _auth: RequireAdmin,
+    level: Option<String>,
+    limit: Option<usize>,
+) -> Json<Vec<LogEntry>> {
     let log_dir = "/logs";
     let mut entries = Vec::new();
 
@@ -1,4 +1,7 @@
+pub mod audit;
+pub mod auth;
 pub mod database;
+pub mod events;
 pub mod health_check;
 pub mod logs;
 pub mod plugins;
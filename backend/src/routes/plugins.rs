@@ -2,8 +2,26 @@ use std::path::PathBuf;
 
 use crate::AppState;
 use crate::error::Error;
+use crate::error::StorageError;
+use crate::plugin_manager::logs::LogSubscription;
+use crate::plugin_manager::manager::InstanceState;
+use crate::plugin_manager::parameters::{self, FieldError, ParameterSchema};
+use crate::plugin_manager::pipeline::{PipelineConfig, PipelineRunView};
+use crate::plugin_manager::queue::{JobPriority, QueueSnapshot};
+use crate::plugin_manager::retry::RetryPolicy;
+use crate::plugin_manager::runs::{self, RunOutcome};
+use crate::plugin_manager::sandbox::SandboxLimits;
+use crate::plugin_manager::trigger_filter::TriggerFilter;
+use crate::plugin_manager::watchdog::RunLimits;
+use crate::routes::audit::AuditDetail;
+use crate::routes::auth::{RequireAdmin, RequirePluginOperator, RequireViewer};
+use crate::storage::models::{PluginLogLine, PluginRun};
+use crate::storage::storage_manager::TxID;
+use rocket::response::stream::{Event, EventStream};
 use rocket::serde::json::Json;
-use rocket::{State, get, post, put, response::status};
+use rocket::tokio::select;
+use rocket::tokio::sync::broadcast::error::RecvError;
+use rocket::{Shutdown, State, delete, get, post, put, response::status};
 
 use tokio::time::{Duration, timeout};
 use tracing::debug;
@@ -33,73 +51,142 @@ async fn lock_plugin_manager(
 pub struct PluginInfo {
     name: String,
     description: String,
+    /// Trigger samt Bedingungen aus `PLUGIN_TRIGGER_FILTER`.
     trigger: String,
+    /// Trigger-Bedingungen; `null`, wenn jedes passende Event startet.
+    trigger_filter: Option<TriggerFilter>,
     path: String,
     enabled: bool,
     valid: bool,
     instance_id: Option<u64>,
     state: Option<crate::plugin_manager::manager::InstanceState>,
     progress: Option<f32>,
+    /// Deklarierte Startparameter; `null`, wenn das Plugin keine angibt.
+    parameters: Option<ParameterSchema>,
+    /// Wiederholungsregeln; `null`, wenn gescheiterte Läufe nicht wiederholt werden.
+    retry: Option<RetryPolicy>,
+    /// Zeitlimits in Sekunden; `null` heißt unbegrenzt.
+    limits: RunLimits,
+    /// Ressourcengrenzen aus `plugins.yaml`; `null` heißt unbegrenzt.
+    sandbox: SandboxLimits,
+    /// Ob das Plugin unter `/data` schreiben darf.
+    write_access: bool,
+}
+
+/// Prüft Startdaten gegen das Schema des Plugins und ergänzt Defaults.
+///
+/// `entry`-Parameter müssen auf eine vorhandene Aufnahme zeigen. Alle
+/// Feldfehler werden gesammelt und gemeinsam gemeldet.
+async fn validate_start_data(
+    state: &State<AppState>,
+    schema: &ParameterSchema,
+    payload: serde_json::Map<String, serde_json::Value>,
+) -> Result<serde_json::Map<String, serde_json::Value>, Error> {
+    let validated = schema
+        .validate(payload)
+        .map_err(parameters::invalid_parameters)?;
+    check_entry_parameters(state, schema, &validated).await?;
+    Ok(validated)
+}
+
+/// Startdaten als JSON-Objekt; fehlende Daten oder `null` gelten als leeres Objekt.
+pub(crate) fn start_data_object(
+    payload: Option<serde_json::Value>,
+) -> Result<serde_json::Map<String, serde_json::Value>, Error> {
+    match payload {
+        None | Some(serde_json::Value::Null) => Ok(serde_json::Map::new()),
+        Some(serde_json::Value::Object(map)) => Ok(map),
+        Some(other) => Err(StorageError::ValidationError(format!(
+            "plugin start data must be a JSON object, got {other}"
+        ))
+        .into()),
+    }
+}
+
+/// Meldet `entry`-Parameter in geprüften Startdaten, zu denen es keine
+/// Aufnahme gibt, als Feldfehler.
+pub(crate) async fn check_entry_parameters(
+    state: &State<AppState>,
+    schema: &ParameterSchema,
+    validated: &serde_json::Map<String, serde_json::Value>,
+) -> Result<(), Error> {
+    let mut errors = Vec::new();
+    for (field, path) in schema.entry_paths(validated) {
+        if state
+            .storage_manager
+            .get_entry_by_path(path.to_string(), 0)
+            .await?
+            .is_none()
+        {
+            errors.push(FieldError::new(field, format!("no entry at path '{path}'")));
+        }
+    }
+    if !errors.is_empty() {
+        return Err(parameters::invalid_parameters(errors));
+    }
+    Ok(())
 }
 
 /// Startet eine neue Instanz eines Plugins.
 ///
-/// Optional kann JSON-Payload mitgegeben werden; diese wird serialisiert
-/// und an den Python-Runner durchgereicht.
+/// Optional kann ein JSON-Objekt mitgegeben werden; es wird gegen
+/// `PLUGIN_PARAMETERS` geprüft (400 mit allen Feldfehlern), um Defaults
+/// ergänzt und an den Python-Runner durchgereicht. Die Instanz wird mit
+/// Vorrang vor ausgelösten Starts eingereiht; die Antwort kommt sofort.
 #[post("/plugins/<plugin_name>/start", data = "<payload>")]
 pub async fn start_plugin_instance(
     state: &State<AppState>,
+    _auth: RequirePluginOperator,
     plugin_name: &str,
     payload: Option<Json<serde_json::Value>>,
+    audit: AuditDetail<'_>,
 ) -> Result<Json<u64>, Error> {
+    audit.record("payload", payload.as_ref().map(|p| &p.0));
     let instance_id = chrono::Utc::now().timestamp_micros().max(0) as u64;
 
-    let mut val = payload
-        .map(|p| p.into_inner())
-        .unwrap_or(serde_json::Value::Null);
-    if !val.is_object() {
-        val = serde_json::Value::Object(serde_json::Map::new());
-    }
+    let val = start_data_object(payload.map(|p| p.into_inner()))?;
 
     debug!(
-        "start_plugin_instance: plugin='{}' instance_id={} payload_json={}",
+        "start_plugin_instance: plugin='{}' instance_id={} payload_json={:?}",
         plugin_name, instance_id, val
     );
 
-    let data_str = val.to_string();
+    let (plugin_index, schema) = {
+        let pm = lock_plugin_manager(state).await?;
+        let (plugin_index, _plugin_path) = pm.prepare_start(plugin_name)?;
+        let schema = pm.registered[plugin_index].parameters().cloned();
+        (plugin_index, schema)
+    };
+
+    let val = match schema {
+        Some(schema) => validate_start_data(state, &schema, val).await?,
+        None => val,
+    };
+
+    let data_str = serde_json::Value::Object(val).to_string();
     debug!(
         "start_plugin_instance: runner data_bytes={} data={}",
         data_str.len(),
         data_str
     );
 
-    let (plugin_index, plugin_path) = {
-        let pm = lock_plugin_manager(state).await?;
-        pm.prepare_start(plugin_name)?
-    };
-
-    let handle = timeout(ROUTE_OP_TIMEOUT, async {
-        let pm = lock_plugin_manager(state).await?;
-        pm.build_started_instance_with_data(plugin_index, &plugin_path, instance_id, data_str)
-            .await
-    })
-    .await
-    .map_err(|_| Error::CustomError(format!("start timed out after {:?}", ROUTE_OP_TIMEOUT)))??;
-
-    {
-        let mut pm = lock_plugin_manager(state).await?;
-        pm.commit_started_instance(instance_id, handle)?;
-    }
+    let pm = lock_plugin_manager(state).await?;
+    pm.enqueue(plugin_index, instance_id, data_str, JobPriority::Manual)?;
 
     Ok(Json(instance_id))
 }
 
 #[put("/plugins/register")]
-pub async fn register_plugins(state: &State<AppState>) -> Result<status::NoContent, Error> {
-    // Zuerst laufende Handles unter Lock kopieren, damit wir sie außerhalb
-    // des Locks stoppen können.
+pub async fn register_plugins(
+    state: &State<AppState>,
+    _auth: RequireAdmin,
+) -> Result<status::NoContent, Error> {
+    // Zuerst Pipeline-Läufe abbrechen, damit gestoppte Schritte keine weiteren
+    // einreihen, und laufende Handles unter Lock kopieren, damit wir sie
+    // außerhalb des Locks stoppen können.
     let running_handles = {
-        let pm = lock_plugin_manager(state).await?;
+        let mut pm = lock_plugin_manager(state).await?;
+        pm.cancel_pipeline_runs("plugins were rescanned");
         pm.get_running_handles()
     };
 
@@ -128,9 +215,19 @@ pub async fn register_plugins(state: &State<AppState>) -> Result<status::NoConte
         }
     }
 
-    // Danach Zustand zurücksetzen und Plugins frisch einlesen.
+    // Danach Zustand zurücksetzen und Plugins frisch einlesen. Wartende Jobs
+    // verweisen auf die alte Liste und werden verworfen.
     {
         let mut pm = lock_plugin_manager(state).await?;
+        for job in pm.queue().drain() {
+            pm.finish_queued_instance(
+                job,
+                RunOutcome::failed(
+                    InstanceState::Stopped,
+                    "plugins were rescanned before start".to_string(),
+                ),
+            );
+        }
         pm.running.clear();
         pm.history.clear();
         pm.registered.clear();
@@ -145,6 +242,7 @@ pub async fn register_plugins(state: &State<AppState>) -> Result<status::NoConte
 #[put("/plugins/<plugin_name>/register")]
 pub async fn register_plugin(
     state: &State<AppState>,
+    _auth: RequireAdmin,
     plugin_name: &str,
 ) -> Result<status::NoContent, Error> {
     let mut pm = lock_plugin_manager(state).await?;
@@ -152,15 +250,21 @@ pub async fn register_plugin(
     Ok(status::NoContent)
 }
 
-/// Stoppt eine laufende Plugin-Instanz.
+/// Stoppt eine laufende Plugin-Instanz; eine wartende wird aus der
+/// Warteschlange genommen.
 #[put("/plugins/<instance_id>/stop")]
 pub async fn stop_plugin_instance(
     state: &State<AppState>,
+    _auth: RequirePluginOperator,
     instance_id: u64,
 ) -> Result<status::NoContent, Error> {
     // Handle unter Lock holen, aber Stop selbst außerhalb ausführen.
     let handle = {
-        let pm = lock_plugin_manager(state).await?;
+        let mut pm = lock_plugin_manager(state).await?;
+        if pm.queue().contains(instance_id) {
+            pm.cancel_queued_instance(instance_id)?;
+            return Ok(status::NoContent);
+        }
         pm.get_instance_handle(instance_id)?
     };
 
@@ -190,6 +294,7 @@ pub async fn stop_plugin_instance(
 #[put("/plugins/<instance_id>/pause")]
 pub async fn pause_plugin_instance(
     state: &State<AppState>,
+    _auth: RequirePluginOperator,
     instance_id: u64,
 ) -> Result<status::NoContent, Error> {
     let handle = {
@@ -210,6 +315,7 @@ pub async fn pause_plugin_instance(
 #[put("/plugins/<instance_id>/resume")]
 pub async fn resume_plugin_instance(
     state: &State<AppState>,
+    _auth: RequirePluginOperator,
     instance_id: u64,
 ) -> Result<status::NoContent, Error> {
     let handle = {
@@ -227,61 +333,245 @@ pub async fn resume_plugin_instance(
     Ok(status::NoContent)
 }
 
-#[get("/plugin/instances")]
-pub async fn get_plugin_instances(state: &State<AppState>) -> Result<Json<Vec<PluginInfo>>, Error> {
+/// Wartende Jobs in Startreihenfolge, belegte Worker und die Grenzen aus `plugins.yaml`.
+#[get("/plugin/queue")]
+pub async fn get_plugin_queue(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+) -> Result<Json<QueueSnapshot>, Error> {
     let pm = lock_plugin_manager(state).await?;
+    Ok(Json(pm.queue().snapshot()))
+}
 
-    let mut results: Vec<PluginInfo> = Vec::new();
+/// Nimmt eine wartende Instanz aus der Warteschlange; ihr Lauf endet als `Stopped`.
+#[delete("/plugin/queue/<instance_id>")]
+pub async fn cancel_queued_plugin_instance(
+    state: &State<AppState>,
+    _auth: RequirePluginOperator,
+    instance_id: u64,
+) -> Result<status::NoContent, Error> {
+    let mut pm = lock_plugin_manager(state).await?;
+    pm.cancel_queued_instance(instance_id)?;
+    Ok(status::NoContent)
+}
+
+/// Pipelines aus `plugins.yaml`.
+#[get("/plugin/pipelines")]
+pub async fn get_pipelines(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+) -> Result<Json<Vec<PipelineConfig>>, Error> {
+    let pm = lock_plugin_manager(state).await?;
+    Ok(Json(pm.pipelines().configs().to_vec()))
+}
 
-    // Laufende Instanzen inkl. Status und Fortschritt
-    for (p, instance_id, status) in pm.get_running_instances() {
-        if !p.enabled() {
-            continue;
+/// Startet einen Lauf einer Pipeline. Das optionale JSON-Objekt erhalten die
+/// Schritte ohne `needs`; die Schritte werden wie manuelle Starts eingereiht.
+#[post("/plugin/pipelines/<name>/start", data = "<payload>")]
+pub async fn start_pipeline(
+    state: &State<AppState>,
+    _auth: RequirePluginOperator,
+    name: &str,
+    payload: Option<Json<serde_json::Value>>,
+    audit: AuditDetail<'_>,
+) -> Result<Json<u64>, Error> {
+    audit.record("payload", payload.as_ref().map(|p| &p.0));
+    let data = match payload.map(|p| p.into_inner()) {
+        None | Some(serde_json::Value::Null) => serde_json::Value::Object(Default::default()),
+        Some(value @ serde_json::Value::Object(_)) => value,
+        Some(other) => {
+            return Err(StorageError::ValidationError(format!(
+                "pipeline start data must be a JSON object, got {other}"
+            ))
+            .into());
         }
+    };
+    let mut pm = lock_plugin_manager(state).await?;
+    let run_id = pm.start_pipeline(name, data.to_string(), JobPriority::Manual)?;
+    Ok(Json(run_id))
+}
 
-        let progress = pm
-            .running
-            .get(&instance_id)
-            .map(|h| *h.progress_rx.borrow());
+/// Pipeline-Läufe, neueste zuerst, mit zusammengefasstem Zustand und Fortschritt.
+#[get("/plugin/pipelines/runs")]
+pub async fn get_pipeline_runs(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+) -> Result<Json<Vec<PipelineRunView>>, Error> {
+    let pm = lock_plugin_manager(state).await?;
+    Ok(Json(pm.pipeline_run_views()))
+}
 
-        results.push(PluginInfo {
-            name: p.name().clone(),
-            description: p.description().clone(),
-            trigger: p.trigger().to_string(),
-            path: p.path().to_string_lossy().into_owned(),
-            enabled: p.enabled(),
-            valid: p.valid(),
-            instance_id: Some(instance_id),
-            state: Some(status),
-            progress,
-        });
+/// Ein Pipeline-Lauf mit dem Zustand jedes Schritts.
+#[get("/plugin/pipelines/runs/<run_id>")]
+pub async fn get_pipeline_run(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+    run_id: u64,
+) -> Result<Json<PipelineRunView>, Error> {
+    let pm = lock_plugin_manager(state).await?;
+    pm.pipeline_run_view(run_id)
+        .map(Json)
+        .ok_or_else(|| StorageError::NotFound(format!("pipeline run {run_id} not found")).into())
+}
+
+/// Lauf aus `plugin_runs`; laufende Instanzen mit aktuellem Zustand und Fortschritt.
+#[derive(serde::Serialize)]
+pub struct PluginRunInfo {
+    #[serde(flatten)]
+    run: PluginRun,
+    progress: Option<f32>,
+}
+
+/// Verlauf aller Plugin-Instanzen, neueste zuerst, mit Seitenzahl.
+#[get("/plugin/instances?<plugin>&<page>&<page_size>&<txid>")]
+pub async fn get_plugin_instances(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+    plugin: Option<String>,
+    page: Option<u32>,
+    page_size: Option<u32>,
+    txid: Option<TxID>,
+) -> Result<Json<(Vec<PluginRunInfo>, u32)>, Error> {
+    let (plugin_runs, num_pages) = state
+        .storage_manager
+        .get_plugin_runs(plugin, page, page_size, txid.unwrap_or(0))
+        .await?;
+
+    let pm = lock_plugin_manager(state).await?;
+    let results = plugin_runs
+        .into_iter()
+        .map(|mut run| {
+            let handle = run
+                .ended_at
+                .is_none()
+                .then(|| pm.running.get(&(run.instance_id as u64)))
+                .flatten();
+            let progress = handle.map(|h| {
+                // Pausen und Endzustände vor dem Nachtragen stehen nur im Watch-Kanal.
+                run.state = runs::state_name(*h.status_rx.borrow());
+                *h.progress_rx.borrow()
+            });
+            PluginRunInfo { run, progress }
+        })
+        .collect();
+
+    Ok(Json((results, num_pages)))
+}
+
+/// Alle Versuche des Laufs, zu dem die Instanz gehört, ab dem ersten.
+#[get("/plugin/instances/<instance_id>/attempts")]
+pub async fn get_plugin_instance_attempts(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+    instance_id: u64,
+) -> Result<Json<Vec<PluginRun>>, Error> {
+    let attempts = state
+        .storage_manager
+        .get_plugin_run_attempts(instance_id as i64)
+        .await?;
+    Ok(Json(attempts))
+}
+
+/// Log-Zeilen einer Instanz in ihrer Reihenfolge, mit Seitenzahl.
+#[get("/plugin/instances/<instance_id>/logs?<page>&<page_size>&<txid>")]
+pub async fn get_plugin_instance_logs(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+    instance_id: u64,
+    page: Option<u32>,
+    page_size: Option<u32>,
+    txid: Option<TxID>,
+) -> Result<Json<(Vec<PluginLogLine>, u32)>, Error> {
+    let running = lock_plugin_manager(state)
+        .await?
+        .running
+        .contains_key(&instance_id);
+    if !running {
+        state
+            .storage_manager
+            .get_plugin_run(instance_id as i64)
+            .await?;
     }
+    let (lines, num_pages) = state
+        .storage_manager
+        .get_plugin_logs(instance_id as i64, page, page_size, txid.unwrap_or(0))
+        .await?;
+    Ok(Json((lines, num_pages)))
+}
 
-    // Bereits beendete/entfernte Instanzen aus der Historie
-    for (p, instance_id, status) in pm.get_history_instances() {
-        if !p.enabled() {
-            continue;
+/// Live-Tail der Logs einer Instanz als Server-Sent Events.
+///
+/// Jede Zeile kommt als `log` mit dem JSON-Objekt. Läuft die Instanz noch,
+/// kommen zuerst ihre letzten Zeilen (höchstens
+/// [`crate::plugin_manager::logs::LOG_BACKLOG`]) und dann jede neue, sonst alle
+/// gespeicherten Zeilen. `end` schließt den Log ab; `lagged` meldet verpasste
+/// Zeilen wie bei `GET /events`.
+#[get("/plugin/instances/<instance_id>/logs/stream")]
+pub async fn stream_plugin_instance_logs(
+    state: &State<AppState>,
+    _auth: RequireViewer,
+    instance_id: u64,
+    mut shutdown: Shutdown,
+) -> Result<EventStream![], Error> {
+    let subscription = lock_plugin_manager(state)
+        .await?
+        .running
+        .get(&instance_id)
+        .and_then(|h| h.logs.subscribe());
+    let mut stored = Vec::new();
+    if subscription.is_none() {
+        let storage = &state.storage_manager;
+        storage.get_plugin_run(instance_id as i64).await?;
+        let mut page = 0;
+        loop {
+            let (lines, num_pages) = storage
+                .get_plugin_logs(instance_id as i64, Some(page), None, 0)
+                .await?;
+            stored.extend(lines);
+            page += 1;
+            if page >= num_pages {
+                break;
+            }
         }
-        results.push(PluginInfo {
-            name: p.name().clone(),
-            description: p.description().clone(),
-            trigger: p.trigger().to_string(),
-            path: p.path().to_string_lossy().into_owned(),
-            enabled: p.enabled(),
-            valid: p.valid(),
-            instance_id: Some(instance_id),
-            state: Some(status),
-            progress: None,
-        });
     }
 
-    Ok(Json(results))
+    Ok(EventStream! {
+        for line in stored {
+            yield Event::json(&line).event("log");
+        }
+        let mut ended = true;
+        if let Some(LogSubscription { backlog, mut rx }) = subscription {
+            for line in backlog {
+                yield Event::json(&line).event("log");
+            }
+            loop {
+                let received = select! {
+                    received = rx.recv() => received,
+                    _ = &mut shutdown => {
+                        ended = false;
+                        break;
+                    }
+                };
+                match received {
+                    Ok(line) => yield Event::json(&line).event("log"),
+                    Err(RecvError::Lagged(missed)) => {
+                        yield Event::data(missed.to_string()).event("lagged");
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        }
+        if ended {
+            yield Event::data(instance_id.to_string()).event("end");
+        }
+    })
 }
 
 /// Liefert alle registrierten und aktivierten Plugins zurück.
 #[get("/plugins/registered")]
 pub async fn get_registered_plugins(
     state: &State<AppState>,
+    _auth: RequireViewer,
 ) -> Result<Json<Vec<PluginInfo>>, Error> {
     let pm = lock_plugin_manager(state).await?;
 
@@ -292,13 +582,19 @@ pub async fn get_registered_plugins(
         .map(|p| PluginInfo {
             name: p.name().clone(),
             description: p.description().clone(),
-            trigger: p.trigger().to_string(),
+            trigger: p.trigger_description(),
+            trigger_filter: p.trigger_filter().cloned(),
             path: p.path().to_string_lossy().into_owned(),
             enabled: p.enabled(),
             valid: p.valid(),
             instance_id: None,
             state: None,
             progress: None,
+            parameters: p.parameters().cloned(),
+            retry: p.retry_policy().cloned(),
+            limits: p.run_limits(),
+            sandbox: p.sandbox_limits().clone(),
+            write_access: p.write_access(),
         })
         .collect();
 
@@ -308,6 +604,7 @@ pub async fn get_registered_plugins(
 #[put("/plugins/<plugin_name>/enable")]
 pub async fn enable_plugin(
     state: &State<AppState>,
+    _auth: RequireAdmin,
     plugin_name: &str,
 ) -> Result<status::NoContent, Error> {
     let mut pm = lock_plugin_manager(state).await?;
@@ -318,6 +615,7 @@ pub async fn enable_plugin(
 #[put("/plugins/<plugin_name>/disable")]
 pub async fn disable_plugin(
     state: &State<AppState>,
+    _auth: RequireAdmin,
     plugin_name: &str,
 ) -> Result<status::NoContent, Error> {
     let mut pm = lock_plugin_manager(state).await?;
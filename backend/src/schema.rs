@@ -16,6 +16,9 @@ diesel::table! {
         status -> Varchar,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        version -> BigInt,
+        mcap_start_ns -> Nullable<BigInt>,
+        mcap_end_ns -> Nullable<BigInt>,
         // from yaml
         time_machine -> Nullable<Double>,
         platform_name -> Nullable<Varchar>,
@@ -49,6 +52,18 @@ diesel::table! {
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
         tags -> Array<Text>,
+        version -> BigInt,
+    }
+}
+
+diesel::table! {
+    tag_definitions (name) {
+        name -> Text,
+        color -> Nullable<Text>,
+        description -> Nullable<Text>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        version -> BigInt,
     }
 }
 
@@ -74,11 +89,235 @@ diesel::table! {
         sensor_type -> Nullable<Varchar>,
         ros_topics -> Array<Text>,
         custom_parameters -> Nullable<Jsonb>,
+        version -> BigInt,
+    }
+}
+
+diesel::table! {
+    collections (id) {
+        id -> BigInt,
+        name -> Text,
+        description -> Nullable<Text>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        version -> BigInt,
+    }
+}
+
+diesel::table! {
+    collection_items (id) {
+        id -> BigInt,
+        collection_id -> BigInt,
+        entry_id -> BigInt,
+        sequence_id -> Nullable<BigInt>,
+        added_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    collection_snapshots (id) {
+        id -> BigInt,
+        collection_id -> BigInt,
+        snapshot_version -> Integer,
+        note -> Nullable<Text>,
+        item_count -> Integer,
+        total_size -> BigInt,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    collection_snapshot_items (id) {
+        id -> BigInt,
+        snapshot_id -> BigInt,
+        entry_id -> BigInt,
+        entry_name -> Text,
+        path -> Text,
+        size -> BigInt,
+        sequence_id -> Nullable<BigInt>,
+        sequence_name -> Nullable<Text>,
+        start_timestamp -> Nullable<BigInt>,
+        end_timestamp -> Nullable<BigInt>,
+    }
+}
+
+diesel::table! {
+    entry_consistency (entry_id) {
+        entry_id -> BigInt,
+        checked_at -> Timestamptz,
+        missing_topics -> Integer,
+        unattributed_topics -> Integer,
+        frequency_mismatches -> Integer,
+        issues -> Jsonb,
+    }
+}
+
+diesel::table! {
+    sensor_catalog (id) {
+        id -> BigInt,
+        sensor_name -> Text,
+        manufacturer -> Nullable<Text>,
+        sensor_type -> Nullable<Text>,
+        model -> Nullable<Text>,
+        serial_number -> Nullable<Text>,
+        calibration -> Nullable<Jsonb>,
+        custom_parameters -> Nullable<Jsonb>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        version -> BigInt,
+    }
+}
+
+diesel::table! {
+    sensor_identity_keys (identity_key) {
+        identity_key -> Text,
+        catalog_sensor_id -> BigInt,
+    }
+}
+
+diesel::table! {
+    entry_sensors (id) {
+        id -> BigInt,
+        entry_id -> BigInt,
+        catalog_sensor_id -> BigInt,
+        sensor_id -> BigInt,
+        ros_topics -> Array<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    users (id) {
+        id -> BigInt,
+        username -> Text,
+        password_hash -> Text,
+        role -> Text,
+        disabled -> Bool,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        version -> BigInt,
+    }
+}
+
+diesel::table! {
+    sessions (token_hash) {
+        token_hash -> Text,
+        user_id -> BigInt,
+        created_at -> Timestamptz,
+        expires_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    api_keys (id) {
+        id -> BigInt,
+        name -> Text,
+        key_hash -> Text,
+        key_prefix -> Text,
+        scopes -> Array<Text>,
+        created_by -> Nullable<BigInt>,
+        created_at -> Timestamptz,
+        expires_at -> Nullable<Timestamptz>,
+        last_used_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    audit_log (id) {
+        id -> BigInt,
+        at -> Timestamptz,
+        actor -> Text,
+        method -> Text,
+        route -> Nullable<Text>,
+        path -> Text,
+        target_ids -> Jsonb,
+        parameters -> Jsonb,
+        status -> Integer,
+        outcome -> Text,
+    }
+}
+
+diesel::table! {
+    plugin_runs (instance_id) {
+        instance_id -> BigInt,
+        plugin_name -> Text,
+        plugin_version -> Nullable<Text>,
+        trigger -> Text,
+        payload -> Jsonb,
+        entry_path -> Nullable<Text>,
+        started_at -> Timestamptz,
+        ended_at -> Nullable<Timestamptz>,
+        state -> Text,
+        result -> Nullable<Jsonb>,
+        error -> Nullable<Text>,
+        trace -> Nullable<Text>,
+        queued_at -> Nullable<Timestamptz>,
+        attempt -> Integer,
+        retry_of -> Nullable<BigInt>,
+    }
+}
+
+diesel::table! {
+    plugin_logs (instance_id, seq) {
+        instance_id -> BigInt,
+        seq -> Integer,
+        at -> Timestamptz,
+        level -> Text,
+        logger -> Text,
+        message -> Text,
+    }
+}
+
+diesel::table! {
+    entry_artifacts (id) {
+        id -> BigInt,
+        entry_id -> BigInt,
+        kind -> Text,
+        content -> Jsonb,
+        plugin_name -> Text,
+        plugin_version -> Nullable<Text>,
+        instance_id -> BigInt,
+        created_at -> Timestamptz,
     }
 }
 
 diesel::joinable!(sequences -> entries (entry_id));
 diesel::joinable!(sensors -> entries (entry_id));
 diesel::joinable!(topics -> entries (entry_id));
+diesel::joinable!(collection_items -> collections (collection_id));
+diesel::joinable!(collection_items -> entries (entry_id));
+diesel::joinable!(collection_items -> sequences (sequence_id));
+diesel::joinable!(collection_snapshots -> collections (collection_id));
+diesel::joinable!(collection_snapshot_items -> collection_snapshots (snapshot_id));
+diesel::joinable!(entry_consistency -> entries (entry_id));
+diesel::joinable!(entry_sensors -> entries (entry_id));
+diesel::joinable!(entry_sensors -> sensor_catalog (catalog_sensor_id));
+diesel::joinable!(entry_sensors -> sensors (sensor_id));
+diesel::joinable!(sensor_identity_keys -> sensor_catalog (catalog_sensor_id));
+diesel::joinable!(sessions -> users (user_id));
+diesel::joinable!(api_keys -> users (created_by));
+diesel::joinable!(entry_artifacts -> entries (entry_id));
 
-diesel::allow_tables_to_appear_in_same_query!(entries, sequences, sensors, files, topics);
+diesel::allow_tables_to_appear_in_same_query!(
+    entries,
+    sequences,
+    sensors,
+    files,
+    topics,
+    sensor_catalog,
+    sensor_identity_keys,
+    entry_sensors,
+    entry_consistency,
+    tag_definitions,
+    collections,
+    collection_items,
+    collection_snapshots,
+    collection_snapshot_items,
+    users,
+    sessions,
+    api_keys,
+    audit_log,
+    plugin_runs,
+    plugin_logs,
+    entry_artifacts,
+);
@@ -0,0 +1,315 @@
+//! Ergebnisse von Plugins, die als Artefakte an Einträgen hängen.
+//!
+//! Ein Plugin meldet sie im `result` seiner `exited`-Nachricht, also im
+//! Rückgabewert von `run(data)`:
+//!
+//! ```json
+//! {
+//!   "entry_id": 12,
+//!   "artifacts": [
+//!     {"type": "tags", "tags": ["weather/rain"]},
+//!     {"type": "metadata", "patch": {"weather_fog": true}},
+//!     {"type": "sequence", "name": "Überholen", "start_timestamp": 10, "end_timestamp": 20},
+//!     {"type": "file", "path": "/data/thumbs/a.png", "role": "thumbnail", "media_type": "image/png"},
+//!     {"type": "metrics", "values": {"frames": 1200, "max_speed_kmh": 87.5}}
+//!   ]
+//! }
+//! ```
+//!
+//! Ohne `entry_id` gilt die Aufnahme des Laufs. Geprüft wird hier alles, was
+//! ohne Datenbank geht; die Zeitgrenzen von Sequenzen prüft der Storage-Manager.
+//!
+//! Dateien müssen nach Auflösen aller Symlinks im Arbeitsverzeichnis der
+//! Instanz, unter `/exports` oder unter `/data` liegen (siehe [`resolve_files`]);
+//! gespeichert wird der aufgelöste Pfad. Das Arbeitsverzeichnis wird nach dem
+//! Lauf gelöscht, Dateien von dort landen deshalb vorher unter
+//! `/exports/artifacts/<instance_id>/` (siehe [`keep_workdir_files`]).
+
+use crate::error::StorageError;
+use crate::storage::models::{EntryID, EntryMetadataPatch};
+use crate::storage::tags;
+use rocket::serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Alle Artefaktarten, wie sie in `type` und in `entry_artifacts.kind` stehen.
+pub const ARTIFACT_KINDS: &[&str] = &["tags", "metadata", "sequence", "file", "metrics"];
+
+/// Zulässige Rollen erzeugter Dateien.
+pub const FILE_ROLES: &[&str] = &["thumbnail", "report", "converted", "other"];
+
+/// Höchstzahl an Artefakten je Lauf.
+pub const MAX_ARTIFACTS: usize = 100;
+
+/// Unterverzeichnis des Export-Verzeichnisses für Dateien aus Arbeitsverzeichnissen.
+pub const ARTIFACT_DIR: &str = "artifacts";
+
+fn default_file_role() -> String {
+    "other".to_string()
+}
+
+/// Ein typisiertes Ergebnis eines Plugins.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    crate = "rocket::serde",
+    tag = "type",
+    rename_all = "snake_case",
+    deny_unknown_fields
+)]
+pub enum PluginArtifact {
+    /// Tags, die dem Eintrag hinzugefügt werden.
+    Tags { tags: Vec<String> },
+    /// Teiländerung der Metadaten wie bei `PATCH` über die API.
+    Metadata { patch: Box<EntryMetadataPatch> },
+    /// Neue Sequenz innerhalb der Aufnahme.
+    Sequence {
+        name: String,
+        #[serde(default)]
+        description: String,
+        start_timestamp: i64,
+        end_timestamp: i64,
+        #[serde(default)]
+        tags: Vec<String>,
+    },
+    /// Vom Plugin erzeugte Datei, z.B. ein Vorschaubild oder ein Bericht.
+    File {
+        path: String,
+        #[serde(default = "default_file_role")]
+        role: String,
+        #[serde(default)]
+        media_type: Option<String>,
+    },
+    /// Kennzahlen als Name/Wert-Paare.
+    Metrics { values: BTreeMap<String, f64> },
+}
+
+impl PluginArtifact {
+    /// Art des Artefakts, eine der [`ARTIFACT_KINDS`].
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PluginArtifact::Tags { .. } => "tags",
+            PluginArtifact::Metadata { .. } => "metadata",
+            PluginArtifact::Sequence { .. } => "sequence",
+            PluginArtifact::File { .. } => "file",
+            PluginArtifact::Metrics { .. } => "metrics",
+        }
+    }
+
+    /// Prüft das Artefakt ohne Zugriff auf das Dateisystem; ob eine Datei
+    /// existiert und wo sie liegt, prüft [`resolve_files`].
+    pub fn validate(&self) -> Result<(), StorageError> {
+        match self {
+            PluginArtifact::Tags { tags } => {
+                if tags.is_empty() {
+                    return Err(StorageError::ValidationError("no tags given".to_string()));
+                }
+                tags.iter().try_for_each(|t| tags::validate_tag_name(t))
+            }
+            PluginArtifact::Metadata { patch } => {
+                if patch.is_empty() {
+                    return Err(StorageError::ValidationError(
+                        "metadata patch contains no fields".to_string(),
+                    ));
+                }
+                Ok(())
+            }
+            PluginArtifact::Sequence {
+                name,
+                start_timestamp,
+                end_timestamp,
+                tags,
+                ..
+            } => {
+                if name.trim().is_empty() {
+                    return Err(StorageError::ValidationError(
+                        "sequence name must not be empty".to_string(),
+                    ));
+                }
+                if end_timestamp < start_timestamp {
+                    return Err(StorageError::ValidationError(format!(
+                        "end_timestamp {end_timestamp} is before start_timestamp {start_timestamp}"
+                    )));
+                }
+                tags.iter().try_for_each(|t| tags::validate_tag_name(t))
+            }
+            PluginArtifact::File { path, role, .. } => {
+                if !FILE_ROLES.contains(&role.as_str()) {
+                    return Err(StorageError::ValidationError(format!(
+                        "unknown file role '{role}', expected one of {}",
+                        FILE_ROLES.join(", ")
+                    )));
+                }
+                if !Path::new(path).is_absolute() {
+                    return Err(StorageError::ValidationError(format!(
+                        "file path '{path}' must be absolute"
+                    )));
+                }
+                Ok(())
+            }
+            PluginArtifact::Metrics { values } => {
+                if values.is_empty() {
+                    return Err(StorageError::ValidationError(
+                        "no metrics given".to_string(),
+                    ));
+                }
+                if values.keys().any(|k| k.trim().is_empty()) {
+                    return Err(StorageError::ValidationError(
+                        "metric names must not be empty".to_string(),
+                    ));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Inhalt für `entry_artifacts.content`: das Artefakt ohne `type`.
+    pub fn content(&self) -> Value {
+        let mut value = serde_json::to_value(self).unwrap_or(Value::Null);
+        if let Some(object) = value.as_object_mut() {
+            object.remove("type");
+        }
+        value
+    }
+}
+
+/// Artefakte aus dem Ergebnis eines Laufs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PluginResult {
+    /// Ziel-Eintrag; ohne Angabe der Eintrag des Laufs.
+    pub entry_id: Option<EntryID>,
+    pub artifacts: Vec<PluginArtifact>,
+}
+
+/// Herkunft eines Artefakts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArtifactProvenance {
+    pub plugin_name: String,
+    pub plugin_version: Option<String>,
+    pub instance_id: i64,
+}
+
+/// Liest und prüft die Artefakte aus `result`. Ergebnisse ohne `artifacts`
+/// (z.B. ein einfacher String) liefern `None`.
+pub fn parse_result(result: &Value) -> Result<Option<PluginResult>, StorageError> {
+    let Some(raw) = result.get("artifacts") else {
+        return Ok(None);
+    };
+    let entry_id = match result.get("entry_id") {
+        None | Some(Value::Null) => None,
+        Some(id) => Some(id.as_i64().ok_or_else(|| {
+            StorageError::ValidationError(format!("entry_id must be an integer, got {id}"))
+        })?),
+    };
+    let Some(raw) = raw.as_array() else {
+        return Err(StorageError::ValidationError(
+            "artifacts must be a list".to_string(),
+        ));
+    };
+    if raw.len() > MAX_ARTIFACTS {
+        return Err(StorageError::ValidationError(format!(
+            "at most {MAX_ARTIFACTS} artifacts per run, got {}",
+            raw.len()
+        )));
+    }
+    let mut artifacts = Vec::with_capacity(raw.len());
+    for (i, value) in raw.iter().enumerate() {
+        let artifact: PluginArtifact = serde_json::from_value(value.clone())
+            .map_err(|e| StorageError::ValidationError(format!("artifact {i}: {e}")))?;
+        artifact.validate().map_err(|e| match e {
+            StorageError::ValidationError(msg) => {
+                StorageError::ValidationError(format!("artifact {i}: {msg}"))
+            }
+            other => other,
+        })?;
+        artifacts.push(artifact);
+    }
+    Ok(Some(PluginResult {
+        entry_id,
+        artifacts,
+    }))
+}
+
+/// Löst die Pfade der Datei-Artefakte auf und ersetzt sie durch den
+/// kanonischen Pfad. Jede Datei muss eine reguläre Datei unterhalb eines der
+/// `roots` sein; Wurzeln, die es nicht gibt, werden übergangen.
+pub async fn resolve_files(
+    artifacts: &mut [PluginArtifact],
+    roots: &[PathBuf],
+) -> Result<(), StorageError> {
+    let mut allowed = Vec::with_capacity(roots.len());
+    for root in roots {
+        if let Ok(root) = tokio::fs::canonicalize(root).await {
+            allowed.push(root);
+        }
+    }
+    for (i, artifact) in artifacts.iter_mut().enumerate() {
+        let PluginArtifact::File { path, .. } = artifact else {
+            continue;
+        };
+        let missing = || {
+            StorageError::ValidationError(format!(
+                "artifact {i}: file '{path}' does not exist or is not a regular file"
+            ))
+        };
+        let resolved = tokio::fs::canonicalize(path.as_str())
+            .await
+            .map_err(|_| missing())?;
+        if !allowed.iter().any(|root| resolved.starts_with(root)) {
+            return Err(StorageError::ValidationError(format!(
+                "artifact {i}: file '{path}' is outside the instance directory, /exports and /data"
+            )));
+        }
+        let is_file = tokio::fs::metadata(&resolved)
+            .await
+            .is_ok_and(|m| m.is_file());
+        if !is_file {
+            return Err(missing());
+        }
+        *path = resolved.to_string_lossy().into_owned();
+    }
+    Ok(())
+}
+
+/// Kopiert Datei-Artefakte, die im Arbeitsverzeichnis `workdir` der Instanz
+/// liegen, mit ihrem relativen Pfad nach `target` und trägt den neuen Pfad
+/// ein. Erwartet Pfade, die [`resolve_files`] schon aufgelöst hat.
+pub async fn keep_workdir_files(
+    artifacts: &mut [PluginArtifact],
+    workdir: &Path,
+    target: &Path,
+) -> Result<(), StorageError> {
+    let Ok(workdir) = tokio::fs::canonicalize(workdir).await else {
+        return Ok(());
+    };
+    for artifact in artifacts.iter_mut() {
+        let PluginArtifact::File { path, .. } = artifact else {
+            continue;
+        };
+        let Ok(relative) = Path::new(path.as_str()).strip_prefix(&workdir) else {
+            continue;
+        };
+        let kept = target.join(relative);
+        if let Some(parent) = kept.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::copy(path.as_str(), &kept).await?;
+        *path = tokio::fs::canonicalize(&kept)
+            .await?
+            .to_string_lossy()
+            .into_owned();
+    }
+    Ok(())
+}
+
+/// Prüft einen `kind`-Filter.
+pub fn validate_kind(kind: &str) -> Result<(), StorageError> {
+    if ARTIFACT_KINDS.contains(&kind) {
+        Ok(())
+    } else {
+        Err(StorageError::ValidationError(format!(
+            "unknown artifact kind '{kind}', expected one of {}",
+            ARTIFACT_KINDS.join(", ")
+        )))
+    }
+}
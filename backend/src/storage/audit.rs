@@ -0,0 +1,112 @@
+//! Hilfsfunktionen für das Audit-Log ändernder API-Aufrufe.
+//!
+//! Geschrieben wird das Log vom Fairing in [`crate::routes::audit`]; hier liegt
+//! nur, was ohne Rocket und Datenbank auskommt.
+
+use crate::error::StorageError;
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value};
+
+/// Einträge je Seite, wenn `GET /audit` kein `page_size` bekommt.
+pub const DEFAULT_PAGE_SIZE: u32 = 100;
+
+/// Akteur ohne (gültige) Anmeldung.
+pub const ANONYMOUS: &str = "anonymous";
+
+/// Methoden, deren Aufrufe protokolliert werden.
+pub fn is_audited_method(method: &str) -> bool {
+    matches!(method, "POST" | "PUT" | "PATCH" | "DELETE")
+}
+
+/// Ergebnis eines Aufrufs aus seinem HTTP-Status.
+pub fn outcome(status: u16) -> &'static str {
+    match status {
+        401 | 403 => "denied",
+        400..=499 => "rejected",
+        500.. => "error",
+        _ => "success",
+    }
+}
+
+/// Werte der dynamischen Segmente von `template` (z.B. `/entries/<entry_id>`)
+/// in `path`. Ein Segment `<rest..>` nimmt den Rest des Pfads auf.
+pub fn path_params(template: &str, path: &str) -> Map<String, Value> {
+    let mut params = Map::new();
+    let mut segments = path.trim_start_matches('/').split('/');
+    for pattern in template.trim_start_matches('/').split('/') {
+        let Some(name) = pattern.strip_prefix('<').and_then(|p| p.strip_suffix('>')) else {
+            if segments.next().is_none() {
+                break;
+            }
+            continue;
+        };
+        if let Some(name) = name.strip_suffix("..") {
+            let rest: Vec<&str> = segments.by_ref().collect();
+            params.insert(name.to_string(), Value::String(rest.join("/")));
+            break;
+        }
+        match segments.next() {
+            Some(value) => {
+                params.insert(name.to_string(), Value::String(value.to_string()));
+            }
+            None => break,
+        }
+    }
+    params
+}
+
+/// Liest einen Zeitpunkt im RFC-3339-Format, z.B. `2026-05-04T12:00:00Z`.
+pub fn parse_time(name: &str, value: &str) -> Result<DateTime<Utc>, StorageError> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|e| {
+            StorageError::ValidationError(format!(
+                "{name} must be an RFC 3339 timestamp, got '{value}': {e}"
+            ))
+        })
+}
+
+/// Filter für `GET /audit`. Leere Felder lassen alles durch.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuditFilter {
+    /// Genauer Akteur oder dessen Anfang bis zu einem `:`; `plugin:append_42`
+    /// findet alle Instanzen des Plugins.
+    pub actor: Option<String>,
+    /// Pfad der Ressource; findet auch Aufrufe auf Unterpfade.
+    pub target: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl AuditFilter {
+    pub fn parse(
+        actor: Option<String>,
+        target: Option<String>,
+        since: Option<&str>,
+        until: Option<&str>,
+    ) -> Result<Self, StorageError> {
+        let since = since.map(|s| parse_time("since", s)).transpose()?;
+        let until = until.map(|u| parse_time("until", u)).transpose()?;
+        if let (Some(since), Some(until)) = (since, until)
+            && since > until
+        {
+            return Err(StorageError::ValidationError(format!(
+                "since ({since}) must not be after until ({until})"
+            )));
+        }
+        let target = target.map(|t| {
+            let t = t.trim_end_matches('/');
+            if t.starts_with('/') {
+                t.to_string()
+            } else {
+                format!("/{t}")
+            }
+        });
+        Ok(AuditFilter {
+            actor: actor.filter(|a| !a.is_empty()),
+            target,
+            since,
+            until,
+        })
+    }
+}
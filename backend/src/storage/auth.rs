@@ -0,0 +1,329 @@
+//! Rollen, Passwort-Hashing und Tokens für Benutzer, API-Schlüssel und Plugins.
+//!
+//! Passwörter werden mit Argon2id gehasht. Sitzungs-Tokens und API-Schlüssel sind
+//! 32 zufällige Bytes (hex); in der Datenbank liegt nur ihr SHA-256.
+//! Runner-Tokens werden nicht gespeichert, sondern mit einem beim Prozessstart
+//! gewürfelten Schlüssel signiert und verfallen spätestens mit dem Neustart;
+//! angenommen werden sie nur, solange ihre Instanz läuft.
+//! Die Funktionen hier sind rein (ohne Datenbank).
+
+use crate::error::StorageError;
+use argon2::Argon2;
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use chrono::{DateTime, TimeDelta, Utc};
+use hmac::{Hmac, Mac};
+use rocket::serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::sync::{Mutex, OnceLock};
+
+/// Gültigkeit einer Sitzung ab Anmeldung.
+pub const SESSION_TTL: TimeDelta = TimeDelta::hours(12);
+
+/// Name des Cookies, das alternativ zum `Authorization`-Header das Token trägt.
+pub const SESSION_COOKIE: &str = "session";
+
+pub const MIN_PASSWORD_LENGTH: usize = 10;
+
+/// Präfix aller API-Schlüssel; daran erkennt die Anmeldung die Token-Art.
+pub const API_KEY_PREFIX: &str = "ck_";
+
+/// Länge von [`crate::storage::models::ApiKey::key_prefix`].
+pub const API_KEY_DISPLAY_LENGTH: usize = 11;
+
+/// Präfix der Tokens, die der Plugin-Manager jeder Instanz mitgibt.
+pub const RUNNER_TOKEN_PREFIX: &str = "rt.";
+
+/// Gültigkeit eines Runner-Tokens ab Start der Instanz.
+pub const RUNNER_TOKEN_TTL: TimeDelta = TimeDelta::hours(6);
+
+/// Was ein Plugin über sein Runner-Token darf.
+pub const RUNNER_SCOPES: [Scope; 2] = [Scope::ReadEntries, Scope::WriteMetadata];
+
+/// Rollen in aufsteigender Reihenfolge; jede Rolle schließt die Rechte der
+/// vorherigen ein.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", rename_all = "kebab-case")]
+pub enum Role {
+    /// Lesender Zugriff auf Katalog, Sammlungen und Plugin-Status.
+    Viewer,
+    /// Zusätzlich Metadaten, Tags, Sequenzen, Sensoren und Sammlungen ändern.
+    Editor,
+    /// Zusätzlich Plugins starten, stoppen, pausieren und fortsetzen.
+    PluginOperator,
+    /// Zusätzlich Plugins registrieren/aktivieren, Logs lesen, Benutzer verwalten.
+    Admin,
+}
+
+impl Role {
+    pub const ALL: [Role; 4] = [
+        Role::Viewer,
+        Role::Editor,
+        Role::PluginOperator,
+        Role::Admin,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Editor => "editor",
+            Role::PluginOperator => "plugin-operator",
+            Role::Admin => "admin",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Role, StorageError> {
+        Role::ALL
+            .into_iter()
+            .find(|r| r.as_str() == value)
+            .ok_or_else(|| {
+                StorageError::ValidationError(format!(
+                    "unknown role '{value}', expected one of viewer, editor, plugin-operator, admin"
+                ))
+            })
+    }
+
+    /// Darf ein Benutzer mit dieser Rolle, was `required` verlangt?
+    pub fn allows(&self, required: Role) -> bool {
+        *self >= required
+    }
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Berechtigungen eines API-Schlüssels oder Runner-Tokens.
+///
+/// Jeder Scope deckt genau eine Rolle ab (siehe [`Scope::for_role`]);
+/// Admin-Routen sind nur mit einer Benutzersitzung erreichbar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub enum Scope {
+    /// Was `viewer` darf.
+    #[serde(rename = "read:entries")]
+    ReadEntries,
+    /// Was `editor` darf.
+    #[serde(rename = "write:metadata")]
+    WriteMetadata,
+    /// Was `plugin-operator` darf.
+    #[serde(rename = "run:plugins")]
+    RunPlugins,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 3] = [Scope::ReadEntries, Scope::WriteMetadata, Scope::RunPlugins];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::ReadEntries => "read:entries",
+            Scope::WriteMetadata => "write:metadata",
+            Scope::RunPlugins => "run:plugins",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Scope, StorageError> {
+        Scope::ALL
+            .into_iter()
+            .find(|s| s.as_str() == value)
+            .ok_or_else(|| {
+                StorageError::ValidationError(format!(
+                    "unknown scope '{value}', expected one of read:entries, write:metadata, run:plugins"
+                ))
+            })
+    }
+
+    /// Scope, der eine Route mit Mindestrolle `role` freigibt; `None` für `admin`.
+    pub fn for_role(role: Role) -> Option<Scope> {
+        match role {
+            Role::Viewer => Some(Scope::ReadEntries),
+            Role::Editor => Some(Scope::WriteMetadata),
+            Role::PluginOperator => Some(Scope::RunPlugins),
+            Role::Admin => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Scope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Benutzernamen: 1 bis 64 Zeichen aus Buchstaben, Ziffern, `.`, `_` und `-`.
+pub fn validate_username(username: &str) -> Result<(), StorageError> {
+    let valid = !username.is_empty()
+        && username.len() <= 64
+        && username
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+    if valid {
+        Ok(())
+    } else {
+        Err(StorageError::ValidationError(format!(
+            "username '{username}' must have 1 to 64 characters from a-z, A-Z, 0-9, '.', '_' and '-'"
+        )))
+    }
+}
+
+pub fn validate_password(password: &str) -> Result<(), StorageError> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(StorageError::ValidationError(format!(
+            "password must have at least {MIN_PASSWORD_LENGTH} characters"
+        )));
+    }
+    Ok(())
+}
+
+/// Argon2id-Hash im PHC-Format mit zufälligem Salt.
+pub fn hash_password(password: &str) -> Result<String, StorageError> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| StorageError::CustomError(format!("password hashing failed: {e}")))
+}
+
+/// Prüft ein Passwort gegen einen gespeicherten Hash; unlesbare Hashes passen nie.
+pub fn verify_password(password: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .map(|parsed| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &parsed)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+/// Prüft ein Passwort gegen einen festen Hash und liefert immer `false`.
+///
+/// Für unbekannte Benutzernamen, damit die Antwortzeit nicht verrät, ob ein
+/// Konto existiert.
+pub fn verify_dummy_password(password: &str) -> bool {
+    static DUMMY: OnceLock<String> = OnceLock::new();
+    let hash = DUMMY.get_or_init(|| hash_password("dummy password").unwrap_or_default());
+    verify_password(password, hash);
+    false
+}
+
+/// Neues Sitzungs-Token (64 Hex-Zeichen).
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex(&bytes)
+}
+
+/// SHA-256 eines Tokens, so wie es in `sessions.token_hash` steht.
+pub fn token_hash(token: &str) -> String {
+    hex(&Sha256::digest(token.as_bytes()))
+}
+
+/// Neuer API-Schlüssel (`ck_` und 64 Hex-Zeichen).
+pub fn generate_api_key() -> String {
+    format!("{API_KEY_PREFIX}{}", generate_token())
+}
+
+/// Angaben aus einem gültigen Runner-Token.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RunnerClaims {
+    pub instance_id: u64,
+    pub plugin_name: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+fn runner_key() -> &'static [u8; 32] {
+    static KEY: OnceLock<[u8; 32]> = OnceLock::new();
+    KEY.get_or_init(|| {
+        let mut key = [0u8; 32];
+        OsRng.fill_bytes(&mut key);
+        key
+    })
+}
+
+fn runner_mac(payload: &str) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(runner_key()).expect("HMAC accepts keys of any length");
+    mac.update(payload.as_bytes());
+    mac
+}
+
+/// Instanzen, deren Runner-Token noch angenommen wird.
+fn live_runners() -> &'static Mutex<HashSet<u64>> {
+    static LIVE: OnceLock<Mutex<HashSet<u64>>> = OnceLock::new();
+    LIVE.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+/// Token für eine Plugin-Instanz, gültig bis `now + RUNNER_TOKEN_TTL` oder bis
+/// [`revoke_runner_token`] für die Instanz aufgerufen wird.
+///
+/// Aufbau: `rt.<instance_id>.<ablauf als Unix-Zeit>.<plugin_name hex>.<HMAC hex>`.
+pub fn issue_runner_token(instance_id: u64, plugin_name: &str, now: DateTime<Utc>) -> String {
+    live_runners()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(instance_id);
+    let expires = (now + RUNNER_TOKEN_TTL).timestamp();
+    let payload = format!("{instance_id}.{expires}.{}", hex(plugin_name.as_bytes()));
+    let signature = hex(&runner_mac(&payload).finalize().into_bytes());
+    format!("{RUNNER_TOKEN_PREFIX}{payload}.{signature}")
+}
+
+/// Entzieht dem Token einer Instanz die Gültigkeit; der Plugin-Manager ruft das
+/// auf, sobald die Instanz endet.
+pub fn revoke_runner_token(instance_id: u64) {
+    live_runners()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&instance_id);
+}
+
+/// Prüft Signatur, Ablauf und ob die Instanz des Runner-Tokens noch läuft.
+pub fn verify_runner_token(token: &str, now: DateTime<Utc>) -> Option<RunnerClaims> {
+    let rest = token.strip_prefix(RUNNER_TOKEN_PREFIX)?;
+    let (payload, signature) = rest.rsplit_once('.')?;
+    runner_mac(payload).verify_slice(&unhex(signature)?).ok()?;
+    let mut parts = payload.split('.');
+    let instance_id = parts.next()?.parse().ok()?;
+    let expires_at = DateTime::from_timestamp(parts.next()?.parse().ok()?, 0)?;
+    let plugin_name = String::from_utf8(unhex(parts.next()?)?).ok()?;
+    if parts.next().is_some() || expires_at <= now {
+        return None;
+    }
+    if !live_runners()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .contains(&instance_id)
+    {
+        return None;
+    }
+    Some(RunnerClaims {
+        instance_id,
+        plugin_name,
+        expires_at,
+    })
+}
+
+fn unhex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Token aus einem `Authorization: Bearer <token>`-Header.
+pub fn bearer_token(header: &str) -> Option<&str> {
+    let (scheme, token) = header.trim().split_once(' ')?;
+    let token = token.trim();
+    (scheme.eq_ignore_ascii_case("bearer") && !token.is_empty()).then_some(token)
+}
@@ -0,0 +1,119 @@
+//! Ausschneiden eines Zeitfensters aus einer MCAP-Aufnahme.
+//!
+//! Schemas und Channels der Quelle werden übernommen; der `mcap::Writer` legt sie
+//! beim ersten Auftreten einer Nachricht im Ziel an.
+
+use crate::error::StorageError;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+
+/// Laufnummer für temporäre Clip-Dateien; gleichzeitige Exporte desselben
+/// Fensters dürfen sich nicht gegenseitig die halbe Datei überschreiben.
+static PARTIAL_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Kennzahlen eines geschriebenen Clips.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClipStats {
+    pub messages: u64,
+    pub channels: usize,
+}
+
+/// Schreibt alle Nachrichten aus `source`, deren Log-Zeit in `[start_ns, end_ns]`
+/// liegt, als neue MCAP-Datei nach `target`.
+///
+/// Ist `topics` gesetzt, werden nur Nachrichten dieser Topics übernommen. Das Ziel
+/// wird zuerst unter einem temporären Namen geschrieben und erst am Ende umbenannt,
+/// damit nie ein halber Clip ausgeliefert wird. Blockierend, also aus async-Code
+/// über `spawn_blocking` aufrufen.
+pub fn write_clip(
+    source: &Path,
+    target: &Path,
+    start_ns: u64,
+    end_ns: u64,
+    topics: Option<&HashSet<String>>,
+) -> Result<ClipStats, StorageError> {
+    let partial = target.with_extension(format!(
+        "mcap.partial-{}-{}",
+        std::process::id(),
+        PARTIAL_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let result = write_clip_to(source, &partial, start_ns, end_ns, topics).and_then(|stats| {
+        std::fs::rename(&partial, target)
+            .map(|_| stats)
+            .map_err(Into::into)
+    });
+    if result.is_err() {
+        let _ = std::fs::remove_file(&partial);
+    }
+    result
+}
+
+/// Löscht alle Dateien unter `dir`, die zuletzt vor `cutoff` geändert wurden.
+///
+/// Verzeichnisse bleiben stehen, Unterverzeichnisse aus `keep` werden nicht
+/// betreten; liefert die Anzahl gelöschter Dateien. Fehlt `dir`, gibt es
+/// nichts zu tun. Blockierend.
+pub fn remove_files_older_than(
+    dir: &Path,
+    cutoff: SystemTime,
+    keep: &[PathBuf],
+) -> Result<usize, StorageError> {
+    if !dir.is_dir() {
+        return Ok(0);
+    }
+    let mut removed = 0;
+    for entry in walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_entry(|e| !keep.iter().any(|k| e.path() == k))
+        .filter_map(Result::ok)
+    {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let modified = entry.metadata().ok().and_then(|m| m.modified().ok());
+        if modified.is_some_and(|m| m < cutoff) && std::fs::remove_file(entry.path()).is_ok() {
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+fn write_clip_to(
+    source: &Path,
+    target: &Path,
+    start_ns: u64,
+    end_ns: u64,
+    topics: Option<&HashSet<String>>,
+) -> Result<ClipStats, StorageError> {
+    let file = File::open(source)?;
+    // Safety: Aufnahmen unter /data werden nur ersetzt, nicht in-place verändert.
+    let mapped = unsafe { memmap2::Mmap::map(&file) }?;
+
+    let mut writer = mcap::Writer::new(BufWriter::new(File::create(target)?))?;
+    let mut messages = 0u64;
+    let mut channels = HashSet::new();
+    for message in mcap::MessageStream::new(&mapped)? {
+        let message = message?;
+        if message.log_time < start_ns || message.log_time > end_ns {
+            continue;
+        }
+        if let Some(topics) = topics
+            && !topics.contains(&message.channel.topic)
+        {
+            continue;
+        }
+        writer.write(&message)?;
+        messages += 1;
+        channels.insert(message.channel.id);
+    }
+    writer.finish()?;
+
+    Ok(ClipStats {
+        messages,
+        channels: channels.len(),
+    })
+}
@@ -0,0 +1,102 @@
+//! Vergleich und Export von Sammlungs-Snapshots.
+//!
+//! Ein Element wird über `(entry_id, sequence_id)` identifiziert; Pfad, Größe und
+//! Zeitfenster sind sein Inhalt. Die Funktionen hier sind rein (ohne Datenbank).
+
+use crate::storage::models::{CollectionSnapshotItem, EntryID, SequenceID};
+use rocket::serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Geändertes Element: gleicher Eintrag bzw. gleiche Sequenz, anderer Inhalt.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(crate = "rocket::serde")]
+pub struct ChangedItem {
+    pub before: CollectionSnapshotItem,
+    pub after: CollectionSnapshotItem,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(crate = "rocket::serde")]
+pub struct CollectionDiff {
+    /// Snapshot-Version der alten Seite; `None` für den aktuellen Stand.
+    pub from_version: Option<i32>,
+    /// Snapshot-Version der neuen Seite; `None` für den aktuellen Stand.
+    pub to_version: Option<i32>,
+    pub added: Vec<CollectionSnapshotItem>,
+    pub removed: Vec<CollectionSnapshotItem>,
+    pub changed: Vec<ChangedItem>,
+}
+
+impl CollectionDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+fn keyed(
+    items: &[CollectionSnapshotItem],
+) -> BTreeMap<(EntryID, Option<SequenceID>), &CollectionSnapshotItem> {
+    items
+        .iter()
+        .map(|item| ((item.entry_id, item.sequence_id), item))
+        .collect()
+}
+
+/// Vergleicht zwei Stände einer Sammlung. Ergebnisse sind nach Eintrag und
+/// Sequenz sortiert.
+pub fn diff_items(
+    from: &[CollectionSnapshotItem],
+    to: &[CollectionSnapshotItem],
+) -> CollectionDiff {
+    let from = keyed(from);
+    let to = keyed(to);
+    let mut diff = CollectionDiff::default();
+    for (key, before) in from.iter() {
+        match to.get(key) {
+            None => diff.removed.push((*before).clone()),
+            Some(after) if after != before => diff.changed.push(ChangedItem {
+                before: (*before).clone(),
+                after: (*after).clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for (key, after) in to.iter() {
+        if !from.contains_key(key) {
+            diff.added.push((*after).clone());
+        }
+    }
+    diff
+}
+
+/// Maskiert ein CSV-Feld nach RFC 4180.
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Snapshot-Elemente als CSV mit Kopfzeile.
+pub fn items_to_csv(items: &[CollectionSnapshotItem]) -> String {
+    let opt = |v: Option<i64>| v.map(|v| v.to_string()).unwrap_or_default();
+    let mut out = String::from(
+        "entry_id,entry_name,path,size,sequence_id,sequence_name,start_timestamp,end_timestamp\n",
+    );
+    for item in items {
+        let row = [
+            item.entry_id.to_string(),
+            csv_field(&item.entry_name),
+            csv_field(&item.path),
+            item.size.to_string(),
+            opt(item.sequence_id),
+            csv_field(item.sequence_name.as_deref().unwrap_or("")),
+            opt(item.start_timestamp),
+            opt(item.end_timestamp),
+        ];
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    out
+}
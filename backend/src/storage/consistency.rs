@@ -0,0 +1,130 @@
+//! Abgleich der in der Metadaten-YAML deklarierten Sensor-Topics mit den
+//! tatsächlich aufgezeichneten MCAP-Topics.
+//!
+//! Die Prüfung ist rein (ohne Datenbank); der Storage-Manager speichert das
+//! Ergebnis je Eintrag in `entry_consistency`.
+
+use crate::storage::models::{Sensor, SensorID, Topic};
+use rocket::serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Erlaubte relative Abweichung der gemessenen von der erwarteten Frequenz.
+pub const FREQUENCY_TOLERANCE: f64 = 0.25;
+
+/// Infrastruktur-Topics, die keinem Sensor zugeordnet sein müssen.
+pub const IGNORED_TOPICS: &[&str] = &[
+    "/clock",
+    "/diagnostics",
+    "/parameter_events",
+    "/rosout",
+    "/rosout_agg",
+    "/tf",
+    "/tf_static",
+];
+
+/// Schlüssel in `custom_parameters`, unter denen die erwartete Frequenz (Hz)
+/// gesucht wird. Der Wert ist eine Zahl, ein String wie `"10 Hz"` oder eine
+/// Abbildung Topic -> Frequenz.
+pub const EXPECTED_FREQUENCY_KEYS: &[&str] = &["expected_frequency", "frequency", "rate"];
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde", tag = "kind", rename_all = "snake_case")]
+pub enum ConsistencyIssue {
+    /// Der Sensor deklariert ein Topic, das in der Aufnahme fehlt (oder leer ist).
+    MissingTopic {
+        sensor_id: SensorID,
+        sensor_name: String,
+        topic: String,
+    },
+    /// Aufgezeichnetes Topic, das keinem Sensor zugeordnet ist.
+    UnattributedTopic { topic: String },
+    /// Gemessene Frequenz weicht um mehr als [`FREQUENCY_TOLERANCE`] ab.
+    FrequencyMismatch {
+        sensor_id: SensorID,
+        sensor_name: String,
+        topic: String,
+        expected_hz: f64,
+        actual_hz: f64,
+    },
+}
+
+/// Erwartete Frequenz eines Sensors für `topic` aus den `custom_parameters`.
+pub fn expected_frequency(sensor: &Sensor, topic: &str) -> Option<f64> {
+    let params = sensor.custom_parameters.as_ref()?.as_object()?;
+    let value = EXPECTED_FREQUENCY_KEYS
+        .iter()
+        .find_map(|k| params.get(*k))?;
+    let value = match value.as_object() {
+        Some(per_topic) => per_topic.get(topic)?,
+        None => value,
+    };
+    let hz = match value {
+        serde_json::Value::Number(n) => n.as_f64(),
+        serde_json::Value::String(s) => {
+            let s = s.trim();
+            let s = s
+                .strip_suffix("Hz")
+                .or_else(|| s.strip_suffix("hz"))
+                .unwrap_or(s);
+            s.trim().parse::<f64>().ok()
+        }
+        _ => None,
+    }?;
+    (hz.is_finite() && hz > 0.0).then_some(hz)
+}
+
+/// Vergleicht deklarierte Sensor-Topics mit den aufgezeichneten Topics.
+///
+/// Nicht zugeordnete Topics werden nur gemeldet, wenn der Eintrag überhaupt
+/// Sensoren deklariert; ohne Metadaten-YAML wäre sonst jedes Topic ein Befund.
+pub fn check_consistency(sensors: &[Sensor], topics: &[Topic]) -> Vec<ConsistencyIssue> {
+    let mut sensors: Vec<&Sensor> = sensors.iter().collect();
+    sensors.sort_by_key(|s| s.id);
+    let mut topics: Vec<&Topic> = topics.iter().collect();
+    topics.sort_by(|a, b| a.topic_name.cmp(&b.topic_name));
+
+    let mut issues = Vec::new();
+    for sensor in sensors.iter() {
+        for declared in sensor.ros_topics.iter() {
+            let recorded = topics
+                .iter()
+                .find(|t| &t.topic_name == declared && t.message_count > 0);
+            let Some(recorded) = recorded else {
+                issues.push(ConsistencyIssue::MissingTopic {
+                    sensor_id: sensor.id,
+                    sensor_name: sensor.sensor_name.clone(),
+                    topic: declared.clone(),
+                });
+                continue;
+            };
+            if let (Some(expected_hz), Some(actual_hz)) =
+                (expected_frequency(sensor, declared), recorded.frequency)
+                && (actual_hz - expected_hz).abs() > expected_hz * FREQUENCY_TOLERANCE
+            {
+                issues.push(ConsistencyIssue::FrequencyMismatch {
+                    sensor_id: sensor.id,
+                    sensor_name: sensor.sensor_name.clone(),
+                    topic: declared.clone(),
+                    expected_hz,
+                    actual_hz,
+                });
+            }
+        }
+    }
+
+    if !sensors.is_empty() {
+        let declared: HashSet<&str> = sensors
+            .iter()
+            .flat_map(|s| s.ros_topics.iter().map(String::as_str))
+            .collect();
+        for topic in topics.iter() {
+            let name = topic.topic_name.as_str();
+            if !declared.contains(name) && !IGNORED_TOPICS.contains(&name) {
+                issues.push(ConsistencyIssue::UnattributedTopic {
+                    topic: topic.topic_name.clone(),
+                });
+            }
+        }
+    }
+    issues
+}
@@ -2,6 +2,7 @@ use std::collections::HashSet;
 use std::path::PathBuf;
 use std::{path::Path, time::Duration};
 
+use crate::events::CatalogEvent;
 use crate::schema::files;
 use crate::storage::models::*;
 use crate::{
@@ -16,74 +17,47 @@ use tracing::{debug, error, instrument, warn};
 use walkdir::WalkDir;
 
 // NEW: plugin manager type
-use crate::plugin_manager::manager::{
-    PluginManager, build_started_instance_core, build_started_instance_core_with_data,
-};
+use crate::plugin_manager::manager::PluginManager;
+use crate::plugin_manager::queue::JobPriority;
 use crate::plugin_manager::plugin::BackendEvent;
+use crate::plugin_manager::trigger_filter::TriggerSubject;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
-// Helper: fire plugin backend event without holding global lock across awaits
+// Helper: fire plugin backend event; the instances are queued and started by the dispatcher.
+// Pipelines with a matching trigger start as well.
 async fn fire_plugin_event(
     plugin_manager: Arc<Mutex<PluginManager>>,
     event: BackendEvent,
+    subject: &TriggerSubject,
+    entry_id: EntryID,
     data: Option<String>,
 ) {
-    // Phase 1: prepare under lock + attach plugin_name for detached build
-    let plans: Vec<(usize, String, std::path::PathBuf, u64)> = {
-        let pm = plugin_manager.lock().await;
-        let raw = match pm.prepare_fire_event(&event) {
-            Ok(v) => v,
-            Err(e) => {
-                warn!("prepare_fire_event failed: {:?}", e);
-                return;
-            }
-        };
-
-        raw.into_iter()
-            .map(|(plugin_index, plugin_path, instance_id)| {
-                let plugin_name = pm
-                    .registered
-                    .get(plugin_index)
-                    .map(|p| p.name().clone())
-                    .unwrap_or_else(|| "unknown".to_string());
-                (plugin_index, plugin_name, plugin_path, instance_id)
-            })
-            .collect()
+    let mut pm = plugin_manager.lock().await;
+    let plans = match pm.prepare_fire_event_for(&event, subject) {
+        Ok(v) => v,
+        Err(e) => {
+            warn!("prepare_fire_event failed: {:?}", e);
+            return;
+        }
     };
-    // Phase 2: build without lock
-    let mut built: Vec<(u64, crate::plugin_manager::manager::PluginHandle)> = Vec::new();
-    for (plugin_index, plugin_name, plugin_path, instance_id) in plans {
-        let handle_res = match &data {
-            Some(d) => {
-                build_started_instance_core_with_data(
-                    plugin_index,
-                    plugin_name,
-                    &plugin_path,
-                    instance_id,
-                    d.clone(),
-                )
-                .await
-            }
-            None => {
-                build_started_instance_core(plugin_index, plugin_name, &plugin_path, instance_id)
-                    .await
-            }
-        };
 
-        match handle_res {
-            Ok(handle) => built.push((instance_id, handle)),
-            Err(e) => warn!("build_started_instance_detached failed: {:?}", e),
+    for (plugin_index, _plugin_path, instance_id) in plans {
+        let data = data.clone().unwrap_or_default();
+        if let Err(e) = pm.enqueue(plugin_index, instance_id, data, JobPriority::Triggered) {
+            warn!("enqueue_fired_event_instance failed: {:?}", e);
         }
     }
 
-    // Phase 3: commit under lock
-    let mut pm = plugin_manager.lock().await;
-    for (instance_id, handle) in built {
-        if let Err(e) = pm.commit_started_instance(instance_id, handle) {
-            warn!("commit_fired_event_instance failed: {:?}", e);
-        }
-    }
+    let input = data.unwrap_or_else(|| {
+        serde_json::json!({
+            "event": event.event_name(),
+            "path": subject.path,
+            "entry_id": entry_id,
+        })
+        .to_string()
+    });
+    pm.start_pipelines_for(&event, subject, input);
 }
 
 async fn sync_file_added_or_modified(
@@ -109,11 +83,31 @@ async fn sync_file_added_or_modified(
     };
     // If this is an MCAP, insert/update the entry in the DB
     if is_mcap {
-        fire_plugin_event(plugin_manager.clone(), backend_event, None).await;
-        if let Err(e) =
-            parsing::insert_entry_into_db(storage_manager, path, plugin_manager.clone()).await
-        {
-            error!("Failed to insert/update entry from scan: {:?}", e);
+        match parsing::insert_entry_into_db(storage_manager, path, plugin_manager.clone()).await {
+            Ok(entry) => {
+                // Trigger-Bedingungen brauchen den Eintrag samt Topics.
+                let topics = storage_manager
+                    .get_topics(entry.id, storage_manager.start_transaction())
+                    .await
+                    .map(|topics| {
+                        topics
+                            .into_values()
+                            .map(|t| (t.topic_name, t.topic_type))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let subject = TriggerSubject::from_entry(&entry, topics);
+                fire_plugin_event(plugin_manager.clone(), backend_event, &subject, entry.id, None)
+                    .await;
+
+                let (entry_id, path) = (entry.id, entry.path);
+                storage_manager.events().publish(if created {
+                    CatalogEvent::EntryCreated { entry_id, path }
+                } else {
+                    CatalogEvent::EntryUpdated { entry_id, path }
+                });
+            }
+            Err(e) => error!("Failed to insert/update entry from scan: {:?}", e),
         }
     }
 
@@ -124,14 +118,23 @@ async fn sync_file_added_or_modified(
                 while let Ok(Some(ent)) = dir.next_entry().await {
                     let p = ent.path();
                     if parsing::file_is_mcap(&p) {
-                        if let Err(e) = parsing::insert_entry_into_db(
+                        match parsing::insert_entry_into_db(
                             storage_manager,
                             &p,
                             plugin_manager.clone(),
                         )
                         .await
                         {
-                            error!("Failed to insert/update entry from metadata scan: {:?}", e);
+                            Ok(entry) => storage_manager.events().publish(
+                                CatalogEvent::EntryUpdated {
+                                    entry_id: entry.id,
+                                    path: entry.path,
+                                },
+                            ),
+                            Err(e) => error!(
+                                "Failed to insert/update entry from metadata scan: {:?}",
+                                e
+                            ),
                         }
                     }
                 }
@@ -156,9 +159,11 @@ async fn sync_file_removed(
     {
         let txid = storage_manager.start_transaction();
 
-        // remove topics
+        // remove topics (für die Trigger-Bedingungen gemerkt)
+        let mut removed_topics = Vec::new();
         if let Ok(topics_map) = storage_manager.get_topics(entry.id, txid).await {
-            for (tid, _t) in topics_map.into_iter() {
+            for (tid, t) in topics_map.into_iter() {
+                removed_topics.push((t.topic_name, t.topic_type));
                 if let Err(e) = storage_manager.remove_topic(tid, txid).await {
                     error!(
                         "Failed to remove topic {} for entry {}: {:?}",
@@ -170,7 +175,7 @@ async fn sync_file_removed(
         // remove sensors
         if let Ok(sensors_map) = storage_manager.get_sensors(entry.id, txid).await {
             for (sid, _s) in sensors_map.into_iter() {
-                if let Err(e) = storage_manager.remove_sensor(sid, txid).await {
+                if let Err(e) = storage_manager.remove_sensor(sid, None, txid).await {
                     error!(
                         "Failed to remove sensor {} for entry {}: {:?}",
                         sid, entry.id, e
@@ -181,7 +186,7 @@ async fn sync_file_removed(
         // remove sequences
         if let Ok(seqs_map) = storage_manager.get_sequences(entry.id, txid).await {
             for (seqid, _s) in seqs_map.into_iter() {
-                if let Err(e) = storage_manager.remove_sequence(entry.id, seqid, txid).await {
+                if let Err(e) = storage_manager.remove_sequence(entry.id, seqid, None, txid).await {
                     error!(
                         "Failed to remove sequence {} for entry {}: {:?}",
                         seqid, entry.id, e
@@ -225,6 +230,11 @@ async fn sync_file_removed(
 
         // Trigger only if DB delete actually happened
         if deleted_ok {
+            storage_manager.events().publish(CatalogEvent::EntryDeleted {
+                entry_id,
+                path: removed_path.clone(),
+            });
+
             // Provide payload for plugins (so they don't see empty data on delete)
             let plugin_data = serde_json::json!({
                 "metadata": {
@@ -256,6 +266,8 @@ async fn sync_file_removed(
                 BackendEvent::EntryDeleted {
                     path: removed_path.clone(),
                 },
+                &TriggerSubject::from_entry(&entry, removed_topics),
+                entry.id,
                 Some(plugin_data),
             )
             .await;
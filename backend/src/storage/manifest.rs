@@ -0,0 +1,478 @@
+//! Flaches Katalog-Manifest (eine Zeile je Eintrag) für Analyse und Übergabe.
+//!
+//! Alle Formate haben dieselben Spalten in derselben Reihenfolge
+//! ([`MANIFEST_COLUMNS`]); Listen (Tags, Topics, ...) sind in CSV mit `;`
+//! verbunden. Der [`ManifestEncoder`] erzeugt die Ausgabe stückweise, damit die
+//! Route große Kataloge streamen kann. Die Funktionen hier sind rein (ohne
+//! Datenbank).
+
+use crate::error::StorageError;
+use crate::storage::collections::csv_field;
+use crate::storage::models::{Entry, EntryID, Sensor, Sequence, Topic};
+use arrow_array::builder::{ListBuilder, StringBuilder};
+use arrow_array::{
+    ArrayRef, BooleanArray, Float64Array, Int64Array, RecordBatch, StringArray,
+    TimestampMicrosecondArray,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use chrono::{DateTime, Utc};
+use parquet::arrow::ArrowWriter;
+use parquet::file::metadata::KeyValue;
+use parquet::file::properties::WriterProperties;
+use rocket::serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+/// Wird erhöht, wenn sich Spalten ändern; steht im Header jedes Exports.
+pub const MANIFEST_SCHEMA_VERSION: u32 = 1;
+
+/// Spalten in Ausgabereihenfolge, identisch zu den Feldern von [`ManifestRow`].
+pub const MANIFEST_COLUMNS: &[&str] = &[
+    "entry_id",
+    "name",
+    "path",
+    "size",
+    "status",
+    "created_at",
+    "updated_at",
+    "mcap_start_ns",
+    "mcap_end_ns",
+    "time_machine",
+    "platform_name",
+    "platform_image_link",
+    "scenario_name",
+    "scenario_creation_time",
+    "scenario_description",
+    "sequence_duration",
+    "sequence_distance",
+    "sequence_lat_starting_point_deg",
+    "sequence_lon_starting_point_deg",
+    "weather_cloudiness",
+    "weather_precipitation",
+    "weather_precipitation_deposits",
+    "weather_wind_intensity",
+    "weather_road_humidity",
+    "weather_fog",
+    "weather_snow",
+    "tags",
+    "topic_count",
+    "message_count",
+    "topics",
+    "sensors",
+    "sequence_count",
+    "sequences",
+];
+
+/// Header-Schlüssel in den Key-Value-Metadaten einer Parquet-Datei.
+pub const PARQUET_HEADER_KEY: &str = "manifest_header";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestFormat {
+    Json,
+    Csv,
+    Parquet,
+}
+
+impl ManifestFormat {
+    pub fn parse(value: &str) -> Result<Self, StorageError> {
+        match value {
+            "json" => Ok(ManifestFormat::Json),
+            "csv" => Ok(ManifestFormat::Csv),
+            "parquet" => Ok(ManifestFormat::Parquet),
+            other => Err(StorageError::ValidationError(format!(
+                "unsupported export format '{other}', expected json, csv or parquet"
+            ))),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ManifestFormat::Json => "json",
+            ManifestFormat::Csv => "csv",
+            ManifestFormat::Parquet => "parquet",
+        }
+    }
+}
+
+/// Filter, mit dem der Export erzeugt wurde (wie bei `GET /entries`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct ManifestFilter {
+    pub search_string: Option<String>,
+    pub consistency: Option<String>,
+    pub sort_by: Option<String>,
+    pub ascending: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct ManifestHeader {
+    pub schema_version: u32,
+    pub generated_at: DateTime<Utc>,
+    pub filter: ManifestFilter,
+    pub entry_count: usize,
+    pub columns: Vec<String>,
+}
+
+impl ManifestHeader {
+    pub fn new(filter: ManifestFilter, entry_count: usize) -> Self {
+        ManifestHeader {
+            schema_version: MANIFEST_SCHEMA_VERSION,
+            generated_at: Utc::now(),
+            filter,
+            entry_count,
+            columns: MANIFEST_COLUMNS.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+}
+
+/// Eine Zeile des Manifests. Feldreihenfolge = [`MANIFEST_COLUMNS`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct ManifestRow {
+    pub entry_id: EntryID,
+    pub name: String,
+    pub path: String,
+    pub size: i64,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub mcap_start_ns: Option<i64>,
+    pub mcap_end_ns: Option<i64>,
+    pub time_machine: Option<f64>,
+    pub platform_name: Option<String>,
+    pub platform_image_link: Option<String>,
+    pub scenario_name: Option<String>,
+    pub scenario_creation_time: Option<DateTime<Utc>>,
+    pub scenario_description: Option<String>,
+    pub sequence_duration: Option<f64>,
+    pub sequence_distance: Option<f64>,
+    pub sequence_lat_starting_point_deg: Option<f64>,
+    pub sequence_lon_starting_point_deg: Option<f64>,
+    pub weather_cloudiness: Option<String>,
+    pub weather_precipitation: Option<String>,
+    pub weather_precipitation_deposits: Option<String>,
+    pub weather_wind_intensity: Option<String>,
+    pub weather_road_humidity: Option<String>,
+    pub weather_fog: Option<bool>,
+    pub weather_snow: Option<bool>,
+    pub tags: Vec<String>,
+    pub topic_count: i64,
+    /// Summe der Nachrichten über alle Topics.
+    pub message_count: i64,
+    pub topics: Vec<String>,
+    pub sensors: Vec<String>,
+    pub sequence_count: i64,
+    pub sequences: Vec<String>,
+}
+
+impl ManifestRow {
+    /// Baut die Zeile eines Eintrags; Topics, Sensoren und Sequenzen werden nach
+    /// Namen sortiert, damit Exporte vergleichbar bleiben.
+    pub fn new(entry: Entry, topics: &[Topic], sensors: &[Sensor], sequences: &[Sequence]) -> Self {
+        let sorted = |mut names: Vec<String>| {
+            names.sort();
+            names
+        };
+        ManifestRow {
+            entry_id: entry.id,
+            name: entry.name,
+            path: entry.path,
+            size: entry.size,
+            status: entry.status,
+            created_at: entry.created_at,
+            updated_at: entry.updated_at,
+            mcap_start_ns: entry.mcap_start_ns,
+            mcap_end_ns: entry.mcap_end_ns,
+            time_machine: entry.time_machine,
+            platform_name: entry.platform_name,
+            platform_image_link: entry.platform_image_link,
+            scenario_name: entry.scenario_name,
+            scenario_creation_time: entry.scenario_creation_time,
+            scenario_description: entry.scenario_description,
+            sequence_duration: entry.sequence_duration,
+            sequence_distance: entry.sequence_distance,
+            sequence_lat_starting_point_deg: entry.sequence_lat_starting_point_deg,
+            sequence_lon_starting_point_deg: entry.sequence_lon_starting_point_deg,
+            weather_cloudiness: entry.weather_cloudiness,
+            weather_precipitation: entry.weather_precipitation,
+            weather_precipitation_deposits: entry.weather_precipitation_deposits,
+            weather_wind_intensity: entry.weather_wind_intensity,
+            weather_road_humidity: entry.weather_road_humidity,
+            weather_fog: entry.weather_fog,
+            weather_snow: entry.weather_snow,
+            tags: entry.tags,
+            topic_count: topics.len() as i64,
+            message_count: topics.iter().map(|t| t.message_count).sum(),
+            topics: sorted(topics.iter().map(|t| t.topic_name.clone()).collect()),
+            sensors: sorted(sensors.iter().map(|s| s.sensor_name.clone()).collect()),
+            sequence_count: sequences.len() as i64,
+            sequences: sorted(sequences.iter().map(|s| s.name.clone()).collect()),
+        }
+    }
+}
+
+/// Header als `#`-Kommentarzeilen plus Spaltenzeile
+/// (`pandas.read_csv(..., comment="#")`).
+pub fn csv_header(header: &ManifestHeader) -> String {
+    let filter = serde_json::to_string(&header.filter).unwrap_or_default();
+    format!(
+        "# schema_version: {}\n# generated_at: {}\n# filter: {}\n# entry_count: {}\n{}\n",
+        header.schema_version,
+        header.generated_at.to_rfc3339(),
+        filter,
+        header.entry_count,
+        MANIFEST_COLUMNS.join(",")
+    )
+}
+
+pub fn csv_row(row: &ManifestRow) -> String {
+    let value = serde_json::to_value(row).unwrap_or_default();
+    let fields: Vec<String> = MANIFEST_COLUMNS
+        .iter()
+        .map(|column| match &value[*column] {
+            serde_json::Value::Null => String::new(),
+            serde_json::Value::String(s) => csv_field(s),
+            serde_json::Value::Array(items) => csv_field(
+                &items
+                    .iter()
+                    .map(|i| i.as_str().map(str::to_string).unwrap_or(i.to_string()))
+                    .collect::<Vec<String>>()
+                    .join(";"),
+            ),
+            other => other.to_string(),
+        })
+        .collect();
+    let mut line = fields.join(",");
+    line.push('\n');
+    line
+}
+
+/// Arrow-Schema der Parquet-Ausgabe; Listen sind `List<Utf8>`, Zeitpunkte UTC
+/// in Mikrosekunden.
+pub fn arrow_schema() -> SchemaRef {
+    let list = || DataType::List(Arc::new(Field::new("item", DataType::Utf8, true)));
+    let ts = || DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()));
+    let fields: Vec<Field> = MANIFEST_COLUMNS
+        .iter()
+        .map(|column| {
+            let (data_type, nullable) = match *column {
+                "entry_id" | "size" | "topic_count" | "message_count" | "sequence_count" => {
+                    (DataType::Int64, false)
+                }
+                "name" | "path" | "status" => (DataType::Utf8, false),
+                "created_at" | "updated_at" => (ts(), false),
+                "scenario_creation_time" => (ts(), true),
+                "mcap_start_ns" | "mcap_end_ns" => (DataType::Int64, true),
+                "time_machine"
+                | "sequence_duration"
+                | "sequence_distance"
+                | "sequence_lat_starting_point_deg"
+                | "sequence_lon_starting_point_deg" => (DataType::Float64, true),
+                "weather_fog" | "weather_snow" => (DataType::Boolean, true),
+                "tags" | "topics" | "sensors" | "sequences" => (list(), false),
+                _ => (DataType::Utf8, true),
+            };
+            Field::new(*column, data_type, nullable)
+        })
+        .collect();
+    Arc::new(Schema::new(fields))
+}
+
+fn parquet_error(err: impl std::fmt::Display) -> StorageError {
+    StorageError::CustomError(format!("parquet export failed: {err}"))
+}
+
+/// Baut einen Arrow-Batch aus Manifestzeilen (Spalten wie [`arrow_schema`]).
+pub fn record_batch(rows: &[ManifestRow]) -> Result<RecordBatch, StorageError> {
+    fn ints(rows: &[ManifestRow], f: impl Fn(&ManifestRow) -> Option<i64>) -> ArrayRef {
+        Arc::new(Int64Array::from(rows.iter().map(f).collect::<Vec<_>>()))
+    }
+    fn floats(rows: &[ManifestRow], f: impl Fn(&ManifestRow) -> Option<f64>) -> ArrayRef {
+        Arc::new(Float64Array::from(rows.iter().map(f).collect::<Vec<_>>()))
+    }
+    fn bools(rows: &[ManifestRow], f: impl Fn(&ManifestRow) -> Option<bool>) -> ArrayRef {
+        Arc::new(BooleanArray::from(rows.iter().map(f).collect::<Vec<_>>()))
+    }
+    fn strings(rows: &[ManifestRow], f: impl Fn(&ManifestRow) -> Option<&str>) -> ArrayRef {
+        Arc::new(StringArray::from(rows.iter().map(f).collect::<Vec<_>>()))
+    }
+    fn times(rows: &[ManifestRow], f: impl Fn(&ManifestRow) -> Option<DateTime<Utc>>) -> ArrayRef {
+        let micros: Vec<Option<i64>> = rows
+            .iter()
+            .map(|r| f(r).map(|t| t.timestamp_micros()))
+            .collect();
+        Arc::new(TimestampMicrosecondArray::from(micros).with_timezone("UTC"))
+    }
+    fn lists(rows: &[ManifestRow], f: impl Fn(&ManifestRow) -> &Vec<String>) -> ArrayRef {
+        let mut builder = ListBuilder::new(StringBuilder::new());
+        for row in rows {
+            for item in f(row) {
+                builder.values().append_value(item);
+            }
+            builder.append(true);
+        }
+        Arc::new(builder.finish())
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+        ints(rows, |r| Some(r.entry_id)),
+        strings(rows, |r| Some(r.name.as_str())),
+        strings(rows, |r| Some(r.path.as_str())),
+        ints(rows, |r| Some(r.size)),
+        strings(rows, |r| Some(r.status.as_str())),
+        times(rows, |r| Some(r.created_at)),
+        times(rows, |r| Some(r.updated_at)),
+        ints(rows, |r| r.mcap_start_ns),
+        ints(rows, |r| r.mcap_end_ns),
+        floats(rows, |r| r.time_machine),
+        strings(rows, |r| r.platform_name.as_deref()),
+        strings(rows, |r| r.platform_image_link.as_deref()),
+        strings(rows, |r| r.scenario_name.as_deref()),
+        times(rows, |r| r.scenario_creation_time),
+        strings(rows, |r| r.scenario_description.as_deref()),
+        floats(rows, |r| r.sequence_duration),
+        floats(rows, |r| r.sequence_distance),
+        floats(rows, |r| r.sequence_lat_starting_point_deg),
+        floats(rows, |r| r.sequence_lon_starting_point_deg),
+        strings(rows, |r| r.weather_cloudiness.as_deref()),
+        strings(rows, |r| r.weather_precipitation.as_deref()),
+        strings(rows, |r| r.weather_precipitation_deposits.as_deref()),
+        strings(rows, |r| r.weather_wind_intensity.as_deref()),
+        strings(rows, |r| r.weather_road_humidity.as_deref()),
+        bools(rows, |r| r.weather_fog),
+        bools(rows, |r| r.weather_snow),
+        lists(rows, |r| &r.tags),
+        ints(rows, |r| Some(r.topic_count)),
+        ints(rows, |r| Some(r.message_count)),
+        lists(rows, |r| &r.topics),
+        lists(rows, |r| &r.sensors),
+        ints(rows, |r| Some(r.sequence_count)),
+        lists(rows, |r| &r.sequences),
+    ];
+    RecordBatch::try_new(arrow_schema(), columns).map_err(parquet_error)
+}
+
+/// Schreibziel des Parquet-Writers, aus dem die Route fertige Bytes abholt.
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Erzeugt ein Manifest stückweise: [`begin`](Self::begin), beliebig oft
+/// [`encode`](Self::encode), dann [`finish`](Self::finish).
+///
+/// Bei Parquet wird je `encode` eine Row-Group geschrieben; der Header steht
+/// unter [`PARQUET_HEADER_KEY`] in den Datei-Metadaten.
+pub struct ManifestEncoder(EncoderState);
+
+enum EncoderState {
+    Json {
+        header: ManifestHeader,
+        first: bool,
+    },
+    Csv {
+        header: ManifestHeader,
+    },
+    Parquet {
+        writer: Box<ArrowWriter<SharedBuffer>>,
+        buffer: SharedBuffer,
+    },
+}
+
+impl ManifestEncoder {
+    pub fn new(format: ManifestFormat, header: ManifestHeader) -> Result<Self, StorageError> {
+        let state = match format {
+            ManifestFormat::Json => EncoderState::Json {
+                header,
+                first: true,
+            },
+            ManifestFormat::Csv => EncoderState::Csv { header },
+            ManifestFormat::Parquet => {
+                let header = serde_json::to_string(&header).map_err(parquet_error)?;
+                let props = WriterProperties::builder()
+                    .set_key_value_metadata(Some(vec![KeyValue::new(
+                        PARQUET_HEADER_KEY.to_string(),
+                        header,
+                    )]))
+                    .build();
+                let buffer = SharedBuffer::default();
+                let writer = ArrowWriter::try_new(buffer.clone(), arrow_schema(), Some(props))
+                    .map_err(parquet_error)?;
+                EncoderState::Parquet {
+                    writer: Box::new(writer),
+                    buffer,
+                }
+            }
+        };
+        Ok(ManifestEncoder(state))
+    }
+
+    pub fn begin(&mut self) -> Result<Vec<u8>, StorageError> {
+        match &mut self.0 {
+            EncoderState::Json { header, .. } => {
+                let header = serde_json::to_string(header)
+                    .map_err(|e| StorageError::CustomError(e.to_string()))?;
+                Ok(format!("{{\"header\":{header},\"entries\":[\n").into_bytes())
+            }
+            EncoderState::Csv { header } => Ok(csv_header(header).into_bytes()),
+            EncoderState::Parquet { buffer, .. } => Ok(buffer.take()),
+        }
+    }
+
+    pub fn encode(&mut self, rows: &[ManifestRow]) -> Result<Vec<u8>, StorageError> {
+        match &mut self.0 {
+            EncoderState::Json { first, .. } => {
+                let mut out = String::new();
+                for row in rows {
+                    if !*first {
+                        out.push_str(",\n");
+                    }
+                    *first = false;
+                    out.push_str(
+                        &serde_json::to_string(row)
+                            .map_err(|e| StorageError::CustomError(e.to_string()))?,
+                    );
+                }
+                Ok(out.into_bytes())
+            }
+            EncoderState::Csv { .. } => {
+                Ok(rows.iter().map(csv_row).collect::<String>().into_bytes())
+            }
+            EncoderState::Parquet { writer, buffer } => {
+                if rows.is_empty() {
+                    return Ok(Vec::new());
+                }
+                writer.write(&record_batch(rows)?).map_err(parquet_error)?;
+                writer.flush().map_err(parquet_error)?;
+                Ok(buffer.take())
+            }
+        }
+    }
+
+    pub fn finish(self) -> Result<Vec<u8>, StorageError> {
+        match self.0 {
+            EncoderState::Json { .. } => Ok(b"\n]}\n".to_vec()),
+            EncoderState::Csv { .. } => Ok(Vec::new()),
+            EncoderState::Parquet { writer, buffer } => {
+                writer.close().map_err(parquet_error)?;
+                Ok(buffer.take())
+            }
+        }
+    }
+}
@@ -0,0 +1,486 @@
+//! Einlesen von Metadaten-Importen (CSV oder JSON), Zeile für Zeile.
+//!
+//! Jede Zeile adressiert einen Eintrag über `path` (bevorzugt) oder `name` und
+//! enthält beliebige Felder aus [`EntryMetadataPatch`], zusätzliche `tags` und
+//! neue Sequenzen. Leere bzw. fehlende Felder lassen den gespeicherten Wert
+//! unverändert. Spalten des Manifest-Exports, die nicht importierbar sind
+//! (z.B. `size`, `topics`), werden ignoriert, damit ein exportiertes Manifest
+//! bearbeitet und wieder eingespielt werden kann. Die Funktionen hier sind rein
+//! (ohne Datenbank).
+
+use crate::error::StorageError;
+use crate::routes::database::MetadataWeb;
+use crate::storage::manifest::MANIFEST_COLUMNS;
+use crate::storage::models::{Entry, EntryID, EntryMetadataPatch, Timestamp};
+use chrono::NaiveDate;
+use rocket::serde::{Deserialize, Serialize};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+/// Metadatenfelder mit Zahlenwert (in CSV als Dezimalzahl).
+pub const FLOAT_FIELDS: &[&str] = &[
+    "time_machine",
+    "sequence_duration",
+    "sequence_distance",
+    "sequence_lat_starting_point_deg",
+    "sequence_lon_starting_point_deg",
+];
+
+/// Metadatenfelder mit Wahrheitswert (`true/false`, `yes/no`, `1/0`).
+pub const BOOL_FIELDS: &[&str] = &["weather_fog", "weather_snow"];
+
+/// CSV-Spalten einer neuen Sequenz; eine Sequenz je Zeile.
+pub const SEQUENCE_COLUMNS: &[&str] = &[
+    "sequence_name",
+    "sequence_description",
+    "sequence_start_timestamp",
+    "sequence_end_timestamp",
+    "sequence_tags",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(
+    crate = "rocket::serde",
+    tag = "by",
+    content = "value",
+    rename_all = "snake_case"
+)]
+pub enum ImportKey {
+    Path(String),
+    Name(String),
+}
+
+impl std::fmt::Display for ImportKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportKey::Path(p) => write!(f, "path '{p}'"),
+            ImportKey::Name(n) => write!(f, "name '{n}'"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", deny_unknown_fields)]
+pub struct ImportSequence {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub start_timestamp: Timestamp,
+    pub end_timestamp: Timestamp,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Gültig eingelesene Zeile. `row` ist bei CSV die Zeilennummer in der Datei
+/// (Kopfzeile = 1), bei JSON die Position im Array ab 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportRow {
+    pub row: usize,
+    pub key: ImportKey,
+    pub metadata: EntryMetadataPatch,
+    pub tags: Vec<String>,
+    pub sequences: Vec<ImportSequence>,
+}
+
+/// Zeile, die schon beim Einlesen scheitert (z.B. keine Zahl in `time_machine`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowError {
+    pub row: usize,
+    pub key: Option<ImportKey>,
+    pub message: String,
+}
+
+pub type ParsedRow = Result<ImportRow, RowError>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", rename_all = "snake_case")]
+pub enum ImportRowStatus {
+    /// Probelauf: Zeile wäre angewendet worden.
+    Valid,
+    Applied,
+    Invalid,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ImportRowReport {
+    pub row: usize,
+    pub key: Option<ImportKey>,
+    pub entry_id: Option<EntryID>,
+    pub status: ImportRowStatus,
+    /// Gesetzte Metadatenfelder.
+    pub fields: Vec<String>,
+    pub tags: Vec<String>,
+    pub sequences: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+impl ImportRowReport {
+    pub fn invalid(error: RowError) -> Self {
+        ImportRowReport {
+            row: error.row,
+            key: error.key,
+            entry_id: None,
+            status: ImportRowStatus::Invalid,
+            fields: Vec::new(),
+            tags: Vec::new(),
+            sequences: Vec::new(),
+            errors: vec![error.message],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct MetadataImportReport {
+    pub dry_run: bool,
+    /// Nur `true`, wenn alle Zeilen gültig waren und geschrieben wurden.
+    pub applied: bool,
+    pub total: usize,
+    pub invalid: usize,
+    pub rows: Vec<ImportRowReport>,
+}
+
+/// Geprüfter Import, den [`StorageManager::apply_metadata_import`] schreibt.
+///
+/// [`StorageManager::apply_metadata_import`]: crate::storage::storage_manager::StorageManager::apply_metadata_import
+#[derive(Debug, Clone)]
+pub struct MetadataImportPlan {
+    /// Bericht der Prüfung; `applied` sagt, ob geschrieben werden soll.
+    pub report: MetadataImportReport,
+    /// Zeilen mit ihrem Eintrag in der bei der Prüfung gelesenen Version.
+    pub(crate) writes: Vec<(Entry, ImportRow)>,
+}
+
+/// Namen aller importierbaren Metadatenfelder.
+pub fn metadata_fields() -> Vec<String> {
+    match serde_json::to_value(EntryMetadataPatch::default()) {
+        Ok(JsonValue::Object(fields)) => fields.keys().cloned().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Gesetzte Felder eines Patches, alphabetisch sortiert.
+pub fn patch_fields(patch: &EntryMetadataPatch) -> Vec<String> {
+    match serde_json::to_value(patch) {
+        Ok(JsonValue::Object(values)) => values
+            .into_iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(key, _)| key)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Gespeicherte Metadaten eines Eintrags, überlagert mit den gesetzten Feldern
+/// des Patches (Eingabe für `update_entry`).
+pub fn metadata_with_patch(entry: &Entry, patch: &EntryMetadataPatch) -> MetadataWeb {
+    let p = patch.clone();
+    MetadataWeb {
+        time_machine: p.time_machine.or(entry.time_machine),
+        platform_name: p.platform_name.or(entry.platform_name.clone()),
+        platform_image_link: p.platform_image_link.or(entry.platform_image_link.clone()),
+        scenario_name: p.scenario_name.or(entry.scenario_name.clone()),
+        scenario_creation_time: p.scenario_creation_time.or(entry.scenario_creation_time),
+        scenario_description: p
+            .scenario_description
+            .or(entry.scenario_description.clone()),
+        sequence_duration: p.sequence_duration.or(entry.sequence_duration),
+        sequence_distance: p.sequence_distance.or(entry.sequence_distance),
+        sequence_lat_starting_point_deg: p
+            .sequence_lat_starting_point_deg
+            .or(entry.sequence_lat_starting_point_deg),
+        sequence_lon_starting_point_deg: p
+            .sequence_lon_starting_point_deg
+            .or(entry.sequence_lon_starting_point_deg),
+        weather_cloudiness: p.weather_cloudiness.or(entry.weather_cloudiness.clone()),
+        weather_precipitation: p
+            .weather_precipitation
+            .or(entry.weather_precipitation.clone()),
+        weather_precipitation_deposits: p
+            .weather_precipitation_deposits
+            .or(entry.weather_precipitation_deposits.clone()),
+        weather_wind_intensity: p
+            .weather_wind_intensity
+            .or(entry.weather_wind_intensity.clone()),
+        weather_road_humidity: p
+            .weather_road_humidity
+            .or(entry.weather_road_humidity.clone()),
+        weather_fog: p.weather_fog.or(entry.weather_fog),
+        weather_snow: p.weather_snow.or(entry.weather_snow),
+        topics: None,
+    }
+}
+
+/// Spalten mit eigener Bedeutung neben den Metadatenfeldern.
+const ROW_COLUMNS: &[&str] = &["path", "name", "tags", "sequences"];
+
+/// Nur lesbare Spalten des Manifest-Exports.
+fn is_ignored_column(column: &str) -> bool {
+    MANIFEST_COLUMNS.contains(&column)
+        && !ROW_COLUMNS.contains(&column)
+        && !metadata_fields().iter().any(|f| f == column)
+}
+
+/// Baut eine Zeile aus einem JSON-Objekt.
+///
+/// `sequences` muss aus Objekten bestehen; Strings (wie im Manifest-Export, der
+/// nur Namen bestehender Sequenzen enthält) werden übersprungen.
+pub fn row_from_object(row: usize, mut object: JsonMap<String, JsonValue>) -> ParsedRow {
+    let text = |v: Option<JsonValue>| v.and_then(|v| v.as_str().map(str::trim).map(str::to_string));
+    let path = text(object.remove("path")).filter(|p| !p.is_empty());
+    let name = text(object.remove("name")).filter(|n| !n.is_empty());
+    let key = match (path, name) {
+        (Some(p), _) => ImportKey::Path(p),
+        (None, Some(n)) => ImportKey::Name(n),
+        (None, None) => {
+            return Err(RowError {
+                row,
+                key: None,
+                message: "row has neither path nor name".to_string(),
+            });
+        }
+    };
+    let error = |message: String| RowError {
+        row,
+        key: Some(key.clone()),
+        message,
+    };
+
+    let tags = match object.remove("tags") {
+        None | Some(JsonValue::Null) => Vec::new(),
+        Some(JsonValue::String(s)) => split_list(&s),
+        Some(value) => {
+            serde_json::from_value::<Vec<String>>(value).map_err(|e| error(format!("tags: {e}")))?
+        }
+    };
+    let sequences = match object.remove("sequences") {
+        None | Some(JsonValue::Null) => Vec::new(),
+        Some(JsonValue::Array(items)) => items
+            .into_iter()
+            .filter(|item| !item.is_string())
+            .map(serde_json::from_value::<ImportSequence>)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| error(format!("sequences: {e}")))?,
+        Some(_) => return Err(error("sequences must be an array".to_string())),
+    };
+    object.retain(|column, value| !is_ignored_column(column) && !value.is_null());
+    let metadata = serde_json::from_value::<EntryMetadataPatch>(JsonValue::Object(object))
+        .map_err(|e| error(e.to_string()))?;
+    Ok(ImportRow {
+        row,
+        key,
+        metadata,
+        tags,
+        sequences,
+    })
+}
+
+/// JSON-Import: ein Array von Objekten.
+pub fn parse_json_rows(body: &str) -> Result<Vec<ParsedRow>, StorageError> {
+    let rows: Vec<JsonValue> = serde_json::from_str(body)
+        .map_err(|e| StorageError::DecodingError(format!("invalid JSON import: {e}")))?;
+    Ok(rows
+        .into_iter()
+        .enumerate()
+        .map(|(i, value)| match value {
+            JsonValue::Object(object) => row_from_object(i + 1, object),
+            _ => Err(RowError {
+                row: i + 1,
+                key: None,
+                message: "row is not a JSON object".to_string(),
+            }),
+        })
+        .collect())
+}
+
+/// Trennt eine `;`-Liste (wie im Manifest-Export) in ihre Elemente.
+pub fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(';')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Zerlegt CSV nach RFC 4180 in Datensätze mit Zeilennummer; führende
+/// `#`-Zeilen (Header des Manifest-Exports) und Leerzeilen werden übersprungen.
+pub fn parse_csv(body: &str) -> Result<Vec<(usize, Vec<String>)>, StorageError> {
+    // Kommentarzeilen vorab entfernen, sie können unausgeglichene Quotes enthalten
+    let mut line = 1;
+    let mut body = body;
+    while let Some((first, rest)) = body.split_once('\n')
+        && (first.starts_with('#') || first.trim().is_empty())
+    {
+        body = rest;
+        line += 1;
+    }
+    let mut rows = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut record_line = line;
+    let mut chars = body.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, c) => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+            (false, '"') if field.is_empty() => quoted = true,
+            (false, ',') => record.push(std::mem::take(&mut field)),
+            (false, '\r') if chars.peek() == Some(&'\n') => {}
+            (false, '\n') => {
+                record.push(std::mem::take(&mut field));
+                rows.push((record_line, std::mem::take(&mut record)));
+                line += 1;
+                record_line = line;
+            }
+            (false, c) => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(StorageError::DecodingError(format!(
+            "unterminated quoted field starting in line {record_line}"
+        )));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        rows.push((record_line, record));
+    }
+    Ok(rows
+        .into_iter()
+        .filter(|(_, r)| !(r.len() == 1 && r[0].trim().is_empty()))
+        .collect())
+}
+
+fn csv_cell_to_json(column: &str, cell: &str) -> Result<JsonValue, String> {
+    if FLOAT_FIELDS.contains(&column) {
+        return cell
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(JsonValue::Number)
+            .ok_or_else(|| format!("{column}: '{cell}' is not a number"));
+    }
+    if BOOL_FIELDS.contains(&column) {
+        return match cell.to_lowercase().as_str() {
+            "true" | "yes" | "1" => Ok(JsonValue::Bool(true)),
+            "false" | "no" | "0" => Ok(JsonValue::Bool(false)),
+            _ => Err(format!("{column}: '{cell}' is not a boolean")),
+        };
+    }
+    if column == "scenario_creation_time"
+        && let Ok(date) = NaiveDate::parse_from_str(cell, "%Y-%m-%d")
+    {
+        // Tabellen exportieren oft nur das Datum
+        return Ok(JsonValue::String(format!("{date}T00:00:00Z")));
+    }
+    Ok(JsonValue::String(cell.to_string()))
+}
+
+/// CSV-Import mit Kopfzeile. Unbekannte Spalten machen den ganzen Import
+/// ungültig, Wertfehler nur die jeweilige Zeile.
+pub fn parse_csv_rows(body: &str) -> Result<Vec<ParsedRow>, StorageError> {
+    let mut records = parse_csv(body)?.into_iter();
+    let Some((_, header)) = records.next() else {
+        return Ok(Vec::new());
+    };
+    let header: Vec<String> = header.iter().map(|c| c.trim().to_string()).collect();
+    let fields = metadata_fields();
+    let unknown: Vec<String> = header
+        .iter()
+        .filter(|c| {
+            !ROW_COLUMNS.contains(&c.as_str())
+                && !SEQUENCE_COLUMNS.contains(&c.as_str())
+                && !fields.contains(c)
+                && !is_ignored_column(c)
+        })
+        .map(|c| format!("'{c}'"))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(StorageError::ValidationError(format!(
+            "unknown columns: {}",
+            unknown.join(", ")
+        )));
+    }
+    if !header.iter().any(|c| c == "path" || c == "name") {
+        return Err(StorageError::ValidationError(
+            "a path or name column is required".to_string(),
+        ));
+    }
+
+    Ok(records
+        .map(|(row, cells)| {
+            if cells.len() != header.len() {
+                return Err(RowError {
+                    row,
+                    key: None,
+                    message: format!("expected {} columns, found {}", header.len(), cells.len()),
+                });
+            }
+            let mut object = JsonMap::new();
+            let mut sequence = JsonMap::new();
+            let mut errors = Vec::new();
+            for (column, cell) in header.iter().zip(cells.iter()) {
+                let cell = cell.trim();
+                // `sequences` enthält im Manifest nur Namen; neue Sequenzen kommen
+                // über die `sequence_*`-Spalten
+                if cell.is_empty() || is_ignored_column(column) || column == "sequences" {
+                    continue;
+                }
+                if let Some(field) = column.strip_prefix("sequence_")
+                    && SEQUENCE_COLUMNS.contains(&column.as_str())
+                {
+                    let value = match field {
+                        "start_timestamp" | "end_timestamp" => match cell.parse::<i64>() {
+                            Ok(ns) => JsonValue::from(ns),
+                            Err(_) => {
+                                errors.push(format!("{column}: '{cell}' is not an integer"));
+                                continue;
+                            }
+                        },
+                        "tags" => JsonValue::from(split_list(cell)),
+                        _ => JsonValue::String(cell.to_string()),
+                    };
+                    sequence.insert(field.to_string(), value);
+                    continue;
+                }
+                match column.as_str() {
+                    "path" | "name" | "tags" => {
+                        object.insert(column.clone(), JsonValue::String(cell.to_string()));
+                    }
+                    _ => match csv_cell_to_json(column, cell) {
+                        Ok(value) => {
+                            object.insert(column.clone(), value);
+                        }
+                        Err(e) => errors.push(e),
+                    },
+                }
+            }
+            if !sequence.is_empty() {
+                object.insert(
+                    "sequences".to_string(),
+                    JsonValue::Array(vec![JsonValue::Object(sequence)]),
+                );
+            }
+            let parsed = row_from_object(row, object)?;
+            if errors.is_empty() {
+                Ok(parsed)
+            } else {
+                Err(RowError {
+                    row,
+                    key: Some(parsed.key),
+                    message: errors.join("; "),
+                })
+            }
+        })
+        .collect())
+}
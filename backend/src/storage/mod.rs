@@ -1,4 +1,14 @@
+pub mod artifacts;
+pub mod audit;
+pub mod auth;
+pub mod clip;
+pub mod collections;
+pub mod consistency;
 pub mod file_watcher;
+pub mod manifest;
+pub mod metadata_import;
 pub mod models;
 pub mod parsing;
 pub mod storage_manager;
+pub mod tags;
+pub mod timeline;
@@ -7,6 +7,12 @@ pub type SequenceID = i64;
 pub type SensorID = i64;
 pub type Timestamp = i64;
 pub type TopicID = i64;
+pub type CatalogSensorID = i64;
+pub type CollectionID = i64;
+pub type UserID = i64;
+pub type ApiKeyID = i64;
+pub type AuditID = i64;
+pub type ArtifactID = i64;
 
 #[derive(
     Queryable, Selectable, Insertable, Debug, Clone, Serialize, Deserialize, PartialEq, Eq,
@@ -31,6 +37,11 @@ pub struct Entry {
     pub size: i64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub version: i64,
+    /// Beginn der Aufnahme laut MCAP in Nanosekunden.
+    pub mcap_start_ns: Option<i64>,
+    /// Ende der Aufnahme laut MCAP in Nanosekunden.
+    pub mcap_end_ns: Option<i64>,
     pub status: String,
     pub time_machine: Option<f64>,
     pub platform_name: Option<String>,
@@ -82,6 +93,7 @@ pub struct Sequence {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub tags: Vec<String>,
+    pub version: i64,
 }
 
 #[derive(Queryable, Selectable, Insertable, Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -96,4 +108,489 @@ pub struct Sensor {
     pub sensor_type: Option<String>,
     pub ros_topics: Vec<String>,
     pub custom_parameters: Option<serde_json::Value>,
+    pub version: i64,
+}
+
+/// Eindeutiger Sensor (Seriennummer bzw. Modell) im globalen Sensorkatalog.
+#[derive(Queryable, Selectable, Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[diesel(table_name = crate::schema::sensor_catalog)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[serde(crate = "rocket::serde")]
+pub struct CatalogSensor {
+    pub id: CatalogSensorID,
+    pub sensor_name: String,
+    pub manufacturer: Option<String>,
+    pub sensor_type: Option<String>,
+    pub model: Option<String>,
+    pub serial_number: Option<String>,
+    pub calibration: Option<serde_json::Value>,
+    pub custom_parameters: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub version: i64,
+}
+
+/// Zuordnung eines Eintrags zu einem Katalogsensor samt der ROS-Topics, unter
+/// denen der Sensor in dieser Aufnahme publiziert. Wird per DB-Trigger aus
+/// `sensors` gepflegt.
+#[derive(Queryable, Selectable, Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[diesel(table_name = crate::schema::entry_sensors)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[serde(crate = "rocket::serde")]
+pub struct EntrySensor {
+    pub id: i64,
+    pub entry_id: EntryID,
+    pub catalog_sensor_id: CatalogSensorID,
+    pub sensor_id: SensorID,
+    pub ros_topics: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Gespeichertes Ergebnis der Sensor/Topic-Konsistenzprüfung eines Eintrags.
+///
+/// `issues` enthält die einzelnen Befunde als
+/// [`ConsistencyIssue`](crate::storage::consistency::ConsistencyIssue)-Liste.
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[diesel(table_name = crate::schema::entry_consistency)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[serde(crate = "rocket::serde")]
+pub struct EntryConsistency {
+    pub entry_id: EntryID,
+    pub checked_at: DateTime<Utc>,
+    pub missing_topics: i32,
+    pub unattributed_topics: i32,
+    pub frequency_mismatches: i32,
+    pub issues: serde_json::Value,
+}
+
+/// Kuratierte Metadaten eines Tags.
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[diesel(table_name = crate::schema::tag_definitions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[serde(crate = "rocket::serde")]
+pub struct TagDefinition {
+    pub name: String,
+    pub color: Option<String>,
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub version: i64,
+}
+
+/// Teiländerung der Metadaten eines Eintrags; fehlende Felder bleiben unverändert.
+#[derive(AsChangeset, Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[diesel(table_name = crate::schema::entries)]
+#[serde(crate = "rocket::serde", deny_unknown_fields)]
+pub struct EntryMetadataPatch {
+    pub time_machine: Option<f64>,
+    pub platform_name: Option<String>,
+    pub platform_image_link: Option<String>,
+    pub scenario_name: Option<String>,
+    pub scenario_creation_time: Option<DateTime<Utc>>,
+    pub scenario_description: Option<String>,
+    pub sequence_duration: Option<f64>,
+    pub sequence_distance: Option<f64>,
+    pub sequence_lat_starting_point_deg: Option<f64>,
+    pub sequence_lon_starting_point_deg: Option<f64>,
+    pub weather_cloudiness: Option<String>,
+    pub weather_precipitation: Option<String>,
+    pub weather_precipitation_deposits: Option<String>,
+    pub weather_wind_intensity: Option<String>,
+    pub weather_road_humidity: Option<String>,
+    pub weather_fog: Option<bool>,
+    pub weather_snow: Option<bool>,
+}
+
+impl EntryMetadataPatch {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Legt `later` über diesen Patch; gesetzte Felder von `later` gewinnen.
+    pub fn overlay(self, later: &EntryMetadataPatch) -> EntryMetadataPatch {
+        let l = later.clone();
+        EntryMetadataPatch {
+            time_machine: l.time_machine.or(self.time_machine),
+            platform_name: l.platform_name.or(self.platform_name),
+            platform_image_link: l.platform_image_link.or(self.platform_image_link),
+            scenario_name: l.scenario_name.or(self.scenario_name),
+            scenario_creation_time: l.scenario_creation_time.or(self.scenario_creation_time),
+            scenario_description: l.scenario_description.or(self.scenario_description),
+            sequence_duration: l.sequence_duration.or(self.sequence_duration),
+            sequence_distance: l.sequence_distance.or(self.sequence_distance),
+            sequence_lat_starting_point_deg: l
+                .sequence_lat_starting_point_deg
+                .or(self.sequence_lat_starting_point_deg),
+            sequence_lon_starting_point_deg: l
+                .sequence_lon_starting_point_deg
+                .or(self.sequence_lon_starting_point_deg),
+            weather_cloudiness: l.weather_cloudiness.or(self.weather_cloudiness),
+            weather_precipitation: l.weather_precipitation.or(self.weather_precipitation),
+            weather_precipitation_deposits: l
+                .weather_precipitation_deposits
+                .or(self.weather_precipitation_deposits),
+            weather_wind_intensity: l.weather_wind_intensity.or(self.weather_wind_intensity),
+            weather_road_humidity: l.weather_road_humidity.or(self.weather_road_humidity),
+            weather_fog: l.weather_fog.or(self.weather_fog),
+            weather_snow: l.weather_snow.or(self.weather_snow),
+        }
+    }
+}
+
+/// Benannte Sammlung von Einträgen bzw. Sequenzen, z.B. ein Trainingsdatensatz.
+#[derive(Queryable, Selectable, Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[diesel(table_name = crate::schema::collections)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[serde(crate = "rocket::serde")]
+pub struct Collection {
+    pub id: CollectionID,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub version: i64,
+}
+
+/// Element einer Sammlung; ohne `sequence_id` gehört der ganze Eintrag dazu.
+#[derive(Queryable, Selectable, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[diesel(table_name = crate::schema::collection_items)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[serde(crate = "rocket::serde")]
+pub struct CollectionItem {
+    pub id: i64,
+    pub collection_id: CollectionID,
+    pub entry_id: EntryID,
+    pub sequence_id: Option<SequenceID>,
+    pub added_at: DateTime<Utc>,
+}
+
+/// Eingefrorene Version einer Sammlung.
+#[derive(Queryable, Selectable, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[diesel(table_name = crate::schema::collection_snapshots)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[serde(crate = "rocket::serde")]
+pub struct CollectionSnapshot {
+    pub id: i64,
+    pub collection_id: CollectionID,
+    pub snapshot_version: i32,
+    pub note: Option<String>,
+    pub item_count: i32,
+    /// Summe der Dateigrößen in Bytes.
+    pub total_size: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Stand eines Sammlungselements: Datei, Größe und ggf. Zeitfenster der Sequenz.
+#[derive(Queryable, Selectable, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[diesel(table_name = crate::schema::collection_snapshot_items)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[serde(crate = "rocket::serde")]
+pub struct CollectionSnapshotItem {
+    pub entry_id: EntryID,
+    pub entry_name: String,
+    pub path: String,
+    pub size: i64,
+    pub sequence_id: Option<SequenceID>,
+    pub sequence_name: Option<String>,
+    pub start_timestamp: Option<Timestamp>,
+    pub end_timestamp: Option<Timestamp>,
+}
+
+/// Kurzfassung eines Eintrags, z.B. als Kontext zu Sequenz-Suchtreffern.
+#[derive(Queryable, Selectable, Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[diesel(table_name = crate::schema::entries)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[serde(crate = "rocket::serde")]
+pub struct EntrySummary {
+    pub id: EntryID,
+    pub name: String,
+    pub path: String,
+    pub status: String,
+    pub platform_name: Option<String>,
+    pub scenario_name: Option<String>,
+    pub tags: Vec<String>,
+}
+
+/// Treffer der katalogweiten Sequenzsuche: Sequenz samt zugehörigem Eintrag.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct SequenceSearchHit {
+    pub sequence: Sequence,
+    pub entry: EntrySummary,
+}
+
+/// Katalogsensor mit Anzahl der Einträge, in denen er verwendet wird.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct CatalogSensorWithUsage {
+    #[serde(flatten)]
+    pub sensor: CatalogSensor,
+    pub entry_count: i64,
+}
+
+/// Verwendung eines Katalogsensors in einem Eintrag.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct CatalogSensorUsage {
+    pub assignment: EntrySensor,
+    pub entry: EntrySummary,
+}
+
+/// Sensor eines Eintrags mit seinem Katalogeintrag.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct EntrySensorAssignment {
+    pub assignment: EntrySensor,
+    pub sensor: CatalogSensor,
+}
+
+/// Tag mit Verwendungszahlen und (falls vorhanden) seinen Metadaten.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct TagInfo {
+    pub name: String,
+    pub namespace: Option<String>,
+    pub color: Option<String>,
+    pub description: Option<String>,
+    pub entry_count: i64,
+    pub sequence_count: i64,
+    /// Version der Tag-Definition; `None`, solange keine angelegt wurde.
+    pub version: Option<i64>,
+}
+
+/// Anzahl der durch eine Tag-Operation geänderten Einträge und Sequenzen.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(crate = "rocket::serde")]
+pub struct TagChangeCount {
+    pub entries: usize,
+    pub sequences: usize,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(crate = "rocket::serde", rename_all = "snake_case")]
+pub enum BulkEntryStatus {
+    Updated,
+    Unchanged,
+    NotFound,
+    /// Plugin-Instanz für den Eintrag eingereiht.
+    Queued,
+    Failed,
+}
+
+/// Ergebnis einer Sammeloperation für einen einzelnen Eintrag.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct BulkEntryResult {
+    pub entry_id: EntryID,
+    pub status: BulkEntryStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance_id: Option<u64>,
+}
+
+impl BulkEntryResult {
+    pub fn new(entry_id: EntryID, status: BulkEntryStatus) -> Self {
+        BulkEntryResult {
+            entry_id,
+            status,
+            message: None,
+            instance_id: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct CollectionSummary {
+    #[serde(flatten)]
+    pub collection: Collection,
+    pub item_count: i64,
+    pub latest_snapshot_version: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct CollectionDetails {
+    #[serde(flatten)]
+    pub collection: Collection,
+    pub items: Vec<CollectionItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct CollectionSnapshotDetails {
+    pub collection_name: String,
+    #[serde(flatten)]
+    pub snapshot: CollectionSnapshot,
+    pub items: Vec<CollectionSnapshotItem>,
+}
+
+/// Lokales Benutzerkonto; `role` ist einer der Namen aus [`crate::storage::auth::Role`].
+#[derive(Queryable, Selectable, Debug, Clone, Serialize, PartialEq, Eq)]
+#[diesel(table_name = crate::schema::users)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[serde(crate = "rocket::serde")]
+pub struct User {
+    pub id: UserID,
+    pub username: String,
+    #[serde(skip_serializing)]
+    pub password_hash: String,
+    pub role: String,
+    pub disabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub version: i64,
+}
+
+/// Antwort auf eine Anmeldung. Das Token wird nur hier im Klartext ausgeliefert.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(crate = "rocket::serde")]
+pub struct Session {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+    pub user: User,
+}
+
+/// API-Schlüssel; `scopes` sind Namen aus [`crate::storage::auth::Scope`].
+#[derive(Queryable, Selectable, Debug, Clone, Serialize, PartialEq, Eq)]
+#[diesel(table_name = crate::schema::api_keys)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[serde(crate = "rocket::serde")]
+pub struct ApiKey {
+    pub id: ApiKeyID,
+    pub name: String,
+    #[serde(skip_serializing)]
+    pub key_hash: String,
+    pub key_prefix: String,
+    pub scopes: Vec<String>,
+    pub created_by: Option<UserID>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// Antwort auf das Anlegen eines Schlüssels; nur hier steht er im Klartext.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(crate = "rocket::serde")]
+pub struct NewApiKey {
+    pub key: String,
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+}
+
+/// Eintrag im Audit-Log; `outcome` ist einer der Werte aus
+/// [`crate::storage::audit::outcome`].
+#[derive(Queryable, Selectable, Debug, Clone, Serialize, PartialEq)]
+#[diesel(table_name = crate::schema::audit_log)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[serde(crate = "rocket::serde")]
+pub struct AuditRecord {
+    pub id: AuditID,
+    pub at: DateTime<Utc>,
+    pub actor: String,
+    pub method: String,
+    pub route: Option<String>,
+    pub path: String,
+    pub target_ids: serde_json::Value,
+    pub parameters: serde_json::Value,
+    pub status: i32,
+    pub outcome: String,
+}
+
+#[derive(Insertable, Debug, Clone, PartialEq)]
+#[diesel(table_name = crate::schema::audit_log)]
+pub struct NewAuditRecord {
+    pub actor: String,
+    pub method: String,
+    pub route: Option<String>,
+    pub path: String,
+    pub target_ids: serde_json::Value,
+    pub parameters: serde_json::Value,
+    pub status: i32,
+    pub outcome: String,
+}
+
+/// Ein Lauf einer Plugin-Instanz; `state` ist der Name eines
+/// [`crate::plugin_manager::manager::InstanceState`].
+#[derive(Queryable, Selectable, Debug, Clone, Serialize, PartialEq)]
+#[diesel(table_name = crate::schema::plugin_runs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[serde(crate = "rocket::serde")]
+pub struct PluginRun {
+    pub instance_id: i64,
+    pub plugin_name: String,
+    pub plugin_version: Option<String>,
+    pub trigger: String,
+    pub payload: serde_json::Value,
+    pub entry_path: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub state: String,
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+    pub trace: Option<String>,
+    /// Eingang in die Warteschlange; `started_at` ist der Start des Runners.
+    pub queued_at: Option<DateTime<Utc>>,
+    /// Nummer des Versuchs, ab 1.
+    pub attempt: i32,
+    /// Erster Versuch, falls dieser Lauf eine Wiederholung ist.
+    pub retry_of: Option<i64>,
+}
+
+#[derive(Insertable, Debug, Clone, PartialEq)]
+#[diesel(table_name = crate::schema::plugin_runs)]
+pub struct NewPluginRun {
+    pub instance_id: i64,
+    pub plugin_name: String,
+    pub plugin_version: Option<String>,
+    pub trigger: String,
+    pub payload: serde_json::Value,
+    pub entry_path: Option<String>,
+    pub state: String,
+    pub queued_at: Option<DateTime<Utc>>,
+    pub attempt: i32,
+    pub retry_of: Option<i64>,
+}
+
+/// Log-Zeile einer Plugin-Instanz; `seq` zählt je Instanz ab 0.
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, Serialize, PartialEq)]
+#[diesel(table_name = crate::schema::plugin_logs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[serde(crate = "rocket::serde")]
+pub struct PluginLogLine {
+    pub instance_id: i64,
+    pub seq: i32,
+    pub at: DateTime<Utc>,
+    pub level: String,
+    pub logger: String,
+    pub message: String,
+}
+
+/// Ergebnis eines Plugin-Laufs an einem Eintrag; `kind` ist eine der
+/// [`crate::storage::artifacts::ARTIFACT_KINDS`].
+#[derive(Queryable, Selectable, Debug, Clone, Serialize, PartialEq)]
+#[diesel(table_name = crate::schema::entry_artifacts)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[serde(crate = "rocket::serde")]
+pub struct EntryArtifact {
+    pub id: ArtifactID,
+    pub entry_id: EntryID,
+    pub kind: String,
+    pub content: serde_json::Value,
+    pub plugin_name: String,
+    pub plugin_version: Option<String>,
+    pub instance_id: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable, Debug, Clone, PartialEq)]
+#[diesel(table_name = crate::schema::entry_artifacts)]
+pub struct NewEntryArtifact {
+    pub entry_id: EntryID,
+    pub kind: String,
+    pub content: serde_json::Value,
+    pub plugin_name: String,
+    pub plugin_version: Option<String>,
+    pub instance_id: i64,
 }
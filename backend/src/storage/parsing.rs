@@ -14,9 +14,12 @@ use tokio::process::Command;
 use tracing::debug;
 use tracing::error;
 use tracing::instrument;
+use tracing::warn;
 
-use crate::plugin_manager::manager::{PluginManager, build_started_instance_core_with_data};
+use crate::plugin_manager::manager::PluginManager;
+use crate::plugin_manager::queue::JobPriority;
 use crate::plugin_manager::plugin::BackendEvent;
+use crate::plugin_manager::trigger_filter::TriggerSubject;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
@@ -38,6 +41,33 @@ pub struct McapInfo {
     pub duration_seconds: Option<f64>,
 }
 
+/// Wandelt Sekunden mit Nachkommastellen (z.B. `"1700000000.123456789"`) exakt
+/// in Nanosekunden um. Über `f64` gingen bei Epoch-Zeiten die letzten Stellen verloren.
+pub fn parse_seconds_ns(s: &str) -> Option<i64> {
+    let s = s.trim();
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    let (secs, frac) = s.split_once('.').unwrap_or((s, ""));
+    if secs.is_empty() && frac.is_empty() {
+        return None;
+    }
+    if !secs.chars().all(|c| c.is_ascii_digit()) || !frac.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let secs: i64 = if secs.is_empty() {
+        0
+    } else {
+        secs.parse().ok()?
+    };
+    // auf 9 Stellen kürzen bzw. auffüllen
+    let frac: String = frac.chars().chain(std::iter::repeat('0')).take(9).collect();
+    let nanos: i64 = frac.parse().ok()?;
+    let total = secs.checked_mul(1_000_000_000)?.checked_add(nanos)?;
+    Some(if negative { -total } else { total })
+}
+
 async fn get_mcap_info(path: &Path) -> Result<McapInfo, StorageError> {
     // Use the `mcap` CLI plaintext output: `mcap info <path>` and parse it.
     let mut cmd = Command::new("mcap");
@@ -87,9 +117,7 @@ async fn get_mcap_info(path: &Path) -> Result<McapInfo, StorageError> {
             if let Some(idx1) = line.find('(') {
                 if let Some(idx2) = line[idx1 + 1..].find(')') {
                     let inside = &line[idx1 + 1..idx1 + 1 + idx2];
-                    if let Ok(sec) = inside.trim().parse::<f64>() {
-                        start_time_ns = Some((sec * 1e9) as i64);
-                    }
+                    start_time_ns = parse_seconds_ns(inside);
                 }
             }
             continue;
@@ -98,9 +126,7 @@ async fn get_mcap_info(path: &Path) -> Result<McapInfo, StorageError> {
             if let Some(idx1) = line.find('(') {
                 if let Some(idx2) = line[idx1 + 1..].find(')') {
                     let inside = &line[idx1 + 1..idx1 + 1 + idx2];
-                    if let Ok(sec) = inside.trim().parse::<f64>() {
-                        end_time_ns = Some((sec * 1e9) as i64);
-                    }
+                    end_time_ns = parse_seconds_ns(inside);
                 }
             }
             continue;
@@ -407,6 +433,9 @@ pub async fn get_entry_from_mcap(path: &Path) -> Result<Entry, StorageError> {
         size,
         created_at: now,
         updated_at: now,
+        version: 1,
+        mcap_start_ns: mcap_info.start_time_ns,
+        mcap_end_ns: mcap_info.end_time_ns,
         status,
         time_machine,
         platform_name,
@@ -487,6 +516,8 @@ pub async fn insert_entry_into_db(
     // insert entry into DB and get new id (idempotent)
     let txid = storage_manager.start_transaction();
 
+    let mut created = false;
+
     // Check if entry with same path already exists
     if let Ok(Some(existing)) = storage_manager
         .get_entry_by_path(entry.path.clone(), txid)
@@ -519,12 +550,24 @@ pub async fn insert_entry_into_db(
             weather_snow: entry.weather_snow,
             topics: None,
         };
-        if let Err(e) = storage_manager.update_entry(entry.id, md, txid).await {
-            error!("Failed to update existing entry {}: {:?}", entry.id, e);
+        // Nur schreiben, wenn seit dem Lesen niemand (z.B. über die API) die
+        // Metadaten geändert hat; sonst gewinnt die manuelle Änderung.
+        match storage_manager
+            .update_entry(entry.id, md, Some(existing.version), txid)
+            .await
+        {
+            Ok(()) => {}
+            Err(StorageError::PreconditionFailed(msg)) => {
+                warn!(
+                    "Entry {} was modified concurrently, keeping its metadata: {}",
+                    entry.id, msg
+                );
+            }
+            Err(e) => error!("Failed to update existing entry {}: {:?}", entry.id, e),
         }
         // also ensure tags are present
         for tag in entry.tags.clone().into_iter() {
-            if let Err(e) = storage_manager.add_tag(entry.id, tag, txid).await {
+            if let Err(e) = storage_manager.add_tag(entry.id, tag, None, txid).await {
                 error!("Failed to add tag for entry {}: {:?}", entry.id, e);
             }
         }
@@ -533,6 +576,8 @@ pub async fn insert_entry_into_db(
         let entry_size = entry.size;
         let entry_updated_at = entry.updated_at;
         let entry_status = entry.status.clone();
+        let entry_mcap_start_ns = entry.mcap_start_ns;
+        let entry_mcap_end_ns = entry.mcap_end_ns;
         if let Ok(conn2) = pool.get().await {
             if let Err(e) = conn2
                 .interact(move |conn| {
@@ -544,13 +589,15 @@ pub async fn insert_entry_into_db(
                         crate::schema::entries::dsl::size.eq(entry_size),
                         crate::schema::entries::dsl::updated_at.eq(entry_updated_at),
                         crate::schema::entries::dsl::status.eq(entry_status),
+                        crate::schema::entries::dsl::mcap_start_ns.eq(entry_mcap_start_ns),
+                        crate::schema::entries::dsl::mcap_end_ns.eq(entry_mcap_end_ns),
                     ))
                     .execute(conn)
                 })
                 .await
             {
                 error!(
-                    "Failed to update size/updated_at/status for entry {}: {:?}",
+                    "Failed to update size/updated_at/status/time bounds for entry {}: {:?}",
                     entry_id, e
                 );
             }
@@ -568,88 +615,13 @@ pub async fn insert_entry_into_db(
         let new_id = storage_manager.add_entry(entry_clone, txid).await?;
         entry.id = new_id;
 
-        // NEW: fire OnEntryCreate trigger after successful insert
-        let event = BackendEvent::EntryCreated {
-            path: entry.path.clone(),
-        };
-
-        // Build payload for plugins that expect metadata on create
-        let plugin_data = serde_json::json!({
-            "metadata": {
-                "time_machine": entry.time_machine,
-                "platform_name": entry.platform_name,
-                "platform_image_link": entry.platform_image_link,
-                "scenario_name": entry.scenario_name,
-                "scenario_creation_time": entry.scenario_creation_time.map(|dt| dt.to_rfc3339()),
-                "scenario_description": entry.scenario_description,
-                "sequence_duration": entry.sequence_duration,
-                "sequence_distance": entry.sequence_distance,
-                "sequence_lat_starting_point_deg": entry.sequence_lat_starting_point_deg,
-                "sequence_lon_starting_point_deg": entry.sequence_lon_starting_point_deg,
-                "weather_cloudiness": entry.weather_cloudiness,
-                "weather_precipitation": entry.weather_precipitation,
-                "weather_precipitation_deposits": entry.weather_precipitation_deposits,
-                "weather_wind_intensity": entry.weather_wind_intensity,
-                "weather_road_humidity": entry.weather_road_humidity,
-                "weather_fog": entry.weather_fog,
-                "weather_snow": entry.weather_snow,
-                // topics/tags live elsewhere; keep payload minimal and stable
-            },
-            "mcap_path": entry.path,
-        })
-        .to_string();
-
-        // Phase 1: prepare (kurz unter Lock) + Namen für detached build holen
-        let plans: Vec<(usize, String, PathBuf, u64)> = {
-            let pm = plugin_manager.lock().await;
-
-            let raw_plans = pm.prepare_fire_event(&event).map_err(|e| {
-                StorageError::CustomError(format!("prepare_fire_event failed: {e:?}"))
-            })?;
-
-            raw_plans
-                .into_iter()
-                .map(|(plugin_index, plugin_path, instance_id)| {
-                    let plugin_name = pm
-                        .registered
-                        .get(plugin_index)
-                        .map(|p| p.name().clone())
-                        .unwrap_or_else(|| "unknown".to_string());
-                    (plugin_index, plugin_name, plugin_path, instance_id)
-                })
-                .collect()
-        };
-
-        // Phase 2: build (langsam, ohne globalen lock)
-        let mut built: Vec<(u64, crate::plugin_manager::manager::PluginHandle)> = Vec::new();
-        for (plugin_index, plugin_name, plugin_path, instance_id) in plans {
-            let handle = build_started_instance_core_with_data(
-                plugin_index,
-                plugin_name,
-                &plugin_path,
-                instance_id,
-                plugin_data.clone(),
-            )
-            .await
-            .map_err(|e| {
-                StorageError::CustomError(format!("build_started_instance failed: {e:?}"))
-            })?;
-
-            built.push((instance_id, handle));
-        }
-
-        // Phase 3: commit (kurz unter Lock)
-        {
-            let mut pm = plugin_manager.lock().await;
-            for (instance_id, handle) in built {
-                pm.commit_started_instance(instance_id, handle)
-                    .map_err(|e| StorageError::CustomError(format!("commit failed: {e:?}")))?;
-            }
-        }
+        // OnEntryCreate erst nach dem Einlesen der Topics auslösen, damit
+        // PLUGIN_TRIGGER_FILTER sie prüfen kann.
+        created = true;
 
         // add tags for new entry
         for tag in entry.tags.clone().into_iter() {
-            if let Err(e) = storage_manager.add_tag(entry.id, tag, txid).await {
+            if let Err(e) = storage_manager.add_tag(entry.id, tag, None, txid).await {
                 error!("Failed to add tag for entry {}: {:?}", entry.id, e);
             }
         }
@@ -721,6 +693,8 @@ pub async fn insert_entry_into_db(
     }
     // insert sequences from YAML: main sequence (if duration present) and subsequences
     if let Some(y) = yaml.as_ref() {
+        // Subsequenzen ohne eigenen Start beginnen mit der Hauptsequenz bzw. der Aufnahme.
+        let mut parent_start_ts = mcap_info.start_time_ns;
         // main sequence: if there is duration or description
         if let Some(seq_node) = y.get("definitions").and_then(|d| d.get("sequence")) {
             let desc = seq_node
@@ -732,14 +706,17 @@ pub async fn insert_entry_into_db(
                 .get("duration")
                 .and_then(|v| v.as_f64())
                 .unwrap_or(0.0);
+            // Zeitstempel sind MCAP-Log-Zeit in Nanosekunden; `duration` ist in Sekunden.
             let start_ts = seq_node
                 .get("start_time_machine")
                 .and_then(|v| v.as_i64())
+                .or(mcap_info.start_time_ns)
                 .unwrap_or(0);
+            parent_start_ts = Some(start_ts);
             let end_ts = if duration > 0.0 {
-                start_ts + (duration as i64)
+                start_ts + (duration * 1e9).round() as i64
             } else {
-                start_ts
+                mcap_info.end_time_ns.unwrap_or(start_ts)
             };
             let now = Utc::now();
             let name = seq_node
@@ -758,6 +735,7 @@ pub async fn insert_entry_into_db(
                 created_at: now,
                 updated_at: now,
                 tags: Vec::new(),
+                version: 1,
             };
             // upsert main sequence: match by description + timestamps
             let existing_seqs = storage_manager.get_sequences(entry.id, txid).await.ok();
@@ -771,7 +749,7 @@ pub async fn insert_entry_into_db(
                         let mut seq_to_update = sequence.clone();
                         seq_to_update.id = *id;
                         if let Err(e) = storage_manager
-                            .update_sequence(entry.id, *id, seq_to_update, txid)
+                            .update_sequence(entry.id, *id, seq_to_update, Some(es.version), txid)
                             .await
                         {
                             error!("Failed to update sequence for entry {}: {:?}", entry.id, e);
@@ -800,8 +778,17 @@ pub async fn insert_entry_into_db(
                     let start_ts = sub
                         .get("start_time_machine")
                         .and_then(|v| v.as_i64())
+                        .or(parent_start_ts)
                         .unwrap_or(0);
-                    let end_ts = sub.get("end_time").and_then(|v| v.as_i64()).unwrap_or(0);
+                    let end_ts = sub
+                        .get("end_time")
+                        .and_then(|v| v.as_i64())
+                        .or_else(|| {
+                            sub.get("duration")
+                                .and_then(|v| v.as_f64())
+                                .map(|d| start_ts + (d * 1e9).round() as i64)
+                        })
+                        .unwrap_or(start_ts);
                     let now = Utc::now();
                     let name = sub
                         .get("name")
@@ -819,6 +806,7 @@ pub async fn insert_entry_into_db(
                         created_at: now,
                         updated_at: now,
                         tags: Vec::new(),
+                        version: 1,
                     };
                     // subsequence upsert: match by description + timestamps
                     let existing_seqs = storage_manager.get_sequences(entry.id, txid).await.ok();
@@ -832,7 +820,13 @@ pub async fn insert_entry_into_db(
                                 let mut seq_to_update = sequence.clone();
                                 seq_to_update.id = *id;
                                 if let Err(e) = storage_manager
-                                    .update_sequence(entry.id, *id, seq_to_update, txid)
+                                    .update_sequence(
+                                        entry.id,
+                                        *id,
+                                        seq_to_update,
+                                        Some(es.version),
+                                        txid,
+                                    )
                                     .await
                                 {
                                     error!(
@@ -906,6 +900,7 @@ pub async fn insert_entry_into_db(
                         sensor_type,
                         ros_topics,
                         custom_parameters,
+                        version: 1,
                     };
                     // upsert sensor by name
                     let existing_sensors = storage_manager.get_sensors(entry.id, txid).await.ok();
@@ -915,8 +910,9 @@ pub async fn insert_entry_into_db(
                             if es.sensor_name == sensor.sensor_name {
                                 let mut s_to_update = sensor.clone();
                                 s_to_update.id = *id;
-                                if let Err(e) =
-                                    storage_manager.update_sensor(s_to_update, txid).await
+                                if let Err(e) = storage_manager
+                                    .update_sensor(s_to_update, Some(es.version), txid)
+                                    .await
                                 {
                                     error!(
                                         "Failed to update sensor for entry {}: {:?}",
@@ -938,5 +934,75 @@ pub async fn insert_entry_into_db(
         }
     }
 
+    // Sensor-Topics gegen die aufgezeichneten Topics abgleichen
+    if let Err(e) = storage_manager
+        .check_entry_consistency(entry.id, txid)
+        .await
+    {
+        error!(
+            "Failed to check sensor/topic consistency for entry {}: {:?}",
+            entry.id, e
+        );
+    }
+
+    // fire OnEntryCreate trigger after successful insert
+    if created {
+        let event = BackendEvent::EntryCreated {
+            path: entry.path.clone(),
+        };
+        let subject = TriggerSubject::from_entry(
+            &entry,
+            topics_list
+                .iter()
+                .map(|t| (t.topic.clone(), t.r#type.clone()))
+                .collect(),
+        );
+
+        // Build payload for plugins that expect metadata on create
+        let plugin_data = serde_json::json!({
+            "metadata": {
+                "time_machine": entry.time_machine,
+                "platform_name": entry.platform_name,
+                "platform_image_link": entry.platform_image_link,
+                "scenario_name": entry.scenario_name,
+                "scenario_creation_time": entry.scenario_creation_time.map(|dt| dt.to_rfc3339()),
+                "scenario_description": entry.scenario_description,
+                "sequence_duration": entry.sequence_duration,
+                "sequence_distance": entry.sequence_distance,
+                "sequence_lat_starting_point_deg": entry.sequence_lat_starting_point_deg,
+                "sequence_lon_starting_point_deg": entry.sequence_lon_starting_point_deg,
+                "weather_cloudiness": entry.weather_cloudiness,
+                "weather_precipitation": entry.weather_precipitation,
+                "weather_precipitation_deposits": entry.weather_precipitation_deposits,
+                "weather_wind_intensity": entry.weather_wind_intensity,
+                "weather_road_humidity": entry.weather_road_humidity,
+                "weather_fog": entry.weather_fog,
+                "weather_snow": entry.weather_snow,
+                // topics/tags live elsewhere; keep payload minimal and stable
+            },
+            "entry_id": entry.id,
+            "mcap_path": entry.path,
+        })
+        .to_string();
+
+        // Passende Plugins bestimmen und einreihen (kurz unter Lock);
+        // gestartet wird im Dispatcher der Warteschlange.
+        let pm = plugin_manager.lock().await;
+
+        let plans = pm.prepare_fire_event_for(&event, &subject).map_err(|e| {
+            StorageError::CustomError(format!("prepare_fire_event failed: {e:?}"))
+        })?;
+
+        for (plugin_index, _plugin_path, instance_id) in plans {
+            pm.enqueue(
+                plugin_index,
+                instance_id,
+                plugin_data.clone(),
+                JobPriority::Triggered,
+            )
+            .map_err(|e| StorageError::CustomError(format!("enqueue failed: {e:?}")))?;
+        }
+    }
+
     Ok(entry)
 }
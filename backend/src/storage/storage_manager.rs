@@ -2,14 +2,24 @@ use crate::routes;
 use itertools::Itertools;
 use std::{
     collections::HashSet,
+    hash::{Hash, Hasher},
     path::PathBuf,
     sync::{
         Arc, Mutex,
         atomic::{AtomicU64, Ordering},
     },
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 // use crate::schema::metadata::dsl::{entry_id as metadata_entry_id, metadata};
+use crate::events::{CatalogEvent, EventBus};
+use crate::plugin_manager::logs;
+use crate::plugin_manager::manager::InstanceState;
+use crate::plugin_manager::runs::{self, RunOutcome};
+use crate::storage::artifacts::{self, ArtifactProvenance, PluginArtifact};
+use crate::storage::audit::{self, AuditFilter};
+use crate::storage::auth::{self, Role, Scope};
 use crate::storage::models::*;
+use crate::storage::{clip, collections, consistency, manifest, metadata_import, tags, timeline};
 use crate::{error::StorageError, schema};
 
 use chrono::{DateTime, NaiveDate, TimeZone, Utc};
@@ -24,6 +34,26 @@ pub type Map<K, V> = std::collections::HashMap<K, V>;
 pub type TxID = u64;
 pub type Tag = String;
 pub type TopicID = i64;
+/// Zusätzliche, exakte Filter für [`StorageManager::get_entries_filtered`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EntryFilter {
+    /// Ergebnis der Konsistenzprüfung: `ok`, `issues`, `missing_topics`,
+    /// `unattributed_topics` oder `frequency_mismatches`.
+    pub consistency: Option<String>,
+}
+
+/// Zeilen je INSERT beim Einfrieren einer Sammlung; Postgres erlaubt höchstens
+/// 65535 Bind-Parameter je Anweisung, ein Snapshot-Eintrag belegt neun davon.
+const SNAPSHOT_ITEMS_PER_INSERT: usize = 1000;
+
+/// Änderung, die [`StorageManager::bulk_update_entries`] auf jeden Eintrag anwendet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BulkEntryChange {
+    AddTags(Vec<Tag>),
+    RemoveTags(Vec<Tag>),
+    PatchMetadata(Box<EntryMetadataPatch>),
+}
+
 fn contains_part(value: &str, part: &str) -> bool {
     value.to_lowercase().contains(part)
 }
@@ -69,9 +99,13 @@ fn entry_matches_date(entry: &Entry, date_time: &DateTime<Utc>) -> bool {
 pub struct StorageManager {
     db_connection_pool: Pool,
     watch_dir: PathBuf,
+    /// Zielverzeichnis für Exporte (z.B. MCAP-Clips), per `EXPORT_DIR` konfigurierbar.
+    export_dir: PathBuf,
     tx_counter: Arc<AtomicU64>,
     /// Set of transaction IDs that have been started but not yet ended.
     active_transactions: Arc<Mutex<HashSet<TxID>>>,
+    /// Änderungsereignisse für `GET /events`.
+    events: EventBus,
 }
 
 impl StorageManager {
@@ -88,8 +122,11 @@ impl StorageManager {
             db_connection_pool: pool,
             // this only refers to the directory inside the docker container
             watch_dir: PathBuf::from("/data"),
+            // bewusst außerhalb von /data, sonst würde der Scanner Exporte indizieren
+            export_dir: PathBuf::from("/exports"),
             tx_counter: Arc::new(AtomicU64::new(0)),
             active_transactions: Arc::new(Mutex::new(HashSet::new())),
+            events: EventBus::new(),
         })
     }
 
@@ -97,6 +134,35 @@ impl StorageManager {
         &self.watch_dir
     }
 
+    pub fn with_export_dir(mut self, export_dir: impl Into<PathBuf>) -> Self {
+        self.export_dir = export_dir.into();
+        self
+    }
+
+    pub fn export_dir(&self) -> &PathBuf {
+        &self.export_dir
+    }
+
+    /// Verzeichnis, in dem Datei-Artefakte eines Laufs dauerhaft liegen, wenn
+    /// das Plugin sie in seinem Arbeitsverzeichnis erzeugt hat.
+    pub fn artifact_dir(&self, instance_id: i64) -> PathBuf {
+        self.export_dir
+            .join(artifacts::ARTIFACT_DIR)
+            .join(instance_id.to_string())
+    }
+
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    /// Meldet jeden geänderten Eintrag einmal als [`CatalogEvent::MetadataUpdated`].
+    fn publish_metadata_updated(&self, entry_ids: impl IntoIterator<Item = EntryID>) {
+        for entry_id in entry_ids.into_iter().unique() {
+            self.events
+                .publish(CatalogEvent::MetadataUpdated { entry_id });
+        }
+    }
+
     #[instrument]
     pub fn db_connection_pool(&self) -> &Pool {
         &self.db_connection_pool
@@ -112,55 +178,62 @@ impl StorageManager {
         Ok(entry)
     }
 
+    /// Überschreibt die Metadaten eines Eintrags.
+    ///
+    /// Ist `expected_version` gesetzt, wird nur geschrieben, wenn die gespeicherte
+    /// Version noch übereinstimmt (optimistische Nebenläufigkeitskontrolle); sonst
+    /// liefert die Methode `StorageError::PreconditionFailed`.
     #[instrument]
     pub async fn update_entry(
         &self,
         entry_id_: EntryID,
         entry_metadata: routes::database::MetadataWeb,
+        expected_version: Option<i64>,
         txid: TxID,
     ) -> Result<(), StorageError> {
         let conn = self.db_connection_pool().get().await?;
-        conn.interact(move |conn| {
-            diesel::update(
-                schema::entries::dsl::entries.filter(schema::entries::dsl::id.eq(entry_id_)),
-            )
-            .set((
-                schema::entries::dsl::time_machine.eq(entry_metadata.time_machine),
-                schema::entries::dsl::platform_name.eq(entry_metadata.platform_name.clone()),
-                schema::entries::dsl::platform_image_link
-                    .eq(entry_metadata.platform_image_link.clone()),
-                schema::entries::dsl::scenario_name.eq(entry_metadata.scenario_name.clone()),
-                schema::entries::dsl::scenario_creation_time
-                    .eq(entry_metadata.scenario_creation_time),
-                schema::entries::dsl::scenario_description
-                    .eq(entry_metadata.scenario_description.clone()),
-                schema::entries::dsl::sequence_duration.eq(entry_metadata.sequence_duration),
-                schema::entries::dsl::sequence_distance.eq(entry_metadata.sequence_distance),
-                schema::entries::dsl::sequence_lat_starting_point_deg
-                    .eq(entry_metadata.sequence_lat_starting_point_deg),
-                schema::entries::dsl::sequence_lon_starting_point_deg
-                    .eq(entry_metadata.sequence_lon_starting_point_deg),
-                schema::entries::dsl::weather_cloudiness
-                    .eq(entry_metadata.weather_cloudiness.clone()),
-                schema::entries::dsl::weather_precipitation
-                    .eq(entry_metadata.weather_precipitation.clone()),
-                schema::entries::dsl::weather_precipitation_deposits
-                    .eq(entry_metadata.weather_precipitation_deposits.clone()),
-                schema::entries::dsl::weather_wind_intensity
-                    .eq(entry_metadata.weather_wind_intensity.clone()),
-                schema::entries::dsl::weather_road_humidity
-                    .eq(entry_metadata.weather_road_humidity.clone()),
-                schema::entries::dsl::weather_fog.eq(entry_metadata.weather_fog),
-                schema::entries::dsl::weather_snow.eq(entry_metadata.weather_snow),
-            ))
-            .execute(conn)
-        })
-        .await??;
+        let changed = conn
+            .interact(move |conn| {
+                update_entry_metadata(conn, entry_id_, &entry_metadata, expected_version)
+            })
+            .await??;
 
         // debug!("Updated entry {}", entry_id_);
+        if changed {
+            self.events.publish(CatalogEvent::MetadataUpdated {
+                entry_id: entry_id_,
+            });
+        }
         Ok(())
     }
 
+    /// Wertet einen bedingten Schreibzugriff (`If-Match`) aus.
+    ///
+    /// Hat der Schreibzugriff keine Zeile getroffen, wird über `current_version`
+    /// unterschieden, ob die Zeile fehlt (`NotFound`), ihre Version veraltet ist
+    /// (`PreconditionFailed`) oder der Zugriff schlicht nichts zu ändern hatte.
+    async fn check_versioned_write<F>(
+        &self,
+        rows: usize,
+        expected_version: Option<i64>,
+        what: &'static str,
+        id: i64,
+        current_version: F,
+    ) -> Result<(), StorageError>
+    where
+        F: FnOnce(&mut PgConnection) -> QueryResult<Option<i64>> + Send + 'static,
+    {
+        let Some(expected) = expected_version else {
+            return Ok(());
+        };
+        if rows > 0 {
+            return Ok(());
+        }
+        let conn = self.db_connection_pool().get().await?;
+        let current = conn.interact(current_version).await??;
+        check_version(what, id, expected, current)
+    }
+
     /// Returns true if this entry matches the search: every word in `search_parts` must appear
     /// in at least one of the entry's string fields, or (if the word is a valid date) match
     /// one of the entry's date fields (created_at, updated_at, scenario_creation_time).
@@ -208,12 +281,73 @@ impl StorageManager {
         page_size: Option<u32>,
         txid: TxID,
     ) -> Result<(Vec<Entry>, u32), StorageError> {
+        self.get_entries_filtered(
+            search_string,
+            EntryFilter::default(),
+            sort_by,
+            ascending,
+            page,
+            page_size,
+            txid,
+        )
+        .await
+    }
+
+    /// Wie [`get_entries`](Self::get_entries), zusätzlich eingeschränkt durch `filter`.
+    #[allow(clippy::too_many_arguments)]
+    #[instrument]
+    pub async fn get_entries_filtered(
+        &self,
+        search_string: Option<String>,
+        filter: EntryFilter,
+        sort_by: Option<String>,
+        ascending: Option<bool>,
+        page: Option<u32>,
+        page_size: Option<u32>,
+        txid: TxID,
+    ) -> Result<(Vec<Entry>, u32), StorageError> {
+        let consistency = filter.consistency.clone();
+        if let Some(c) = consistency.as_deref()
+            && !matches!(
+                c,
+                "ok" | "issues" | "missing_topics" | "unattributed_topics" | "frequency_mismatches"
+            )
+        {
+            return Err(StorageError::ValidationError(format!(
+                "unknown consistency filter '{c}'"
+            )));
+        }
         let conn = self.db_connection_pool().get().await?;
         let entries = conn
             .interact(move |conn| {
-                schema::entries::dsl::entries
-                    .select(Entry::as_select())
-                    .load::<Entry>(conn)
+                use schema::entries::dsl as entries_dsl;
+                use schema::entry_consistency::dsl as ec_dsl;
+
+                let mut query = entries_dsl::entries.into_boxed();
+                if let Some(c) = consistency.as_deref() {
+                    let checked = ec_dsl::entry_consistency.into_boxed();
+                    let checked = match c {
+                        "ok" => checked.filter(
+                            ec_dsl::missing_topics
+                                .eq(0)
+                                .and(ec_dsl::unattributed_topics.eq(0))
+                                .and(ec_dsl::frequency_mismatches.eq(0)),
+                        ),
+                        "missing_topics" => checked.filter(ec_dsl::missing_topics.gt(0)),
+                        "unattributed_topics" => checked.filter(ec_dsl::unattributed_topics.gt(0)),
+                        "frequency_mismatches" => {
+                            checked.filter(ec_dsl::frequency_mismatches.gt(0))
+                        }
+                        _ => checked.filter(
+                            ec_dsl::missing_topics
+                                .gt(0)
+                                .or(ec_dsl::unattributed_topics.gt(0))
+                                .or(ec_dsl::frequency_mismatches.gt(0)),
+                        ),
+                    };
+                    query = query.filter(entries_dsl::id.eq_any(checked.select(ec_dsl::entry_id)));
+                }
+                query.select(Entry::as_select()).load::<Entry>(conn)
             })
             .await??;
         // debug!("Queried all entries, count: {}", entries.len());
@@ -294,6 +428,112 @@ impl StorageManager {
         Ok((paged, num_pages))
     }
 
+    /// Katalogweite Sequenzsuche.
+    ///
+    /// Jedes Wort in `search_string` muss in Name, Beschreibung oder Tags der Sequenz
+    /// oder im Namen des Eintrags vorkommen. `tag` filtert exakt, `min_duration` ist
+    /// in Sekunden. Filter, Sortierung (`Start`, `Name`, `Duration`, `Entry`) und
+    /// Paging laufen in SQL.
+    #[allow(clippy::too_many_arguments)]
+    #[instrument]
+    pub async fn search_sequences(
+        &self,
+        search_string: Option<String>,
+        tag: Option<String>,
+        min_duration: Option<f64>,
+        sort_by: Option<String>,
+        ascending: Option<bool>,
+        page: Option<u32>,
+        page_size: Option<u32>,
+        txid: TxID,
+    ) -> Result<(Vec<SequenceSearchHit>, u32), StorageError> {
+        let search_parts: Vec<String> = search_string
+            .as_deref()
+            .unwrap_or("")
+            .split_whitespace()
+            .map(|p| format!("%{}%", escape_like(p)))
+            .collect();
+        let min_duration_ns = min_duration.map(|d| (d * 1e9).round() as i64);
+        let ascending = ascending.unwrap_or(true);
+
+        let conn = self.db_connection_pool().get().await?;
+        let (hits, total) = conn
+            .interact(move |conn| {
+                use diesel::dsl::sql;
+                use diesel::sql_types::{Bool, Text};
+                use schema::entries::dsl as entries_dsl;
+                use schema::sequences::dsl as sequences_dsl;
+
+                let filtered = || {
+                    let mut query = sequences_dsl::sequences
+                        .inner_join(entries_dsl::entries)
+                        .into_boxed();
+                    for part in search_parts.iter() {
+                        query = query.filter(
+                            sequences_dsl::name
+                                .ilike(part.clone())
+                                .or(sequences_dsl::description.ilike(part.clone()))
+                                .or(entries_dsl::name.ilike(part.clone()))
+                                .or(sql::<Bool>("array_to_string(sequences.tags, ' ') ILIKE ")
+                                    .bind::<Text, _>(part.clone())),
+                        );
+                    }
+                    if let Some(t) = tag.as_ref() {
+                        query = query.filter(sequences_dsl::tags.contains(vec![t.clone()]));
+                    }
+                    if let Some(ns) = min_duration_ns {
+                        query = query.filter(
+                            (sequences_dsl::end_timestamp - sequences_dsl::start_timestamp).ge(ns),
+                        );
+                    }
+                    query
+                };
+
+                let total: i64 = filtered().count().get_result(conn)?;
+
+                let mut query =
+                    filtered().select((Sequence::as_select(), EntrySummary::as_select()));
+                query = match (sort_by.as_deref(), ascending) {
+                    (Some("Name"), true) => query.order_by(sequences_dsl::name.asc()),
+                    (Some("Name"), false) => query.order_by(sequences_dsl::name.desc()),
+                    (Some("Duration"), true) => query.order_by(
+                        (sequences_dsl::end_timestamp - sequences_dsl::start_timestamp).asc(),
+                    ),
+                    (Some("Duration"), false) => query.order_by(
+                        (sequences_dsl::end_timestamp - sequences_dsl::start_timestamp).desc(),
+                    ),
+                    (Some("Entry"), true) => query
+                        .order_by(entries_dsl::name.asc())
+                        .then_order_by(sequences_dsl::start_timestamp.asc()),
+                    (Some("Entry"), false) => query
+                        .order_by(entries_dsl::name.desc())
+                        .then_order_by(sequences_dsl::start_timestamp.desc()),
+                    (_, true) => query.order_by(sequences_dsl::start_timestamp.asc()),
+                    (_, false) => query.order_by(sequences_dsl::start_timestamp.desc()),
+                };
+                // stabile Reihenfolge über Seiten hinweg
+                query = query.then_order_by(sequences_dsl::id.asc());
+                if let (Some(p), Some(ps)) = (page, page_size.filter(|&ps| ps > 0)) {
+                    query = query
+                        .offset(i64::from(p).saturating_mul(i64::from(ps)))
+                        .limit(i64::from(ps));
+                }
+                let rows = query.load::<(Sequence, EntrySummary)>(conn)?;
+                Ok::<_, diesel::result::Error>((rows, total))
+            })
+            .await??;
+
+        let num_pages = page_size
+            .filter(|&ps| ps > 0)
+            .map(|ps| (total as f64 / ps as f64).ceil() as u32)
+            .unwrap_or(1);
+        let hits = hits
+            .into_iter()
+            .map(|(sequence, entry)| SequenceSearchHit { sequence, entry })
+            .collect();
+        Ok((hits, num_pages))
+    }
+
     #[instrument]
     pub async fn get_entry(
         &self,
@@ -357,6 +597,28 @@ impl StorageManager {
         Ok(sequences_map)
     }
 
+    /// Einzelne Sequenz eines Eintrags, `None` falls es sie nicht gibt.
+    #[instrument]
+    pub async fn get_sequence(
+        &self,
+        entry_id_: EntryID,
+        sequence_id: SequenceID,
+        txid: TxID,
+    ) -> Result<Option<Sequence>, StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        let sequence = conn
+            .interact(move |conn| {
+                schema::sequences::dsl::sequences
+                    .filter(schema::sequences::dsl::id.eq(sequence_id))
+                    .filter(schema::sequences::dsl::entry_id.eq(entry_id_))
+                    .select(Sequence::as_select())
+                    .first::<Sequence>(conn)
+                    .optional()
+            })
+            .await??;
+        Ok(sequence)
+    }
+
     #[instrument]
     pub async fn get_sensors(
         &self,
@@ -380,6 +642,28 @@ impl StorageManager {
         Ok(sensors_map)
     }
 
+    /// Einzelner Sensor eines Eintrags, `None` falls es ihn nicht gibt.
+    #[instrument]
+    pub async fn get_sensor(
+        &self,
+        entry_id_: EntryID,
+        sensor_id: SensorID,
+        txid: TxID,
+    ) -> Result<Option<Sensor>, StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        let sensor = conn
+            .interact(move |conn| {
+                schema::sensors::dsl::sensors
+                    .filter(schema::sensors::dsl::id.eq(sensor_id))
+                    .filter(schema::sensors::dsl::entry_id.eq(entry_id_))
+                    .select(Sensor::as_select())
+                    .first::<Sensor>(conn)
+                    .optional()
+            })
+            .await??;
+        Ok(sensor)
+    }
+
     #[instrument]
     pub async fn get_all_sensors(&self, txid: TxID) -> Result<Map<SensorID, Sensor>, StorageError> {
         let conn = self.db_connection_pool().get().await?;
@@ -415,6 +699,95 @@ impl StorageManager {
         Ok(topics_map)
     }
 
+    /// Prüft die Sensor-Topics eines Eintrags gegen die aufgezeichneten Topics und
+    /// speichert das Ergebnis (ersetzt eine frühere Prüfung).
+    #[instrument]
+    pub async fn check_entry_consistency(
+        &self,
+        entry_id_: EntryID,
+        txid: TxID,
+    ) -> Result<EntryConsistency, StorageError> {
+        if self.get_entry(entry_id_, txid).await?.is_none() {
+            return Err(StorageError::NotFound(format!(
+                "entry {entry_id_} not found"
+            )));
+        }
+        let sensors: Vec<Sensor> = self
+            .get_sensors(entry_id_, txid)
+            .await?
+            .into_values()
+            .collect();
+        let topics: Vec<Topic> = self
+            .get_topics(entry_id_, txid)
+            .await?
+            .into_values()
+            .collect();
+        let issues = consistency::check_consistency(&sensors, &topics);
+        let count = |f: fn(&consistency::ConsistencyIssue) -> bool| {
+            issues.iter().filter(|i| f(i)).count() as i32
+        };
+        let report = EntryConsistency {
+            entry_id: entry_id_,
+            checked_at: Utc::now(),
+            missing_topics: count(|i| {
+                matches!(i, consistency::ConsistencyIssue::MissingTopic { .. })
+            }),
+            unattributed_topics: count(|i| {
+                matches!(i, consistency::ConsistencyIssue::UnattributedTopic { .. })
+            }),
+            frequency_mismatches: count(|i| {
+                matches!(i, consistency::ConsistencyIssue::FrequencyMismatch { .. })
+            }),
+            issues: serde_json::to_value(&issues)
+                .map_err(|e| StorageError::CustomError(e.to_string()))?,
+        };
+
+        let conn = self.db_connection_pool().get().await?;
+        let row = report.clone();
+        conn.interact(move |conn| {
+            use schema::entry_consistency::dsl as ec_dsl;
+            diesel::insert_into(ec_dsl::entry_consistency)
+                .values(&row)
+                .on_conflict(ec_dsl::entry_id)
+                .do_update()
+                .set((
+                    ec_dsl::checked_at.eq(row.checked_at),
+                    ec_dsl::missing_topics.eq(row.missing_topics),
+                    ec_dsl::unattributed_topics.eq(row.unattributed_topics),
+                    ec_dsl::frequency_mismatches.eq(row.frequency_mismatches),
+                    ec_dsl::issues.eq(&row.issues),
+                ))
+                .execute(conn)
+        })
+        .await??;
+        debug!(
+            "Consistency check for entry_id {}: {} issue(s)",
+            entry_id_,
+            issues.len()
+        );
+        Ok(report)
+    }
+
+    /// Zuletzt gespeicherte Konsistenzprüfung eines Eintrags.
+    #[instrument]
+    pub async fn get_entry_consistency(
+        &self,
+        entry_id_: EntryID,
+        txid: TxID,
+    ) -> Result<Option<EntryConsistency>, StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        let report = conn
+            .interact(move |conn| {
+                schema::entry_consistency::dsl::entry_consistency
+                    .find(entry_id_)
+                    .select(EntryConsistency::as_select())
+                    .first::<EntryConsistency>(conn)
+                    .optional()
+            })
+            .await??;
+        Ok(report)
+    }
+
     #[instrument]
     pub async fn add_topic(
         &self,
@@ -525,6 +898,8 @@ impl StorageManager {
                         entries_dsl::size.eq(e.size),
                         entries_dsl::created_at.eq(e.created_at),
                         entries_dsl::updated_at.eq(e.updated_at),
+                        entries_dsl::mcap_start_ns.eq(e.mcap_start_ns),
+                        entries_dsl::mcap_end_ns.eq(e.mcap_end_ns),
                         entries_dsl::time_machine.eq(e.time_machine),
                         entries_dsl::platform_name.eq(e.platform_name),
                         entries_dsl::platform_image_link.eq(e.platform_image_link),
@@ -558,39 +933,358 @@ impl StorageManager {
     }
 
     #[instrument]
-    pub async fn update_sensor(&self, sensor: Sensor, txid: TxID) -> Result<(), StorageError> {
+    pub async fn update_sensor(
+        &self,
+        sensor: Sensor,
+        expected_version: Option<i64>,
+        txid: TxID,
+    ) -> Result<(), StorageError> {
         let sensor_id = sensor.id;
         let conn = self.db_connection_pool().get().await?;
-        conn.interact(move |conn| {
-            diesel::update(
-                schema::sensors::dsl::sensors.filter(schema::sensors::dsl::id.eq(sensor_id)),
-            )
-            .set((
-                schema::sensors::dsl::sensor_name.eq(sensor.sensor_name),
-                schema::sensors::dsl::manufacturer.eq(sensor.manufacturer),
-                schema::sensors::dsl::sensor_type.eq(sensor.sensor_type),
-                schema::sensors::dsl::ros_topics.eq(sensor.ros_topics),
-                schema::sensors::dsl::custom_parameters.eq(sensor.custom_parameters),
-            ))
-            .execute(conn)
-        })
-        .await??;
+        let rows = conn
+            .interact(move |conn| {
+                let changes = (
+                    schema::sensors::dsl::sensor_name.eq(sensor.sensor_name),
+                    schema::sensors::dsl::manufacturer.eq(sensor.manufacturer),
+                    schema::sensors::dsl::sensor_type.eq(sensor.sensor_type),
+                    schema::sensors::dsl::ros_topics.eq(sensor.ros_topics),
+                    schema::sensors::dsl::custom_parameters.eq(sensor.custom_parameters),
+                );
+                match expected_version {
+                    Some(v) => diesel::update(
+                        schema::sensors::dsl::sensors
+                            .filter(schema::sensors::dsl::id.eq(sensor_id))
+                            .filter(schema::sensors::dsl::version.eq(v)),
+                    )
+                    .set(changes)
+                    .execute(conn),
+                    None => diesel::update(
+                        schema::sensors::dsl::sensors
+                            .filter(schema::sensors::dsl::id.eq(sensor_id)),
+                    )
+                    .set(changes)
+                    .execute(conn),
+                }
+            })
+            .await??;
         // debug!("Updated sensor {}", sensor_id);
-        Ok(())
+        self.check_versioned_write(rows, expected_version, "sensor", sensor_id, move |conn| {
+            sensor_version(conn, sensor_id)
+        })
+        .await
     }
 
     #[instrument]
-    pub async fn remove_sensor(&self, sensor_id: SensorID, txid: TxID) -> Result<(), StorageError> {
+    pub async fn remove_sensor(
+        &self,
+        sensor_id: SensorID,
+        expected_version: Option<i64>,
+        txid: TxID,
+    ) -> Result<(), StorageError> {
         let conn = self.db_connection_pool().get().await?;
-        conn.interact(move |conn| {
-            diesel::delete(
-                schema::sensors::dsl::sensors.filter(schema::sensors::dsl::id.eq(sensor_id)),
-            )
-            .execute(conn)
-        })
-        .await??;
+        let rows = conn
+            .interact(move |conn| match expected_version {
+                Some(v) => diesel::delete(
+                    schema::sensors::dsl::sensors
+                        .filter(schema::sensors::dsl::id.eq(sensor_id))
+                        .filter(schema::sensors::dsl::version.eq(v)),
+                )
+                .execute(conn),
+                None => diesel::delete(
+                    schema::sensors::dsl::sensors.filter(schema::sensors::dsl::id.eq(sensor_id)),
+                )
+                .execute(conn),
+            })
+            .await??;
         // debug!("Removed sensor with id {}", sensor_id);
-        Ok(())
+        self.check_versioned_write(rows, expected_version, "sensor", sensor_id, move |conn| {
+            sensor_version(conn, sensor_id)
+        })
+        .await
+    }
+
+    /// Globaler Sensorkatalog mit der Anzahl der Einträge je Sensor.
+    ///
+    /// Jedes Wort in `search_string` muss in Name, Hersteller, Typ, Modell oder
+    /// Seriennummer vorkommen.
+    #[instrument]
+    pub async fn get_sensor_catalog(
+        &self,
+        search_string: Option<String>,
+        txid: TxID,
+    ) -> Result<Vec<CatalogSensorWithUsage>, StorageError> {
+        let search_parts: Vec<String> = search_string
+            .as_deref()
+            .unwrap_or("")
+            .split_whitespace()
+            .map(|p| format!("%{}%", escape_like(p)))
+            .collect();
+        let conn = self.db_connection_pool().get().await?;
+        let (sensors, counts) = conn
+            .interact(move |conn| {
+                use diesel::dsl::sql;
+                use diesel::sql_types::BigInt;
+                use schema::entry_sensors::dsl as es_dsl;
+                use schema::sensor_catalog::dsl as catalog_dsl;
+
+                let mut query = catalog_dsl::sensor_catalog.into_boxed();
+                for part in search_parts.iter() {
+                    query = query.filter(
+                        catalog_dsl::sensor_name
+                            .ilike(part.clone())
+                            .or(catalog_dsl::manufacturer.ilike(part.clone()))
+                            .or(catalog_dsl::sensor_type.ilike(part.clone()))
+                            .or(catalog_dsl::model.ilike(part.clone()))
+                            .or(catalog_dsl::serial_number.ilike(part.clone())),
+                    );
+                }
+                let sensors = query
+                    .order_by(catalog_dsl::sensor_name.asc())
+                    .then_order_by(catalog_dsl::id.asc())
+                    .select(CatalogSensor::as_select())
+                    .load::<CatalogSensor>(conn)?;
+                let ids: Vec<CatalogSensorID> = sensors.iter().map(|s| s.id).collect();
+                let counts = es_dsl::entry_sensors
+                    .filter(es_dsl::catalog_sensor_id.eq_any(ids))
+                    .group_by(es_dsl::catalog_sensor_id)
+                    .select((
+                        es_dsl::catalog_sensor_id,
+                        sql::<BigInt>("COUNT(DISTINCT entry_sensors.entry_id)"),
+                    ))
+                    .load::<(CatalogSensorID, i64)>(conn)?;
+                Ok::<_, diesel::result::Error>((sensors, counts))
+            })
+            .await??;
+        let counts: Map<CatalogSensorID, i64> = counts.into_iter().collect();
+        Ok(sensors
+            .into_iter()
+            .map(|sensor| CatalogSensorWithUsage {
+                entry_count: counts.get(&sensor.id).copied().unwrap_or(0),
+                sensor,
+            })
+            .collect())
+    }
+
+    /// Alle Einträge, in denen ein Katalogsensor verwendet wird.
+    #[instrument]
+    pub async fn get_catalog_sensor_entries(
+        &self,
+        catalog_sensor_id: CatalogSensorID,
+        txid: TxID,
+    ) -> Result<Vec<CatalogSensorUsage>, StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        let rows = conn
+            .interact(move |conn| {
+                use schema::entries::dsl as entries_dsl;
+                use schema::entry_sensors::dsl as es_dsl;
+                use schema::sensor_catalog::dsl as catalog_dsl;
+
+                let exists = catalog_dsl::sensor_catalog
+                    .find(catalog_sensor_id)
+                    .select(catalog_dsl::id)
+                    .first::<CatalogSensorID>(conn)
+                    .optional()?
+                    .is_some();
+                if !exists {
+                    return Ok(None);
+                }
+                es_dsl::entry_sensors
+                    .inner_join(entries_dsl::entries)
+                    .filter(es_dsl::catalog_sensor_id.eq(catalog_sensor_id))
+                    .order_by(entries_dsl::name.asc())
+                    .then_order_by(es_dsl::id.asc())
+                    .select((EntrySensor::as_select(), EntrySummary::as_select()))
+                    .load::<(EntrySensor, EntrySummary)>(conn)
+                    .map(Some)
+            })
+            .await??
+            .ok_or_else(|| {
+                StorageError::NotFound(format!("catalog sensor {catalog_sensor_id} not found"))
+            })?;
+        Ok(rows
+            .into_iter()
+            .map(|(assignment, entry)| CatalogSensorUsage { assignment, entry })
+            .collect())
+    }
+
+    /// Katalogsensoren der Sensoren eines Eintrags.
+    #[instrument]
+    pub async fn get_entry_sensor_assignments(
+        &self,
+        entry_id_: EntryID,
+        txid: TxID,
+    ) -> Result<Vec<EntrySensorAssignment>, StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        let rows = conn
+            .interact(move |conn| {
+                use schema::entry_sensors::dsl as es_dsl;
+                use schema::sensor_catalog::dsl as catalog_dsl;
+
+                es_dsl::entry_sensors
+                    .inner_join(catalog_dsl::sensor_catalog)
+                    .filter(es_dsl::entry_id.eq(entry_id_))
+                    .order_by(es_dsl::sensor_id.asc())
+                    .select((EntrySensor::as_select(), CatalogSensor::as_select()))
+                    .load::<(EntrySensor, CatalogSensor)>(conn)
+            })
+            .await??;
+        Ok(rows
+            .into_iter()
+            .map(|(assignment, sensor)| EntrySensorAssignment { assignment, sensor })
+            .collect())
+    }
+
+    /// Überschreibt die Stammdaten eines Katalogsensors (`id` und `version` von
+    /// `sensor` bestimmen Ziel bzw. werden ignoriert).
+    #[instrument]
+    pub async fn update_catalog_sensor(
+        &self,
+        sensor: CatalogSensor,
+        expected_version: Option<i64>,
+        txid: TxID,
+    ) -> Result<(), StorageError> {
+        let sensor_id = sensor.id;
+        let conn = self.db_connection_pool().get().await?;
+        let rows = conn
+            .interact(move |conn| {
+                use schema::sensor_catalog::dsl as catalog_dsl;
+                let changes = (
+                    catalog_dsl::sensor_name.eq(sensor.sensor_name),
+                    catalog_dsl::manufacturer.eq(sensor.manufacturer),
+                    catalog_dsl::sensor_type.eq(sensor.sensor_type),
+                    catalog_dsl::model.eq(sensor.model),
+                    catalog_dsl::serial_number.eq(sensor.serial_number),
+                    catalog_dsl::calibration.eq(sensor.calibration),
+                    catalog_dsl::custom_parameters.eq(sensor.custom_parameters),
+                    catalog_dsl::updated_at.eq(Utc::now()),
+                );
+                match expected_version {
+                    Some(v) => diesel::update(
+                        catalog_dsl::sensor_catalog
+                            .filter(catalog_dsl::id.eq(sensor_id))
+                            .filter(catalog_dsl::version.eq(v)),
+                    )
+                    .set(changes)
+                    .execute(conn),
+                    None => diesel::update(catalog_dsl::sensor_catalog.find(sensor_id))
+                        .set(changes)
+                        .execute(conn),
+                }
+            })
+            .await??;
+        if rows == 0 && expected_version.is_none() {
+            return Err(StorageError::NotFound(format!(
+                "catalog sensor {sensor_id} not found"
+            )));
+        }
+        self.check_versioned_write(
+            rows,
+            expected_version,
+            "catalog sensor",
+            sensor_id,
+            move |conn| catalog_sensor_version(conn, sensor_id),
+        )
+        .await
+    }
+
+    /// Führt doppelt erfasste Katalogsensoren in `target_id` zusammen.
+    ///
+    /// Zuordnungen und Identitätsschlüssel der Quellen wandern zum Ziel, damit
+    /// spätere Scans ebenfalls dort landen; leere Felder des Ziels werden aus den
+    /// Quellen ergänzt. Die Quellen werden anschließend gelöscht.
+    #[instrument]
+    pub async fn merge_catalog_sensors(
+        &self,
+        target_id: CatalogSensorID,
+        source_ids: Vec<CatalogSensorID>,
+        txid: TxID,
+    ) -> Result<CatalogSensor, StorageError> {
+        let sources: Vec<CatalogSensorID> = source_ids
+            .into_iter()
+            .filter(|id| *id != target_id)
+            .unique()
+            .collect();
+        if sources.is_empty() {
+            return Err(StorageError::ValidationError(
+                "at least one source sensor distinct from the target is required".to_string(),
+            ));
+        }
+        let conn = self.db_connection_pool().get().await?;
+        let merged = conn
+            .interact(move |conn| {
+                conn.transaction::<_, StorageError, _>(|conn| {
+                    use schema::entry_sensors::dsl as es_dsl;
+                    use schema::sensor_catalog::dsl as catalog_dsl;
+                    use schema::sensor_identity_keys::dsl as keys_dsl;
+
+                    let mut all = sources.clone();
+                    all.push(target_id);
+                    let rows = catalog_dsl::sensor_catalog
+                        .filter(catalog_dsl::id.eq_any(&all))
+                        .order_by(catalog_dsl::id.asc())
+                        .select(CatalogSensor::as_select())
+                        .for_update()
+                        .load::<CatalogSensor>(conn)?;
+                    if rows.len() != all.len() {
+                        let missing = all
+                            .iter()
+                            .filter(|id| !rows.iter().any(|s| s.id == **id))
+                            .join(", ");
+                        return Err(StorageError::NotFound(format!(
+                            "catalog sensors {missing} not found"
+                        )));
+                    }
+                    let (mut target, others): (Vec<CatalogSensor>, Vec<CatalogSensor>) =
+                        rows.into_iter().partition(|s| s.id == target_id);
+                    let Some(target) = target.pop() else {
+                        return Err(StorageError::NotFound(format!(
+                            "catalog sensor {target_id} not found"
+                        )));
+                    };
+                    let fill =
+                        |own: Option<String>, pick: fn(&CatalogSensor) -> &Option<String>| {
+                            own.or_else(|| others.iter().find_map(|s| pick(s).clone()))
+                        };
+                    let manufacturer = fill(target.manufacturer, |s| &s.manufacturer);
+                    let sensor_type = fill(target.sensor_type, |s| &s.sensor_type);
+                    let model = fill(target.model, |s| &s.model);
+                    let serial_number = fill(target.serial_number, |s| &s.serial_number);
+                    let calibration = target
+                        .calibration
+                        .or_else(|| others.iter().find_map(|s| s.calibration.clone()));
+
+                    diesel::update(
+                        es_dsl::entry_sensors.filter(es_dsl::catalog_sensor_id.eq_any(&sources)),
+                    )
+                    .set(es_dsl::catalog_sensor_id.eq(target_id))
+                    .execute(conn)?;
+                    diesel::update(
+                        keys_dsl::sensor_identity_keys
+                            .filter(keys_dsl::catalog_sensor_id.eq_any(&sources)),
+                    )
+                    .set(keys_dsl::catalog_sensor_id.eq(target_id))
+                    .execute(conn)?;
+                    diesel::delete(
+                        catalog_dsl::sensor_catalog.filter(catalog_dsl::id.eq_any(&sources)),
+                    )
+                    .execute(conn)?;
+                    let merged = diesel::update(catalog_dsl::sensor_catalog.find(target_id))
+                        .set((
+                            catalog_dsl::manufacturer.eq(manufacturer),
+                            catalog_dsl::sensor_type.eq(sensor_type),
+                            catalog_dsl::model.eq(model),
+                            catalog_dsl::serial_number.eq(serial_number),
+                            catalog_dsl::calibration.eq(calibration),
+                            catalog_dsl::updated_at.eq(Utc::now()),
+                        ))
+                        .returning(CatalogSensor::as_returning())
+                        .get_result::<CatalogSensor>(conn)?;
+                    Ok(merged)
+                })
+            })
+            .await??;
+        debug!("Merged catalog sensors into {}", merged.id);
+        Ok(merged)
     }
 
     #[instrument]
@@ -600,6 +1294,12 @@ impl StorageManager {
         sequence: Sequence,
         txid: TxID,
     ) -> Result<SequenceID, StorageError> {
+        let bounds = self.entry_time_bounds(entry_id_).await?;
+        timeline::validate_sequence_range(
+            sequence.start_timestamp,
+            sequence.end_timestamp,
+            bounds,
+        )?;
         let conn = self.db_connection_pool().get().await?;
         let s = sequence.clone();
         debug!("Adding sequence for entry_id {}: {:?}", entry_id_, s);
@@ -634,28 +1334,46 @@ impl StorageManager {
         entry_id_: EntryID,
         sequence_id: SequenceID,
         sequence: Sequence,
+        expected_version: Option<i64>,
         txid: TxID,
     ) -> Result<(), StorageError> {
+        let bounds = self.entry_time_bounds(entry_id_).await?;
+        timeline::validate_sequence_range(
+            sequence.start_timestamp,
+            sequence.end_timestamp,
+            bounds,
+        )?;
         let conn = self.db_connection_pool().get().await?;
-        conn.interact(move |conn| {
-            diesel::update(
-                schema::sequences::dsl::sequences
+        let rows = conn
+            .interact(move |conn| {
+                let changes = (
+                    schema::sequences::dsl::name.eq(sequence.name),
+                    schema::sequences::dsl::description.eq(sequence.description),
+                    schema::sequences::dsl::start_timestamp.eq(sequence.start_timestamp),
+                    schema::sequences::dsl::end_timestamp.eq(sequence.end_timestamp),
+                    schema::sequences::dsl::updated_at.eq(sequence.updated_at),
+                    schema::sequences::dsl::tags.eq(sequence.tags),
+                );
+                let target = schema::sequences::dsl::sequences
                     .filter(schema::sequences::dsl::id.eq(sequence_id))
-                    .filter(schema::sequences::dsl::entry_id.eq(entry_id_)),
-            )
-            .set((
-                schema::sequences::dsl::name.eq(sequence.name),
-                schema::sequences::dsl::description.eq(sequence.description),
-                schema::sequences::dsl::start_timestamp.eq(sequence.start_timestamp),
-                schema::sequences::dsl::end_timestamp.eq(sequence.end_timestamp),
-                schema::sequences::dsl::updated_at.eq(sequence.updated_at),
-                schema::sequences::dsl::tags.eq(sequence.tags),
-            ))
-            .execute(conn)
-        })
-        .await??;
+                    .filter(schema::sequences::dsl::entry_id.eq(entry_id_));
+                match expected_version {
+                    Some(v) => diesel::update(target.filter(schema::sequences::dsl::version.eq(v)))
+                        .set(changes)
+                        .execute(conn),
+                    None => diesel::update(target).set(changes).execute(conn),
+                }
+            })
+            .await??;
         // debug!("Updated sequences");
-        Ok(())
+        self.check_versioned_write(
+            rows,
+            expected_version,
+            "sequence",
+            sequence_id,
+            move |conn| sequence_version(conn, entry_id_, sequence_id),
+        )
+        .await
     }
 
     #[instrument]
@@ -663,64 +1381,2502 @@ impl StorageManager {
         &self,
         entry_id_: EntryID,
         sequence_id: SequenceID,
+        expected_version: Option<i64>,
         txid: TxID,
     ) -> Result<(), StorageError> {
         let conn = self.db_connection_pool().get().await?;
-        conn.interact(move |conn| {
-            diesel::delete(
-                schema::sequences::dsl::sequences
+        let rows = conn
+            .interact(move |conn| {
+                let target = schema::sequences::dsl::sequences
                     .filter(schema::sequences::dsl::id.eq(sequence_id))
-                    .filter(schema::sequences::dsl::entry_id.eq(entry_id_)),
-            )
-            .execute(conn)
-        })
-        .await??;
+                    .filter(schema::sequences::dsl::entry_id.eq(entry_id_));
+                match expected_version {
+                    Some(v) => diesel::delete(target.filter(schema::sequences::dsl::version.eq(v)))
+                        .execute(conn),
+                    None => diesel::delete(target).execute(conn),
+                }
+            })
+            .await??;
         debug!(
             "Removed sequence with id {} for entry_id {}",
             sequence_id, entry_id_
         );
-        Ok(())
+        self.check_versioned_write(
+            rows,
+            expected_version,
+            "sequence",
+            sequence_id,
+            move |conn| sequence_version(conn, entry_id_, sequence_id),
+        )
+        .await
     }
 
+    /// Teilt eine Sequenz am Zeitpunkt `at` in zwei aneinandergrenzende Sequenzen.
+    ///
+    /// Die bestehende Sequenz endet danach bei `at`, die neue beginnt dort und
+    /// übernimmt Name, Beschreibung und Tags. Liefert beide Hälften.
     #[instrument]
-    pub async fn add_tag(
+    pub async fn split_sequence(
         &self,
         entry_id_: EntryID,
-        tag: Tag,
+        sequence_id: SequenceID,
+        at: Timestamp,
+        expected_version: Option<i64>,
         txid: TxID,
-    ) -> Result<(), StorageError> {
+    ) -> Result<(Sequence, Sequence), StorageError> {
         let conn = self.db_connection_pool().get().await?;
-        let t = tag.clone();
-        conn.interact(move |conn| {
-            diesel::sql_query("UPDATE entries SET tags = array_append(tags, $1) WHERE id = $2 AND NOT ($1 = ANY(tags))")
+        let halves = conn
+            .interact(move |conn| {
+                conn.transaction::<_, StorageError, _>(|conn| {
+                    use crate::schema::sequences::dsl as sequences_dsl;
+                    let seq = sequences_dsl::sequences
+                        .filter(sequences_dsl::id.eq(sequence_id))
+                        .filter(sequences_dsl::entry_id.eq(entry_id_))
+                        .select(Sequence::as_select())
+                        .for_update()
+                        .first::<Sequence>(conn)
+                        .optional()?
+                        .ok_or_else(|| {
+                            StorageError::NotFound(format!("sequence {sequence_id} not found"))
+                        })?;
+                    if let Some(v) = expected_version
+                        && seq.version != v
+                    {
+                        return Err(StorageError::PreconditionFailed(format!(
+                            "sequence {sequence_id} has version {}, but version {v} was expected",
+                            seq.version
+                        )));
+                    }
+                    if at <= seq.start_timestamp || at >= seq.end_timestamp {
+                        return Err(StorageError::ValidationError(format!(
+                            "split point {at} must lie strictly between {} and {}",
+                            seq.start_timestamp, seq.end_timestamp
+                        )));
+                    }
+                    let now = Utc::now();
+                    let first = diesel::update(sequences_dsl::sequences.find(seq.id))
+                        .set((
+                            sequences_dsl::end_timestamp.eq(at),
+                            sequences_dsl::updated_at.eq(now),
+                        ))
+                        .returning(Sequence::as_returning())
+                        .get_result::<Sequence>(conn)?;
+                    let second = diesel::insert_into(sequences_dsl::sequences)
+                        .values((
+                            sequences_dsl::entry_id.eq(entry_id_),
+                            sequences_dsl::name.eq(seq.name),
+                            sequences_dsl::description.eq(seq.description),
+                            sequences_dsl::start_timestamp.eq(at),
+                            sequences_dsl::end_timestamp.eq(seq.end_timestamp),
+                            sequences_dsl::created_at.eq(now),
+                            sequences_dsl::updated_at.eq(now),
+                            sequences_dsl::tags.eq(seq.tags),
+                        ))
+                        .returning(Sequence::as_returning())
+                        .get_result::<Sequence>(conn)?;
+                    Ok((first, second))
+                })
+            })
+            .await??;
+        debug!(
+            "Split sequence {} of entry_id {} at {} into {} and {}",
+            sequence_id, entry_id_, at, halves.0.id, halves.1.id
+        );
+        Ok(halves)
+    }
+
+    /// Fasst mehrere Sequenzen eines Eintrags zu einer zusammen.
+    ///
+    /// Die früheste Sequenz bleibt erhalten und spannt danach vom frühesten Start
+    /// bis zum spätesten Ende; Tags und Beschreibungen werden vereinigt, die
+    /// übrigen Sequenzen gelöscht.
+    #[instrument]
+    pub async fn merge_sequences(
+        &self,
+        entry_id_: EntryID,
+        sequence_ids: Vec<SequenceID>,
+        name: Option<String>,
+        txid: TxID,
+    ) -> Result<Sequence, StorageError> {
+        let ids: Vec<SequenceID> = sequence_ids.into_iter().unique().collect();
+        if ids.len() < 2 {
+            return Err(StorageError::ValidationError(
+                "at least two distinct sequences are required for a merge".to_string(),
+            ));
+        }
+        let conn = self.db_connection_pool().get().await?;
+        let merged = conn
+            .interact(move |conn| {
+                conn.transaction::<_, StorageError, _>(|conn| {
+                    use crate::schema::sequences::dsl as sequences_dsl;
+                    let mut seqs = sequences_dsl::sequences
+                        .filter(sequences_dsl::entry_id.eq(entry_id_))
+                        .filter(sequences_dsl::id.eq_any(&ids))
+                        .select(Sequence::as_select())
+                        .for_update()
+                        .load::<Sequence>(conn)?;
+                    if seqs.len() != ids.len() {
+                        let missing = ids
+                            .iter()
+                            .filter(|id| !seqs.iter().any(|s| s.id == **id))
+                            .join(", ");
+                        return Err(StorageError::NotFound(format!(
+                            "sequences {missing} not found for entry {entry_id_}"
+                        )));
+                    }
+                    seqs.sort_by_key(|s| (s.start_timestamp, s.id));
+                    let keep = seqs[0].clone();
+                    let start = seqs
+                        .iter()
+                        .map(|s| s.start_timestamp)
+                        .min()
+                        .unwrap_or(keep.start_timestamp);
+                    let end = seqs
+                        .iter()
+                        .map(|s| s.end_timestamp)
+                        .max()
+                        .unwrap_or(keep.end_timestamp);
+                    let tags: Vec<String> =
+                        seqs.iter().flat_map(|s| s.tags.clone()).unique().collect();
+                    let description = seqs
+                        .iter()
+                        .map(|s| s.description.trim())
+                        .filter(|d| !d.is_empty())
+                        .unique()
+                        .join("; ");
+                    let name = name.unwrap_or(keep.name);
+
+                    let merged = diesel::update(sequences_dsl::sequences.find(keep.id))
+                        .set((
+                            sequences_dsl::name.eq(name),
+                            sequences_dsl::description.eq(description),
+                            sequences_dsl::start_timestamp.eq(start),
+                            sequences_dsl::end_timestamp.eq(end),
+                            sequences_dsl::updated_at.eq(Utc::now()),
+                            sequences_dsl::tags.eq(tags),
+                        ))
+                        .returning(Sequence::as_returning())
+                        .get_result::<Sequence>(conn)?;
+                    let others: Vec<SequenceID> = seqs[1..].iter().map(|s| s.id).collect();
+                    diesel::delete(
+                        sequences_dsl::sequences.filter(sequences_dsl::id.eq_any(others)),
+                    )
+                    .execute(conn)?;
+                    Ok(merged)
+                })
+            })
+            .await??;
+        debug!(
+            "Merged sequences of entry_id {} into sequence {}",
+            entry_id_, merged.id
+        );
+        Ok(merged)
+    }
+
+    /// Alle Sequenzen eines Eintrags, die das Intervall `[start, end]` schneiden.
+    #[instrument]
+    pub async fn get_overlapping_sequences(
+        &self,
+        entry_id_: EntryID,
+        start: Timestamp,
+        end: Timestamp,
+        txid: TxID,
+    ) -> Result<Map<SequenceID, Sequence>, StorageError> {
+        timeline::validate_sequence_range(start, end, (None, None))?;
+        let conn = self.db_connection_pool().get().await?;
+        let sequences = conn
+            .interact(move |conn| {
+                schema::sequences::dsl::sequences
+                    .filter(schema::sequences::dsl::entry_id.eq(entry_id_))
+                    .filter(schema::sequences::dsl::start_timestamp.lt(end))
+                    .filter(schema::sequences::dsl::end_timestamp.gt(start))
+                    .select(Sequence::as_select())
+                    .load::<Sequence>(conn)
+            })
+            .await??;
+        Ok(sequences.into_iter().map(|s| (s.id, s)).collect())
+    }
+
+    /// Zeitachse eines Eintrags: Sequenzen, Lücken und Überlappungen.
+    #[instrument]
+    pub async fn get_timeline(
+        &self,
+        entry_id_: EntryID,
+        txid: TxID,
+    ) -> Result<timeline::Timeline, StorageError> {
+        let bounds = self.entry_time_bounds(entry_id_).await?;
+        let sequences = self.get_sequences(entry_id_, txid).await?;
+        Ok(timeline::build_timeline(
+            entry_id_,
+            bounds,
+            sequences.into_values(),
+        ))
+    }
+
+    /// Schneidet das Zeitfenster einer Sequenz als eigene MCAP-Datei aus.
+    ///
+    /// Der Clip landet im Export-Verzeichnis; zurückgegeben wird sein Pfad. Mit
+    /// `topics` werden nur die angegebenen Topics übernommen.
+    #[instrument]
+    pub async fn export_sequence_clip(
+        &self,
+        entry_id_: EntryID,
+        sequence_id: SequenceID,
+        topics: Option<Vec<String>>,
+        txid: TxID,
+    ) -> Result<PathBuf, StorageError> {
+        let entry = self
+            .get_entry(entry_id_, txid)
+            .await?
+            .ok_or_else(|| StorageError::NotFound(format!("entry {entry_id_} not found")))?;
+        let sequence = self
+            .get_sequences(entry_id_, txid)
+            .await?
+            .remove(&sequence_id)
+            .ok_or_else(|| StorageError::NotFound(format!("sequence {sequence_id} not found")))?;
+        let (start_ns, end_ns) = match (
+            u64::try_from(sequence.start_timestamp),
+            u64::try_from(sequence.end_timestamp),
+        ) {
+            (Ok(start), Ok(end)) if start <= end => (start, end),
+            _ => {
+                return Err(StorageError::ValidationError(format!(
+                    "sequence {sequence_id} has no valid time window"
+                )));
+            }
+        };
+
+        let topics: Option<HashSet<String>> = topics
+            .map(|t| t.into_iter().filter(|t| !t.is_empty()).collect())
+            .filter(|t: &HashSet<String>| !t.is_empty());
+        let stem = PathBuf::from(&entry.path)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| format!("entry{entry_id_}"));
+        let mut file_name = format!("{stem}_seq{sequence_id}_{start_ns}-{end_ns}");
+        if let Some(t) = topics.as_ref() {
+            // gleiche Topic-Auswahl -> gleicher Dateiname, unabhängig von der Reihenfolge
+            let mut hasher = std::hash::DefaultHasher::new();
+            t.iter().sorted().for_each(|topic| topic.hash(&mut hasher));
+            file_name.push_str(&format!("_{:016x}", hasher.finish()));
+        }
+        file_name.push_str(".mcap");
+
+        tokio::fs::create_dir_all(&self.export_dir).await?;
+        let target = self.export_dir.join(file_name);
+        let source = PathBuf::from(&entry.path);
+        let target_clone = target.clone();
+        let stats = tokio::task::spawn_blocking(move || {
+            clip::write_clip(&source, &target_clone, start_ns, end_ns, topics.as_ref())
+        })
+        .await
+        .map_err(|e| StorageError::CustomError(format!("clip export task failed: {e}")))??;
+        info!(
+            "Exported clip of sequence {} ({} messages on {} channels) to {:?}",
+            sequence_id, stats.messages, stats.channels, target
+        );
+        Ok(target)
+    }
+
+    /// Entfernt Exporte, die älter als `max_age` sind, und liefert ihre Anzahl.
+    ///
+    /// Clips werden bei jeder Anfrage neu geschrieben, das Export-Verzeichnis ist
+    /// also nur ein Zwischenlager und darf regelmäßig geleert werden. Ausgenommen
+    /// sind Datei-Artefakte, auf die Einträge verweisen.
+    #[instrument]
+    pub async fn cleanup_exports(&self, max_age: Duration) -> Result<usize, StorageError> {
+        let dir = self.export_dir.clone();
+        let keep = [dir.join(artifacts::ARTIFACT_DIR)];
+        let cutoff = SystemTime::now().checked_sub(max_age).unwrap_or(UNIX_EPOCH);
+        tokio::task::spawn_blocking(move || clip::remove_files_older_than(&dir, cutoff, &keep))
+            .await
+            .map_err(|e| StorageError::CustomError(format!("export cleanup task failed: {e}")))?
+    }
+
+    /// Aufnahmezeitraum (MCAP-Start/-Ende in ns) eines Eintrags.
+    async fn entry_time_bounds(
+        &self,
+        entry_id_: EntryID,
+    ) -> Result<timeline::TimeBounds, StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        conn.interact(move |conn| {
+            schema::entries::dsl::entries
+                .find(entry_id_)
+                .select((
+                    schema::entries::dsl::mcap_start_ns,
+                    schema::entries::dsl::mcap_end_ns,
+                ))
+                .first::<timeline::TimeBounds>(conn)
+                .optional()
+        })
+        .await??
+        .ok_or_else(|| StorageError::NotFound(format!("entry {entry_id_} not found")))
+    }
+
+    #[instrument]
+    pub async fn add_tag(
+        &self,
+        entry_id_: EntryID,
+        tag: Tag,
+        expected_version: Option<i64>,
+        txid: TxID,
+    ) -> Result<(), StorageError> {
+        tags::validate_tag_name(&tag)?;
+        let conn = self.db_connection_pool().get().await?;
+        let t = tag.clone();
+        let rows = conn.interact(move |conn| {
+            diesel::sql_query("UPDATE entries SET tags = array_append(tags, $1) WHERE id = $2 AND NOT ($1 = ANY(tags)) AND ($3::BIGINT IS NULL OR version = $3)")
                 .bind::<diesel::sql_types::Text,_>(t)
                 .bind::<diesel::sql_types::BigInt,_>(entry_id_)
+                .bind::<diesel::sql_types::Nullable<diesel::sql_types::BigInt>,_>(expected_version)
+                .execute(conn)
+        }).await??;
+        debug!("Added tag for entry_id {}", entry_id_);
+        self.check_versioned_write(rows, expected_version, "entry", entry_id_, move |conn| {
+            entry_version(conn, entry_id_)
+        })
+        .await
+    }
+
+    #[instrument]
+    pub async fn remove_tag(
+        &self,
+        entry_id_: EntryID,
+        tag: Tag,
+        expected_version: Option<i64>,
+        txid: TxID,
+    ) -> Result<(), StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        let t = tag.clone();
+        let rows = conn
+            .interact(move |conn| {
+                diesel::sql_query(
+                    "UPDATE entries SET tags = array_remove(tags, $1) WHERE id = $2 AND ($3::BIGINT IS NULL OR version = $3)",
+                )
+                .bind::<diesel::sql_types::Text, _>(t)
+                .bind::<diesel::sql_types::BigInt, _>(entry_id_)
+                .bind::<diesel::sql_types::Nullable<diesel::sql_types::BigInt>, _>(expected_version)
+                .execute(conn)
+            })
+            .await??;
+        debug!("Removed tag");
+        self.check_versioned_write(rows, expected_version, "entry", entry_id_, move |conn| {
+            entry_version(conn, entry_id_)
+        })
+        .await
+    }
+
+    /// Alle Tags aus Einträgen, Sequenzen und Tag-Definitionen mit Verwendungszahlen.
+    ///
+    /// `namespace` liefert den Namensraum samt Unterräumen, `search_string` filtert
+    /// ohne Beachtung der Groß-/Kleinschreibung auf den Namen.
+    #[instrument]
+    pub async fn get_tags(
+        &self,
+        namespace: Option<String>,
+        search_string: Option<String>,
+        txid: TxID,
+    ) -> Result<Vec<TagInfo>, StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        let rows = conn
+            .interact(move |conn| {
+                diesel::sql_query(
+                    "WITH usage AS ( \
+                       SELECT tag, count(*) AS entry_count, 0::BIGINT AS sequence_count \
+                         FROM entries, unnest(tags) AS tag GROUP BY tag \
+                       UNION ALL \
+                       SELECT tag, 0, count(*) FROM sequences, unnest(tags) AS tag GROUP BY tag \
+                       UNION ALL \
+                       SELECT name, 0, 0 FROM tag_definitions \
+                     ) \
+                     SELECT u.tag AS name, \
+                            SUM(u.entry_count)::BIGINT AS entry_count, \
+                            SUM(u.sequence_count)::BIGINT AS sequence_count, \
+                            d.color, d.description, d.version \
+                       FROM usage u LEFT JOIN tag_definitions d ON d.name = u.tag \
+                      GROUP BY u.tag, d.color, d.description, d.version \
+                      ORDER BY u.tag",
+                )
+                .load::<TagUsageRow>(conn)
+            })
+            .await??;
+
+        let search = search_string.map(|s| s.to_lowercase());
+        Ok(rows
+            .into_iter()
+            .filter(|r| {
+                namespace.as_deref().is_none_or(|ns| {
+                    r.name
+                        .strip_prefix(ns)
+                        .is_some_and(|rest| rest.starts_with('/'))
+                })
+            })
+            .filter(|r| search.as_deref().is_none_or(|s| contains_part(&r.name, s)))
+            .map(|r| TagInfo {
+                namespace: tags::tag_namespace(&r.name).map(str::to_string),
+                name: r.name,
+                color: r.color,
+                description: r.description,
+                entry_count: r.entry_count,
+                sequence_count: r.sequence_count,
+                version: r.version,
+            })
+            .collect())
+    }
+
+    /// Legt die Metadaten eines Tags an oder überschreibt sie.
+    #[instrument]
+    pub async fn update_tag_definition(
+        &self,
+        name: Tag,
+        color: Option<String>,
+        description: Option<String>,
+        expected_version: Option<i64>,
+        txid: TxID,
+    ) -> Result<TagDefinition, StorageError> {
+        tags::validate_tag_name(&name)?;
+        if let Some(c) = color.as_deref() {
+            tags::validate_tag_color(c)?;
+        }
+        let conn = self.db_connection_pool().get().await?;
+        let tag = name.clone();
+        let definition = conn
+            .interact(move |conn| {
+                use schema::tag_definitions::dsl as td_dsl;
+                let now = Utc::now();
+                match expected_version {
+                    Some(v) => diesel::update(
+                        td_dsl::tag_definitions
+                            .filter(td_dsl::name.eq(&tag))
+                            .filter(td_dsl::version.eq(v)),
+                    )
+                    .set((
+                        td_dsl::color.eq(&color),
+                        td_dsl::description.eq(&description),
+                        td_dsl::updated_at.eq(now),
+                    ))
+                    .returning(TagDefinition::as_returning())
+                    .get_result::<TagDefinition>(conn)
+                    .optional(),
+                    None => diesel::insert_into(td_dsl::tag_definitions)
+                        .values((
+                            td_dsl::name.eq(&tag),
+                            td_dsl::color.eq(&color),
+                            td_dsl::description.eq(&description),
+                        ))
+                        .on_conflict(td_dsl::name)
+                        .do_update()
+                        .set((
+                            td_dsl::color.eq(&color),
+                            td_dsl::description.eq(&description),
+                            td_dsl::updated_at.eq(now),
+                        ))
+                        .returning(TagDefinition::as_returning())
+                        .get_result::<TagDefinition>(conn)
+                        .optional(),
+                }
+            })
+            .await??;
+        if let Some(d) = definition {
+            return Ok(d);
+        }
+        let tag = name.clone();
+        let conn = self.db_connection_pool().get().await?;
+        let current = conn
+            .interact(move |conn| tag_definition_version(conn, &tag))
+            .await??;
+        Err(match (current, expected_version) {
+            (Some(v), Some(expected)) => StorageError::PreconditionFailed(format!(
+                "tag '{name}' has version {v}, but version {expected} was expected"
+            )),
+            _ => StorageError::NotFound(format!("tag definition '{name}' not found")),
+        })
+    }
+
+    /// Benennt einen Tag überall um (Einträge, Sequenzen, Tag-Definition).
+    ///
+    /// Trägt ein Eintrag bereits den neuen Namen, wird der alte nur entfernt.
+    #[instrument]
+    pub async fn rename_tag(
+        &self,
+        old_name: Tag,
+        new_name: Tag,
+        txid: TxID,
+    ) -> Result<TagChangeCount, StorageError> {
+        if old_name == new_name {
+            return Err(StorageError::ValidationError(
+                "new tag name equals the old one".to_string(),
+            ));
+        }
+        self.merge_tags(vec![old_name], new_name, txid).await
+    }
+
+    /// Führt die Tags `sources` in `target` zusammen, z.B. `Night` und
+    /// `nighttime` in `night`.
+    ///
+    /// Die Reihenfolge der Tags eines Eintrags bleibt erhalten. Metadaten einer
+    /// Quelle werden übernommen, falls `target` noch keine hat.
+    #[instrument]
+    pub async fn merge_tags(
+        &self,
+        sources: Vec<Tag>,
+        target: Tag,
+        txid: TxID,
+    ) -> Result<TagChangeCount, StorageError> {
+        tags::validate_tag_name(&target)?;
+        let sources: Vec<Tag> = sources
+            .into_iter()
+            .filter(|s| *s != target)
+            .unique()
+            .collect();
+        if sources.is_empty() {
+            return Err(StorageError::ValidationError(
+                "at least one source tag distinct from the target is required".to_string(),
+            ));
+        }
+        let conn = self.db_connection_pool().get().await?;
+        let target_ = target.clone();
+        let (changed, touched) = conn
+            .interact(move |conn| {
+                let target = target_;
+                conn.transaction::<_, StorageError, _>(|conn| {
+                    use diesel::sql_types::Text;
+                    let set_tags = "SET tags = CASE WHEN $2 = ANY(tags) \
+                                      THEN array_remove(tags, $1) ELSE array_replace(tags, $1, $2) END \
+                                    WHERE $1 = ANY(tags)";
+                    let mut changed = TagChangeCount::default();
+                    let mut touched = Vec::new();
+                    for source in sources.iter() {
+                        let entries =
+                            diesel::sql_query(format!("UPDATE entries {set_tags} RETURNING id"))
+                                .bind::<Text, _>(source)
+                                .bind::<Text, _>(&target)
+                                .load::<EntryIdRow>(conn)?;
+                        let sequences = diesel::sql_query(format!("UPDATE sequences {set_tags}"))
+                            .bind::<Text, _>(source)
+                            .bind::<Text, _>(&target)
+                            .execute(conn)?;
+                        let mut known = !entries.is_empty() || sequences > 0;
+                        changed.entries += entries.len();
+                        changed.sequences += sequences;
+                        touched.extend(entries.into_iter().map(|row| row.id));
+                        let moved = diesel::sql_query(
+                            "UPDATE tag_definitions SET name = $2, updated_at = now() WHERE name = $1 \
+                               AND NOT EXISTS (SELECT 1 FROM tag_definitions WHERE name = $2)",
+                        )
+                        .bind::<Text, _>(source)
+                        .bind::<Text, _>(&target)
+                        .execute(conn)?;
+                        let deleted = diesel::delete(
+                            schema::tag_definitions::dsl::tag_definitions
+                                .filter(schema::tag_definitions::dsl::name.eq(source)),
+                        )
+                        .execute(conn)?;
+                        known |= moved > 0 || deleted > 0;
+                        if !known {
+                            return Err(StorageError::NotFound(format!(
+                                "tag '{source}' not found"
+                            )));
+                        }
+                    }
+                    Ok((changed, touched))
+                })
+            })
+            .await??;
+        debug!(
+            "Merged tags into '{}': {} entries, {} sequences",
+            target, changed.entries, changed.sequences
+        );
+        self.publish_metadata_updated(touched);
+        Ok(changed)
+    }
+
+    /// Entfernt einen Tag aus allen Einträgen und Sequenzen und löscht seine Definition.
+    #[instrument]
+    pub async fn delete_tag(&self, name: Tag, txid: TxID) -> Result<TagChangeCount, StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        let tag = name.clone();
+        let (changed, deleted, touched) = conn
+            .interact(move |conn| {
+                conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                    use diesel::sql_types::Text;
+                    let touched = diesel::sql_query(
+                        "UPDATE entries SET tags = array_remove(tags, $1) WHERE $1 = ANY(tags) \
+                         RETURNING id",
+                    )
+                    .bind::<Text, _>(&tag)
+                    .load::<EntryIdRow>(conn)?;
+                    let sequences = diesel::sql_query(
+                        "UPDATE sequences SET tags = array_remove(tags, $1) WHERE $1 = ANY(tags)",
+                    )
+                    .bind::<Text, _>(&tag)
+                    .execute(conn)?;
+                    let deleted = diesel::delete(
+                        schema::tag_definitions::dsl::tag_definitions
+                            .filter(schema::tag_definitions::dsl::name.eq(&tag)),
+                    )
+                    .execute(conn)?;
+                    let entries = touched.len();
+                    Ok((TagChangeCount { entries, sequences }, deleted, touched))
+                })
+            })
+            .await??;
+        if changed == TagChangeCount::default() && deleted == 0 {
+            return Err(StorageError::NotFound(format!("tag '{name}' not found")));
+        }
+        self.publish_metadata_updated(touched.into_iter().map(|row| row.id));
+        Ok(changed)
+    }
+
+    /// Setzt bzw. entfernt Tags an allen Einträgen, die die Suche treffen.
+    ///
+    /// Suche und Filter entsprechen [`get_entries_filtered`](Self::get_entries_filtered);
+    /// ohne beides wird abgelehnt, damit nicht versehentlich alle Einträge getaggt
+    /// werden. Zurück kommt die Zahl der getroffenen Einträge.
+    #[instrument]
+    pub async fn bulk_tag_entries(
+        &self,
+        search_string: Option<String>,
+        filter: EntryFilter,
+        add: Vec<Tag>,
+        remove: Vec<Tag>,
+        txid: TxID,
+    ) -> Result<usize, StorageError> {
+        if add.is_empty() && remove.is_empty() {
+            return Err(StorageError::ValidationError(
+                "nothing to add or remove".to_string(),
+            ));
+        }
+        for tag in add.iter() {
+            tags::validate_tag_name(tag)?;
+        }
+        let ids = self
+            .select_entry_ids(None, search_string, filter, txid)
+            .await?;
+        let matched = ids.len();
+
+        let conn = self.db_connection_pool().get().await?;
+        let touched = conn
+            .interact(move |conn| {
+                conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                    use diesel::sql_types::{Array, BigInt, Text};
+                    let mut touched = Vec::new();
+                    for tag in remove.iter() {
+                        touched.extend(
+                            diesel::sql_query(
+                                "UPDATE entries SET tags = array_remove(tags, $1) \
+                                 WHERE id = ANY($2) AND $1 = ANY(tags) RETURNING id",
+                            )
+                            .bind::<Text, _>(tag)
+                            .bind::<Array<BigInt>, _>(&ids)
+                            .load::<EntryIdRow>(conn)?,
+                        );
+                    }
+                    for tag in add.iter() {
+                        touched.extend(
+                            diesel::sql_query(
+                                "UPDATE entries SET tags = array_append(tags, $1) \
+                                 WHERE id = ANY($2) AND NOT ($1 = ANY(tags)) RETURNING id",
+                            )
+                            .bind::<Text, _>(tag)
+                            .bind::<Array<BigInt>, _>(&ids)
+                            .load::<EntryIdRow>(conn)?,
+                        );
+                    }
+                    Ok(touched)
+                })
+            })
+            .await??;
+        debug!("Bulk-tagged {} entries", matched);
+        self.publish_metadata_updated(touched.into_iter().map(|row| row.id));
+        Ok(matched)
+    }
+
+    /// Bestimmt die Einträge für eine Sammeloperation: entweder die explizite
+    /// `entry_ids`-Liste oder alle Treffer von Suche und Filter.
+    ///
+    /// Ohne Liste, Suche und Filter wird abgelehnt, damit nicht versehentlich
+    /// alle Einträge betroffen sind.
+    #[instrument]
+    pub async fn select_entry_ids(
+        &self,
+        entry_ids: Option<Vec<EntryID>>,
+        search_string: Option<String>,
+        filter: EntryFilter,
+        txid: TxID,
+    ) -> Result<Vec<EntryID>, StorageError> {
+        let has_search = search_string
+            .as_deref()
+            .is_some_and(|s| !s.trim().is_empty())
+            || filter != EntryFilter::default();
+        match entry_ids {
+            Some(_) if has_search => Err(StorageError::ValidationError(
+                "either entry_ids or a search may be given, not both".to_string(),
+            )),
+            Some(ids) => Ok(ids.into_iter().unique().collect()),
+            None if !has_search => Err(StorageError::ValidationError(
+                "entry_ids, a search string or a filter is required".to_string(),
+            )),
+            None => {
+                let (entries, _) = self
+                    .get_entries_filtered(search_string, filter, None, None, None, None, txid)
+                    .await?;
+                Ok(entries.into_iter().map(|e| e.id).collect())
+            }
+        }
+    }
+
+    /// Wendet `change` in einer Transaktion auf alle `entry_ids` an.
+    ///
+    /// Nicht existierende Einträge werden im Bericht als `not_found` gemeldet und
+    /// brechen die Operation nicht ab; jeder Datenbankfehler dagegen rollt alle
+    /// Änderungen zurück.
+    #[instrument]
+    pub async fn bulk_update_entries(
+        &self,
+        entry_ids: Vec<EntryID>,
+        change: BulkEntryChange,
+        txid: TxID,
+    ) -> Result<Vec<BulkEntryResult>, StorageError> {
+        match &change {
+            BulkEntryChange::AddTags(t) | BulkEntryChange::RemoveTags(t) if t.is_empty() => {
+                return Err(StorageError::ValidationError("no tags given".to_string()));
+            }
+            BulkEntryChange::AddTags(t) => {
+                for tag in t.iter() {
+                    tags::validate_tag_name(tag)?;
+                }
+            }
+            BulkEntryChange::PatchMetadata(patch) if patch.is_empty() => {
+                return Err(StorageError::ValidationError(
+                    "metadata patch contains no fields".to_string(),
+                ));
+            }
+            _ => {}
+        }
+        let conn = self.db_connection_pool().get().await?;
+        let results = conn
+            .interact(move |conn| {
+                conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                    use schema::entries::dsl as entries_dsl;
+                    let current: Map<EntryID, Vec<String>> = entries_dsl::entries
+                        .filter(entries_dsl::id.eq_any(&entry_ids))
+                        .select((entries_dsl::id, entries_dsl::tags))
+                        .for_update()
+                        .load::<(EntryID, Vec<String>)>(conn)?
+                        .into_iter()
+                        .collect();
+
+                    let mut results = Vec::with_capacity(entry_ids.len());
+                    for entry_id_ in entry_ids.iter().copied() {
+                        let Some(old_tags) = current.get(&entry_id_) else {
+                            results
+                                .push(BulkEntryResult::new(entry_id_, BulkEntryStatus::NotFound));
+                            continue;
+                        };
+                        let new_tags = match &change {
+                            BulkEntryChange::AddTags(add) => Some(
+                                old_tags
+                                    .iter()
+                                    .chain(add.iter())
+                                    .unique()
+                                    .cloned()
+                                    .collect::<Vec<String>>(),
+                            ),
+                            BulkEntryChange::RemoveTags(remove) => Some(
+                                old_tags
+                                    .iter()
+                                    .filter(|t| !remove.contains(t))
+                                    .cloned()
+                                    .collect::<Vec<String>>(),
+                            ),
+                            BulkEntryChange::PatchMetadata(_) => None,
+                        };
+                        let status = match (new_tags, &change) {
+                            (Some(tags), _) if &tags == old_tags => BulkEntryStatus::Unchanged,
+                            (Some(tags), _) => {
+                                diesel::update(entries_dsl::entries.find(entry_id_))
+                                    .set(entries_dsl::tags.eq(tags))
+                                    .execute(conn)?;
+                                BulkEntryStatus::Updated
+                            }
+                            (None, BulkEntryChange::PatchMetadata(patch)) => {
+                                diesel::update(entries_dsl::entries.find(entry_id_))
+                                    .set((patch.as_ref(), entries_dsl::updated_at.eq(Utc::now())))
+                                    .execute(conn)?;
+                                BulkEntryStatus::Updated
+                            }
+                            (None, _) => BulkEntryStatus::Unchanged,
+                        };
+                        results.push(BulkEntryResult::new(entry_id_, status));
+                    }
+                    Ok(results)
+                })
+            })
+            .await??;
+        debug!("Bulk update touched {} entries", results.len());
+        for result in results.iter() {
+            if result.status == BulkEntryStatus::Updated {
+                self.events.publish(CatalogEvent::MetadataUpdated {
+                    entry_id: result.entry_id,
+                });
+            }
+        }
+        Ok(results)
+    }
+
+    /// Alle Sammlungen mit Elementanzahl und neuester Snapshot-Version.
+    #[instrument]
+    pub async fn get_collections(
+        &self,
+        txid: TxID,
+    ) -> Result<Vec<CollectionSummary>, StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        let (collections, counts, latest) = conn
+            .interact(move |conn| {
+                use diesel::dsl::{count_star, max};
+                use schema::collection_items::dsl as items_dsl;
+                use schema::collection_snapshots::dsl as snapshots_dsl;
+                use schema::collections::dsl as collections_dsl;
+
+                let collections = collections_dsl::collections
+                    .order_by(collections_dsl::name.asc())
+                    .select(Collection::as_select())
+                    .load::<Collection>(conn)?;
+                let counts = items_dsl::collection_items
+                    .group_by(items_dsl::collection_id)
+                    .select((items_dsl::collection_id, count_star()))
+                    .load::<(CollectionID, i64)>(conn)?;
+                let latest = snapshots_dsl::collection_snapshots
+                    .group_by(snapshots_dsl::collection_id)
+                    .select((
+                        snapshots_dsl::collection_id,
+                        max(snapshots_dsl::snapshot_version),
+                    ))
+                    .load::<(CollectionID, Option<i32>)>(conn)?;
+                Ok::<_, diesel::result::Error>((collections, counts, latest))
+            })
+            .await??;
+        let counts: Map<CollectionID, i64> = counts.into_iter().collect();
+        let latest: Map<CollectionID, Option<i32>> = latest.into_iter().collect();
+        Ok(collections
+            .into_iter()
+            .map(|collection| CollectionSummary {
+                item_count: counts.get(&collection.id).copied().unwrap_or(0),
+                latest_snapshot_version: latest.get(&collection.id).copied().flatten(),
+                collection,
+            })
+            .collect())
+    }
+
+    #[instrument]
+    pub async fn get_collection(
+        &self,
+        collection_id: CollectionID,
+        txid: TxID,
+    ) -> Result<Option<CollectionDetails>, StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        let details = conn
+            .interact(move |conn| {
+                use schema::collection_items::dsl as items_dsl;
+                let Some(collection) = schema::collections::dsl::collections
+                    .find(collection_id)
+                    .select(Collection::as_select())
+                    .first::<Collection>(conn)
+                    .optional()?
+                else {
+                    return Ok(None);
+                };
+                let items = items_dsl::collection_items
+                    .filter(items_dsl::collection_id.eq(collection_id))
+                    .order_by(items_dsl::id.asc())
+                    .select(CollectionItem::as_select())
+                    .load::<CollectionItem>(conn)?;
+                Ok::<_, diesel::result::Error>(Some(CollectionDetails { collection, items }))
+            })
+            .await??;
+        Ok(details)
+    }
+
+    #[instrument]
+    pub async fn create_collection(
+        &self,
+        name: String,
+        description: Option<String>,
+        txid: TxID,
+    ) -> Result<Collection, StorageError> {
+        let name = validate_collection_name(name)?;
+        let conn = self.db_connection_pool().get().await?;
+        let n = name.clone();
+        let created = conn
+            .interact(move |conn| {
+                use schema::collections::dsl as collections_dsl;
+                diesel::insert_into(collections_dsl::collections)
+                    .values((
+                        collections_dsl::name.eq(&n),
+                        collections_dsl::description.eq(description),
+                    ))
+                    .on_conflict(collections_dsl::name)
+                    .do_nothing()
+                    .returning(Collection::as_returning())
+                    .get_result::<Collection>(conn)
+                    .optional()
+            })
+            .await??;
+        created.ok_or_else(|| {
+            StorageError::AlreadyExists(format!("collection '{name}' already exists"))
+        })
+    }
+
+    #[instrument]
+    pub async fn update_collection(
+        &self,
+        collection_id: CollectionID,
+        name: String,
+        description: Option<String>,
+        expected_version: Option<i64>,
+        txid: TxID,
+    ) -> Result<(), StorageError> {
+        let name = validate_collection_name(name)?;
+        let conn = self.db_connection_pool().get().await?;
+        let n = name.clone();
+        let rows = conn
+            .interact(move |conn| {
+                use schema::collections::dsl as collections_dsl;
+                let taken = collections_dsl::collections
+                    .filter(collections_dsl::name.eq(&n))
+                    .filter(collections_dsl::id.ne(collection_id))
+                    .count()
+                    .get_result::<i64>(conn)?;
+                if taken > 0 {
+                    return Ok(None);
+                }
+                let changes = (
+                    collections_dsl::name.eq(&n),
+                    collections_dsl::description.eq(&description),
+                    collections_dsl::updated_at.eq(Utc::now()),
+                );
+                match expected_version {
+                    Some(v) => diesel::update(
+                        collections_dsl::collections
+                            .filter(collections_dsl::id.eq(collection_id))
+                            .filter(collections_dsl::version.eq(v)),
+                    )
+                    .set(changes)
+                    .execute(conn),
+                    None => diesel::update(collections_dsl::collections.find(collection_id))
+                        .set(changes)
+                        .execute(conn),
+                }
+                .map(Some)
+            })
+            .await??;
+        let Some(rows) = rows else {
+            return Err(StorageError::AlreadyExists(format!(
+                "collection '{name}' already exists"
+            )));
+        };
+        if rows == 0 && expected_version.is_none() {
+            return Err(StorageError::NotFound(format!(
+                "collection {collection_id} not found"
+            )));
+        }
+        self.check_versioned_write(
+            rows,
+            expected_version,
+            "collection",
+            collection_id,
+            move |conn| collection_version(conn, collection_id),
+        )
+        .await
+    }
+
+    /// Löscht eine Sammlung. Sammlungen mit Snapshots bleiben erhalten, damit
+    /// eingefrorene Datensätze reproduzierbar bleiben.
+    #[instrument]
+    pub async fn delete_collection(
+        &self,
+        collection_id: CollectionID,
+        txid: TxID,
+    ) -> Result<(), StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        let (snapshots, rows) = conn
+            .interact(move |conn| {
+                conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                    use schema::collection_snapshots::dsl as snapshots_dsl;
+                    let snapshots = snapshots_dsl::collection_snapshots
+                        .filter(snapshots_dsl::collection_id.eq(collection_id))
+                        .count()
+                        .get_result::<i64>(conn)?;
+                    if snapshots > 0 {
+                        return Ok((snapshots, 0));
+                    }
+                    let rows =
+                        diesel::delete(schema::collections::dsl::collections.find(collection_id))
+                            .execute(conn)?;
+                    Ok((0, rows))
+                })
+            })
+            .await??;
+        if snapshots > 0 {
+            return Err(StorageError::ValidationError(format!(
+                "collection {collection_id} has {snapshots} frozen snapshot(s) and cannot be deleted"
+            )));
+        }
+        if rows == 0 {
+            return Err(StorageError::NotFound(format!(
+                "collection {collection_id} not found"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Fügt Einträge bzw. Sequenzen (`(entry_id, Some(sequence_id))`) hinzu.
+    /// Bereits enthaltene Elemente werden übersprungen.
+    #[instrument]
+    pub async fn add_collection_items(
+        &self,
+        collection_id: CollectionID,
+        items: Vec<(EntryID, Option<SequenceID>)>,
+        txid: TxID,
+    ) -> Result<Vec<CollectionItem>, StorageError> {
+        let items: Vec<(EntryID, Option<SequenceID>)> = items.into_iter().unique().collect();
+        if items.is_empty() {
+            return Err(StorageError::ValidationError("no items given".to_string()));
+        }
+        let conn = self.db_connection_pool().get().await?;
+        let added = conn
+            .interact(move |conn| {
+                conn.transaction::<_, StorageError, _>(|conn| {
+                    use schema::collection_items::dsl as items_dsl;
+                    use schema::sequences::dsl as sequences_dsl;
+                    if collection_version(conn, collection_id)?.is_none() {
+                        return Err(StorageError::NotFound(format!(
+                            "collection {collection_id} not found"
+                        )));
+                    }
+                    let entry_ids: Vec<EntryID> = items.iter().map(|(e, _)| *e).unique().collect();
+                    let known_entries: Vec<EntryID> = schema::entries::dsl::entries
+                        .filter(schema::entries::dsl::id.eq_any(&entry_ids))
+                        .select(schema::entries::dsl::id)
+                        .load(conn)?;
+                    let sequence_ids: Vec<SequenceID> =
+                        items.iter().filter_map(|(_, s)| *s).collect();
+                    let known_sequences: Vec<(SequenceID, EntryID)> = sequences_dsl::sequences
+                        .filter(sequences_dsl::id.eq_any(&sequence_ids))
+                        .select((sequences_dsl::id, sequences_dsl::entry_id))
+                        .load(conn)?;
+                    for (entry_id_, sequence_id) in items.iter() {
+                        if !known_entries.contains(entry_id_) {
+                            return Err(StorageError::NotFound(format!(
+                                "entry {entry_id_} not found"
+                            )));
+                        }
+                        if let Some(sid) = sequence_id
+                            && !known_sequences.contains(&(*sid, *entry_id_))
+                        {
+                            return Err(StorageError::NotFound(format!(
+                                "sequence {sid} not found for entry {entry_id_}"
+                            )));
+                        }
+                    }
+                    let rows: Vec<_> = items
+                        .iter()
+                        .map(|(entry_id_, sequence_id)| {
+                            (
+                                items_dsl::collection_id.eq(collection_id),
+                                items_dsl::entry_id.eq(*entry_id_),
+                                items_dsl::sequence_id.eq(*sequence_id),
+                            )
+                        })
+                        .collect();
+                    let added = diesel::insert_into(items_dsl::collection_items)
+                        .values(&rows)
+                        .on_conflict_do_nothing()
+                        .returning(CollectionItem::as_returning())
+                        .get_results::<CollectionItem>(conn)?;
+                    diesel::update(schema::collections::dsl::collections.find(collection_id))
+                        .set(schema::collections::dsl::updated_at.eq(Utc::now()))
+                        .execute(conn)?;
+                    Ok(added)
+                })
+            })
+            .await??;
+        Ok(added)
+    }
+
+    #[instrument]
+    pub async fn remove_collection_item(
+        &self,
+        collection_id: CollectionID,
+        item_id: i64,
+        txid: TxID,
+    ) -> Result<(), StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        let rows = conn
+            .interact(move |conn| {
+                use schema::collection_items::dsl as items_dsl;
+                diesel::delete(
+                    items_dsl::collection_items
+                        .filter(items_dsl::id.eq(item_id))
+                        .filter(items_dsl::collection_id.eq(collection_id)),
+                )
+                .execute(conn)
+            })
+            .await??;
+        if rows == 0 {
+            return Err(StorageError::NotFound(format!(
+                "item {item_id} not found in collection {collection_id}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Friert den aktuellen Stand einer Sammlung als neue Snapshot-Version ein.
+    ///
+    /// Pfade, Dateigrößen und Sequenzgrenzen werden kopiert, sodass spätere
+    /// Änderungen an Einträgen den Snapshot nicht verändern.
+    #[instrument]
+    pub async fn freeze_collection(
+        &self,
+        collection_id: CollectionID,
+        note: Option<String>,
+        txid: TxID,
+    ) -> Result<CollectionSnapshotDetails, StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        let details = conn
+            .interact(move |conn| {
+                conn.transaction::<_, StorageError, _>(|conn| {
+                    use diesel::dsl::max;
+                    use schema::collection_snapshot_items::dsl as si_dsl;
+                    use schema::collection_snapshots::dsl as snapshots_dsl;
+
+                    // sperrt die Sammlung, damit parallele Freezes nicht dieselbe Version vergeben
+                    let Some(collection) = schema::collections::dsl::collections
+                        .find(collection_id)
+                        .select(Collection::as_select())
+                        .for_update()
+                        .first::<Collection>(conn)
+                        .optional()?
+                    else {
+                        return Err(StorageError::NotFound(format!(
+                            "collection {collection_id} not found"
+                        )));
+                    };
+                    let items = current_collection_items(conn, collection_id)?;
+                    if items.is_empty() {
+                        return Err(StorageError::ValidationError(format!(
+                            "collection {collection_id} is empty"
+                        )));
+                    }
+                    let latest = snapshots_dsl::collection_snapshots
+                        .filter(snapshots_dsl::collection_id.eq(collection_id))
+                        .select(max(snapshots_dsl::snapshot_version))
+                        .first::<Option<i32>>(conn)?;
+                    let snapshot = diesel::insert_into(snapshots_dsl::collection_snapshots)
+                        .values((
+                            snapshots_dsl::collection_id.eq(collection_id),
+                            snapshots_dsl::snapshot_version.eq(latest.unwrap_or(0) + 1),
+                            snapshots_dsl::note.eq(note),
+                            snapshots_dsl::item_count.eq(items.len() as i32),
+                            snapshots_dsl::total_size.eq(items.iter().map(|i| i.size).sum::<i64>()),
+                        ))
+                        .returning(CollectionSnapshot::as_returning())
+                        .get_result::<CollectionSnapshot>(conn)?;
+                    let rows: Vec<_> = items
+                        .iter()
+                        .map(|item| {
+                            (
+                                si_dsl::snapshot_id.eq(snapshot.id),
+                                si_dsl::entry_id.eq(item.entry_id),
+                                si_dsl::entry_name.eq(&item.entry_name),
+                                si_dsl::path.eq(&item.path),
+                                si_dsl::size.eq(item.size),
+                                si_dsl::sequence_id.eq(item.sequence_id),
+                                si_dsl::sequence_name.eq(&item.sequence_name),
+                                si_dsl::start_timestamp.eq(item.start_timestamp),
+                                si_dsl::end_timestamp.eq(item.end_timestamp),
+                            )
+                        })
+                        .collect();
+                    for chunk in rows.chunks(SNAPSHOT_ITEMS_PER_INSERT) {
+                        diesel::insert_into(si_dsl::collection_snapshot_items)
+                            .values(chunk)
+                            .execute(conn)?;
+                    }
+                    Ok(CollectionSnapshotDetails {
+                        collection_name: collection.name,
+                        snapshot,
+                        items,
+                    })
+                })
+            })
+            .await??;
+        info!(
+            "Froze collection {} as snapshot version {}",
+            collection_id, details.snapshot.snapshot_version
+        );
+        Ok(details)
+    }
+
+    #[instrument]
+    pub async fn get_collection_snapshots(
+        &self,
+        collection_id: CollectionID,
+        txid: TxID,
+    ) -> Result<Vec<CollectionSnapshot>, StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        let snapshots = conn
+            .interact(move |conn| {
+                use schema::collection_snapshots::dsl as snapshots_dsl;
+                snapshots_dsl::collection_snapshots
+                    .filter(snapshots_dsl::collection_id.eq(collection_id))
+                    .order_by(snapshots_dsl::snapshot_version.asc())
+                    .select(CollectionSnapshot::as_select())
+                    .load::<CollectionSnapshot>(conn)
+            })
+            .await??;
+        Ok(snapshots)
+    }
+
+    #[instrument]
+    pub async fn get_collection_snapshot(
+        &self,
+        collection_id: CollectionID,
+        snapshot_version: i32,
+        txid: TxID,
+    ) -> Result<Option<CollectionSnapshotDetails>, StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        let details = conn
+            .interact(move |conn| {
+                use schema::collection_snapshot_items::dsl as si_dsl;
+                use schema::collection_snapshots::dsl as snapshots_dsl;
+                let found = snapshots_dsl::collection_snapshots
+                    .inner_join(schema::collections::dsl::collections)
+                    .filter(snapshots_dsl::collection_id.eq(collection_id))
+                    .filter(snapshots_dsl::snapshot_version.eq(snapshot_version))
+                    .select((
+                        CollectionSnapshot::as_select(),
+                        schema::collections::dsl::name,
+                    ))
+                    .first::<(CollectionSnapshot, String)>(conn)
+                    .optional()?;
+                let Some((snapshot, collection_name)) = found else {
+                    return Ok(None);
+                };
+                let items = si_dsl::collection_snapshot_items
+                    .filter(si_dsl::snapshot_id.eq(snapshot.id))
+                    .order_by(si_dsl::id.asc())
+                    .select(CollectionSnapshotItem::as_select())
+                    .load::<CollectionSnapshotItem>(conn)?;
+                Ok::<_, diesel::result::Error>(Some(CollectionSnapshotDetails {
+                    collection_name,
+                    snapshot,
+                    items,
+                }))
+            })
+            .await??;
+        Ok(details)
+    }
+
+    /// Vergleicht zwei Stände einer Sammlung.
+    ///
+    /// Ohne `from` wird der neueste Snapshot genommen, ohne `to` der aktuelle
+    /// (nicht eingefrorene) Stand – also "was hat sich seit dem letzten Freeze
+    /// geändert".
+    #[instrument]
+    pub async fn diff_collection(
+        &self,
+        collection_id: CollectionID,
+        from: Option<i32>,
+        to: Option<i32>,
+        txid: TxID,
+    ) -> Result<collections::CollectionDiff, StorageError> {
+        if self.get_collection(collection_id, txid).await?.is_none() {
+            return Err(StorageError::NotFound(format!(
+                "collection {collection_id} not found"
+            )));
+        }
+        let from = match from {
+            Some(v) => Some(v),
+            None => self
+                .get_collection_snapshots(collection_id, txid)
+                .await?
+                .last()
+                .map(|s| s.snapshot_version),
+        };
+        let from_items = match from {
+            Some(v) => self.snapshot_items(collection_id, v, txid).await?,
+            None => Vec::new(),
+        };
+        let to_items = match to {
+            Some(v) => self.snapshot_items(collection_id, v, txid).await?,
+            None => {
+                let conn = self.db_connection_pool().get().await?;
+                conn.interact(move |conn| current_collection_items(conn, collection_id))
+                    .await??
+            }
+        };
+        Ok(collections::CollectionDiff {
+            from_version: from,
+            to_version: to,
+            ..collections::diff_items(&from_items, &to_items)
+        })
+    }
+
+    async fn snapshot_items(
+        &self,
+        collection_id: CollectionID,
+        snapshot_version: i32,
+        txid: TxID,
+    ) -> Result<Vec<CollectionSnapshotItem>, StorageError> {
+        self.get_collection_snapshot(collection_id, snapshot_version, txid)
+            .await?
+            .map(|d| d.items)
+            .ok_or_else(|| {
+                StorageError::NotFound(format!(
+                    "snapshot {snapshot_version} of collection {collection_id} not found"
+                ))
+            })
+    }
+
+    /// Manifestzeilen für `entries` (Reihenfolge bleibt erhalten); Topics,
+    /// Sensoren und Sequenzen werden je Aufruf mit drei Abfragen geladen.
+    #[instrument(skip(entries))]
+    pub async fn get_manifest_rows(
+        &self,
+        entries: Vec<Entry>,
+        txid: TxID,
+    ) -> Result<Vec<manifest::ManifestRow>, StorageError> {
+        let ids: Vec<EntryID> = entries.iter().map(|e| e.id).collect();
+        let conn = self.db_connection_pool().get().await?;
+        let (topics, sensors, sequences) = conn
+            .interact(move |conn| {
+                use schema::sensors::dsl as sensors_dsl;
+                use schema::sequences::dsl as sequences_dsl;
+                use schema::topics::dsl as topics_dsl;
+                let topics = topics_dsl::topics
+                    .filter(topics_dsl::entry_id.eq_any(&ids))
+                    .select(crate::storage::models::Topic::as_select())
+                    .load::<crate::storage::models::Topic>(conn)?;
+                let sensors = sensors_dsl::sensors
+                    .filter(sensors_dsl::entry_id.eq_any(&ids))
+                    .select(Sensor::as_select())
+                    .load::<Sensor>(conn)?;
+                let sequences = sequences_dsl::sequences
+                    .filter(sequences_dsl::entry_id.eq_any(&ids))
+                    .select(Sequence::as_select())
+                    .load::<Sequence>(conn)?;
+                Ok::<_, diesel::result::Error>((topics, sensors, sequences))
+            })
+            .await??;
+        let topics = topics.into_iter().into_group_map_by(|t| t.entry_id);
+        let sensors = sensors.into_iter().into_group_map_by(|s| s.entry_id);
+        let sequences = sequences.into_iter().into_group_map_by(|s| s.entry_id);
+        Ok(entries
+            .into_iter()
+            .map(|entry| {
+                let id = entry.id;
+                manifest::ManifestRow::new(
+                    entry,
+                    topics.get(&id).map(Vec::as_slice).unwrap_or_default(),
+                    sensors.get(&id).map(Vec::as_slice).unwrap_or_default(),
+                    sequences.get(&id).map(Vec::as_slice).unwrap_or_default(),
+                )
+            })
+            .collect())
+    }
+
+    /// Spielt eingelesene Import-Zeilen ein.
+    ///
+    /// Alle Zeilen werden zuerst geprüft ([`StorageManager::plan_metadata_import`]).
+    /// Nur wenn keine Zeile ungültig ist und `dry_run` nicht gesetzt ist, wird
+    /// geschrieben ([`StorageManager::apply_metadata_import`]).
+    #[instrument(skip(rows))]
+    pub async fn import_metadata(
+        &self,
+        rows: Vec<metadata_import::ParsedRow>,
+        dry_run: bool,
+        txid: TxID,
+    ) -> Result<metadata_import::MetadataImportReport, StorageError> {
+        let plan = self.plan_metadata_import(rows, dry_run, txid).await?;
+        self.apply_metadata_import(plan, txid).await
+    }
+
+    /// Prüft Import-Zeilen, ohne zu schreiben: Eintrag vorhanden und eindeutig,
+    /// Tags, Sequenzgrenzen. Der Plan merkt sich die gelesene Version jedes
+    /// Eintrags.
+    #[instrument(skip(rows))]
+    pub async fn plan_metadata_import(
+        &self,
+        rows: Vec<metadata_import::ParsedRow>,
+        dry_run: bool,
+        txid: TxID,
+    ) -> Result<metadata_import::MetadataImportPlan, StorageError> {
+        use metadata_import::{ImportKey, ImportRowReport, ImportRowStatus};
+
+        let conn = self.db_connection_pool().get().await?;
+        let (report, writes) = conn
+            .interact(move |conn| {
+                conn.transaction::<_, StorageError, _>(|conn| {
+                    use schema::entries::dsl as entries_dsl;
+
+                    let mut paths = Vec::new();
+                    let mut names = Vec::new();
+                    for row in rows.iter().flatten() {
+                        match &row.key {
+                            ImportKey::Path(p) => paths.push(p.clone()),
+                            ImportKey::Name(n) => names.push(n.clone()),
+                        }
+                    }
+                    let candidates = entries_dsl::entries
+                        .filter(
+                            entries_dsl::path
+                                .eq_any(&paths)
+                                .or(entries_dsl::name.eq_any(&names)),
+                        )
+                        .select(Entry::as_select())
+                        .load::<Entry>(conn)?;
+
+                    let mut reports = Vec::with_capacity(rows.len());
+                    let mut plan = Vec::new();
+                    for parsed in rows {
+                        let row = match parsed {
+                            Ok(row) => row,
+                            Err(e) => {
+                                reports.push(ImportRowReport::invalid(e));
+                                continue;
+                            }
+                        };
+                        let matches: Vec<&Entry> = candidates
+                            .iter()
+                            .filter(|e| match &row.key {
+                                ImportKey::Path(p) => &e.path == p,
+                                ImportKey::Name(n) => &e.name == n,
+                            })
+                            .collect();
+                        let message = |e: StorageError| match e {
+                            StorageError::ValidationError(m) => m,
+                            other => format!("{other:?}"),
+                        };
+                        let mut errors = Vec::new();
+                        let entry = match matches.as_slice() {
+                            [entry] => Some(*entry),
+                            [] => {
+                                errors.push(format!("no entry with {}", row.key));
+                                None
+                            }
+                            _ => {
+                                errors.push(format!(
+                                    "{} entries with {}, use the path instead",
+                                    matches.len(),
+                                    row.key
+                                ));
+                                None
+                            }
+                        };
+                        let sequence_tags = row.sequences.iter().flat_map(|s| s.tags.iter());
+                        for tag in row.tags.iter().chain(sequence_tags) {
+                            if let Err(e) = tags::validate_tag_name(tag) {
+                                errors.push(message(e));
+                            }
+                        }
+                        if let Some(entry) = entry {
+                            for s in row.sequences.iter() {
+                                if let Err(e) = timeline::validate_sequence_range(
+                                    s.start_timestamp,
+                                    s.end_timestamp,
+                                    (entry.mcap_start_ns, entry.mcap_end_ns),
+                                ) {
+                                    errors.push(format!("sequence '{}': {}", s.name, message(e)));
+                                }
+                            }
+                        }
+                        let fields = metadata_import::patch_fields(&row.metadata);
+                        if fields.is_empty() && row.tags.is_empty() && row.sequences.is_empty() {
+                            errors.push("row changes nothing".to_string());
+                        }
+                        reports.push(ImportRowReport {
+                            row: row.row,
+                            key: Some(row.key.clone()),
+                            entry_id: entry.map(|e| e.id),
+                            status: if errors.is_empty() {
+                                ImportRowStatus::Valid
+                            } else {
+                                ImportRowStatus::Invalid
+                            },
+                            fields,
+                            tags: row.tags.clone(),
+                            sequences: row.sequences.iter().map(|s| s.name.clone()).collect(),
+                            errors,
+                        });
+                        if let Some(entry) = entry {
+                            plan.push((entry.clone(), row));
+                        }
+                    }
+
+                    let invalid = reports
+                        .iter()
+                        .filter(|r| r.status == ImportRowStatus::Invalid)
+                        .count();
+                    let report = metadata_import::MetadataImportReport {
+                        dry_run,
+                        applied: invalid == 0 && !dry_run,
+                        total: reports.len(),
+                        invalid,
+                        rows: reports,
+                    };
+                    Ok((report, plan))
+                })
+            })
+            .await??;
+        Ok(metadata_import::MetadataImportPlan { report, writes })
+    }
+
+    /// Schreibt einen geprüften Import in einer Transaktion: die Metadaten je
+    /// Eintrag mit der bei der Prüfung gelesenen Version (wurde ein Eintrag
+    /// inzwischen geändert, bricht der Import mit `PreconditionFailed` ab, ohne
+    /// etwas zu schreiben), danach Tags und Sequenzen. Bereits vorhandene Tags und
+    /// gleichnamige Sequenzen mit identischem Zeitfenster werden übersprungen,
+    /// damit ein Import wiederholbar ist. `MetadataUpdated` folgt erst nach dem
+    /// Commit.
+    #[instrument(skip(plan))]
+    pub async fn apply_metadata_import(
+        &self,
+        plan: metadata_import::MetadataImportPlan,
+        txid: TxID,
+    ) -> Result<metadata_import::MetadataImportReport, StorageError> {
+        use metadata_import::ImportRowStatus;
+
+        let metadata_import::MetadataImportPlan { mut report, writes } = plan;
+        if !report.applied {
+            return Ok(report);
+        }
+
+        // Metadaten: alle Zeilen eines Eintrags zu einem Patch zusammenfassen.
+        let mut metadata: Vec<(Entry, EntryMetadataPatch)> = Vec::new();
+        for (entry, row) in writes.iter() {
+            if row.metadata.is_empty() {
+                continue;
+            }
+            match metadata.iter_mut().find(|(e, _)| e.id == entry.id) {
+                Some((_, patch)) => *patch = std::mem::take(patch).overlay(&row.metadata),
+                None => metadata.push((entry.clone(), row.metadata.clone())),
+            }
+        }
+
+        // Metadaten, Tags und Sequenzen in einer Transaktion: ist ein Eintrag seit
+        // der Prüfung geändert worden, bleibt der ganze Import ungeschrieben.
+        let conn = self.db_connection_pool().get().await?;
+        conn.interact(move |conn| {
+            conn.transaction::<_, StorageError, _>(|conn| {
+                use schema::entries::dsl as entries_dsl;
+                use schema::sequences::dsl as sequences_dsl;
+
+                for (entry, patch) in metadata.iter() {
+                    let web = metadata_import::metadata_with_patch(entry, patch);
+                    update_entry_metadata(conn, entry.id, &web, Some(entry.version))?;
+                }
+                let now = Utc::now();
+                for (entry, row) in writes {
+                    let entry_id_ = entry.id;
+                    // neu laden: frühere Zeilen können dieselben Tags ergänzen
+                    let mut tags = entries_dsl::entries
+                        .find(entry_id_)
+                        .select(entries_dsl::tags)
+                        .first::<Vec<Tag>>(conn)?;
+                    let before = tags.len();
+                    for tag in row.tags {
+                        if !tags.contains(&tag) {
+                            tags.push(tag);
+                        }
+                    }
+                    if tags.len() != before {
+                        diesel::update(entries_dsl::entries.find(entry_id_))
+                            .set(entries_dsl::tags.eq(tags))
+                            .execute(conn)?;
+                    }
+                    for s in row.sequences {
+                        let exists = sequences_dsl::sequences
+                            .filter(sequences_dsl::entry_id.eq(entry_id_))
+                            .filter(sequences_dsl::name.eq(&s.name))
+                            .filter(sequences_dsl::start_timestamp.eq(s.start_timestamp))
+                            .filter(sequences_dsl::end_timestamp.eq(s.end_timestamp))
+                            .count()
+                            .get_result::<i64>(conn)?;
+                        if exists > 0 {
+                            continue;
+                        }
+                        diesel::insert_into(sequences_dsl::sequences)
+                            .values((
+                                sequences_dsl::entry_id.eq(entry_id_),
+                                sequences_dsl::name.eq(s.name),
+                                sequences_dsl::description.eq(s.description),
+                                sequences_dsl::start_timestamp.eq(s.start_timestamp),
+                                sequences_dsl::end_timestamp.eq(s.end_timestamp),
+                                sequences_dsl::created_at.eq(now),
+                                sequences_dsl::updated_at.eq(now),
+                                sequences_dsl::tags.eq(s.tags),
+                            ))
+                            .execute(conn)?;
+                    }
+                }
+                Ok(())
+            })
+        })
+        .await??;
+        for row in report.rows.iter_mut() {
+            row.status = ImportRowStatus::Applied;
+        }
+
+        info!("Imported metadata for {} rows", report.total);
+        for entry_id_ in report.rows.iter().filter_map(|r| r.entry_id).unique() {
+            self.events.publish(CatalogEvent::MetadataUpdated {
+                entry_id: entry_id_,
+            });
+        }
+        Ok(report)
+    }
+
+    /// Alle Benutzerkonten, nach Namen sortiert.
+    #[instrument]
+    pub async fn get_users(&self, txid: TxID) -> Result<Vec<User>, StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        let users = conn
+            .interact(move |conn| {
+                use schema::users::dsl as users_dsl;
+                users_dsl::users
+                    .order(users_dsl::username.asc())
+                    .select(User::as_select())
+                    .load::<User>(conn)
+            })
+            .await??;
+        Ok(users)
+    }
+
+    #[instrument]
+    pub async fn get_user(&self, user_id: UserID, txid: TxID) -> Result<User, StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        let user = conn
+            .interact(move |conn| {
+                use schema::users::dsl as users_dsl;
+                users_dsl::users
+                    .find(user_id)
+                    .select(User::as_select())
+                    .first::<User>(conn)
+                    .optional()
+            })
+            .await??;
+        user.ok_or_else(|| StorageError::NotFound(format!("user {user_id} not found")))
+    }
+
+    #[instrument(skip(password))]
+    pub async fn create_user(
+        &self,
+        username: String,
+        password: String,
+        role: Role,
+        txid: TxID,
+    ) -> Result<User, StorageError> {
+        auth::validate_username(&username)?;
+        auth::validate_password(&password)?;
+        let conn = self.db_connection_pool().get().await?;
+        let name = username.clone();
+        let created = conn
+            .interact(move |conn| {
+                use schema::users::dsl as users_dsl;
+                let password_hash = auth::hash_password(&password)?;
+                diesel::insert_into(users_dsl::users)
+                    .values((
+                        users_dsl::username.eq(&name),
+                        users_dsl::password_hash.eq(password_hash),
+                        users_dsl::role.eq(role.as_str()),
+                    ))
+                    .on_conflict(users_dsl::username)
+                    .do_nothing()
+                    .returning(User::as_returning())
+                    .get_result::<User>(conn)
+                    .optional()
+                    .map_err(StorageError::from)
+            })
+            .await??;
+        let user = created.ok_or_else(|| {
+            StorageError::AlreadyExists(format!("user '{username}' already exists"))
+        })?;
+        info!("Created user '{}' with role {}", user.username, user.role);
+        Ok(user)
+    }
+
+    /// Ändert Rolle, Passwort oder Sperre eines Kontos.
+    ///
+    /// Ein neues Passwort oder eine Sperre beendet alle Sitzungen des Kontos. Der
+    /// letzte aktive Admin kann weder herabgestuft noch gesperrt werden.
+    #[instrument(skip(password))]
+    pub async fn update_user(
+        &self,
+        user_id: UserID,
+        role: Option<Role>,
+        password: Option<String>,
+        disabled: Option<bool>,
+        expected_version: Option<i64>,
+        txid: TxID,
+    ) -> Result<User, StorageError> {
+        if let Some(password) = password.as_deref() {
+            auth::validate_password(password)?;
+        }
+        let conn = self.db_connection_pool().get().await?;
+        let user = conn
+            .interact(move |conn| {
+                conn.transaction::<_, StorageError, _>(|conn| {
+                    use schema::sessions::dsl as sessions_dsl;
+                    use schema::users::dsl as users_dsl;
+                    let user = users_dsl::users
+                        .find(user_id)
+                        .select(User::as_select())
+                        .for_update()
+                        .first::<User>(conn)
+                        .optional()?
+                        .ok_or_else(|| {
+                            StorageError::NotFound(format!("user {user_id} not found"))
+                        })?;
+                    if let Some(expected) = expected_version
+                        && expected != user.version
+                    {
+                        return Err(StorageError::PreconditionFailed(format!(
+                            "user {user_id} has version {}, but version {expected} was expected",
+                            user.version
+                        )));
+                    }
+                    let role = role.map(|r| r.as_str().to_string()).unwrap_or(user.role);
+                    let disabled = disabled.unwrap_or(user.disabled);
+                    if role != Role::Admin.as_str() || disabled {
+                        ensure_other_admin(conn, user_id)?;
+                    }
+                    let password_hash = match password.as_deref() {
+                        Some(p) => auth::hash_password(p)?,
+                        None => user.password_hash,
+                    };
+                    if password.is_some() || disabled {
+                        diesel::delete(
+                            sessions_dsl::sessions.filter(sessions_dsl::user_id.eq(user_id)),
+                        )
+                        .execute(conn)?;
+                    }
+                    diesel::update(users_dsl::users.find(user_id))
+                        .set((
+                            users_dsl::role.eq(role),
+                            users_dsl::disabled.eq(disabled),
+                            users_dsl::password_hash.eq(password_hash),
+                            users_dsl::updated_at.eq(Utc::now()),
+                        ))
+                        .returning(User::as_returning())
+                        .get_result::<User>(conn)
+                        .map_err(StorageError::from)
+                })
+            })
+            .await??;
+        Ok(user)
+    }
+
+    /// Löscht ein Konto samt Sitzungen; der letzte aktive Admin bleibt erhalten.
+    #[instrument]
+    pub async fn delete_user(&self, user_id: UserID, txid: TxID) -> Result<(), StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        conn.interact(move |conn| {
+            conn.transaction::<_, StorageError, _>(|conn| {
+                use schema::users::dsl as users_dsl;
+                let exists = users_dsl::users
+                    .find(user_id)
+                    .select(users_dsl::id)
+                    .for_update()
+                    .first::<UserID>(conn)
+                    .optional()?;
+                if exists.is_none() {
+                    return Err(StorageError::NotFound(format!("user {user_id} not found")));
+                }
+                ensure_other_admin(conn, user_id)?;
+                diesel::delete(users_dsl::users.find(user_id)).execute(conn)?;
+                Ok(())
+            })
+        })
+        .await??;
+        Ok(())
+    }
+
+    /// Legt beim ersten Start ein Admin-Konto an, solange es noch keine Benutzer
+    /// gibt. Liefert `true`, wenn ein Konto angelegt wurde.
+    #[instrument(skip(password))]
+    pub async fn ensure_initial_admin(
+        &self,
+        username: String,
+        password: String,
+    ) -> Result<bool, StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        let count = conn
+            .interact(move |conn| {
+                use schema::users::dsl as users_dsl;
+                users_dsl::users.count().get_result::<i64>(conn)
+            })
+            .await??;
+        if count > 0 {
+            return Ok(false);
+        }
+        self.create_user(username, password, Role::Admin, 0).await?;
+        Ok(true)
+    }
+
+    /// Meldet einen Benutzer an und legt eine Sitzung an.
+    ///
+    /// Liefert `None` bei unbekanntem Namen, falschem Passwort oder gesperrtem
+    /// Konto, ohne die Fälle zu unterscheiden. Abgelaufene Sitzungen werden dabei
+    /// aufgeräumt.
+    #[instrument(skip(password))]
+    pub async fn login(
+        &self,
+        username: String,
+        password: String,
+    ) -> Result<Option<Session>, StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        let session = conn
+            .interact(move |conn| {
+                use schema::sessions::dsl as sessions_dsl;
+                use schema::users::dsl as users_dsl;
+                let now = Utc::now();
+                diesel::delete(sessions_dsl::sessions.filter(sessions_dsl::expires_at.le(now)))
+                    .execute(conn)?;
+                let user = users_dsl::users
+                    .filter(users_dsl::username.eq(&username))
+                    .select(User::as_select())
+                    .first::<User>(conn)
+                    .optional()?;
+                let user = match user {
+                    Some(user) if auth::verify_password(&password, &user.password_hash) => user,
+                    Some(_) => return Ok(None),
+                    None => {
+                        auth::verify_dummy_password(&password);
+                        return Ok(None);
+                    }
+                };
+                if user.disabled {
+                    return Ok(None);
+                }
+                let token = auth::generate_token();
+                let expires_at = now + auth::SESSION_TTL;
+                diesel::insert_into(sessions_dsl::sessions)
+                    .values((
+                        sessions_dsl::token_hash.eq(auth::token_hash(&token)),
+                        sessions_dsl::user_id.eq(user.id),
+                        sessions_dsl::expires_at.eq(expires_at),
+                    ))
+                    .execute(conn)?;
+                Ok::<_, diesel::result::Error>(Some(Session {
+                    token,
+                    expires_at,
+                    user,
+                }))
+            })
+            .await??;
+        Ok(session)
+    }
+
+    /// Benutzer zu einem gültigen, nicht abgelaufenen Token eines aktiven Kontos.
+    pub async fn authenticate(&self, token: String) -> Result<Option<User>, StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        let user = conn
+            .interact(move |conn| {
+                use schema::sessions::dsl as sessions_dsl;
+                use schema::users::dsl as users_dsl;
+                sessions_dsl::sessions
+                    .inner_join(users_dsl::users)
+                    .filter(sessions_dsl::token_hash.eq(auth::token_hash(&token)))
+                    .filter(sessions_dsl::expires_at.gt(Utc::now()))
+                    .filter(users_dsl::disabled.eq(false))
+                    .select(User::as_select())
+                    .first::<User>(conn)
+                    .optional()
+            })
+            .await??;
+        Ok(user)
+    }
+
+    /// Beendet die Sitzung zu `token`; unbekannte Tokens werden ignoriert.
+    pub async fn logout(&self, token: String) -> Result<(), StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        conn.interact(move |conn| {
+            use schema::sessions::dsl as sessions_dsl;
+            diesel::delete(sessions_dsl::sessions.find(auth::token_hash(&token))).execute(conn)
+        })
+        .await??;
+        Ok(())
+    }
+
+    #[instrument]
+    pub async fn get_api_keys(&self, txid: TxID) -> Result<Vec<ApiKey>, StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        let keys = conn
+            .interact(move |conn| {
+                use schema::api_keys::dsl as keys_dsl;
+                keys_dsl::api_keys
+                    .order(keys_dsl::id.asc())
+                    .select(ApiKey::as_select())
+                    .load::<ApiKey>(conn)
+            })
+            .await??;
+        Ok(keys)
+    }
+
+    /// Legt einen API-Schlüssel an. Der Schlüssel selbst steht nur in der Antwort.
+    #[instrument]
+    pub async fn create_api_key(
+        &self,
+        name: String,
+        scopes: Vec<Scope>,
+        expires_at: Option<DateTime<Utc>>,
+        created_by: Option<UserID>,
+        txid: TxID,
+    ) -> Result<NewApiKey, StorageError> {
+        if name.trim().is_empty() {
+            return Err(StorageError::ValidationError(
+                "api key name must not be empty".to_string(),
+            ));
+        }
+        if scopes.is_empty() {
+            return Err(StorageError::ValidationError(
+                "api key needs at least one scope".to_string(),
+            ));
+        }
+        if let Some(expires_at) = expires_at
+            && expires_at <= Utc::now()
+        {
+            return Err(StorageError::ValidationError(format!(
+                "expires_at {expires_at} is in the past"
+            )));
+        }
+        let scopes: Vec<String> = scopes
+            .into_iter()
+            .sorted()
+            .dedup()
+            .map(|s| s.as_str().to_string())
+            .collect();
+        let key = auth::generate_api_key();
+        let key_hash = auth::token_hash(&key);
+        let key_prefix = key[..auth::API_KEY_DISPLAY_LENGTH].to_string();
+        let conn = self.db_connection_pool().get().await?;
+        let api_key = conn
+            .interact(move |conn| {
+                use schema::api_keys::dsl as keys_dsl;
+                diesel::insert_into(keys_dsl::api_keys)
+                    .values((
+                        keys_dsl::name.eq(name),
+                        keys_dsl::key_hash.eq(key_hash),
+                        keys_dsl::key_prefix.eq(key_prefix),
+                        keys_dsl::scopes.eq(scopes),
+                        keys_dsl::created_by.eq(created_by),
+                        keys_dsl::expires_at.eq(expires_at),
+                    ))
+                    .returning(ApiKey::as_returning())
+                    .get_result::<ApiKey>(conn)
+            })
+            .await??;
+        info!(
+            "Created api key {} '{}' with scopes {:?}",
+            api_key.id, api_key.name, api_key.scopes
+        );
+        Ok(NewApiKey { key, api_key })
+    }
+
+    /// Widerruft einen Schlüssel; er ist danach sofort ungültig.
+    #[instrument]
+    pub async fn delete_api_key(&self, key_id: ApiKeyID, txid: TxID) -> Result<(), StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        let deleted = conn
+            .interact(move |conn| {
+                use schema::api_keys::dsl as keys_dsl;
+                diesel::delete(keys_dsl::api_keys.find(key_id)).execute(conn)
+            })
+            .await??;
+        if deleted == 0 {
+            return Err(StorageError::NotFound(format!(
+                "api key {key_id} not found"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Schlüssel zu `key`, sofern er existiert und nicht abgelaufen ist; setzt
+    /// dabei `last_used_at`.
+    pub async fn authenticate_api_key(&self, key: String) -> Result<Option<ApiKey>, StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        let api_key = conn
+            .interact(move |conn| {
+                use schema::api_keys::dsl as keys_dsl;
+                let now = Utc::now();
+                diesel::update(
+                    keys_dsl::api_keys
+                        .filter(keys_dsl::key_hash.eq(auth::token_hash(&key)))
+                        .filter(
+                            keys_dsl::expires_at
+                                .is_null()
+                                .or(keys_dsl::expires_at.gt(now)),
+                        ),
+                )
+                .set(keys_dsl::last_used_at.eq(now))
+                .returning(ApiKey::as_returning())
+                .get_result::<ApiKey>(conn)
+                .optional()
+            })
+            .await??;
+        Ok(api_key)
+    }
+
+    /// Schreibt einen Eintrag ins Audit-Log.
+    pub async fn record_audit(&self, record: NewAuditRecord) -> Result<(), StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        conn.interact(move |conn| {
+            use schema::audit_log::dsl as audit_dsl;
+            diesel::insert_into(audit_dsl::audit_log)
+                .values(&record)
                 .execute(conn)
-        }).await??;
-        debug!("Added tag for entry_id {}", entry_id_);
+        })
+        .await??;
         Ok(())
     }
 
+    /// Audit-Einträge, neueste zuerst, mit Seitenzahl wie bei `get_entries`.
     #[instrument]
-    pub async fn remove_tag(
+    pub async fn get_audit_log(
         &self,
-        entry_id_: EntryID,
-        tag: Tag,
+        filter: AuditFilter,
+        page: Option<u32>,
+        page_size: Option<u32>,
         txid: TxID,
-    ) -> Result<(), StorageError> {
+    ) -> Result<(Vec<AuditRecord>, u32), StorageError> {
+        let page = page.unwrap_or(0);
+        let page_size = page_size
+            .filter(|&ps| ps > 0)
+            .unwrap_or(audit::DEFAULT_PAGE_SIZE);
+        let conn = self.db_connection_pool().get().await?;
+        let (records, total) = conn
+            .interact(move |conn| {
+                use schema::audit_log::dsl as audit_dsl;
+                let filtered =
+                    || {
+                        let mut query = audit_dsl::audit_log.into_boxed();
+                        if let Some(actor) = filter.actor.as_ref() {
+                            query =
+                                query.filter(audit_dsl::actor.eq(actor.clone()).or(
+                                    audit_dsl::actor.like(format!("{}:%", escape_like(actor))),
+                                ));
+                        }
+                        if let Some(target) = filter.target.as_ref() {
+                            query =
+                                query.filter(audit_dsl::path.eq(target.clone()).or(
+                                    audit_dsl::path.like(format!("{}/%", escape_like(target))),
+                                ));
+                        }
+                        if let Some(since) = filter.since {
+                            query = query.filter(audit_dsl::at.ge(since));
+                        }
+                        if let Some(until) = filter.until {
+                            query = query.filter(audit_dsl::at.le(until));
+                        }
+                        query
+                    };
+                let total: i64 = filtered().count().get_result(conn)?;
+                let records = filtered()
+                    .order_by(audit_dsl::at.desc())
+                    .then_order_by(audit_dsl::id.desc())
+                    .offset(i64::from(page).saturating_mul(i64::from(page_size)))
+                    .limit(i64::from(page_size))
+                    .select(AuditRecord::as_select())
+                    .load::<AuditRecord>(conn)?;
+                Ok::<_, diesel::result::Error>((records, total))
+            })
+            .await??;
+        let num_pages = (total as f64 / page_size as f64).ceil() as u32;
+        Ok((records, num_pages))
+    }
+
+    /// Legt den Lauf einer wartenden oder gerade gestarteten Plugin-Instanz an.
+    ///
+    /// Ein wartender Lauf (`Queued`) ändert eine vorhandene Zeile nie; ein
+    /// gestarteter übernimmt eine wartende Zeile mit neuem `started_at`.
+    #[instrument]
+    pub async fn insert_plugin_run(&self, run: NewPluginRun) -> Result<(), StorageError> {
+        let queued = runs::state_name(InstanceState::Queued);
         let conn = self.db_connection_pool().get().await?;
-        let t = tag.clone();
         conn.interact(move |conn| {
-            diesel::sql_query("UPDATE entries SET tags = array_remove(tags, $1) WHERE id = $2")
-                .bind::<diesel::sql_types::Text, _>(t)
-                .bind::<diesel::sql_types::BigInt, _>(entry_id_)
+            use schema::plugin_runs::dsl as runs_dsl;
+            conn.transaction(|conn| {
+                let inserted = diesel::insert_into(runs_dsl::plugin_runs)
+                    .values(&run)
+                    .on_conflict_do_nothing()
+                    .execute(conn)?;
+                if inserted == 0 && run.state != queued {
+                    diesel::update(
+                        runs_dsl::plugin_runs
+                            .filter(runs_dsl::instance_id.eq(run.instance_id))
+                            .filter(runs_dsl::state.eq(&queued)),
+                    )
+                    .set((
+                        runs_dsl::state.eq(&run.state),
+                        runs_dsl::started_at.eq(Utc::now()),
+                    ))
+                    .execute(conn)?;
+                }
+                Ok::<_, diesel::result::Error>(())
+            })
+        })
+        .await??;
+        Ok(())
+    }
+
+    /// Trägt Endzustand und Ergebnis eines Laufs ein. Ein bereits beendeter
+    /// Lauf bleibt unverändert; gibt zurück, ob eine Zeile geändert wurde.
+    #[instrument]
+    pub async fn finish_plugin_run(
+        &self,
+        instance_id: i64,
+        outcome: RunOutcome,
+    ) -> Result<bool, StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        let updated = conn
+            .interact(move |conn| {
+                use schema::plugin_runs::dsl as runs_dsl;
+                diesel::update(
+                    runs_dsl::plugin_runs
+                        .filter(runs_dsl::instance_id.eq(instance_id))
+                        .filter(runs_dsl::ended_at.is_null()),
+                )
+                .set((
+                    runs_dsl::ended_at.eq(Utc::now()),
+                    runs_dsl::state.eq(runs::state_name(outcome.state)),
+                    runs_dsl::result.eq(outcome.result),
+                    runs_dsl::error.eq(outcome.error),
+                    runs_dsl::trace.eq(outcome.trace),
+                ))
+                .execute(conn)
+            })
+            .await??;
+        Ok(updated > 0)
+    }
+
+    /// Läufe, neueste zuerst, optional nur die eines Plugins.
+    #[instrument]
+    pub async fn get_plugin_runs(
+        &self,
+        plugin_name: Option<String>,
+        page: Option<u32>,
+        page_size: Option<u32>,
+        txid: TxID,
+    ) -> Result<(Vec<PluginRun>, u32), StorageError> {
+        let page = page.unwrap_or(0);
+        let page_size = page_size
+            .filter(|&ps| ps > 0)
+            .unwrap_or(runs::DEFAULT_PAGE_SIZE);
+        let conn = self.db_connection_pool().get().await?;
+        let (plugin_runs, total) = conn
+            .interact(move |conn| {
+                use schema::plugin_runs::dsl as runs_dsl;
+                let filtered = || {
+                    let mut query = runs_dsl::plugin_runs.into_boxed();
+                    if let Some(name) = plugin_name.as_ref() {
+                        query = query.filter(runs_dsl::plugin_name.eq(name.clone()));
+                    }
+                    query
+                };
+                let total: i64 = filtered().count().get_result(conn)?;
+                let plugin_runs = filtered()
+                    .order_by(runs_dsl::started_at.desc())
+                    .then_order_by(runs_dsl::instance_id.desc())
+                    .offset(i64::from(page).saturating_mul(i64::from(page_size)))
+                    .limit(i64::from(page_size))
+                    .select(PluginRun::as_select())
+                    .load::<PluginRun>(conn)?;
+                Ok::<_, diesel::result::Error>((plugin_runs, total))
+            })
+            .await??;
+        let num_pages = (total as f64 / page_size as f64).ceil() as u32;
+        Ok((plugin_runs, num_pages))
+    }
+
+    /// Ein Lauf aus `plugin_runs`.
+    #[instrument]
+    pub async fn get_plugin_run(&self, instance_id: i64) -> Result<PluginRun, StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        let run = conn
+            .interact(move |conn| {
+                use schema::plugin_runs::dsl as runs_dsl;
+                runs_dsl::plugin_runs
+                    .find(instance_id)
+                    .select(PluginRun::as_select())
+                    .first::<PluginRun>(conn)
+                    .optional()
+            })
+            .await??;
+        run.ok_or_else(|| {
+            StorageError::NotFound(format!("plugin instance {instance_id} not found"))
+        })
+    }
+
+    /// Alle Versuche des Laufs, zu dem `instance_id` gehört, nach `attempt` sortiert.
+    #[instrument]
+    pub async fn get_plugin_run_attempts(
+        &self,
+        instance_id: i64,
+    ) -> Result<Vec<PluginRun>, StorageError> {
+        let first = self.get_plugin_run(instance_id).await?;
+        let first_id = first.retry_of.unwrap_or(first.instance_id);
+        let conn = self.db_connection_pool().get().await?;
+        let attempts = conn
+            .interact(move |conn| {
+                use schema::plugin_runs::dsl as runs_dsl;
+                runs_dsl::plugin_runs
+                    .filter(
+                        runs_dsl::instance_id
+                            .eq(first_id)
+                            .or(runs_dsl::retry_of.eq(first_id)),
+                    )
+                    .order_by(runs_dsl::attempt.asc())
+                    .then_order_by(runs_dsl::instance_id.asc())
+                    .select(PluginRun::as_select())
+                    .load::<PluginRun>(conn)
+            })
+            .await??;
+        Ok(attempts)
+    }
+
+    /// Speichert Log-Zeilen von Plugin-Instanzen; schon vorhandene bleiben.
+    #[instrument(skip(lines))]
+    pub async fn insert_plugin_logs(&self, lines: Vec<PluginLogLine>) -> Result<(), StorageError> {
+        if lines.is_empty() {
+            return Ok(());
+        }
+        let conn = self.db_connection_pool().get().await?;
+        conn.interact(move |conn| {
+            use schema::plugin_logs::dsl as logs_dsl;
+            diesel::insert_into(logs_dsl::plugin_logs)
+                .values(&lines)
+                .on_conflict_do_nothing()
                 .execute(conn)
         })
         .await??;
-        debug!("Removed tag");
         Ok(())
     }
 
+    /// Log-Zeilen einer Instanz in ihrer Reihenfolge, mit Seitenzahl.
+    #[instrument]
+    pub async fn get_plugin_logs(
+        &self,
+        instance_id: i64,
+        page: Option<u32>,
+        page_size: Option<u32>,
+        txid: TxID,
+    ) -> Result<(Vec<PluginLogLine>, u32), StorageError> {
+        let page = page.unwrap_or(0);
+        let page_size = page_size
+            .filter(|&ps| ps > 0)
+            .unwrap_or(logs::DEFAULT_PAGE_SIZE);
+        let conn = self.db_connection_pool().get().await?;
+        let (lines, total) = conn
+            .interact(move |conn| {
+                use schema::plugin_logs::dsl as logs_dsl;
+                let total: i64 = logs_dsl::plugin_logs
+                    .filter(logs_dsl::instance_id.eq(instance_id))
+                    .count()
+                    .get_result(conn)?;
+                let lines = logs_dsl::plugin_logs
+                    .filter(logs_dsl::instance_id.eq(instance_id))
+                    .order_by(logs_dsl::seq.asc())
+                    .offset(i64::from(page).saturating_mul(i64::from(page_size)))
+                    .limit(i64::from(page_size))
+                    .select(PluginLogLine::as_select())
+                    .load::<PluginLogLine>(conn)?;
+                Ok::<_, diesel::result::Error>((lines, total))
+            })
+            .await??;
+        let num_pages = (total as f64 / page_size as f64).ceil() as u32;
+        Ok((lines, num_pages))
+    }
+
+    /// Übernimmt geprüfte Artefakte eines Plugin-Laufs in einer Transaktion:
+    /// Tags, Metadaten und Sequenzen am Eintrag, alle als Zeile in `entry_artifacts`.
+    #[instrument(skip(artifacts))]
+    pub async fn apply_plugin_artifacts(
+        &self,
+        entry_id_: EntryID,
+        provenance: ArtifactProvenance,
+        artifacts: Vec<PluginArtifact>,
+    ) -> Result<Vec<EntryArtifact>, StorageError> {
+        let bounds = self.entry_time_bounds(entry_id_).await?;
+        let mut rows = Vec::with_capacity(artifacts.len());
+        for artifact in artifacts.iter() {
+            let mut content = artifact.content();
+            match artifact {
+                PluginArtifact::Sequence {
+                    start_timestamp,
+                    end_timestamp,
+                    ..
+                } => timeline::validate_sequence_range(*start_timestamp, *end_timestamp, bounds)?,
+                PluginArtifact::File { path, .. } => {
+                    let size = tokio::fs::metadata(path).await?.len();
+                    content["size_bytes"] = serde_json::json!(size);
+                }
+                _ => {}
+            }
+            rows.push(NewEntryArtifact {
+                entry_id: entry_id_,
+                kind: artifact.kind().to_string(),
+                content,
+                plugin_name: provenance.plugin_name.clone(),
+                plugin_version: provenance.plugin_version.clone(),
+                instance_id: provenance.instance_id,
+            });
+        }
+        let changes_entry = artifacts.iter().any(|a| {
+            matches!(
+                a,
+                PluginArtifact::Tags { .. }
+                    | PluginArtifact::Metadata { .. }
+                    | PluginArtifact::Sequence { .. }
+            )
+        });
+
+        let conn = self.db_connection_pool().get().await?;
+        let stored = conn
+            .interact(move |conn| {
+                conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                    use schema::entries::dsl as entries_dsl;
+                    use schema::entry_artifacts::dsl as artifacts_dsl;
+                    use schema::sequences::dsl as sequences_dsl;
+                    let now = Utc::now();
+                    let mut stored = Vec::with_capacity(rows.len());
+                    for (artifact, mut row) in artifacts.into_iter().zip(rows) {
+                        match artifact {
+                            PluginArtifact::Tags { tags: add } => {
+                                let old_tags: Vec<String> = entries_dsl::entries
+                                    .find(entry_id_)
+                                    .select(entries_dsl::tags)
+                                    .for_update()
+                                    .first(conn)?;
+                                let new_tags: Vec<String> = old_tags
+                                    .iter()
+                                    .chain(add.iter())
+                                    .unique()
+                                    .cloned()
+                                    .collect();
+                                if new_tags != old_tags {
+                                    diesel::update(entries_dsl::entries.find(entry_id_))
+                                        .set(entries_dsl::tags.eq(new_tags))
+                                        .execute(conn)?;
+                                }
+                            }
+                            PluginArtifact::Metadata { patch } => {
+                                diesel::update(entries_dsl::entries.find(entry_id_))
+                                    .set((patch.as_ref(), entries_dsl::updated_at.eq(now)))
+                                    .execute(conn)?;
+                            }
+                            PluginArtifact::Sequence {
+                                name,
+                                description,
+                                start_timestamp,
+                                end_timestamp,
+                                tags,
+                            } => {
+                                let sequence_id = diesel::insert_into(sequences_dsl::sequences)
+                                    .values((
+                                        sequences_dsl::entry_id.eq(entry_id_),
+                                        sequences_dsl::name.eq(name),
+                                        sequences_dsl::description.eq(description),
+                                        sequences_dsl::start_timestamp.eq(start_timestamp),
+                                        sequences_dsl::end_timestamp.eq(end_timestamp),
+                                        sequences_dsl::created_at.eq(now),
+                                        sequences_dsl::updated_at.eq(now),
+                                        sequences_dsl::tags.eq(tags),
+                                    ))
+                                    .returning(sequences_dsl::id)
+                                    .get_result::<SequenceID>(conn)?;
+                                row.content["sequence_id"] = serde_json::json!(sequence_id);
+                            }
+                            PluginArtifact::File { .. } | PluginArtifact::Metrics { .. } => {}
+                        }
+                        stored.push(
+                            diesel::insert_into(artifacts_dsl::entry_artifacts)
+                                .values(&row)
+                                .returning(EntryArtifact::as_returning())
+                                .get_result::<EntryArtifact>(conn)?,
+                        );
+                    }
+                    Ok(stored)
+                })
+            })
+            .await??;
+        debug!(
+            "Stored {} artifacts of plugin instance {} for entry {}",
+            stored.len(),
+            provenance.instance_id,
+            entry_id_
+        );
+        if changes_entry {
+            self.events.publish(CatalogEvent::MetadataUpdated {
+                entry_id: entry_id_,
+            });
+        }
+        Ok(stored)
+    }
+
+    /// Artefakte eines Eintrags in der Reihenfolge ihres Entstehens.
+    #[instrument]
+    pub async fn get_entry_artifacts(
+        &self,
+        entry_id_: EntryID,
+        kind: Option<String>,
+        txid: TxID,
+    ) -> Result<Vec<EntryArtifact>, StorageError> {
+        if let Some(kind) = kind.as_deref() {
+            artifacts::validate_kind(kind)?;
+        }
+        self.entry_time_bounds(entry_id_).await?;
+        let conn = self.db_connection_pool().get().await?;
+        let stored = conn
+            .interact(move |conn| {
+                use schema::entry_artifacts::dsl as artifacts_dsl;
+                let mut query = artifacts_dsl::entry_artifacts
+                    .filter(artifacts_dsl::entry_id.eq(entry_id_))
+                    .into_boxed();
+                if let Some(kind) = kind {
+                    query = query.filter(artifacts_dsl::kind.eq(kind));
+                }
+                query
+                    .order_by(artifacts_dsl::created_at.asc())
+                    .then_order_by(artifacts_dsl::id.asc())
+                    .select(EntryArtifact::as_select())
+                    .load::<EntryArtifact>(conn)
+            })
+            .await??;
+        Ok(stored)
+    }
+
+    /// Schließt Läufe ab, die beim letzten Beenden des Backends noch liefen.
+    /// Deren Runner-Prozesse gibt es nicht mehr.
+    #[instrument]
+    pub async fn abandon_unfinished_plugin_runs(&self) -> Result<usize, StorageError> {
+        let conn = self.db_connection_pool().get().await?;
+        let abandoned = conn
+            .interact(move |conn| {
+                use schema::plugin_runs::dsl as runs_dsl;
+                diesel::update(runs_dsl::plugin_runs.filter(runs_dsl::ended_at.is_null()))
+                    .set((
+                        runs_dsl::ended_at.eq(Utc::now()),
+                        runs_dsl::state.eq(runs::state_name(InstanceState::Failed)),
+                        runs_dsl::error.eq("backend stopped before the run finished"),
+                    ))
+                    .execute(conn)
+            })
+            .await??;
+        Ok(abandoned)
+    }
+
     #[instrument]
     pub fn start_transaction(&self) -> TxID {
         let txid = self.tx_counter.fetch_add(1, Ordering::Relaxed);
@@ -769,6 +3925,259 @@ impl std::fmt::Debug for StorageManager {
     }
 }
 
+/// Maskiert `%`, `_` und `\\` für die Verwendung in einem `ILIKE`-Muster.
+fn escape_like(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Unterscheidet nach einem bedingten Schreibzugriff ohne getroffene Zeile,
+/// ob die Zeile fehlt, ihre Version veraltet ist oder es nichts zu ändern gab.
+fn check_version(
+    what: &'static str,
+    id: i64,
+    expected: i64,
+    current: Option<i64>,
+) -> Result<(), StorageError> {
+    match current {
+        None => Err(StorageError::NotFound(format!("{what} {id} not found"))),
+        Some(v) if v == expected => Ok(()),
+        Some(v) => Err(StorageError::PreconditionFailed(format!(
+            "{what} {id} has version {v}, but version {expected} was expected"
+        ))),
+    }
+}
+
+/// Versionierter Schreibzugriff auf die Metadaten eines Eintrags über eine
+/// bestehende Verbindung, damit er Teil einer größeren Transaktion sein kann.
+/// Fehler wie bei [`StorageManager::update_entry`]; liefert, ob der Eintrag
+/// geändert wurde. Ereignisse meldet der Aufrufer.
+fn update_entry_metadata(
+    conn: &mut PgConnection,
+    entry_id_: EntryID,
+    entry_metadata: &routes::database::MetadataWeb,
+    expected_version: Option<i64>,
+) -> Result<bool, StorageError> {
+    let rows = write_entry_metadata(conn, entry_id_, entry_metadata, expected_version)?;
+    if rows == 0
+        && let Some(expected) = expected_version
+    {
+        check_version(
+            "entry",
+            entry_id_,
+            expected,
+            entry_version(conn, entry_id_)?,
+        )?;
+    }
+    Ok(rows > 0)
+}
+
+/// Schreibt alle Metadatenfelder eines Eintrags; mit `expected_version` nur,
+/// solange die gespeicherte Version noch übereinstimmt. Liefert die Zahl der
+/// geänderten Zeilen, die Auswertung übernimmt `update_entry_metadata`.
+fn write_entry_metadata(
+    conn: &mut PgConnection,
+    entry_id_: EntryID,
+    entry_metadata: &routes::database::MetadataWeb,
+    expected_version: Option<i64>,
+) -> QueryResult<usize> {
+    let changes = (
+        schema::entries::dsl::time_machine.eq(entry_metadata.time_machine),
+        schema::entries::dsl::platform_name.eq(entry_metadata.platform_name.clone()),
+        schema::entries::dsl::platform_image_link.eq(entry_metadata.platform_image_link.clone()),
+        schema::entries::dsl::scenario_name.eq(entry_metadata.scenario_name.clone()),
+        schema::entries::dsl::scenario_creation_time.eq(entry_metadata.scenario_creation_time),
+        schema::entries::dsl::scenario_description.eq(entry_metadata.scenario_description.clone()),
+        schema::entries::dsl::sequence_duration.eq(entry_metadata.sequence_duration),
+        schema::entries::dsl::sequence_distance.eq(entry_metadata.sequence_distance),
+        schema::entries::dsl::sequence_lat_starting_point_deg
+            .eq(entry_metadata.sequence_lat_starting_point_deg),
+        schema::entries::dsl::sequence_lon_starting_point_deg
+            .eq(entry_metadata.sequence_lon_starting_point_deg),
+        schema::entries::dsl::weather_cloudiness.eq(entry_metadata.weather_cloudiness.clone()),
+        schema::entries::dsl::weather_precipitation
+            .eq(entry_metadata.weather_precipitation.clone()),
+        schema::entries::dsl::weather_precipitation_deposits
+            .eq(entry_metadata.weather_precipitation_deposits.clone()),
+        schema::entries::dsl::weather_wind_intensity
+            .eq(entry_metadata.weather_wind_intensity.clone()),
+        schema::entries::dsl::weather_road_humidity
+            .eq(entry_metadata.weather_road_humidity.clone()),
+        schema::entries::dsl::weather_fog.eq(entry_metadata.weather_fog),
+        schema::entries::dsl::weather_snow.eq(entry_metadata.weather_snow),
+    );
+    match expected_version {
+        Some(v) => diesel::update(
+            schema::entries::dsl::entries
+                .filter(schema::entries::dsl::id.eq(entry_id_))
+                .filter(schema::entries::dsl::version.eq(v)),
+        )
+        .set(changes)
+        .execute(conn),
+        None => diesel::update(
+            schema::entries::dsl::entries.filter(schema::entries::dsl::id.eq(entry_id_)),
+        )
+        .set(changes)
+        .execute(conn),
+    }
+}
+
+/// Scheitert, wenn `user_id` der einzige aktive Admin ist.
+///
+/// Sperrt vorher alle aktiven Admin-Zeilen (in fester Reihenfolge), damit zwei
+/// gleichzeitige Herabstufungen nicht beide noch einen anderen Admin sehen.
+fn ensure_other_admin(conn: &mut PgConnection, user_id: UserID) -> Result<(), StorageError> {
+    use schema::users::dsl as users_dsl;
+    let admins = users_dsl::users
+        .filter(users_dsl::role.eq(Role::Admin.as_str()))
+        .filter(users_dsl::disabled.eq(false))
+        .order(users_dsl::id.asc())
+        .select(users_dsl::id)
+        .for_update()
+        .load::<UserID>(conn)?;
+    if admins.contains(&user_id) && admins.len() == 1 {
+        return Err(StorageError::ValidationError(
+            "at least one active admin must remain".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Aktuelle Version eines Eintrags, `None` falls er nicht existiert.
+fn entry_version(conn: &mut PgConnection, entry_id_: EntryID) -> QueryResult<Option<i64>> {
+    schema::entries::dsl::entries
+        .find(entry_id_)
+        .select(schema::entries::dsl::version)
+        .first::<i64>(conn)
+        .optional()
+}
+
+/// Aktuelle Version eines Sensors, `None` falls er nicht existiert.
+fn sensor_version(conn: &mut PgConnection, sensor_id: SensorID) -> QueryResult<Option<i64>> {
+    schema::sensors::dsl::sensors
+        .find(sensor_id)
+        .select(schema::sensors::dsl::version)
+        .first::<i64>(conn)
+        .optional()
+}
+
+/// Aktuelle Version eines Katalogsensors, `None` falls er nicht existiert.
+fn catalog_sensor_version(
+    conn: &mut PgConnection,
+    catalog_sensor_id: CatalogSensorID,
+) -> QueryResult<Option<i64>> {
+    schema::sensor_catalog::dsl::sensor_catalog
+        .find(catalog_sensor_id)
+        .select(schema::sensor_catalog::dsl::version)
+        .first::<i64>(conn)
+        .optional()
+}
+
+/// Aktuelle Version einer Tag-Definition, `None` falls es keine gibt.
+fn tag_definition_version(conn: &mut PgConnection, tag: &str) -> QueryResult<Option<i64>> {
+    schema::tag_definitions::dsl::tag_definitions
+        .find(tag)
+        .select(schema::tag_definitions::dsl::version)
+        .first::<i64>(conn)
+        .optional()
+}
+
+/// Von einem `UPDATE entries ... RETURNING id` geänderter Eintrag.
+#[derive(QueryableByName)]
+struct EntryIdRow {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    id: EntryID,
+}
+
+/// Zeile der Tag-Übersicht aus [`StorageManager::get_tags`].
+#[derive(QueryableByName)]
+struct TagUsageRow {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    name: String,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    entry_count: i64,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    sequence_count: i64,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    color: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    description: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::BigInt>)]
+    version: Option<i64>,
+}
+
+/// Aktuelle Version einer Sammlung, `None` falls sie nicht existiert.
+fn collection_version(
+    conn: &mut PgConnection,
+    collection_id: CollectionID,
+) -> QueryResult<Option<i64>> {
+    schema::collections::dsl::collections
+        .find(collection_id)
+        .select(schema::collections::dsl::version)
+        .first::<i64>(conn)
+        .optional()
+}
+
+/// Aktueller Stand der Elemente einer Sammlung, in der Form eines Snapshots.
+fn current_collection_items(
+    conn: &mut PgConnection,
+    collection_id: CollectionID,
+) -> QueryResult<Vec<CollectionSnapshotItem>> {
+    use schema::collection_items::dsl as items_dsl;
+    use schema::entries::dsl as entries_dsl;
+    use schema::sequences::dsl as sequences_dsl;
+    items_dsl::collection_items
+        .inner_join(entries_dsl::entries)
+        .left_join(sequences_dsl::sequences)
+        .filter(items_dsl::collection_id.eq(collection_id))
+        .order_by((
+            items_dsl::entry_id.asc(),
+            items_dsl::sequence_id.asc().nulls_first(),
+        ))
+        .select((
+            entries_dsl::id,
+            entries_dsl::name,
+            entries_dsl::path,
+            entries_dsl::size,
+            sequences_dsl::id.nullable(),
+            sequences_dsl::name.nullable(),
+            sequences_dsl::start_timestamp.nullable(),
+            sequences_dsl::end_timestamp.nullable(),
+        ))
+        .load::<CollectionSnapshotItem>(conn)
+}
+
+/// Sammlungsnamen dürfen nicht leer sein und werden getrimmt.
+fn validate_collection_name(name: String) -> Result<String, StorageError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(StorageError::ValidationError(
+            "collection name must not be empty".to_string(),
+        ));
+    }
+    Ok(name)
+}
+
+/// Aktuelle Version einer Sequenz des Eintrags, `None` falls sie nicht existiert.
+fn sequence_version(
+    conn: &mut PgConnection,
+    entry_id_: EntryID,
+    sequence_id: SequenceID,
+) -> QueryResult<Option<i64>> {
+    schema::sequences::dsl::sequences
+        .filter(schema::sequences::dsl::id.eq(sequence_id))
+        .filter(schema::sequences::dsl::entry_id.eq(entry_id_))
+        .select(schema::sequences::dsl::version)
+        .first::<i64>(conn)
+        .optional()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -816,6 +4225,9 @@ mod tests {
             size: 0,
             created_at: base,
             updated_at: base,
+            version: 1,
+            mcap_start_ns: None,
+            mcap_end_ns: None,
             status: "Complete".to_string(),
             time_machine: None,
             platform_name: None,
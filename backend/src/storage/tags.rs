@@ -0,0 +1,46 @@
+//! Regeln für Tag-Namen und Tag-Metadaten.
+//!
+//! Tags sind frei wählbare Strings; ein `/` trennt einen Namensraum ab
+//! (`weather/rain` liegt im Namensraum `weather`).
+
+use crate::error::StorageError;
+
+/// Namensraum eines Tags, also alles vor dem letzten `/`.
+pub fn tag_namespace(tag: &str) -> Option<&str> {
+    tag.rsplit_once('/').map(|(ns, _)| ns)
+}
+
+/// Prüft einen Tag-Namen: nicht leer, ohne umgebende Leerzeichen und ohne leere
+/// Namensraum-Segmente (`weather//rain`, `/rain`).
+pub fn validate_tag_name(tag: &str) -> Result<(), StorageError> {
+    if tag.is_empty() {
+        return Err(StorageError::ValidationError(
+            "tag must not be empty".to_string(),
+        ));
+    }
+    if tag.trim() != tag {
+        return Err(StorageError::ValidationError(format!(
+            "tag '{tag}' must not start or end with whitespace"
+        )));
+    }
+    if tag.split('/').any(|segment| segment.trim().is_empty()) {
+        return Err(StorageError::ValidationError(format!(
+            "tag '{tag}' contains an empty namespace segment"
+        )));
+    }
+    Ok(())
+}
+
+/// Prüft eine Tag-Farbe im Format `#rrggbb`.
+pub fn validate_tag_color(color: &str) -> Result<(), StorageError> {
+    let valid = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+    if valid {
+        Ok(())
+    } else {
+        Err(StorageError::ValidationError(format!(
+            "color '{color}' must have the form #rrggbb"
+        )))
+    }
+}
@@ -0,0 +1,154 @@
+//! Zeitachsen-Logik für Sequenzen.
+//!
+//! Alle Zeitstempel sind MCAP-Log-Zeit in Nanosekunden. Die Funktionen hier sind
+//! rein (ohne Datenbank), damit Storage-Manager und Routen sie gemeinsam nutzen.
+
+use crate::error::StorageError;
+use crate::storage::models::{EntryID, Sequence, SequenceID, Timestamp};
+use rocket::serde::{Deserialize, Serialize};
+
+/// Aufnahmezeitraum eines Eintrags (`mcap_start_ns`, `mcap_end_ns`).
+pub type TimeBounds = (Option<Timestamp>, Option<Timestamp>);
+
+/// Prüft, ob `[start, end]` eine gültige Sequenz innerhalb der Aufnahme ist.
+///
+/// Unbekannte Grenzen (z.B. weil `mcap info` fehlschlug) werden nicht geprüft.
+pub fn validate_sequence_range(
+    start: Timestamp,
+    end: Timestamp,
+    bounds: TimeBounds,
+) -> Result<(), StorageError> {
+    if end < start {
+        return Err(StorageError::ValidationError(format!(
+            "end_timestamp {end} is before start_timestamp {start}"
+        )));
+    }
+    if let Some(lo) = bounds.0
+        && start < lo
+    {
+        return Err(StorageError::ValidationError(format!(
+            "start_timestamp {start} is before the recording start {lo}"
+        )));
+    }
+    if let Some(hi) = bounds.1
+        && end > hi
+    {
+        return Err(StorageError::ValidationError(format!(
+            "end_timestamp {end} is after the recording end {hi}"
+        )));
+    }
+    Ok(())
+}
+
+/// Zwei Intervalle überlappen, wenn sie sich echt schneiden (Berühren zählt nicht).
+pub fn ranges_overlap(a: (Timestamp, Timestamp), b: (Timestamp, Timestamp)) -> bool {
+    a.0 < b.1 && b.0 < a.1
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde", tag = "kind", rename_all = "snake_case")]
+pub enum TimelineSegment {
+    Sequence {
+        sequence_id: SequenceID,
+        name: String,
+        start_timestamp: Timestamp,
+        end_timestamp: Timestamp,
+    },
+    /// Abschnitt der Aufnahme, der von keiner Sequenz abgedeckt wird.
+    Gap {
+        start_timestamp: Timestamp,
+        end_timestamp: Timestamp,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct SequenceOverlap {
+    pub first: SequenceID,
+    pub second: SequenceID,
+    pub start_timestamp: Timestamp,
+    pub end_timestamp: Timestamp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct Timeline {
+    pub entry_id: EntryID,
+    pub start_timestamp: Option<Timestamp>,
+    pub end_timestamp: Option<Timestamp>,
+    /// Sequenzen und Lücken, nach Startzeit sortiert.
+    pub segments: Vec<TimelineSegment>,
+    pub overlaps: Vec<SequenceOverlap>,
+}
+
+/// Baut die Zeitachse eines Eintrags aus seinen Sequenzen.
+///
+/// Lücken werden zwischen den Sequenzen sowie zum Anfang und Ende der Aufnahme
+/// gebildet, soweit diese bekannt sind.
+pub fn build_timeline(
+    entry_id: EntryID,
+    bounds: TimeBounds,
+    sequences: impl IntoIterator<Item = Sequence>,
+) -> Timeline {
+    let mut sequences: Vec<Sequence> = sequences.into_iter().collect();
+    sequences.sort_by_key(|s| (s.start_timestamp, s.end_timestamp, s.id));
+
+    let mut segments = Vec::with_capacity(sequences.len() * 2 + 1);
+    let mut cursor = bounds
+        .0
+        .or_else(|| sequences.first().map(|s| s.start_timestamp));
+    for s in sequences.iter() {
+        if let Some(c) = cursor
+            && s.start_timestamp > c
+        {
+            segments.push(TimelineSegment::Gap {
+                start_timestamp: c,
+                end_timestamp: s.start_timestamp,
+            });
+        }
+        segments.push(TimelineSegment::Sequence {
+            sequence_id: s.id,
+            name: s.name.clone(),
+            start_timestamp: s.start_timestamp,
+            end_timestamp: s.end_timestamp,
+        });
+        cursor = Some(cursor.map_or(s.end_timestamp, |c| c.max(s.end_timestamp)));
+    }
+    if let (Some(c), Some(hi)) = (cursor, bounds.1)
+        && hi > c
+    {
+        segments.push(TimelineSegment::Gap {
+            start_timestamp: c,
+            end_timestamp: hi,
+        });
+    }
+
+    let mut overlaps = Vec::new();
+    for (i, a) in sequences.iter().enumerate() {
+        // sortiert nach Start: sobald b hinter a beginnt, kann nichts mehr überlappen
+        for b in sequences[i + 1..]
+            .iter()
+            .take_while(|b| b.start_timestamp < a.end_timestamp)
+        {
+            if ranges_overlap(
+                (a.start_timestamp, a.end_timestamp),
+                (b.start_timestamp, b.end_timestamp),
+            ) {
+                overlaps.push(SequenceOverlap {
+                    first: a.id,
+                    second: b.id,
+                    start_timestamp: b.start_timestamp,
+                    end_timestamp: a.end_timestamp.min(b.end_timestamp),
+                });
+            }
+        }
+    }
+
+    Timeline {
+        entry_id,
+        start_timestamp: bounds.0,
+        end_timestamp: bounds.1,
+        segments,
+        overlaps,
+    }
+}
@@ -4,16 +4,27 @@ mod common;
 use std::env;
 use std::sync::Arc;
 
-use backend::routes::database::{get_entries, get_entry, get_entry_by_path};
+use backend::error::StorageError;
+use backend::routes::audit::{get_audit_log, AuditLog};
+use backend::routes::auth::{forbidden, login, me, unauthorized};
+use backend::plugin_manager::parameters::ParameterSchema;
+use backend::plugin_manager::plugin::{Plugin, Trigger};
+use backend::plugin_manager::trigger_filter::TriggerFilter;
+use backend::routes::database::{
+    bulk_entries, commit_transaction, create_collection, get_entries, get_entry,
+    get_entry_by_path, update_metadata,
+};
 use backend::routes::health_check::health;
+use backend::storage::auth::{issue_runner_token, Role, Scope};
 use backend::storage::models::Entry;
 use backend::storage::storage_manager::StorageManager;
 use backend::AppState;
-use rocket::http::Status;
+use rocket::http::{ContentType, Header, Status};
 use rocket::local::asynchronous::Client;
 use rocket::serde::json::serde_json;
 
 const TXID: u64 = 0;
+const API_PASSWORD: &str = "api-test-password";
 
 fn skip_if_no_db() -> bool {
     if env::var("DATABASE_URL").is_err() {
@@ -26,6 +37,19 @@ fn skip_if_no_db() -> bool {
 async fn build_test_rocket() -> rocket::Rocket<rocket::Build> {
     let db_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
     let storage_manager = StorageManager::new(&db_url).expect("failed to create StorageManager");
+    for (username, role) in [
+        ("api-viewer", Role::Viewer),
+        ("api-editor", Role::Editor),
+        ("api-admin", Role::Admin),
+    ] {
+        match storage_manager
+            .create_user(username.to_string(), API_PASSWORD.to_string(), role, TXID)
+            .await
+        {
+            Ok(_) | Err(StorageError::AlreadyExists(_)) => {}
+            Err(e) => panic!("failed to create test user: {e:?}"),
+        }
+    }
     let plugin_manager = Arc::new(tokio::sync::Mutex::new(
         backend::plugin_manager::manager::PluginManager::new(),
     ));
@@ -33,14 +57,80 @@ async fn build_test_rocket() -> rocket::Rocket<rocket::Build> {
     rocket::build()
         .mount(
             "/",
-            rocket::routes![health, get_entries, get_entry, get_entry_by_path],
+            rocket::routes![
+                health,
+                login,
+                me,
+                get_entries,
+                get_entry,
+                get_entry_by_path,
+                create_collection,
+                commit_transaction,
+                update_metadata,
+                bulk_entries,
+                get_audit_log
+            ],
         )
+        .register("/", rocket::catchers![unauthorized, forbidden])
+        .attach(AuditLog)
         .manage(AppState {
             storage_manager,
             plugin_manager,
         })
 }
 
+/// Meldet einen Testbenutzer an und liefert den `Authorization`-Header.
+async fn auth_header(client: &Client, username: &str) -> Header<'static> {
+    let resp = client
+        .post("/auth/login")
+        .header(ContentType::JSON)
+        .body(
+            serde_json::json!({ "username": username, "password": API_PASSWORD }).to_string(),
+        )
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+    let session: serde_json::Value = resp.into_json().await.unwrap();
+    let token = session["token"].as_str().unwrap().to_string();
+    Header::new("Authorization", format!("Bearer {token}"))
+}
+
+/// Legt einen Eintrag mit eindeutigem Pfad unter `/test/api/` an.
+async fn add_test_entry(state: &AppState, name: &str, prefix: &str) -> i64 {
+    let now = chrono::Utc::now();
+    let entry = Entry {
+        id: 0,
+        name: name.to_string(),
+        created_at: now,
+        updated_at: now,
+        version: 1,
+        mcap_start_ns: None,
+        mcap_end_ns: None,
+        path: format!("/test/api/{prefix}-{}.mcap", now.timestamp_micros()),
+        size: 0,
+        status: "Complete".to_string(),
+        time_machine: None,
+        platform_name: None,
+        platform_image_link: None,
+        scenario_name: None,
+        scenario_creation_time: None,
+        scenario_description: None,
+        sequence_duration: None,
+        sequence_distance: None,
+        sequence_lat_starting_point_deg: None,
+        sequence_lon_starting_point_deg: None,
+        weather_cloudiness: None,
+        weather_precipitation: None,
+        weather_precipitation_deposits: None,
+        weather_wind_intensity: None,
+        weather_road_humidity: None,
+        weather_fog: None,
+        weather_snow: None,
+        tags: vec![],
+    };
+    state.storage_manager.add_entry(entry, TXID).await.unwrap()
+}
+
 #[tokio::test]
 async fn test_health_endpoint_ok() {
     if skip_if_no_db() {
@@ -76,6 +166,7 @@ async fn test_get_entries_empty_list_ok() {
 
     let resp = client
         .get("/entries?txid=0&page=0&page_size=10")
+        .header(auth_header(&client, "api-viewer").await)
         .dispatch()
         .await;
     assert_eq!(resp.status(), Status::Ok);
@@ -100,9 +191,479 @@ async fn test_get_entry_404_for_unknown_id() {
         .await
         .expect("failed to build rocket client");
 
-    let resp = client.get("/entries/999999/tx/0").dispatch().await;
+    let resp = client
+        .get("/entries/999999/tx/0")
+        .header(auth_header(&client, "api-viewer").await)
+        .dispatch()
+        .await;
     let status = resp.status();
     // Für eine unbekannte ID erwarten wir auf jeden Fall keinen 200-Status.
     assert_ne!(status, Status::Ok);
 }
 
+
+#[tokio::test]
+async fn test_requests_without_session_are_rejected() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let client = Client::tracked(build_test_rocket().await)
+        .await
+        .expect("failed to build rocket client");
+
+    let resp = client.get("/entries?txid=0").dispatch().await;
+    assert_eq!(resp.status(), Status::Unauthorized);
+    assert_eq!(resp.headers().get_one("WWW-Authenticate"), Some("Bearer"));
+
+    let resp = client
+        .get("/entries?txid=0")
+        .header(Header::new("Authorization", "Bearer not-a-session"))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let body = resp.into_string().await.unwrap();
+    assert!(body.contains("invalid or expired session"), "{body}");
+
+    let resp = client
+        .post("/auth/login")
+        .header(ContentType::JSON)
+        .body(r#"{"username": "api-viewer", "password": "wrong password"}"#)
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Unauthorized);
+}
+
+#[tokio::test]
+async fn test_roles_are_enforced() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let client = Client::tracked(build_test_rocket().await)
+        .await
+        .expect("failed to build rocket client");
+
+    let viewer = auth_header(&client, "api-viewer").await;
+    let resp = client.get("/auth/me").header(viewer.clone()).dispatch().await;
+    assert_eq!(resp.status(), Status::Ok);
+    let me: serde_json::Value = resp.into_json().await.unwrap();
+    assert_eq!(me["role"], "viewer");
+    assert!(me.get("password_hash").is_none());
+
+    let body = r#"{"name": "api-roles-test", "description": null}"#;
+    let resp = client
+        .post("/collections")
+        .header(ContentType::JSON)
+        .header(viewer)
+        .body(body)
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Forbidden);
+    let message = resp.into_string().await.unwrap();
+    assert!(message.contains("role editor required"), "{message}");
+
+    let resp = client
+        .post("/collections")
+        .header(ContentType::JSON)
+        .header(auth_header(&client, "api-editor").await)
+        .body(body)
+        .dispatch()
+        .await;
+    // beim zweiten Lauf existiert die Sammlung schon; entscheidend ist nur der Zugriff
+    assert_ne!(resp.status(), Status::Forbidden);
+    assert_ne!(resp.status(), Status::Unauthorized);
+}
+
+#[tokio::test]
+async fn test_api_keys_and_runner_tokens_are_scoped() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let client = Client::tracked(build_test_rocket().await)
+        .await
+        .expect("failed to build rocket client");
+    let state = client.rocket().state::<AppState>().unwrap();
+    let created = state
+        .storage_manager
+        .create_api_key("api-read-only".to_string(), vec![Scope::ReadEntries], None, None, TXID)
+        .await
+        .unwrap();
+    assert!(created.key.starts_with(&created.api_key.key_prefix));
+    assert!(created.api_key.last_used_at.is_none());
+    let key = Header::new("Authorization", format!("Bearer {}", created.key));
+
+    let resp = client.get("/entries").header(key.clone()).dispatch().await;
+    assert_eq!(resp.status(), Status::Ok);
+    let keys = state.storage_manager.get_api_keys(TXID).await.unwrap();
+    let used = keys.iter().find(|k| k.id == created.api_key.id).unwrap();
+    assert!(used.last_used_at.is_some());
+
+    let body = r#"{"name": "api-scopes-test", "description": null}"#;
+    let resp = client
+        .post("/collections")
+        .header(ContentType::JSON)
+        .header(key.clone())
+        .body(body)
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Forbidden);
+    let message = resp.into_string().await.unwrap();
+    assert!(message.contains("scope write:metadata required"), "{message}");
+
+    // /auth/me braucht eine Sitzung
+    let resp = client.get("/auth/me").header(key).dispatch().await;
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    state
+        .storage_manager
+        .delete_api_key(created.api_key.id, TXID)
+        .await
+        .unwrap();
+    let resp = client
+        .get("/entries")
+        .header(Header::new("Authorization", format!("Bearer {}", created.key)))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    // Runner-Tokens dürfen lesen und Metadaten schreiben
+    let token = issue_runner_token(4711, "api-test-plugin", chrono::Utc::now());
+    let runner = Header::new("Authorization", format!("Bearer {token}"));
+    let resp = client.get("/entries").header(runner.clone()).dispatch().await;
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client
+        .post("/collections")
+        .header(ContentType::JSON)
+        .header(runner)
+        .body(body)
+        .dispatch()
+        .await;
+    assert_ne!(resp.status(), Status::Forbidden);
+    assert_ne!(resp.status(), Status::Unauthorized);
+
+    // letztes Zeichen ersetzen, ohne zufällig dasselbe zu treffen
+    let last = if token.ends_with('0') { '1' } else { '0' };
+    let forged = format!("{}{last}", &token[..token.len() - 1]);
+    let resp = client
+        .get("/entries")
+        .header(Header::new("Authorization", format!("Bearer {forged}")))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Unauthorized);
+}
+
+#[tokio::test]
+async fn test_mutating_requests_are_audited() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let client = Client::tracked(build_test_rocket().await)
+        .await
+        .expect("failed to build rocket client");
+    let editor = auth_header(&client, "api-editor").await;
+    let admin = auth_header(&client, "api-admin").await;
+
+    let name = format!("api-audit-{}", chrono::Utc::now().timestamp_micros());
+    let body = serde_json::json!({ "name": name, "description": null }).to_string();
+    let resp = client
+        .post("/collections?txid=0")
+        .header(ContentType::JSON)
+        .header(editor.clone())
+        .body(body.clone())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Created);
+    // der Client hält das Sitzungs-Cookie, daher ein ungültiges Token
+    let resp = client
+        .post("/collections")
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", "Bearer expired"))
+        .body(body)
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    // Lesezugriffe landen nicht im Log
+    let resp = client.get("/entries").header(editor.clone()).dispatch().await;
+    assert_eq!(resp.status(), Status::Ok);
+
+    let resp = client
+        .get("/audit?actor=user:api-editor&target=/collections&page_size=1")
+        .header(editor.clone())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = client
+        .get("/audit?actor=user:api-editor&target=/collections&page_size=1")
+        .header(admin.clone())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+    let (records, _): (Vec<serde_json::Value>, u32) = resp.into_json().await.unwrap();
+    let record = &records[0];
+    assert_eq!(record["method"], "POST");
+    assert_eq!(record["route"], "/collections");
+    assert_eq!(record["parameters"]["txid"], "0");
+    assert_eq!(record["parameters"]["body"]["name"], name.as_str());
+    assert_eq!(record["status"], 201);
+    assert_eq!(record["outcome"], "success");
+
+    // ändernde GET-Routen melden sich über `AuditDetail` selbst an
+    client
+        .get("/transaction/0/commit")
+        .header(editor.clone())
+        .dispatch()
+        .await;
+    let resp = client
+        .get("/audit?actor=user:api-editor&target=/transaction&page_size=1")
+        .header(admin.clone())
+        .dispatch()
+        .await;
+    let (records, _): (Vec<serde_json::Value>, u32) = resp.into_json().await.unwrap();
+    assert_eq!(records[0]["method"], "GET");
+    assert_eq!(records[0]["route"], "/transaction/<txid>/commit");
+
+    let resp = client
+        .get("/audit?actor=anonymous&target=/collections&page_size=1")
+        .header(admin.clone())
+        .dispatch()
+        .await;
+    let (records, _): (Vec<serde_json::Value>, u32) = resp.into_json().await.unwrap();
+    assert_eq!(records[0]["outcome"], "denied");
+    assert_eq!(records[0]["status"], 401);
+
+    let resp = client
+        .get("/audit?since=yesterday")
+        .header(admin)
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::BadRequest);
+}
+
+#[tokio::test]
+async fn test_metadata_update_checks_trigger_conditions_against_entry() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let client = Client::tracked(build_test_rocket().await)
+        .await
+        .expect("failed to build rocket client");
+    let editor = auth_header(&client, "api-editor").await;
+    let state = client.rocket().state::<AppState>().unwrap();
+
+    let entry_id = add_test_entry(state, "Trigger Entry", "trigger").await;
+
+    {
+        let mut pm = state.plugin_manager.lock().await;
+        for (name, platform) in [("api_car2", "car2"), ("api_car1", "car1")] {
+            let mut plugin = Plugin::new(
+                name.to_string(),
+                "d".to_string(),
+                Trigger::OnEntryUpdate,
+                std::path::PathBuf::from(format!("/tmp/{name}.py")),
+            );
+            plugin.set_enabled(true);
+            plugin.set_trigger_filter(Some(
+                TriggerFilter::from_declaration(
+                    serde_json::json!({ "metadata": { "platform_name": platform } }),
+                )
+                .unwrap(),
+            ));
+            pm.registered.push(plugin);
+        }
+    }
+
+    let resp = client
+        .put(format!("/entries/{entry_id}/metadata/tx/{TXID}"))
+        .header(ContentType::JSON)
+        .header(editor)
+        .body(serde_json::json!({ "platform_name": "car2" }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::NoContent);
+
+    // die Bedingung sieht die neuen Metadaten, nicht nur den Pfad
+    let pm = state.plugin_manager.lock().await;
+    let queued: Vec<String> = pm
+        .queue()
+        .snapshot()
+        .jobs
+        .into_iter()
+        .map(|job| job.plugin_name)
+        .collect();
+    assert_eq!(queued, ["api_car2"]);
+}
+
+#[tokio::test]
+async fn test_bulk_metadata_patch_fires_entry_update_triggers() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let client = Client::tracked(build_test_rocket().await)
+        .await
+        .expect("failed to build rocket client");
+    let editor = auth_header(&client, "api-editor").await;
+    let state = client.rocket().state::<AppState>().unwrap();
+
+    let mut entry_ids = Vec::new();
+    for i in 0..2 {
+        entry_ids.push(add_test_entry(state, &format!("Bulk Trigger {i}"), "bulk-trigger").await);
+    }
+
+    {
+        let mut pm = state.plugin_manager.lock().await;
+        for (name, platform) in [("bulk_car2", "car2"), ("bulk_car1", "car1")] {
+            let mut plugin = Plugin::new(
+                name.to_string(),
+                "d".to_string(),
+                Trigger::OnEntryUpdate,
+                std::path::PathBuf::from(format!("/tmp/{name}.py")),
+            );
+            plugin.set_enabled(true);
+            plugin.set_trigger_filter(Some(
+                TriggerFilter::from_declaration(
+                    serde_json::json!({ "metadata": { "platform_name": platform } }),
+                )
+                .unwrap(),
+            ));
+            pm.registered.push(plugin);
+        }
+    }
+
+    let resp = client
+        .post("/entries/bulk")
+        .header(ContentType::JSON)
+        .header(editor)
+        .body(
+            serde_json::json!({
+                "entry_ids": entry_ids,
+                "operation": {
+                    "kind": "patch_metadata",
+                    "fields": { "platform_name": "car2" },
+                },
+            })
+            .to_string(),
+        )
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+
+    // je geändertem Eintrag ein Job, mit derselben Nutzlast wie beim Einzel-Update
+    let jobs = state.plugin_manager.lock().await.queue().snapshot().jobs;
+    assert_eq!(jobs.len(), 2);
+    for (job, entry_id) in jobs.iter().zip(entry_ids) {
+        let entry = state
+            .storage_manager
+            .get_entry(entry_id, TXID)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(job.plugin_name, "bulk_car2");
+        let data: serde_json::Value = serde_json::from_str(&job.data).unwrap();
+        assert_eq!(data["mcap_path"], entry.path.as_str());
+        assert_eq!(data["metadata"]["platform_name"], "car2");
+    }
+}
+
+#[tokio::test]
+async fn test_bulk_plugin_start_validates_parameters() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let client = Client::tracked(build_test_rocket().await)
+        .await
+        .expect("failed to build rocket client");
+    let admin = auth_header(&client, "api-admin").await;
+    let state = client.rocket().state::<AppState>().unwrap();
+    let entry_id = add_test_entry(state, "Bulk Start Entry", "bulk-start").await;
+    let entry_path = state
+        .storage_manager
+        .get_entry(entry_id, TXID)
+        .await
+        .unwrap()
+        .unwrap()
+        .path;
+
+    {
+        let mut pm = state.plugin_manager.lock().await;
+        let mut plugin = Plugin::new(
+            "api_params".to_string(),
+            "d".to_string(),
+            Trigger::Manual,
+            std::path::PathBuf::from("/tmp/api_params.py"),
+        );
+        plugin.set_enabled(true);
+        plugin.set_parameters(Some(
+            ParameterSchema::from_declaration(vec![
+                (
+                    "recording".to_string(),
+                    serde_json::json!({ "type": "entry", "required": true }),
+                ),
+                (
+                    "limit".to_string(),
+                    serde_json::json!({ "type": "integer", "default": 10 }),
+                ),
+            ])
+            .unwrap(),
+        ));
+        pm.registered.push(plugin);
+    }
+
+    let start = |parameters: serde_json::Value| {
+        serde_json::json!({
+            "entry_ids": [entry_id],
+            "operation": {
+                "kind": "start_plugin",
+                "plugin_name": "api_params",
+                "parameters": parameters,
+            },
+        })
+        .to_string()
+    };
+
+    // ungültige Parameter: 400 mit Feldfehlern, nichts eingereiht
+    let resp = client
+        .post("/entries/bulk")
+        .header(ContentType::JSON)
+        .header(admin.clone())
+        .body(start(serde_json::json!({ "limit": "3", "recording": "/data/other.mcap" })))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::BadRequest);
+    let errors: serde_json::Value = resp.into_json().await.unwrap();
+    let fields: Vec<&str> = errors
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, ["recording", "limit"]);
+    assert!(state.plugin_manager.lock().await.queue().snapshot().jobs.is_empty());
+
+    // gültig: dieselbe flache Form wie ein Einzelstart, Pfad im entry-Parameter
+    let resp = client
+        .post("/entries/bulk")
+        .header(ContentType::JSON)
+        .header(admin)
+        .body(start(serde_json::Value::Null))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+    let jobs = state.plugin_manager.lock().await.queue().snapshot().jobs;
+    assert_eq!(jobs.len(), 1);
+    let data: serde_json::Value = serde_json::from_str(&jobs[0].data).unwrap();
+    assert_eq!(data, serde_json::json!({ "recording": entry_path, "limit": 10 }));
+}
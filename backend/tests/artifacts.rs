@@ -0,0 +1,212 @@
+//! Plugin artifacts: parsing, validation and stored content (pure functions, no DB).
+
+#[cfg(test)]
+mod tests {
+    use backend::error::StorageError;
+    use backend::storage::artifacts::{
+        MAX_ARTIFACTS, PluginArtifact, parse_result, resolve_files, validate_kind,
+    };
+    use serde_json::json;
+
+    fn rejected(result: serde_json::Value) -> String {
+        match parse_result(&result) {
+            Err(StorageError::ValidationError(msg)) => msg,
+            other => panic!("expected validation error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn results_without_artifacts_are_ignored() {
+        assert_eq!(parse_result(&json!("stopped")).unwrap(), None);
+        assert_eq!(parse_result(&json!({ "count": 3 })).unwrap(), None);
+        assert_eq!(parse_result(&serde_json::Value::Null).unwrap(), None);
+    }
+
+    #[test]
+    fn typed_artifacts_are_parsed() {
+        let result = parse_result(&json!({
+            "entry_id": 12,
+            "artifacts": [
+                { "type": "tags", "tags": ["weather/rain"] },
+                { "type": "metadata", "patch": { "weather_fog": true } },
+                { "type": "sequence", "name": "overtake", "start_timestamp": 10, "end_timestamp": 20 },
+                { "type": "metrics", "values": { "frames": 1200, "max_speed_kmh": 87.5 } }
+            ]
+        }))
+        .unwrap()
+        .unwrap();
+        assert_eq!(result.entry_id, Some(12));
+        let kinds: Vec<&str> = result.artifacts.iter().map(|a| a.kind()).collect();
+        assert_eq!(kinds, ["tags", "metadata", "sequence", "metrics"]);
+        assert_eq!(
+            result.artifacts[2],
+            PluginArtifact::Sequence {
+                name: "overtake".to_string(),
+                description: String::new(),
+                start_timestamp: 10,
+                end_timestamp: 20,
+                tags: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn entry_id_is_optional() {
+        let result = parse_result(&json!({
+            "artifacts": [{ "type": "tags", "tags": ["a"] }]
+        }))
+        .unwrap()
+        .unwrap();
+        assert_eq!(result.entry_id, None);
+        assert!(rejected(json!({ "entry_id": "12", "artifacts": [] })).contains("entry_id"));
+    }
+
+    #[test]
+    fn malformed_artifacts_are_rejected_with_index() {
+        assert_eq!(
+            rejected(json!({ "artifacts": { "type": "tags" } })),
+            "artifacts must be a list"
+        );
+        let msg = rejected(json!({ "artifacts": [
+            { "type": "tags", "tags": ["ok"] },
+            { "type": "video", "path": "/tmp/x" }
+        ] }));
+        assert!(msg.starts_with("artifact 1:"), "{msg}");
+        let msg = rejected(json!({ "artifacts": [
+            { "type": "tags", "tags": ["ok"], "colour": "red" }
+        ] }));
+        assert!(msg.contains("colour"), "{msg}");
+        let msg = rejected(json!({ "artifacts": [
+            { "type": "metadata", "patch": { "no_such_field": 1 } }
+        ] }));
+        assert!(msg.starts_with("artifact 0:"), "{msg}");
+        let too_many: Vec<_> = (0..=MAX_ARTIFACTS)
+            .map(|_| json!({ "type": "tags", "tags": ["a"] }))
+            .collect();
+        assert!(rejected(json!({ "artifacts": too_many })).contains("at most"));
+    }
+
+    #[test]
+    fn artifact_rules() {
+        let msg = rejected(json!({ "artifacts": [{ "type": "tags", "tags": [] }] }));
+        assert_eq!(msg, "artifact 0: no tags given");
+        let msg = rejected(json!({ "artifacts": [{ "type": "tags", "tags": ["a//b"] }] }));
+        assert!(msg.contains("empty namespace segment"), "{msg}");
+        let msg = rejected(json!({ "artifacts": [{ "type": "metadata", "patch": {} }] }));
+        assert!(msg.contains("no fields"), "{msg}");
+        let msg = rejected(json!({ "artifacts": [
+            { "type": "sequence", "name": "x", "start_timestamp": 5, "end_timestamp": 1 }
+        ] }));
+        assert!(msg.contains("before start_timestamp"), "{msg}");
+        let msg = rejected(json!({ "artifacts": [{ "type": "metrics", "values": {} }] }));
+        assert!(msg.contains("no metrics"), "{msg}");
+        let msg = rejected(json!({ "artifacts": [
+            { "type": "metrics", "values": { "fps": "fast" } }
+        ] }));
+        assert!(msg.starts_with("artifact 0:"), "{msg}");
+    }
+
+    #[test]
+    fn files_need_absolute_path_and_known_role() {
+        let path = std::env::temp_dir().join(format!(
+            "artifact-{}.png",
+            chrono::Utc::now().timestamp_micros()
+        ));
+        std::fs::write(&path, b"png").unwrap();
+        let path = path.to_string_lossy().to_string();
+
+        let result = parse_result(&json!({ "artifacts": [
+            { "type": "file", "path": path, "role": "thumbnail", "media_type": "image/png" }
+        ] }))
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            result.artifacts[0].content(),
+            json!({ "path": path, "role": "thumbnail", "media_type": "image/png" })
+        );
+        // ohne Rolle gilt `other`
+        let result = parse_result(&json!({ "artifacts": [{ "type": "file", "path": path }] }))
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.artifacts[0].content()["role"], "other");
+
+        let msg = rejected(json!({ "artifacts": [
+            { "type": "file", "path": path, "role": "poster" }
+        ] }));
+        assert!(msg.contains("unknown file role 'poster'"), "{msg}");
+        let msg = rejected(json!({ "artifacts": [{ "type": "file", "path": "thumbs/a.png" }] }));
+        assert!(msg.contains("must be absolute"), "{msg}");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    fn file(path: &std::path::Path) -> PluginArtifact {
+        PluginArtifact::File {
+            path: path.to_string_lossy().to_string(),
+            role: "other".to_string(),
+            media_type: None,
+        }
+    }
+
+    async fn file_rejected(path: &std::path::Path, roots: &[std::path::PathBuf]) -> String {
+        match resolve_files(&mut [file(path)], roots).await {
+            Err(StorageError::ValidationError(msg)) => msg,
+            other => panic!("expected validation error, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn files_must_lie_below_allowed_roots() {
+        let base = std::env::temp_dir().join(format!(
+            "artifact-roots-{}",
+            chrono::Utc::now().timestamp_micros()
+        ));
+        let root = base.join("instance-1");
+        let outside = base.join("secret.txt");
+        std::fs::create_dir_all(root.join("out")).unwrap();
+        std::fs::write(root.join("out/a.png"), b"png").unwrap();
+        std::fs::write(&outside, b"secret").unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("link.txt")).unwrap();
+        // fehlende Wurzeln werden übergangen
+        let roots = vec![base.join("missing"), root.clone()];
+
+        let mut artifacts = vec![
+            PluginArtifact::Tags {
+                tags: vec!["a".to_string()],
+            },
+            file(&root.join("out/../out/a.png")),
+        ];
+        resolve_files(&mut artifacts, &roots).await.unwrap();
+        let canonical = std::fs::canonicalize(root.join("out/a.png")).unwrap();
+        assert_eq!(artifacts[1], file(&canonical));
+
+        let msg = file_rejected(&outside, &roots).await;
+        assert!(msg.contains("is outside"), "{msg}");
+        let msg = file_rejected(&root.join("../secret.txt"), &roots).await;
+        assert!(msg.contains("is outside"), "{msg}");
+        let msg = file_rejected(&root.join("link.txt"), &roots).await;
+        assert!(msg.contains("is outside"), "{msg}");
+        let msg = file_rejected(&root.join("out"), &roots).await;
+        assert!(msg.contains("not a regular file"), "{msg}");
+        let msg = file_rejected(&root.join("gone.png"), &roots).await;
+        assert_eq!(
+            msg,
+            format!(
+                "artifact 0: file '{}' does not exist or is not a regular file",
+                root.join("gone.png").display()
+            )
+        );
+
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn kind_filter_is_checked() {
+        for kind in ["tags", "metadata", "sequence", "file", "metrics"] {
+            assert!(validate_kind(kind).is_ok());
+        }
+        assert!(matches!(
+            validate_kind("video"),
+            Err(StorageError::ValidationError(_))
+        ));
+    }
+}
@@ -0,0 +1,79 @@
+//! Audit log helpers: outcome, route parameters and filters (pure functions, no DB).
+
+#[cfg(test)]
+mod tests {
+    use backend::error::StorageError;
+    use backend::storage::audit::{AuditFilter, is_audited_method, outcome, path_params};
+    use serde_json::json;
+
+    #[test]
+    fn only_mutating_methods_are_audited() {
+        for method in ["POST", "PUT", "PATCH", "DELETE"] {
+            assert!(is_audited_method(method));
+        }
+        for method in ["GET", "HEAD", "OPTIONS"] {
+            assert!(!is_audited_method(method));
+        }
+    }
+
+    #[test]
+    fn outcome_follows_status() {
+        assert_eq!(outcome(200), "success");
+        assert_eq!(outcome(204), "success");
+        assert_eq!(outcome(401), "denied");
+        assert_eq!(outcome(403), "denied");
+        assert_eq!(outcome(412), "rejected");
+        assert_eq!(outcome(422), "rejected");
+        assert_eq!(outcome(500), "error");
+    }
+
+    #[test]
+    fn path_params_follow_the_route() {
+        let params = path_params(
+            "/entries/<entry_id>/sensors/<sensor_id>",
+            "/entries/12/sensors/3",
+        );
+        assert_eq!(
+            serde_json::Value::Object(params),
+            json!({ "entry_id": "12", "sensor_id": "3" })
+        );
+        let params = path_params("/plugins/register", "/plugins/register");
+        assert!(params.is_empty());
+        let params = path_params("/files/<path..>", "/files/data/a/b.mcap");
+        assert_eq!(
+            serde_json::Value::Object(params),
+            json!({ "path": "data/a/b.mcap" })
+        );
+    }
+
+    #[test]
+    fn filter_parses_times_and_targets() {
+        let filter = AuditFilter::parse(
+            Some("user:anna".to_string()),
+            Some("entries/12/".to_string()),
+            Some("2026-05-01T00:00:00Z"),
+            Some("2026-05-04T12:00:00+02:00"),
+        )
+        .unwrap();
+        assert_eq!(filter.target.as_deref(), Some("/entries/12"));
+        assert_eq!(
+            filter.until.unwrap().to_rfc3339(),
+            "2026-05-04T10:00:00+00:00"
+        );
+
+        let err = AuditFilter::parse(None, None, Some("yesterday"), None).unwrap_err();
+        assert!(matches!(err, StorageError::ValidationError(m) if m.contains("since")));
+        let err = AuditFilter::parse(
+            None,
+            None,
+            Some("2026-05-04T00:00:00Z"),
+            Some("2026-05-01T00:00:00Z"),
+        )
+        .unwrap_err();
+        assert!(matches!(err, StorageError::ValidationError(_)));
+        assert_eq!(
+            AuditFilter::parse(Some(String::new()), None, None, None).unwrap(),
+            AuditFilter::default()
+        );
+    }
+}
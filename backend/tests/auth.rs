@@ -0,0 +1,132 @@
+//! Roles, scopes, password hashing, session and runner tokens (pure functions, no DB).
+
+#[cfg(test)]
+mod tests {
+    use backend::error::StorageError;
+    use backend::storage::auth::{
+        API_KEY_PREFIX, RUNNER_TOKEN_TTL, Role, Scope, bearer_token, generate_api_key,
+        generate_token, hash_password, issue_runner_token, revoke_runner_token, token_hash,
+        validate_password, validate_username, verify_dummy_password, verify_password,
+        verify_runner_token,
+    };
+    use chrono::{TimeDelta, Utc};
+
+    #[test]
+    fn roles_are_ordered() {
+        assert!(Role::Admin.allows(Role::PluginOperator));
+        assert!(Role::PluginOperator.allows(Role::Editor));
+        assert!(Role::Editor.allows(Role::Viewer));
+        assert!(Role::Editor.allows(Role::Editor));
+        assert!(!Role::Editor.allows(Role::PluginOperator));
+        assert!(!Role::Viewer.allows(Role::Editor));
+        for role in Role::ALL {
+            assert_eq!(Role::parse(role.as_str()).unwrap(), role);
+            let json = serde_json::to_value(role).unwrap();
+            assert_eq!(json, role.as_str());
+        }
+        assert!(matches!(
+            Role::parse("plugin_operator"),
+            Err(StorageError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn passwords_are_salted_and_verified() {
+        let hash = hash_password("correct horse battery").unwrap();
+        assert!(hash.starts_with("$argon2id$"));
+        assert_ne!(hash, hash_password("correct horse battery").unwrap());
+        assert!(verify_password("correct horse battery", &hash));
+        assert!(!verify_password("correct horse battery!", &hash));
+        assert!(!verify_password("correct horse battery", "not a hash"));
+        assert!(!verify_dummy_password("dummy password"));
+    }
+
+    #[test]
+    fn tokens_are_random_and_hashed() {
+        let token = generate_token();
+        assert_eq!(token.len(), 64);
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(token, generate_token());
+        assert_eq!(
+            token_hash("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn bearer_header() {
+        assert_eq!(bearer_token("Bearer abc"), Some("abc"));
+        assert_eq!(bearer_token("bearer  abc "), Some("abc"));
+        assert_eq!(bearer_token("Basic abc"), None);
+        assert_eq!(bearer_token("Bearer "), None);
+        assert_eq!(bearer_token("abc"), None);
+    }
+
+    #[test]
+    fn usernames_and_passwords_are_validated() {
+        assert!(validate_username("anna.mueller-2").is_ok());
+        assert!(validate_username("").is_err());
+        assert!(validate_username("anna mueller").is_err());
+        assert!(validate_username(&"a".repeat(65)).is_err());
+        assert!(validate_password("0123456789").is_ok());
+        assert!(validate_password("short").is_err());
+    }
+
+    #[test]
+    fn scopes_map_to_roles() {
+        assert_eq!(Scope::for_role(Role::Viewer), Some(Scope::ReadEntries));
+        assert_eq!(Scope::for_role(Role::Editor), Some(Scope::WriteMetadata));
+        assert_eq!(
+            Scope::for_role(Role::PluginOperator),
+            Some(Scope::RunPlugins)
+        );
+        assert_eq!(Scope::for_role(Role::Admin), None);
+        for scope in Scope::ALL {
+            assert_eq!(Scope::parse(scope.as_str()).unwrap(), scope);
+            assert_eq!(serde_json::to_value(scope).unwrap(), scope.as_str());
+        }
+        assert!(Scope::parse("write:everything").is_err());
+    }
+
+    #[test]
+    fn api_keys_are_prefixed() {
+        let key = generate_api_key();
+        assert!(key.starts_with(API_KEY_PREFIX));
+        assert_eq!(key.len(), API_KEY_PREFIX.len() + 64);
+    }
+
+    #[test]
+    fn runner_tokens_are_signed_and_expire() {
+        let now = Utc::now();
+        let token = issue_runner_token(42, "metadata_yaml_export", now);
+        let claims = verify_runner_token(&token, now).unwrap();
+        assert_eq!(claims.instance_id, 42);
+        assert_eq!(claims.plugin_name, "metadata_yaml_export");
+        assert_eq!(
+            claims.expires_at.timestamp(),
+            (now + RUNNER_TOKEN_TTL).timestamp()
+        );
+
+        let later = now + RUNNER_TOKEN_TTL + TimeDelta::seconds(1);
+        assert!(verify_runner_token(&token, later).is_none());
+
+        // andere Instanz, gleiche Signatur
+        let forged = token.replacen("rt.42.", "rt.43.", 1);
+        assert!(verify_runner_token(&forged, now).is_none());
+        assert!(verify_runner_token("rt.42.1.00.00", now).is_none());
+        assert!(verify_runner_token(&generate_token(), now).is_none());
+    }
+
+    #[test]
+    fn runner_tokens_end_with_their_instance() {
+        let now = Utc::now();
+        let token = issue_runner_token(4242, "metadata_yaml_export", now);
+        let other = issue_runner_token(4243, "metadata_yaml_export", now);
+        assert!(verify_runner_token(&token, now).is_some());
+
+        revoke_runner_token(4242);
+        assert!(verify_runner_token(&token, now).is_none());
+        assert!(verify_runner_token(&other, now).is_some());
+        revoke_runner_token(4243);
+    }
+}
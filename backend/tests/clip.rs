@@ -0,0 +1,181 @@
+//! Clip tests: write a small MCAP, cut a time window / topic subset and read it back.
+
+#[cfg(test)]
+mod tests {
+    use backend::storage::clip::{ClipStats, remove_files_older_than, write_clip};
+    use std::borrow::Cow;
+    use std::collections::{BTreeMap, HashSet};
+    use std::fs::File;
+    use std::io::BufWriter;
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+    use std::time::{Duration, SystemTime};
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("clip_test_{}_{name}", std::process::id()))
+    }
+
+    /// Zwei Topics mit je 10 Nachrichten bei Log-Zeit 0, 1000, ..., 9000 ns.
+    fn write_source(path: &Path) {
+        let mut writer = mcap::Writer::new(BufWriter::new(File::create(path).unwrap())).unwrap();
+        let schema = Arc::new(mcap::Schema {
+            id: 1,
+            name: "Example".to_string(),
+            encoding: "jsonschema".to_string(),
+            data: Cow::Borrowed(b"{}"),
+        });
+        for (id, topic) in [(0u16, "/a"), (1, "/b")] {
+            let channel = Arc::new(mcap::Channel {
+                id,
+                topic: topic.to_string(),
+                schema: Some(schema.clone()),
+                message_encoding: "json".to_string(),
+                metadata: BTreeMap::new(),
+            });
+            for i in 0..10u64 {
+                writer
+                    .write(&mcap::Message {
+                        channel: channel.clone(),
+                        sequence: i as u32,
+                        log_time: i * 1000,
+                        publish_time: i * 1000,
+                        data: Cow::Borrowed(b"{}"),
+                    })
+                    .unwrap();
+            }
+        }
+        writer.finish().unwrap();
+    }
+
+    fn read_back(path: &Path) -> Vec<(String, u64, Option<String>)> {
+        let bytes = std::fs::read(path).unwrap();
+        mcap::MessageStream::new(&bytes)
+            .unwrap()
+            .map(|m| {
+                let m = m.unwrap();
+                (
+                    m.channel.topic.clone(),
+                    m.log_time,
+                    m.channel.schema.as_ref().map(|s| s.name.clone()),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn clip_keeps_only_messages_in_window() {
+        let source = temp_path("window_src.mcap");
+        let target = temp_path("window_out.mcap");
+        write_source(&source);
+
+        let stats = write_clip(&source, &target, 3000, 5000, None).unwrap();
+        assert_eq!(
+            stats,
+            ClipStats {
+                messages: 6,
+                channels: 2
+            }
+        );
+
+        let messages = read_back(&target);
+        assert_eq!(messages.len(), 6);
+        assert!(messages.iter().all(|(_, t, _)| (3000..=5000).contains(t)));
+        // Schema bleibt erhalten
+        assert!(
+            messages
+                .iter()
+                .all(|(_, _, s)| s.as_deref() == Some("Example"))
+        );
+
+        let _ = std::fs::remove_file(source);
+        let _ = std::fs::remove_file(target);
+    }
+
+    #[test]
+    fn clip_filters_topics() {
+        let source = temp_path("topics_src.mcap");
+        let target = temp_path("topics_out.mcap");
+        write_source(&source);
+
+        let topics: HashSet<String> = ["/b".to_string()].into_iter().collect();
+        let stats = write_clip(&source, &target, 0, 9000, Some(&topics)).unwrap();
+        assert_eq!(
+            stats,
+            ClipStats {
+                messages: 10,
+                channels: 1
+            }
+        );
+        assert!(read_back(&target).iter().all(|(topic, _, _)| topic == "/b"));
+
+        let _ = std::fs::remove_file(source);
+        let _ = std::fs::remove_file(target);
+    }
+
+    #[test]
+    fn clip_of_missing_source_leaves_no_file() {
+        let target = temp_path("missing_out.mcap");
+        assert!(write_clip(&temp_path("does_not_exist.mcap"), &target, 0, 1, None).is_err());
+        assert!(!target.exists());
+    }
+
+    #[test]
+    fn concurrent_clips_of_same_window_do_not_collide() {
+        let source = temp_path("concurrent_src.mcap");
+        let target = temp_path("concurrent_out.mcap");
+        write_source(&source);
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let (source, target) = (source.clone(), target.clone());
+                std::thread::spawn(move || write_clip(&source, &target, 0, 9000, None))
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap().unwrap().messages, 20);
+        }
+        assert_eq!(read_back(&target).len(), 20);
+
+        let _ = std::fs::remove_file(source);
+        let _ = std::fs::remove_file(target);
+    }
+
+    #[test]
+    fn kept_directories_survive_export_cleanup() {
+        let dir = temp_path("exports_keep");
+        std::fs::create_dir_all(dir.join("artifacts").join("7")).unwrap();
+        std::fs::write(dir.join("clip.mcap"), b"x").unwrap();
+        std::fs::write(dir.join("artifacts").join("7").join("thumb.png"), b"x").unwrap();
+
+        let future = SystemTime::now() + Duration::from_secs(3600);
+        let keep = [dir.join("artifacts")];
+        assert_eq!(remove_files_older_than(&dir, future, &keep).unwrap(), 1);
+        assert!(!dir.join("clip.mcap").exists());
+        assert!(dir.join("artifacts").join("7").join("thumb.png").exists());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn expired_exports_are_removed() {
+        let dir = temp_path("exports");
+        std::fs::create_dir_all(dir.join("plugin")).unwrap();
+        std::fs::write(dir.join("clip.mcap"), b"x").unwrap();
+        std::fs::write(dir.join("plugin").join("report.zip"), b"x").unwrap();
+
+        let past = SystemTime::now() - Duration::from_secs(3600);
+        assert_eq!(remove_files_older_than(&dir, past, &[]).unwrap(), 0);
+        assert!(dir.join("clip.mcap").exists());
+
+        let future = SystemTime::now() + Duration::from_secs(3600);
+        assert_eq!(remove_files_older_than(&dir, future, &[]).unwrap(), 2);
+        assert!(!dir.join("clip.mcap").exists());
+        assert!(dir.join("plugin").is_dir());
+
+        let _ = std::fs::remove_dir_all(dir);
+        assert_eq!(
+            remove_files_older_than(&temp_path("no_exports"), future, &[]).unwrap(),
+            0
+        );
+    }
+}
@@ -0,0 +1,64 @@
+//! Snapshot diff and CSV export of collections (pure functions, no DB).
+
+#[cfg(test)]
+mod tests {
+    use backend::storage::collections::{csv_field, diff_items, items_to_csv};
+    use backend::storage::models::CollectionSnapshotItem;
+
+    fn item(entry_id: i64, sequence_id: Option<i64>, size: i64) -> CollectionSnapshotItem {
+        CollectionSnapshotItem {
+            entry_id,
+            entry_name: format!("entry{entry_id}"),
+            path: format!("/data/entry{entry_id}.mcap"),
+            size,
+            sequence_id,
+            sequence_name: sequence_id.map(|s| format!("seq{s}")),
+            start_timestamp: sequence_id.map(|_| 100),
+            end_timestamp: sequence_id.map(|_| 200),
+        }
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_changed_items() {
+        let from = vec![item(1, None, 10), item(2, Some(5), 20), item(3, None, 30)];
+        let to = vec![item(1, None, 10), item(2, Some(5), 25), item(4, None, 40)];
+        let diff = diff_items(&from, &to);
+        assert_eq!(diff.added, vec![item(4, None, 40)]);
+        assert_eq!(diff.removed, vec![item(3, None, 30)]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].before.size, 20);
+        assert_eq!(diff.changed[0].after.size, 25);
+    }
+
+    #[test]
+    fn whole_entry_and_its_sequence_are_different_items() {
+        let from = vec![item(1, None, 10)];
+        let to = vec![item(1, None, 10), item(1, Some(7), 10)];
+        let diff = diff_items(&from, &to);
+        assert_eq!(diff.added, vec![item(1, Some(7), 10)]);
+        assert!(diff.removed.is_empty());
+        assert!(diff.changed.is_empty());
+        assert!(diff_items(&to, &to).is_empty());
+    }
+
+    #[test]
+    fn csv_fields_are_quoted_only_when_needed() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn csv_export_has_header_and_one_row_per_item() {
+        let csv = items_to_csv(&[item(1, None, 10), item(2, Some(5), 20)]);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "entry_id,entry_name,path,size,sequence_id,sequence_name,start_timestamp,end_timestamp",
+                "1,entry1,/data/entry1.mcap,10,,,,",
+                "2,entry2,/data/entry2.mcap,20,5,seq5,100,200",
+            ]
+        );
+    }
+}
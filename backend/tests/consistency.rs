@@ -0,0 +1,109 @@
+//! Sensor/topic consistency tests (pure functions, no DB).
+
+#[cfg(test)]
+mod tests {
+    use backend::storage::consistency::{ConsistencyIssue, check_consistency, expected_frequency};
+    use backend::storage::models::{Sensor, Topic};
+    use chrono::Utc;
+    use serde_json::json;
+
+    fn sensor(id: i64, name: &str, topics: &[&str], params: Option<serde_json::Value>) -> Sensor {
+        Sensor {
+            id,
+            entry_id: 1,
+            sensor_name: name.to_string(),
+            manufacturer: None,
+            sensor_type: None,
+            ros_topics: topics.iter().map(|t| t.to_string()).collect(),
+            custom_parameters: params,
+            version: 1,
+        }
+    }
+
+    fn topic(name: &str, message_count: i64, frequency: Option<f64>) -> Topic {
+        let now = Utc::now();
+        Topic {
+            id: 0,
+            entry_id: 1,
+            topic_name: name.to_string(),
+            topic_type: None,
+            message_count,
+            frequency,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn consistent_recording_has_no_issues() {
+        let sensors = [sensor(1, "front_camera", &["/cam/front"], None)];
+        let topics = [topic("/cam/front", 300, Some(30.0)), topic("/tf", 10, None)];
+        assert!(check_consistency(&sensors, &topics).is_empty());
+    }
+
+    #[test]
+    fn flags_missing_and_empty_sensor_topics() {
+        let sensors = [sensor(
+            1,
+            "front_camera",
+            &["/cam/front", "/cam/front/info"],
+            None,
+        )];
+        let topics = [topic("/cam/front/info", 0, None)];
+        let issues = check_consistency(&sensors, &topics);
+        assert_eq!(issues.len(), 2);
+        assert!(
+            issues
+                .iter()
+                .all(|i| matches!(i, ConsistencyIssue::MissingTopic { sensor_id: 1, .. }))
+        );
+    }
+
+    #[test]
+    fn flags_unattributed_topics_only_when_sensors_are_declared() {
+        let topics = [topic("/radar/raw", 50, None)];
+        assert!(check_consistency(&[], &topics).is_empty());
+
+        let sensors = [sensor(1, "lidar", &[], None)];
+        assert_eq!(
+            check_consistency(&sensors, &topics),
+            vec![ConsistencyIssue::UnattributedTopic {
+                topic: "/radar/raw".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn flags_frequency_far_off_expected_rate() {
+        let sensors = [sensor(
+            1,
+            "lidar",
+            &["/lidar/points"],
+            Some(json!({"expected_frequency": "10 Hz"})),
+        )];
+        assert!(check_consistency(&sensors, &[topic("/lidar/points", 100, Some(9.0))]).is_empty());
+        let issues = check_consistency(&sensors, &[topic("/lidar/points", 100, Some(4.0))]);
+        assert!(matches!(
+            issues.as_slice(),
+            [ConsistencyIssue::FrequencyMismatch { expected_hz, actual_hz, .. }]
+                if *expected_hz == 10.0 && *actual_hz == 4.0
+        ));
+    }
+
+    #[test]
+    fn expected_frequency_supports_per_topic_rates() {
+        let s = sensor(
+            1,
+            "camera",
+            &["/cam/image", "/cam/info"],
+            Some(json!({"rate": {"/cam/image": 30, "/cam/info": 1.5}})),
+        );
+        assert_eq!(expected_frequency(&s, "/cam/image"), Some(30.0));
+        assert_eq!(expected_frequency(&s, "/cam/info"), Some(1.5));
+        assert_eq!(expected_frequency(&s, "/cam/other"), None);
+        assert_eq!(
+            expected_frequency(&sensor(2, "x", &[], None), "/cam/image"),
+            None
+        );
+    }
+}
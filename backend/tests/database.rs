@@ -65,6 +65,9 @@ async fn test_get_entry() {
         name: "Test Entry".to_string(),
         created_at: now,
         updated_at: now,
+        version: 1,
+        mcap_start_ns: None,
+        mcap_end_ns: None,
         path: "/test/path/entry".to_string(),
         size: 123,
         status: "Complete".to_string(),
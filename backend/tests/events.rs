@@ -0,0 +1,120 @@
+//! Change events: type filter, entry filter and plugin instance forwarding (no DB).
+
+#[cfg(test)]
+mod tests {
+    use backend::error::StorageError;
+    use backend::events::{CatalogEvent, EVENT_TYPES, EventBus, EventFilter};
+    use backend::plugin_manager::logs::InstanceLogs;
+    use backend::plugin_manager::manager::{InstanceState, PluginHandle, PluginManager};
+    use std::time::Duration;
+    use tokio::sync::{mpsc, watch};
+
+    fn entry_created(entry_id: i64) -> CatalogEvent {
+        CatalogEvent::EntryCreated {
+            entry_id,
+            path: format!("/data/drive_{entry_id}.mcap"),
+        }
+    }
+
+    #[test]
+    fn events_serialize_with_their_type() {
+        let json = serde_json::to_value(entry_created(7)).unwrap();
+        assert_eq!(json["type"], "entry_created");
+        assert_eq!(json["entry_id"], 7);
+        let json = serde_json::to_value(CatalogEvent::PluginState {
+            instance_id: 3,
+            plugin_name: "detector".to_string(),
+            state: InstanceState::Completed,
+        })
+        .unwrap();
+        assert_eq!(json["type"], "plugin_state");
+        assert_eq!(json["state"], "Completed");
+        assert!(EVENT_TYPES.contains(&entry_created(1).kind()));
+    }
+
+    #[test]
+    fn filter_by_type_and_entry() {
+        let all = EventFilter::parse(None, None).unwrap();
+        assert!(all.matches(&entry_created(1)));
+
+        let filter = EventFilter::parse(Some("metadata_updated, entry_created"), Some(1)).unwrap();
+        assert!(filter.matches(&entry_created(1)));
+        assert!(!filter.matches(&entry_created(2)));
+        assert!(filter.matches(&CatalogEvent::MetadataUpdated { entry_id: 1 }));
+        assert!(!filter.matches(&CatalogEvent::EntryDeleted {
+            entry_id: 1,
+            path: String::new(),
+        }));
+        // Plugin-Ereignisse gehören zu keinem Eintrag
+        let progress = CatalogEvent::PluginProgress {
+            instance_id: 1,
+            plugin_name: "detector".to_string(),
+            progress: 0.5,
+        };
+        assert!(
+            !EventFilter::parse(None, Some(1))
+                .unwrap()
+                .matches(&progress)
+        );
+        assert!(
+            EventFilter::parse(Some("plugin_progress"), None)
+                .unwrap()
+                .matches(&progress)
+        );
+
+        assert!(matches!(
+            EventFilter::parse(Some("entry_created,entry_moved"), None),
+            Err(StorageError::ValidationError(m)) if m.contains("entry_moved")
+        ));
+    }
+
+    #[tokio::test]
+    async fn plugin_instance_transitions_are_forwarded() {
+        let bus = EventBus::new();
+        let mut rx = bus.subscribe();
+        let mut pm = PluginManager::new().with_events(bus.clone());
+
+        let (command_tx, _command_rx) = mpsc::channel(1);
+        let (status_tx, status_rx) = watch::channel(InstanceState::Running);
+        let (progress_tx, progress_rx) = watch::channel(0.0_f32);
+        let handle = PluginHandle {
+            plugin_index: 0,
+            command_tx,
+            status_rx,
+            progress_rx,
+            data: String::new(),
+            outcome_rx: watch::channel(None).1,
+            logs: InstanceLogs::new(42),
+            workdir_hold: None,
+        };
+        pm.commit_started_instance(42, handle).unwrap();
+
+        let mut next = async || {
+            tokio::time::timeout(Duration::from_secs(1), rx.recv())
+                .await
+                .expect("event expected")
+                .unwrap()
+        };
+        assert!(matches!(
+            next().await,
+            CatalogEvent::PluginState {
+                instance_id: 42,
+                state: InstanceState::Running,
+                ..
+            }
+        ));
+        progress_tx.send(0.5).unwrap();
+        assert!(matches!(
+            next().await,
+            CatalogEvent::PluginProgress { progress, .. } if progress == 0.5
+        ));
+        status_tx.send(InstanceState::Completed).unwrap();
+        assert!(matches!(
+            next().await,
+            CatalogEvent::PluginState {
+                state: InstanceState::Completed,
+                ..
+            }
+        ));
+    }
+}
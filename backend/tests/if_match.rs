@@ -0,0 +1,30 @@
+//! `If-Match`-Auswertung: starke Tags, Wildcard und abgelehnte schwache Tags (pure, no DB).
+
+#[cfg(test)]
+mod tests {
+    use backend::error::{Error, StorageError};
+    use backend::routes::database::IfMatch;
+
+    #[test]
+    fn strong_tag_and_wildcard_are_accepted() {
+        assert_eq!(IfMatch::parse("\"3\"").unwrap(), IfMatch(Some(3)));
+        assert_eq!(IfMatch::parse(" 7 ").unwrap(), IfMatch(Some(7)));
+        assert_eq!(IfMatch::parse("*").unwrap(), IfMatch(None));
+    }
+
+    #[test]
+    fn weak_tag_fails_precondition() {
+        assert!(matches!(
+            IfMatch::parse("W/\"3\""),
+            Err(Error::StorageError(StorageError::PreconditionFailed(_)))
+        ));
+    }
+
+    #[test]
+    fn garbage_is_a_parsing_error() {
+        assert!(matches!(
+            IfMatch::parse("\"abc\""),
+            Err(Error::ParsingError(_))
+        ));
+    }
+}
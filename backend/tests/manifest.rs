@@ -0,0 +1,153 @@
+//! Catalog manifest encoding in JSON, CSV and Parquet (pure functions, no DB).
+
+#[cfg(test)]
+mod tests {
+    use backend::storage::manifest::{
+        MANIFEST_COLUMNS, ManifestEncoder, ManifestFilter, ManifestFormat, ManifestHeader,
+        ManifestRow, PARQUET_HEADER_KEY, arrow_schema, csv_row,
+    };
+    use chrono::{TimeZone, Utc};
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    fn row(entry_id: i64) -> ManifestRow {
+        let t = Utc.with_ymd_and_hms(2026, 4, 20, 8, 0, 0).unwrap();
+        ManifestRow {
+            entry_id,
+            name: format!("drive_{entry_id}"),
+            path: format!("/data/drive_{entry_id}.mcap"),
+            size: 4096,
+            status: "Complete".to_string(),
+            created_at: t,
+            updated_at: t,
+            mcap_start_ns: Some(1),
+            mcap_end_ns: Some(2),
+            time_machine: None,
+            platform_name: Some("TestCar, blue".to_string()),
+            platform_image_link: None,
+            scenario_name: None,
+            scenario_creation_time: None,
+            scenario_description: None,
+            sequence_duration: Some(12.5),
+            sequence_distance: None,
+            sequence_lat_starting_point_deg: None,
+            sequence_lon_starting_point_deg: None,
+            weather_cloudiness: None,
+            weather_precipitation: None,
+            weather_precipitation_deposits: None,
+            weather_wind_intensity: None,
+            weather_road_humidity: None,
+            weather_fog: Some(false),
+            weather_snow: None,
+            tags: vec!["weather/rain".to_string(), "night".to_string()],
+            topic_count: 2,
+            message_count: 300,
+            topics: vec!["/camera".to_string(), "/lidar".to_string()],
+            sensors: vec!["front_camera".to_string()],
+            sequence_count: 0,
+            sequences: vec![],
+        }
+    }
+
+    fn header() -> ManifestHeader {
+        ManifestHeader::new(
+            ManifestFilter {
+                search_string: Some("drive".to_string()),
+                ..Default::default()
+            },
+            2,
+        )
+    }
+
+    fn encode(format: ManifestFormat, chunks: &[Vec<ManifestRow>]) -> Vec<u8> {
+        let mut encoder = ManifestEncoder::new(format, header()).unwrap();
+        let mut out = encoder.begin().unwrap();
+        for chunk in chunks {
+            out.extend(encoder.encode(chunk).unwrap());
+        }
+        out.extend(encoder.finish().unwrap());
+        out
+    }
+
+    #[test]
+    fn columns_match_row_fields_and_arrow_schema() {
+        let json = serde_json::to_string(&row(1)).unwrap();
+        let positions: Vec<usize> = MANIFEST_COLUMNS
+            .iter()
+            .map(|c| json.find(&format!("\"{c}\":")).unwrap())
+            .collect();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
+        let value = serde_json::to_value(row(1)).unwrap();
+        assert_eq!(value.as_object().unwrap().len(), MANIFEST_COLUMNS.len());
+        let schema = arrow_schema();
+        let fields: Vec<&str> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+        assert_eq!(fields, MANIFEST_COLUMNS);
+    }
+
+    #[test]
+    fn csv_has_comment_header_and_joined_lists() {
+        let csv = String::from_utf8(encode(ManifestFormat::Csv, &[vec![row(1)]])).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert!(lines[0].starts_with("# schema_version: "));
+        assert!(lines[1].starts_with("# generated_at: "));
+        assert!(lines[2].contains("\"search_string\":\"drive\""));
+        assert_eq!(lines[4], MANIFEST_COLUMNS.join(","));
+        assert_eq!(lines.len(), 6);
+        assert!(lines[5].starts_with("1,drive_1,/data/drive_1.mcap,4096,Complete,"));
+        assert!(lines[5].contains(",\"TestCar, blue\","));
+        assert!(
+            lines[5].ends_with(",false,,weather/rain;night,2,300,/camera;/lidar,front_camera,0,")
+        );
+        assert_eq!(csv_row(&row(1)), format!("{}\n", lines[5]));
+    }
+
+    #[test]
+    fn json_stream_is_one_valid_document() {
+        let bytes = encode(ManifestFormat::Json, &[vec![row(1)], vec![], vec![row(2)]]);
+        let doc: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(doc["header"]["entry_count"], 2);
+        assert_eq!(doc["header"]["filter"]["search_string"], "drive");
+        let entries = doc["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1]["entry_id"], 2);
+        assert_eq!(entries[0]["tags"][0], "weather/rain");
+
+        let empty = encode(ManifestFormat::Json, &[]);
+        let doc: serde_json::Value = serde_json::from_slice(&empty).unwrap();
+        assert!(doc["entries"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn parquet_has_one_row_group_per_chunk_and_header_metadata() {
+        let bytes = encode(
+            ManifestFormat::Parquet,
+            &[vec![row(1), row(2)], vec![row(3)]],
+        );
+        let path =
+            std::env::temp_dir().join(format!("manifest_test_{}.parquet", std::process::id()));
+        std::fs::write(&path, &bytes).unwrap();
+        let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let metadata = reader.metadata();
+        assert_eq!(metadata.num_row_groups(), 2);
+        assert_eq!(metadata.file_metadata().num_rows(), 3);
+        let header = metadata
+            .file_metadata()
+            .key_value_metadata()
+            .unwrap()
+            .iter()
+            .find(|kv| kv.key == PARQUET_HEADER_KEY)
+            .and_then(|kv| kv.value.clone())
+            .unwrap();
+        let header: ManifestHeader = serde_json::from_str(&header).unwrap();
+        assert_eq!(header.columns, MANIFEST_COLUMNS);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn unknown_formats_are_rejected() {
+        assert_eq!(
+            ManifestFormat::parse("parquet").unwrap(),
+            ManifestFormat::Parquet
+        );
+        assert!(ManifestFormat::parse("xlsx").is_err());
+    }
+}
@@ -0,0 +1,154 @@
+//! Parsing of metadata imports from CSV and JSON (pure functions, no DB).
+
+#[cfg(test)]
+mod tests {
+    use backend::error::StorageError;
+    use backend::storage::manifest::MANIFEST_COLUMNS;
+    use backend::storage::metadata_import::{
+        ImportKey, metadata_fields, parse_csv, parse_csv_rows, parse_json_rows, patch_fields,
+    };
+
+    #[test]
+    fn csv_records_follow_rfc4180() {
+        let body = "# comment with \"quote\n\npath,scenario_description\r\n\
+                    /data/a.mcap,\"line one\nline \"\"two\"\"\"\n/data/b.mcap,plain\n";
+        let records = parse_csv(body).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].0, 3);
+        assert_eq!(records[1].1[1], "line one\nline \"two\"");
+        assert_eq!(records[2].0, 6);
+        assert!(matches!(
+            parse_csv("path\n\"/data/a.mcap\n"),
+            Err(StorageError::DecodingError(_))
+        ));
+    }
+
+    #[test]
+    fn csv_rows_build_patches_tags_and_sequences() {
+        let body = "path,name,time_machine,weather_fog,scenario_creation_time,tags,\
+                    sequence_name,sequence_start_timestamp,sequence_end_timestamp,sequence_tags\n\
+                    /data/a.mcap,,1.5,yes,2026-04-20,night;weather/rain,overtake,10,20,manoeuvre\n\
+                    ,drive_b,,,,,,,,\n\
+                    /data/c.mcap,,fast,,,,,,,\n";
+        let rows = parse_csv_rows(body).unwrap();
+        assert_eq!(rows.len(), 3);
+
+        let a = rows[0].as_ref().unwrap();
+        assert_eq!(a.row, 2);
+        assert_eq!(a.key, ImportKey::Path("/data/a.mcap".to_string()));
+        assert_eq!(a.metadata.time_machine, Some(1.5));
+        assert_eq!(a.metadata.weather_fog, Some(true));
+        assert_eq!(
+            a.metadata.scenario_creation_time.unwrap().to_rfc3339(),
+            "2026-04-20T00:00:00+00:00"
+        );
+        assert_eq!(
+            patch_fields(&a.metadata),
+            vec!["scenario_creation_time", "time_machine", "weather_fog"]
+        );
+        assert_eq!(a.tags, vec!["night", "weather/rain"]);
+        assert_eq!(a.sequences.len(), 1);
+        assert_eq!(a.sequences[0].name, "overtake");
+        assert_eq!(
+            (a.sequences[0].start_timestamp, a.sequences[0].end_timestamp),
+            (10, 20)
+        );
+        assert_eq!(a.sequences[0].tags, vec!["manoeuvre"]);
+
+        let b = rows[1].as_ref().unwrap();
+        assert_eq!(b.key, ImportKey::Name("drive_b".to_string()));
+        assert!(patch_fields(&b.metadata).is_empty());
+
+        let c = rows[2].as_ref().unwrap_err();
+        assert_eq!(c.row, 4);
+        assert_eq!(c.key, Some(ImportKey::Path("/data/c.mcap".to_string())));
+        assert!(c.message.contains("time_machine"), "{}", c.message);
+    }
+
+    #[test]
+    fn csv_header_is_checked() {
+        assert!(matches!(
+            parse_csv_rows("path,wheather_fog\n/data/a.mcap,true\n"),
+            Err(StorageError::ValidationError(m)) if m.contains("'wheather_fog'")
+        ));
+        assert!(matches!(
+            parse_csv_rows("scenario_name\nhighway\n"),
+            Err(StorageError::ValidationError(_))
+        ));
+        let rows = parse_csv_rows("path,scenario_name\n/data/a.mcap\n").unwrap();
+        assert!(rows[0].as_ref().unwrap_err().message.contains("columns"));
+    }
+
+    #[test]
+    fn manifest_header_is_accepted() {
+        // Alle Manifest-Spalten sind importierbar oder werden ignoriert
+        let header = MANIFEST_COLUMNS.join(",");
+        let mut cells = vec![String::new(); MANIFEST_COLUMNS.len()];
+        let column = |name: &str| MANIFEST_COLUMNS.iter().position(|c| *c == name).unwrap();
+        cells[column("path")] = "/data/a.mcap".to_string();
+        cells[column("size")] = "4096".to_string();
+        cells[column("topics")] = "/camera;/lidar".to_string();
+        cells[column("sequences")] = "overtake".to_string();
+        cells[column("platform_name")] = "TestCar".to_string();
+        let body = format!("# manifest\n{header}\n{}\n", cells.join(","));
+        let rows = parse_csv_rows(&body).unwrap();
+        let row = rows[0].as_ref().unwrap();
+        assert_eq!(patch_fields(&row.metadata), vec!["platform_name"]);
+        assert!(row.sequences.is_empty());
+        assert!(
+            metadata_fields()
+                .iter()
+                .all(|f| MANIFEST_COLUMNS.contains(&f.as_str()))
+        );
+    }
+
+    #[test]
+    fn json_rows() {
+        let body = r#"[
+            {"path": "/data/a.mcap", "name": "ignored", "weather_snow": false,
+             "tags": ["night"], "size": 1, "topics": ["/camera"],
+             "sequences": ["existing", {"name": "stop", "start_timestamp": 1, "end_timestamp": 2}]},
+            {"name": "drive_b", "tags": "a;b"},
+            {"path": "/data/c.mcap", "weather_fgo": true},
+            42
+        ]"#;
+        let rows = parse_json_rows(body).unwrap();
+        assert_eq!(rows.len(), 4);
+        let a = rows[0].as_ref().unwrap();
+        assert_eq!(a.key, ImportKey::Path("/data/a.mcap".to_string()));
+        assert_eq!(a.metadata.weather_snow, Some(false));
+        assert_eq!(a.tags, vec!["night"]);
+        assert_eq!(a.sequences.len(), 1);
+        assert_eq!(a.sequences[0].description, "");
+        let b = rows[1].as_ref().unwrap();
+        assert_eq!(b.tags, vec!["a", "b"]);
+        assert!(
+            rows[2]
+                .as_ref()
+                .unwrap_err()
+                .message
+                .contains("weather_fgo")
+        );
+        assert_eq!(rows[3].as_ref().unwrap_err().row, 4);
+        assert!(matches!(
+            parse_json_rows("{\"path\": 1}"),
+            Err(StorageError::DecodingError(_))
+        ));
+    }
+
+    #[test]
+    fn later_rows_overlay_earlier_patches() {
+        let rows = parse_csv_rows(
+            "path,scenario_name,weather_fog\n\
+             /data/a.mcap,highway,true\n\
+             /data/a.mcap,city,\n",
+        )
+        .unwrap();
+        let first = rows[0].as_ref().unwrap().metadata.clone();
+        let second = &rows[1].as_ref().unwrap().metadata;
+        let merged = first.overlay(second);
+        assert_eq!(merged.scenario_name.as_deref(), Some("city"));
+        assert_eq!(merged.weather_fog, Some(true));
+        assert_eq!(patch_fields(&merged), vec!["scenario_name", "weather_fog"]);
+    }
+}
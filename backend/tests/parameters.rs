@@ -0,0 +1,199 @@
+//! Plugin parameter schemas: declaration checks, defaults and field errors (pure functions, no DB).
+
+#[cfg(test)]
+mod tests {
+    use backend::error::Error;
+    use backend::plugin_manager::parameters::{FieldError, ParameterSchema, invalid_parameters};
+    use serde_json::{Map, Value, json};
+
+    fn schema() -> ParameterSchema {
+        ParameterSchema::from_declaration(vec![
+            (
+                "entry_path".to_string(),
+                json!({ "type": "entry", "required": true, "description": "Aufnahme" }),
+            ),
+            (
+                "format".to_string(),
+                json!({ "type": "string", "enum": ["yaml", "json"], "default": "yaml" }),
+            ),
+            (
+                "limit".to_string(),
+                json!({ "type": "integer", "minimum": 1, "maximum": 100 }),
+            ),
+            (
+                "dry_run".to_string(),
+                json!({ "type": "boolean", "default": false }),
+            ),
+        ])
+        .expect("valid schema")
+    }
+
+    fn object(value: Value) -> Map<String, Value> {
+        value.as_object().cloned().expect("object")
+    }
+
+    #[test]
+    fn schema_keeps_declaration_order_and_serializes_as_list() {
+        let schema = schema();
+        let names: Vec<&str> = schema
+            .parameters()
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(names, ["entry_path", "format", "limit", "dry_run"]);
+
+        let value = serde_json::to_value(&schema).unwrap();
+        assert_eq!(
+            value[1],
+            json!({
+                "name": "format",
+                "type": "string",
+                "description": "",
+                "required": false,
+                "default": "yaml",
+                "enum": ["yaml", "json"],
+            })
+        );
+    }
+
+    #[test]
+    fn valid_payload_gets_defaults() {
+        let validated = schema()
+            .validate(object(json!({ "entry_path": "/data/a.mcap", "limit": 5 })))
+            .expect("valid payload");
+        assert_eq!(
+            Value::Object(validated),
+            json!({
+                "entry_path": "/data/a.mcap",
+                "format": "yaml",
+                "limit": 5,
+                "dry_run": false,
+            })
+        );
+    }
+
+    #[test]
+    fn null_counts_as_missing() {
+        let validated = schema()
+            .validate(object(
+                json!({ "entry_path": "/data/a.mcap", "format": null, "limit": null }),
+            ))
+            .expect("valid payload");
+        assert_eq!(validated["format"], json!("yaml"));
+        assert!(!validated.contains_key("limit"));
+    }
+
+    #[test]
+    fn all_field_errors_are_reported() {
+        let errors = schema()
+            .validate(object(json!({
+                "format": "xml",
+                "limit": 0,
+                "dry_run": "yes",
+                "verbose": true,
+            })))
+            .unwrap_err();
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            ["verbose", "entry_path", "format", "limit", "dry_run"]
+        );
+        assert_eq!(errors[0].message, "unknown parameter");
+        assert_eq!(errors[1].message, "required parameter is missing");
+    }
+
+    #[test]
+    fn integers_and_bounds_are_checked() {
+        let schema = schema();
+        for limit in [json!(1.5), json!("3"), json!(101)] {
+            let errors = schema
+                .validate(object(
+                    json!({ "entry_path": "/data/a.mcap", "limit": limit }),
+                ))
+                .unwrap_err();
+            assert_eq!(errors.len(), 1, "limit {limit}");
+            assert_eq!(errors[0].field, "limit");
+        }
+    }
+
+    #[test]
+    fn entry_paths_lists_entry_parameters() {
+        let schema = schema();
+        let validated = schema
+            .validate(object(json!({ "entry_path": "/data/a.mcap" })))
+            .unwrap();
+        assert_eq!(
+            schema.entry_paths(&validated),
+            [("entry_path", "/data/a.mcap")]
+        );
+
+        let errors = schema
+            .validate(object(json!({ "entry_path": " " })))
+            .unwrap_err();
+        assert_eq!(errors[0].field, "entry_path");
+    }
+
+    #[test]
+    fn per_entry_validation_leaves_entry_parameter_to_caller() {
+        let schema = schema();
+        assert_eq!(schema.entry_parameter(), Some("entry_path"));
+        let validated = schema
+            .validate_per_entry(object(json!({ "limit": 5 })))
+            .unwrap();
+        assert_eq!(
+            Value::Object(validated),
+            json!({ "format": "yaml", "limit": 5, "dry_run": false })
+        );
+        let errors = schema
+            .validate_per_entry(object(json!({ "entry_path": "/data/a.mcap", "limit": 0 })))
+            .unwrap_err();
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, ["entry_path", "limit"]);
+    }
+
+    #[test]
+    fn invalid_declarations_are_rejected() {
+        let declare =
+            |spec: Value| ParameterSchema::from_declaration(vec![("p".to_string(), spec)]);
+        assert!(declare(json!({ "type": "date" })).is_err());
+        assert!(declare(json!({ "description": "no type" })).is_err());
+        assert!(declare(json!({ "type": "string", "pattern": ".*" })).is_err());
+        assert!(declare(json!({ "type": "string", "required": true, "default": "a" })).is_err());
+        assert!(declare(json!({ "type": "integer", "enum": [1, "two"] })).is_err());
+        assert!(declare(json!({ "type": "integer", "minimum": 5, "default": 1 })).is_err());
+        assert!(declare(json!({ "type": "string", "maximum": 3 })).is_err());
+        assert!(declare(json!({ "type": "string", "enum": [] })).is_err());
+        assert!(
+            ParameterSchema::from_declaration(vec![(" ".to_string(), json!({ "type": "string" }))])
+                .is_err()
+        );
+        assert!(declare(json!({ "type": "number", "minimum": 0.5, "default": 1 })).is_ok());
+    }
+
+    #[rocket::get("/start")]
+    fn rejected_start() -> Result<(), Error> {
+        Err(invalid_parameters(vec![
+            FieldError::new("entry_path", "required parameter is missing"),
+            FieldError::new("limit", "expected integer, got \"3\""),
+        ]))
+    }
+
+    #[rocket::async_test]
+    async fn field_errors_become_structured_bad_request() {
+        let rocket = rocket::build().mount("/", rocket::routes![rejected_start]);
+        let client = rocket::local::asynchronous::Client::untracked(rocket)
+            .await
+            .unwrap();
+        let resp = client.get("/start").dispatch().await;
+        assert_eq!(resp.status(), rocket::http::Status::BadRequest);
+        assert_eq!(resp.content_type(), Some(rocket::http::ContentType::JSON));
+        let body: Value = resp.into_json().await.unwrap();
+        assert_eq!(
+            body,
+            json!([
+                { "field": "entry_path", "message": "required parameter is missing" },
+                { "field": "limit", "message": "expected integer, got \"3\"" }
+            ])
+        );
+    }
+}
@@ -13,7 +13,10 @@ async fn test_mcap_reading() {
     // );
     let path = std::path::Path::new("/data/excavator_drive.mcap");
     if !path.exists() {
-        eprintln!("Skipping test_mcap_reading: test file {:?} does not exist", path);
+        eprintln!(
+            "Skipping test_mcap_reading: test file {:?} does not exist",
+            path
+        );
         return;
     }
     // prefer resolving via `which mcap` (checks PATH)
@@ -68,3 +71,16 @@ async fn test_mcap_reading() {
         .await
         .expect("Failed to read MCAP file");
 }
+
+#[test]
+fn test_parse_seconds_ns_is_exact() {
+    use backend::storage::parsing::parse_seconds_ns;
+    // über f64 würden die letzten Nanosekunden verloren gehen
+    assert_eq!(
+        parse_seconds_ns("1700000000.123456789"),
+        Some(1_700_000_000_123_456_789)
+    );
+    assert_eq!(parse_seconds_ns(" 31.5 "), Some(31_500_000_000));
+    assert_eq!(parse_seconds_ns("12"), Some(12_000_000_000));
+    assert_eq!(parse_seconds_ns("abc"), None);
+}
@@ -0,0 +1,367 @@
+//! Plugin pipelines: config checks, result passing, failure policies and run status (pure, no DB).
+
+#[cfg(test)]
+mod tests {
+    use backend::plugin_manager::manager::{InstanceState, PluginManager};
+    use backend::plugin_manager::pipeline::{
+        PipelineConfig, PipelineRun, PipelineStatus, ReadyStep, StepState,
+    };
+    use backend::plugin_manager::plugin::{Plugin, Trigger};
+    use backend::plugin_manager::queue::{JobPriority, QueuedJob};
+    use backend::plugin_manager::runs::RunOutcome;
+    use serde_json::{Value, json};
+    use std::path::PathBuf;
+
+    fn config(yaml: &str) -> PipelineConfig {
+        serde_yaml::from_str(yaml).expect("pipeline config parses")
+    }
+
+    /// a -> (b, c) -> d
+    fn diamond(on_failure: &str) -> PipelineConfig {
+        config(&format!(
+            r#"
+name: diamond
+on_failure: {on_failure}
+steps:
+  - {{ name: a, plugin: pa }}
+  - {{ name: b, plugin: pb, needs: [a] }}
+  - {{ name: c, plugin: pc, needs: [a] }}
+  - {{ name: d, plugin: pd, needs: [b, c] }}
+"#
+        ))
+    }
+
+    fn completed(result: Value) -> RunOutcome {
+        RunOutcome {
+            result: Some(result),
+            ..RunOutcome::new(InstanceState::Completed)
+        }
+    }
+
+    fn failed() -> RunOutcome {
+        RunOutcome::failed(InstanceState::Failed, "boom".to_string())
+    }
+
+    fn names(ready: &[ReadyStep]) -> Vec<&str> {
+        ready.iter().map(|s| s.step.as_str()).collect()
+    }
+
+    fn states(run: &PipelineRun) -> Vec<(String, StepState)> {
+        run.view(|_| None)
+            .steps
+            .into_iter()
+            .map(|s| (s.name, s.state))
+            .collect()
+    }
+
+    fn state(run: &PipelineRun, step: &str) -> StepState {
+        states(run)
+            .into_iter()
+            .find(|(name, _)| name == step)
+            .map(|(_, state)| state)
+            .expect("step exists")
+    }
+
+    #[test]
+    fn invalid_pipelines_are_rejected() {
+        let check = |yaml: &str| config(yaml).validate();
+        assert!(check("{ name: p, steps: [{ name: a, plugin: x }] }").is_ok());
+        assert!(check("{ name: p, steps: [] }").is_err());
+        assert!(
+            check("{ name: p, steps: [{ name: a, plugin: x }, { name: a, plugin: y }] }").is_err()
+        );
+        assert!(check("{ name: p, steps: [{ name: a, plugin: x, needs: [b] }] }").is_err());
+        assert!(check("{ name: p, steps: [{ name: a, plugin: x, needs: [a] }] }").is_err());
+        let cycle = check(
+            "{ name: p, steps: [{ name: a, plugin: x }, \
+             { name: b, plugin: x, needs: [a, c] }, { name: c, plugin: x, needs: [b] }] }",
+        )
+        .unwrap_err();
+        assert!(cycle.contains("b, c"), "{cycle}");
+        // Filter nur mit Entry-Trigger.
+        assert!(
+            check("{ name: p, filter: { paths: ['*.mcap'] }, steps: [{ name: a, plugin: x }] }")
+                .is_err()
+        );
+
+        let parse = |yaml: &str| serde_yaml::from_str::<PipelineConfig>(yaml);
+        assert!(parse("{ name: p, steps: [{ name: a, plugin: x, need: [b] }] }").is_err());
+        assert!(parse("{ name: p, on_failure: ignore, steps: [{ name: a, plugin: x }] }").is_err());
+        assert!(
+            parse(
+                "{ name: p, trigger: on_entry_create, filter: { size: 1 }, \
+                 steps: [{ name: a, plugin: x }] }"
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn results_are_passed_to_dependent_steps() {
+        let mut run = PipelineRun::new(1, &diamond("stop"), r#"{"entry_id":7}"#.to_string());
+        let ready = run.take_ready();
+        assert_eq!(names(&ready), ["a"]);
+        assert_eq!(ready[0].plugin, "pa");
+        assert_eq!(ready[0].data, r#"{"entry_id":7}"#);
+        assert!(run.take_ready().is_empty());
+
+        run.finish("a", &completed(json!({ "entry_path": "/data/a.mcap" })));
+        let ready = run.take_ready();
+        assert_eq!(names(&ready), ["b", "c"]);
+        assert_eq!(ready[0].data, r#"{"entry_path":"/data/a.mcap"}"#);
+
+        // Text geht unverändert weiter, mehrere Vorgänger als Objekt.
+        run.finish("b", &completed(json!("stopped")));
+        run.finish("c", &RunOutcome::new(InstanceState::Completed));
+        let ready = run.take_ready();
+        assert_eq!(names(&ready), ["d"]);
+        let data: Value = serde_json::from_str(&ready[0].data).unwrap();
+        assert_eq!(data, json!({ "b": "stopped", "c": null }));
+
+        assert_eq!(run.status(), PipelineStatus::Running);
+        run.finish("d", &completed(json!("stopped")));
+        assert_eq!(run.status(), PipelineStatus::Completed);
+        assert!(run.finished_at.is_some());
+    }
+
+    #[test]
+    fn skip_dependents_keeps_independent_branches() {
+        let mut run = PipelineRun::new(1, &diamond("skip_dependents"), String::new());
+        run.take_ready();
+        run.finish("a", &completed(json!(1)));
+        assert_eq!(names(&run.take_ready()), ["b", "c"]);
+
+        assert!(run.finish("b", &failed()).is_empty());
+        assert!(run.take_ready().is_empty());
+        // `d` braucht `b` und entfällt, `c` läuft weiter.
+        assert_eq!(state(&run, "d"), StepState::Skipped);
+        assert_eq!(state(&run, "c"), StepState::Queued);
+        assert_eq!(run.status(), PipelineStatus::Running);
+
+        run.finish("c", &completed(json!(2)));
+        assert_eq!(run.status(), PipelineStatus::Failed);
+        let view = run.view(|_| None);
+        assert_eq!(view.steps[1].error.as_deref(), Some("boom"));
+        assert!(view.steps[3].error.as_deref().unwrap().contains("'b'"));
+    }
+
+    #[test]
+    fn continue_starts_dependents_without_result() {
+        let pipeline = config(
+            r#"
+name: p
+steps:
+  - { name: a, plugin: x, on_failure: continue }
+  - { name: b, plugin: y, needs: [a] }
+  - { name: c, plugin: z, needs: [b] }
+"#,
+        );
+        let mut run = PipelineRun::new(1, &pipeline, String::new());
+        run.take_ready();
+        run.finish("a", &failed());
+        let ready = run.take_ready();
+        assert_eq!(names(&ready), ["b"]);
+        assert_eq!(ready[0].data, "null");
+
+        // Für `b` gilt der Standard der Pipeline.
+        run.finish("b", &failed());
+        assert!(run.take_ready().is_empty());
+        assert_eq!(state(&run, "c"), StepState::Skipped);
+        assert_eq!(run.status(), PipelineStatus::Failed);
+    }
+
+    #[test]
+    fn stop_cancels_steps_that_have_not_started() {
+        let pipeline = config(
+            r#"
+name: p
+on_failure: stop
+steps:
+  - { name: a, plugin: x }
+  - { name: b, plugin: y }
+  - { name: c, plugin: z, needs: [a] }
+"#,
+        );
+        let mut run = PipelineRun::new(1, &pipeline, String::new());
+        assert_eq!(names(&run.take_ready()), ["a", "b"]);
+        run.assign("a", 11);
+        run.assign("b", 12);
+
+        // Die Instanz von `a` soll aus der Warteschlange, `c` startet nie.
+        assert_eq!(run.finish("b", &failed()), [11]);
+        assert_eq!(state(&run, "c"), StepState::Cancelled);
+        assert!(run.take_ready().is_empty());
+        assert_eq!(run.status(), PipelineStatus::Running);
+
+        run.finish(
+            "a",
+            &RunOutcome::failed(InstanceState::Stopped, "cancelled before start".to_string()),
+        );
+        assert_eq!(state(&run, "a"), StepState::Cancelled);
+        assert_eq!(run.status(), PipelineStatus::Failed);
+
+        // Spätere Meldungen zu beendeten Schritten ändern nichts mehr.
+        assert!(run.finish("a", &completed(json!(1))).is_empty());
+        assert_eq!(state(&run, "a"), StepState::Cancelled);
+    }
+
+    #[test]
+    fn stopped_step_counts_as_cancelled() {
+        let mut run = PipelineRun::new(1, &diamond("continue"), String::new());
+        run.take_ready();
+        run.finish(
+            "a",
+            &RunOutcome::failed(InstanceState::Stopped, "stopped by user".to_string()),
+        );
+        assert!(run.take_ready().is_empty());
+        assert_eq!(
+            states(&run),
+            [
+                ("a".to_string(), StepState::Cancelled),
+                ("b".to_string(), StepState::Skipped),
+                ("c".to_string(), StepState::Skipped),
+                ("d".to_string(), StepState::Skipped),
+            ]
+        );
+        assert_eq!(run.status(), PipelineStatus::Failed);
+    }
+
+    #[test]
+    fn view_aggregates_step_progress() {
+        let mut run = PipelineRun::new(1, &diamond("stop"), String::new());
+        run.take_ready();
+        run.finish("a", &completed(json!(1)));
+        run.take_ready();
+        run.assign("b", 21);
+        run.assign("c", 22);
+
+        let view = run.view(|instance_id| match instance_id {
+            21 => Some((InstanceState::Running, 0.5)),
+            22 => Some((InstanceState::Paused, 0.25)),
+            _ => None,
+        });
+        assert_eq!(view.status, PipelineStatus::Running);
+        assert_eq!(view.steps[1].state, StepState::Running);
+        assert_eq!(view.steps[2].state, StepState::Paused);
+        assert_eq!(view.steps[3].state, StepState::Waiting);
+        assert!((view.progress - (1.0 + 0.5 + 0.25) / 4.0).abs() < 1e-6);
+
+        let value = serde_json::to_value(&view).unwrap();
+        assert_eq!(value["status"], "Running");
+        assert_eq!(value["steps"][0]["state"], "Completed");
+        assert_eq!(value["steps"][3]["needs"], json!(["b", "c"]));
+    }
+
+    fn manager_with_config(yaml: &str) -> PluginManager {
+        let mut pm = PluginManager::new();
+        for name in ["stats", "export"] {
+            let mut plugin = Plugin::new(
+                name.to_string(),
+                "d".to_string(),
+                Trigger::Manual,
+                PathBuf::from(format!("/tmp/{name}.py")),
+            );
+            plugin.set_enabled(true);
+            pm.registered.push(plugin);
+        }
+        let path = std::env::temp_dir().join(format!(
+            "pipeline_test_{}_{}.yaml",
+            std::process::id(),
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        std::fs::write(&path, yaml).unwrap();
+        let applied = pm.load_config_and_apply(path.to_str().unwrap());
+        std::fs::remove_file(&path).ok();
+        applied.expect("config applies");
+        pm
+    }
+
+    fn queued(pm: &PluginManager) -> Vec<QueuedJob> {
+        pm.queue().snapshot().jobs
+    }
+
+    #[test]
+    fn manager_enqueues_steps_as_they_become_ready() {
+        let mut pm = manager_with_config(
+            r#"
+plugins: []
+pipelines:
+  - name: report
+    enabled: true
+    steps:
+      - { name: s, plugin: stats }
+      - { name: e, plugin: export, needs: [s] }
+  - name: broken
+    enabled: true
+    steps:
+      - { name: s, plugin: missing }
+  - name: off
+    steps:
+      - { name: s, plugin: stats }
+"#,
+        );
+        // Pipelines mit unbekannten Plugins werden übersprungen.
+        let names: Vec<&str> = pm
+            .pipelines()
+            .configs()
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(names, ["report", "off"]);
+        assert!(
+            pm.start_pipeline("off", String::new(), JobPriority::Manual)
+                .is_err()
+        );
+        assert!(
+            pm.start_pipeline("nope", String::new(), JobPriority::Manual)
+                .is_err()
+        );
+
+        let run_id = pm
+            .start_pipeline(
+                "report",
+                r#"{"entry_id":7}"#.to_string(),
+                JobPriority::Manual,
+            )
+            .unwrap();
+        let jobs = queued(&pm);
+        assert_eq!(jobs.len(), 1);
+        let first = jobs[0].clone();
+        assert_eq!(first.plugin_name, "stats");
+        assert_eq!(first.data, r#"{"entry_id":7}"#);
+        let step = first.pipeline.clone().expect("job belongs to a pipeline");
+        assert_eq!((step.run_id, step.step.as_str()), (run_id, "s"));
+
+        let first = pm.queue().cancel(first.instance_id).unwrap();
+        pm.pipeline_step_finished(&first, &completed(json!({ "entry_path": "/data/a.mcap" })));
+        let jobs = queued(&pm);
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].plugin_name, "export");
+        assert_eq!(jobs[0].priority, JobPriority::Manual);
+        assert_eq!(jobs[0].data, r#"{"entry_path":"/data/a.mcap"}"#);
+
+        // Abbrechen der Warteschlange beendet den Schritt und damit den Lauf.
+        pm.cancel_queued_instance(jobs[0].instance_id).unwrap();
+        let view = pm.pipeline_run_view(run_id).unwrap();
+        assert_eq!(view.status, PipelineStatus::Failed);
+        assert_eq!(view.steps[1].state, StepState::Cancelled);
+        assert_eq!(pm.pipeline_run_views().len(), 1);
+    }
+
+    #[test]
+    fn invalid_pipeline_config_fails_loading() {
+        let mut pm = PluginManager::new();
+        let path = std::env::temp_dir().join(format!("pipeline_cycle_{}.yaml", std::process::id()));
+        std::fs::write(
+            &path,
+            "plugins: []\npipelines:\n  - name: p\n    steps:\n      \
+             - { name: a, plugin: x, needs: [b] }\n      - { name: b, plugin: x, needs: [a] }\n",
+        )
+        .unwrap();
+        let err = pm
+            .load_config_and_apply(path.to_str().unwrap())
+            .unwrap_err();
+        std::fs::remove_file(&path).ok();
+        assert!(format!("{err:?}").contains("cycle"), "{err:?}");
+    }
+}
@@ -0,0 +1,77 @@
+//! Per-instance plugin logs: sequence numbers, backlog and live subscription (no DB).
+
+#[cfg(test)]
+mod tests {
+    use backend::plugin_manager::logs::{InstanceLogs, LOG_BACKLOG, LOG_CHANNEL_CAPACITY};
+    use tokio::sync::broadcast::error::RecvError;
+
+    #[test]
+    fn lines_are_numbered_per_instance() {
+        let logs = InstanceLogs::new(7);
+        logs.push("INFO", "plugin", "first");
+        logs.push("ERROR", "stderr", "second");
+
+        let subscription = logs.subscribe().unwrap();
+        let lines = subscription.backlog;
+        assert_eq!(lines.len(), 2);
+        assert_eq!((lines[0].instance_id, lines[0].seq), (7, 0));
+        assert_eq!(lines[0].message, "first");
+        assert_eq!((lines[1].seq, lines[1].level.as_str()), (1, "ERROR"));
+        assert_eq!(lines[1].logger, "stderr");
+        assert!(lines[0].at <= lines[1].at);
+    }
+
+    #[test]
+    fn backlog_keeps_latest_lines() {
+        let logs = InstanceLogs::new(1);
+        for i in 0..LOG_BACKLOG + 5 {
+            logs.push("DEBUG", "plugin", &format!("line {i}"));
+        }
+        let backlog = logs.subscribe().unwrap().backlog;
+        assert_eq!(backlog.len(), LOG_BACKLOG);
+        assert_eq!(backlog[0].seq, 5);
+        assert_eq!(backlog.last().unwrap().seq as usize, LOG_BACKLOG + 4);
+    }
+
+    #[tokio::test]
+    async fn subscribers_see_later_lines_until_finish() {
+        let logs = InstanceLogs::new(3);
+        logs.push("INFO", "plugin", "before");
+        let mut subscription = logs.subscribe().unwrap();
+        logs.push("INFO", "plugin", "after");
+        logs.finish();
+        // nach dem Ende wird nichts mehr angenommen
+        logs.push("INFO", "plugin", "too late");
+
+        assert_eq!(subscription.backlog.len(), 1);
+        let line = subscription.rx.recv().await.unwrap();
+        assert_eq!((line.seq, line.message.as_str()), (1, "after"));
+        assert!(matches!(
+            subscription.rx.recv().await,
+            Err(RecvError::Closed)
+        ));
+        assert!(logs.subscribe().is_none());
+    }
+
+    #[tokio::test]
+    async fn persistence_receives_every_line() {
+        let logs = InstanceLogs::new(5);
+        logs.push("INFO", "plugin", "early");
+        let mut persisted = logs.persist().unwrap();
+        // weit mehr als ein Live-Abonnent puffern würde
+        for i in 0..LOG_CHANNEL_CAPACITY * 3 {
+            logs.push("DEBUG", "plugin", &format!("line {i}"));
+        }
+        logs.finish();
+
+        let mut seqs = Vec::new();
+        while let Some(line) = persisted.recv().await {
+            seqs.push(line.seq);
+        }
+        assert_eq!(
+            seqs,
+            (0..=(LOG_CHANNEL_CAPACITY * 3) as i32).collect::<Vec<_>>()
+        );
+        assert!(logs.persist().is_none());
+    }
+}
@@ -0,0 +1,83 @@
+//! Plugin run history: payload parsing, entry path, trigger and first outcome (pure functions, no DB).
+
+#[cfg(test)]
+mod tests {
+    use backend::plugin_manager::manager::InstanceState;
+    use backend::plugin_manager::runs::{
+        RunOutcome, entry_path, parse_payload, record_outcome, run_trigger, state_name,
+    };
+    use serde_json::json;
+    use tokio::sync::watch;
+
+    #[test]
+    fn payload_is_json_or_string() {
+        assert_eq!(parse_payload(""), serde_json::Value::Null);
+        assert_eq!(
+            parse_payload(r#"{"entry_path": "/data/a.mcap"}"#),
+            json!({ "entry_path": "/data/a.mcap" })
+        );
+        assert_eq!(parse_payload("not json"), json!("not json"));
+    }
+
+    #[test]
+    fn entry_path_from_manual_metadata_and_file_events() {
+        assert_eq!(
+            entry_path(&json!({ "entry_path": "/data/a.mcap" })).as_deref(),
+            Some("/data/a.mcap")
+        );
+        assert_eq!(
+            entry_path(&json!({ "metadata": {}, "mcap_path": "/data/b.mcap" })).as_deref(),
+            Some("/data/b.mcap")
+        );
+        assert_eq!(
+            entry_path(&json!({ "event": "created", "path": "/data/c.mcap" })).as_deref(),
+            Some("/data/c.mcap")
+        );
+        assert_eq!(
+            entry_path(&json!({ "event": "manual", "entry_id": 4, "path": "/data/d.mcap" }))
+                .as_deref(),
+            Some("/data/d.mcap")
+        );
+        // geplante Läufe beziehen sich auf das ganze Datenverzeichnis
+        assert_eq!(
+            entry_path(&json!({ "event": "schedule", "path": "/data" })),
+            None
+        );
+        assert_eq!(entry_path(&serde_json::Value::Null), None);
+    }
+
+    #[test]
+    fn trigger_from_event_or_plugin() {
+        let trigger = |payload| run_trigger(&payload, "on_entry_update");
+        assert_eq!(trigger(json!({ "event": "created" })), "on_entry_create");
+        assert_eq!(trigger(json!({ "event": "deleted" })), "on_entry_delete");
+        assert_eq!(trigger(json!({ "event": "schedule" })), "on_schedule");
+        assert_eq!(trigger(json!({ "event": "manual" })), "manual");
+        assert_eq!(
+            trigger(json!({ "mcap_path": "/data/a.mcap" })),
+            "on_entry_update"
+        );
+        assert_eq!(trigger(json!({ "entry_path": "/data/a.mcap" })), "manual");
+        assert_eq!(trigger(serde_json::Value::Null), "manual");
+    }
+
+    #[test]
+    fn state_names_match_api() {
+        assert_eq!(state_name(InstanceState::Completed), "Completed");
+        assert_eq!(state_name(InstanceState::Unresponsive), "Unresponsive");
+    }
+
+    #[test]
+    fn first_outcome_wins() {
+        let (tx, rx) = watch::channel(None);
+        record_outcome(&tx, RunOutcome::new(InstanceState::Stopped));
+        record_outcome(
+            &tx,
+            RunOutcome::failed(InstanceState::Failed, "exit 1".to_string()),
+        );
+        assert_eq!(
+            rx.borrow().clone(),
+            Some(RunOutcome::new(InstanceState::Stopped))
+        );
+    }
+}
@@ -0,0 +1,142 @@
+//! Plugin job queue: priorities, worker and per-plugin limits, cancelling (pure, no DB).
+
+#[cfg(test)]
+mod tests {
+    use backend::plugin_manager::queue::{JobPriority, JobQueue, QueuedJob};
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    fn job(instance_id: u64, plugin_name: &str, priority: JobPriority) -> QueuedJob {
+        QueuedJob::new(
+            instance_id,
+            0,
+            plugin_name.to_string(),
+            PathBuf::from(format!("/plugins/{plugin_name}.py")),
+            String::new(),
+            priority,
+        )
+    }
+
+    fn ids(queue: &JobQueue) -> Vec<u64> {
+        queue
+            .snapshot()
+            .jobs
+            .iter()
+            .map(|job| job.instance_id)
+            .collect()
+    }
+
+    #[test]
+    fn manual_jobs_overtake_triggered_ones() {
+        let queue = JobQueue::new(4);
+        queue.push(job(1, "a", JobPriority::Triggered));
+        queue.push(job(2, "a", JobPriority::Manual));
+        queue.push(job(3, "a", JobPriority::Triggered));
+        queue.push(job(4, "a", JobPriority::Manual));
+        assert_eq!(ids(&queue), [2, 4, 1, 3]);
+        assert_eq!(queue.next_ready().unwrap().instance_id, 2);
+    }
+
+    #[test]
+    fn worker_limit_holds_jobs_back_until_release() {
+        let queue = JobQueue::new(2);
+        for id in 1..=3 {
+            queue.push(job(id, "a", JobPriority::Triggered));
+        }
+        assert_eq!(queue.next_ready().unwrap().instance_id, 1);
+        assert_eq!(queue.next_ready().unwrap().instance_id, 2);
+        assert!(queue.next_ready().is_none());
+        assert_eq!(queue.snapshot().active, 2);
+
+        queue.release("a");
+        assert_eq!(queue.next_ready().unwrap().instance_id, 3);
+        assert!(queue.next_ready().is_none());
+    }
+
+    #[test]
+    fn busy_plugin_does_not_block_others() {
+        let queue = JobQueue::new(4);
+        queue.configure(4, HashMap::from([("compress".to_string(), 1)]));
+        queue.push(job(1, "compress", JobPriority::Manual));
+        queue.push(job(2, "compress", JobPriority::Manual));
+        queue.push(job(3, "export", JobPriority::Triggered));
+
+        assert_eq!(queue.next_ready().unwrap().instance_id, 1);
+        assert_eq!(queue.next_ready().unwrap().instance_id, 3);
+        assert!(queue.next_ready().is_none());
+        assert_eq!(ids(&queue), [2]);
+
+        let snapshot = queue.snapshot();
+        assert_eq!(snapshot.running.get("compress"), Some(&1));
+        assert_eq!(snapshot.running.get("export"), Some(&1));
+        assert_eq!(snapshot.limits.get("compress"), Some(&1));
+
+        queue.release("compress");
+        assert_eq!(queue.next_ready().unwrap().instance_id, 2);
+    }
+
+    #[test]
+    fn cancel_and_drain_remove_waiting_jobs() {
+        let queue = JobQueue::new(1);
+        for id in 1..=3 {
+            queue.push(job(id, "a", JobPriority::Triggered));
+        }
+        assert!(queue.contains(2));
+        assert_eq!(queue.cancel(2).unwrap().instance_id, 2);
+        assert!(!queue.contains(2));
+        assert!(queue.cancel(2).is_none());
+
+        let drained: Vec<u64> = queue.drain().iter().map(|j| j.instance_id).collect();
+        assert_eq!(drained, [1, 3]);
+        assert!(queue.next_ready().is_none());
+    }
+
+    #[test]
+    fn retries_wait_until_due_without_blocking_others() {
+        let queue = JobQueue::new(4);
+        let failed = job(1, "a", JobPriority::Manual);
+        let retry = failed.next_attempt(2, Duration::from_secs(3600));
+        assert_eq!(retry.attempt, 2);
+        assert_eq!(retry.retry_of, Some(1));
+        assert_eq!(retry.data, failed.data);
+        assert_eq!(retry.priority, JobPriority::Manual);
+        // weitere Versuche verweisen weiter auf den ersten
+        assert_eq!(retry.next_attempt(3, Duration::ZERO).retry_of, Some(1));
+
+        let due_at = retry.not_before.expect("retry has not_before");
+        queue.push(retry);
+        queue.push(job(3, "a", JobPriority::Triggered));
+        assert_eq!(queue.next_ready().unwrap().instance_id, 3);
+        assert!(queue.next_ready().is_none());
+        assert_eq!(queue.next_due(), Some(due_at));
+
+        queue.push(failed.next_attempt(4, Duration::ZERO));
+        assert_eq!(queue.next_ready().unwrap().instance_id, 4);
+        assert_eq!(ids(&queue), [2]);
+    }
+
+    #[test]
+    fn snapshot_serializes_without_internal_fields() {
+        let queue = JobQueue::new(2);
+        queue.push(QueuedJob::new(
+            7,
+            3,
+            "compress".to_string(),
+            PathBuf::from("/plugins/compress.py"),
+            r#"{"entry_path": "/data/a.mcap"}"#.to_string(),
+            JobPriority::Manual,
+        ));
+        let value = serde_json::to_value(queue.snapshot()).unwrap();
+        assert_eq!(value["max_workers"], 2);
+        assert_eq!(value["active"], 0);
+        let job = &value["jobs"][0];
+        assert_eq!(job["instance_id"], 7);
+        assert_eq!(job["priority"], "manual");
+        assert_eq!(job["entry_path"], "/data/a.mcap");
+        assert_eq!(job["attempt"], 1);
+        assert!(job["not_before"].is_null());
+        assert!(job.get("data").is_none());
+        assert!(job.get("plugin_index").is_none());
+    }
+}
@@ -0,0 +1,134 @@
+//! Plugin retry policies: declaration checks, backoff and retryable failure classes (pure, no DB).
+
+#[cfg(test)]
+mod tests {
+    use backend::plugin_manager::manager::InstanceState;
+    use backend::plugin_manager::retry::{
+        CRASH_CLASS, RetryPolicy, TIMEOUT_CLASS, failure_classes,
+    };
+    use backend::plugin_manager::runs::RunOutcome;
+    use serde_json::{Value, json};
+    use std::time::Duration;
+
+    fn policy(raw: Value) -> RetryPolicy {
+        RetryPolicy::from_declaration(raw).expect("valid policy")
+    }
+
+    fn exception(classes: &[&str]) -> RunOutcome {
+        RunOutcome {
+            error_classes: classes.iter().map(|c| c.to_string()).collect(),
+            ..RunOutcome::failed(InstanceState::Failed, "boom".to_string())
+        }
+    }
+
+    #[test]
+    fn defaults_are_filled_in() {
+        let policy = policy(json!({ "max_attempts": 3 }));
+        assert_eq!(policy.backoff_seconds, 10.0);
+        assert_eq!(policy.backoff_factor, 2.0);
+        assert_eq!(policy.max_backoff_seconds, 3600.0);
+        assert_eq!(policy.retry_on, None);
+    }
+
+    #[test]
+    fn backoff_grows_and_is_capped() {
+        let policy = policy(json!({
+            "max_attempts": 10,
+            "backoff_seconds": 5,
+            "backoff_factor": 3,
+            "max_backoff_seconds": 60,
+        }));
+        assert_eq!(policy.delay(1), Duration::from_secs(5));
+        assert_eq!(policy.delay(2), Duration::from_secs(15));
+        assert_eq!(policy.delay(3), Duration::from_secs(45));
+        assert_eq!(policy.delay(4), Duration::from_secs(60));
+        assert_eq!(policy.delay(u32::MAX), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn attempts_are_limited() {
+        let policy = policy(json!({ "max_attempts": 2, "backoff_seconds": 1 }));
+        let outcome = exception(&["ValueError", "Exception", "BaseException"]);
+        assert_eq!(
+            policy.retry_delay(1, &outcome),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(policy.retry_delay(2, &outcome), None);
+    }
+
+    #[test]
+    fn only_failed_runs_are_retried() {
+        let policy = policy(json!({ "max_attempts": 3 }));
+        for state in [
+            InstanceState::Completed,
+            InstanceState::Stopped,
+            InstanceState::Unresponsive,
+        ] {
+            let outcome = RunOutcome::failed(state, "x".to_string());
+            assert_eq!(policy.retry_delay(1, &outcome), None, "{state:?}");
+        }
+    }
+
+    #[test]
+    fn retry_on_matches_base_classes_and_crashes() {
+        let policy = policy(json!({ "max_attempts": 3, "retry_on": ["crash", "OSError"] }));
+        let reset = exception(&[
+            "ConnectionResetError",
+            "ConnectionError",
+            "OSError",
+            "Exception",
+            "BaseException",
+        ]);
+        assert!(policy.retry_delay(1, &reset).is_some());
+        assert!(
+            policy
+                .retry_delay(1, &exception(&["KeyError", "LookupError", "Exception"]))
+                .is_none()
+        );
+
+        let crash = RunOutcome::failed(
+            InstanceState::Failed,
+            "runner ended without final state".to_string(),
+        );
+        assert_eq!(failure_classes(&crash), [CRASH_CLASS]);
+        assert!(policy.retry_delay(1, &crash).is_some());
+    }
+
+    #[test]
+    fn timeouts_are_retried_only_when_listed() {
+        let timed_out = RunOutcome::failed(
+            InstanceState::TimedOut,
+            "run exceeded PLUGIN_TIMEOUT of 60s".to_string(),
+        );
+        assert!(
+            policy(json!({ "max_attempts": 3 }))
+                .retry_delay(1, &timed_out)
+                .is_none()
+        );
+        assert!(
+            policy(json!({ "max_attempts": 3, "retry_on": ["crash"] }))
+                .retry_delay(1, &timed_out)
+                .is_none()
+        );
+        let listed = policy(json!({ "max_attempts": 2, "retry_on": [TIMEOUT_CLASS] }));
+        assert!(listed.retry_delay(1, &timed_out).is_some());
+        assert!(listed.retry_delay(2, &timed_out).is_none());
+    }
+
+    #[test]
+    fn invalid_declarations_are_rejected() {
+        let declare = |raw: Value| RetryPolicy::from_declaration(raw);
+        assert!(declare(json!({})).is_err());
+        assert!(declare(json!({ "max_attempts": 0 })).is_err());
+        assert!(declare(json!({ "max_attempts": 11 })).is_err());
+        assert!(declare(json!({ "max_attempts": 2, "backoff_seconds": -1 })).is_err());
+        assert!(declare(json!({ "max_attempts": 2, "backoff_factor": 0.5 })).is_err());
+        assert!(
+            declare(json!({ "max_attempts": 2, "backoff_seconds": 20, "max_backoff_seconds": 10 }))
+                .is_err()
+        );
+        assert!(declare(json!({ "max_attempts": 2, "retry_on": [""] })).is_err());
+        assert!(declare(json!({ "max_attempts": 2, "jitter": true })).is_err());
+        assert!(declare(json!([3])).is_err());
+    }
+}
@@ -0,0 +1,207 @@
+//! Plugin sandbox: limits from plugins.yaml, rlimits, cgroup values and scratch dirs (pure, no DB).
+
+#[cfg(test)]
+mod tests {
+    use backend::plugin_manager::sandbox::{
+        Isolation, NetworkMode, Resource, Rlimit, SandboxLimits, SandboxPolicy, SandboxSettings,
+    };
+    use std::path::PathBuf;
+
+    const MIB: u64 = 1024 * 1024;
+
+    fn settings(yaml: &str) -> SandboxSettings {
+        serde_yaml::from_str(yaml).expect("valid sandbox settings")
+    }
+
+    fn policy(limits: SandboxLimits, write_access: bool) -> SandboxPolicy {
+        SandboxPolicy::new(&SandboxSettings::default(), limits, write_access)
+    }
+
+    #[test]
+    fn plugin_limits_fall_back_to_defaults() {
+        let settings = settings(
+            "
+            scratch_dir: /srv/scratch
+            defaults:
+              memory_mb: 2048
+              max_open_files: 256
+            ",
+        );
+        assert_eq!(settings.scratch_dir(), PathBuf::from("/srv/scratch"));
+        assert_eq!(settings.cgroup_root, None);
+
+        let own: SandboxLimits = serde_yaml::from_str("{memory_mb: 512, network: deny}").unwrap();
+        let merged = own.with_defaults(&settings.defaults);
+        assert_eq!(merged.memory_mb, Some(512));
+        assert_eq!(merged.max_open_files, Some(256));
+        assert_eq!(merged.cpu_seconds, None);
+        assert_eq!(merged.network(), NetworkMode::Deny);
+        assert_eq!(SandboxLimits::default().network(), NetworkMode::Allow);
+    }
+
+    #[test]
+    fn invalid_limits_are_rejected() {
+        assert!(serde_yaml::from_str::<SandboxLimits>("{memory: 1}").is_err());
+        assert!(serde_yaml::from_str::<SandboxLimits>("{network: offline}").is_err());
+
+        let check = |yaml: &str| {
+            serde_yaml::from_str::<SandboxLimits>(yaml)
+                .unwrap()
+                .validate()
+        };
+        assert!(check("{memory_mb: 0}").is_err());
+        assert!(check("{cpu_seconds: 0}").is_err());
+        assert!(check("{max_file_mb: 0}").is_err());
+        assert!(check("{max_open_files: 3}").is_err());
+        assert!(check("{cpus: 0}").is_err());
+        assert!(check("{cpus: -1.5}").is_err());
+        assert!(check("{memory_mb: 1, cpus: 0.5, max_open_files: 64}").is_ok());
+    }
+
+    #[test]
+    fn memory_uses_rlimit_only_without_cgroup() {
+        let limited = policy(
+            SandboxLimits {
+                memory_mb: Some(1024),
+                cpu_seconds: Some(60),
+                max_open_files: Some(128),
+                max_file_mb: Some(10),
+                ..SandboxLimits::default()
+            },
+            false,
+        );
+        let without_cgroup = limited.rlimits(false);
+        assert_eq!(
+            without_cgroup,
+            [
+                Rlimit {
+                    resource: Resource::AddressSpace,
+                    soft: 1024 * MIB,
+                    hard: 1024 * MIB,
+                },
+                Rlimit {
+                    resource: Resource::CpuTime,
+                    soft: 60,
+                    hard: 65,
+                },
+                Rlimit {
+                    resource: Resource::OpenFiles,
+                    soft: 128,
+                    hard: 128,
+                },
+                Rlimit {
+                    resource: Resource::FileSize,
+                    soft: 10 * MIB,
+                    hard: 10 * MIB,
+                },
+            ]
+        );
+        let with_cgroup = limited.rlimits(true);
+        assert_eq!(with_cgroup.len(), 3);
+        assert!(
+            with_cgroup
+                .iter()
+                .all(|r| r.resource != Resource::AddressSpace)
+        );
+        assert!(
+            policy(SandboxLimits::default(), false)
+                .rlimits(false)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn cgroup_files_hold_memory_and_cpu_share() {
+        let policy = policy(
+            SandboxLimits {
+                memory_mb: Some(256),
+                cpus: Some(1.5),
+                ..SandboxLimits::default()
+            },
+            false,
+        );
+        assert_eq!(
+            policy.cgroup_files(),
+            [
+                ("memory.max", (256 * MIB).to_string()),
+                ("cpu.max", "150000 100000".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn data_is_read_only_without_write_access() {
+        let deny = SandboxLimits {
+            network: Some(NetworkMode::Deny),
+            ..SandboxLimits::default()
+        };
+        assert_eq!(
+            policy(deny, false).isolation(),
+            Isolation {
+                private_network: true,
+                read_only_data: true,
+            }
+        );
+        assert_eq!(
+            policy(SandboxLimits::default(), true).isolation(),
+            Isolation::default()
+        );
+    }
+
+    /// Braucht `CAP_SYS_ADMIN` wie der Backend-Prozess selbst.
+    #[tokio::test]
+    async fn denied_network_gets_own_namespace() {
+        let root = std::env::temp_dir().join(format!("sandbox-netns-{}", std::process::id()));
+        let settings = SandboxSettings {
+            scratch_dir: Some(root.clone()),
+            ..SandboxSettings::default()
+        };
+        let deny = SandboxLimits {
+            network: Some(NetworkMode::Deny),
+            ..SandboxLimits::default()
+        };
+        let prepared = SandboxPolicy::new(&settings, deny, true)
+            .prepare(7)
+            .expect("prepare sandbox");
+        assert!(prepared.private_network());
+        assert_eq!(prepared.read_only_data(), None);
+
+        let mut command = tokio::process::Command::new("readlink");
+        command.arg("/proc/self/ns/net");
+        prepared.apply(&mut command);
+        let output = command.output().await.expect("run in sandbox");
+        prepared.cleanup();
+        let _ = std::fs::remove_dir(&root);
+
+        assert!(output.status.success(), "{output:?}");
+        let own = std::fs::read_link("/proc/self/ns/net").unwrap();
+        assert_ne!(
+            String::from_utf8_lossy(&output.stdout).trim(),
+            own.to_string_lossy()
+        );
+    }
+
+    #[test]
+    fn scratch_dir_is_created_and_removed() {
+        let root = std::env::temp_dir().join(format!("sandbox-test-{}", std::process::id()));
+        let settings = SandboxSettings {
+            scratch_dir: Some(root.clone()),
+            ..SandboxSettings::default()
+        };
+        let policy = SandboxPolicy::new(&settings, SandboxLimits::default(), false);
+
+        let prepared = policy.prepare(42).expect("prepare sandbox");
+        let workdir = prepared.workdir().to_path_buf();
+        assert_eq!(workdir, root.join("instance-42"));
+        std::fs::write(workdir.join("left-over.tmp"), b"x").unwrap();
+
+        // Reste eines früheren Laufs mit derselben ID werden verworfen.
+        let again = policy.prepare(42).expect("prepare sandbox again");
+        assert!(!workdir.join("left-over.tmp").exists());
+
+        again.cleanup();
+        prepared.cleanup();
+        assert!(!workdir.exists());
+        let _ = std::fs::remove_dir(&root);
+    }
+}
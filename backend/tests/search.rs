@@ -27,6 +27,9 @@ async fn test_search() {
         name: "Test Entry".to_string(),
         created_at: now,
         updated_at: now,
+        version: 1,
+        mcap_start_ns: None,
+        mcap_end_ns: None,
         path: SEARCH_TEST_ENTRY_PATH.to_string(),
         size: 123,
         status: "Complete".to_string(),
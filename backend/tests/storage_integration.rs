@@ -5,10 +5,22 @@ mod common;
 
 use std::env;
 
+use backend::error::StorageError;
+use backend::events::CatalogEvent;
+use backend::plugin_manager::manager::{InstanceState, PluginManager};
+use backend::plugin_manager::runs::RunOutcome;
+use backend::plugin_manager::sandbox::SandboxSettings;
 use backend::routes::database::MetadataWeb;
 use backend::schema;
-use backend::storage::models::{Entry, Sensor, Sequence, Topic};
-use backend::storage::storage_manager::StorageManager;
+use backend::storage::artifacts::{ArtifactProvenance, PluginArtifact};
+use backend::storage::audit::AuditFilter;
+use backend::storage::auth::{Role, Scope};
+use backend::storage::metadata_import::{self, ImportRowStatus};
+use backend::storage::models::{
+    BulkEntryStatus, Entry, EntryMetadataPatch, NewAuditRecord, NewPluginRun, PluginLogLine,
+    Sensor, Sequence, Topic,
+};
+use backend::storage::storage_manager::{BulkEntryChange, EntryFilter, StorageManager};
 use chrono::{SubsecRound, Utc};
 use diesel::prelude::*;
 
@@ -30,6 +42,9 @@ fn minimal_entry(id: i64, name: &str, path: &str) -> Entry {
         name: name.to_string(),
         created_at: now,
         updated_at: now,
+        version: 1,
+        mcap_start_ns: None,
+        mcap_end_ns: None,
         path: path.to_string(),
         size: 0,
         status: "Complete".to_string(),
@@ -230,6 +245,7 @@ async fn test_sequences_add_get_remove() {
         created_at: now,
         updated_at: now,
         tags: vec!["seq_tag".to_string()],
+        version: 1,
     };
 
     let seq_id = storage
@@ -245,13 +261,170 @@ async fn test_sequences_add_get_remove() {
     assert_eq!(s.start_timestamp, 1000);
 
     storage
-        .remove_sequence(entry_id, seq_id, txid)
+        .remove_sequence(entry_id, seq_id, None, txid)
         .await
         .unwrap();
     let sequences_after = storage.get_sequences(entry_id, TXID).await.unwrap();
     assert!(sequences_after.is_empty());
 }
 
+#[tokio::test]
+async fn test_sequence_split_merge_and_bounds() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let db_url = env::var("DATABASE_URL").unwrap();
+    let storage = StorageManager::new(&db_url).unwrap();
+
+    let mut entry = minimal_entry(
+        INTEGRATION_ENTRY_ID_BASE + 25,
+        "TimelineEntry",
+        "/test/integration/entry_timeline",
+    );
+    entry.mcap_start_ns = Some(1_000);
+    entry.mcap_end_ns = Some(5_000);
+    let inserted = insert_entry(&storage, entry).await;
+    let entry_id = inserted.id;
+    let txid = storage.start_transaction();
+
+    let now = Utc::now().trunc_subsecs(3);
+    let seq = Sequence {
+        name: "drive".to_string(),
+        id: 0,
+        entry_id,
+        description: "drive".to_string(),
+        start_timestamp: 1_000,
+        end_timestamp: 4_000,
+        created_at: now,
+        updated_at: now,
+        tags: vec!["a".to_string()],
+        version: 1,
+    };
+
+    // außerhalb der Aufnahme -> abgelehnt
+    let mut outside = seq.clone();
+    outside.end_timestamp = 6_000;
+    let err = storage
+        .add_sequence(entry_id, outside, txid)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StorageError::ValidationError(_)));
+
+    let seq_id = storage
+        .add_sequence(entry_id, seq.clone(), txid)
+        .await
+        .unwrap();
+
+    let (first, second) = storage
+        .split_sequence(entry_id, seq_id, 2_500, None, txid)
+        .await
+        .unwrap();
+    assert_eq!((first.start_timestamp, first.end_timestamp), (1_000, 2_500));
+    assert_eq!(
+        (second.start_timestamp, second.end_timestamp),
+        (2_500, 4_000)
+    );
+    assert_eq!(second.tags, vec!["a".to_string()]);
+
+    let overlapping = storage
+        .get_overlapping_sequences(entry_id, 2_000, 2_600, TXID)
+        .await
+        .unwrap();
+    assert_eq!(overlapping.len(), 2);
+
+    let timeline = storage.get_timeline(entry_id, TXID).await.unwrap();
+    assert_eq!(timeline.segments.len(), 3, "two halves plus trailing gap");
+
+    let merged = storage
+        .merge_sequences(entry_id, vec![second.id, first.id], None, txid)
+        .await
+        .unwrap();
+    assert_eq!(merged.id, first.id);
+    assert_eq!(
+        (merged.start_timestamp, merged.end_timestamp),
+        (1_000, 4_000)
+    );
+    let sequences = storage.get_sequences(entry_id, TXID).await.unwrap();
+    assert_eq!(sequences.len(), 1);
+}
+
+#[tokio::test]
+async fn test_search_sequences_across_entries() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let db_url = env::var("DATABASE_URL").unwrap();
+    let storage = StorageManager::new(&db_url).unwrap();
+    let txid = storage.start_transaction();
+    let now = Utc::now().trunc_subsecs(3);
+
+    // eindeutiger Tag, damit Sequenzen anderer Tests nicht mitgezählt werden
+    let tag = "cut_in_search_it".to_string();
+    let mut entry_ids = Vec::new();
+    for (i, secs) in [3_i64, 7, 12].iter().enumerate() {
+        let entry = minimal_entry(
+            INTEGRATION_ENTRY_ID_BASE + 26 + i as i64,
+            &format!("SearchSeqEntry{i}"),
+            &format!("/test/integration/search_sequences_{i}"),
+        );
+        let entry_id = insert_entry(&storage, entry).await.id;
+        entry_ids.push(entry_id);
+        let seq = Sequence {
+            name: format!("overtake {i}"),
+            id: 0,
+            entry_id,
+            description: "highway".to_string(),
+            start_timestamp: 0,
+            end_timestamp: secs * 1_000_000_000,
+            created_at: now,
+            updated_at: now,
+            tags: vec![tag.clone()],
+            version: 1,
+        };
+        storage.add_sequence(entry_id, seq, txid).await.unwrap();
+    }
+
+    let (hits, num_pages) = storage
+        .search_sequences(
+            None,
+            Some(tag.clone()),
+            Some(5.0),
+            Some("Duration".to_string()),
+            Some(false),
+            None,
+            None,
+            TXID,
+        )
+        .await
+        .unwrap();
+    assert_eq!(num_pages, 1);
+    assert_eq!(hits.len(), 2);
+    assert_eq!(hits[0].entry.id, entry_ids[2], "longest sequence first");
+    assert_eq!(hits[0].entry.name, "SearchSeqEntry2");
+    assert_eq!(hits[1].entry.id, entry_ids[1]);
+
+    // Suchwörter durchsuchen auch den Eintragsnamen; Paging in SQL
+    let (page, num_pages) = storage
+        .search_sequences(
+            Some("searchseqentry highway".to_string()),
+            Some(tag),
+            None,
+            None,
+            None,
+            Some(1),
+            Some(2),
+            TXID,
+        )
+        .await
+        .unwrap();
+    assert_eq!(num_pages, 2);
+    assert_eq!(page.len(), 1);
+}
+
 #[tokio::test]
 async fn test_tags_add_remove() {
     if skip_if_no_db() {
@@ -273,7 +446,7 @@ async fn test_tags_add_remove() {
     let txid = storage.start_transaction();
 
     storage
-        .add_tag(entry_id, "new_tag".to_string(), txid)
+        .add_tag(entry_id, "new_tag".to_string(), None, txid)
         .await
         .unwrap();
 
@@ -282,7 +455,7 @@ async fn test_tags_add_remove() {
     assert!(updated.tags.contains(&"new_tag".to_string()));
 
     storage
-        .remove_tag(entry_id, "new_tag".to_string(), txid)
+        .remove_tag(entry_id, "new_tag".to_string(), None, txid)
         .await
         .unwrap();
 
@@ -445,6 +618,7 @@ async fn test_sensors_add_get_all_update_remove() {
         sensor_type: Some("lidar".to_string()),
         ros_topics: vec!["/lidar".to_string()],
         custom_parameters: None,
+        version: 1,
     };
 
     let sensor_id = storage.add_sensor(sensor.clone(), txid).await.unwrap();
@@ -470,10 +644,11 @@ async fn test_sensors_add_get_all_update_remove() {
         sensor_type: Some("camera".to_string()),
         ros_topics: vec!["/camera".to_string()],
         custom_parameters: None,
+        version: 1,
     };
 
     storage
-        .update_sensor(updated_sensor.clone(), txid)
+        .update_sensor(updated_sensor.clone(), None, txid)
         .await
         .unwrap();
 
@@ -482,7 +657,7 @@ async fn test_sensors_add_get_all_update_remove() {
     assert_eq!(s2.sensor_name, "Camera");
     assert_eq!(s2.manufacturer.as_deref(), Some("OtherCorp"));
 
-    storage.remove_sensor(sensor_id, txid).await.unwrap();
+    storage.remove_sensor(sensor_id, None, txid).await.unwrap();
     let sensors_final = storage.get_sensors(entry_id, TXID).await.unwrap();
     assert!(sensors_final.is_empty());
 }
@@ -660,7 +835,7 @@ async fn test_update_entry_metadata_fields() {
     };
 
     storage
-        .update_entry(entry_id, md.clone(), TXID)
+        .update_entry(entry_id, md.clone(), None, TXID)
         .await
         .unwrap();
 
@@ -673,7 +848,7 @@ async fn test_update_entry_metadata_fields() {
 }
 
 #[tokio::test]
-async fn test_start_and_commit_transaction() {
+async fn test_update_entry_rejects_stale_version() {
     if skip_if_no_db() {
         return;
     }
@@ -682,8 +857,1827 @@ async fn test_start_and_commit_transaction() {
     let db_url = env::var("DATABASE_URL").unwrap();
     let storage = StorageManager::new(&db_url).unwrap();
 
-    let txid = storage.start_transaction();
-    // commit_transaction currently only affects in-memory active_transactions,
-    // but this test ensures it at least succeeds for a valid txid.
-    storage.commit_transaction(txid).await.unwrap();
+    let entry = minimal_entry(
+        INTEGRATION_ENTRY_ID_BASE + 120,
+        "VersionedEntry",
+        "/test/integration/versioned_entry",
+    );
+    let inserted = insert_entry(&storage, entry).await;
+    let entry_id = inserted.id;
+    let v1 = inserted.version;
+
+    let mut md = MetadataWeb {
+        time_machine: None,
+        platform_name: None,
+        platform_image_link: None,
+        scenario_name: None,
+        scenario_creation_time: None,
+        scenario_description: None,
+        sequence_duration: None,
+        sequence_distance: None,
+        sequence_lat_starting_point_deg: None,
+        sequence_lon_starting_point_deg: None,
+        weather_cloudiness: None,
+        weather_precipitation: None,
+        weather_precipitation_deposits: None,
+        weather_wind_intensity: None,
+        weather_road_humidity: None,
+        weather_fog: None,
+        weather_snow: None,
+        topics: None,
+    };
+
+    md.platform_name = Some("first".to_string());
+    storage
+        .update_entry(entry_id, md.clone(), Some(v1), TXID)
+        .await
+        .unwrap();
+    let after_first = storage.get_entry(entry_id, TXID).await.unwrap().unwrap();
+    assert!(after_first.version > v1, "version must be bumped on update");
+
+    // zweiter Schreiber mit veralteter Version wird abgewiesen
+    md.platform_name = Some("second".to_string());
+    let err = storage
+        .update_entry(entry_id, md.clone(), Some(v1), TXID)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StorageError::PreconditionFailed(_)));
+
+    let unchanged = storage.get_entry(entry_id, TXID).await.unwrap().unwrap();
+    assert_eq!(unchanged.platform_name.as_deref(), Some("first"));
+
+    let err = storage
+        .add_tag(entry_id, "stale".to_string(), Some(v1), TXID)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StorageError::PreconditionFailed(_)));
+}
+
+#[tokio::test]
+async fn test_sensor_catalog_dedup_and_merge() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let db_url = env::var("DATABASE_URL").unwrap();
+    let storage = StorageManager::new(&db_url).unwrap();
+
+    let lidar = |entry_id: i64, manufacturer: &str, params: serde_json::Value| Sensor {
+        id: 0,
+        entry_id,
+        sensor_name: "roof_lidar".to_string(),
+        manufacturer: Some(manufacturer.to_string()),
+        sensor_type: Some("LiDAR".to_string()),
+        ros_topics: vec!["/lidar/points".to_string()],
+        custom_parameters: Some(params),
+        version: 1,
+    };
+
+    let mut entry_ids = Vec::new();
+    for i in 0..3 {
+        let entry = minimal_entry(
+            INTEGRATION_ENTRY_ID_BASE + 130 + i,
+            &format!("CatalogEntry{i}"),
+            &format!("/test/integration/sensor_catalog_{i}"),
+        );
+        entry_ids.push(insert_entry(&storage, entry).await.id);
+    }
+
+    // gleiche Seriennummer, unterschiedliche Schreibweise -> ein Katalogsensor
+    storage
+        .add_sensor(
+            lidar(
+                entry_ids[0],
+                "Velodyne Inc.",
+                serde_json::json!({"serial_number": "VLP-4711-IT"}),
+            ),
+            TXID,
+        )
+        .await
+        .unwrap();
+    storage
+        .add_sensor(
+            lidar(
+                entry_ids[1],
+                "velodyne inc",
+                serde_json::json!({"serial_number": "vlp4711it"}),
+            ),
+            TXID,
+        )
+        .await
+        .unwrap();
+    // ohne Seriennummer nur über das Modell identifizierbar -> eigener Katalogsensor
+    let third = storage
+        .add_sensor(
+            lidar(
+                entry_ids[2],
+                "Velodyne",
+                serde_json::json!({"model": "VLP-16 IT"}),
+            ),
+            TXID,
+        )
+        .await
+        .unwrap();
+
+    let catalog = storage
+        .get_sensor_catalog(Some("VLP-4711-IT".to_string()), TXID)
+        .await
+        .unwrap();
+    assert_eq!(catalog.len(), 1);
+    assert_eq!(catalog[0].entry_count, 2);
+    let target_id = catalog[0].sensor.id;
+
+    let usages = storage
+        .get_catalog_sensor_entries(target_id, TXID)
+        .await
+        .unwrap();
+    let used_in: Vec<i64> = usages.iter().map(|u| u.entry.id).collect();
+    assert_eq!(used_in, entry_ids[..2].to_vec());
+
+    let assignments = storage
+        .get_entry_sensor_assignments(entry_ids[2], TXID)
+        .await
+        .unwrap();
+    assert_eq!(assignments.len(), 1);
+    let duplicate_id = assignments[0].sensor.id;
+    assert_ne!(duplicate_id, target_id);
+
+    let merged = storage
+        .merge_catalog_sensors(target_id, vec![duplicate_id], TXID)
+        .await
+        .unwrap();
+    assert_eq!(merged.serial_number.as_deref(), Some("VLP-4711-IT"));
+    assert_eq!(
+        merged.model.as_deref(),
+        Some("VLP-16 IT"),
+        "missing fields are filled from sources"
+    );
+    let err = storage
+        .get_catalog_sensor_entries(duplicate_id, TXID)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StorageError::NotFound(_)));
+
+    // erneutes Schreiben des Sensors (z.B. durch einen Scan) bleibt beim Ziel
+    let mut sensor = storage.get_sensors(entry_ids[2], TXID).await.unwrap()[&third].clone();
+    sensor.ros_topics.push("/lidar/packets".to_string());
+    storage.update_sensor(sensor, None, TXID).await.unwrap();
+    let usages = storage
+        .get_catalog_sensor_entries(target_id, TXID)
+        .await
+        .unwrap();
+    assert_eq!(usages.len(), 3);
+    assert_eq!(usages[2].assignment.ros_topics.len(), 2);
+}
+
+#[tokio::test]
+async fn test_consistency_check_and_filter() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let db_url = env::var("DATABASE_URL").unwrap();
+    let storage = StorageManager::new(&db_url).unwrap();
+    let now = Utc::now().trunc_subsecs(3);
+
+    let entry = minimal_entry(
+        INTEGRATION_ENTRY_ID_BASE + 140,
+        "DeadCameraEntry",
+        "/test/integration/dead_camera",
+    );
+    let entry_id = insert_entry(&storage, entry).await.id;
+    storage
+        .add_sensor(
+            Sensor {
+                id: 0,
+                entry_id,
+                sensor_name: "front_camera".to_string(),
+                manufacturer: None,
+                sensor_type: Some("Camera".to_string()),
+                ros_topics: vec!["/cam/front/image".to_string()],
+                custom_parameters: None,
+                version: 1,
+            },
+            TXID,
+        )
+        .await
+        .unwrap();
+    storage
+        .add_topic(
+            Topic {
+                id: 0,
+                entry_id,
+                topic_name: "/cam/rear/image".to_string(),
+                topic_type: None,
+                message_count: 10,
+                frequency: Some(10.0),
+                created_at: now,
+                updated_at: now,
+            },
+            TXID,
+        )
+        .await
+        .unwrap();
+
+    assert!(
+        storage
+            .get_entry_consistency(entry_id, TXID)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    let report = storage
+        .check_entry_consistency(entry_id, TXID)
+        .await
+        .unwrap();
+    assert_eq!(report.missing_topics, 1);
+    assert_eq!(report.unattributed_topics, 1);
+    assert_eq!(report.frequency_mismatches, 0);
+    let stored = storage
+        .get_entry_consistency(entry_id, TXID)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.issues, report.issues);
+
+    let filter = |c: &str| EntryFilter {
+        consistency: Some(c.to_string()),
+    };
+    let (dead, _) = storage
+        .get_entries_filtered(None, filter("missing_topics"), None, None, None, None, TXID)
+        .await
+        .unwrap();
+    assert!(dead.iter().any(|e| e.id == entry_id));
+    let (ok, _) = storage
+        .get_entries_filtered(None, filter("ok"), None, None, None, None, TXID)
+        .await
+        .unwrap();
+    assert!(!ok.iter().any(|e| e.id == entry_id));
+
+    let err = storage
+        .get_entries_filtered(None, filter("broken"), None, None, None, None, TXID)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StorageError::ValidationError(_)));
+}
+
+#[tokio::test]
+async fn test_tag_curation() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let db_url = env::var("DATABASE_URL").unwrap();
+    let storage = StorageManager::new(&db_url).unwrap();
+
+    // eigener Namensraum, damit Tags anderer Tests nicht mitgezählt werden
+    let ns = "it_curation";
+    let tag = |t: &str| format!("{ns}/{t}");
+    let mut entry_ids = Vec::new();
+    for (i, tags) in [
+        vec![tag("Night"), tag("highway")],
+        vec![tag("nighttime")],
+        vec![tag("night"), tag("Night")],
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let mut entry = minimal_entry(
+            INTEGRATION_ENTRY_ID_BASE + 150 + i as i64,
+            &format!("TagCurationEntry{i}"),
+            &format!("/test/integration/tag_curation_{i}"),
+        );
+        entry.tags = tags;
+        entry_ids.push(insert_entry(&storage, entry).await.id);
+    }
+
+    let definition = storage
+        .update_tag_definition(
+            tag("Night"),
+            Some("#112233".to_string()),
+            Some("after sunset".to_string()),
+            None,
+            TXID,
+        )
+        .await
+        .unwrap();
+    let err = storage
+        .update_tag_definition(tag("Night"), None, None, Some(definition.version + 1), TXID)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StorageError::PreconditionFailed(_)));
+
+    let tags = storage
+        .get_tags(Some(ns.to_string()), None, TXID)
+        .await
+        .unwrap();
+    let names: Vec<&str> = tags.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "it_curation/Night",
+            "it_curation/highway",
+            "it_curation/night",
+            "it_curation/nighttime"
+        ]
+    );
+    assert_eq!(tags[0].entry_count, 2);
+    assert_eq!(tags[0].namespace.as_deref(), Some(ns));
+    assert_eq!(tags[0].color.as_deref(), Some("#112233"));
+
+    // jeder geänderte Eintrag wird einmal gemeldet
+    let mut events = storage.events().subscribe();
+    let mut updated = || {
+        let mut ids = Vec::new();
+        while let Ok(CatalogEvent::MetadataUpdated { entry_id }) = events.try_recv() {
+            ids.push(entry_id);
+        }
+        ids.sort();
+        ids
+    };
+
+    let changed = storage
+        .merge_tags(vec![tag("Night"), tag("nighttime")], tag("night"), TXID)
+        .await
+        .unwrap();
+    assert_eq!(changed.entries, 3);
+    assert_eq!(updated(), entry_ids);
+    let first = storage
+        .get_entry(entry_ids[0], TXID)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        first.tags,
+        vec![tag("night"), tag("highway")],
+        "position is kept"
+    );
+    let third = storage
+        .get_entry(entry_ids[2], TXID)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(third.tags, vec![tag("night")], "no duplicates after merge");
+
+    let tags = storage
+        .get_tags(Some(ns.to_string()), Some("NIGHT".to_string()), TXID)
+        .await
+        .unwrap();
+    assert_eq!(tags.len(), 1);
+    assert_eq!(tags[0].entry_count, 3);
+    assert_eq!(
+        tags[0].description.as_deref(),
+        Some("after sunset"),
+        "metadata moves to the target"
+    );
+
+    storage
+        .rename_tag(tag("highway"), tag("road/highway"), TXID)
+        .await
+        .unwrap();
+    assert_eq!(updated(), vec![entry_ids[0]]);
+    let err = storage
+        .rename_tag(tag("highway"), tag("road/highway"), TXID)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StorageError::NotFound(_)));
+    assert!(updated().is_empty());
+
+    let matched = storage
+        .bulk_tag_entries(
+            Some("TagCurationEntry".to_string()),
+            EntryFilter::default(),
+            vec![tag("reviewed")],
+            vec![tag("night")],
+            TXID,
+        )
+        .await
+        .unwrap();
+    assert_eq!(matched, 3);
+    let tags = storage
+        .get_tags(Some(ns.to_string()), None, TXID)
+        .await
+        .unwrap();
+    let counts: Vec<(&str, i64)> = tags
+        .iter()
+        .map(|t| (t.name.as_str(), t.entry_count))
+        .collect();
+    assert_eq!(
+        counts,
+        vec![
+            ("it_curation/night", 0),
+            ("it_curation/reviewed", 3),
+            ("it_curation/road/highway", 1)
+        ]
+    );
+    assert_eq!(updated(), entry_ids);
+
+    let changed = storage.delete_tag(tag("reviewed"), TXID).await.unwrap();
+    assert_eq!(changed.entries, 3);
+    assert_eq!(updated(), entry_ids);
+}
+
+#[tokio::test]
+async fn test_bulk_update_entries() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let db_url = env::var("DATABASE_URL").unwrap();
+    let storage = StorageManager::new(&db_url).unwrap();
+
+    let mut entry_ids = Vec::new();
+    for i in 0..3 {
+        let entry = minimal_entry(
+            INTEGRATION_ENTRY_ID_BASE + 160 + i,
+            &format!("BulkCampaignEntry{i}"),
+            &format!("/test/integration/bulk_campaign_{i}"),
+        );
+        entry_ids.push(insert_entry(&storage, entry).await.id);
+    }
+
+    // entweder Id-Liste oder Suche
+    let err = storage
+        .select_entry_ids(None, None, EntryFilter::default(), TXID)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StorageError::ValidationError(_)));
+    let selected = storage
+        .select_entry_ids(
+            None,
+            Some("BulkCampaignEntry".to_string()),
+            EntryFilter::default(),
+            TXID,
+        )
+        .await
+        .unwrap();
+    assert_eq!(selected.len(), 3);
+
+    let missing = INTEGRATION_ENTRY_ID_BASE + 169;
+    let mut ids = vec![entry_ids[0], entry_ids[1], missing];
+    let report = storage
+        .bulk_update_entries(
+            ids.clone(),
+            BulkEntryChange::AddTags(vec!["campaign_2026".to_string()]),
+            TXID,
+        )
+        .await
+        .unwrap();
+    let statuses: Vec<BulkEntryStatus> = report.iter().map(|r| r.status).collect();
+    assert_eq!(
+        statuses,
+        vec![
+            BulkEntryStatus::Updated,
+            BulkEntryStatus::Updated,
+            BulkEntryStatus::NotFound
+        ]
+    );
+
+    ids.push(entry_ids[2]);
+    let report = storage
+        .bulk_update_entries(
+            ids,
+            BulkEntryChange::AddTags(vec!["campaign_2026".to_string()]),
+            TXID,
+        )
+        .await
+        .unwrap();
+    assert_eq!(report[0].status, BulkEntryStatus::Unchanged);
+    assert_eq!(report[3].status, BulkEntryStatus::Updated);
+
+    let patch = EntryMetadataPatch {
+        platform_name: Some("CampaignCar".to_string()),
+        ..Default::default()
+    };
+    storage
+        .bulk_update_entries(
+            selected,
+            BulkEntryChange::PatchMetadata(Box::new(patch)),
+            TXID,
+        )
+        .await
+        .unwrap();
+    for id in entry_ids.iter() {
+        let entry = storage.get_entry(*id, TXID).await.unwrap().unwrap();
+        assert_eq!(entry.platform_name.as_deref(), Some("CampaignCar"));
+        assert_eq!(entry.tags, vec!["campaign_2026".to_string()]);
+        assert_eq!(
+            entry.scenario_name, None,
+            "fields outside the patch are kept"
+        );
+    }
+
+    let err = storage
+        .bulk_update_entries(
+            entry_ids,
+            BulkEntryChange::PatchMetadata(Box::default()),
+            TXID,
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StorageError::ValidationError(_)));
+}
+
+#[tokio::test]
+async fn test_start_and_commit_transaction() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let db_url = env::var("DATABASE_URL").unwrap();
+    let storage = StorageManager::new(&db_url).unwrap();
+
+    let txid = storage.start_transaction();
+    // commit_transaction currently only affects in-memory active_transactions,
+    // but this test ensures it at least succeeds for a valid txid.
+    storage.commit_transaction(txid).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_collection_snapshots_and_diff() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let db_url = env::var("DATABASE_URL").unwrap();
+    let storage = StorageManager::new(&db_url).unwrap();
+
+    let mut entry_ids = Vec::new();
+    for i in 0..3 {
+        let mut entry = minimal_entry(
+            INTEGRATION_ENTRY_ID_BASE + 170 + i,
+            &format!("CollectionEntry{i}"),
+            &format!("/test/integration/collection_{i}"),
+        );
+        entry.size = 1000 * (i + 1);
+        entry_ids.push(insert_entry(&storage, entry).await.id);
+    }
+    let now = Utc::now().trunc_subsecs(3);
+    let seq = Sequence {
+        name: "lane_change".to_string(),
+        id: 0,
+        entry_id: entry_ids[2],
+        description: String::new(),
+        start_timestamp: 100,
+        end_timestamp: 200,
+        created_at: now,
+        updated_at: now,
+        tags: vec![],
+        version: 1,
+    };
+    let seq_id = storage.add_sequence(entry_ids[2], seq, TXID).await.unwrap();
+
+    let collection = storage
+        .create_collection("lane-changes-v1".to_string(), None, TXID)
+        .await
+        .unwrap();
+    let err = storage
+        .create_collection(" lane-changes-v1 ".to_string(), None, TXID)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StorageError::AlreadyExists(_)));
+
+    let added = storage
+        .add_collection_items(
+            collection.id,
+            vec![(entry_ids[0], None), (entry_ids[2], Some(seq_id))],
+            TXID,
+        )
+        .await
+        .unwrap();
+    assert_eq!(added.len(), 2);
+    // bereits enthaltene Elemente werden übersprungen
+    let added_again = storage
+        .add_collection_items(collection.id, vec![(entry_ids[0], None)], TXID)
+        .await
+        .unwrap();
+    assert!(added_again.is_empty());
+    // Sequenz muss zum Eintrag gehören
+    let err = storage
+        .add_collection_items(collection.id, vec![(entry_ids[1], Some(seq_id))], TXID)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StorageError::NotFound(_)));
+
+    let v1 = storage
+        .freeze_collection(collection.id, Some("first cut".to_string()), TXID)
+        .await
+        .unwrap();
+    assert_eq!(v1.snapshot.snapshot_version, 1);
+    assert_eq!(v1.snapshot.item_count, 2);
+    assert_eq!(v1.snapshot.total_size, 1000 + 3000);
+    assert!(
+        storage
+            .diff_collection(collection.id, None, None, TXID)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    // Änderungen am Live-Stand verändern den Snapshot nicht
+    let first_item = added.iter().find(|i| i.entry_id == entry_ids[0]).unwrap();
+    storage
+        .remove_collection_item(collection.id, first_item.id, TXID)
+        .await
+        .unwrap();
+    storage
+        .add_collection_items(collection.id, vec![(entry_ids[1], None)], TXID)
+        .await
+        .unwrap();
+    let diff = storage
+        .diff_collection(collection.id, None, None, TXID)
+        .await
+        .unwrap();
+    assert_eq!(diff.from_version, Some(1));
+    assert_eq!(diff.to_version, None);
+    assert_eq!(diff.added.len(), 1);
+    assert_eq!(diff.added[0].entry_id, entry_ids[1]);
+    assert_eq!(diff.removed.len(), 1);
+    assert_eq!(diff.removed[0].entry_id, entry_ids[0]);
+
+    let v2 = storage
+        .freeze_collection(collection.id, None, TXID)
+        .await
+        .unwrap();
+    assert_eq!(v2.snapshot.snapshot_version, 2);
+    let stored_v1 = storage
+        .get_collection_snapshot(collection.id, 1, TXID)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored_v1.items, v1.items);
+    let diff = storage
+        .diff_collection(collection.id, Some(1), Some(2), TXID)
+        .await
+        .unwrap();
+    assert_eq!((diff.added.len(), diff.removed.len()), (1, 1));
+
+    let summaries = storage.get_collections(TXID).await.unwrap();
+    let summary = summaries
+        .iter()
+        .find(|s| s.collection.id == collection.id)
+        .unwrap();
+    assert_eq!(summary.item_count, 2);
+    assert_eq!(summary.latest_snapshot_version, Some(2));
+
+    // eingefrorene Sammlungen bleiben erhalten
+    let err = storage
+        .delete_collection(collection.id, TXID)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StorageError::ValidationError(_)));
+    let err = storage
+        .diff_collection(collection.id, Some(9), None, TXID)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StorageError::NotFound(_)));
+}
+
+#[tokio::test]
+async fn test_freeze_large_collection() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let db_url = env::var("DATABASE_URL").unwrap();
+    let storage = StorageManager::new(&db_url).unwrap();
+
+    let entry = minimal_entry(
+        INTEGRATION_ENTRY_ID_BASE + 211,
+        "LargeCollectionEntry",
+        "/test/integration/large_collection",
+    );
+    let entry_id = insert_entry(&storage, entry).await.id;
+    let now = Utc::now().trunc_subsecs(3);
+    // mehr Elemente, als in eine einzelne Anweisung passen (65535 / 9 Parameter)
+    let mut items = Vec::new();
+    for i in 0..7300 {
+        let seq = Sequence {
+            name: format!("slice_{i}"),
+            id: 0,
+            entry_id,
+            description: String::new(),
+            start_timestamp: 100,
+            end_timestamp: 200,
+            created_at: now,
+            updated_at: now,
+            tags: vec![],
+            version: 1,
+        };
+        let seq_id = storage.add_sequence(entry_id, seq, TXID).await.unwrap();
+        items.push((entry_id, Some(seq_id)));
+    }
+    let collection = storage
+        .create_collection("large-collection".to_string(), None, TXID)
+        .await
+        .unwrap();
+    storage
+        .add_collection_items(collection.id, items, TXID)
+        .await
+        .unwrap();
+
+    let snapshot = storage
+        .freeze_collection(collection.id, None, TXID)
+        .await
+        .unwrap();
+    assert_eq!(snapshot.snapshot.item_count, 7300);
+    assert_eq!(snapshot.items.len(), 7300);
+    let stored = storage
+        .get_collection_snapshot(collection.id, 1, TXID)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.items.len(), 7300);
+}
+
+#[tokio::test]
+async fn test_manifest_rows() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let db_url = env::var("DATABASE_URL").unwrap();
+    let storage = StorageManager::new(&db_url).unwrap();
+
+    let mut entries = Vec::new();
+    for i in 0..2 {
+        let entry = minimal_entry(
+            INTEGRATION_ENTRY_ID_BASE + 180 + i,
+            &format!("ManifestEntry{i}"),
+            &format!("/test/integration/manifest_{i}"),
+        );
+        entries.push(insert_entry(&storage, entry).await);
+    }
+    let sensor = Sensor {
+        id: 0,
+        entry_id: entries[1].id,
+        sensor_name: "RearRadar".to_string(),
+        manufacturer: None,
+        sensor_type: None,
+        ros_topics: vec!["/radar".to_string()],
+        custom_parameters: None,
+        version: 1,
+    };
+    storage.add_sensor(sensor, TXID).await.unwrap();
+    let now = Utc::now().trunc_subsecs(3);
+    let seq = Sequence {
+        name: "overtake".to_string(),
+        id: 0,
+        entry_id: entries[1].id,
+        description: String::new(),
+        start_timestamp: 0,
+        end_timestamp: 10,
+        created_at: now,
+        updated_at: now,
+        tags: vec![],
+        version: 1,
+    };
+    storage
+        .add_sequence(entries[1].id, seq, TXID)
+        .await
+        .unwrap();
+
+    // Reihenfolge der Einträge bleibt erhalten
+    let reversed: Vec<Entry> = entries.iter().rev().cloned().collect();
+    let rows = storage.get_manifest_rows(reversed, TXID).await.unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].entry_id, entries[1].id);
+    assert_eq!(rows[0].sensors, vec!["RearRadar".to_string()]);
+    assert_eq!(rows[0].sequences, vec!["overtake".to_string()]);
+    assert_eq!(rows[0].sequence_count, 1);
+    assert_eq!(rows[1].entry_id, entries[0].id);
+    assert!(rows[1].sensors.is_empty());
+    assert_eq!(rows[1].topic_count, 0);
+}
+
+#[tokio::test]
+async fn test_metadata_import() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let db_url = env::var("DATABASE_URL").unwrap();
+    let storage = StorageManager::new(&db_url).unwrap();
+
+    let mut entry = minimal_entry(
+        INTEGRATION_ENTRY_ID_BASE + 190,
+        "ImportEntry",
+        "/test/integration/import_0",
+    );
+    entry.mcap_start_ns = Some(0);
+    entry.mcap_end_ns = Some(100);
+    entry.tags = vec!["existing".to_string()];
+    let entry = insert_entry(&storage, entry).await;
+
+    let body = "path,scenario_name,weather_fog,tags,sequence_name,sequence_start_timestamp,sequence_end_timestamp\n\
+                /test/integration/import_0,highway,true,existing;imported,overtake,10,20\n";
+    let rows = metadata_import::parse_csv_rows(body).unwrap();
+
+    // Probelauf schreibt nichts
+    let report = storage
+        .import_metadata(rows.clone(), true, TXID)
+        .await
+        .unwrap();
+    assert!(!report.applied);
+    assert_eq!(report.invalid, 0);
+    assert_eq!(report.rows[0].status, ImportRowStatus::Valid);
+    assert_eq!(report.rows[0].entry_id, Some(entry.id));
+    assert_eq!(report.rows[0].fields, vec!["scenario_name", "weather_fog"]);
+    let unchanged = storage.get_entry(entry.id, TXID).await.unwrap().unwrap();
+    assert_eq!(unchanged.scenario_name, None);
+
+    // eine ungültige Zeile blockiert den ganzen Import
+    let mut blocked = rows.clone();
+    blocked.extend(
+        metadata_import::parse_csv_rows(
+            "path,sequence_name,sequence_start_timestamp,sequence_end_timestamp\n\
+             /test/integration/import_0,late,50,500\n\
+             /test/integration/missing,,,\n",
+        )
+        .unwrap(),
+    );
+    let report = storage.import_metadata(blocked, false, TXID).await.unwrap();
+    assert!(!report.applied);
+    assert_eq!(report.invalid, 2);
+    assert!(report.rows[1].errors[0].contains("recording end"));
+    assert!(report.rows[2].errors[0].contains("no entry"));
+    let unchanged = storage.get_entry(entry.id, TXID).await.unwrap().unwrap();
+    assert_eq!(unchanged.scenario_name, None);
+
+    // Anwenden; Wiederholung legt weder Tags noch Sequenzen doppelt an
+    for _ in 0..2 {
+        let report = storage
+            .import_metadata(rows.clone(), false, TXID)
+            .await
+            .unwrap();
+        assert!(report.applied);
+        assert_eq!(report.rows[0].status, ImportRowStatus::Applied);
+    }
+    let imported = storage.get_entry(entry.id, TXID).await.unwrap().unwrap();
+    assert_eq!(imported.scenario_name.as_deref(), Some("highway"));
+    assert_eq!(imported.weather_fog, Some(true));
+    assert_eq!(imported.tags, vec!["existing", "imported"]);
+    assert!(imported.version > entry.version);
+    let sequences = storage.get_sequences(entry.id, TXID).await.unwrap();
+    assert_eq!(sequences.len(), 1);
+    assert!(sequences.values().all(|s| s.name == "overtake"));
+}
+
+#[tokio::test]
+async fn test_metadata_import_is_atomic() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let db_url = env::var("DATABASE_URL").unwrap();
+    let storage = StorageManager::new(&db_url).unwrap();
+    let first = insert_entry(
+        &storage,
+        minimal_entry(
+            INTEGRATION_ENTRY_ID_BASE + 192,
+            "ImportFirst",
+            "/test/integration/import_first",
+        ),
+    )
+    .await;
+    let second = insert_entry(
+        &storage,
+        minimal_entry(
+            INTEGRATION_ENTRY_ID_BASE + 193,
+            "ImportSecond",
+            "/test/integration/import_second",
+        ),
+    )
+    .await;
+
+    let body = "path,scenario_name,tags\n\
+                /test/integration/import_first,highway,imported\n\
+                /test/integration/import_second,city,imported\n";
+    let rows = metadata_import::parse_csv_rows(body).unwrap();
+    let plan = storage
+        .plan_metadata_import(rows, false, TXID)
+        .await
+        .unwrap();
+    assert!(plan.report.applied);
+
+    // der zweite Eintrag ändert sich zwischen Prüfung und Schreiben
+    let patch = EntryMetadataPatch {
+        scenario_name: Some("edited".to_string()),
+        ..Default::default()
+    };
+    let web = metadata_import::metadata_with_patch(&second, &patch);
+    storage
+        .update_entry(second.id, web, Some(second.version), TXID)
+        .await
+        .unwrap();
+
+    let mut events = storage.events().subscribe();
+    let err = storage.apply_metadata_import(plan, TXID).await.unwrap_err();
+    assert!(matches!(err, StorageError::PreconditionFailed(_)));
+
+    let first_after = storage.get_entry(first.id, TXID).await.unwrap().unwrap();
+    assert_eq!(first_after.scenario_name, None);
+    assert!(first_after.tags.is_empty());
+    assert_eq!(first_after.version, first.version);
+    let second_after = storage.get_entry(second.id, TXID).await.unwrap().unwrap();
+    assert_eq!(second_after.scenario_name.as_deref(), Some("edited"));
+    assert!(second_after.tags.is_empty());
+    assert!(events.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_metadata_updates_publish_events() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let db_url = env::var("DATABASE_URL").unwrap();
+    let storage = StorageManager::new(&db_url).unwrap();
+    let entry = insert_entry(
+        &storage,
+        minimal_entry(
+            INTEGRATION_ENTRY_ID_BASE + 191,
+            "EventEntry",
+            "/test/integration/events_0",
+        ),
+    )
+    .await;
+    let mut events = storage.events().subscribe();
+
+    let patch = EntryMetadataPatch {
+        scenario_name: Some("events".to_string()),
+        ..Default::default()
+    };
+    let metadata = metadata_import::metadata_with_patch(&entry, &patch);
+    storage
+        .update_entry(entry.id, metadata, None, TXID)
+        .await
+        .unwrap();
+    assert_eq!(
+        events.try_recv().unwrap(),
+        CatalogEvent::MetadataUpdated { entry_id: entry.id }
+    );
+
+    // unveränderte Einträge erzeugen kein Ereignis
+    let results = storage
+        .bulk_update_entries(
+            vec![entry.id, INTEGRATION_ENTRY_ID_BASE + 199],
+            BulkEntryChange::AddTags(vec!["events".to_string()]),
+            TXID,
+        )
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(
+        events.try_recv().unwrap(),
+        CatalogEvent::MetadataUpdated { entry_id: entry.id }
+    );
+    assert!(events.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_users_and_sessions() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let db_url = env::var("DATABASE_URL").unwrap();
+    let storage = StorageManager::new(&db_url).unwrap();
+    let conn = storage.db_connection_pool().get().await.unwrap();
+    conn.interact(|conn| {
+        use schema::users::dsl as users_dsl;
+        diesel::delete(users_dsl::users.filter(users_dsl::username.like("integration-%")))
+            .execute(conn)
+    })
+    .await
+    .unwrap()
+    .unwrap();
+
+    let password = "integration-password".to_string();
+    let admin = storage
+        .create_user(
+            "integration-admin".to_string(),
+            password.clone(),
+            Role::Admin,
+            TXID,
+        )
+        .await
+        .unwrap();
+    let editor = storage
+        .create_user(
+            "integration-editor".to_string(),
+            password.clone(),
+            Role::Editor,
+            TXID,
+        )
+        .await
+        .unwrap();
+    assert_ne!(editor.password_hash, password);
+    let err = storage
+        .create_user(
+            "integration-editor".to_string(),
+            password.clone(),
+            Role::Viewer,
+            TXID,
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StorageError::AlreadyExists(_)));
+
+    // Anmeldung
+    assert!(
+        storage
+            .login(
+                "integration-editor".to_string(),
+                "wrong-password".to_string()
+            )
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        storage
+            .login("integration-nobody".to_string(), password.clone())
+            .await
+            .unwrap()
+            .is_none()
+    );
+    let session = storage
+        .login("integration-editor".to_string(), password.clone())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(session.expires_at > Utc::now());
+    let user = storage.authenticate(session.token.clone()).await.unwrap();
+    assert_eq!(user.map(|u| u.id), Some(editor.id));
+    assert!(
+        storage
+            .authenticate("unknown".to_string())
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    // neues Passwort beendet alle Sitzungen
+    let updated = storage
+        .update_user(
+            editor.id,
+            Some(Role::PluginOperator),
+            Some("integration-password-2".to_string()),
+            None,
+            Some(editor.version),
+            TXID,
+        )
+        .await
+        .unwrap();
+    assert_eq!(updated.role, "plugin-operator");
+    assert!(updated.version > editor.version);
+    assert!(storage.authenticate(session.token).await.unwrap().is_none());
+    let err = storage
+        .update_user(
+            editor.id,
+            None,
+            None,
+            Some(true),
+            Some(editor.version),
+            TXID,
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StorageError::PreconditionFailed(_)));
+
+    // gesperrte Konten können sich nicht anmelden
+    storage
+        .update_user(editor.id, None, None, Some(true), None, TXID)
+        .await
+        .unwrap();
+    assert!(
+        storage
+            .login(
+                "integration-editor".to_string(),
+                "integration-password-2".to_string()
+            )
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    // der letzte aktive Admin bleibt erhalten; andere Tests (api.rs) legen in
+    // derselben Datenbank eigene Admins an
+    let other_admins = storage
+        .get_users(TXID)
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|u| u.id != admin.id && u.role == "admin" && !u.disabled)
+        .count();
+    if other_admins == 0 {
+        let err = storage
+            .update_user(admin.id, Some(Role::Editor), None, None, None, TXID)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, StorageError::ValidationError(_)));
+        let err = storage.delete_user(admin.id, TXID).await.unwrap_err();
+        assert!(matches!(err, StorageError::ValidationError(_)));
+    }
+    storage.delete_user(editor.id, TXID).await.unwrap();
+    let err = storage.get_user(editor.id, TXID).await.unwrap_err();
+    assert!(matches!(err, StorageError::NotFound(_)));
+    assert!(
+        !storage
+            .ensure_initial_admin("integration-second".to_string(), password)
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_api_keys() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let db_url = env::var("DATABASE_URL").unwrap();
+    let storage = StorageManager::new(&db_url).unwrap();
+
+    for (name, scopes) in [("", vec![Scope::ReadEntries]), ("integration-ci", vec![])] {
+        let err = storage
+            .create_api_key(name.to_string(), scopes, None, None, TXID)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, StorageError::ValidationError(_)));
+    }
+    let err = storage
+        .create_api_key(
+            "integration-ci".to_string(),
+            vec![Scope::ReadEntries],
+            Some(Utc::now() - chrono::TimeDelta::minutes(1)),
+            None,
+            TXID,
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StorageError::ValidationError(_)));
+
+    let created = storage
+        .create_api_key(
+            "integration-ci".to_string(),
+            vec![Scope::RunPlugins, Scope::ReadEntries, Scope::RunPlugins],
+            Some(Utc::now() + chrono::TimeDelta::days(1)),
+            None,
+            TXID,
+        )
+        .await
+        .unwrap();
+    assert_eq!(created.api_key.scopes, vec!["read:entries", "run:plugins"]);
+    assert_ne!(created.api_key.key_hash, created.key);
+
+    let found = storage
+        .authenticate_api_key(created.key.clone())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.id, created.api_key.id);
+    assert!(found.last_used_at.is_some());
+    assert!(
+        storage
+            .authenticate_api_key(format!("{}0", created.key))
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    // abgelaufene Schlüssel werden abgewiesen
+    let key_id = created.api_key.id;
+    let conn = storage.db_connection_pool().get().await.unwrap();
+    conn.interact(move |conn| {
+        use schema::api_keys::dsl as keys_dsl;
+        diesel::update(keys_dsl::api_keys.find(key_id))
+            .set(keys_dsl::expires_at.eq(Utc::now() - chrono::TimeDelta::seconds(1)))
+            .execute(conn)
+    })
+    .await
+    .unwrap()
+    .unwrap();
+    assert!(
+        storage
+            .authenticate_api_key(created.key.clone())
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    storage.delete_api_key(key_id, TXID).await.unwrap();
+    let err = storage.delete_api_key(key_id, TXID).await.unwrap_err();
+    assert!(matches!(err, StorageError::NotFound(_)));
+    let keys = storage.get_api_keys(TXID).await.unwrap();
+    assert!(keys.iter().all(|k| k.id != key_id));
+}
+
+#[tokio::test]
+async fn test_audit_log() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let db_url = env::var("DATABASE_URL").unwrap();
+    let storage = StorageManager::new(&db_url).unwrap();
+    // eindeutiger Akteur, damit frühere Läufe nicht mitzählen
+    let actor = format!("plugin:integration-audit:{}", Utc::now().timestamp_micros());
+    let record = |path: &str, status: i32, outcome: &str| NewAuditRecord {
+        actor: actor.clone(),
+        method: "PUT".to_string(),
+        route: Some("/entries/<entry_id>/metadata".to_string()),
+        path: path.to_string(),
+        target_ids: serde_json::json!({ "entry_id": "12" }),
+        parameters: serde_json::json!({ "txid": "0" }),
+        status,
+        outcome: outcome.to_string(),
+    };
+    let before = Utc::now();
+    storage
+        .record_audit(record("/entries/12/metadata", 200, "success"))
+        .await
+        .unwrap();
+    storage
+        .record_audit(record("/entries/120/metadata", 412, "rejected"))
+        .await
+        .unwrap();
+    storage
+        .record_audit(record("/entries/12", 403, "denied"))
+        .await
+        .unwrap();
+
+    let filter = |target: Option<&str>| AuditFilter {
+        actor: Some(actor.clone()),
+        target: target.map(str::to_string),
+        ..Default::default()
+    };
+    let (records, num_pages) = storage
+        .get_audit_log(filter(None), None, None, TXID)
+        .await
+        .unwrap();
+    assert_eq!(num_pages, 1);
+    let paths: Vec<&str> = records.iter().map(|r| r.path.as_str()).collect();
+    assert_eq!(
+        paths,
+        vec![
+            "/entries/12",
+            "/entries/120/metadata",
+            "/entries/12/metadata"
+        ]
+    );
+    assert_eq!(records[0].outcome, "denied");
+    assert_eq!(records[2].target_ids["entry_id"], "12");
+
+    // Ziel findet Unterpfade, aber nicht /entries/120
+    let (records, _) = storage
+        .get_audit_log(filter(Some("/entries/12")), None, None, TXID)
+        .await
+        .unwrap();
+    assert_eq!(records.len(), 2);
+
+    // Akteur-Präfix bis zum Doppelpunkt
+    let prefix = AuditFilter {
+        actor: Some(actor.rsplit_once(':').unwrap().0.to_string()),
+        since: Some(before),
+        ..Default::default()
+    };
+    let (records, _) = storage
+        .get_audit_log(prefix, None, None, TXID)
+        .await
+        .unwrap();
+    assert!(records.len() >= 3);
+    assert!(
+        records
+            .iter()
+            .all(|r| r.actor.starts_with("plugin:integration-audit:"))
+    );
+
+    let (records, num_pages) = storage
+        .get_audit_log(filter(None), Some(1), Some(2), TXID)
+        .await
+        .unwrap();
+    assert_eq!(num_pages, 2);
+    assert_eq!(records.len(), 1);
+
+    let future = AuditFilter {
+        since: Some(Utc::now() + chrono::TimeDelta::hours(1)),
+        ..filter(None)
+    };
+    let (records, _) = storage
+        .get_audit_log(future, None, None, TXID)
+        .await
+        .unwrap();
+    assert!(records.is_empty());
+}
+
+#[tokio::test]
+async fn test_plugin_runs() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let db_url = env::var("DATABASE_URL").unwrap();
+    let storage = StorageManager::new(&db_url).unwrap();
+    // eindeutiger Plugin-Name und Instanz-IDs, damit frühere Läufe nicht mitzählen
+    let base = Utc::now().timestamp_micros();
+    let plugin_name = format!("integration-runs-{base}");
+    let run = |offset: i64| NewPluginRun {
+        instance_id: base + offset,
+        plugin_name: plugin_name.clone(),
+        plugin_version: Some("1.2.0".to_string()),
+        trigger: "manual".to_string(),
+        payload: serde_json::json!({ "entry_path": "/data/runs.mcap" }),
+        entry_path: Some("/data/runs.mcap".to_string()),
+        state: "Running".to_string(),
+        queued_at: None,
+        attempt: 1,
+        retry_of: None,
+    };
+    for offset in 0..3 {
+        storage.insert_plugin_run(run(offset)).await.unwrap();
+    }
+
+    let completed = RunOutcome {
+        result: Some(serde_json::json!({ "answer": 42 })),
+        ..RunOutcome::new(InstanceState::Completed)
+    };
+    assert!(storage.finish_plugin_run(base, completed).await.unwrap());
+    // das erste Ergebnis bleibt stehen
+    let late = RunOutcome::failed(InstanceState::Failed, "late".to_string());
+    assert!(!storage.finish_plugin_run(base, late).await.unwrap());
+
+    let failed = RunOutcome {
+        trace: Some("Traceback ...".to_string()),
+        ..RunOutcome::failed(InstanceState::Failed, "boom".to_string())
+    };
+    assert!(storage.finish_plugin_run(base + 1, failed).await.unwrap());
+
+    let (runs, num_pages) = storage
+        .get_plugin_runs(Some(plugin_name.clone()), None, Some(2), TXID)
+        .await
+        .unwrap();
+    assert_eq!(num_pages, 2);
+    assert_eq!(runs.len(), 2);
+    // neueste zuerst
+    assert_eq!(runs[0].instance_id, base + 2);
+    assert_eq!(runs[0].state, "Running");
+    assert!(runs[0].ended_at.is_none());
+    assert_eq!(runs[1].instance_id, base + 1);
+    assert_eq!(runs[1].state, "Failed");
+    assert_eq!(runs[1].error.as_deref(), Some("boom"));
+    assert_eq!(runs[1].trace.as_deref(), Some("Traceback ..."));
+
+    let (runs, _) = storage
+        .get_plugin_runs(Some(plugin_name.clone()), Some(1), Some(2), TXID)
+        .await
+        .unwrap();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].instance_id, base);
+    assert_eq!(runs[0].state, "Completed");
+    assert_eq!(runs[0].result, Some(serde_json::json!({ "answer": 42 })));
+    assert_eq!(runs[0].error, None);
+    assert_eq!(runs[0].plugin_version.as_deref(), Some("1.2.0"));
+    assert!(runs[0].ended_at.is_some());
+
+    assert!(storage.abandon_unfinished_plugin_runs().await.unwrap() >= 1);
+    let (runs, _) = storage
+        .get_plugin_runs(Some(plugin_name), None, None, TXID)
+        .await
+        .unwrap();
+    assert_eq!(runs.len(), 3);
+    assert!(runs.iter().all(|r| r.ended_at.is_some()));
+    assert_eq!(runs[0].state, "Failed");
+    assert_eq!(
+        runs[0].error.as_deref(),
+        Some("backend stopped before the run finished")
+    );
+}
+
+#[tokio::test]
+async fn test_queued_plugin_runs() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let db_url = env::var("DATABASE_URL").unwrap();
+    let storage = StorageManager::new(&db_url).unwrap();
+    let base = Utc::now().timestamp_micros();
+    let plugin_name = format!("integration-queue-{base}");
+    let queued_at = Utc::now().trunc_subsecs(3);
+    let run = |offset: i64, state: InstanceState| NewPluginRun {
+        instance_id: base + offset,
+        plugin_name: plugin_name.clone(),
+        plugin_version: None,
+        trigger: "on_entry_create".to_string(),
+        payload: serde_json::json!({ "event": "created", "path": "/data/q.mcap" }),
+        entry_path: Some("/data/q.mcap".to_string()),
+        state: format!("{state:?}"),
+        queued_at: Some(queued_at),
+        attempt: 1,
+        retry_of: None,
+    };
+
+    // eingereiht, dann gestartet: die Zeile wird übernommen
+    storage
+        .insert_plugin_run(run(0, InstanceState::Queued))
+        .await
+        .unwrap();
+    storage
+        .insert_plugin_run(run(0, InstanceState::Running))
+        .await
+        .unwrap();
+    // ein verspätetes Einreihen überschreibt den Start nicht
+    storage
+        .insert_plugin_run(run(0, InstanceState::Queued))
+        .await
+        .unwrap();
+
+    // abgebrochen, bevor er lief; ein späterer Start ändert nichts mehr
+    storage
+        .insert_plugin_run(run(1, InstanceState::Queued))
+        .await
+        .unwrap();
+    let cancelled = RunOutcome::failed(InstanceState::Stopped, "cancelled before start".into());
+    assert!(
+        storage
+            .finish_plugin_run(base + 1, cancelled)
+            .await
+            .unwrap()
+    );
+    storage
+        .insert_plugin_run(run(1, InstanceState::Running))
+        .await
+        .unwrap();
+
+    let (runs, _) = storage
+        .get_plugin_runs(Some(plugin_name), None, None, TXID)
+        .await
+        .unwrap();
+    assert_eq!(runs.len(), 2);
+    assert_eq!(runs[1].instance_id, base);
+    assert_eq!(runs[1].state, "Running");
+    assert_eq!(runs[1].queued_at, Some(queued_at));
+    assert!(runs[1].started_at >= queued_at);
+    assert_eq!(runs[0].instance_id, base + 1);
+    assert_eq!(runs[0].state, "Stopped");
+    assert_eq!(runs[0].error.as_deref(), Some("cancelled before start"));
+    assert!(runs[0].ended_at.is_some());
+}
+
+#[tokio::test]
+async fn test_plugin_run_attempts() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let db_url = env::var("DATABASE_URL").unwrap();
+    let storage = StorageManager::new(&db_url).unwrap();
+    let base = Utc::now().timestamp_micros();
+    let run = |offset: i64, attempt: i32| NewPluginRun {
+        instance_id: base + offset,
+        plugin_name: format!("integration-retry-{base}"),
+        plugin_version: None,
+        trigger: "manual".to_string(),
+        payload: serde_json::json!({ "entry_path": "/data/retry.mcap" }),
+        entry_path: Some("/data/retry.mcap".to_string()),
+        state: "Running".to_string(),
+        queued_at: None,
+        attempt,
+        retry_of: (attempt > 1).then_some(base),
+    };
+    storage.insert_plugin_run(run(0, 1)).await.unwrap();
+    let retrying = RunOutcome::failed(InstanceState::Retrying, "share offline".to_string());
+    assert!(storage.finish_plugin_run(base, retrying).await.unwrap());
+    storage.insert_plugin_run(run(7, 2)).await.unwrap();
+    let failed = RunOutcome::failed(InstanceState::Failed, "share offline".to_string());
+    assert!(storage.finish_plugin_run(base + 7, failed).await.unwrap());
+    storage.insert_plugin_run(run(9, 3)).await.unwrap();
+
+    // gleich, ob über den ersten oder einen späteren Versuch abgefragt
+    for instance_id in [base, base + 9] {
+        let attempts = storage.get_plugin_run_attempts(instance_id).await.unwrap();
+        let summary: Vec<(i64, i32, &str)> = attempts
+            .iter()
+            .map(|r| (r.instance_id, r.attempt, r.state.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (base, 1, "Retrying"),
+                (base + 7, 2, "Failed"),
+                (base + 9, 3, "Running"),
+            ]
+        );
+        assert_eq!(attempts[0].retry_of, None);
+        assert!(attempts[1..].iter().all(|r| r.retry_of == Some(base)));
+    }
+
+    assert!(matches!(
+        storage.get_plugin_run_attempts(base + 5).await,
+        Err(StorageError::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_plugin_logs() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let db_url = env::var("DATABASE_URL").unwrap();
+    let storage = StorageManager::new(&db_url).unwrap();
+    let instance_id = Utc::now().timestamp_micros();
+    let line = |seq: i32, message: &str| PluginLogLine {
+        instance_id,
+        seq,
+        at: Utc::now().trunc_subsecs(3),
+        level: "INFO".to_string(),
+        logger: "plugin".to_string(),
+        message: message.to_string(),
+    };
+    // in beliebiger Reihenfolge geschrieben, nach `seq` gelesen
+    storage
+        .insert_plugin_logs(vec![line(2, "third"), line(0, "first")])
+        .await
+        .unwrap();
+    storage
+        .insert_plugin_logs(vec![line(1, "second"), line(0, "duplicate")])
+        .await
+        .unwrap();
+    storage.insert_plugin_logs(Vec::new()).await.unwrap();
+
+    let (lines, num_pages) = storage
+        .get_plugin_logs(instance_id, None, Some(2), TXID)
+        .await
+        .unwrap();
+    assert_eq!(num_pages, 2);
+    let messages: Vec<&str> = lines.iter().map(|l| l.message.as_str()).collect();
+    assert_eq!(messages, ["first", "second"]);
+    assert_eq!(
+        (lines[0].level.as_str(), lines[0].logger.as_str()),
+        ("INFO", "plugin")
+    );
+
+    let (lines, _) = storage
+        .get_plugin_logs(instance_id, Some(1), Some(2), TXID)
+        .await
+        .unwrap();
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0].seq, 2);
+
+    let (lines, num_pages) = storage
+        .get_plugin_logs(instance_id + 1, None, None, TXID)
+        .await
+        .unwrap();
+    assert!(lines.is_empty());
+    assert_eq!(num_pages, 0);
+
+    assert!(matches!(
+        storage.get_plugin_run(instance_id).await,
+        Err(StorageError::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_plugin_artifacts() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let db_url = env::var("DATABASE_URL").unwrap();
+    let storage = StorageManager::new(&db_url).unwrap();
+    let mut entry = minimal_entry(
+        INTEGRATION_ENTRY_ID_BASE + 200,
+        "ArtifactEntry",
+        "/test/integration/artifacts",
+    );
+    entry.mcap_start_ns = Some(0);
+    entry.mcap_end_ns = Some(100);
+    entry.tags = vec!["existing".to_string()];
+    let entry = insert_entry(&storage, entry).await;
+    let provenance = ArtifactProvenance {
+        plugin_name: "thumbnailer".to_string(),
+        plugin_version: Some("0.3.0".to_string()),
+        instance_id: 4242,
+    };
+    let report = common::unique_temp_file_path("artifact_report.txt");
+    std::fs::write(&report, b"report").unwrap();
+    let report = report.to_string_lossy().to_string();
+
+    let stored = storage
+        .apply_plugin_artifacts(
+            entry.id,
+            provenance.clone(),
+            vec![
+                PluginArtifact::Tags {
+                    tags: vec!["existing".to_string(), "plugin/thumb".to_string()],
+                },
+                PluginArtifact::Metadata {
+                    patch: Box::new(EntryMetadataPatch {
+                        weather_fog: Some(true),
+                        ..Default::default()
+                    }),
+                },
+                PluginArtifact::Sequence {
+                    name: "detected".to_string(),
+                    description: String::new(),
+                    start_timestamp: 10,
+                    end_timestamp: 20,
+                    tags: vec![],
+                },
+                PluginArtifact::File {
+                    path: report.clone(),
+                    role: "report".to_string(),
+                    media_type: Some("text/plain".to_string()),
+                },
+            ],
+        )
+        .await
+        .unwrap();
+    assert_eq!(stored.len(), 4);
+    assert!(stored.iter().all(|a| a.plugin_name == "thumbnailer"
+        && a.plugin_version.as_deref() == Some("0.3.0")
+        && a.instance_id == 4242
+        && a.entry_id == entry.id));
+    assert_eq!(stored[3].content["size_bytes"], 6);
+
+    let updated = storage.get_entry(entry.id, TXID).await.unwrap().unwrap();
+    assert_eq!(updated.tags, vec!["existing", "plugin/thumb"]);
+    assert_eq!(updated.weather_fog, Some(true));
+    let sequence_id = stored[2].content["sequence_id"].as_i64().unwrap();
+    let sequences = storage.get_sequences(entry.id, TXID).await.unwrap();
+    assert_eq!(sequences[&sequence_id].name, "detected");
+
+    // eine Sequenz außerhalb der Aufnahme verwirft alle Artefakte des Laufs
+    let err = storage
+        .apply_plugin_artifacts(
+            entry.id,
+            provenance.clone(),
+            vec![
+                PluginArtifact::Metrics {
+                    values: [("frames".to_string(), 10.0)].into(),
+                },
+                PluginArtifact::Sequence {
+                    name: "late".to_string(),
+                    description: String::new(),
+                    start_timestamp: 50,
+                    end_timestamp: 500,
+                    tags: vec![],
+                },
+            ],
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StorageError::ValidationError(_)));
+
+    let artifacts = storage
+        .get_entry_artifacts(entry.id, None, TXID)
+        .await
+        .unwrap();
+    assert_eq!(artifacts, stored);
+    let files = storage
+        .get_entry_artifacts(entry.id, Some("file".to_string()), TXID)
+        .await
+        .unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].content["path"], report.as_str());
+    assert!(matches!(
+        storage
+            .get_entry_artifacts(entry.id, Some("video".to_string()), TXID)
+            .await,
+        Err(StorageError::ValidationError(_))
+    ));
+    assert!(matches!(
+        storage
+            .get_entry_artifacts(INTEGRATION_ENTRY_ID_BASE + 209, None, TXID)
+            .await,
+        Err(StorageError::NotFound(_))
+    ));
+    std::fs::remove_file(&report).unwrap();
+}
+
+/// Ein Plugin legt seinen Bericht im Arbeitsverzeichnis ab; die Datei muss das
+/// Entfernen des Verzeichnisses nach dem Lauf überstehen.
+#[tokio::test]
+async fn test_plugin_workdir_artifacts() {
+    if skip_if_no_db() {
+        return;
+    }
+    common::init_test_logging();
+
+    let db_url = env::var("DATABASE_URL").unwrap();
+    let export_dir = common::unique_temp_file_path("workdir_artifact_exports");
+    let storage = StorageManager::new(&db_url)
+        .unwrap()
+        .with_export_dir(&export_dir);
+    let entry = insert_entry(
+        &storage,
+        minimal_entry(
+            INTEGRATION_ENTRY_ID_BASE + 210,
+            "WorkdirArtifactEntry",
+            "/test/integration/workdir_artifacts",
+        ),
+    )
+    .await;
+
+    let plugin_dir = common::unique_temp_file_path("workdir_artifact_plugin");
+    std::fs::create_dir_all(&plugin_dir).unwrap();
+    let plugin_path = plugin_dir.join("workdir_report.py");
+    std::fs::write(
+        &plugin_path,
+        r#"import os
+
+PLUGIN_NAME = "workdir_report"
+PLUGIN_TRIGGER = "manual"
+
+
+class PluginImpl:
+    def __init__(self, path, data=""):
+        pass
+
+    def run(self, data):
+        with open("report.txt", "w") as f:
+            f.write("report")
+        return {"artifacts": [{"type": "file", "path": os.path.abspath("report.txt"), "role": "report"}]}
+"#,
+    )
+    .unwrap();
+
+    let mut pm = PluginManager::new().with_storage(storage.clone());
+    pm.register_plugin(plugin_path).unwrap();
+    pm.enable_plugin("workdir_report").unwrap();
+    let (plugin_index, plugin_path) = pm.prepare_start("workdir_report").unwrap();
+    let instance_id = Utc::now().timestamp_micros() as u64;
+    let data = serde_json::json!({ "entry_path": entry.path }).to_string();
+    let handle = pm
+        .build_started_instance_with_data(plugin_index, &plugin_path, instance_id, data)
+        .await
+        .unwrap();
+    pm.commit_started_instance(instance_id, handle).unwrap();
+
+    // der Runner meldet sein Ende auf die Lebendprüfung des Reapers hin
+    let run = tokio::time::timeout(std::time::Duration::from_secs(30), async {
+        loop {
+            pm.reap_dead_and_unresponsive().await;
+            if let Ok(run) = storage.get_plugin_run(instance_id as i64).await
+                && run.ended_at.is_some()
+            {
+                return run;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .expect("plugin run should finish");
+    assert_eq!(run.state, "Completed", "{:?}", run.error);
+
+    let files = storage
+        .get_entry_artifacts(entry.id, Some("file".to_string()), TXID)
+        .await
+        .unwrap();
+    assert_eq!(files.len(), 1);
+    let kept = std::path::PathBuf::from(files[0].content["path"].as_str().unwrap());
+    let kept_dir = storage
+        .artifact_dir(instance_id as i64)
+        .canonicalize()
+        .unwrap();
+    assert_eq!(kept, kept_dir.join("report.txt"));
+    assert_eq!(files[0].content["size_bytes"], 6);
+
+    // das Arbeitsverzeichnis verschwindet, die übernommene Datei bleibt
+    let workdir = SandboxSettings::default()
+        .scratch_dir()
+        .join(format!("instance-{instance_id}"));
+    tokio::time::timeout(std::time::Duration::from_secs(10), async {
+        while workdir.exists() {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .expect("workdir should be removed after the run");
+    assert_eq!(std::fs::read_to_string(&kept).unwrap(), "report");
+
+    let _ = std::fs::remove_dir_all(&export_dir);
+    let _ = std::fs::remove_dir_all(&plugin_dir);
 }
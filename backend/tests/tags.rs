@@ -0,0 +1,38 @@
+//! Tag name, namespace and colour rules (pure functions, no DB).
+
+#[cfg(test)]
+mod tests {
+    use backend::error::StorageError;
+    use backend::storage::tags::{tag_namespace, validate_tag_color, validate_tag_name};
+
+    #[test]
+    fn namespace_is_everything_before_the_last_slash() {
+        assert_eq!(tag_namespace("weather/rain"), Some("weather"));
+        assert_eq!(tag_namespace("weather/rain/heavy"), Some("weather/rain"));
+        assert_eq!(tag_namespace("night"), None);
+    }
+
+    #[test]
+    fn tag_names_reject_empty_segments_and_padding() {
+        assert!(validate_tag_name("weather/rain").is_ok());
+        for bad in [
+            "",
+            " night",
+            "night\n",
+            "/rain",
+            "weather/",
+            "weather//rain",
+        ] {
+            let err = validate_tag_name(bad).unwrap_err();
+            assert!(matches!(err, StorageError::ValidationError(_)), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn colors_must_be_hex_rgb() {
+        assert!(validate_tag_color("#1a2B3c").is_ok());
+        assert!(validate_tag_color("1a2b3c").is_err());
+        assert!(validate_tag_color("#12345").is_err());
+        assert!(validate_tag_color("#12345g").is_err());
+    }
+}
@@ -0,0 +1,138 @@
+//! Timeline tests: sequence range validation, gaps and overlaps (pure functions, no DB).
+
+#[cfg(test)]
+mod tests {
+    use backend::error::StorageError;
+    use backend::storage::models::Sequence;
+    use backend::storage::timeline::{
+        SequenceOverlap, TimelineSegment, build_timeline, ranges_overlap, validate_sequence_range,
+    };
+    use chrono::Utc;
+
+    fn seq(id: i64, start: i64, end: i64) -> Sequence {
+        let now = Utc::now();
+        Sequence {
+            id,
+            entry_id: 1,
+            name: format!("seq{id}"),
+            description: String::new(),
+            start_timestamp: start,
+            end_timestamp: end,
+            created_at: now,
+            updated_at: now,
+            tags: vec![],
+            version: 1,
+        }
+    }
+
+    #[test]
+    fn validate_rejects_end_before_start() {
+        let err = validate_sequence_range(200, 100, (None, None)).unwrap_err();
+        assert!(matches!(err, StorageError::ValidationError(_)));
+    }
+
+    #[test]
+    fn validate_checks_recording_bounds() {
+        let bounds = (Some(1_000), Some(2_000));
+        assert!(validate_sequence_range(1_000, 2_000, bounds).is_ok());
+        assert!(validate_sequence_range(999, 1_500, bounds).is_err());
+        assert!(validate_sequence_range(1_500, 2_001, bounds).is_err());
+        // unbekannte Grenzen werden nicht geprüft
+        assert!(validate_sequence_range(0, 5_000, (None, None)).is_ok());
+    }
+
+    #[test]
+    fn touching_ranges_do_not_overlap() {
+        assert!(!ranges_overlap((0, 10), (10, 20)));
+        assert!(ranges_overlap((0, 11), (10, 20)));
+    }
+
+    #[test]
+    fn timeline_contains_gaps_between_and_around_sequences() {
+        let timeline = build_timeline(
+            1,
+            (Some(0), Some(100)),
+            vec![seq(2, 40, 60), seq(1, 10, 20)],
+        );
+        assert_eq!(
+            timeline.segments,
+            vec![
+                TimelineSegment::Gap {
+                    start_timestamp: 0,
+                    end_timestamp: 10
+                },
+                TimelineSegment::Sequence {
+                    sequence_id: 1,
+                    name: "seq1".to_string(),
+                    start_timestamp: 10,
+                    end_timestamp: 20,
+                },
+                TimelineSegment::Gap {
+                    start_timestamp: 20,
+                    end_timestamp: 40
+                },
+                TimelineSegment::Sequence {
+                    sequence_id: 2,
+                    name: "seq2".to_string(),
+                    start_timestamp: 40,
+                    end_timestamp: 60,
+                },
+                TimelineSegment::Gap {
+                    start_timestamp: 60,
+                    end_timestamp: 100
+                },
+            ]
+        );
+        assert!(timeline.overlaps.is_empty());
+    }
+
+    #[test]
+    fn timeline_reports_overlaps_without_gap() {
+        let timeline = build_timeline(
+            1,
+            (Some(0), Some(50)),
+            vec![seq(1, 0, 30), seq(2, 20, 50), seq(3, 25, 28)],
+        );
+        assert!(
+            !timeline
+                .segments
+                .iter()
+                .any(|s| matches!(s, TimelineSegment::Gap { .. }))
+        );
+        assert_eq!(
+            timeline.overlaps,
+            vec![
+                SequenceOverlap {
+                    first: 1,
+                    second: 2,
+                    start_timestamp: 20,
+                    end_timestamp: 30
+                },
+                SequenceOverlap {
+                    first: 1,
+                    second: 3,
+                    start_timestamp: 25,
+                    end_timestamp: 28
+                },
+                SequenceOverlap {
+                    first: 2,
+                    second: 3,
+                    start_timestamp: 25,
+                    end_timestamp: 28
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_timeline_is_one_gap() {
+        let timeline = build_timeline(1, (Some(5), Some(9)), Vec::new());
+        assert_eq!(
+            timeline.segments,
+            vec![TimelineSegment::Gap {
+                start_timestamp: 5,
+                end_timestamp: 9
+            }]
+        );
+    }
+}
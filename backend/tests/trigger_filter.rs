@@ -0,0 +1,186 @@
+//! Trigger conditions: declaration checks, globs, topics, metadata and plugin selection (pure, no DB).
+
+#[cfg(test)]
+mod tests {
+    use backend::plugin_manager::manager::PluginManager;
+    use backend::plugin_manager::plugin::{BackendEvent, Plugin, Trigger};
+    use backend::plugin_manager::trigger_filter::{TriggerFilter, TriggerSubject, glob_match};
+    use serde_json::{Value, json};
+    use std::path::PathBuf;
+
+    const MIB: u64 = 1024 * 1024;
+
+    fn filter(raw: Value) -> TriggerFilter {
+        TriggerFilter::from_declaration(raw).expect("valid filter")
+    }
+
+    fn recording(path: &str, topics: &[(&str, &str)], metadata: Value) -> TriggerSubject {
+        TriggerSubject {
+            path: path.to_string(),
+            size_bytes: Some(500 * MIB),
+            metadata: metadata.as_object().cloned().unwrap_or_default(),
+            topics: Some(
+                topics
+                    .iter()
+                    .map(|(name, ty)| (name.to_string(), Some(ty.to_string())))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn lidar() -> TriggerSubject {
+        recording(
+            "/data/car2/drive_01.mcap",
+            &[
+                ("/lidar/top/points", "sensor_msgs/msg/PointCloud2"),
+                ("/radar/front", "radar_msgs/msg/RadarScan"),
+            ],
+            json!({ "platform_name": "car2", "sequence_duration": 42.5, "tags": ["night"] }),
+        )
+    }
+
+    fn radar_only() -> TriggerSubject {
+        recording(
+            "/data/car2/drive_02.mcap",
+            &[("/radar/front", "radar_msgs/msg/RadarScan")],
+            json!({ "platform_name": "car2" }),
+        )
+    }
+
+    #[test]
+    fn globs_respect_path_separators() {
+        assert!(glob_match("/data/*.mcap", "/data/a.mcap"));
+        assert!(!glob_match("/data/*.mcap", "/data/car2/a.mcap"));
+        assert!(glob_match("/data/**/*.mcap", "/data/car2/day1/a.mcap"));
+        assert!(glob_match("/data/**/*.mcap", "/data/a.mcap"));
+        assert!(glob_match("/lidar/*", "/lidar/top"));
+        assert!(!glob_match("/lidar/*", "/lidar/top/points"));
+        assert!(glob_match("drive_0?.mcap", "drive_01.mcap"));
+        assert!(!glob_match("drive_0?.mcap", "drive_1.mcap"));
+    }
+
+    #[test]
+    fn message_types_skip_radar_only_recordings() {
+        let lidar_filter = filter(json!({
+            "paths": ["*.mcap"],
+            "message_types": ["sensor_msgs/msg/PointCloud2"],
+        }));
+        assert!(lidar_filter.matches(&lidar()));
+        assert!(!lidar_filter.matches(&radar_only()));
+
+        let topics = filter(json!({ "topics": ["/lidar/**", "/radar/front"] }));
+        assert!(topics.matches(&lidar()));
+        assert!(!topics.matches(&radar_only()));
+
+        // Ohne bekannte Topics kann die Bedingung nicht erfüllt sein.
+        assert!(!topics.matches(&TriggerSubject::from_path("/data/car2/drive_01.mcap")));
+    }
+
+    #[test]
+    fn paths_and_size_limit_the_trigger() {
+        let paths = filter(json!({ "paths": ["/data/car1/**", "/data/car2/*.mcap"] }));
+        assert!(paths.matches(&TriggerSubject::from_path("/data/car2/drive_01.mcap")));
+        assert!(!paths.matches(&TriggerSubject::from_path("/data/car3/drive_01.mcap")));
+
+        let big = filter(json!({ "min_size_mb": 100 }));
+        assert!(big.matches(&lidar()));
+        let small = TriggerSubject {
+            size_bytes: Some(99 * MIB),
+            ..lidar()
+        };
+        assert!(!big.matches(&small));
+        assert!(!big.matches(&TriggerSubject::from_path("/data/x.mcap")));
+    }
+
+    #[test]
+    fn metadata_predicates_compare_entry_fields() {
+        let matches = |metadata: Value| filter(json!({ "metadata": metadata })).matches(&lidar());
+        assert!(matches(json!({ "platform_name": "car2" })));
+        assert!(!matches(json!({ "platform_name": "car1" })));
+        assert!(matches(json!({ "platform_name": { "!=": "car1" } })));
+        assert!(matches(
+            json!({ "platform_name": { "in": ["car1", "car2"] } })
+        ));
+        assert!(matches(json!({ "sequence_duration": { ">=": 30 } })));
+        assert!(!matches(json!({ "sequence_duration": { "<": 30 } })));
+        assert!(matches(json!({ "tags": { "contains": "night" } })));
+        // Fehlende Felder erfüllen auch `!=` nicht.
+        assert!(!matches(json!({ "scenario_name": { "!=": "test" } })));
+    }
+
+    #[test]
+    fn invalid_declarations_are_rejected() {
+        let declare = |raw: Value| TriggerFilter::from_declaration(raw);
+        assert!(declare(json!({})).is_err());
+        assert!(declare(json!(["*.mcap"])).is_err());
+        assert!(declare(json!({ "path": ["*.mcap"] })).is_err());
+        assert!(declare(json!({ "topics": [""] })).is_err());
+        assert!(declare(json!({ "min_size_mb": -1 })).is_err());
+        assert!(declare(json!({ "metadata": { "platfrom_name": "car2" } })).is_err());
+        assert!(declare(json!({ "metadata": { "platform_name": { "~": "car" } } })).is_err());
+        assert!(declare(json!({ "metadata": { "size": { ">": 1, "<": 5 } } })).is_err());
+        assert!(declare(json!({ "metadata": { "size": { ">": [1] } } })).is_err());
+        assert!(declare(json!({ "metadata": { "platform_name": { "in": [] } } })).is_err());
+        assert!(declare(json!({ "metadata": { "platform_name": null } })).is_err());
+    }
+
+    #[test]
+    fn description_lists_all_conditions() {
+        let mut plugin = Plugin::new(
+            "lidar".to_string(),
+            "d".to_string(),
+            Trigger::OnEntryCreate,
+            PathBuf::from("/tmp/lidar.py"),
+        );
+        assert_eq!(plugin.trigger_description(), "OnEntryCreate");
+
+        plugin.set_trigger_filter(Some(filter(json!({
+            "paths": ["*.mcap"],
+            "message_types": ["sensor_msgs/msg/PointCloud2"],
+            "metadata": { "platform_name": "car2" },
+            "min_size_mb": 100,
+        }))));
+        assert_eq!(
+            plugin.trigger_description(),
+            "OnEntryCreate if path matches *.mcap and has message type \
+             sensor_msgs/msg/PointCloud2 and platform_name == \"car2\" and size >= 100 MB"
+        );
+    }
+
+    #[test]
+    fn prepare_fire_event_for_checks_conditions() {
+        let mut pm = PluginManager::new();
+        for (name, raw) in [
+            ("all", None),
+            (
+                "lidar",
+                Some(json!({ "message_types": ["sensor_msgs/msg/PointCloud2"] })),
+            ),
+            ("car2_paths", Some(json!({ "paths": ["/data/car2/**"] }))),
+        ] {
+            let mut plugin = Plugin::new(
+                name.to_string(),
+                "d".to_string(),
+                Trigger::OnEntryCreate,
+                PathBuf::from(format!("/tmp/{name}.py")),
+            );
+            plugin.set_enabled(true);
+            plugin.set_trigger_filter(raw.map(filter));
+            pm.registered.push(plugin);
+        }
+        let created = BackendEvent::EntryCreated {
+            path: "/data/car2/drive_02.mcap".to_string(),
+        };
+        let selected = |plans: Vec<(usize, PathBuf, u64)>| -> Vec<usize> {
+            plans.into_iter().map(|(index, _, _)| index).collect()
+        };
+
+        let plans = pm.prepare_fire_event_for(&created, &lidar()).unwrap();
+        assert_eq!(selected(plans), [0, 1, 2]);
+        let plans = pm.prepare_fire_event_for(&created, &radar_only()).unwrap();
+        assert_eq!(selected(plans), [0, 2]);
+        // Nur mit Pfad werden Plugins mit Topic-Bedingungen übersprungen.
+        let plans = pm.prepare_fire_event(&created).unwrap();
+        assert_eq!(selected(plans), [0, 2]);
+    }
+}
@@ -0,0 +1,103 @@
+//! Plugin run limits: declaration checks, deadlines, pauses and stalls (pure, no DB).
+
+#[cfg(test)]
+mod tests {
+    use backend::plugin_manager::watchdog::{RunLimits, TimeoutKind, Watchdog};
+    use std::time::Duration;
+    use tokio::time::Instant;
+
+    fn limits(timeout: Option<f64>, stall: Option<f64>) -> RunLimits {
+        RunLimits::from_seconds(timeout, stall).expect("valid limits")
+    }
+
+    #[test]
+    fn without_limits_nothing_expires() {
+        let start = Instant::now();
+        let watchdog = Watchdog::new(RunLimits::default(), start);
+        assert_eq!(watchdog.deadline(), None);
+        assert_eq!(watchdog.expired(start + Duration::from_secs(86_400)), None);
+    }
+
+    #[test]
+    fn run_timeout_expires_after_declared_seconds() {
+        let start = Instant::now();
+        let watchdog = Watchdog::new(limits(Some(60.0), None), start);
+        assert_eq!(
+            watchdog.deadline(),
+            Some((start + Duration::from_secs(60), TimeoutKind::Run))
+        );
+        assert_eq!(watchdog.expired(start + Duration::from_secs(59)), None);
+        assert_eq!(
+            watchdog.expired(start + Duration::from_secs(60)),
+            Some(TimeoutKind::Run)
+        );
+    }
+
+    #[test]
+    fn progress_resets_only_the_stall_timeout() {
+        let start = Instant::now();
+        let mut watchdog = Watchdog::new(limits(Some(100.0), Some(30.0)), start);
+        assert_eq!(
+            watchdog.deadline(),
+            Some((start + Duration::from_secs(30), TimeoutKind::Stall))
+        );
+
+        watchdog.progress(start + Duration::from_secs(25));
+        watchdog.progress(start + Duration::from_secs(50));
+        watchdog.progress(start + Duration::from_secs(75));
+        assert_eq!(watchdog.expired(start + Duration::from_secs(99)), None);
+        assert_eq!(
+            watchdog.deadline(),
+            Some((start + Duration::from_secs(100), TimeoutKind::Run))
+        );
+    }
+
+    #[test]
+    fn paused_time_does_not_count() {
+        let start = Instant::now();
+        let mut watchdog = Watchdog::new(limits(Some(60.0), Some(20.0)), start);
+        watchdog.progress(start + Duration::from_secs(10));
+        watchdog.pause(start + Duration::from_secs(15));
+        assert_eq!(watchdog.deadline(), None);
+        assert_eq!(watchdog.expired(start + Duration::from_secs(3600)), None);
+
+        let resumed = start + Duration::from_secs(1015);
+        watchdog.resume(resumed);
+        // Laufzeit um die Pause verlängert, Stall-Frist ab dem Fortsetzen.
+        assert_eq!(
+            watchdog.deadline(),
+            Some((resumed + Duration::from_secs(20), TimeoutKind::Stall))
+        );
+        watchdog.progress(resumed + Duration::from_secs(19));
+        watchdog.progress(resumed + Duration::from_secs(38));
+        assert_eq!(
+            watchdog.deadline(),
+            Some((start + Duration::from_secs(1060), TimeoutKind::Run))
+        );
+    }
+
+    #[test]
+    fn invalid_limits_are_rejected() {
+        assert!(RunLimits::from_seconds(Some(0.0), None).is_err());
+        assert!(RunLimits::from_seconds(Some(-5.0), None).is_err());
+        assert!(RunLimits::from_seconds(None, Some(f64::NAN)).is_err());
+        assert!(RunLimits::from_seconds(None, Some(f64::INFINITY)).is_err());
+        assert_eq!(
+            RunLimits::from_seconds(Some(1.5), None).unwrap().timeout(),
+            Some(Duration::from_millis(1500))
+        );
+    }
+
+    #[test]
+    fn messages_name_the_exceeded_limit() {
+        let limits = limits(Some(3600.0), Some(300.0));
+        assert_eq!(
+            TimeoutKind::Run.message(&limits),
+            "run exceeded PLUGIN_TIMEOUT of 3600s"
+        );
+        assert_eq!(
+            TimeoutKind::Stall.message(&limits),
+            "no progress within PLUGIN_STALL_TIMEOUT of 300s"
+        );
+    }
+}
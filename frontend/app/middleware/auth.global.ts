@@ -0,0 +1,12 @@
+// Ohne gültige Sitzung führt jede Seite außer /login zur Anmeldung.
+export default defineNuxtRouteMiddleware(async (to) => {
+    // Cookie und Token liegen nur im Browser vor.
+    if (import.meta.server || to.path === "/login") return;
+
+    const user = useSessionUser();
+    if (user.value) return;
+    user.value = await fetchSessionUser();
+    if (!user.value) {
+        return navigateTo({ path: "/login", query: { redirect: to.fullPath } });
+    }
+});
@@ -0,0 +1,99 @@
+// Anmeldung am Backend.
+//
+// Das Backend setzt beim Login ein HttpOnly-Cookie `session` und liefert
+// dasselbe Token für `Authorization: Bearer`. Der Browser schickt das Cookie
+// automatisch mit; das Token halten wir zusätzlich im sessionStorage, damit
+// alle Anfragen den Header tragen.
+
+export interface SessionUser {
+    id: number;
+    username: string;
+    role: string;
+    disabled: boolean;
+}
+
+interface Session {
+    token: string;
+    expires_at: string;
+    user: SessionUser;
+}
+
+const TOKEN_KEY = "session-token";
+
+export const useSessionUser = () => useState<SessionUser | null>("session-user", () => null);
+
+const getToken = (): string | null => (import.meta.client ? sessionStorage.getItem(TOKEN_KEY) : null);
+
+export const authHeaders = (): Record<string, string> => {
+    const token = getToken();
+    return token ? { Authorization: `Bearer ${token}` } : {};
+};
+
+// Abgelaufene oder ungültige Sitzung: Token verwerfen und zur Anmeldung.
+const handleUnauthorized = () => {
+    if (!import.meta.client) return;
+    sessionStorage.removeItem(TOKEN_KEY);
+    useSessionUser().value = null;
+    if (window.location.pathname !== "/login") {
+        const redirect = window.location.pathname + window.location.search;
+        window.location.href = `/login?redirect=${encodeURIComponent(redirect)}`;
+    }
+};
+
+// `$fetch` für alle Backend-Routen hinter einem Session-Guard.
+export const backendFetch = $fetch.create({
+    credentials: "include",
+    onRequest({ options }) {
+        const token = getToken();
+        if (token) options.headers.set("Authorization", `Bearer ${token}`);
+    },
+    onResponseError({ response }) {
+        if (response.status === 401) handleUnauthorized();
+    },
+});
+
+// Gegenstück zu `backendFetch` für Stellen, die das rohe `fetch` nutzen.
+export const authFetch = async (input: string, init: RequestInit = {}): Promise<Response> => {
+    const headers = new Headers(init.headers);
+    for (const [name, value] of Object.entries(authHeaders())) headers.set(name, value);
+    const res = await fetch(input, { ...init, headers, credentials: "include" });
+    if (res.status === 401) handleUnauthorized();
+    return res;
+};
+
+export const login = async (username: string, password: string): Promise<SessionUser> => {
+    try {
+        const session = await $fetch<Session>("/backend/auth/login", {
+            method: "POST",
+            body: { username, password },
+            credentials: "include",
+        });
+        sessionStorage.setItem(TOKEN_KEY, session.token);
+        useSessionUser().value = session.user;
+        return session.user;
+    } catch (error: any) {
+        const message = typeof error.data === "string" ? error.data : error.message;
+        throw new Error(message || "login failed");
+    }
+};
+
+export const logout = async (): Promise<void> => {
+    try {
+        await backendFetch("/backend/auth/logout", { method: "POST" });
+    } finally {
+        sessionStorage.removeItem(TOKEN_KEY);
+        useSessionUser().value = null;
+    }
+};
+
+// Angemeldeter Benutzer laut Backend, `null` ohne gültige Sitzung.
+export const fetchSessionUser = async (): Promise<SessionUser | null> => {
+    try {
+        return await $fetch<SessionUser>("/backend/auth/me", {
+            headers: authHeaders(),
+            credentials: "include",
+        });
+    } catch {
+        return null;
+    }
+};
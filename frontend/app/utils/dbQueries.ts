@@ -4,10 +4,11 @@ import type { Sequence, sequenceID, SequenceWeb } from "./sequence";
 import type { MetadataWeb } from "./metadata";
 import type { Sensor, SensorWeb } from "./sensor";
 import type { Topic } from "./topic";
+import { backendFetch } from "./auth";
 
 export const fetchEntries = async (searchString: string, sortBy: Sorting, ascending: boolean, page: number, pageSize: number): Promise<[Entry[], number]> => {
     try {
-        const data = await $fetch<[Entry[], number]>("/backend/entries", {
+        const data = await backendFetch<[Entry[], number]>("/backend/entries", {
             method: "GET",
             query: {
                 search_string: searchString,
@@ -25,7 +26,7 @@ export const fetchEntries = async (searchString: string, sortBy: Sorting, ascend
 
 export const fetchSequences = async (entryID: entryID): Promise<Record<number, Sequence>> => {
     try {
-        return await $fetch<Record<number, Sequence>>(`/backend/entries/${entryID}/sequences/tx/0`);
+        return await backendFetch<Record<number, Sequence>>(`/backend/entries/${entryID}/sequences/tx/0`);
     } catch (error: any) {
         throw new Error(error.message || "unknown error");
     }
@@ -33,7 +34,7 @@ export const fetchSequences = async (entryID: entryID): Promise<Record<number, S
 
 export const fetchSensors = async (entryID: entryID): Promise<Record<number, Sensor>> => {
     try {
-        return await $fetch<Record<number, Sensor>>(`/backend/entries/${entryID}/sensors/tx/0`);
+        return await backendFetch<Record<number, Sensor>>(`/backend/entries/${entryID}/sensors/tx/0`);
     } catch (error: any) {
         throw new Error(error.message || 'error fetching sensors');
     }
@@ -41,7 +42,7 @@ export const fetchSensors = async (entryID: entryID): Promise<Record<number, Sen
 
 export const fetchAllSensors = async (): Promise<Record<number, Sensor>> => {
     try {
-        return await $fetch<Record<number, Sensor>>(`/backend/sensors/tx/0`);
+        return await backendFetch<Record<number, Sensor>>(`/backend/sensors/tx/0`);
     } catch (error: any) {
         throw new Error(error.message || 'error fetching all sensors');
     }
@@ -49,7 +50,7 @@ export const fetchAllSensors = async (): Promise<Record<number, Sensor>> => {
 
 export const fetchTopics = async (entryID: entryID): Promise<Record<number, Topic>> => {
     try {
-        return await $fetch<Record<number, Topic>>(`/backend/entries/${entryID}/topics/tx/0`);
+        return await backendFetch<Record<number, Topic>>(`/backend/entries/${entryID}/topics/tx/0`);
     } catch (error: any) {
         throw new Error(error.message || 'error fetching topics');
     }
@@ -57,7 +58,7 @@ export const fetchTopics = async (entryID: entryID): Promise<Record<number, Topi
 
 export const fetchEntry = async (entryID: entryID): Promise<Entry> => {
     try {
-        return await $fetch<Entry>(`/backend/entries/${entryID}/tx/0`);
+        return await backendFetch<Entry>(`/backend/entries/${entryID}/tx/0`);
     } catch (error: any) {
         throw new Error(error.message || 'error fetching entry');
     }
@@ -65,7 +66,7 @@ export const fetchEntry = async (entryID: entryID): Promise<Entry> => {
 
 export const addTag = async (entryID: entryID, tag: string): Promise<void> => {
     try {
-        await $fetch(`/backend/entries/${entryID}/tags/tx/0`, {
+        await backendFetch(`/backend/entries/${entryID}/tags/tx/0`, {
             method: 'PUT',
             body: tag
         });
@@ -76,7 +77,7 @@ export const addTag = async (entryID: entryID, tag: string): Promise<void> => {
 
 export const removeTag = async (entryID: entryID, tag: string): Promise<void> => {
     try {
-        await $fetch(`/backend/entries/${entryID}/tags/tx/0`, {
+        await backendFetch(`/backend/entries/${entryID}/tags/tx/0`, {
             method: 'DELETE',
             body: tag
         });
@@ -87,7 +88,7 @@ export const removeTag = async (entryID: entryID, tag: string): Promise<void> =>
 
 export const addSequence = async (entryID: entryID, sequence: SequenceWeb): Promise<number> => {
     try {
-        return await $fetch<number>(`/backend/entries/${entryID}/sequences/tx/0`, {
+        return await backendFetch<number>(`/backend/entries/${entryID}/sequences/tx/0`, {
             method: 'POST',
             body: sequence
         });
@@ -98,7 +99,7 @@ export const addSequence = async (entryID: entryID, sequence: SequenceWeb): Prom
 
 export const updateSequence = async (entryID: entryID, sequenceID: number, sequence: SequenceWeb): Promise<void> => {
     try {
-        await $fetch(`/backend/entries/${entryID}/sequences/${sequenceID}/tx/0`, {
+        await backendFetch(`/backend/entries/${entryID}/sequences/${sequenceID}/tx/0`, {
             method: 'PUT',
             body: sequence
         });
@@ -109,7 +110,7 @@ export const updateSequence = async (entryID: entryID, sequenceID: number, seque
 
 export const removeSequence = async (entryID: entryID, sequenceID: number): Promise<void> => {
     try {
-        await $fetch(`/backend/entries/${entryID}/sequences/${sequenceID}/tx/0`, {
+        await backendFetch(`/backend/entries/${entryID}/sequences/${sequenceID}/tx/0`, {
             method: 'DELETE'
         });
     } catch (error: any) {
@@ -119,7 +120,7 @@ export const removeSequence = async (entryID: entryID, sequenceID: number): Prom
 
 export const updateMetadata = async (entryID: entryID, metadata: MetadataWeb): Promise<void> => {
     try {
-        await $fetch(`/backend/entries/${entryID}/metadata/tx/0`, {
+        await backendFetch(`/backend/entries/${entryID}/metadata/tx/0`, {
             method: 'PUT',
             body: metadata
         });
@@ -130,7 +131,7 @@ export const updateMetadata = async (entryID: entryID, metadata: MetadataWeb): P
 
 export const addSensor = async (entryID: entryID, sensor: SensorWeb): Promise<number> => {
     try {
-        return await $fetch<number>(`/backend/entries/${entryID}/sensors/tx/0`, {
+        return await backendFetch<number>(`/backend/entries/${entryID}/sensors/tx/0`, {
             method: 'POST',
             body: sensor
         });
@@ -141,7 +142,7 @@ export const addSensor = async (entryID: entryID, sensor: SensorWeb): Promise<nu
 
 export const updateSensor = async (entryID: entryID, sensorID: number, sensor: SensorWeb): Promise<void> => {
     try {
-        await $fetch(`/backend/entries/${entryID}/sensors/${sensorID}/tx/0`, {
+        await backendFetch(`/backend/entries/${entryID}/sensors/${sensorID}/tx/0`, {
             method: 'PUT',
             body: sensor
         });
@@ -152,7 +153,7 @@ export const updateSensor = async (entryID: entryID, sensorID: number, sensor: S
 
 export const removeSensor = async (sensorID: number): Promise<void> => {
     try {
-        await $fetch(`/backend/sensors/${sensorID}/tx/0`, {
+        await backendFetch(`/backend/sensors/${sensorID}/tx/0`, {
             method: 'DELETE'
         });
     } catch (error: any) {
@@ -1,4 +1,5 @@
 // frontend/stores/logsStore.ts
+import { authFetch } from '~/utils/auth'
 import { defineStore, skipHydrate } from 'pinia'
 import { useLocalStorage } from '@vueuse/core'
 import { ref } from 'vue'
@@ -23,7 +24,7 @@ export const useLogsStore = defineStore('logs', () => {
     loading.value = true
     try {
       const url = `/backend/logs?limit=${limit.value}&level=${levelFilter.value}`
-      const response = await fetch(url)
+      const response = await authFetch(url)
       if (!response.ok) throw new Error('Failed to fetch logs')
       logs.value = await response.json()
     } catch (err: any) {
@@ -1,3 +1,4 @@
+import { authFetch } from '~/utils/auth'
 import { defineStore } from 'pinia'
 import { useLogsStore } from './logsStore'
 
@@ -29,7 +30,7 @@ export const usePluginsStore = defineStore('plugins', {
     async loadPlugins() {
       if (this.plugins.length > 0) return
       try {
-        const res = await fetch('/backend/plugins/registered')
+        const res = await authFetch('/backend/plugins/registered')
         if (!res.ok) throw new Error('Failed to load plugins')
         const data = await res.json()
         this.plugins = data.map((p: any, idx: number) => ({
@@ -52,9 +53,10 @@ export const usePluginsStore = defineStore('plugins', {
       if (this._pollInterval) return
       this._pollInterval = setInterval(async () => {
         try {
-          const res = await fetch('/backend/plugin/instances')
+          const res = await authFetch('/backend/plugin/instances')
           if (!res.ok) return
-          const data = await res.json()
+          // paged response: [runs, num_pages]; the first page holds the newest runs
+          const [data] = await res.json()
 
           // map to runningPlugins (preserve any entryName we seeded locally)
           const existingById: Record<string, RunningPlugin> = {}
@@ -72,7 +74,7 @@ export const usePluginsStore = defineStore('plugins', {
 
             return {
               runId: id,
-              pluginName: p.name,
+              pluginName: p.plugin_name,
               entryName: existing ? existing.entryName : '',
               progress,
               state,
@@ -113,7 +115,7 @@ export const usePluginsStore = defineStore('plugins', {
       try {
         console.debug('[plugins] starting plugin', { plugin: plugin.name, entryName: entryPath, payload })
 
-        const res = await fetch(`/backend/plugins/${encodeURIComponent(plugin.name)}/start`, {
+        const res = await authFetch(`/backend/plugins/${encodeURIComponent(plugin.name)}/start`, {
           method: 'POST',
           body: JSON.stringify({ entry_path: entryPath, payload }),
         })
@@ -136,7 +138,7 @@ export const usePluginsStore = defineStore('plugins', {
 
     async registerPlugins() {
       try {
-        const res = await fetch('/backend/plugins/register', { method: 'PUT' })
+        const res = await authFetch('/backend/plugins/register', { method: 'PUT' })
         if (!res.ok) throw new Error('Failed to register plugins')
 
         // force reload of registered plugins
@@ -149,7 +151,7 @@ export const usePluginsStore = defineStore('plugins', {
 
     async stopInstance(runId: number) {
       try {
-        const res = await fetch(`/backend/plugins/${encodeURIComponent(runId)}/stop`, {
+        const res = await authFetch(`/backend/plugins/${encodeURIComponent(runId)}/stop`, {
           method: 'PUT',
         })
         if (!res.ok) throw new Error('Failed to stop instance')
@@ -171,7 +173,7 @@ export const usePluginsStore = defineStore('plugins', {
 
     async pauseInstance(runId: number) {
       try {
-        const res = await fetch(`/backend/plugins/${encodeURIComponent(runId)}/pause`, {
+        const res = await authFetch(`/backend/plugins/${encodeURIComponent(runId)}/pause`, {
           method: 'PUT',
         })
         if (!res.ok) throw new Error('Failed to pause instance')
@@ -191,7 +193,7 @@ export const usePluginsStore = defineStore('plugins', {
 
     async resumeInstance(runId: number) {
       try {
-        const res = await fetch(`/backend/plugins/${encodeURIComponent(runId)}/resume`, {
+        const res = await authFetch(`/backend/plugins/${encodeURIComponent(runId)}/resume`, {
           method: 'PUT',
         })
         if (!res.ok) throw new Error('Failed to resume instance')
@@ -0,0 +1,24 @@
+import { test, expect } from '@playwright/test'
+
+// Ohne Sitzung landet jede Seite auf /login; nach der Anmeldung geht es zurück.
+test('unauthenticated visit redirects to login and back', async ({ page }) => {
+    await page.goto('/plugins')
+    await expect(page).toHaveURL(/\/login\?redirect=/)
+
+    await page.getByLabel('Username').fill(process.env.ADMIN_USERNAME || 'admin')
+    await page.getByLabel('Password').fill(process.env.ADMIN_PASSWORD || 'admin-change-me')
+    await page.getByRole('button', { name: 'Sign in' }).click()
+
+    await expect(page).toHaveURL(/\/plugins$/)
+    await expect(page.getByRole('button', { name: 'Sign out' })).toBeVisible()
+})
+
+test('wrong password shows an error', async ({ page }) => {
+    await page.goto('/login')
+    await page.getByLabel('Username').fill('admin')
+    await page.getByLabel('Password').fill('definitely-wrong')
+    await page.getByRole('button', { name: 'Sign in' }).click()
+
+    await expect(page.locator('.alert-error')).toBeVisible()
+    await expect(page).toHaveURL(/\/login/)
+})